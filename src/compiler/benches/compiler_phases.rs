@@ -0,0 +1,72 @@
+use compiler::phases;
+use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main};
+use std::fs;
+use std::path::Path;
+
+/// Example programs that make it through semantic analysis, so every phase
+/// has input to run on.
+fn corpus() -> Vec<(String, String)> {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+    let mut programs: Vec<(String, String)> = fs::read_dir(&examples)
+        .expect("examples directory exists")
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "aero" {
+                return None;
+            }
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            Some((name, fs::read_to_string(&path).ok()?))
+        })
+        .filter(|(_, source)| {
+            phases::parse(phases::tokenize(source))
+                .and_then(phases::analyze)
+                .is_ok()
+        })
+        .collect();
+    programs.sort();
+    programs
+}
+
+fn benchmark_phases(c: &mut Criterion) {
+    for (name, source) in corpus() {
+        let tokens = phases::tokenize(&source);
+        let ast = phases::parse(tokens.clone()).unwrap();
+        let typed_ast = phases::analyze(ast.clone()).unwrap();
+
+        let mut group = c.benchmark_group(format!("phases/{}", name));
+        group.bench_function("tokenize", |b| {
+            b.iter(|| phases::tokenize(black_box(&source)))
+        });
+        group.bench_function("parse", |b| {
+            b.iter_batched(|| tokens.clone(), phases::parse, BatchSize::SmallInput)
+        });
+        group.bench_function("analyze", |b| {
+            b.iter_batched(|| ast.clone(), phases::analyze, BatchSize::SmallInput)
+        });
+        group.bench_function("generate_ir", |b| {
+            b.iter_batched(
+                || typed_ast.clone(),
+                phases::generate_ir,
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function("optimize", |b| {
+            b.iter_batched(
+                || phases::generate_ir(typed_ast.clone()),
+                |mut ir| phases::optimize(&mut ir, 2),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function("generate_code", |b| {
+            b.iter_batched(
+                || phases::generate_ir(typed_ast.clone()),
+                phases::generate_code,
+                BatchSize::SmallInput,
+            )
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(phase_benchmarks, benchmark_phases);
+criterion_main!(phase_benchmarks);
@@ -1,5 +1,5 @@
 use compiler::tokenize;
-use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
 
 fn benchmark_function_call_tokenization(c: &mut Criterion) {
     let simple_function_code = r#"
@@ -258,12 +258,33 @@ fn main() {
     });
 }
 
+fn benchmark_megabyte_tokenization(c: &mut Criterion) {
+    // Generated code the size of a large machine-written module (1MB).
+    let mut source = String::new();
+    let mut i = 0;
+    while source.len() < 1 << 20 {
+        source.push_str(&format!(
+            "// generated item {i}\nfn step{i}(x: i32, scale: f64) -> f64 {{\n    let label = \"step {i}\";\n    if x >= {i} && x != 0 {{ return scale * 1.5e3; }}\n    (x + {i}) as f64 / scale\n}}\n\n"
+        ));
+        i += 1;
+    }
+
+    let mut group = c.benchmark_group("tokenize_1mb");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.sample_size(20);
+    group.bench_function("generated_functions", |b| {
+        b.iter(|| tokenize(black_box(&source)))
+    });
+    group.finish();
+}
+
 criterion_group!(
     lexer_benchmarks,
     benchmark_function_call_tokenization,
     benchmark_loop_tokenization,
     benchmark_io_tokenization,
     benchmark_compilation_speed_tokenization,
-    benchmark_performance_regression_tokenization
+    benchmark_performance_regression_tokenization,
+    benchmark_megabyte_tokenization
 );
 criterion_main!(lexer_benchmarks);
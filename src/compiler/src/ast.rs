@@ -1,12 +1,15 @@
 #![allow(dead_code)]
 
+use crate::errors::SourceLocation;
 use crate::types::Ty;
 
 #[derive(Debug, Clone)]
 pub enum Expression {
     IntegerLiteral(i64),
     FloatLiteral(f64),
+    BooleanLiteral(bool),
     StringLiteral(String),
+    CharLiteral(char),
     Identifier(String),
     Binary {
         op: BinaryOp,
@@ -18,10 +21,17 @@ pub enum Expression {
         name: String,
         arguments: Vec<Expression>,
     },
+    // `name: value` in a call's argument list; `keyword_arguments::resolve`
+    // turns these into positional arguments before analysis
+    NamedArgument {
+        name: String,
+        value: Box<Expression>,
+    },
     MethodCall {
         object: Box<Expression>,
         method: String,
         arguments: Vec<Expression>,
+        type_args: Vec<Type>, // turbofish: s.parse::<f64>()
     },
     Print {
         format_string: String,
@@ -54,6 +64,8 @@ pub enum Expression {
     IndexAccess {
         object: Box<Expression>,
         index: Box<Expression>,
+        // Reported when the bounds check fails
+        location: Option<SourceLocation>,
     },
     FieldAccess {
         object: Box<Expression>,
@@ -71,18 +83,45 @@ pub enum Expression {
     EnumVariant {
         enum_name: String,
         variant: String,
-        data: Option<Box<Expression>>,
+        data: Option<Box<Expression>>, // struct variants carry a StructLiteral named after the variant
     },
     Match {
         expr: Box<Expression>,
         arms: Vec<MatchArm>,
     },
+    // `if let pattern = expr { .. } else { .. }` in value position
+    IfLet {
+        pattern: Box<Pattern>,
+        expr: Box<Expression>,
+        then_block: Box<Block>,
+        else_block: Option<Box<Block>>,
+    },
+    // matches!(expr, pattern): boolean pattern test without arm bodies
+    Matches {
+        expr: Box<Expression>,
+        pattern: Box<Pattern>,
+    },
+    // todo!() / unimplemented!(): panics with the message; never produces a value
+    Panic {
+        message: String,
+        location: Option<SourceLocation>,
+    },
+    // `{ stmts; expr }` as a match arm body: its own scope, valued by its tail
+    Block(Box<Block>),
     // Phase 5: Ownership & borrowing
     Borrow {
         expr: Box<Expression>,
         mutable: bool,
     },
     Deref(Box<Expression>),
+    // Box::new(value): move a value into a fresh heap allocation
+    BoxNew(Box<Expression>),
+    // `start..end` or `start..=end`; only parsed as a `for` loop iterable
+    Range {
+        start: Box<Expression>,
+        end: Box<Expression>,
+        inclusive: bool,
+    },
     // Phase 7: Closures (v1.0.0)
     Closure {
         params: Vec<Parameter>,
@@ -98,6 +137,17 @@ pub enum Statement {
         type_annotation: Option<Type>,
         value: Option<Expression>,
     },
+    // `let [a, b] = expr;`: bind the names of an irrefutable pattern
+    LetPattern {
+        pattern: Pattern,
+        mutable: bool,
+        value: Expression,
+    },
+    // `target = value;` where target is a variable or an indexed element `v[i]`
+    Assign {
+        target: Expression,
+        value: Expression,
+    },
     Return(Option<Expression>),
     Expression(Expression),
     Block(Block),
@@ -106,14 +156,22 @@ pub enum Statement {
         parameters: Vec<Parameter>,
         return_type: Option<Type>,
         body: Block,
-        type_params: Vec<String>, // Phase 5: generic type parameters <T, U>
+        type_params: Vec<String>,  // Phase 5: generic type parameters <T, U>
+        const_params: Vec<String>, // const generic parameters <const N: usize>
         trait_bounds: Vec<(String, Vec<String>)>, // Phase 5: T: Display + Clone -> [("T", ["Display", "Clone"])]
+        no_inline: bool,                          // `@inline(never)`
     },
     If {
         condition: Expression,
         then_block: Block,
         else_block: Option<Box<Statement>>,
     },
+    IfLet {
+        pattern: Box<Pattern>,
+        expression: Expression,
+        then_block: Block,
+        else_block: Option<Box<Statement>>,
+    },
     While {
         condition: Expression,
         body: Block,
@@ -160,6 +218,28 @@ pub enum Statement {
         path: Vec<String>,     // e.g. ["std", "collections", "HashMap"]
         alias: Option<String>, // e.g. `as Foo`
     },
+    // File-scope `static NAME: Type = <const expr>;`
+    Static {
+        name: String,
+        mutable: bool,
+        type_annotation: Type,
+        value: Expression,
+    },
+    // `@cfg(predicate) item`: dropped by `cfg::strip_inactive_items` unless the predicate holds
+    Cfg {
+        predicate: CfgPredicate,
+        item: Box<Statement>,
+    },
+}
+
+/// Predicate of a `@cfg(...)` attribute
+#[derive(Debug, Clone)]
+pub enum CfgPredicate {
+    Feature(String),        // feature = "x"
+    Not(Box<CfgPredicate>), // not(p)
+    All(Vec<CfgPredicate>), // all(p, q, ...)
+    Any(Vec<CfgPredicate>), // any(p, q, ...)
+    Test,                   // `@test`: kept only in the programs `aero test` builds
 }
 
 /// Match arm: pattern => expression/block
@@ -183,7 +263,20 @@ pub enum Pattern {
     Enum {
         enum_name: String,
         variant: String,
-        data: Option<Box<Pattern>>,
+        data: Option<Box<Pattern>>, // struct variants match through a Struct pattern named after the variant
+    },
+    Range {
+        start: Expression,
+        end: Expression,
+        inclusive: bool, // 1..=5 vs 1..5
+    },
+    Binding {
+        name: String,
+        pattern: Box<Pattern>, // n @ 1..=5
+    },
+    Array {
+        elements: Vec<Pattern>,
+        rest: Option<(usize, Option<String>)>, // [a, ..rest, z]: index of `..` in elements, bound name
     },
 }
 
@@ -238,11 +331,14 @@ pub struct Block {
 #[derive(Debug, Clone)]
 pub enum Type {
     Named(String),
-    Array(Box<Type>, usize), // [T; N]
-    Tuple(Vec<Type>),        // (T1, T2, ...)
+    Array(Box<Type>, usize),       // [T; N]
+    ConstArray(Box<Type>, String), // [T; N] where N is a const generic parameter
+    Tuple(Vec<Type>),              // (T1, T2, ...)
     // Phase 5
     Reference(Box<Type>, bool), // &T (false) or &mut T (true)
     Generic(String, Vec<Type>), // Name<T1, T2> e.g., Vec<i32>
+    TraitObject(String),        // dyn Trait
+    ImplTrait(String),          // impl Trait, in return position
 }
 
 #[derive(Debug, Clone)]
@@ -288,19 +384,64 @@ impl BinaryOp {
     }
 }
 
+impl ComparisonOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ComparisonOp::Equal => "==",
+            ComparisonOp::NotEqual => "!=",
+            ComparisonOp::LessThan => "<",
+            ComparisonOp::GreaterThan => ">",
+            ComparisonOp::LessEqual => "<=",
+            ComparisonOp::GreaterEqual => ">=",
+        }
+    }
+}
+
+impl LogicalOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogicalOp::And => "&&",
+            LogicalOp::Or => "||",
+        }
+    }
+}
+
 impl std::fmt::Display for BinaryOp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
 
+/// The `{}` and `{:?}` placeholders of a print format string, in order, as
+/// their byte offset and whether they ask for the Debug (`{:?}`) rendering.
+pub fn format_placeholders(format_string: &str) -> Vec<(usize, bool)> {
+    let mut placeholders = Vec::new();
+    let mut offset = 0;
+    while let Some(pos) = format_string[offset..].find('{') {
+        let at = offset + pos;
+        let rest = &format_string[at..];
+        if rest.starts_with("{}") {
+            placeholders.push((at, false));
+            offset = at + 2;
+        } else if rest.starts_with("{:?}") {
+            placeholders.push((at, true));
+            offset = at + 4;
+        } else {
+            offset = at + 1;
+        }
+    }
+    placeholders
+}
+
 impl Expression {
     /// Get the inferred type of an expression (used for literals)
     pub fn get_literal_type(&self) -> Option<Ty> {
         match self {
             Expression::IntegerLiteral(_) => Some(Ty::Int),
             Expression::FloatLiteral(_) => Some(Ty::Float),
+            Expression::BooleanLiteral(_) => Some(Ty::Bool),
             Expression::StringLiteral(_) => Some(Ty::String),
+            Expression::CharLiteral(_) => Some(Ty::Char),
             Expression::Binary { ty, .. } => ty.clone(),
             Expression::Identifier(_) => None,
             Expression::FunctionCall { .. } => None,
@@ -320,12 +461,45 @@ impl Expression {
             Expression::TupleIndex { .. } => None,
             Expression::StructLiteral { .. } => None,
             Expression::EnumVariant { .. } => None,
-            Expression::Match { .. } => None,
+            Expression::Match { .. } | Expression::IfLet { .. } | Expression::Block(_) => None,
             Expression::Borrow { .. } => None,
             Expression::Deref(_) => None,
+            Expression::BoxNew(_) => None,
+            Expression::Range { .. } => None,
+            Expression::NamedArgument { .. } => None,
+            Expression::Matches { .. } => Some(Ty::Bool),
+            Expression::Panic { .. } => Some(Ty::Never),
             Expression::Closure { .. } => None,
         }
     }
+
+    /// `s.parse()` bound by `let x: Result<T, E>` parses into `T`: the call
+    /// with `T` as its turbofish, when `self` is such a call.
+    pub fn with_annotated_parse_target(&self, annotation: &Type) -> Option<Expression> {
+        match (self, annotation) {
+            (
+                Expression::MethodCall {
+                    object,
+                    method,
+                    arguments,
+                    type_args,
+                },
+                Type::Generic(name, args),
+            ) if method == "parse"
+                && type_args.is_empty()
+                && name == "Result"
+                && args.len() == 2 =>
+            {
+                Some(Expression::MethodCall {
+                    object: object.clone(),
+                    method: method.clone(),
+                    arguments: arguments.clone(),
+                    type_args: vec![args[0].clone()],
+                })
+            }
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
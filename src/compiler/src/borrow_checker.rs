@@ -0,0 +1,691 @@
+// src/compiler/src/borrow_checker.rs
+
+//! Move checking. Values of non-`Copy` types (strings, structs, enums, `Vec`,
+//! `Box`, ...) are moved when passed by value, bound with `let`, or placed in
+//! an aggregate, and any later use of the moved variable is an error. Scalars
+//! are `Copy` and never move.
+
+use crate::ast::{AstNode, Block, Expression, MatchArm, Parameter, Pattern, Statement, Type};
+use crate::types::Ty;
+use std::collections::HashMap;
+
+/// Report the first use of a moved value in each top-level item of the
+/// program, keyed by the item's index.
+pub fn check(ast: &[AstNode]) -> Result<(), Vec<(usize, String)>> {
+    let mut checker = BorrowChecker::new(ast);
+    checker.enter_scope();
+    let mut errors = Vec::new();
+    for (index, node) in ast.iter().enumerate() {
+        // An item that fails can leave its scopes behind; the next item
+        // starts from the bindings as they were before it.
+        let scopes = checker.scopes.clone();
+        let checked = match node {
+            AstNode::Statement(stmt) => checker.check_statement(stmt).map(|_| ()),
+            AstNode::Expression(expr) => checker.check_expression(expr),
+        };
+        if let Err(err) = checked {
+            checker.scopes = scopes;
+            checker.type_params.clear();
+            errors.push((index, err));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[derive(Clone)]
+struct Binding {
+    copy: bool,
+    moved: bool,
+}
+
+type Scope = HashMap<String, Binding>;
+
+/// Declared signature of a function, used to decide whether an argument is
+/// passed into a parameter that takes ownership.
+struct Signature {
+    type_params: Vec<String>,
+    parameters: Vec<Type>,
+    return_type: Option<Type>,
+}
+
+struct BorrowChecker {
+    signatures: HashMap<String, Signature>,
+    /// Type parameters of the function being checked.
+    type_params: Vec<String>,
+    scopes: Vec<Scope>,
+}
+
+impl BorrowChecker {
+    fn new(ast: &[AstNode]) -> Self {
+        let signatures = ast
+            .iter()
+            .filter_map(|node| match node {
+                AstNode::Statement(Statement::Function {
+                    name,
+                    parameters,
+                    return_type,
+                    type_params,
+                    ..
+                }) => Some((
+                    name.clone(),
+                    Signature {
+                        type_params: type_params.clone(),
+                        parameters: parameters.iter().map(|p| p.param_type.clone()).collect(),
+                        return_type: return_type.clone(),
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+        BorrowChecker {
+            signatures,
+            type_params: Vec::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(Scope::new());
+    }
+
+    fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &str, copy: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), Binding { copy, moved: false });
+        }
+    }
+
+    fn lookup(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+    }
+
+    /// Variables not declared in this pass (statics, functions) are treated
+    /// as `Copy` so they are never reported.
+    fn is_copy_variable(&mut self, name: &str) -> bool {
+        self.lookup(name).is_none_or(|binding| binding.copy)
+    }
+
+    fn mark_moved(&mut self, name: &str) {
+        if let Some(binding) = self.lookup(name) {
+            binding.moved = true;
+        }
+    }
+
+    /// Move `expr` if it names a variable. `takes_ownership` forces the move
+    /// for destinations declared with a non-`Copy` type.
+    fn move_operand(&mut self, expr: &Expression, takes_ownership: bool) {
+        if let Expression::Identifier(name) = expr
+            && (takes_ownership || !self.is_copy_variable(name))
+        {
+            self.mark_moved(name);
+        }
+    }
+
+    fn type_to_ty(&self, ty: &Type) -> Ty {
+        match ty {
+            Type::Named(name) => match name.as_str() {
+                "i32" | "int" => Ty::Int,
+                "f64" | "float" => Ty::Float,
+                "bool" => Ty::Bool,
+                "char" => Ty::Char,
+                "String" => Ty::String,
+                other if self.type_params.iter().any(|p| p == other) => {
+                    Ty::TypeParam(other.to_string())
+                }
+                other => Ty::Struct(other.to_string()),
+            },
+            Type::Array(elem, size) => Ty::Array(Box::new(self.type_to_ty(elem)), *size),
+            Type::ConstArray(elem, _) => Ty::Vec(Box::new(self.type_to_ty(elem))),
+            Type::Tuple(types) if types.is_empty() => Ty::Unit,
+            Type::Tuple(types) => Ty::Tuple(types.iter().map(|t| self.type_to_ty(t)).collect()),
+            Type::Reference(inner, mutable) => {
+                Ty::Reference(Box::new(self.type_to_ty(inner)), *mutable)
+            }
+            Type::TraitObject(name) | Type::ImplTrait(name) => Ty::TraitObject(name.clone()),
+            Type::Generic(name, args) => match (name.as_str(), args.as_slice()) {
+                ("Vec", [elem]) => Ty::Vec(Box::new(self.type_to_ty(elem))),
+                ("Box", [inner]) => Ty::Box(Box::new(self.type_to_ty(inner))),
+                ("Option", [inner]) => Ty::Option(Box::new(self.type_to_ty(inner))),
+                ("Result", [ok, err]) => Ty::Result(
+                    Box::new(self.type_to_ty(ok)),
+                    Box::new(self.type_to_ty(err)),
+                ),
+                ("HashMap", [key, value]) => Ty::HashMap(
+                    Box::new(self.type_to_ty(key)),
+                    Box::new(self.type_to_ty(value)),
+                ),
+                _ => Ty::TypeParam(name.clone()),
+            },
+        }
+    }
+
+    /// Whether the `index`th parameter of `function` takes ownership of its
+    /// argument. Generic parameters depend on the argument, so they don't.
+    fn parameter_takes_ownership(&self, function: &str, index: usize) -> bool {
+        let Some(signature) = self.signatures.get(function) else {
+            return false;
+        };
+        let Some(param_type) = signature.parameters.get(index) else {
+            return false;
+        };
+        let generic = |name: &str| signature.type_params.iter().any(|p| p == name);
+        match param_type {
+            Type::Named(name) if generic(name) => false,
+            ty => !self.type_to_ty(ty).is_copy_type(),
+        }
+    }
+
+    /// Best-effort `Copy`-ness of the value `expr` produces. Anything whose
+    /// type isn't evident from the syntax counts as `Copy`.
+    fn is_copy_expression(&mut self, expr: &Expression) -> bool {
+        match expr {
+            Expression::StringLiteral(_)
+            | Expression::StructLiteral { .. }
+            | Expression::EnumVariant { .. }
+            | Expression::BoxNew(_) => false,
+            Expression::Identifier(name) => self.is_copy_variable(name),
+            Expression::ArrayLiteral(elements) | Expression::TupleLiteral(elements) => {
+                elements.iter().all(|e| self.is_copy_expression(e))
+            }
+            Expression::ArrayRepeat { value, .. } => self.is_copy_expression(value),
+            Expression::FunctionCall { name, .. } => {
+                match self
+                    .signatures
+                    .get(name)
+                    .and_then(|s| s.return_type.clone())
+                {
+                    Some(ty) => self.type_to_ty(&ty).is_copy_type(),
+                    None => true,
+                }
+            }
+            _ => true,
+        }
+    }
+
+    fn check_function(
+        &mut self,
+        parameters: &[Parameter],
+        type_params: &[String],
+        body: &Block,
+    ) -> Result<(), String> {
+        let saved_scopes = std::mem::take(&mut self.scopes);
+        let saved_type_params = std::mem::replace(&mut self.type_params, type_params.to_vec());
+        self.enter_scope();
+        for param in parameters {
+            let copy = self.type_to_ty(&param.param_type).is_copy_type();
+            self.declare(&param.name, copy);
+        }
+        let result = self.check_block(body).map(|_| ());
+        self.scopes = saved_scopes;
+        self.type_params = saved_type_params;
+        result
+    }
+
+    /// Check a block in its own scope. Returns true if the block always
+    /// leaves through `return`, `break`, or `continue`.
+    fn check_block(&mut self, block: &Block) -> Result<bool, String> {
+        self.enter_scope();
+        let result = self.check_block_contents(block);
+        self.exit_scope();
+        result
+    }
+
+    fn check_block_contents(&mut self, block: &Block) -> Result<bool, String> {
+        for stmt in &block.statements {
+            if self.check_statement(stmt)? {
+                return Ok(true);
+            }
+        }
+        if let Some(expr) = &block.expression {
+            self.check_expression(expr)?;
+        }
+        Ok(false)
+    }
+
+    /// Check a statement. Returns true if control never continues past it.
+    fn check_statement(&mut self, stmt: &Statement) -> Result<bool, String> {
+        match stmt {
+            Statement::Let {
+                name,
+                type_annotation,
+                value,
+                ..
+            } => {
+                let copy = match (type_annotation, value) {
+                    (Some(ty), _) => self.type_to_ty(ty).is_copy_type(),
+                    (None, Some(value)) => self.is_copy_expression(value),
+                    (None, None) => true,
+                };
+                if let Some(value) = value {
+                    self.check_expression(value)?;
+                    self.move_operand(value, !copy);
+                }
+                self.declare(name, copy);
+                Ok(false)
+            }
+            Statement::LetPattern { pattern, value, .. } => {
+                self.check_expression(value)?;
+                // `let _ = x;` binds nothing, so `x` keeps its value.
+                if !matches!(pattern, Pattern::Wildcard) {
+                    self.move_operand(value, false);
+                }
+                self.declare_pattern(pattern);
+                Ok(false)
+            }
+            Statement::Assign { target, value } => {
+                self.check_expression(value)?;
+                let copy = self.is_copy_expression(value);
+                self.move_operand(value, !copy);
+                match target {
+                    // Assigning a fresh value makes a moved variable usable again
+                    Expression::Identifier(name) => {
+                        if let Some(binding) = self.lookup(name) {
+                            binding.moved = false;
+                        }
+                    }
+                    _ => self.check_expression(target)?,
+                }
+                Ok(false)
+            }
+            Statement::Return(value) => {
+                if let Some(value) = value {
+                    self.check_expression(value)?;
+                }
+                Ok(true)
+            }
+            Statement::Break | Statement::Continue => Ok(true),
+            Statement::Expression(expr) => {
+                self.check_expression(expr)?;
+                Ok(false)
+            }
+            Statement::Block(block) => self.check_block(block),
+            Statement::Function {
+                parameters,
+                type_params,
+                body,
+                ..
+            } => {
+                self.check_function(parameters, type_params, body)?;
+                Ok(false)
+            }
+            Statement::ImplBlock { methods, .. } => {
+                for method in methods {
+                    self.check_statement(method)?;
+                }
+                Ok(false)
+            }
+            Statement::If {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                self.check_expression(condition)?;
+                self.check_branches(
+                    |checker| checker.check_block(then_block),
+                    |checker| match else_block {
+                        Some(else_stmt) => checker.check_statement(else_stmt),
+                        None => Ok(false),
+                    },
+                )
+            }
+            Statement::IfLet {
+                pattern,
+                expression,
+                then_block,
+                else_block,
+            } => {
+                self.check_expression(expression)?;
+                self.check_branches(
+                    |checker| checker.check_pattern_block(pattern, then_block),
+                    |checker| match else_block {
+                        Some(else_stmt) => checker.check_statement(else_stmt),
+                        None => Ok(false),
+                    },
+                )
+            }
+            Statement::While { condition, body } => {
+                // A second pass over the body catches values moved in the
+                // previous iteration.
+                for _ in 0..2 {
+                    self.check_expression(condition)?;
+                    self.check_block(body)?;
+                }
+                Ok(false)
+            }
+            Statement::For {
+                variable,
+                iterable,
+                body,
+            } => {
+                self.check_expression(iterable)?;
+                self.move_operand(iterable, false);
+                for _ in 0..2 {
+                    self.enter_scope();
+                    self.declare(variable, true);
+                    let result = self.check_block_contents(body);
+                    self.exit_scope();
+                    result?;
+                }
+                Ok(false)
+            }
+            Statement::Loop { body } => {
+                for _ in 0..2 {
+                    self.check_block(body)?;
+                }
+                Ok(false)
+            }
+            Statement::StructDef { .. }
+            | Statement::EnumDef { .. }
+            | Statement::TraitDef { .. }
+            | Statement::ModDecl { .. }
+            | Statement::UseImport { .. }
+            | Statement::Static { .. }
+            | Statement::Cfg { .. } => Ok(false),
+        }
+    }
+
+    /// Check two alternative paths from the current state. A variable is
+    /// moved afterwards if either path that falls through moved it.
+    fn check_branches(
+        &mut self,
+        first: impl FnOnce(&mut Self) -> Result<bool, String>,
+        second: impl FnOnce(&mut Self) -> Result<bool, String>,
+    ) -> Result<bool, String> {
+        let before = self.scopes.clone();
+        let first_diverges = first(self)?;
+        let after_first = std::mem::replace(&mut self.scopes, before);
+        let second_diverges = second(self)?;
+        match (first_diverges, second_diverges) {
+            (true, _) => {}
+            (false, true) => self.scopes = after_first,
+            (false, false) => self.merge_moves(&after_first),
+        }
+        Ok(first_diverges && second_diverges)
+    }
+
+    fn merge_moves(&mut self, other: &[Scope]) {
+        for (scope, other_scope) in self.scopes.iter_mut().zip(other) {
+            for (name, binding) in scope.iter_mut() {
+                if other_scope.get(name).is_some_and(|b| b.moved) {
+                    binding.moved = true;
+                }
+            }
+        }
+    }
+
+    /// Check `block` with the names bound by `pattern` in scope.
+    fn check_pattern_block(&mut self, pattern: &Pattern, block: &Block) -> Result<bool, String> {
+        self.enter_scope();
+        self.declare_pattern(pattern);
+        let result = self.check_block_contents(block);
+        self.exit_scope();
+        result
+    }
+
+    /// Pattern bindings are not tracked, so they are declared `Copy`.
+    fn declare_pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Identifier(name) => self.declare(name, true),
+            Pattern::Binding { name, pattern } => {
+                self.declare(name, true);
+                self.declare_pattern(pattern);
+            }
+            Pattern::Tuple(patterns) => {
+                for pattern in patterns {
+                    self.declare_pattern(pattern);
+                }
+            }
+            Pattern::Array { elements, rest } => {
+                for pattern in elements {
+                    self.declare_pattern(pattern);
+                }
+                if let Some((_, Some(name))) = rest {
+                    self.declare(name, true);
+                }
+            }
+            Pattern::Struct { fields, .. } => {
+                for (_, pattern) in fields {
+                    self.declare_pattern(pattern);
+                }
+            }
+            Pattern::Enum {
+                data: Some(inner), ..
+            } => self.declare_pattern(inner),
+            Pattern::Enum { data: None, .. }
+            | Pattern::Wildcard
+            | Pattern::Literal(_)
+            | Pattern::Range { .. } => {}
+        }
+    }
+
+    fn check_match_arms(&mut self, arms: &[MatchArm]) -> Result<(), String> {
+        let before = self.scopes.clone();
+        let mut moved_in_arms = Vec::new();
+        for arm in arms {
+            self.scopes = before.clone();
+            self.enter_scope();
+            self.declare_pattern(&arm.pattern);
+            let result = self.check_expression(&arm.body);
+            self.exit_scope();
+            result?;
+            moved_in_arms.push(std::mem::take(&mut self.scopes));
+        }
+        self.scopes = before;
+        for arm_scopes in &moved_in_arms {
+            self.merge_moves(arm_scopes);
+        }
+        Ok(())
+    }
+
+    fn check_expression(&mut self, expr: &Expression) -> Result<(), String> {
+        match expr {
+            Expression::Identifier(name) => {
+                if self.lookup(name).is_some_and(|binding| binding.moved) {
+                    return Err(format!("Error: use of moved value: '{}'", name));
+                }
+                Ok(())
+            }
+            Expression::IntegerLiteral(_)
+            | Expression::FloatLiteral(_)
+            | Expression::BooleanLiteral(_)
+            | Expression::StringLiteral(_)
+            | Expression::CharLiteral(_)
+            | Expression::Panic { .. } => Ok(()),
+            Expression::Binary { left, right, .. }
+            | Expression::Comparison { left, right, .. }
+            | Expression::Logical { left, right, .. } => {
+                self.check_expression(left)?;
+                self.check_expression(right)
+            }
+            Expression::Unary { operand, .. } => self.check_expression(operand),
+            Expression::FunctionCall { name, arguments } => {
+                for (index, argument) in arguments.iter().enumerate() {
+                    self.check_expression(argument)?;
+                    let takes_ownership = self.parameter_takes_ownership(name, index);
+                    self.move_operand(argument, takes_ownership);
+                }
+                Ok(())
+            }
+            Expression::MethodCall {
+                object, arguments, ..
+            } => {
+                // Methods borrow their receiver.
+                self.check_expression(object)?;
+                for argument in arguments {
+                    self.check_expression(argument)?;
+                    self.move_operand(argument, false);
+                }
+                Ok(())
+            }
+            // Formatting macros only borrow their arguments.
+            Expression::Print { arguments, .. } | Expression::Println { arguments, .. } => {
+                for argument in arguments {
+                    self.check_expression(argument)?;
+                }
+                Ok(())
+            }
+            Expression::ArrayLiteral(elements) | Expression::TupleLiteral(elements) => {
+                for element in elements {
+                    self.check_expression(element)?;
+                    self.move_operand(element, false);
+                }
+                Ok(())
+            }
+            Expression::StructLiteral { fields, .. } => {
+                for (_, value) in fields {
+                    self.check_expression(value)?;
+                    self.move_operand(value, false);
+                }
+                Ok(())
+            }
+            Expression::EnumVariant { data, .. } => match data {
+                Some(data) => {
+                    self.check_expression(data)?;
+                    self.move_operand(data, false);
+                    Ok(())
+                }
+                None => Ok(()),
+            },
+            Expression::BoxNew(value) => {
+                self.check_expression(value)?;
+                self.move_operand(value, false);
+                Ok(())
+            }
+            Expression::Range { start, end, .. } => {
+                self.check_expression(start)?;
+                self.check_expression(end)
+            }
+            Expression::NamedArgument { value, .. } => self.check_expression(value),
+            Expression::ArrayRepeat { value, .. } => self.check_expression(value),
+            Expression::IndexAccess { object, index, .. } => {
+                self.check_expression(object)?;
+                self.check_expression(index)
+            }
+            Expression::FieldAccess { object, .. } | Expression::TupleIndex { object, .. } => {
+                self.check_expression(object)
+            }
+            Expression::Match { expr, arms } => {
+                self.check_expression(expr)?;
+                self.check_match_arms(arms)
+            }
+            Expression::IfLet {
+                pattern,
+                expr,
+                then_block,
+                else_block,
+            } => {
+                self.check_expression(expr)?;
+                self.check_branches(
+                    |checker| checker.check_pattern_block(pattern, then_block),
+                    |checker| match else_block {
+                        Some(else_block) => checker.check_block(else_block),
+                        None => Ok(false),
+                    },
+                )
+                .map(|_| ())
+            }
+            Expression::Block(block) => self.check_block(block).map(|_| ()),
+            Expression::Matches { expr, .. } => self.check_expression(expr),
+            Expression::Borrow { expr, .. } | Expression::Deref(expr) => {
+                self.check_expression(expr)
+            }
+            Expression::Closure { params, body } => {
+                self.enter_scope();
+                for param in params {
+                    let copy = self.type_to_ty(&param.param_type).is_copy_type();
+                    self.declare(&param.name, copy);
+                }
+                let result = self.check_expression(body);
+                self.exit_scope();
+                result
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokenize;
+    use crate::parser::parse;
+
+    fn check_source(source: &str) -> Result<(), String> {
+        check(&parse(tokenize(source))).map_err(|mut errors| errors.remove(0).1)
+    }
+
+    #[test]
+    fn vec_used_after_being_passed_by_value_is_an_error() {
+        let err = check_source(
+            r#"
+            fn consume(v: Vec<i32>) -> i32 { return 0; }
+            fn main() {
+                let v: Vec<i32> = vec![1, 2, 3];
+                consume(v);
+                let n = v.len();
+            }
+            "#,
+        )
+        .unwrap_err();
+        assert_eq!(err, "Error: use of moved value: 'v'");
+    }
+
+    #[test]
+    fn i32_used_after_being_passed_by_value_is_fine() {
+        let result = check_source(
+            r#"
+            fn double(x: i32) -> i32 { return x * 2; }
+            fn main() {
+                let x = 21;
+                let y = double(x);
+                let z = x + y;
+            }
+            "#,
+        );
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn move_in_a_returning_branch_does_not_poison_the_fallthrough() {
+        let result = check_source(
+            r#"
+            fn consume(s: String) -> i32 { return 0; }
+            fn main() {
+                let s = "hello";
+                if true {
+                    consume(s);
+                    return;
+                }
+                consume(s);
+            }
+            "#,
+        );
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn move_inside_loop_is_reported_on_the_next_iteration() {
+        let err = check_source(
+            r#"
+            fn consume(s: String) -> i32 { return 0; }
+            fn main() {
+                let s = "hello";
+                while true {
+                    consume(s);
+                }
+            }
+            "#,
+        )
+        .unwrap_err();
+        assert!(err.contains("use of moved value: 's'"), "{}", err);
+    }
+}
@@ -0,0 +1,89 @@
+// src/compiler/src/cfg.rs
+
+//! Conditional compilation for `@cfg(...)` attributes.
+//!
+//! Items gated on a predicate that does not hold for the enabled features are
+//! dropped before semantic analysis; the rest are unwrapped into plain items,
+//! so later phases never see a `Statement::Cfg`.
+
+use crate::ast::{AstNode, CfgPredicate, Statement};
+
+impl CfgPredicate {
+    /// Whether the predicate holds when exactly `features` are enabled.
+    pub fn is_active(&self, features: &[String]) -> bool {
+        match self {
+            CfgPredicate::Feature(name) => features.iter().any(|feature| feature == name),
+            CfgPredicate::Not(inner) => !inner.is_active(features),
+            CfgPredicate::All(predicates) => predicates.iter().all(|p| p.is_active(features)),
+            CfgPredicate::Any(predicates) => predicates.iter().any(|p| p.is_active(features)),
+            // `test_harness::harness_program` unwraps the test it runs first.
+            CfgPredicate::Test => false,
+        }
+    }
+}
+
+/// Drop the items of `ast` whose `@cfg` predicate is false for `features` and
+/// unwrap the others, including methods inside `impl` blocks.
+pub fn strip_inactive_items(ast: Vec<AstNode>, features: &[String]) -> Vec<AstNode> {
+    ast.into_iter()
+        .filter_map(|node| match node {
+            AstNode::Statement(statement) => {
+                resolve_statement(statement, features).map(AstNode::Statement)
+            }
+            expression => Some(expression),
+        })
+        .collect()
+}
+
+fn resolve_statement(statement: Statement, features: &[String]) -> Option<Statement> {
+    match statement {
+        Statement::Cfg { predicate, item } => {
+            if predicate.is_active(features) {
+                resolve_statement(*item, features)
+            } else {
+                None
+            }
+        }
+        Statement::ImplBlock {
+            type_name,
+            methods,
+            type_params,
+            trait_name,
+        } => Some(Statement::ImplBlock {
+            type_name,
+            methods: methods
+                .into_iter()
+                .filter_map(|method| resolve_statement(method, features))
+                .collect(),
+            type_params,
+            trait_name,
+        }),
+        other => Some(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn combinators_evaluate_against_enabled_features() {
+        let gpu = || CfgPredicate::Feature("gpu".to_string());
+        let fast = || CfgPredicate::Feature("fast".to_string());
+        let both = CfgPredicate::All(vec![gpu(), fast()]);
+        let either = CfgPredicate::Any(vec![gpu(), fast()]);
+        let no_gpu = CfgPredicate::Not(Box::new(gpu()));
+
+        let enabled = features(&["gpu"]);
+        assert!(!both.is_active(&enabled));
+        assert!(either.is_active(&enabled));
+        assert!(!no_gpu.is_active(&enabled));
+        assert!(no_gpu.is_active(&features(&[])));
+        assert!(CfgPredicate::All(Vec::new()).is_active(&enabled));
+        assert!(!CfgPredicate::Any(Vec::new()).is_active(&enabled));
+    }
+}
@@ -1,18 +1,112 @@
 use crate::ir::{Function, Inst, Value};
-use std::collections::HashMap;
+use crate::ir_optimizer::prune_unused_registers;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 type FunctionDef = (Vec<(String, String)>, Option<String>, Vec<Inst>);
 
+/// The `define` line of a function with parameters: its name, signature and
+/// whether it was marked `#[inline(never)]`.
+struct FunctionHeader<'a> {
+    name: &'a str,
+    parameters: &'a [(String, String)],
+    return_type: &'a Option<String>,
+    no_inline: bool,
+}
+
+/// What a `panic` does at run time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicStrategy {
+    /// Print the message to stderr and call `abort()`, killing the process
+    /// with SIGABRT.
+    #[default]
+    Abort,
+    /// Print the message to stderr and `exit(101)`, as Rust does.
+    Exit,
+}
+
+impl PanicStrategy {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "abort" => Some(Self::Abort),
+            "exit" => Some(Self::Exit),
+            _ => None,
+        }
+    }
+}
+
+/// Prints a string quoted as `{:?}` shows it, escaping quotes, backslashes, tabs
+/// and line breaks.
+const DEBUG_STR_HELPER: &str = r#"define private void @aero_debug_str(i8* %s) {
+entry:
+  call i32 @putchar(i32 34)
+  br label %next
+next:
+  %i = phi i64 [ 0, %entry ], [ %i.next, %plain ], [ %i.next, %escape ]
+  %p = getelementptr inbounds i8, i8* %s, i64 %i
+  %c = load i8, i8* %p
+  %i.next = add i64 %i, 1
+  switch i8 %c, label %plain [
+    i8 0, label %done
+    i8 9, label %tab
+    i8 10, label %newline
+    i8 13, label %return
+    i8 34, label %literal
+    i8 92, label %literal
+  ]
+tab:
+  br label %escape
+newline:
+  br label %escape
+return:
+  br label %escape
+literal:
+  br label %escape
+escape:
+  %e = phi i8 [ 116, %tab ], [ 110, %newline ], [ 114, %return ], [ %c, %literal ]
+  call i32 @putchar(i32 92)
+  %e.wide = zext i8 %e to i32
+  call i32 @putchar(i32 %e.wide)
+  br label %next
+plain:
+  %c.wide = zext i8 %c to i32
+  call i32 @putchar(i32 %c.wide)
+  br label %next
+done:
+  call i32 @putchar(i32 34)
+  ret void
+}"#;
+
+/// What a function may do to memory outside its own stack frame, weakest
+/// first, so the effect of a body is the maximum over its instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MemoryEffect {
+    None,   // `readnone`
+    Reads,  // `readonly`
+    Writes, // no attribute
+}
+
 pub struct CodeGenerator {
     next_reg: u32,
     next_ptr: u32,
+    global_types: HashMap<String, String>, // LLVM type of each module-level global
+    intrinsics: BTreeSet<String>, // Declarations of LLVM intrinsics called so far, and string constants
+    runtime: BTreeSet<&'static str>, // Runtime functions called so far, see `runtime_function`
+    panic_strategy: PanicStrategy,
 }
 
 impl CodeGenerator {
     pub fn new() -> Self {
+        Self::with_panic_strategy(PanicStrategy::default())
+    }
+
+    pub fn with_panic_strategy(panic_strategy: PanicStrategy) -> Self {
         CodeGenerator {
             next_reg: 0,
             next_ptr: 0,
+            global_types: HashMap::new(),
+            intrinsics: BTreeSet::new(),
+            runtime: BTreeSet::new(),
+            panic_strategy,
         }
     }
 }
@@ -77,6 +171,24 @@ impl CodeGenerator {
                     Self::bump_seed_from_value(&mut seed, result);
                     Self::bump_seed_from_value(&mut seed, operand);
                 }
+                Inst::Select {
+                    result,
+                    condition,
+                    if_true,
+                    if_false,
+                } => {
+                    for value in [result, condition, if_true, if_false] {
+                        Self::bump_seed_from_value(&mut seed, value);
+                    }
+                }
+                Inst::FloatIntrinsic {
+                    result, arguments, ..
+                } => {
+                    Self::bump_seed_from_value(&mut seed, result);
+                    for arg in arguments {
+                        Self::bump_seed_from_value(&mut seed, arg);
+                    }
+                }
                 Inst::Call {
                     arguments, result, ..
                 } => {
@@ -98,16 +210,47 @@ impl CodeGenerator {
                     left,
                     right,
                     ..
+                }
+                | Inst::StrEq {
+                    result,
+                    left,
+                    right,
+                }
+                | Inst::StrConcat {
+                    result,
+                    left,
+                    right,
                 } => {
                     Self::bump_seed_from_value(&mut seed, result);
                     Self::bump_seed_from_value(&mut seed, left);
                     Self::bump_seed_from_value(&mut seed, right);
                 }
+                Inst::ParseNumber {
+                    source,
+                    value_ptr,
+                    tag_ptr,
+                    ..
+                } => {
+                    Self::bump_seed_from_value(&mut seed, source);
+                    Self::bump_seed_from_value(&mut seed, value_ptr);
+                    Self::bump_seed_from_value(&mut seed, tag_ptr);
+                }
                 Inst::Print { arguments, .. } | Inst::Println { arguments, .. } => {
                     for arg in arguments {
                         Self::bump_seed_from_value(&mut seed, arg);
                     }
                 }
+                Inst::StrConst { result, .. } => {
+                    Self::bump_seed_from_value(&mut seed, result);
+                }
+                Inst::StrFormat { result, value, .. }
+                | Inst::StrLen {
+                    result,
+                    source: value,
+                } => {
+                    Self::bump_seed_from_value(&mut seed, result);
+                    Self::bump_seed_from_value(&mut seed, value);
+                }
                 Inst::GetElementPtr {
                     result,
                     base,
@@ -125,6 +268,39 @@ impl CodeGenerator {
                     Self::bump_seed_from_value(&mut seed, result);
                     Self::bump_seed_from_value(&mut seed, base);
                 }
+                Inst::StructCopy { result, source, .. } => {
+                    Self::bump_seed_from_value(&mut seed, result);
+                    Self::bump_seed_from_value(&mut seed, source);
+                }
+                Inst::GlobalAddr { result, .. } | Inst::HeapAlloc { result, .. } => {
+                    Self::bump_seed_from_value(&mut seed, result);
+                }
+                Inst::PtrToSlot { result, ptr, .. } => {
+                    Self::bump_seed_from_value(&mut seed, result);
+                    Self::bump_seed_from_value(&mut seed, ptr);
+                }
+                Inst::SlotToPtr { result, value, .. } => {
+                    Self::bump_seed_from_value(&mut seed, result);
+                    Self::bump_seed_from_value(&mut seed, value);
+                }
+                Inst::GlobalDef { .. } | Inst::VTableDef { .. } => {}
+                Inst::VTableAddr { result, .. } => {
+                    Self::bump_seed_from_value(&mut seed, result);
+                }
+                Inst::CallIndirect {
+                    vtable,
+                    arguments,
+                    result,
+                    ..
+                } => {
+                    Self::bump_seed_from_value(&mut seed, vtable);
+                    for arg in arguments {
+                        Self::bump_seed_from_value(&mut seed, arg);
+                    }
+                    if let Some(result) = result {
+                        Self::bump_seed_from_value(&mut seed, result);
+                    }
+                }
                 Inst::VecAlloca { result, .. }
                 | Inst::VecPop { result, .. }
                 | Inst::VecLength { result, .. }
@@ -181,7 +357,13 @@ impl CodeGenerator {
                 Inst::FunctionDef { body, .. } => {
                     seed = seed.max(Self::infer_next_reg_seed(body));
                 }
-                Inst::Jump(_) | Inst::Label(_) => {}
+                Inst::Phi { result, incoming } => {
+                    Self::bump_seed_from_value(&mut seed, result);
+                    for (value, _) in incoming {
+                        Self::bump_seed_from_value(&mut seed, value);
+                    }
+                }
+                Inst::Jump(_) | Inst::Label(_) | Inst::Unreachable => {}
             }
         }
 
@@ -218,6 +400,15 @@ impl CodeGenerator {
         }
     }
 
+    /// An `i1` operand: immediates are spelled `true` or `false`.
+    fn value_to_i1_operand(&self, value: &Value) -> String {
+        match value {
+            Value::ImmInt(n) => (*n != 0).to_string(),
+            Value::ImmFloat(f) => (*f != 0.0).to_string(),
+            _ => self.value_to_string(value),
+        }
+    }
+
     fn value_to_int_string(&self, value: &Value) -> String {
         match value {
             Value::ImmInt(n) => format!("{}", n),
@@ -227,13 +418,26 @@ impl CodeGenerator {
         }
     }
 
+    /// Convert a double to `int_type` with `llvm.fptosi.sat`, which clamps
+    /// out-of-range values and maps NaN to 0 as Rust's `as` does. A plain
+    /// `fptosi` yields poison for those inputs.
+    fn emit_fptosi_sat(&mut self, llvm_ir: &mut String, result: &str, value: &str, int_type: &str) {
+        let intrinsic = format!("llvm.fptosi.sat.{}.f64", int_type);
+        self.intrinsics
+            .insert(format!("declare {} @{}(double)", int_type, intrinsic));
+        llvm_ir.push_str(&format!(
+            "  %{} = call {} @{}(double {})\n",
+            result, int_type, intrinsic, value
+        ));
+    }
+
     fn value_to_i32_operand(&mut self, llvm_ir: &mut String, value: &Value) -> String {
         match value {
             Value::ImmInt(n) => n.to_string(),
-            Value::ImmFloat(f) => (*f as i64).to_string(),
+            Value::ImmFloat(f) => (*f as i32).to_string(),
             Value::Reg(r) => {
                 let tmp = self.fresh_reg();
-                llvm_ir.push_str(&format!("  %{} = fptosi double %reg{} to i32\n", tmp, r));
+                self.emit_fptosi_sat(llvm_ir, &tmp, &format!("%reg{}", r), "i32");
                 format!("%{}", tmp)
             }
             Value::ImmString(_) => panic!("String value cannot be lowered as i32 operand"),
@@ -246,7 +450,7 @@ impl CodeGenerator {
             Value::ImmFloat(f) => (*f as i64).to_string(),
             Value::Reg(r) => {
                 let tmp = self.fresh_reg();
-                llvm_ir.push_str(&format!("  %{} = fptosi double %reg{} to i64\n", tmp, r));
+                self.emit_fptosi_sat(llvm_ir, &tmp, &format!("%reg{}", r), "i64");
                 format!("%{}", tmp)
             }
             Value::ImmString(_) => panic!("String value cannot be lowered as i64 operand"),
@@ -290,21 +494,35 @@ impl CodeGenerator {
         llvm_ir.push_str("target datalayout = \"e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128\"\n");
         llvm_ir.push_str("target triple = \"x86_64-pc-linux-gnu\"\n\n");
 
-        // Add printf declaration for I/O operations
-        self.generate_printf_declaration(&mut llvm_ir);
+        // Struct types referenced by aggregate instructions
+        self.generate_struct_type_declarations(&mut llvm_ir, &ir_functions);
+
+        // Module-level statics
+        self.generate_global_definitions(&mut llvm_ir, &ir_functions);
+
+        // The runtime preamble goes here once the bodies show what is used.
+        let preamble_at = llvm_ir.len();
+
+        // Emit in a stable order so identical programs give identical modules.
+        let emission_order = Self::function_emission_order(&ir_functions);
 
         // First pass: collect function definitions from IR instructions
         let mut function_defs: HashMap<String, FunctionDef> = HashMap::new();
+        let mut no_inline: HashSet<String> = HashSet::new();
 
-        for func in ir_functions.values() {
+        for func in emission_order.iter().map(|name| &ir_functions[name]) {
             for inst in &func.body {
                 if let Inst::FunctionDef {
                     name,
                     parameters,
                     return_type,
                     body,
+                    no_inline: marked,
                 } = inst
                 {
+                    if *marked {
+                        no_inline.insert(name.clone());
+                    }
                     function_defs.insert(
                         name.clone(),
                         (parameters.clone(), return_type.clone(), body.clone()),
@@ -313,15 +531,22 @@ impl CodeGenerator {
             }
         }
 
+        self.generate_vtable_definitions(&mut llvm_ir, &ir_functions, &function_defs);
+
         // Generate function definitions
-        for (func_name, func) in ir_functions {
+        for func_name in emission_order {
+            let func = &ir_functions[&func_name];
             // Check if this function has a definition with parameters
             if let Some((parameters, return_type, body)) = function_defs.get(&func_name) {
-                self.generate_function_definition(
-                    &mut llvm_ir,
-                    &func_name,
+                let header = FunctionHeader {
+                    name: &func_name,
                     parameters,
                     return_type,
+                    no_inline: no_inline.contains(&func_name),
+                };
+                self.generate_function_definition(
+                    &mut llvm_ir,
+                    header,
                     body,
                     func.next_reg,
                     &function_defs,
@@ -342,29 +567,134 @@ impl CodeGenerator {
             }
         }
 
+        // Intrinsics are only known once the bodies have been emitted.
+        for declaration in std::mem::take(&mut self.intrinsics) {
+            llvm_ir.push_str(&format!("{}\n", declaration));
+        }
+
+        let preamble = self.generate_runtime_preamble();
+        llvm_ir.insert_str(preamble_at, &preamble);
         llvm_ir
     }
 
+    /// Declaration of a C function, or definition of an Aero helper, that
+    /// generated code may call, with the runtime functions it calls itself.
+    fn runtime_function(&self, name: &str) -> (String, &'static [&'static str]) {
+        let declaration = match name {
+            "abort" => "declare void @abort()",
+            "exit" => "declare void @exit(i32)",
+            "fflush" => "declare i32 @fflush(i8*)",
+            "free" => "declare void @free(i8*)",
+            "malloc" => "declare i8* @malloc(i64)",
+            "memcmp" => "declare i32 @memcmp(i8*, i8*, i64)",
+            "memcpy" => "declare void @llvm.memcpy.p0i8.p0i8.i64(i8*, i8*, i64, i1)",
+            "printf" => "declare i32 @printf(i8*, ...)",
+            "putchar" => "declare i32 @putchar(i32)",
+            "realloc" => "declare i8* @realloc(i8*, i64)",
+            "snprintf" => "declare i32 @snprintf(i8*, i64, i8*, ...)",
+            "strlen" => "declare i64 @strlen(i8*)",
+            "strtod" => "declare double @strtod(i8*, i8**)",
+            "strtol" => "declare i64 @strtol(i8*, i8**, i32)",
+            "write" => "declare i64 @write(i32, i8*, i64)",
+            "aero_debug_str" => return (DEBUG_STR_HELPER.to_string(), &["putchar"]),
+            "aero_panic" => {
+                // `abort` skips the stdio flush `exit` does, so output the
+                // program buffered before the panic is flushed by hand.
+                let (end, calls): (_, &'static [&'static str]) = match self.panic_strategy {
+                    PanicStrategy::Abort => (
+                        "%flushed = call i32 @fflush(i8* null)\n  call void @abort()",
+                        &["write", "fflush", "abort"],
+                    ),
+                    PanicStrategy::Exit => ("call void @exit(i32 101)", &["write", "exit"]),
+                };
+                let helper = format!(
+                    "define private void @aero_panic(i8* %report, i64 %len) noreturn {{\nentry:\n  %written = call i64 @write(i32 2, i8* %report, i64 %len)\n  {}\n  unreachable\n}}",
+                    end
+                );
+                return (helper, calls);
+            }
+            other => panic!("Unknown runtime function `{}`", other),
+        };
+        (declaration.to_string(), &[])
+    }
+
+    /// Record that generated code calls the runtime function `name`.
+    fn use_runtime(&mut self, name: &'static str) {
+        if self.runtime.insert(name) {
+            for callee in self.runtime_function(name).1 {
+                self.use_runtime(callee);
+            }
+        }
+    }
+
+    /// Declare or define, once each, the runtime functions the module uses.
+    fn generate_runtime_preamble(&mut self) -> String {
+        let mut preamble = String::new();
+        for name in std::mem::take(&mut self.runtime) {
+            preamble.push_str(&self.runtime_function(name).0);
+            preamble.push('\n');
+        }
+        if !preamble.is_empty() {
+            preamble.push('\n');
+        }
+        preamble
+    }
+
+    /// Function names in emission order: declaration order (the order their
+    /// `FunctionDef`s appear in, starting from `main`), then any remaining
+    /// functions by name, with `main` last.
+    fn function_emission_order(ir_functions: &HashMap<String, Function>) -> Vec<String> {
+        fn collect(instructions: &[Inst], order: &mut Vec<String>) {
+            for inst in instructions {
+                if let Inst::FunctionDef { name, body, .. } = inst {
+                    if !order.contains(name) {
+                        order.push(name.clone());
+                    }
+                    collect(body, order);
+                }
+            }
+        }
+
+        let mut names: Vec<&String> = ir_functions.keys().collect();
+        names.sort_by_key(|name| (name.as_str() != "main", name.as_str()));
+
+        let mut declared = Vec::new();
+        for name in &names {
+            collect(&ir_functions[*name].body, &mut declared);
+        }
+
+        let mut order: Vec<String> = declared
+            .into_iter()
+            .filter(|name| name != "main" && ir_functions.contains_key(name))
+            .collect();
+        for name in names {
+            if name != "main" && !order.contains(name) {
+                order.push(name.clone());
+            }
+        }
+        if ir_functions.contains_key("main") {
+            order.push("main".to_string());
+        }
+        order
+    }
+
     fn generate_function_definition(
         &mut self,
         llvm_ir: &mut String,
-        func_name: &str,
-        parameters: &[(String, String)],
-        return_type: &Option<String>,
+        header: FunctionHeader,
         body: &[Inst],
         next_reg_seed: u32,
         function_defs: &HashMap<String, FunctionDef>,
     ) {
+        let FunctionHeader {
+            name: func_name,
+            parameters,
+            return_type,
+            no_inline,
+        } = header;
+
         // Generate function signature
-        let return_llvm_type = if let Some(ret_type) = return_type {
-            self.type_to_llvm(ret_type).to_string()
-        } else if func_name == "main" {
-            // Keep C ABI-compatible entrypoint semantics even when source omits
-            // an explicit return type.
-            "i32".to_string()
-        } else {
-            "void".to_string()
-        };
+        let return_llvm_type = self.return_type_to_llvm(func_name, return_type);
 
         let mut param_str = String::new();
         for (i, (param_name, param_type)) in parameters.iter().enumerate() {
@@ -378,9 +708,15 @@ impl CodeGenerator {
             ));
         }
 
+        let attributes = Self::function_attributes(func_name, function_defs, no_inline);
+        let attributes = if attributes.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", attributes)
+        };
         llvm_ir.push_str(&format!(
-            "define {} @{}({}) {{\nentry:\n",
-            return_llvm_type, func_name, param_str
+            "define {} @{}({}){} {{\nentry:\n",
+            return_llvm_type, func_name, param_str, attributes
         ));
 
         let mut param_types = HashMap::new();
@@ -399,6 +735,227 @@ impl CodeGenerator {
         llvm_ir.push_str("}\n\n");
     }
 
+    fn return_type_to_llvm(&self, func_name: &str, return_type: &Option<String>) -> String {
+        if let Some(ret_type) = return_type {
+            self.type_to_llvm(ret_type).to_string()
+        } else if func_name == "main" {
+            // Keep C ABI-compatible entrypoint semantics even when source omits
+            // an explicit return type.
+            "i32".to_string()
+        } else {
+            "void".to_string()
+        }
+    }
+
+    /// LLVM pointer type of a function with the given IR signature.
+    fn function_pointer_type(
+        &self,
+        func_name: &str,
+        parameters: &[(String, String)],
+        return_type: &Option<String>,
+    ) -> String {
+        let params = parameters
+            .iter()
+            .map(|(_, ty)| self.type_to_llvm(ty))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "{} ({})*",
+            self.return_type_to_llvm(func_name, return_type),
+            params
+        )
+    }
+
+    /// Attributes for a user function's `define` line: `noinline` when marked
+    /// `@inline(never)`, `nounwind` when nothing it can reach panics, and
+    /// `readnone`/`readonly` when it touches no memory but its own stack.
+    fn function_attributes(
+        func_name: &str,
+        function_defs: &HashMap<String, FunctionDef>,
+        no_inline: bool,
+    ) -> String {
+        let mut reachable = HashSet::new();
+        let mut effect = MemoryEffect::None;
+        let mut may_panic = false;
+        let mut pending = vec![func_name];
+        while let Some(name) = pending.pop() {
+            if !reachable.insert(name) {
+                continue;
+            }
+            let Some((_, _, body)) = function_defs.get(name) else {
+                continue;
+            };
+            effect = effect.max(Self::memory_effect(body, function_defs));
+            for inst in body {
+                match inst {
+                    Inst::Call { function, .. } if function_defs.contains_key(function) => {
+                        pending.push(function);
+                    }
+                    Inst::Call { function, .. } if function == "panic" => may_panic = true,
+                    // The callee is only known at run time.
+                    Inst::CallIndirect { .. } => may_panic = true,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut attributes = Vec::new();
+        if no_inline {
+            attributes.push("noinline");
+        }
+        if !may_panic {
+            attributes.push("nounwind");
+        }
+        match effect {
+            MemoryEffect::None => attributes.push("readnone"),
+            MemoryEffect::Reads => attributes.push("readonly"),
+            MemoryEffect::Writes => {}
+        }
+        attributes.join(" ")
+    }
+
+    /// Strongest memory effect of a body on its own, calls to other user
+    /// functions aside. Loads and stores through pointers to the body's own
+    /// allocas are free; anything not known to be harmless counts as a write.
+    fn memory_effect(body: &[Inst], function_defs: &HashMap<String, FunctionDef>) -> MemoryEffect {
+        fn ptr_id(value: &Value) -> Option<u32> {
+            match value {
+                Value::Reg(id) => Some(*id),
+                _ => None,
+            }
+        }
+
+        let mut locals = HashSet::new();
+        let mut effect = MemoryEffect::None;
+        for inst in body {
+            let access = match inst {
+                Inst::Alloca(ptr, _)
+                | Inst::AllocaArray { result: ptr, .. }
+                | Inst::AllocaStruct { result: ptr, .. } => {
+                    locals.extend(ptr_id(ptr));
+                    MemoryEffect::None
+                }
+                Inst::GetElementPtr { result, base, .. }
+                | Inst::GetFieldPtr { result, base, .. } => {
+                    if ptr_id(base).is_some_and(|id| locals.contains(&id)) {
+                        locals.extend(ptr_id(result));
+                    }
+                    MemoryEffect::None
+                }
+                Inst::StructCopy { result, source, .. } => {
+                    locals.extend(ptr_id(result));
+                    if ptr_id(source).is_some_and(|id| locals.contains(&id)) {
+                        MemoryEffect::None
+                    } else {
+                        MemoryEffect::Reads
+                    }
+                }
+                Inst::Load(_, ptr) => {
+                    if ptr_id(ptr).is_some_and(|id| locals.contains(&id)) {
+                        MemoryEffect::None
+                    } else {
+                        MemoryEffect::Reads
+                    }
+                }
+                Inst::Store(ptr, _) => {
+                    if ptr_id(ptr).is_some_and(|id| locals.contains(&id)) {
+                        MemoryEffect::None
+                    } else {
+                        MemoryEffect::Writes
+                    }
+                }
+                // Callees are scanned separately, see `function_attributes`.
+                Inst::Call { function, .. } if function_defs.contains_key(function) => {
+                    MemoryEffect::None
+                }
+                Inst::Add(..)
+                | Inst::FAdd(..)
+                | Inst::Sub(..)
+                | Inst::FSub(..)
+                | Inst::Mul(..)
+                | Inst::FMul(..)
+                | Inst::Div(..)
+                | Inst::FDiv(..)
+                | Inst::SIToFP(..)
+                | Inst::FPToSI(..)
+                | Inst::ICmp { .. }
+                | Inst::FCmp { .. }
+                | Inst::And { .. }
+                | Inst::Or { .. }
+                | Inst::Not { .. }
+                | Inst::Neg { .. }
+                | Inst::Select { .. }
+                | Inst::Phi { .. }
+                | Inst::FloatIntrinsic { .. }
+                | Inst::SlotToPtr { .. }
+                | Inst::GlobalAddr { .. }
+                | Inst::Branch { .. }
+                | Inst::Jump(_)
+                | Inst::Label(_)
+                | Inst::Return(_)
+                | Inst::Unreachable
+                | Inst::FunctionDef { .. } => MemoryEffect::None,
+                _ => MemoryEffect::Writes,
+            };
+            effect = effect.max(access);
+        }
+        effect
+    }
+
+    /// Vtables for trait objects: one constant array of method pointers per
+    /// `impl Trait for Type`, typed as `i8*` and cast back at the call site.
+    fn generate_vtable_definitions(
+        &mut self,
+        llvm_ir: &mut String,
+        ir_functions: &HashMap<String, Function>,
+        function_defs: &HashMap<String, FunctionDef>,
+    ) {
+        fn collect<'a>(instructions: &'a [Inst], vtables: &mut BTreeMap<&'a str, &'a [String]>) {
+            for inst in instructions {
+                match inst {
+                    Inst::VTableDef { name, functions } => {
+                        vtables.insert(name, functions);
+                    }
+                    Inst::FunctionDef { body, .. } => collect(body, vtables),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut vtables = BTreeMap::new();
+        for func in ir_functions.values() {
+            collect(&func.body, &mut vtables);
+        }
+        if vtables.is_empty() {
+            return;
+        }
+
+        for (name, functions) in vtables {
+            let entries = functions
+                .iter()
+                .map(|function| {
+                    let (parameters, return_type, _) = function_defs
+                        .get(function)
+                        .cloned()
+                        .unwrap_or((Vec::new(), None, Vec::new()));
+                    format!(
+                        "i8* bitcast ({} @{} to i8*)",
+                        self.function_pointer_type(function, &parameters, &return_type),
+                        function
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let vtable_type = format!("[{} x i8*]", functions.len());
+            llvm_ir.push_str(&format!(
+                "@{} = constant {} [{}], align 8\n",
+                name, vtable_type, entries
+            ));
+            self.global_types.insert(name.to_string(), vtable_type);
+        }
+        llvm_ir.push('\n');
+    }
+
     fn generate_function_body(
         &mut self,
         llvm_ir: &mut String,
@@ -410,7 +967,11 @@ impl CodeGenerator {
     ) {
         self.next_reg = next_reg_seed.max(Self::infer_next_reg_seed(instructions));
 
-        for inst in instructions {
+        // Results nothing reads are not worth emitting.
+        let mut instructions = instructions.to_vec();
+        prune_unused_registers(&mut instructions);
+
+        for inst in &instructions {
             match inst {
                 Inst::Alloca(ptr_reg, name) => {
                     let ptr_id = match ptr_reg {
@@ -544,10 +1105,7 @@ impl CodeGenerator {
                         _ => panic!("Expected register for fptosi result"),
                     };
                     let val_str = self.value_to_string(value);
-                    llvm_ir.push_str(&format!(
-                        "  %{} = fptosi double {} to i64\n",
-                        result_str, val_str
-                    ));
+                    self.emit_fptosi_sat(llvm_ir, &result_str, &val_str, "i64");
                 }
                 Inst::Return(value) => self.emit_return(llvm_ir, value, return_llvm_type),
                 Inst::SIToFP(result_reg, value) => {
@@ -576,6 +1134,14 @@ impl CodeGenerator {
                 } => self.generate_branch(llvm_ir, condition, true_label, false_label),
                 Inst::Jump(label) => llvm_ir.push_str(&format!("  br label %{}\n", label)),
                 Inst::Label(label) => llvm_ir.push_str(&format!("{}:\n", label)),
+                Inst::Phi { result, incoming } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for phi result"),
+                    };
+                    self.generate_phi_node(llvm_ir, &result_str, incoming);
+                }
+                Inst::Unreachable => llvm_ir.push_str("  unreachable\n"),
                 Inst::ICmp {
                     op,
                     result,
@@ -610,6 +1176,59 @@ impl CodeGenerator {
                         result_str, op, left_str, right_str
                     ));
                 }
+                Inst::StrEq {
+                    result,
+                    left,
+                    right,
+                } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for string equality result"),
+                    };
+                    self.generate_string_eq(llvm_ir, &result_str, left, right);
+                }
+                Inst::StrConcat {
+                    result,
+                    left,
+                    right,
+                } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for string concatenation result"),
+                    };
+                    self.generate_string_concat(llvm_ir, &result_str, left, right);
+                }
+                Inst::StrLen { result, source } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for string length result"),
+                    };
+                    self.generate_string_len(llvm_ir, &result_str, source);
+                }
+                Inst::StrFormat {
+                    result,
+                    value,
+                    specifier,
+                } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for string format result"),
+                    };
+                    self.generate_string_format(llvm_ir, &result_str, value, *specifier);
+                }
+                Inst::StrConst { result, text } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for string constant result"),
+                    };
+                    self.generate_string_constant(llvm_ir, &result_str, text);
+                }
+                Inst::ParseNumber {
+                    source,
+                    float,
+                    value_ptr,
+                    tag_ptr,
+                } => self.generate_parse_number(llvm_ir, source, *float, value_ptr, tag_ptr),
                 Inst::Print {
                     format_string,
                     arguments,
@@ -627,8 +1246,8 @@ impl CodeGenerator {
                         Value::Reg(r) => format!("reg{}", r),
                         _ => panic!("Expected register for and result"),
                     };
-                    let left_str = self.value_to_string(left);
-                    let right_str = self.value_to_string(right);
+                    let left_str = self.value_to_i1_operand(left);
+                    let right_str = self.value_to_i1_operand(right);
                     llvm_ir.push_str(&format!(
                         "  %{} = and i1 {}, {}\n",
                         result_str, left_str, right_str
@@ -643,8 +1262,8 @@ impl CodeGenerator {
                         Value::Reg(r) => format!("reg{}", r),
                         _ => panic!("Expected register for or result"),
                     };
-                    let left_str = self.value_to_string(left);
-                    let right_str = self.value_to_string(right);
+                    let left_str = self.value_to_i1_operand(left);
+                    let right_str = self.value_to_i1_operand(right);
                     llvm_ir.push_str(&format!(
                         "  %{} = or i1 {}, {}\n",
                         result_str, left_str, right_str
@@ -655,7 +1274,7 @@ impl CodeGenerator {
                         Value::Reg(r) => format!("reg{}", r),
                         _ => panic!("Expected register for not result"),
                     };
-                    let operand_str = self.value_to_string(operand);
+                    let operand_str = self.value_to_i1_operand(operand);
                     llvm_ir.push_str(&format!(
                         "  %{} = xor i1 {}, true\n",
                         result_str, operand_str
@@ -672,6 +1291,47 @@ impl CodeGenerator {
                         result_str, operand_str
                     ));
                 }
+                Inst::Select {
+                    result,
+                    condition,
+                    if_true,
+                    if_false,
+                } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for select result"),
+                    };
+                    llvm_ir.push_str(&format!(
+                        "  %{} = select i1 {}, double {}, double {}\n",
+                        result_str,
+                        self.value_to_string(condition),
+                        self.value_to_string(if_true),
+                        self.value_to_string(if_false)
+                    ));
+                }
+                Inst::FloatIntrinsic {
+                    result,
+                    intrinsic,
+                    arguments,
+                } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for intrinsic result"),
+                    };
+                    let params = vec!["double"; arguments.len()].join(", ");
+                    self.intrinsics
+                        .insert(format!("declare double @{}({})", intrinsic, params));
+                    let args: Vec<String> = arguments
+                        .iter()
+                        .map(|arg| format!("double {}", self.value_to_string(arg)))
+                        .collect();
+                    llvm_ir.push_str(&format!(
+                        "  %{} = call double @{}({})\n",
+                        result_str,
+                        intrinsic,
+                        args.join(", ")
+                    ));
+                }
                 Inst::AllocaArray {
                     result,
                     elem_type,
@@ -738,6 +1398,204 @@ impl CodeGenerator {
                         result_str, struct_type, struct_type, base_str, field_index
                     ));
                 }
+                Inst::HeapAlloc {
+                    result,
+                    struct_type,
+                } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("ptr{}", r),
+                        _ => panic!("Expected register for heap allocation"),
+                    };
+                    let ptr_type = Self::pointee_pointer_type(struct_type);
+                    let pointee = ptr_type.trim_end_matches('*');
+                    // sizeof(T) via the null-GEP idiom
+                    let size_ptr = self.fresh_reg();
+                    let size = self.fresh_reg();
+                    let raw = self.fresh_reg();
+                    llvm_ir.push_str(&format!(
+                        "  %{} = getelementptr {}, {} null, i32 1\n",
+                        size_ptr, pointee, ptr_type
+                    ));
+                    llvm_ir.push_str(&format!(
+                        "  %{} = ptrtoint {} %{} to i64\n",
+                        size, ptr_type, size_ptr
+                    ));
+                    self.use_runtime("malloc");
+                    llvm_ir.push_str(&format!("  %{} = call i8* @malloc(i64 %{})\n", raw, size));
+                    llvm_ir.push_str(&format!(
+                        "  %{} = bitcast i8* %{} to {}\n",
+                        result_str, raw, ptr_type
+                    ));
+                }
+                Inst::PtrToSlot {
+                    result,
+                    ptr,
+                    struct_type,
+                } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for packed pointer"),
+                    };
+                    let ptr_str = match ptr {
+                        Value::Reg(r) => format!("ptr{}", r),
+                        _ => panic!("Expected pointer to pack"),
+                    };
+                    let bits = self.fresh_reg();
+                    llvm_ir.push_str(&format!(
+                        "  %{} = ptrtoint {} %{} to i64\n",
+                        bits,
+                        Self::pointee_pointer_type(struct_type),
+                        ptr_str
+                    ));
+                    llvm_ir.push_str(&format!(
+                        "  %{} = bitcast i64 %{} to double\n",
+                        result_str, bits
+                    ));
+                }
+                Inst::SlotToPtr {
+                    result,
+                    value,
+                    struct_type,
+                } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("ptr{}", r),
+                        _ => panic!("Expected register for unpacked pointer"),
+                    };
+                    let bits = self.fresh_reg();
+                    llvm_ir.push_str(&format!(
+                        "  %{} = bitcast double {} to i64\n",
+                        bits,
+                        self.value_to_string(value)
+                    ));
+                    llvm_ir.push_str(&format!(
+                        "  %{} = inttoptr i64 %{} to {}\n",
+                        result_str,
+                        bits,
+                        Self::pointee_pointer_type(struct_type)
+                    ));
+                }
+                Inst::VTableAddr { result, name } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("reg{}", r),
+                        _ => panic!("Expected register for vtable address"),
+                    };
+                    let vtable_type = self
+                        .global_types
+                        .get(name)
+                        .cloned()
+                        .unwrap_or_else(|| "[0 x i8*]".to_string());
+                    let bits = self.fresh_reg();
+                    llvm_ir.push_str(&format!(
+                        "  %{} = ptrtoint {}* @{} to i64\n",
+                        bits, vtable_type, name
+                    ));
+                    llvm_ir.push_str(&format!(
+                        "  %{} = bitcast i64 %{} to double\n",
+                        result_str, bits
+                    ));
+                }
+                Inst::CallIndirect {
+                    vtable,
+                    slot,
+                    parameters,
+                    return_type,
+                    arguments,
+                    result,
+                } => {
+                    let bits = self.fresh_reg();
+                    llvm_ir.push_str(&format!(
+                        "  %{} = bitcast double {} to i64\n",
+                        bits,
+                        self.value_to_string(vtable)
+                    ));
+                    let table = self.fresh_reg();
+                    llvm_ir.push_str(&format!("  %{} = inttoptr i64 %{} to i8**\n", table, bits));
+                    let entry = self.fresh_reg();
+                    llvm_ir.push_str(&format!(
+                        "  %{} = getelementptr inbounds i8*, i8** %{}, i64 {}\n",
+                        entry, table, slot
+                    ));
+                    let raw = self.fresh_reg();
+                    llvm_ir.push_str(&format!(
+                        "  %{} = load i8*, i8** %{}, align 8\n",
+                        raw, entry
+                    ));
+                    let param_defs: Vec<(String, String)> = parameters
+                        .iter()
+                        .map(|ty| (String::new(), ty.clone()))
+                        .collect();
+                    let callee = self.fresh_reg();
+                    llvm_ir.push_str(&format!(
+                        "  %{} = bitcast i8* %{} to {}\n",
+                        callee,
+                        raw,
+                        self.function_pointer_type("", &param_defs, return_type)
+                    ));
+                    self.generate_call(
+                        llvm_ir,
+                        &format!("%{}", callee),
+                        &param_defs,
+                        return_type.clone(),
+                        arguments,
+                        result,
+                    );
+                }
+                Inst::GlobalAddr { result, name } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("ptr{}", r),
+                        _ => panic!("Expected register for global address"),
+                    };
+                    let global_type = self
+                        .global_types
+                        .get(name)
+                        .cloned()
+                        .unwrap_or_else(|| "double".to_string());
+                    llvm_ir.push_str(&format!(
+                        "  %{} = getelementptr inbounds {}, {}* @{}, i64 0\n",
+                        result_str, global_type, global_type, name
+                    ));
+                }
+                Inst::StructCopy {
+                    result,
+                    source,
+                    struct_type,
+                    field_count,
+                } => {
+                    let result_str = match result {
+                        Value::Reg(r) => format!("ptr{}", r),
+                        _ => panic!("Expected register for struct copy result"),
+                    };
+                    let source_str = match source {
+                        Value::Reg(r) => format!("ptr{}", r),
+                        _ => panic!("Expected register for struct copy source"),
+                    };
+                    llvm_ir.push_str(&format!(
+                        "  %{} = alloca %{}, align 8\n",
+                        result_str, struct_type
+                    ));
+                    // Field-by-field copy; every field is a double slot.
+                    for field_index in 0..*field_count {
+                        let src_field = self.fresh_reg();
+                        let field_value = self.fresh_reg();
+                        let dst_field = self.fresh_reg();
+                        llvm_ir.push_str(&format!(
+                            "  %{} = getelementptr inbounds %{}, %{}* %{}, i32 0, i32 {}\n",
+                            src_field, struct_type, struct_type, source_str, field_index
+                        ));
+                        llvm_ir.push_str(&format!(
+                            "  %{} = load double, double* %{}, align 8\n",
+                            field_value, src_field
+                        ));
+                        llvm_ir.push_str(&format!(
+                            "  %{} = getelementptr inbounds %{}, %{}* %{}, i32 0, i32 {}\n",
+                            dst_field, struct_type, struct_type, result_str, field_index
+                        ));
+                        llvm_ir.push_str(&format!(
+                            "  store double %{}, double* %{}, align 8\n",
+                            field_value, dst_field
+                        ));
+                    }
+                }
                 _ => {}
             }
         }
@@ -765,13 +1623,61 @@ impl CodeGenerator {
         result: &Option<Value>,
         function_defs: &HashMap<String, FunctionDef>,
     ) {
+        // `panic` ends the program; the `unreachable` that follows it in the
+        // IR ends the block.
+        if function == "panic" && !function_defs.contains_key(function) {
+            self.generate_panic(llvm_ir, arguments);
+            return;
+        }
         let (param_defs, return_type) =
             if let Some((params, ret, _body)) = function_defs.get(function) {
                 (params.clone(), ret.clone())
             } else {
                 (Vec::new(), None)
             };
+        self.generate_call(
+            llvm_ir,
+            &format!("@{}", function),
+            &param_defs,
+            return_type,
+            arguments,
+            result,
+        );
+    }
+
+    /// Write the panic message, and the `file:line:column` of the panic site
+    /// when the IR passes one, to stderr, then end the program.
+    fn generate_panic(&mut self, llvm_ir: &mut String, arguments: &[Value]) {
+        let mut report = match arguments.first() {
+            Some(Value::ImmString(message)) => format!("thread 'main' panicked at '{}'", message),
+            _ => "thread 'main' panicked".to_string(),
+        };
+        if let Some(Value::ImmString(location)) = arguments.get(1) {
+            report.push(' ');
+            report.push_str(location);
+        }
+        report.push('\n');
 
+        self.use_runtime("aero_panic");
+        let ptr = self.emit_stack_string_literal(llvm_ir, &report);
+        llvm_ir.push_str(&format!(
+            "  call void @aero_panic(i8* {}, i64 {})\n",
+            ptr,
+            report.len()
+        ));
+    }
+
+    /// Emit a call to `callee` (a global symbol or a function pointer
+    /// register), converting its result into a double register.
+    fn generate_call(
+        &mut self,
+        llvm_ir: &mut String,
+        callee: &str,
+        param_defs: &[(String, String)],
+        return_type: Option<String>,
+        arguments: &[Value],
+        result: &Option<Value>,
+    ) {
         let mut args = Vec::new();
         for (i, arg) in arguments.iter().enumerate() {
             let target_type = if let Some((_name, ty)) = param_defs.get(i) {
@@ -800,14 +1706,14 @@ impl CodeGenerator {
 
             match return_llvm_type.as_str() {
                 "double" => llvm_ir.push_str(&format!(
-                    "  %{} = call double @{}({})\n",
-                    result_str, function, args_str
+                    "  %{} = call double {}({})\n",
+                    result_str, callee, args_str
                 )),
                 "i32" => {
                     let call_reg = self.fresh_reg();
                     llvm_ir.push_str(&format!(
-                        "  %{} = call i32 @{}({})\n",
-                        call_reg, function, args_str
+                        "  %{} = call i32 {}({})\n",
+                        call_reg, callee, args_str
                     ));
                     llvm_ir.push_str(&format!(
                         "  %{} = sitofp i32 %{} to double\n",
@@ -817,8 +1723,8 @@ impl CodeGenerator {
                 "i64" => {
                     let call_reg = self.fresh_reg();
                     llvm_ir.push_str(&format!(
-                        "  %{} = call i64 @{}({})\n",
-                        call_reg, function, args_str
+                        "  %{} = call i64 {}({})\n",
+                        call_reg, callee, args_str
                     ));
                     llvm_ir.push_str(&format!(
                         "  %{} = sitofp i64 %{} to double\n",
@@ -828,8 +1734,8 @@ impl CodeGenerator {
                 "i1" => {
                     let call_reg = self.fresh_reg();
                     llvm_ir.push_str(&format!(
-                        "  %{} = call i1 @{}({})\n",
-                        call_reg, function, args_str
+                        "  %{} = call i1 {}({})\n",
+                        call_reg, callee, args_str
                     ));
                     llvm_ir.push_str(&format!(
                         "  %{} = uitofp i1 %{} to double\n",
@@ -837,21 +1743,21 @@ impl CodeGenerator {
                     ));
                 }
                 "void" => {
-                    llvm_ir.push_str(&format!("  call void @{}({})\n", function, args_str));
+                    llvm_ir.push_str(&format!("  call void {}({})\n", callee, args_str));
                     llvm_ir.push_str(&format!(
                         "  %{} = fadd double 0x0000000000000000, 0x0000000000000000\n",
                         result_str
                     ));
                 }
                 _ => llvm_ir.push_str(&format!(
-                    "  %{} = call double @{}({})\n",
-                    result_str, function, args_str
+                    "  %{} = call double {}({})\n",
+                    result_str, callee, args_str
                 )),
             }
         } else {
             llvm_ir.push_str(&format!(
-                "  call {} @{}({})\n",
-                return_llvm_type, function, args_str
+                "  call {} {}({})\n",
+                return_llvm_type, callee, args_str
             ));
         }
     }
@@ -866,10 +1772,10 @@ impl CodeGenerator {
             "double" => self.value_to_string(value),
             "i32" => match value {
                 Value::ImmInt(n) => n.to_string(),
-                Value::ImmFloat(f) => (*f as i64).to_string(),
+                Value::ImmFloat(f) => (*f as i32).to_string(),
                 Value::Reg(r) => {
                     let tmp = self.fresh_reg();
-                    llvm_ir.push_str(&format!("  %{} = fptosi double %reg{} to i32\n", tmp, r));
+                    self.emit_fptosi_sat(llvm_ir, &tmp, &format!("%reg{}", r), "i32");
                     format!("%{}", tmp)
                 }
                 Value::ImmString(_) => {
@@ -881,7 +1787,7 @@ impl CodeGenerator {
                 Value::ImmFloat(f) => (*f as i64).to_string(),
                 Value::Reg(r) => {
                     let tmp = self.fresh_reg();
-                    llvm_ir.push_str(&format!("  %{} = fptosi double %reg{} to i64\n", tmp, r));
+                    self.emit_fptosi_sat(llvm_ir, &tmp, &format!("%reg{}", r), "i64");
                     format!("%{}", tmp)
                 }
                 Value::ImmString(_) => {
@@ -930,7 +1836,7 @@ impl CodeGenerator {
                 Value::ImmFloat(f) => llvm_ir.push_str(&format!("  ret i64 {}\n", *f as i64)),
                 Value::Reg(r) => {
                     let tmp = self.fresh_reg();
-                    llvm_ir.push_str(&format!("  %{} = fptosi double %reg{} to i64\n", tmp, r));
+                    self.emit_fptosi_sat(llvm_ir, &tmp, &format!("%reg{}", r), "i64");
                     llvm_ir.push_str(&format!("  ret i64 %{}\n", tmp));
                 }
                 Value::ImmString(_) => panic!("Cannot return string value as i64"),
@@ -956,10 +1862,10 @@ impl CodeGenerator {
             },
             _ => match value {
                 Value::ImmInt(n) => llvm_ir.push_str(&format!("  ret i32 {}\n", n)),
-                Value::ImmFloat(f) => llvm_ir.push_str(&format!("  ret i32 {}\n", *f as i64)),
+                Value::ImmFloat(f) => llvm_ir.push_str(&format!("  ret i32 {}\n", *f as i32)),
                 Value::Reg(r) => {
                     let tmp = self.fresh_reg();
-                    llvm_ir.push_str(&format!("  %{} = fptosi double %reg{} to i32\n", tmp, r));
+                    self.emit_fptosi_sat(llvm_ir, &tmp, &format!("%reg{}", r), "i32");
                     llvm_ir.push_str(&format!("  ret i32 %{}\n", tmp));
                 }
                 Value::ImmString(_) => panic!("Cannot return string value as i32"),
@@ -999,14 +1905,13 @@ impl CodeGenerator {
         }
     }
 
-    #[allow(dead_code)]
     fn generate_phi_node(
         &mut self,
         llvm_ir: &mut String,
         result_reg: &str,
         incoming_values: &[(Value, String)],
     ) {
-        // Generate phi node for variable updates in loops and control flow
+        // Select a value by predecessor block at a control-flow merge point
         let mut phi_str = format!("  %{} = phi double ", result_reg);
 
         for (i, (value, label)) in incoming_values.iter().enumerate() {
@@ -1075,6 +1980,15 @@ impl CodeGenerator {
         arguments: &[Value],
         is_println: bool,
     ) {
+        // A `{:q}` string is printed on its own by a helper that quotes and
+        // escapes it, as `{:?}` shows strings.
+        if let ("{:q}", [arg], false) = (format_string, arguments, is_println) {
+            let arg_ptr = self.string_operand_ptr(llvm_ir, arg);
+            self.use_runtime("aero_debug_str");
+            llvm_ir.push_str(&format!("  call void @aero_debug_str(i8* {})\n", arg_ptr));
+            return;
+        }
+
         // Convert Rust-style `{}` placeholders to `printf` specifiers from argument kinds.
         let processed_format = self.process_format_string_with_args(format_string, arguments);
 
@@ -1111,8 +2025,32 @@ impl CodeGenerator {
 
         // Generate printf call
         let mut printf_args = format!("i8* %{}", format_ptr);
-
-        for arg in arguments {
+        let specifiers = Self::placeholder_specifiers(format_string);
+
+        for (index, arg) in arguments.iter().enumerate() {
+            let specifier = specifiers.get(index).copied().flatten();
+            if specifier == Some('s') {
+                // Strings held at runtime are pointers packed into doubles.
+                let arg_ptr = self.string_operand_ptr(llvm_ir, arg);
+                printf_args.push_str(", i8* ");
+                printf_args.push_str(&arg_ptr);
+                continue;
+            }
+            if specifier == Some('c') {
+                // `%c` takes an int, while slots hold chars as doubles.
+                let operand = match arg {
+                    Value::ImmInt(n) => n.to_string(),
+                    _ => {
+                        let tmp = self.fresh_reg();
+                        let value = self.value_to_string(arg);
+                        self.emit_fptosi_sat(llvm_ir, &tmp, &value, "i32");
+                        format!("%{}", tmp)
+                    }
+                };
+                printf_args.push_str(", i32 ");
+                printf_args.push_str(&operand);
+                continue;
+            }
             match arg {
                 Value::ImmString(s) => {
                     let arg_ptr = self.emit_stack_string_literal(llvm_ir, s);
@@ -1135,19 +2073,40 @@ impl CodeGenerator {
         }
 
         // Call printf
-        llvm_ir.push_str(&format!("  call i32 @printf({})\n", printf_args));
+        self.use_runtime("printf");
+        llvm_ir.push_str(&format!("  call i32 (i8*, ...) @printf({})\n", printf_args));
     }
 
     fn escape_for_llvm(&self, input: &str) -> String {
         // Escape special characters for LLVM string literals
         input
             .replace("\\", "\\\\")
-            .replace("\"", "\\\"")
+            .replace("\"", "\\22")
             .replace("\n", "\\0A")
             .replace("\t", "\\09")
             .replace("\r", "\\0D")
     }
 
+    /// For each placeholder in `format_string`, the specifier of a `{:c}`
+    /// char or `{:s}` string placeholder, or `None` for a plain `{}`.
+    fn placeholder_specifiers(format_string: &str) -> Vec<Option<char>> {
+        let mut specifiers = Vec::new();
+        let mut rest = format_string;
+        while let Some(pos) = rest.find('{') {
+            rest = &rest[pos..];
+            if rest.starts_with("{}") {
+                specifiers.push(None);
+                rest = &rest[2..];
+            } else if rest.starts_with("{:c}") || rest.starts_with("{:s}") {
+                specifiers.push(rest[2..].chars().next());
+                rest = &rest[4..];
+            } else {
+                rest = &rest[1..];
+            }
+        }
+        specifiers
+    }
+
     fn process_format_string(&self, format_string: &str, arg_count: usize) -> String {
         // Keep legacy tests and helper calls by treating all placeholders as numeric.
         let numeric_args = vec![Value::ImmInt(0); arg_count];
@@ -1174,6 +2133,15 @@ impl CodeGenerator {
                     } else {
                         result.push_str("{}"); // Keep original if no corresponding argument
                     }
+                } else if placeholder_count < arguments.len()
+                    && let Some(specifier) = ['c', 's']
+                        .into_iter()
+                        .find(|spec| chars.clone().take(3).eq([':', *spec, '}']))
+                {
+                    chars.nth(2); // consume ':c}' or ':s}'
+                    result.push('%');
+                    result.push(specifier);
+                    placeholder_count += 1;
                 } else {
                     result.push(ch);
                 }
@@ -1216,29 +2184,445 @@ impl CodeGenerator {
         result
     }
 
-    fn emit_stack_string_literal(&mut self, llvm_ir: &mut String, text: &str) -> String {
-        let len = text.len() + 1; // +1 for null terminator
-        let const_reg = self.fresh_reg();
-        llvm_ir.push_str(&format!(
-            "  %{} = alloca [{}  x i8], align 1\n",
-            const_reg, len
-        ));
-        let escaped = self.escape_for_llvm(text);
-        llvm_ir.push_str(&format!(
-            "  store [{}  x i8] c\"{}\\00\", [{}  x i8]* %{}, align 1\n",
-            len, escaped, len, const_reg
-        ));
-        let ptr_reg = self.fresh_reg();
-        llvm_ir.push_str(&format!(
-            "  %{} = getelementptr inbounds [{}  x i8], [{}  x i8]* %{}, i64 0, i64 0\n",
-            ptr_reg, len, len, const_reg
-        ));
-        format!("%{}", ptr_reg)
+    /// Lower a string operand to an `i8*`: literals get stack storage, and
+    /// registers hold the pointer packed into a double.
+    fn string_operand_ptr(&mut self, llvm_ir: &mut String, value: &Value) -> String {
+        match value {
+            Value::ImmString(text) => self.emit_stack_string_literal(llvm_ir, text),
+            other => {
+                let bits = self.fresh_reg();
+                llvm_ir.push_str(&format!(
+                    "  %{} = bitcast double {} to i64\n",
+                    bits,
+                    self.value_to_string(other)
+                ));
+                let ptr = self.fresh_reg();
+                llvm_ir.push_str(&format!("  %{} = inttoptr i64 %{} to i8*\n", ptr, bits));
+                format!("%{}", ptr)
+            }
+        }
+    }
+
+    /// Compare two strings by contents: the lengths must match, and then
+    /// `memcmp` over that length must find no differing byte. When the
+    /// lengths differ `memcmp` is given a length of 0, so it never reads past
+    /// the shorter string.
+    fn generate_string_eq(
+        &mut self,
+        llvm_ir: &mut String,
+        result: &str,
+        left: &Value,
+        right: &Value,
+    ) {
+        self.use_runtime("strlen");
+        self.use_runtime("memcmp");
+
+        let left_ptr = self.string_operand_ptr(llvm_ir, left);
+        let right_ptr = self.string_operand_ptr(llvm_ir, right);
+        let left_len = self.fresh_reg();
+        let right_len = self.fresh_reg();
+        let same_len = self.fresh_reg();
+        let compared_len = self.fresh_reg();
+        let order = self.fresh_reg();
+        let same_bytes = self.fresh_reg();
+        let lines = [
+            format!("%{} = call i64 @strlen(i8* {})", left_len, left_ptr),
+            format!("%{} = call i64 @strlen(i8* {})", right_len, right_ptr),
+            format!("%{} = icmp eq i64 %{}, %{}", same_len, left_len, right_len),
+            format!(
+                "%{} = select i1 %{}, i64 %{}, i64 0",
+                compared_len, same_len, left_len
+            ),
+            format!(
+                "%{} = call i32 @memcmp(i8* {}, i8* {}, i64 %{})",
+                order, left_ptr, right_ptr, compared_len
+            ),
+            format!("%{} = icmp eq i32 %{}, 0", same_bytes, order),
+            format!("%{} = and i1 %{}, %{}", result, same_len, same_bytes),
+        ];
+        for line in lines {
+            llvm_ir.push_str(&format!("  {}\n", line));
+        }
+    }
+
+    /// Concatenate two strings into a fresh `malloc`ed buffer: the left
+    /// bytes, then the right bytes with their terminator. The result is the
+    /// buffer's pointer packed into a double, like any string register.
+    fn generate_string_concat(
+        &mut self,
+        llvm_ir: &mut String,
+        result: &str,
+        left: &Value,
+        right: &Value,
+    ) {
+        self.use_runtime("strlen");
+        self.use_runtime("malloc");
+        self.use_runtime("memcpy");
+
+        let left_ptr = self.string_operand_ptr(llvm_ir, left);
+        let right_ptr = self.string_operand_ptr(llvm_ir, right);
+        let left_len = self.fresh_reg();
+        let right_len = self.fresh_reg();
+        let right_size = self.fresh_reg();
+        let size = self.fresh_reg();
+        let buffer = self.fresh_reg();
+        let tail = self.fresh_reg();
+        let bits = self.fresh_reg();
+        let lines = [
+            format!("%{} = call i64 @strlen(i8* {})", left_len, left_ptr),
+            format!("%{} = call i64 @strlen(i8* {})", right_len, right_ptr),
+            format!("%{} = add i64 %{}, 1", right_size, right_len),
+            format!("%{} = add i64 %{}, %{}", size, left_len, right_size),
+            format!("%{} = call i8* @malloc(i64 %{})", buffer, size),
+            format!(
+                "call void @llvm.memcpy.p0i8.p0i8.i64(i8* %{}, i8* {}, i64 %{}, i1 false)",
+                buffer, left_ptr, left_len
+            ),
+            format!(
+                "%{} = getelementptr inbounds i8, i8* %{}, i64 %{}",
+                tail, buffer, left_len
+            ),
+            format!(
+                "call void @llvm.memcpy.p0i8.p0i8.i64(i8* %{}, i8* {}, i64 %{}, i1 false)",
+                tail, right_ptr, right_size
+            ),
+            format!("%{} = ptrtoint i8* %{} to i64", bits, buffer),
+            format!("%{} = bitcast i64 %{} to double", result, bits),
+        ];
+        for line in lines {
+            llvm_ir.push_str(&format!("  {}\n", line));
+        }
+    }
+
+    /// Measure a string with `strlen`, converting the count to a double like
+    /// any other integer register.
+    fn generate_string_len(&mut self, llvm_ir: &mut String, result: &str, source: &Value) {
+        self.use_runtime("strlen");
+
+        let ptr = self.string_operand_ptr(llvm_ir, source);
+        let len = self.fresh_reg();
+        llvm_ir.push_str(&format!("  %{} = call i64 @strlen(i8* {})\n", len, ptr));
+        llvm_ir.push_str(&format!("  %{} = sitofp i64 %{} to double\n", result, len));
+    }
+
+    /// Format `value` with a `printf` specifier into a heap string, sized by
+    /// a first `snprintf` that only measures.
+    fn generate_string_format(
+        &mut self,
+        llvm_ir: &mut String,
+        result: &str,
+        value: &Value,
+        specifier: char,
+    ) {
+        self.use_runtime("snprintf");
+        self.use_runtime("malloc");
+
+        let format = format!("%{}", specifier);
+        let global = format!("@.str.{:x}", md5::compute(&format));
+        self.intrinsics.insert(format!(
+            "{} = private unnamed_addr constant [3 x i8] c\"{}\\00\"",
+            global, format
+        ));
+        let format_ptr = format!(
+            "i8* getelementptr inbounds ([3 x i8], [3 x i8]* {}, i64 0, i64 0)",
+            global
+        );
+        let argument = match (specifier, value) {
+            // `%c` takes an int, while slots hold chars as doubles.
+            ('c', Value::ImmInt(n)) => format!("i32 {}", n),
+            ('c', _) => {
+                let tmp = self.fresh_reg();
+                let value = self.value_to_string(value);
+                self.emit_fptosi_sat(llvm_ir, &tmp, &value, "i32");
+                format!("i32 %{}", tmp)
+            }
+            // MSVC varargs require floating arguments in integer vararg slots.
+            _ if cfg!(windows) => format!(
+                "i64 {}",
+                self.value_to_win_printf_f64_bits_operand(llvm_ir, value)
+            ),
+            _ => format!("double {}", self.value_to_string(value)),
+        };
+
+        let len = self.fresh_reg();
+        let len64 = self.fresh_reg();
+        let size = self.fresh_reg();
+        let buffer = self.fresh_reg();
+        let written = self.fresh_reg();
+        let bits = self.fresh_reg();
+        let lines = [
+            format!(
+                "%{} = call i32 (i8*, i64, i8*, ...) @snprintf(i8* null, i64 0, {}, {})",
+                len, format_ptr, argument
+            ),
+            format!("%{} = sext i32 %{} to i64", len64, len),
+            format!("%{} = add i64 %{}, 1", size, len64),
+            format!("%{} = call i8* @malloc(i64 %{})", buffer, size),
+            format!(
+                "%{} = call i32 (i8*, i64, i8*, ...) @snprintf(i8* %{}, i64 %{}, {}, {})",
+                written, buffer, size, format_ptr, argument
+            ),
+            format!("%{} = ptrtoint i8* %{} to i64", bits, buffer),
+            format!("%{} = bitcast i64 %{} to double", result, bits),
+        ];
+        for line in lines {
+            llvm_ir.push_str(&format!("  {}\n", line));
+        }
+    }
+
+    /// Place `text` in a private global, named after its contents so that
+    /// equal literals share one, and pack its address into a double.
+    fn generate_string_constant(&mut self, llvm_ir: &mut String, result: &str, text: &str) {
+        let len = text.len() + 1; // +1 for null terminator
+        let global = format!("@.str.{:x}", md5::compute(text));
+        self.intrinsics.insert(format!(
+            "{} = private unnamed_addr constant [{} x i8] c\"{}\\00\"",
+            global,
+            len,
+            self.escape_for_llvm(text)
+        ));
+        let bits = self.fresh_reg();
+        llvm_ir.push_str(&format!(
+            "  %{} = ptrtoint i8* getelementptr inbounds ([{} x i8], [{} x i8]* {}, i64 0, i64 0) to i64\n",
+            bits, len, len, global
+        ));
+        llvm_ir.push_str(&format!(
+            "  %{} = bitcast i64 %{} to double\n",
+            result, bits
+        ));
+    }
+
+    /// `strtol`/`strtod` over the whole string. It is an error if no digits
+    /// were read, anything follows the number, or an integer doesn't fit
+    /// `i32`.
+    fn generate_parse_number(
+        &mut self,
+        llvm_ir: &mut String,
+        source: &Value,
+        float: bool,
+        value_ptr: &Value,
+        tag_ptr: &Value,
+    ) {
+        let text = self.string_operand_ptr(llvm_ir, source);
+        let end_slot = self.fresh_reg();
+        let number = self.fresh_reg();
+        let end = self.fresh_reg();
+        let read_any = self.fresh_reg();
+        let next_char = self.fresh_reg();
+        let at_nul = self.fresh_reg();
+        let complete = self.fresh_reg();
+        let mut lines = vec![format!("%{} = alloca i8*, align 8", end_slot)];
+        if float {
+            self.use_runtime("strtod");
+            lines.push(format!(
+                "%{} = call double @strtod(i8* {}, i8** %{})",
+                number, text, end_slot
+            ));
+        } else {
+            self.use_runtime("strtol");
+            lines.push(format!(
+                "%{} = call i64 @strtol(i8* {}, i8** %{}, i32 10)",
+                number, text, end_slot
+            ));
+        }
+        lines.extend([
+            format!("%{} = load i8*, i8** %{}, align 8", end, end_slot),
+            format!("%{} = icmp ne i8* %{}, {}", read_any, end, text),
+            format!("%{} = load i8, i8* %{}, align 1", next_char, end),
+            format!("%{} = icmp eq i8 %{}, 0", at_nul, next_char),
+            format!("%{} = and i1 %{}, %{}", complete, read_any, at_nul),
+        ]);
+        let (value, ok) = if float {
+            (format!("%{}", number), complete)
+        } else {
+            let above_min = self.fresh_reg();
+            let below_max = self.fresh_reg();
+            let in_range = self.fresh_reg();
+            let ok = self.fresh_reg();
+            let value = self.fresh_reg();
+            lines.extend([
+                format!("%{} = icmp sge i64 %{}, -2147483648", above_min, number),
+                format!("%{} = icmp sle i64 %{}, 2147483647", below_max, number),
+                format!("%{} = and i1 %{}, %{}", in_range, above_min, below_max),
+                format!("%{} = and i1 %{}, %{}", ok, complete, in_range),
+                format!("%{} = sitofp i64 %{} to double", value, number),
+            ]);
+            (format!("%{}", value), ok)
+        };
+        let slot = |ptr: &Value| match ptr {
+            Value::Reg(r) => format!("ptr{}", r),
+            _ => panic!("Expected register for parse result field"),
+        };
+        let tag = self.fresh_reg();
+        lines.extend([
+            format!("%{} = select i1 %{}, double 0.0, double 1.0", tag, ok),
+            format!(
+                "store double {}, double* %{}, align 8",
+                value,
+                slot(value_ptr)
+            ),
+            format!("store double %{}, double* %{}, align 8", tag, slot(tag_ptr)),
+        ]);
+        for line in lines {
+            llvm_ir.push_str(&format!("  {}\n", line));
+        }
+    }
+
+    fn emit_stack_string_literal(&mut self, llvm_ir: &mut String, text: &str) -> String {
+        let len = text.len() + 1; // +1 for null terminator
+        let const_reg = self.fresh_reg();
+        llvm_ir.push_str(&format!(
+            "  %{} = alloca [{}  x i8], align 1\n",
+            const_reg, len
+        ));
+        let escaped = self.escape_for_llvm(text);
+        llvm_ir.push_str(&format!(
+            "  store [{}  x i8] c\"{}\\00\", [{}  x i8]* %{}, align 1\n",
+            len, escaped, len, const_reg
+        ));
+        let ptr_reg = self.fresh_reg();
+        llvm_ir.push_str(&format!(
+            "  %{} = getelementptr inbounds [{}  x i8], [{}  x i8]* %{}, i64 0, i64 0\n",
+            ptr_reg, len, len, const_reg
+        ));
+        format!("%{}", ptr_reg)
+    }
+
+    fn generate_struct_type_declarations(
+        &self,
+        llvm_ir: &mut String,
+        ir_functions: &HashMap<String, Function>,
+    ) {
+        fn collect(instructions: &[Inst], field_counts: &mut BTreeMap<String, u32>) {
+            for inst in instructions {
+                match inst {
+                    Inst::AllocaStruct { struct_type, .. } => {
+                        field_counts.entry(struct_type.clone()).or_insert(0);
+                    }
+                    Inst::GetFieldPtr {
+                        struct_type,
+                        field_index,
+                        ..
+                    } => {
+                        let count = field_counts.entry(struct_type.clone()).or_insert(0);
+                        *count = (*count).max(field_index + 1);
+                    }
+                    Inst::StructCopy {
+                        struct_type,
+                        field_count,
+                        ..
+                    } => {
+                        let count = field_counts.entry(struct_type.clone()).or_insert(0);
+                        *count = (*count).max(*field_count);
+                    }
+                    Inst::HeapAlloc {
+                        struct_type: Some(struct_type),
+                        ..
+                    }
+                    | Inst::PtrToSlot {
+                        struct_type: Some(struct_type),
+                        ..
+                    }
+                    | Inst::SlotToPtr {
+                        struct_type: Some(struct_type),
+                        ..
+                    } if !struct_type.starts_with('[') => {
+                        // Array storage is a literal `[N x double]`, not a named struct.
+                        field_counts.entry(struct_type.clone()).or_insert(0);
+                    }
+                    Inst::FunctionDef { body, .. } => collect(body, field_counts),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut field_counts = BTreeMap::new();
+        for func in ir_functions.values() {
+            collect(&func.body, &mut field_counts);
+        }
+        if field_counts.is_empty() {
+            return;
+        }
+
+        // Struct fields use the same double slots as scalar variables, whatever
+        // their source type, and are only reached through typed GEPs on the
+        // named struct, so LLVM's layout keeps every field 8-byte aligned.
+        for (struct_type, count) in field_counts {
+            let fields = vec!["double"; count as usize].join(", ");
+            llvm_ir.push_str(&format!("%{} = type {{ {} }}\n", struct_type, fields));
+        }
+        llvm_ir.push('\n');
+    }
+
+    fn generate_global_definitions(
+        &mut self,
+        llvm_ir: &mut String,
+        ir_functions: &HashMap<String, Function>,
+    ) {
+        fn collect<'a>(instructions: &'a [Inst], globals: &mut BTreeMap<&'a str, &'a Inst>) {
+            for inst in instructions {
+                match inst {
+                    Inst::GlobalDef { name, .. } => {
+                        globals.insert(name, inst);
+                    }
+                    Inst::FunctionDef { body, .. } => collect(body, globals),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut globals = BTreeMap::new();
+        for func in ir_functions.values() {
+            collect(&func.body, &mut globals);
+        }
+        if globals.is_empty() {
+            return;
+        }
+
+        for inst in globals.into_values() {
+            let Inst::GlobalDef {
+                name,
+                values,
+                array_len,
+                mutable,
+            } = inst
+            else {
+                continue;
+            };
+            let linkage = if *mutable { "global" } else { "constant" };
+            let (global_type, initializer) = match array_len {
+                Some(len) => {
+                    let elements = values
+                        .iter()
+                        .map(|value| format!("double {}", self.value_to_string(value)))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    (format!("[{} x double]", len), format!("[{}]", elements))
+                }
+                None => (
+                    "double".to_string(),
+                    values
+                        .first()
+                        .map_or("0.0".to_string(), |value| self.value_to_string(value)),
+                ),
+            };
+            llvm_ir.push_str(&format!(
+                "@{} = {} {} {}, align 8\n",
+                name, linkage, global_type, initializer
+            ));
+            self.global_types.insert(name.clone(), global_type);
+        }
+        llvm_ir.push('\n');
     }
 
-    fn generate_printf_declaration(&mut self, llvm_ir: &mut String) {
-        // Generate printf declaration at module level
-        llvm_ir.push_str("declare i32 @printf(i8*, ...)\n\n");
+    /// LLVM pointer type for a heap or packed pointer.
+    fn pointee_pointer_type(struct_type: &Option<String>) -> String {
+        match struct_type {
+            // Array storage is named by its literal LLVM type.
+            Some(array) if array.starts_with('[') => format!("{}*", array),
+            Some(name) => format!("%{}*", name),
+            None => "double*".to_string(),
+        }
     }
 }
 
@@ -1278,6 +2662,7 @@ mod tests {
                     Inst::Add(Value::Reg(4), Value::Reg(2), Value::Reg(3)),
                     Inst::Return(Value::Reg(4)),
                 ],
+                no_inline: false,
             }],
             next_reg: 5,
             next_ptr: 2,
@@ -1301,6 +2686,76 @@ mod tests {
         assert!(llvm_ir.contains("entry:"));
     }
 
+    #[test]
+    fn pure_functions_are_readnone_and_functions_with_stores_are_not() {
+        let mut generator = CodeGenerator::new();
+
+        // fn square(x: i32) -> i32 { x * x }, marked `@inline(never)`
+        let square = Inst::FunctionDef {
+            name: "square".to_string(),
+            parameters: vec![("x".to_string(), "i32".to_string())],
+            return_type: Some("i32".to_string()),
+            body: vec![
+                Inst::Alloca(Value::Reg(0), "x".to_string()),
+                Inst::Load(Value::Reg(1), Value::Reg(0)),
+                Inst::Mul(Value::Reg(2), Value::Reg(1), Value::Reg(1)),
+                Inst::Return(Value::Reg(2)),
+            ],
+            no_inline: true,
+        };
+        // fn reset(p: &mut f64) { *p = 0.0; }
+        let reset = Inst::FunctionDef {
+            name: "reset".to_string(),
+            parameters: vec![("p".to_string(), "f64".to_string())],
+            return_type: None,
+            body: vec![
+                Inst::Alloca(Value::Reg(0), "p".to_string()),
+                Inst::Load(Value::Reg(1), Value::Reg(0)),
+                Inst::SlotToPtr {
+                    result: Value::Reg(1),
+                    value: Value::Reg(1),
+                    struct_type: None,
+                },
+                Inst::Store(Value::Reg(1), Value::ImmFloat(0.0)),
+            ],
+            no_inline: false,
+        };
+        // fn fails() { panic!() }
+        let fails = Inst::FunctionDef {
+            name: "fails".to_string(),
+            parameters: vec![],
+            return_type: None,
+            body: vec![
+                Inst::Call {
+                    function: "panic".to_string(),
+                    arguments: vec![Value::ImmString("boom".to_string())],
+                    result: None,
+                },
+                Inst::Unreachable,
+            ],
+            no_inline: false,
+        };
+
+        let mut functions = HashMap::new();
+        for (name, def) in [("square", square), ("reset", reset), ("fails", fails)] {
+            functions.insert(
+                name.to_string(),
+                Function {
+                    name: name.to_string(),
+                    body: vec![def],
+                    next_reg: 3,
+                    next_ptr: 1,
+                },
+            );
+        }
+
+        let llvm_ir = generator.generate_code(functions);
+
+        assert!(llvm_ir.contains("define i32 @square(i32 %x) noinline nounwind readnone {"));
+        assert!(llvm_ir.contains("define void @reset(double %p) nounwind {"));
+        assert!(llvm_ir.contains("define void @fails() {"));
+    }
+
     #[test]
     fn test_function_call_generation() {
         let mut generator = CodeGenerator::new();
@@ -1352,6 +2807,7 @@ mod tests {
                     Inst::Add(Value::Reg(4), Value::Reg(2), Value::Reg(3)),
                     Inst::Return(Value::Reg(4)),
                 ],
+                no_inline: false,
             }],
             next_reg: 5,
             next_ptr: 2,
@@ -1396,6 +2852,7 @@ mod tests {
                     format_string: "Hello, World!".to_string(),
                     arguments: vec![],
                 }],
+                no_inline: false,
             }],
             next_reg: 0,
             next_ptr: 0,
@@ -1410,7 +2867,7 @@ mod tests {
         assert!(llvm_ir.contains("define void @print_hello()"));
 
         // Check that print statement is generated with printf call
-        assert!(llvm_ir.contains("call i32 @printf"));
+        assert!(llvm_ir.contains("call i32 (i8*, ...) @printf"));
     }
 
     #[test]
@@ -1437,7 +2894,7 @@ mod tests {
         assert!(llvm_ir.contains("declare i32 @printf(i8*, ...)"));
 
         // Check that print call is generated
-        assert!(llvm_ir.contains("call i32 @printf"));
+        assert!(llvm_ir.contains("call i32 (i8*, ...) @printf"));
         assert!(llvm_ir.contains("Hello, World!"));
     }
 
@@ -1465,7 +2922,7 @@ mod tests {
         assert!(llvm_ir.contains("declare i32 @printf(i8*, ...)"));
 
         // Check that println call is generated with newline
-        assert!(llvm_ir.contains("call i32 @printf"));
+        assert!(llvm_ir.contains("call i32 (i8*, ...) @printf"));
         assert!(llvm_ir.contains("Hello, World!\\0A"));
     }
 
@@ -1520,10 +2977,33 @@ mod tests {
         let llvm_ir = generator.generate_code(functions);
 
         assert!(llvm_ir.contains("Hello, %s\\0A"));
-        assert!(llvm_ir.contains("call i32 @printf(i8*"));
+        assert!(llvm_ir.contains("call i32 (i8*, ...) @printf(i8*"));
         assert!(llvm_ir.contains(", i8* %"));
     }
 
+    #[test]
+    fn test_print_with_char_argument_uses_percent_c() {
+        let mut generator = CodeGenerator::new();
+
+        let function = Function {
+            name: "main".to_string(),
+            body: vec![Inst::Print {
+                format_string: "{:c} and {}".to_string(),
+                arguments: vec![Value::ImmInt('x' as i64), Value::Reg(0)],
+            }],
+            next_reg: 1,
+            next_ptr: 0,
+        };
+
+        let mut functions = HashMap::new();
+        functions.insert("main".to_string(), function);
+
+        let llvm_ir = generator.generate_code(functions);
+
+        assert!(llvm_ir.contains("%c and %g"));
+        assert!(llvm_ir.contains(", i32 120"));
+    }
+
     #[test]
     fn test_comparison_operations() {
         let mut generator = CodeGenerator::new();
@@ -1544,6 +3024,10 @@ mod tests {
                     left: Value::ImmFloat(3.14),
                     right: Value::ImmFloat(4.0),
                 },
+                Inst::Print {
+                    format_string: "{} {}".to_string(),
+                    arguments: vec![Value::Reg(0), Value::Reg(1)],
+                },
             ],
             next_reg: 2,
             next_ptr: 0,
@@ -1581,6 +3065,10 @@ mod tests {
                     result: Value::Reg(6),
                     operand: Value::Reg(7),
                 },
+                Inst::Print {
+                    format_string: "{} {} {}".to_string(),
+                    arguments: vec![Value::Reg(0), Value::Reg(3), Value::Reg(6)],
+                },
             ],
             next_reg: 8,
             next_ptr: 0,
@@ -1597,6 +3085,40 @@ mod tests {
         assert!(llvm_ir.contains("xor i1"));
     }
 
+    #[test]
+    fn test_logical_operations_spell_immediates_as_i1() {
+        let mut generator = CodeGenerator::new();
+        let function = Function {
+            name: "main".to_string(),
+            body: vec![
+                Inst::And {
+                    result: Value::Reg(0),
+                    left: Value::Reg(1),
+                    right: Value::ImmInt(1),
+                },
+                Inst::Or {
+                    result: Value::Reg(2),
+                    left: Value::ImmInt(0),
+                    right: Value::Reg(0),
+                },
+                Inst::Print {
+                    format_string: "{}".to_string(),
+                    arguments: vec![Value::Reg(2)],
+                },
+            ],
+            next_reg: 3,
+            next_ptr: 0,
+        };
+
+        let mut functions = HashMap::new();
+        functions.insert("main".to_string(), function);
+
+        let llvm_ir = generator.generate_code(functions);
+
+        assert!(llvm_ir.contains("%reg0 = and i1 %reg1, true"));
+        assert!(llvm_ir.contains("%reg2 = or i1 false, %reg0"));
+    }
+
     #[test]
     fn test_unary_operations() {
         let mut generator = CodeGenerator::new();
@@ -1604,10 +3126,13 @@ mod tests {
         // Create a function with unary operations
         let function = Function {
             name: "main".to_string(),
-            body: vec![Inst::Neg {
-                result: Value::Reg(0),
-                operand: Value::ImmFloat(5.0),
-            }],
+            body: vec![
+                Inst::Neg {
+                    result: Value::Reg(0),
+                    operand: Value::ImmFloat(5.0),
+                },
+                Inst::Return(Value::Reg(0)),
+            ],
             next_reg: 1,
             next_ptr: 0,
         };
@@ -1640,6 +3165,28 @@ mod tests {
         assert_eq!(result, "Too many: %g {} {}");
     }
 
+    #[test]
+    fn test_debug_string_print_calls_the_quoting_helper() {
+        let mut generator = CodeGenerator::new();
+        let function = Function {
+            name: "main".to_string(),
+            body: vec![Inst::Print {
+                format_string: "{:q}".to_string(),
+                arguments: vec![Value::Reg(0)],
+            }],
+            next_reg: 1,
+            next_ptr: 0,
+        };
+
+        let mut functions = HashMap::new();
+        functions.insert("main".to_string(), function);
+        let llvm_ir = generator.generate_code(functions);
+
+        assert!(llvm_ir.contains("call void @aero_debug_str(i8* "));
+        assert!(llvm_ir.contains("define private void @aero_debug_str(i8* %s)"));
+        assert!(llvm_ir.contains("declare i32 @putchar(i32)"));
+    }
+
     #[test]
     fn test_escape_for_llvm() {
         let generator = CodeGenerator::new();
@@ -1649,7 +3196,7 @@ mod tests {
         assert_eq!(result, "Hello\\0AWorld");
 
         let result = generator.escape_for_llvm("Quote: \"test\"");
-        assert_eq!(result, "Quote: \\\"test\\\"");
+        assert_eq!(result, "Quote: \\22test\\22");
 
         let result = generator.escape_for_llvm("Tab\tSeparated");
         assert_eq!(result, "Tab\\09Separated");
@@ -1748,7 +3295,7 @@ mod tests {
         let llvm_ir = generator.generate_code(functions);
 
         // Check that printf call is generated
-        assert!(llvm_ir.contains("call i32 @printf"));
+        assert!(llvm_ir.contains("call i32 (i8*, ...) @printf"));
         assert!(llvm_ir.contains("Hello, %g!")); // Format string should be processed
         assert!(llvm_ir.contains("getelementptr inbounds")); // String constant access
     }
@@ -1774,7 +3321,7 @@ mod tests {
         let llvm_ir = generator.generate_code(functions);
 
         // Check that printf call is generated with multiple arguments
-        assert!(llvm_ir.contains("call i32 @printf"));
+        assert!(llvm_ir.contains("call i32 (i8*, ...) @printf"));
         assert!(llvm_ir.contains("Values: %g, %g, %g"));
         if cfg!(windows) {
             assert!(llvm_ir.contains("i64 4607182418800017408")); // 1.0 bits
@@ -1846,8 +3393,8 @@ mod tests {
                 },
                 // Test I/O operations
                 Inst::Print {
-                    format_string: "Results: {}, {}, {}".to_string(),
-                    arguments: vec![Value::Reg(2), Value::Reg(3), Value::Reg(5)],
+                    format_string: "Results: {}, {}, {}, {}".to_string(),
+                    arguments: vec![Value::Reg(2), Value::Reg(3), Value::Reg(4), Value::Reg(5)],
                 },
                 Inst::Println {
                     format_string: "Test completed!".to_string(),
@@ -1871,8 +3418,8 @@ mod tests {
         assert!(llvm_ir.contains("or i1"));
         assert!(llvm_ir.contains("xor i1"));
         assert!(llvm_ir.contains("fsub double 0.0"));
-        assert!(llvm_ir.contains("call i32 @printf"));
-        assert!(llvm_ir.contains("Results: %g, %g, %g"));
+        assert!(llvm_ir.contains("call i32 (i8*, ...) @printf"));
+        assert!(llvm_ir.contains("Results: %g, %g, %g, %g"));
         assert!(llvm_ir.contains("Test completed!\\0A"));
     }
 
@@ -1917,6 +3464,10 @@ mod tests {
                     result: Value::Reg(5),
                     operand: Value::ImmFloat(42.0),
                 },
+                Inst::Print {
+                    format_string: "{} {} {} {}".to_string(),
+                    arguments: vec![Value::Reg(2), Value::Reg(3), Value::Reg(4), Value::Reg(5)],
+                },
             ],
             next_reg: 6,
             next_ptr: 0,
@@ -1971,9 +3522,83 @@ mod tests {
         let llvm_ir = generator.generate_code(functions);
 
         // Check that printf call is generated with just format string
-        assert!(llvm_ir.contains("call i32 @printf(i8*"));
+        assert!(llvm_ir.contains("call i32 (i8*, ...) @printf(i8*"));
         assert!(llvm_ir.contains("Hello, World!"));
     }
+
+    #[test]
+    fn test_struct_copy_mutation_does_not_affect_original() {
+        let mut generator = CodeGenerator::new();
+
+        // let a = Point { x: 1, y: 2 }; let b = a; b.x = 99; return a.x;
+        let point = || "Point".to_string();
+        let function = Function {
+            name: "main".to_string(),
+            body: vec![
+                Inst::AllocaStruct {
+                    result: Value::Reg(0),
+                    struct_type: point(),
+                },
+                Inst::GetFieldPtr {
+                    result: Value::Reg(1),
+                    base: Value::Reg(0),
+                    field_index: 0,
+                    struct_type: point(),
+                },
+                Inst::Store(Value::Reg(1), Value::ImmInt(1)),
+                Inst::GetFieldPtr {
+                    result: Value::Reg(2),
+                    base: Value::Reg(0),
+                    field_index: 1,
+                    struct_type: point(),
+                },
+                Inst::Store(Value::Reg(2), Value::ImmInt(2)),
+                Inst::StructCopy {
+                    result: Value::Reg(3),
+                    source: Value::Reg(0),
+                    struct_type: point(),
+                    field_count: 2,
+                },
+                Inst::GetFieldPtr {
+                    result: Value::Reg(4),
+                    base: Value::Reg(3),
+                    field_index: 0,
+                    struct_type: point(),
+                },
+                Inst::Store(Value::Reg(4), Value::ImmInt(99)),
+                Inst::GetFieldPtr {
+                    result: Value::Reg(5),
+                    base: Value::Reg(0),
+                    field_index: 0,
+                    struct_type: point(),
+                },
+                Inst::Load(Value::Reg(6), Value::Reg(5)),
+                Inst::Return(Value::Reg(6)),
+            ],
+            next_reg: 7,
+            next_ptr: 6,
+        };
+
+        let mut functions = HashMap::new();
+        functions.insert("main".to_string(), function);
+
+        let llvm_ir = generator.generate_code(functions);
+
+        assert!(llvm_ir.contains("%Point = type { double, double }"));
+        // The copy gets its own storage...
+        assert!(llvm_ir.contains("%ptr0 = alloca %Point"));
+        assert!(llvm_ir.contains("%ptr3 = alloca %Point"));
+        // ...both fields are copied from the original into it...
+        for field in 0..2 {
+            assert!(llvm_ir.contains(&format!("%Point* %ptr0, i32 0, i32 {}\n  %reg", field)));
+            assert!(llvm_ir.contains(&format!("%Point* %ptr3, i32 0, i32 {}", field)));
+        }
+        // ...and the write through the copy never touches the original.
+        assert!(llvm_ir.contains("%ptr4 = getelementptr inbounds %Point, %Point* %ptr3"));
+        assert!(llvm_ir.contains("store double 0x4058C00000000000, double* %ptr4"));
+        assert!(llvm_ir.contains("%ptr5 = getelementptr inbounds %Point, %Point* %ptr0"));
+        assert!(llvm_ir.contains("load double, double* %ptr5"));
+    }
 }
 
 #[test]
@@ -2065,6 +3690,164 @@ fn test_jump_and_label_generation() {
     assert!(llvm_ir.contains("target_label:"));
 }
 
+#[test]
+fn test_panic_helper_is_defined_once_per_module() {
+    let panic = |message: &str| Inst::Call {
+        function: "panic".to_string(),
+        arguments: vec![Value::ImmString(message.to_string())],
+        result: None,
+    };
+    let function = Function {
+        name: "main".to_string(),
+        body: vec![panic("first"), panic("second"), Inst::Unreachable],
+        next_reg: 0,
+        next_ptr: 0,
+    };
+    let mut functions = HashMap::new();
+    functions.insert("main".to_string(), function);
+
+    let llvm_ir = CodeGenerator::with_panic_strategy(PanicStrategy::Exit).generate_code(functions);
+
+    assert_eq!(
+        llvm_ir.matches("define private void @aero_panic").count(),
+        1
+    );
+    assert_eq!(llvm_ir.matches("call void @aero_panic").count(), 2);
+    assert_eq!(llvm_ir.matches("declare i64 @write").count(), 1);
+    assert!(llvm_ir.contains("declare void @exit(i32)\n"));
+    assert!(!llvm_ir.contains("@abort"));
+}
+
+#[test]
+fn test_unused_register_definitions_are_not_emitted() {
+    let mut generator = CodeGenerator::new();
+
+    let function = Function {
+        name: "test_prune".to_string(),
+        body: vec![
+            Inst::Alloca(Value::Reg(0), "x".to_string()),
+            Inst::FAdd(Value::Reg(1), Value::ImmFloat(1.0), Value::ImmFloat(2.0)),
+            Inst::Store(Value::Reg(0), Value::ImmFloat(3.0)),
+            Inst::Return(Value::ImmInt(0)),
+        ],
+        next_reg: 2,
+        next_ptr: 1,
+    };
+
+    let mut functions = HashMap::new();
+    functions.insert("test_prune".to_string(), function);
+
+    let llvm_ir = generator.generate_code(functions);
+
+    assert!(!llvm_ir.contains("fadd double"), "{}", llvm_ir);
+    assert!(llvm_ir.contains("store double"), "{}", llvm_ir);
+}
+
+#[test]
+fn test_unreachable_generation() {
+    let mut generator = CodeGenerator::new();
+
+    let function = Function {
+        name: "test_unreachable".to_string(),
+        body: vec![
+            Inst::Jump("dead".to_string()),
+            Inst::Label("dead".to_string()),
+            Inst::Unreachable,
+        ],
+        next_reg: 0,
+        next_ptr: 0,
+    };
+
+    let mut functions = HashMap::new();
+    functions.insert("test_unreachable".to_string(), function);
+
+    let llvm_ir = generator.generate_code(functions);
+
+    assert!(llvm_ir.contains("dead:\n  unreachable\n"));
+}
+
+#[test]
+fn test_fptosi_uses_saturating_intrinsic() {
+    let mut generator = CodeGenerator::new();
+
+    let function = Function {
+        name: "test_fptosi".to_string(),
+        body: vec![
+            Inst::FPToSI(Value::Reg(1), Value::Reg(0)),
+            Inst::Return(Value::Reg(1)),
+        ],
+        next_reg: 0,
+        next_ptr: 0,
+    };
+
+    let mut functions = HashMap::new();
+    functions.insert("test_fptosi".to_string(), function);
+
+    let llvm_ir = generator.generate_code(functions);
+
+    assert!(llvm_ir.contains("  %reg1 = call i64 @llvm.fptosi.sat.i64.f64(double %reg0)\n"));
+    assert!(llvm_ir.contains("declare i64 @llvm.fptosi.sat.i64.f64(double)\n"));
+    assert!(!llvm_ir.contains("fptosi double"));
+}
+
+#[test]
+fn test_float_to_int_conversion_saturates_and_maps_nan_to_zero() {
+    let returned = |value: f64| {
+        let function = Function {
+            name: "main".to_string(),
+            body: vec![Inst::Return(Value::ImmFloat(value))],
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        let mut functions = HashMap::new();
+        functions.insert("main".to_string(), function);
+        CodeGenerator::new().generate_code(functions)
+    };
+
+    assert!(returned(1e30).contains("ret i32 2147483647\n"));
+    assert!(returned(-1e30).contains("ret i32 -2147483648\n"));
+    assert!(returned(f64::NAN).contains("ret i32 0\n"));
+}
+
+#[test]
+fn test_phi_generation() {
+    let mut generator = CodeGenerator::new();
+
+    let function = Function {
+        name: "test_phi".to_string(),
+        body: vec![
+            Inst::Branch {
+                condition: Value::Reg(0),
+                true_label: "then".to_string(),
+                false_label: "else".to_string(),
+            },
+            Inst::Label("then".to_string()),
+            Inst::Jump("end".to_string()),
+            Inst::Label("else".to_string()),
+            Inst::Jump("end".to_string()),
+            Inst::Label("end".to_string()),
+            Inst::Phi {
+                result: Value::Reg(1),
+                incoming: vec![
+                    (Value::ImmInt(1), "then".to_string()),
+                    (Value::ImmInt(2), "else".to_string()),
+                ],
+            },
+        ],
+        next_reg: 0,
+        next_ptr: 0,
+    };
+
+    let mut functions = HashMap::new();
+    functions.insert("test_phi".to_string(), function);
+
+    let llvm_ir = generator.generate_code(functions);
+
+    assert!(llvm_ir.contains(
+        "end:\n  %reg1 = phi double [ 0x3FF0000000000000, %then ], [ 0x4000000000000000, %else ]\n"
+    ));
+}
+
 #[test]
 fn test_comparison_operations() {
     let mut generator = CodeGenerator::new();
@@ -2085,6 +3868,10 @@ fn test_comparison_operations() {
                 left: Value::ImmFloat(std::f64::consts::PI),
                 right: Value::ImmFloat(std::f64::consts::E),
             },
+            Inst::Print {
+                format_string: "{}".to_string(),
+                arguments: vec![Value::Reg(1)],
+            },
             Inst::Return(Value::Reg(0)),
         ],
         next_reg: 2,
@@ -2123,6 +3910,10 @@ fn test_logical_operations() {
                 result: Value::Reg(6),
                 operand: Value::Reg(7),
             },
+            Inst::Print {
+                format_string: "{} {}".to_string(),
+                arguments: vec![Value::Reg(3), Value::Reg(6)],
+            },
             Inst::Return(Value::Reg(0)),
         ],
         next_reg: 8,
@@ -119,10 +119,11 @@ impl Type {
     pub fn name(&self) -> &str {
         match self {
             Type::Named(name) => name,
-            Type::Array(_, _) => "array",
+            Type::Array(_, _) | Type::ConstArray(_, _) => "array",
             Type::Tuple(_) => "tuple",
             Type::Reference(_, _) => "ref",
             Type::Generic(name, _) => name,
+            Type::TraitObject(name) | Type::ImplTrait(name) => name,
         }
     }
 
@@ -240,6 +240,7 @@ fn format_type(ty: &Type) -> String {
     match ty {
         Type::Named(name) => name.clone(),
         Type::Array(inner, size) => format!("[{}; {}]", format_type(inner), size),
+        Type::ConstArray(inner, size) => format!("[{}; {}]", format_type(inner), size),
         Type::Tuple(items) => {
             let inner = items.iter().map(format_type).collect::<Vec<_>>().join(", ");
             format!("({})", inner)
@@ -255,6 +256,8 @@ fn format_type(ty: &Type) -> String {
             let rendered_args = args.iter().map(format_type).collect::<Vec<_>>().join(", ");
             format!("{}<{}>", name, rendered_args)
         }
+        Type::TraitObject(name) => format!("dyn {}", name),
+        Type::ImplTrait(name) => format!("impl {}", name),
     }
 }
 
@@ -1,5 +1,6 @@
 use crate::errors::{
-    CompilerError, CompilerErrors, EnhancedError, ErrorContext, ErrorSuggestion, SourceLocation,
+    ColorChoice, CompilerError, CompilerErrors, EnhancedError, ErrorContext, ErrorSuggestion,
+    SourceLocation, render, render_diagnostics,
 };
 
 #[cfg(test)]
@@ -382,4 +383,96 @@ mod tests {
         let result = errors.into_result("success");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_max_errors_caps_reported_diagnostics() {
+        let source = "let = 1;\n".repeat(10);
+        let tokens = crate::lexer::tokenize_with_locations(&source, None);
+        let err = crate::parser::parse_with_locations(tokens).unwrap_err();
+        let diagnostics = err.diagnostics();
+        assert_eq!(diagnostics.len(), 10);
+
+        let rendered = render_diagnostics(&diagnostics, 3, false);
+        let reported = rendered
+            .lines()
+            .filter(|line| !line.starts_with("error:") && !line.starts_with("note:"))
+            .count();
+        assert_eq!(reported, 3);
+        assert!(rendered.contains("error: aborting due to 10 previous errors"));
+        assert!(rendered.contains("note: 7 errors not shown (limit set by --max-errors=3)"));
+    }
+
+    #[test]
+    fn test_diagnostic_summary_distinguishes_warnings() {
+        let warning = CompilerError::UnreachableCode {
+            location: SourceLocation::new(2, 1),
+        };
+        let error = CompilerError::undefined_variable("x", SourceLocation::new(3, 5));
+        let rendered = render_diagnostics(&[&warning, &error], 20, false);
+        assert!(rendered.contains("warning: 1 warning emitted"));
+        assert!(rendered.contains("error: aborting due to 1 previous error\n"));
+        assert!(!rendered.contains("not shown"));
+
+        let warnings_only = render_diagnostics(&[&warning], 20, false);
+        assert!(!warnings_only.contains("aborting"));
+    }
+
+    #[test]
+    fn test_render_underlines_type_error_span() {
+        let source = "let a = 1;\nlet x: int = \"hi\";\n";
+        let tokens = crate::lexer::tokenize_with_locations(source, None);
+        let literal = tokens
+            .iter()
+            .find(|t| matches!(t.token, crate::lexer::Token::StringLiteral(_)))
+            .unwrap();
+        let error = CompilerError::type_mismatch("int", "string", literal.location.clone());
+
+        let rendered = render(&error, source, false);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(
+            lines[0],
+            "Error at 2:14: Type mismatch, expected int, got string"
+        );
+        assert_eq!(lines[1], "  |");
+        assert_eq!(lines[2], "2 | let x: int = \"hi\";");
+        assert_eq!(lines[3], "  |              ^^^^");
+    }
+
+    #[test]
+    fn test_render_without_span_marks_column() {
+        let source = "fn main() {\n    y = 1;\n}\n";
+        let error = CompilerError::undefined_variable("y", SourceLocation::new(2, 5));
+        let rendered = render(&error, source, false);
+        assert!(rendered.ends_with("2 |     y = 1;\n  |     ^\n"));
+
+        let unknown = CompilerError::undefined_variable("y", SourceLocation::unknown());
+        assert_eq!(render(&unknown, source, false), format!("{}\n", unknown));
+    }
+
+    #[test]
+    fn test_color_choice_controls_escape_sequences() {
+        assert_eq!(ColorChoice::parse("never"), Some(ColorChoice::Never));
+        assert_eq!(ColorChoice::parse("always"), Some(ColorChoice::Always));
+        assert_eq!(ColorChoice::parse("sometimes"), None);
+
+        let source = "fn main() {\n    y = 1;\n}\n";
+        let error = CompilerError::undefined_variable("y", SourceLocation::new(2, 5));
+        let warning = CompilerError::UnreachableCode {
+            location: SourceLocation::new(2, 5),
+        };
+
+        let plain = render(&error, source, ColorChoice::Never.enabled());
+        assert!(!plain.contains('\x1b'));
+        let plain_summary =
+            render_diagnostics(&[&warning, &error], 20, ColorChoice::Never.enabled());
+        assert!(!plain_summary.contains('\x1b'));
+
+        let colored = render(&error, source, ColorChoice::Always.enabled());
+        assert!(colored.starts_with("\x1b[1;31mError at 2:5"));
+        assert!(colored.ends_with("  |     \x1b[1m^\x1b[0m\n"));
+        let colored_summary =
+            render_diagnostics(&[&warning, &error], 20, ColorChoice::Always.enabled());
+        assert!(colored_summary.contains("\x1b[1;33mWarning at 2:5"));
+        assert!(colored_summary.contains("\x1b[1;31merror\x1b[0m: aborting"));
+    }
 }
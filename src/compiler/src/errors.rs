@@ -1,11 +1,19 @@
 use std::fmt;
 
+/// Byte range `start..end` into the source text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
 /// Represents a location in source code
 #[derive(Debug, Clone, PartialEq)]
 pub struct SourceLocation {
     pub line: usize,
     pub column: usize,
     pub filename: Option<String>,
+    pub span: Option<Span>,
 }
 
 impl SourceLocation {
@@ -14,6 +22,7 @@ impl SourceLocation {
             line,
             column,
             filename: None,
+            span: None,
         }
     }
 
@@ -22,6 +31,7 @@ impl SourceLocation {
             line,
             column,
             filename: Some(filename),
+            span: None,
         }
     }
 
@@ -30,8 +40,14 @@ impl SourceLocation {
             line: 0,
             column: 0,
             filename: None,
+            span: None,
         }
     }
+
+    pub fn with_span(mut self, start: usize, end: usize) -> Self {
+        self.span = Some(Span { start, end });
+        self
+    }
 }
 
 impl fmt::Display for SourceLocation {
@@ -43,6 +59,10 @@ impl fmt::Display for SourceLocation {
     }
 }
 
+/// A single reported problem. Compilation stages report `CompilerError`s;
+/// `CompilerError::diagnostics` flattens them into individual diagnostics.
+pub type Diagnostic = CompilerError;
+
 /// Suggestion for fixing an error
 #[derive(Debug, Clone)]
 pub struct ErrorSuggestion {
@@ -264,6 +284,9 @@ pub enum CompilerError {
     UnreachableCode {
         location: SourceLocation,
     },
+    InfiniteLoop {
+        location: SourceLocation,
+    },
     InvalidConditionType {
         expected: String,
         actual: String,
@@ -280,6 +303,13 @@ pub enum CompilerError {
         location: SourceLocation,
         previous_location: Option<SourceLocation>,
     },
+    ShadowedBinding {
+        name: String,
+        previous_type: String,
+        new_type: String,
+        location: SourceLocation,
+        previous_location: Option<SourceLocation>,
+    },
     ImmutableAssignment {
         name: String,
         location: SourceLocation,
@@ -331,6 +361,12 @@ pub enum CompilerError {
         message: String,
         location: SourceLocation,
     },
+    /// A semantic analyzer error, located at the start of the top-level
+    /// item it was found in
+    SemanticError {
+        message: String,
+        location: SourceLocation,
+    },
 
     /// Multiple errors collected during parsing (error recovery)
     MultiError {
@@ -447,6 +483,9 @@ impl fmt::Display for CompilerError {
             CompilerError::UnreachableCode { location } => {
                 write!(f, "Warning at {}: Unreachable code detected", location)
             }
+            CompilerError::InfiniteLoop { location } => {
+                write!(f, "Warning at {}: this loop never terminates", location)
+            }
             CompilerError::InvalidConditionType {
                 expected,
                 actual,
@@ -473,6 +512,23 @@ impl fmt::Display for CompilerError {
                 ),
                 None => write!(f, "Error at {}: Variable '{}' redefined", location, name),
             },
+            CompilerError::ShadowedBinding {
+                name,
+                previous_type,
+                new_type,
+                location,
+                previous_location,
+            } => {
+                write!(
+                    f,
+                    "Warning at {}: Variable '{}' of type {} shadows a binding of type {}",
+                    location, name, new_type, previous_type
+                )?;
+                match previous_location {
+                    Some(prev) => write!(f, " (previously declared at {})", prev),
+                    None => Ok(()),
+                }
+            }
             CompilerError::ImmutableAssignment { name, location } => {
                 write!(
                     f,
@@ -562,6 +618,9 @@ impl fmt::Display for CompilerError {
             CompilerError::ScopeError { message, location } => {
                 write!(f, "Error at {}: {}", location, message)
             }
+            CompilerError::SemanticError { message, location } => {
+                write!(f, "Semantic Analysis Error at {}: {}", location, message)
+            }
             CompilerError::MultiError { errors } => {
                 for (i, err) in errors.iter().enumerate() {
                     if i > 0 {
@@ -577,6 +636,255 @@ impl fmt::Display for CompilerError {
 
 impl std::error::Error for CompilerError {}
 
+impl CompilerError {
+    /// Diagnostics that are reported but never abort compilation.
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            CompilerError::UnreachableCode { .. }
+                | CompilerError::InfiniteLoop { .. }
+                | CompilerError::ShadowedBinding { .. }
+        )
+    }
+
+    /// The individual diagnostics carried by this error, flattening `MultiError`.
+    pub fn diagnostics(&self) -> Vec<&CompilerError> {
+        match self {
+            CompilerError::MultiError { errors } => {
+                errors.iter().flat_map(|err| err.diagnostics()).collect()
+            }
+            other => vec![other],
+        }
+    }
+
+    /// Like `diagnostics`, but takes ownership of the individual diagnostics.
+    #[allow(dead_code)] // Used by the library's `phases`, which the binary does not build.
+    pub fn into_diagnostics(self) -> Vec<CompilerError> {
+        match self {
+            CompilerError::MultiError { errors } => errors
+                .into_iter()
+                .flat_map(CompilerError::into_diagnostics)
+                .collect(),
+            other => vec![other],
+        }
+    }
+}
+
+impl CompilerError {
+    /// Where the diagnostic points, if it points anywhere.
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            CompilerError::UnexpectedCharacter { location, .. }
+            | CompilerError::UnterminatedString { location }
+            | CompilerError::InvalidNumber { location, .. }
+            | CompilerError::UnexpectedToken { location, .. }
+            | CompilerError::UnexpectedEndOfInput { location, .. }
+            | CompilerError::InvalidSyntax { location, .. }
+            | CompilerError::FunctionRedefinition { location, .. }
+            | CompilerError::UndefinedFunction { location, .. }
+            | CompilerError::ArityMismatch { location, .. }
+            | CompilerError::ParameterTypeMismatch { location, .. }
+            | CompilerError::ReturnTypeMismatch { location, .. }
+            | CompilerError::BreakOutsideLoop { location }
+            | CompilerError::ContinueOutsideLoop { location }
+            | CompilerError::UnreachableCode { location }
+            | CompilerError::InfiniteLoop { location }
+            | CompilerError::InvalidConditionType { location, .. }
+            | CompilerError::UndefinedVariable { location, .. }
+            | CompilerError::VariableRedefinition { location, .. }
+            | CompilerError::ShadowedBinding { location, .. }
+            | CompilerError::ImmutableAssignment { location, .. }
+            | CompilerError::UninitializedVariable { location, .. }
+            | CompilerError::TypeMismatch { location, .. }
+            | CompilerError::IncompatibleTypes { location, .. }
+            | CompilerError::InvalidTypeAnnotation { location, .. }
+            | CompilerError::InvalidFormatString { location, .. }
+            | CompilerError::FormatArgumentMismatch { location, .. }
+            | CompilerError::InvalidFormatSpecifier { location, .. }
+            | CompilerError::InvalidOperation { location, .. }
+            | CompilerError::ScopeError { location, .. }
+            | CompilerError::SemanticError { location, .. } => Some(location),
+            CompilerError::MultiError { .. } => None,
+        }
+    }
+}
+
+/// Whether diagnostics are colored, as chosen by `--color`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color only when stderr is a terminal and `NO_COLOR` is unset.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(input: &str) -> Option<Self> {
+        match input {
+            "auto" => Some(Self::Auto),
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    /// Whether diagnostics written to stderr should carry ANSI escapes.
+    pub fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::io::IsTerminal::is_terminal(&std::io::stderr())
+            }
+        }
+    }
+}
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// `text` wrapped in `style`, or unchanged when color is off.
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("{}{}{}", style, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+/// The escape a diagnostic's message line is drawn in.
+fn severity_style(diagnostic: &CompilerError) -> &'static str {
+    if diagnostic.is_warning() { YELLOW } else { RED }
+}
+
+/// Render a diagnostic rustc-style: the message, then the offending source
+/// line with `^` under the span. Locations without a span get a single caret
+/// at their column; unknown locations render the message alone. With `color`,
+/// the message is red (yellow for warnings) and the carets bold.
+pub fn render(diagnostic: &CompilerError, source: &str, color: bool) -> String {
+    let message = diagnostic.to_string();
+    let mut output = format!("{}\n", paint(&message, severity_style(diagnostic), color));
+    let Some(span) = diagnostic
+        .location()
+        .and_then(|location| location_span(location, source))
+    else {
+        return output;
+    };
+
+    let start = span.start.min(source.len());
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let line_number = source[..start].matches('\n').count() + 1;
+    let line = source[line_start..line_end].trim_end_matches('\r');
+
+    // Keep tabs in the padding so the caret lines up however tabs are shown.
+    let padding: String = source[line_start..start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let end = span.end.clamp(start, line_end);
+    let width = source[start..end].chars().count().max(1);
+
+    let gutter = " ".repeat(line_number.to_string().len());
+    output.push_str(&format!("{} |\n", gutter));
+    output.push_str(&format!("{} | {}\n", line_number, line));
+    let carets = paint(&"^".repeat(width), BOLD, color);
+    output.push_str(&format!("{} | {}{}\n", gutter, padding, carets));
+    output
+}
+
+/// The byte span a location covers, falling back to the single character at
+/// its line and column.
+fn location_span(location: &SourceLocation, source: &str) -> Option<Span> {
+    if let Some(span) = location.span {
+        return Some(span);
+    }
+    if location.line == 0 || location.column == 0 {
+        return None;
+    }
+    let line_start = if location.line == 1 {
+        0
+    } else {
+        source.match_indices('\n').nth(location.line - 2)?.0 + 1
+    };
+    let start = source[line_start..]
+        .char_indices()
+        .nth(location.column - 1)
+        .map_or(source.len(), |(i, _)| line_start + i);
+    let end = source[start..]
+        .chars()
+        .next()
+        .map_or(start, |c| start + c.len_utf8());
+    Some(Span { start, end })
+}
+
+/// Errors reported before the rest are suppressed, unless `--max-errors` says otherwise.
+pub const DEFAULT_MAX_ERRORS: usize = 20;
+
+/// Render diagnostics one per line, reporting at most `max_errors` errors,
+/// followed by a summary of warnings, errors, and suppressed errors. With
+/// `color`, errors are red and warnings yellow.
+pub fn render_diagnostics(
+    diagnostics: &[&CompilerError],
+    max_errors: usize,
+    color: bool,
+) -> String {
+    let mut output = String::new();
+    let mut error_count = 0;
+    let mut warning_count = 0;
+
+    for diagnostic in diagnostics {
+        if diagnostic.is_warning() {
+            warning_count += 1;
+        } else {
+            error_count += 1;
+            if error_count > max_errors {
+                continue;
+            }
+        }
+        let message = diagnostic.to_string();
+        output.push_str(&format!(
+            "{}\n",
+            paint(&message, severity_style(diagnostic), color)
+        ));
+    }
+
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    if warning_count > 0 {
+        output.push_str(&format!(
+            "{}: {} warning{} emitted\n",
+            paint("warning", YELLOW, color),
+            warning_count,
+            plural(warning_count)
+        ));
+    }
+    if error_count > 0 {
+        output.push_str(&format!(
+            "{}: aborting due to {} previous error{}\n",
+            paint("error", RED, color),
+            error_count,
+            plural(error_count)
+        ));
+    }
+    let suppressed = error_count.saturating_sub(max_errors);
+    if suppressed > 0 {
+        output.push_str(&format!(
+            "note: {} error{} not shown (limit set by --max-errors={})\n",
+            suppressed,
+            plural(suppressed),
+            max_errors
+        ));
+    }
+
+    output
+}
+
 /// Result type for compiler operations
 #[allow(clippy::result_large_err)]
 pub type CompilerResult<T> = Result<T, CompilerError>;
@@ -0,0 +1,129 @@
+// src/compiler/src/hover.rs
+
+//! Position-to-type lookup for editor hovers.
+//!
+//! The parser keeps the token range of every expression; the innermost one
+//! covering the cursor is then parsed again wrapped in a type probe, and
+//! semantic analysis reports the type it infers for it.
+
+use crate::ast::Expression;
+use crate::lexer::{LocatedToken, tokenize_with_locations};
+use crate::parser::Parser;
+use crate::semantic_analyzer::SemanticAnalyzer;
+use crate::types::Ty;
+use std::ops::Range;
+
+/// The syntactic category of the expression under the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Literal,
+    Identifier,
+    Binary,
+    Comparison,
+    Logical,
+    Unary,
+    Call,
+    MethodCall,
+    FieldAccess,
+    Index,
+    Other,
+}
+
+impl NodeKind {
+    pub fn of(expr: &Expression) -> Self {
+        match expr {
+            Expression::IntegerLiteral(_)
+            | Expression::FloatLiteral(_)
+            | Expression::BooleanLiteral(_)
+            | Expression::StringLiteral(_)
+            | Expression::CharLiteral(_) => NodeKind::Literal,
+            Expression::Identifier(_) => NodeKind::Identifier,
+            Expression::Binary { .. } => NodeKind::Binary,
+            Expression::Comparison { .. } => NodeKind::Comparison,
+            Expression::Logical { .. } => NodeKind::Logical,
+            Expression::Unary { .. } => NodeKind::Unary,
+            Expression::FunctionCall { .. } => NodeKind::Call,
+            Expression::MethodCall { .. } => NodeKind::MethodCall,
+            Expression::FieldAccess { .. } => NodeKind::FieldAccess,
+            Expression::IndexAccess { .. } | Expression::TupleIndex { .. } => NodeKind::Index,
+            _ => NodeKind::Other,
+        }
+    }
+}
+
+/// The innermost expression covering the 1-based `line`/`column` of
+/// `source`, and its inferred type. `None` when no expression covers the
+/// position, the source doesn't parse, or analysis stops before reaching it.
+pub fn resolve_at(source: &str, line: usize, column: usize) -> Option<(NodeKind, Ty)> {
+    let offset = byte_offset(source, line, column)?;
+    let tokens = tokenize_with_locations(source, None);
+
+    let mut parser = Parser::new(tokens.clone()).with_expression_spans();
+    parser.parse().ok()?;
+    let innermost = parser
+        .expression_spans()
+        .iter()
+        .filter_map(|span| Some((span, byte_range(&tokens, &span.tokens)?)))
+        .filter(|(_, bytes)| bytes.contains(&offset))
+        .min_by_key(|(_, bytes)| bytes.len())
+        .map(|(span, _)| span)?;
+    let kind = NodeKind::of(&innermost.expression);
+
+    let ast = Parser::new(tokens)
+        .with_type_probe(innermost.tokens.clone())
+        .parse()
+        .ok()?;
+    let mut analyzer = SemanticAnalyzer::new();
+    // Errors after the probe don't change its type.
+    let _ = analyzer.analyze(ast);
+    analyzer.probed_type().map(|ty| (kind, ty))
+}
+
+fn byte_offset(source: &str, line: usize, column: usize) -> Option<usize> {
+    let start = if line == 1 {
+        0
+    } else {
+        source.match_indices('\n').nth(line.checked_sub(2)?)?.0 + 1
+    };
+    source[start..]
+        .char_indices()
+        .nth(column.checked_sub(1)?)
+        .map(|(i, _)| start + i)
+}
+
+fn byte_range(tokens: &[LocatedToken], range: &Range<usize>) -> Option<Range<usize>> {
+    let first = tokens.get(range.start)?.location.span?;
+    let last = tokens.get(range.end.checked_sub(1)?)?.location.span?;
+    Some(first.start..last.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "fn main() {
+    let count = 3;
+    let scale = 2.5;
+    let total = count + scale;
+}";
+
+    #[test]
+    fn hover_reports_variable_and_binary_result_types() {
+        // `count` in `count + scale`
+        assert_eq!(
+            resolve_at(SOURCE, 4, 17),
+            Some((NodeKind::Identifier, Ty::Int))
+        );
+        assert_eq!(
+            resolve_at(SOURCE, 4, 27),
+            Some((NodeKind::Identifier, Ty::Float))
+        );
+        // The `+` belongs only to the binary expression.
+        assert_eq!(
+            resolve_at(SOURCE, 4, 23),
+            Some((NodeKind::Binary, Ty::Float))
+        );
+        // Keywords and names being bound aren't expressions.
+        assert_eq!(resolve_at(SOURCE, 4, 5), None);
+    }
+}
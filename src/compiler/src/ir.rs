@@ -35,6 +35,7 @@ pub enum Inst {
     Store(Value, Value),       // pointer_reg, value_to_store
     Load(Value, Value),        // result_reg, pointer_reg
     Return(Value),             // value to return
+    Unreachable,               // control never reaches this point (e.g. after a panic)
     SIToFP(Value, Value),      // result_reg, int_value (signed integer to floating point)
     FPToSI(Value, Value),      // result_reg, float_value (floating point to signed integer)
 
@@ -44,6 +45,7 @@ pub enum Inst {
         parameters: Vec<(String, String)>, // (name, type)
         return_type: Option<String>,
         body: Vec<Inst>,
+        no_inline: bool, // `@inline(never)`: emitted with the `noinline` attribute
     },
     Call {
         function: String,
@@ -59,6 +61,10 @@ pub enum Inst {
     },
     Jump(String),  // Unconditional jump to label
     Label(String), // Label for jumps and branches
+    Phi {
+        result: Value,
+        incoming: Vec<(Value, String)>, // (value, predecessor block label)
+    },
 
     // Comparison operations
     ICmp {
@@ -106,6 +112,19 @@ pub enum Inst {
         operand: Value,
     },
 
+    // Numeric builtins (`min`, `max`, `abs`)
+    Select {
+        result: Value,
+        condition: Value, // result of an `ICmp`/`FCmp`
+        if_true: Value,
+        if_false: Value,
+    },
+    FloatIntrinsic {
+        result: Value,
+        intrinsic: String, // e.g. "llvm.fabs.f64", taking and returning doubles
+        arguments: Vec<Value>,
+    },
+
     // Aggregate operations (Phase 4)
     AllocaArray {
         result: Value,     // pointer to array
@@ -128,6 +147,95 @@ pub enum Inst {
         field_index: u32,    // field index
         struct_type: String, // LLVM struct type name
     },
+    StructCopy {
+        result: Value,       // pointer to the fresh destination struct
+        source: Value,       // pointer to the struct being copied
+        struct_type: String, // LLVM struct type name
+        field_count: u32,    // number of fields copied
+    },
+
+    // Module-level statics
+    GlobalDef {
+        name: String,
+        values: Vec<Value>, // constant initializer, one per element for arrays
+        array_len: Option<usize>, // Some(n) for `[n x double]` globals
+        mutable: bool,      // `static mut` lowers to `global` instead of `constant`
+    },
+    GlobalAddr {
+        result: Value, // pointer to the global
+        name: String,
+    },
+
+    // Trait objects (`dyn Trait`)
+    VTableDef {
+        name: String,
+        functions: Vec<String>, // method implementations, in trait declaration order
+    },
+    VTableAddr {
+        result: Value, // register holding the vtable pointer packed into a double
+        name: String,
+    },
+    CallIndirect {
+        vtable: Value,               // packed vtable pointer
+        slot: u32,                   // method index within the vtable
+        parameters: Vec<String>,     // IR parameter types, receiver first
+        return_type: Option<String>, // IR return type; None for unit
+        arguments: Vec<Value>,
+        result: Option<Value>,
+    },
+
+    // Heap storage (Box<T>)
+    HeapAlloc {
+        result: Value,               // pointer to the malloc'd storage
+        struct_type: Option<String>, // pointee struct type; None for a single double
+    },
+    PtrToSlot {
+        result: Value,               // register holding the pointer's bits as a double
+        ptr: Value,                  // pointer being packed
+        struct_type: Option<String>, // pointee struct type; None for double*
+    },
+    SlotToPtr {
+        result: Value,               // pointer recovered from the packed bits
+        value: Value,                // register produced by PtrToSlot (or loaded from a slot)
+        struct_type: Option<String>, // pointee struct type; None for double*
+    },
+
+    // Strings
+    StrEq {
+        result: Value, // i1: true when both strings have the same length and bytes
+        left: Value,   // string literal, or register holding a packed `i8*`
+        right: Value,
+    },
+    StrConcat {
+        result: Value, // register holding the packed `i8*` of a new heap string
+        left: Value,
+        right: Value,
+    },
+    StrLen {
+        result: Value, // length in bytes, excluding the terminator
+        source: Value, // string literal, or register holding a packed `i8*`
+    },
+    /// `value.to_string()` for a number (`'g'`) or char (`'c'`), formatted
+    /// as printing it would be.
+    StrFormat {
+        result: Value, // register holding the packed `i8*` of a new heap string
+        value: Value,
+        specifier: char,
+    },
+    /// A string literal in read-only memory, for storing where only a
+    /// double slot fits.
+    StrConst {
+        result: Value, // register holding the literal's packed `i8*`
+        text: String,
+    },
+    /// `str.parse()`: convert the whole string to a number, storing it to
+    /// `value_ptr` and the `Result` tag (0 `Ok`, 1 `Err`) to `tag_ptr`.
+    ParseNumber {
+        source: Value,
+        float: bool,
+        value_ptr: Value,
+        tag_ptr: Value,
+    },
 
     // Phase 6: Vec/Collection IR operations
     VecAlloca {
@@ -198,3 +306,44 @@ pub struct Function {
     pub next_reg: u32,
     pub next_ptr: u32, // New field for unique pointer IDs
 }
+
+/// One instruction per line, with the bodies of nested `FunctionDef`s
+/// indented under their signature, as printed by `--dump-ir-after`.
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_body(f: &mut fmt::Formatter, body: &[Inst], depth: usize) -> fmt::Result {
+            let indent = "  ".repeat(depth);
+            for inst in body {
+                let Inst::FunctionDef {
+                    name,
+                    parameters,
+                    return_type,
+                    body,
+                    no_inline,
+                } = inst
+                else {
+                    writeln!(f, "{}{:?}", indent, inst)?;
+                    continue;
+                };
+                let parameters = parameters
+                    .iter()
+                    .map(|(name, ty)| format!("{}: {}", name, ty))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "{}fn {}({})", indent, name, parameters)?;
+                if let Some(return_type) = return_type {
+                    write!(f, " -> {}", return_type)?;
+                }
+                if *no_inline {
+                    write!(f, " noinline")?;
+                }
+                writeln!(f, ":")?;
+                write_body(f, body, depth + 1)?;
+            }
+            Ok(())
+        }
+
+        writeln!(f, "function {}:", self.name)?;
+        write_body(f, &self.body, 1)
+    }
+}
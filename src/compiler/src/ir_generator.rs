@@ -1,17 +1,63 @@
-use crate::ast::{AstNode, Expression, Statement, Type};
+use crate::ast::{
+    AstNode, Block, Expression, MatchArm, Parameter, Pattern, Statement, TraitMethod, Type,
+    VariantDeclKind, format_placeholders,
+};
+use crate::errors::SourceLocation;
 use crate::ir::{Function, Inst, Value};
 use crate::types::{Ty, needs_promotion};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Struct backing `Ty::Iter` values: the packed collection pointer, then the
+/// index of the next element.
+const ITER_STRUCT: &str = "vec.iter";
+
+/// Structs backing the iterator adapters: the packed source iterator, then
+/// the index of the next item (`enumerate`) or the number of items left
+/// (`take`). A `zip` holds its two packed sources.
+const ENUMERATE_STRUCT: &str = "iter.enumerate";
+const ZIP_STRUCT: &str = "iter.zip";
+const TAKE_STRUCT: &str = "iter.take";
+
+/// A variant as `{:?}` prints it: name, payload types and, for a struct
+/// variant, its field names.
+type DebugVariant = (String, Vec<Ty>, Option<Vec<String>>);
 
 pub struct IrGenerator {
     functions: HashMap<String, Function>,
-    #[allow(dead_code)]
-    current_function_name: String,
+    current_function_name: String, // Function whose body is being lowered
     next_reg: u32,
     next_ptr: u32,
     symbol_table: HashMap<String, (Value, Ty)>, // Track both pointer and type
     loop_label_stack: Vec<(String, String)>,    // Stack of (loop_start, loop_end) labels
     closure_count: u32,                         // Counter for unique closure names
+    struct_layouts: HashMap<String, Vec<(String, Ty)>>, // Field order and types per struct
+    enum_variants: HashMap<String, Vec<(String, Vec<Ty>)>>, // Variant order (= tag) and payload types per enum
+    variant_fields: HashMap<(String, String), Vec<String>>, // Field names of struct variants, in payload order
+    statics: HashMap<String, (Ty, Option<Value>)>, // Static type, plus inlined value if not a global
+    function_returns: HashMap<String, Ty>,         // Declared return type per function
+    generic_functions: HashMap<String, GenericFunction>, // Instantiated per call site
+    trait_methods: HashMap<String, Vec<TraitMethod>>, // Methods per trait, in vtable order
+    function_params: HashMap<String, Vec<Ty>>,     // Declared parameter types per function
+    by_value_receivers: HashSet<String>,           // Methods taking `self` rather than `&self`
+    no_inline: HashSet<String>,                    // Functions marked `@inline(never)`
+    string_bytes: HashMap<String, String>,         // Global byte array per string literal
+}
+
+/// A branch of an `if let` or `match` jumping to the merge point.
+struct BranchExit {
+    jump_index: usize,    // position of the branch's `Jump` in the function body
+    block: String,        // label of the block the jump leaves from
+    value: Option<Value>, // slot representation of the branch value, if any
+}
+
+/// A generic function, kept as AST until a call fixes its type arguments.
+#[derive(Clone)]
+struct GenericFunction {
+    type_params: Vec<String>,
+    const_params: Vec<String>,
+    parameters: Vec<Parameter>,
+    return_type: Option<Type>,
+    body: Block,
 }
 
 impl IrGenerator {
@@ -24,6 +70,17 @@ impl IrGenerator {
             symbol_table: HashMap::new(),
             loop_label_stack: Vec::new(),
             closure_count: 0,
+            struct_layouts: HashMap::new(),
+            enum_variants: HashMap::new(),
+            variant_fields: HashMap::new(),
+            statics: HashMap::new(),
+            function_returns: HashMap::new(),
+            generic_functions: HashMap::new(),
+            trait_methods: HashMap::new(),
+            function_params: HashMap::new(),
+            by_value_receivers: HashSet::new(),
+            no_inline: HashSet::new(),
+            string_bytes: HashMap::new(),
         }
     }
 }
@@ -43,6 +100,30 @@ impl IrGenerator {
             next_ptr: 0,
         };
 
+        // Calls may precede the callee's definition, so unit-returning
+        // functions are known before any body is lowered.
+        for node in &ast {
+            if let AstNode::Statement(Statement::Function {
+                name,
+                return_type,
+                type_params,
+                const_params,
+                no_inline,
+                ..
+            }) = node
+                && type_params.is_empty()
+                && const_params.is_empty()
+            {
+                if *no_inline {
+                    self.no_inline.insert(name.clone());
+                }
+                let declared_return = return_type
+                    .as_ref()
+                    .map_or(Ty::Unit, |ty| self.ast_type_to_ty(ty));
+                self.function_returns.insert(name.clone(), declared_return);
+            }
+        }
+
         for node in ast {
             match node {
                 AstNode::Statement(stmt) => self.generate_statement_ir(stmt, &mut main_function),
@@ -61,7 +142,24 @@ impl IrGenerator {
     }
 
     fn stores_value_directly(ty: &Ty) -> bool {
-        matches!(ty, Ty::String | Ty::Array(_, _) | Ty::Vec(_))
+        matches!(
+            ty,
+            Ty::Unit
+                | Ty::String
+                | Ty::Array(_, _)
+                | Ty::Box(_)
+                | Ty::Vec(_)
+                | Ty::Struct(_)
+                | Ty::Enum(_)
+                | Ty::Option(_)
+                | Ty::Result(_, _)
+                | Ty::TraitObject(_)
+                | Ty::Tuple(_)
+                | Ty::Iter(_, _)
+                | Ty::Enumerate(_)
+                | Ty::Zip(_, _)
+                | Ty::Take(_)
+        ) || Self::is_scalar_reference(ty)
     }
 
     fn generate_statement_ir(&mut self, stmt: Statement, current_function: &mut Function) {
@@ -69,11 +167,31 @@ impl IrGenerator {
             Statement::Let {
                 name,
                 mutable: _,
-                type_annotation: _,
+                type_annotation,
                 value,
             } => {
+                let value = match (value, type_annotation.as_ref()) {
+                    (Some(val), Some(ty)) => {
+                        Some(val.with_annotated_parse_target(ty).unwrap_or(val))
+                    }
+                    (value, _) => value,
+                };
+                let expected = type_annotation.map(|ty| self.ast_type_to_ty(&ty));
                 let (expr_value, expr_type) = if let Some(val) = value {
-                    self.generate_expression_ir(val, current_function)
+                    let binds_existing_struct = matches!(val, Expression::Identifier(_));
+                    match self.generate_expression_ir_as(val, expected.as_ref(), current_function) {
+                        // `let b = a;` gets its own storage so later field
+                        // writes through `b` never alias `a`.
+                        (source, Ty::Struct(struct_name)) if binds_existing_struct => {
+                            let copy = self.generate_struct_copy_ir(
+                                source,
+                                &struct_name,
+                                current_function,
+                            );
+                            (copy, Ty::Struct(struct_name))
+                        }
+                        other => other,
+                    }
                 } else {
                     (Value::ImmInt(0), Ty::Int)
                 };
@@ -89,20 +207,98 @@ impl IrGenerator {
                     current_function
                         .body
                         .push(Inst::Alloca(ptr_reg.clone(), name.clone()));
-                    self.symbol_table.insert(name, (ptr_reg.clone(), expr_type));
 
                     // Store the expression result into the allocated slot
-                    current_function.body.push(Inst::Store(ptr_reg, expr_value));
+                    let expr_value =
+                        self.generate_value_to_slot_ir(expr_value, &expr_type, current_function);
+                    current_function
+                        .body
+                        .push(Inst::Store(ptr_reg.clone(), expr_value));
+                    self.symbol_table.insert(name, (ptr_reg, expr_type));
+                }
+            }
+            Statement::Assign { target, value } => {
+                let (value, value_type) = self.generate_expression_ir(value, current_function);
+                match target {
+                    Expression::Identifier(name) => match self.symbol_table.get(&name).cloned() {
+                        Some((ptr, ty)) if !Self::stores_value_directly(&ty) => {
+                            let value =
+                                self.generate_value_to_slot_ir(value, &ty, current_function);
+                            current_function.body.push(Inst::Store(ptr, value));
+                        }
+                        _ => {
+                            self.symbol_table.insert(name, (value, value_type));
+                        }
+                    },
+                    Expression::IndexAccess {
+                        object,
+                        index,
+                        location,
+                    } => {
+                        let (elem_ptr, elem_ty) =
+                            self.generate_index_ptr_ir(*object, *index, location, current_function);
+                        let slot =
+                            self.generate_value_to_slot_ir(value, &elem_ty, current_function);
+                        current_function.body.push(Inst::Store(elem_ptr, slot));
+                    }
+                    Expression::FieldAccess { object, field } => {
+                        self.generate_field_store_ir(*object, &field, value, current_function);
+                    }
+                    Expression::TupleIndex { object, index } => {
+                        let field = index.to_string();
+                        self.generate_field_store_ir(*object, &field, value, current_function);
+                    }
+                    _ => {}
                 }
             }
+            Statement::LetPattern { pattern, value, .. } => {
+                let (value, value_type) = self.generate_expression_ir(value, current_function);
+                self.generate_pattern_bindings(&value, &value_type, &pattern, current_function);
+            }
             Statement::Return(expr) => {
-                let (return_value, _) = if let Some(val) = expr {
-                    self.generate_expression_ir(val, current_function)
+                let (return_value, return_type) = if let Some(val) = expr {
+                    let (value, ty) = self.generate_expression_ir(val, current_function);
+                    match self
+                        .function_returns
+                        .get(&self.current_function_name)
+                        .cloned()
+                    {
+                        Some(declared) => {
+                            self.generate_coercion_ir(value, ty, &declared, current_function)
+                        }
+                        None => (value, ty),
+                    }
                 } else {
                     (Value::ImmInt(0), Ty::Int)
                 };
+                // Aggregates leave the function as their packed pointer.
+                let return_value =
+                    self.generate_escaping_value_ir(return_value, &return_type, current_function);
+                let return_value =
+                    self.generate_value_to_slot_ir(return_value, &return_type, current_function);
                 current_function.body.push(Inst::Return(return_value));
             }
+            Statement::Function {
+                name,
+                parameters,
+                return_type,
+                body,
+                type_params,
+                const_params,
+                ..
+            } if !type_params.is_empty() || !const_params.is_empty() => {
+                // Lowered per instantiation, see `instantiate_generic_function`.
+                self.generic_functions.insert(
+                    name,
+                    GenericFunction {
+                        type_params,
+                        const_params,
+                        parameters,
+                        return_type,
+                        body,
+                    },
+                );
+            }
             Statement::Function {
                 name,
                 parameters,
@@ -125,6 +321,28 @@ impl IrGenerator {
             } => {
                 self.generate_if_statement_ir(condition, then_block, else_block, current_function);
             }
+            Statement::IfLet {
+                pattern,
+                expression,
+                then_block,
+                else_block,
+            } => {
+                // Lowered like the expression form, with the value discarded.
+                let else_block = else_block.map(|else_stmt| match *else_stmt {
+                    Statement::Block(block) => Box::new(block),
+                    other => Box::new(Block {
+                        statements: vec![other],
+                        expression: None,
+                    }),
+                });
+                self.generate_if_let_ir(
+                    *pattern,
+                    expression,
+                    then_block,
+                    else_block,
+                    current_function,
+                );
+            }
             Statement::While { condition, body } => {
                 self.generate_while_loop_ir(condition, body, current_function);
             }
@@ -146,7 +364,14 @@ impl IrGenerator {
             }
             Statement::Expression(expr) => {
                 // Generate IR for standalone expressions
-                self.generate_expression_ir(expr, current_function);
+                let (value, _) = self.generate_expression_ir(expr, current_function);
+                // The value of a call in statement position is never read,
+                // so the call defines no result register.
+                if let Some(Inst::Call { result, .. }) = current_function.body.last_mut()
+                    && *result == Some(value)
+                {
+                    *result = None;
+                }
             }
             Statement::Block(block) => {
                 // Generate IR for block statements
@@ -157,37 +382,93 @@ impl IrGenerator {
                     self.generate_expression_ir(expr, current_function);
                 }
             }
-            // Phase 4: struct/enum/impl definitions are processed at a higher level;
+            Statement::StructDef { name, fields, .. } => {
+                // Only the field layout is needed; struct definitions emit no IR.
+                let layout = fields
+                    .iter()
+                    .map(|field| (field.name.clone(), self.ast_type_to_ty(&field.field_type)))
+                    .collect();
+                self.struct_layouts.insert(name, layout);
+            }
+            Statement::EnumDef { name, variants, .. } => {
+                // Register the name first so payloads mentioning the enum
+                // itself (through a Box) resolve to `Ty::Enum`.
+                self.enum_variants.insert(name.clone(), Vec::new());
+                let layout = variants
+                    .iter()
+                    .map(|variant| {
+                        let payload = match &variant.kind {
+                            VariantDeclKind::Unit => Vec::new(),
+                            VariantDeclKind::Tuple(types) => {
+                                types.iter().map(|ty| self.ast_type_to_ty(ty)).collect()
+                            }
+                            VariantDeclKind::Struct(fields) => {
+                                self.variant_fields.insert(
+                                    (name.clone(), variant.name.clone()),
+                                    fields.iter().map(|field| field.name.clone()).collect(),
+                                );
+                                fields
+                                    .iter()
+                                    .map(|field| self.ast_type_to_ty(&field.field_type))
+                                    .collect()
+                            }
+                        };
+                        (variant.name.clone(), payload)
+                    })
+                    .collect();
+                self.enum_variants.insert(name, layout);
+            }
+            // Phase 4: impl definitions are processed at a higher level;
             // they don't generate body IR in the same way as executable statements.
-            Statement::StructDef { .. }
-            | Statement::EnumDef { .. }
-            | Statement::ImplBlock { .. }
-            | Statement::TraitDef { .. }
-            | Statement::ModDecl { .. }
-            | Statement::UseImport { .. } => {
+            Statement::Static {
+                name,
+                mutable,
+                type_annotation,
+                value,
+            } => {
+                self.generate_static_ir(name, mutable, type_annotation, value, current_function);
+            }
+            Statement::ImplBlock {
+                type_name,
+                methods,
+                trait_name,
+                ..
+            } => self.generate_impl_block_ir(type_name, methods, trait_name, current_function),
+            Statement::TraitDef { name, methods, .. } => {
+                self.trait_methods.insert(name, methods);
+            }
+            Statement::ModDecl { .. } | Statement::UseImport { .. } => {
                 // Type/module definitions are registered in the semantic pass.
                 // No runtime IR to generate.
             }
+            Statement::Cfg { .. } => {
+                // Active `@cfg` items are unwrapped before semantic analysis,
+                // so any left here were disabled.
+            }
         }
     }
 
     fn generate_expression_ir(&mut self, expr: Expression, function: &mut Function) -> (Value, Ty) {
         match expr {
-            Expression::IntegerLiteral(n) => (Value::ImmInt(n), Ty::Int),
+            Expression::IntegerLiteral(n) => (Value::ImmInt(n), Ty::of_integer_literal(n)),
             Expression::FloatLiteral(f) => (Value::ImmFloat(f), Ty::Float),
+            Expression::BooleanLiteral(b) => (Value::ImmInt(b as i64), Ty::Bool),
+            Expression::CharLiteral(c) => (Value::ImmInt(c as i64), Ty::Char),
             Expression::Identifier(name) => {
-                let (storage, var_type) = self
-                    .symbol_table
-                    .get(&name)
-                    .expect("Undeclared variable")
-                    .clone();
+                let (storage, var_type) = match self.symbol_table.get(&name) {
+                    Some(entry) => entry.clone(),
+                    None => self
+                        .generate_static_ref_ir(&name, function)
+                        .expect("Undeclared variable"),
+                };
                 if Self::stores_value_directly(&var_type) {
                     return (storage, var_type);
                 }
                 let result_reg = Value::Reg(self.next_reg);
                 self.next_reg += 1;
                 function.body.push(Inst::Load(result_reg.clone(), storage));
-                (result_reg, var_type)
+                let result = self.generate_slot_to_value_ir(result_reg, &var_type, function);
+                (result, var_type)
             }
             Expression::Binary {
                 op,
@@ -196,7 +477,13 @@ impl IrGenerator {
                 ty,
             } => {
                 let (lhs_val, lhs_type) = self.generate_expression_ir(*left, function);
+                let (lhs_val, lhs_type) = self.generate_auto_deref_ir(lhs_val, lhs_type, function);
                 let (rhs_val, rhs_type) = self.generate_expression_ir(*right, function);
+                let (rhs_val, rhs_type) = self.generate_auto_deref_ir(rhs_val, rhs_type, function);
+
+                if let ("+", Ty::String, Ty::String) = (op.as_str(), &lhs_type, &rhs_type) {
+                    return self.generate_string_concat_ir(lhs_val, rhs_val, function);
+                }
 
                 // Prefer the result type from the AST (set by semantic analysis).
                 //
@@ -206,6 +493,7 @@ impl IrGenerator {
                 let result_type = ty.unwrap_or_else(|| match (&lhs_type, &rhs_type) {
                     (Ty::Float, _) | (_, Ty::Float) => Ty::Float,
                     (Ty::Int, Ty::Int) => Ty::Int,
+                    (l, r) if l.is_integer() && r.is_integer() => Ty::Int64,
                     (l, r) => panic!(
                         "Cannot infer binary op result type for op '{}' with operand types {:?} and {:?}",
                         op.as_str(),
@@ -236,13 +524,21 @@ impl IrGenerator {
 
                 // Generate the appropriate instruction based on result type
                 let inst = match (&result_type, op.as_str()) {
-                    (Ty::Int, "+") => Inst::Add(result_reg.clone(), promoted_lhs, promoted_rhs),
+                    (Ty::Int | Ty::Int64, "+") => {
+                        Inst::Add(result_reg.clone(), promoted_lhs, promoted_rhs)
+                    }
                     (Ty::Float, "+") => Inst::FAdd(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Int, "-") => Inst::Sub(result_reg.clone(), promoted_lhs, promoted_rhs),
+                    (Ty::Int | Ty::Int64, "-") => {
+                        Inst::Sub(result_reg.clone(), promoted_lhs, promoted_rhs)
+                    }
                     (Ty::Float, "-") => Inst::FSub(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Int, "*") => Inst::Mul(result_reg.clone(), promoted_lhs, promoted_rhs),
+                    (Ty::Int | Ty::Int64, "*") => {
+                        Inst::Mul(result_reg.clone(), promoted_lhs, promoted_rhs)
+                    }
                     (Ty::Float, "*") => Inst::FMul(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Int, "/") => Inst::Div(result_reg.clone(), promoted_lhs, promoted_rhs),
+                    (Ty::Int | Ty::Int64, "/") => {
+                        Inst::Div(result_reg.clone(), promoted_lhs, promoted_rhs)
+                    }
                     (Ty::Float, "/") => Inst::FDiv(result_reg.clone(), promoted_lhs, promoted_rhs),
                     _ => panic!(
                         "Unsupported binary operation: {} for type {:?}",
@@ -253,32 +549,49 @@ impl IrGenerator {
                 function.body.push(inst);
                 (result_reg, result_type)
             }
+            Expression::FunctionCall { name, arguments }
+                if name == "swap" && self.is_builtin_call(&name) =>
+            {
+                self.generate_swap_ir(arguments, function)
+            }
+            Expression::FunctionCall { name, arguments } if self.is_tuple_struct(&name) => {
+                let fields = arguments
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, arg)| (i.to_string(), arg))
+                    .collect();
+                self.generate_expression_ir(Expression::StructLiteral { name, fields }, function)
+            }
+            Expression::FunctionCall { name, arguments } if self.is_builtin_call(&name) => {
+                let operands = arguments
+                    .into_iter()
+                    .map(|arg| self.generate_expression_ir(arg, function))
+                    .collect();
+                self.generate_numeric_builtin_ir(&name, operands, function)
+            }
             Expression::FunctionCall { name, arguments } => {
                 // Generate IR for arguments
                 let mut arg_values = Vec::new();
-                for arg in arguments {
-                    let (arg_value, _) = self.generate_expression_ir(arg, function);
+                let mut arg_types = Vec::new();
+                let param_types = self.function_params.get(&name).cloned().unwrap_or_default();
+                for (i, arg) in arguments.into_iter().enumerate() {
+                    let (arg_value, arg_type) =
+                        self.generate_expression_ir_as(arg, param_types.get(i), function);
+                    // Aggregates cross calls as their pointer, packed into a double.
+                    let arg_value = self.generate_value_to_slot_ir(arg_value, &arg_type, function);
                     arg_values.push(arg_value);
+                    arg_types.push(arg_type);
                 }
 
-                // Generate result register for function call
-                let result_reg = Value::Reg(self.next_reg);
-                self.next_reg += 1;
-
-                // Resolve closure variables to their generated function symbol.
-                let function_name = self.resolve_callable_name(&name);
-
-                // Create function call instruction
-                let call_inst = Inst::Call {
-                    function: function_name,
-                    arguments: arg_values,
-                    result: Some(result_reg.clone()),
+                // Resolve closure variables to their generated function symbol,
+                // and generic functions to their instantiation for these arguments.
+                let function_name = if self.generic_functions.contains_key(&name) {
+                    self.instantiate_generic_function(&name, &arg_types)
+                } else {
+                    self.resolve_callable_name(&name)
                 };
 
-                function.body.push(call_inst);
-
-                // For now, assume function calls return int (this should be looked up from function table in semantic analysis)
-                (result_reg, Ty::Int)
+                self.generate_call_ir(function_name, arg_values, function)
             }
             Expression::Print {
                 format_string,
@@ -300,65 +613,197 @@ impl IrGenerator {
                 object,
                 method,
                 arguments,
+                type_args,
             } => {
                 let (object_value, object_ty) = self.generate_expression_ir(*object, function);
+                let (object_value, object_ty) =
+                    self.generate_auto_deref_ir(object_value, object_ty, function);
                 if method == "iter"
                     && arguments.is_empty()
                     && matches!(object_ty, Ty::Array(_, _) | Ty::Vec(_))
                 {
-                    // Minimal iterator protocol lowering: `.iter()` reuses the collection value.
-                    (object_value, object_ty)
+                    match object_ty {
+                        Ty::Array(elem_ty, len) => {
+                            self.generate_iter_ir(object_value, *elem_ty, len, function)
+                        }
+                        // An unsized `Vec` has no storage to walk yet, so
+                        // `.iter()` reuses the collection value.
+                        other => (object_value, other),
+                    }
+                } else if let ("next", Some(item_ty), []) = (
+                    method.as_str(),
+                    object_ty.iterator_item(),
+                    arguments.as_slice(),
+                ) {
+                    let option_ptr = Value::Reg(self.next_ptr);
+                    self.next_ptr += 1;
+                    function.body.push(Inst::AllocaStruct {
+                        result: option_ptr.clone(),
+                        struct_type: "Option".to_string(),
+                    });
+                    self.generate_iter_next_ir(&object_value, &object_ty, &option_ptr, function);
+                    (option_ptr, Ty::Option(Box::new(item_ty)))
+                } else if matches!(method.as_str(), "enumerate" | "zip" | "take")
+                    && object_ty.iterator_item().is_some()
+                {
+                    self.generate_iter_adapter_ir(
+                        object_value,
+                        object_ty,
+                        &method,
+                        arguments,
+                        function,
+                    )
+                } else if let (Value::ImmString(text), "len", []) =
+                    (&object_value, method.as_str(), arguments.as_slice())
+                {
+                    // Literal lengths (in bytes) are known at compile time.
+                    (Value::ImmInt(text.len() as i64), Ty::Int)
+                } else if let ("len", Ty::String, []) =
+                    (method.as_str(), &object_ty, arguments.as_slice())
+                {
+                    let result = Value::Reg(self.next_reg);
+                    self.next_reg += 1;
+                    function.body.push(Inst::StrLen {
+                        result: result.clone(),
+                        source: object_value,
+                    });
+                    (result, Ty::Int)
+                } else if let ("is_empty", Ty::String | Ty::Array(_, _), []) =
+                    (method.as_str(), &object_ty, arguments.as_slice())
+                {
+                    let length = match (&object_value, &object_ty) {
+                        (Value::ImmString(text), _) => text.len(),
+                        (_, Ty::Array(_, len)) => *len,
+                        _ => {
+                            let length = Value::Reg(self.next_reg);
+                            let result = Value::Reg(self.next_reg + 1);
+                            self.next_reg += 2;
+                            function.body.push(Inst::StrLen {
+                                result: length.clone(),
+                                source: object_value,
+                            });
+                            function.body.push(Inst::ICmp {
+                                op: "eq".to_string(),
+                                result: result.clone(),
+                                left: length,
+                                right: Value::ImmInt(0),
+                            });
+                            return (result, Ty::Bool);
+                        }
+                    };
+                    // Literal and array lengths are known at compile time.
+                    (Value::ImmInt((length == 0) as i64), Ty::Bool)
+                } else if let (Value::ImmString(text), "as_bytes", []) =
+                    (&object_value, method.as_str(), arguments.as_slice())
+                {
+                    self.generate_string_bytes_ir(text.clone(), function)
+                } else if let ("parse", Ty::String, []) =
+                    (method.as_str(), &object_ty, arguments.as_slice())
+                {
+                    let target = type_args
+                        .first()
+                        .map_or(Ty::Int, |ty| self.ast_type_to_ty(ty));
+                    self.generate_parse_number_ir(object_value, target, function)
+                } else if let ("sort" | "reverse", Ty::Array(_, len), []) =
+                    (method.as_str(), &object_ty, arguments.as_slice())
+                {
+                    if method == "sort" {
+                        self.generate_array_sort_ir(&object_value, *len, function);
+                    } else {
+                        self.generate_array_reverse_ir(&object_value, *len, function);
+                    }
+                    (Value::ImmInt(0), Ty::Unit)
+                } else if let ("get", Ty::Array(elem_ty, len), [index]) =
+                    (method.as_str(), &object_ty, arguments.as_slice())
+                {
+                    let (elem_ty, len) = ((**elem_ty).clone(), *len);
+                    self.generate_collection_get_ir(
+                        object_value,
+                        elem_ty,
+                        len,
+                        index.clone(),
+                        function,
+                    )
+                } else if let ("ok_or" | "ok_or_else", Ty::Option(ok_ty), [argument]) =
+                    (method.as_str(), &object_ty, arguments.as_slice())
+                {
+                    let ok_ty = (**ok_ty).clone();
+                    let error = match (method.as_str(), argument.clone()) {
+                        ("ok_or", error) => error,
+                        // The closure captures nothing, so its body runs in place.
+                        (_, Expression::Closure { params, body }) if params.is_empty() => *body,
+                        _ => return (Value::ImmInt(0), Ty::Int), // Rejected by the semantic analyzer
+                    };
+                    self.generate_ok_or_ir(object_value, ok_ty, error, method == "ok_or", function)
+                } else if let ("unwrap_err" | "expect_err", Ty::Result(_, err_ty)) =
+                    (method.as_str(), &object_ty)
+                {
+                    let err_ty = (**err_ty).clone();
+                    let message = match arguments.first() {
+                        Some(Expression::StringLiteral(message)) if method == "expect_err" => {
+                            message.clone()
+                        }
+                        _ => "called `Result::unwrap_err()` on an `Ok` value".to_string(),
+                    };
+                    self.generate_unwrap_err_ir(object_value, err_ty, message, function)
+                } else if let ("as_str", Ty::String, []) =
+                    (method.as_str(), &object_ty, arguments.as_slice())
+                {
+                    // The slice borrows the string's bytes in place.
+                    (object_value, Ty::String)
+                } else if method == "to_string"
+                    && arguments.is_empty()
+                    && matches!(
+                        object_ty,
+                        Ty::String | Ty::Int | Ty::Int64 | Ty::Float | Ty::Bool | Ty::Char
+                    )
+                {
+                    self.generate_to_string_ir(object_value, object_ty, function)
+                } else if matches!(object_ty, Ty::Int | Ty::Float)
+                    && matches!(method.as_str(), "min" | "max" | "abs")
+                {
+                    let mut operands = vec![(object_value, object_ty)];
+                    for arg in arguments {
+                        operands.push(self.generate_expression_ir(arg, function));
+                    }
+                    self.generate_numeric_builtin_ir(&method, operands, function)
+                } else if let Ty::TraitObject(trait_name) = &object_ty {
+                    let trait_name = trait_name.clone();
+                    self.generate_dynamic_call_ir(
+                        object_value,
+                        &trait_name,
+                        &method,
+                        arguments,
+                        function,
+                    )
+                } else if let Some(function_name) = Self::method_symbol(&object_ty, &method)
+                    .filter(|symbol| self.function_returns.contains_key(symbol))
+                {
+                    // Methods are resolved statically on the receiver's type;
+                    // the receiver is passed as the first argument. `&self`
+                    // borrows the receiver in place, `self` gets a copy.
+                    let receiver = match &object_ty {
+                        Ty::Struct(name) if self.by_value_receivers.contains(&function_name) => {
+                            let name = name.clone();
+                            self.generate_struct_copy_ir(object_value, &name, function)
+                        }
+                        _ => object_value,
+                    };
+                    let mut arg_values =
+                        vec![self.generate_value_to_slot_ir(receiver, &object_ty, function)];
+                    for arg in arguments {
+                        let (arg_value, arg_type) = self.generate_expression_ir(arg, function);
+                        arg_values
+                            .push(self.generate_value_to_slot_ir(arg_value, &arg_type, function));
+                    }
+                    self.generate_call_ir(function_name, arg_values, function)
                 } else {
                     // Method calls will be resolved to function calls as method lowering expands.
                     (Value::ImmInt(0), Ty::Int)
                 }
             }
             Expression::ArrayLiteral(elements) => {
-                let count = elements.len();
-                let arr_ptr = Value::Reg(self.next_ptr);
-                self.next_ptr += 1;
-                // Determine element type from first element
-                let elem_type = if count > 0 {
-                    let (first_val, first_ty) =
-                        self.generate_expression_ir(elements[0].clone(), function);
-                    function.body.push(Inst::AllocaArray {
-                        result: arr_ptr.clone(),
-                        elem_type: "double".to_string(),
-                        count,
-                    });
-                    // Store first element
-                    let elem_ptr = Value::Reg(self.next_ptr);
-                    self.next_ptr += 1;
-                    function.body.push(Inst::GetElementPtr {
-                        result: elem_ptr.clone(),
-                        base: arr_ptr.clone(),
-                        index: Value::ImmInt(0),
-                        elem_type: format!("[{} x double]", count),
-                    });
-                    function.body.push(Inst::Store(elem_ptr, first_val));
-                    // Store remaining elements
-                    for (i, elem) in elements.into_iter().skip(1).enumerate() {
-                        let (val, _) = self.generate_expression_ir(elem, function);
-                        let ep = Value::Reg(self.next_ptr);
-                        self.next_ptr += 1;
-                        function.body.push(Inst::GetElementPtr {
-                            result: ep.clone(),
-                            base: arr_ptr.clone(),
-                            index: Value::ImmInt((i + 1) as i64),
-                            elem_type: format!("[{} x double]", count),
-                        });
-                        function.body.push(Inst::Store(ep, val));
-                    }
-                    first_ty
-                } else {
-                    function.body.push(Inst::AllocaArray {
-                        result: arr_ptr.clone(),
-                        elem_type: "double".to_string(),
-                        count: 0,
-                    });
-                    Ty::Int
-                };
-                (arr_ptr, Ty::Array(Box::new(elem_type), count))
+                self.generate_array_literal_ir(elements, None, function)
             }
             Expression::ArrayRepeat { value, count } => {
                 let (val, elem_ty) = self.generate_expression_ir(*value, function);
@@ -382,36 +827,188 @@ impl IrGenerator {
                 }
                 (arr_ptr, Ty::Array(Box::new(elem_ty), count))
             }
-            Expression::IndexAccess { object, index } => {
-                let (arr_val, arr_ty) = self.generate_expression_ir(*object, function);
-                let (idx_val, _) = self.generate_expression_ir(*index, function);
-                let (elem_ty, gep_elem_type) = match &arr_ty {
-                    Ty::Array(et, len) => (*et.clone(), format!("[{} x double]", len)),
-                    _ => (Ty::Int, "double".to_string()),
-                };
-                let elem_ptr = Value::Reg(self.next_ptr);
-                self.next_ptr += 1;
-                function.body.push(Inst::GetElementPtr {
-                    result: elem_ptr.clone(),
-                    base: arr_val,
-                    index: idx_val,
-                    elem_type: gep_elem_type,
-                });
+            Expression::IndexAccess {
+                object,
+                index,
+                location,
+            } => {
+                let (elem_ptr, elem_ty) =
+                    self.generate_index_ptr_ir(*object, *index, location, function);
                 let result = Value::Reg(self.next_reg);
                 self.next_reg += 1;
                 function.body.push(Inst::Load(result.clone(), elem_ptr));
+                let result = self.generate_slot_to_value_ir(result, &elem_ty, function);
                 (result, elem_ty)
             }
-            Expression::FieldAccess { .. }
-            | Expression::TupleLiteral(_)
-            | Expression::TupleIndex { .. }
-            | Expression::StructLiteral { .. }
-            | Expression::EnumVariant { .. }
-            | Expression::Match { .. }
-            | Expression::Borrow { .. }
-            | Expression::Deref(_) => {
-                // Stub: these will be implemented as remaining Phase 4/5 tasks progress
-                (Value::ImmInt(0), Ty::Int)
+            Expression::StructLiteral { name, fields } => {
+                let layout = self.struct_layouts.get(&name).cloned().unwrap_or_default();
+                let struct_ptr = Value::Reg(self.next_ptr);
+                self.next_ptr += 1;
+                function.body.push(Inst::AllocaStruct {
+                    result: struct_ptr.clone(),
+                    struct_type: name.clone(),
+                });
+                for (field_name, field_expr) in fields {
+                    let Some(field_index) = layout.iter().position(|(n, _)| *n == field_name)
+                    else {
+                        continue;
+                    };
+                    let (mut field_value, field_type) =
+                        self.generate_expression_ir(field_expr, function);
+                    field_value = match field_type {
+                        Ty::String => self.generate_string_slot_ir(field_value, function),
+                        Ty::Bool => self.generate_bool_to_slot_ir(field_value, function),
+                        _ => field_value,
+                    };
+                    let field_ptr = self.generate_field_ptr_ir(
+                        &struct_ptr,
+                        &name,
+                        field_index as u32,
+                        function,
+                    );
+                    function.body.push(Inst::Store(field_ptr, field_value));
+                }
+                (struct_ptr, Ty::Struct(name))
+            }
+            Expression::FieldAccess { object, field } => {
+                let (struct_ptr, object_type) = self.generate_expression_ir(*object, function);
+                let Ty::Struct(struct_name) = object_type else {
+                    return (Value::ImmInt(0), Ty::Int);
+                };
+                let Some((field_index, field_type)) = self.struct_field(&struct_name, &field)
+                else {
+                    return (Value::ImmInt(0), Ty::Int);
+                };
+                let field_value = self.generate_field_value_ir(
+                    &struct_ptr,
+                    &struct_name,
+                    field_index,
+                    &field_type,
+                    function,
+                );
+                (field_value, field_type)
+            }
+            Expression::EnumVariant {
+                enum_name,
+                variant,
+                data: Some(data),
+            } if enum_name == "String" && variant == "from" => {
+                let (value, ty) = self.generate_expression_ir(*data, function);
+                let (value, ty) = self.generate_auto_deref_ir(value, ty, function);
+                self.generate_to_string_ir(value, ty, function)
+            }
+            Expression::EnumVariant {
+                enum_name,
+                variant,
+                data,
+            } => self.generate_enum_variant_ir(enum_name, variant, data.map(|d| *d), function),
+            Expression::Matches { expr, pattern } => {
+                let (value, value_type) = self.generate_expression_ir(*expr, function);
+                let matched = self.generate_pattern_check(&value, &value_type, &pattern, function);
+                (matched, Ty::Bool)
+            }
+            Expression::TupleLiteral(elements) if elements.is_empty() => {
+                (Value::ImmInt(0), Ty::Unit)
+            }
+            Expression::Match { expr, arms } => {
+                self.generate_match_expression_ir(*expr, arms, function)
+            }
+            Expression::IfLet {
+                pattern,
+                expr,
+                then_block,
+                else_block,
+            } => self.generate_if_let_ir(*pattern, *expr, *then_block, else_block, function),
+            Expression::Block(block) => {
+                let saved_symbol_table = self.symbol_table.clone();
+                let result = self.generate_branch_value_ir(*block, function);
+                self.symbol_table = saved_symbol_table;
+                result
+            }
+            Expression::BoxNew(value) => self.generate_box_new_ir(*value, function),
+            // Stub: ranges are only lowered as `for` loop iterables.
+            Expression::Range { .. } => (Value::ImmInt(0), Ty::Int),
+            // Resolved into positional arguments before analysis.
+            Expression::NamedArgument { value, .. } => {
+                self.generate_expression_ir(*value, function)
+            }
+            Expression::Panic { message, location } => {
+                self.generate_diverging_panic_ir(message, location, &mut function.body)
+            }
+            Expression::Deref(inner) => {
+                let (value, ty) = self.generate_expression_ir(*inner, function);
+                match ty {
+                    // An aggregate is used through its pointer, so the heap
+                    // storage itself is the dereferenced value.
+                    Ty::Box(inner_ty) if Self::aggregate_struct_type(&inner_ty).is_some() => {
+                        (value, *inner_ty)
+                    }
+                    Ty::Box(inner_ty) => {
+                        let loaded = Value::Reg(self.next_reg);
+                        self.next_reg += 1;
+                        function.body.push(Inst::Load(loaded.clone(), value));
+                        let loaded = self.generate_slot_to_value_ir(loaded, &inner_ty, function);
+                        (loaded, *inner_ty)
+                    }
+                    ty @ Ty::Reference(..) if Self::is_scalar_reference(&ty) => {
+                        self.generate_auto_deref_ir(value, ty, function)
+                    }
+                    // Stub: other reference dereferencing is not lowered yet
+                    _ => (Value::ImmInt(0), Ty::Int),
+                }
+            }
+            Expression::Borrow { expr, mutable } => {
+                // A scalar variable is borrowed through its stack slot.
+                if let Expression::Identifier(name) = expr.as_ref()
+                    && let Some((slot, ty)) = self.symbol_table.get(name).cloned()
+                    && Self::is_scalar(&ty)
+                {
+                    return (slot, Ty::Reference(Box::new(ty), mutable));
+                }
+                let (value, ty) = self.generate_expression_ir(*expr, function);
+                if Self::is_pointer_backed(&ty) {
+                    // A reference to a pointer-backed value is that pointer.
+                    (value, ty)
+                } else if Self::is_scalar(&ty) {
+                    // Other scalars are spilled to a temporary to be borrowed.
+                    let slot = Value::Reg(self.next_ptr);
+                    self.next_ptr += 1;
+                    function
+                        .body
+                        .push(Inst::Alloca(slot.clone(), "borrow".to_string()));
+                    let value = self.generate_value_to_slot_ir(value, &ty, function);
+                    function.body.push(Inst::Store(slot.clone(), value));
+                    (slot, Ty::Reference(Box::new(ty), mutable))
+                } else {
+                    // Stub: other references are not lowered yet.
+                    (Value::ImmInt(0), Ty::Int)
+                }
+            }
+            Expression::TupleLiteral(elements) => {
+                self.generate_tuple_literal_ir(elements, function)
+            }
+            Expression::TupleIndex { object, index } => {
+                let (tuple, tuple_type) = self.generate_expression_ir(*object, function);
+                if let Ty::Struct(struct_name) = &tuple_type {
+                    // Fields of a tuple struct are named by their position.
+                    let Some((field_index, field_type)) =
+                        self.struct_field(struct_name, &index.to_string())
+                    else {
+                        return (Value::ImmInt(0), Ty::Int);
+                    };
+                    let field_value =
+                        self.generate_field_load_ir(&tuple, struct_name, field_index, function);
+                    return (field_value, field_type);
+                }
+                let Ty::Tuple(elem_types) = &tuple_type else {
+                    return (Value::ImmInt(0), Ty::Int);
+                };
+                let Some(elem_ty) = elem_types.get(index).cloned() else {
+                    return (Value::ImmInt(0), Ty::Int);
+                };
+                let element =
+                    self.generate_tuple_element_load_ir(&tuple, &tuple_type, index, function);
+                (element, elem_ty)
             }
             Expression::Closure { params, body } => self.lower_closure_expression(params, *body),
         }
@@ -451,1493 +1048,6582 @@ impl IrGenerator {
         (promoted_lhs, promoted_rhs)
     }
 
-    fn try_constant_fold(
-        &self,
-        op: &str,
-        lhs: &Value,
-        rhs: &Value,
-        result_type: &Ty,
-    ) -> (Option<Value>, Option<Ty>) {
-        match (lhs, rhs, result_type) {
-            (Value::ImmInt(l), Value::ImmInt(r), Ty::Int) => {
-                let result = match op {
-                    "+" => l + r,
-                    "-" => l - r,
-                    "*" => l * r,
-                    "/" => l / r,
-                    _ => return (None, None),
-                };
-                (Some(Value::ImmInt(result)), Some(Ty::Int))
-            }
-            (Value::ImmFloat(l), Value::ImmFloat(r), Ty::Float) => {
-                let result = match op {
-                    "+" => l + r,
-                    "-" => l - r,
-                    "*" => l * r,
-                    "/" => l / r,
-                    _ => return (None, None),
-                };
-                (Some(Value::ImmFloat(result)), Some(Ty::Float))
-            }
-            _ => (None, None),
-        }
-    }
-
-    fn generate_function_definition_ir(
+    /// Lower a static to a module-level global. Strings have no global
+    /// representation yet and are inlined at each use instead.
+    fn generate_static_ir(
         &mut self,
         name: String,
-        parameters: Vec<crate::ast::Parameter>,
-        return_type: Option<Type>,
-        body: crate::ast::Block,
-        current_function: &mut Function,
+        mutable: bool,
+        type_annotation: Type,
+        value: Expression,
+        function: &mut Function,
     ) {
-        // Save current state
-        let saved_symbol_table = self.symbol_table.clone();
-        let saved_next_reg = self.next_reg;
-        let saved_next_ptr = self.next_ptr;
-
-        // Reset for function generation
-        self.symbol_table.clear();
-        self.next_reg = 0;
-        self.next_ptr = 0;
-
-        // Create parameter names and types for IR
-        let param_names: Vec<(String, String)> = parameters
-            .iter()
-            .map(|p| {
-                (
-                    p.name.clone(),
-                    match &p.param_type {
-                        Type::Named(name) => name.clone(),
-                        Type::Array(_, _) => "array".to_string(),
-                        Type::Tuple(_) => "tuple".to_string(),
-                        Type::Reference(_, mutable) => {
-                            if *mutable {
-                                "&mut".to_string()
-                            } else {
-                                "&".to_string()
-                            }
-                        }
-                        Type::Generic(name, _) => name.clone(),
-                    },
-                )
-            })
-            .collect();
-
-        // Set up parameter variables in symbol table
-        for param in &parameters {
-            let ptr_reg = Value::Reg(self.next_ptr);
-            self.next_ptr += 1;
-
-            // Convert AST Type to Ty
-            let param_type = self.ast_type_to_ty(&param.param_type);
-
-            self.symbol_table
-                .insert(param.name.clone(), (ptr_reg, param_type));
-        }
+        let static_type = self.ast_type_to_ty(&type_annotation);
+        let (initializer, array_len) = match value {
+            Expression::ArrayLiteral(elements) => {
+                let len = elements.len();
+                (elements, Some(len))
+            }
+            Expression::ArrayRepeat { value, count } => (vec![*value; count], Some(count)),
+            other => (vec![other], None),
+        };
 
-        // Generate function body IR
-        let mut function_ir = Function {
+        // Initializers are constant expressions, so they fold to immediates
+        // without emitting any instructions.
+        let mut scratch = Function {
             name: name.clone(),
             body: Vec::new(),
             next_reg: 0,
             next_ptr: 0,
         };
+        let values: Vec<Value> = initializer
+            .into_iter()
+            .map(|expr| self.generate_expression_ir(expr, &mut scratch).0)
+            .collect();
 
-        // Allocate parameters
-        for param in &parameters {
-            let (ptr_reg, _) = self.symbol_table.get(&param.name).unwrap().clone();
-            function_ir
-                .body
-                .push(Inst::Alloca(ptr_reg.clone(), param.name.clone()));
+        if let [Value::ImmString(_)] = values.as_slice() {
+            self.statics
+                .insert(name, (static_type, Some(values[0].clone())));
+            return;
         }
 
-        // Generate statements
-        for stmt in body.statements {
-            self.generate_statement_ir(stmt, &mut function_ir);
-        }
+        function.body.push(Inst::GlobalDef {
+            name: name.clone(),
+            values,
+            array_len,
+            mutable,
+        });
+        self.statics.insert(name, (static_type, None));
+    }
 
-        // Handle block expression (implicit return) or default return when needed.
-        if let Some(expr) = body.expression {
-            let (return_value, _) = self.generate_expression_ir(expr, &mut function_ir);
-            function_ir.body.push(Inst::Return(return_value));
-        } else if !function_ir
-            .body
-            .iter()
-            .any(|inst| matches!(inst, Inst::Return(_)))
-        {
-            // If no explicit return exists, emit a default scalar return.
-            // `None` return type is lowered as `void` in codegen.
-            function_ir.body.push(Inst::Return(Value::ImmInt(0)));
+    /// Resolve a static by name: take the global's address in the current
+    /// function, or return its inlined value.
+    fn generate_static_ref_ir(
+        &mut self,
+        name: &str,
+        function: &mut Function,
+    ) -> Option<(Value, Ty)> {
+        let (static_type, inlined) = self.statics.get(name)?.clone();
+        if let Some(value) = inlined {
+            return Some((value, static_type));
         }
+        let global_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::GlobalAddr {
+            result: global_ptr.clone(),
+            name: name.to_string(),
+        });
+        Some((global_ptr, static_type))
+    }
 
-        let ir_return_type = return_type.as_ref().map(|ty| self.ast_type_to_ir_name(ty));
+    /// Copy a struct by value into a freshly allocated slot and return the
+    /// pointer to the copy.
+    fn generate_struct_copy_ir(
+        &mut self,
+        source: Value,
+        struct_name: &str,
+        function: &mut Function,
+    ) -> Value {
+        let field_count = self
+            .struct_layouts
+            .get(struct_name)
+            .map_or(0, |layout| layout.len() as u32);
+        let copy_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::StructCopy {
+            result: copy_ptr.clone(),
+            source,
+            struct_type: struct_name.to_string(),
+            field_count,
+        });
+        copy_ptr
+    }
 
-        // Create function definition instruction
-        let func_def = Inst::FunctionDef {
-            name: name.clone(),
-            parameters: param_names,
-            return_type: ir_return_type,
-            body: function_ir.body.clone(),
+    /// Enum values are pointer-backed `{ tag, payload... }` structs: field 0
+    /// holds the variant index and fields 1.. the payload fields, if any.
+    fn generate_enum_variant_ir(
+        &mut self,
+        enum_name: String,
+        variant: String,
+        data: Option<Expression>,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let enum_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::AllocaStruct {
+            result: enum_ptr.clone(),
+            struct_type: enum_name.clone(),
+        });
+
+        let tag_ptr = self.generate_field_ptr_ir(&enum_ptr, &enum_name, 0, function);
+        let tag = self.variant_index(&enum_name, &variant);
+        function.body.push(Inst::Store(tag_ptr, Value::ImmInt(tag)));
+
+        // Multi-field tuple variants arrive as a tuple of their fields.
+        let declared_fields = self
+            .enum_variants
+            .get(&enum_name)
+            .and_then(|variants| variants.iter().find(|(v, _)| *v == variant))
+            .map_or(1, |(_, payload)| payload.len());
+        // Struct variants name their fields; each is stored in its declared
+        // payload slot, while still being evaluated in source order.
+        let fields: Vec<(usize, Expression)> = match data {
+            Some(Expression::StructLiteral { fields, .. }) => {
+                let names = self
+                    .variant_fields
+                    .get(&(enum_name.clone(), variant.clone()))
+                    .cloned()
+                    .unwrap_or_default();
+                fields
+                    .into_iter()
+                    .filter_map(|(name, value)| {
+                        names.iter().position(|n| *n == name).map(|i| (i, value))
+                    })
+                    .collect()
+            }
+            Some(Expression::TupleLiteral(elements)) if declared_fields > 1 => {
+                elements.into_iter().enumerate().collect()
+            }
+            Some(data) => vec![(0, data)],
+            None => Vec::new(),
         };
 
-        // Add function definition to current function (main)
-        current_function.body.push(func_def);
+        let mut payload_type = None;
+        for (index, field) in fields {
+            let (payload, field_type) = self.generate_expression_ir(field, function);
+            // Aggregates are stored as packed pointers; other pointer-backed
+            // values don't fit a double payload slot yet.
+            if Self::is_pointer_backed(&field_type) || !Self::stores_value_directly(&field_type) {
+                let payload = self.generate_value_to_slot_ir(payload, &field_type, function);
+                let payload_ptr =
+                    self.generate_field_ptr_ir(&enum_ptr, &enum_name, index as u32 + 1, function);
+                function.body.push(Inst::Store(payload_ptr, payload));
+            } else if field_type == Ty::String {
+                let payload = self.generate_string_slot_ir(payload, function);
+                let payload_ptr =
+                    self.generate_field_ptr_ir(&enum_ptr, &enum_name, index as u32 + 1, function);
+                function.body.push(Inst::Store(payload_ptr, payload));
+            }
+            payload_type.get_or_insert(field_type);
+        }
 
-        // Create and store function in functions map
-        let function = Function {
-            name: name.clone(),
-            body: vec![], // The actual body is in the FunctionDef instruction
-            next_reg: self.next_reg,
-            next_ptr: self.next_ptr,
+        let ty = match (enum_name.as_str(), variant.as_str()) {
+            ("Option", _) => Ty::Option(Box::new(payload_type.unwrap_or(Ty::Int))),
+            ("Result", "Ok") => Ty::Result(
+                Box::new(payload_type.unwrap_or(Ty::Int)),
+                Box::new(Ty::String),
+            ),
+            ("Result", _) => {
+                Ty::Result(Box::new(Ty::Int), Box::new(payload_type.unwrap_or(Ty::Int)))
+            }
+            _ => Ty::Enum(enum_name),
         };
-        self.functions.insert(name, function);
+        (enum_ptr, ty)
+    }
 
-        // Restore state
-        self.symbol_table = saved_symbol_table;
-        self.next_reg = saved_next_reg;
-        self.next_ptr = saved_next_ptr;
+    /// `"..".as_bytes()`: the literal's bytes as a constant global array,
+    /// emitted once per distinct literal and indexed like any other array.
+    fn generate_string_bytes_ir(&mut self, text: String, function: &mut Function) -> (Value, Ty) {
+        let len = text.len();
+        let name = match self.string_bytes.get(&text) {
+            Some(name) => name.clone(),
+            None => {
+                let name = format!("bytes.{}", self.string_bytes.len());
+                function.body.push(Inst::GlobalDef {
+                    name: name.clone(),
+                    values: text.bytes().map(|b| Value::ImmInt(b as i64)).collect(),
+                    array_len: Some(len),
+                    mutable: false,
+                });
+                self.string_bytes.insert(text, name.clone());
+                name
+            }
+        };
+        let bytes = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::GlobalAddr {
+            result: bytes.clone(),
+            name,
+        });
+        (bytes, Ty::Array(Box::new(Ty::Int), len))
     }
 
-    fn generate_statement_ir_for_function(
+    /// Emit a call whose arguments are already in slot form. Unit functions
+    /// lower to `void`, so the call produces no value.
+    fn generate_call_ir(
         &mut self,
-        stmt: Statement,
-        function_body: &mut Vec<Inst>,
-    ) {
-        match stmt {
-            Statement::Let {
-                name,
-                mutable: _,
-                type_annotation: _,
-                value,
-            } => {
-                let (expr_value, expr_type) = if let Some(val) = value {
-                    self.generate_expression_ir_for_function(val, function_body)
-                } else {
-                    (Value::ImmInt(0), Ty::Int)
-                };
+        function_name: String,
+        arguments: Vec<Value>,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let result_type = match self.function_returns.get(&function_name) {
+            Some(Ty::Unit) => {
+                function.body.push(Inst::Call {
+                    function: function_name,
+                    arguments,
+                    result: None,
+                });
+                return (Value::ImmInt(0), Ty::Unit);
+            }
+            Some(Ty::Float) => Ty::Float,
+            // Aggregates come back as their packed pointer.
+            Some(ty) if Self::is_pointer_backed(ty) => ty.clone(),
+            // Other results are treated as int until calls carry full types.
+            _ => Ty::Int,
+        };
 
-                if Self::stores_value_directly(&expr_type) {
-                    self.symbol_table.insert(name, (expr_value, expr_type));
-                } else {
-                    // Allocate a stack slot for the variable
-                    let ptr_reg = Value::Reg(self.next_ptr);
-                    self.next_ptr += 1;
-                    function_body.push(Inst::Alloca(ptr_reg.clone(), name.clone()));
-                    self.symbol_table.insert(name, (ptr_reg.clone(), expr_type));
+        let result_reg = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function.body.push(Inst::Call {
+            function: function_name,
+            arguments,
+            result: Some(result_reg.clone()),
+        });
+        let result = self.generate_slot_to_value_ir(result_reg, &result_type, function);
+        (result, result_type)
+    }
 
-                    // Store the expression result into the allocated slot
-                    function_body.push(Inst::Store(ptr_reg, expr_value));
+    /// A call to the builtin `min`, `max`, `abs` or `swap`, which functions
+    /// and variables of the program's own shadow.
+    fn is_builtin_call(&self, name: &str) -> bool {
+        matches!(name, "min" | "max" | "abs" | "swap")
+            && !self.function_returns.contains_key(name)
+            && !self.generic_functions.contains_key(name)
+            && !self.symbol_table.contains_key(name)
+    }
+
+    /// `swap(&mut a, &mut b)`. Scalars are loaded from both cells and each
+    /// stored into the other's. Bindings held by value are exchanged in the
+    /// symbol table instead, a struct's through a `StructCopy` temporary.
+    fn generate_swap_ir(
+        &mut self,
+        arguments: Vec<Expression>,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let borrowed: Vec<Option<(String, Value, Ty)>> = arguments
+            .iter()
+            .map(|argument| match argument {
+                Expression::Borrow { expr, .. } => match expr.as_ref() {
+                    Expression::Identifier(name) => self
+                        .symbol_table
+                        .get(name)
+                        .filter(|(_, ty)| {
+                            Self::stores_value_directly(ty) && !Self::is_scalar_reference(ty)
+                        })
+                        .map(|(value, ty)| (name.clone(), value.clone(), ty.clone())),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        if let [Some((left, left_value, ty)), Some((right, right_value, _))] = borrowed.as_slice() {
+            let left_value = match Self::aggregate_struct_type(ty) {
+                Some(struct_name) => {
+                    self.generate_struct_copy_ir(left_value.clone(), &struct_name, function)
                 }
-            }
-            Statement::Return(expr) => {
-                let (return_value, _) = if let Some(val) = expr {
-                    self.generate_expression_ir_for_function(val, function_body)
-                } else {
-                    (Value::ImmInt(0), Ty::Int)
-                };
-                function_body.push(Inst::Return(return_value));
-            }
-            Statement::Function { .. } => {
-                // Nested functions not supported yet
-                println!("Warning: Nested function definitions are not supported");
-            }
-            _ => {
-                // Other statements not implemented yet
-                println!("Warning: Statement type not yet implemented in function body");
-            }
+                None => left_value.clone(),
+            };
+            self.symbol_table
+                .insert(left.clone(), (right_value.clone(), ty.clone()));
+            self.symbol_table
+                .insert(right.clone(), (left_value, ty.clone()));
+            return (Value::ImmInt(0), Ty::Unit);
+        }
+
+        let cells: Vec<Value> = arguments
+            .into_iter()
+            .map(|argument| self.generate_expression_ir(argument, function).0)
+            .collect();
+        if let [left, right] = cells.as_slice() {
+            let loaded: Vec<Value> = [left, right]
+                .into_iter()
+                .map(|cell| {
+                    let value = Value::Reg(self.next_reg);
+                    self.next_reg += 1;
+                    function.body.push(Inst::Load(value.clone(), cell.clone()));
+                    value
+                })
+                .collect();
+            function
+                .body
+                .push(Inst::Store(left.clone(), loaded[1].clone()));
+            function
+                .body
+                .push(Inst::Store(right.clone(), loaded[0].clone()));
         }
+        (Value::ImmInt(0), Ty::Unit)
     }
 
-    fn generate_expression_ir_for_function(
+    /// `min`, `max` and `abs`. Floats use the LLVM intrinsics, while ints
+    /// compare and select; either folds when the operands are constants.
+    fn generate_numeric_builtin_ir(
         &mut self,
-        expr: Expression,
-        function_body: &mut Vec<Inst>,
+        name: &str,
+        operands: Vec<(Value, Ty)>,
+        function: &mut Function,
     ) -> (Value, Ty) {
-        match expr {
-            Expression::IntegerLiteral(n) => (Value::ImmInt(n), Ty::Int),
-            Expression::FloatLiteral(f) => (Value::ImmFloat(f), Ty::Float),
-            Expression::Identifier(name) => {
-                let (storage, var_type) = self
-                    .symbol_table
-                    .get(&name)
-                    .expect("Undeclared variable")
-                    .clone();
-                if Self::stores_value_directly(&var_type) {
-                    return (storage, var_type);
-                }
-                let result_reg = Value::Reg(self.next_reg);
-                self.next_reg += 1;
-                function_body.push(Inst::Load(result_reg.clone(), storage));
-                (result_reg, var_type)
+        let float = operands.iter().any(|(_, ty)| *ty == Ty::Float);
+        let values: Vec<Value> = operands.into_iter().map(|(value, _)| value).collect();
+
+        if float {
+            let constants: Option<Vec<f64>> = values
+                .iter()
+                .map(|value| match value {
+                    Value::ImmInt(n) => Some(*n as f64),
+                    Value::ImmFloat(f) => Some(*f),
+                    _ => None,
+                })
+                .collect();
+            let folded = match (name, constants.as_deref()) {
+                ("min", Some([l, r])) => Some(l.min(*r)),
+                ("max", Some([l, r])) => Some(l.max(*r)),
+                ("abs", Some([value])) => Some(value.abs()),
+                _ => None,
+            };
+            if let Some(folded) = folded {
+                return (Value::ImmFloat(folded), Ty::Float);
             }
-            Expression::Binary {
-                op,
-                left,
-                right,
-                ty,
-            } => {
-                let (lhs_val, lhs_type) =
-                    self.generate_expression_ir_for_function(*left, function_body);
-                let (rhs_val, rhs_type) =
-                    self.generate_expression_ir_for_function(*right, function_body);
+            let intrinsic = match name {
+                "min" => "llvm.minnum.f64",
+                "max" => "llvm.maxnum.f64",
+                _ => "llvm.fabs.f64",
+            };
+            let result = Value::Reg(self.next_reg);
+            self.next_reg += 1;
+            function.body.push(Inst::FloatIntrinsic {
+                result: result.clone(),
+                intrinsic: intrinsic.to_string(),
+                arguments: values,
+            });
+            return (result, Ty::Float);
+        }
 
-                // Prefer semantic type annotation when present, but don't require it.
-                // Some front-end paths still produce untyped binary nodes.
-                let result_type = ty.unwrap_or_else(|| match (&lhs_type, &rhs_type) {
-                    (Ty::Float, _) | (_, Ty::Float) => Ty::Float,
-                    (Ty::Int, Ty::Int) => Ty::Int,
-                    (l, r) => panic!(
-                        "Cannot infer binary op result type for operand types {:?} and {:?}",
-                        l, r
-                    ),
+        let (if_true, if_false, op, right) = match (name, values.as_slice()) {
+            ("abs", [value]) => {
+                let negated = match value {
+                    Value::ImmInt(n) => Value::ImmInt(n.wrapping_neg()),
+                    _ => {
+                        let negated = Value::Reg(self.next_reg);
+                        self.next_reg += 1;
+                        function.body.push(Inst::Neg {
+                            result: negated.clone(),
+                            operand: value.clone(),
+                        });
+                        negated
+                    }
+                };
+                (negated, value.clone(), "<", Value::ImmInt(0))
+            }
+            ("min", [l, r]) => (l.clone(), r.clone(), "<", r.clone()),
+            (_, [l, r]) => (l.clone(), r.clone(), ">", r.clone()),
+            _ => return (Value::ImmInt(0), Ty::Int), // Rejected by the semantic analyzer
+        };
+        let left = match name {
+            "abs" => if_false.clone(),
+            _ => if_true.clone(),
+        };
+        match self.generate_value_compare_ir(op, &left, &right, function) {
+            Value::ImmInt(holds) => (if holds != 0 { if_true } else { if_false }, Ty::Int),
+            condition => {
+                let result = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function.body.push(Inst::Select {
+                    result: result.clone(),
+                    condition,
+                    if_true,
+                    if_false,
                 });
+                (result, Ty::Int)
+            }
+        }
+    }
 
-                // Handle type promotion if needed
-                let (promoted_lhs, promoted_rhs) = self.handle_type_promotion_for_function(
-                    lhs_val,
-                    lhs_type,
-                    rhs_val,
-                    rhs_type,
-                    &result_type,
-                    function_body,
+    /// Symbol of the method `method` implemented for the receiver's type.
+    fn method_symbol(receiver: &Ty, method: &str) -> Option<String> {
+        match receiver {
+            Ty::Struct(name) | Ty::Enum(name) => Some(format!("{}.{}", name, method)),
+            Ty::Reference(inner, _) => Self::method_symbol(inner, method),
+            _ => None,
+        }
+    }
+
+    /// Lower the methods of an `impl` block as functions named `Type.method`,
+    /// adding any trait default methods the block does not override.
+    fn generate_impl_block_ir(
+        &mut self,
+        type_name: String,
+        methods: Vec<Statement>,
+        trait_name: Option<String>,
+        current_function: &mut Function,
+    ) {
+        let mut lowered = Vec::new();
+        for method in methods {
+            if let Statement::Function {
+                name,
+                parameters,
+                return_type,
+                body,
+                ..
+            } = method
+            {
+                lowered.push(name.clone());
+                self.generate_method_ir(
+                    &type_name,
+                    name,
+                    parameters,
+                    return_type,
+                    body,
+                    current_function,
                 );
+            }
+        }
 
-                // Try constant folding first
-                if let (Some(folded_value), Some(folded_type)) =
-                    self.try_constant_fold(op.as_str(), &promoted_lhs, &promoted_rhs, &result_type)
-                {
-                    return (folded_value, folded_type);
-                }
+        let Some(trait_name) = trait_name else {
+            return;
+        };
+        let trait_methods = self
+            .trait_methods
+            .get(&trait_name)
+            .cloned()
+            .unwrap_or_default();
+        let vtable = trait_methods
+            .iter()
+            .map(|method| format!("{}.{}", type_name, method.name))
+            .collect();
+        for method in trait_methods {
+            if lowered.contains(&method.name) {
+                continue;
+            }
+            if let Some(body) = method.body {
+                self.generate_method_ir(
+                    &type_name,
+                    method.name,
+                    method.parameters,
+                    method.return_type,
+                    body,
+                    current_function,
+                );
+            }
+        }
 
-                let result_reg = Value::Reg(self.next_reg);
-                self.next_reg += 1;
+        // Slots follow the trait's declaration order, so every vtable of the
+        // trait agrees on where a method lives.
+        current_function.body.push(Inst::VTableDef {
+            name: Self::vtable_name(&trait_name, &type_name),
+            functions: vtable,
+        });
+    }
 
-                // Generate the appropriate instruction based on result type
-                let inst = match (&result_type, op.as_str()) {
-                    (Ty::Int, "+") => Inst::Add(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Float, "+") => Inst::FAdd(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Int, "-") => Inst::Sub(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Float, "-") => Inst::FSub(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Int, "*") => Inst::Mul(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Float, "*") => Inst::FMul(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Int, "/") => Inst::Div(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    (Ty::Float, "/") => Inst::FDiv(result_reg.clone(), promoted_lhs, promoted_rhs),
-                    _ => panic!(
-                        "Unsupported binary operation: {} for type {:?}",
-                        op, result_type
-                    ),
-                };
+    fn vtable_name(trait_name: &str, type_name: &str) -> String {
+        format!("vtable.{}.{}", trait_name, type_name)
+    }
 
-                function_body.push(inst);
-                (result_reg, result_type)
-            }
-            Expression::FunctionCall { name, arguments } => {
-                self.generate_function_call_ir(name, arguments, function_body)
+    /// Array literal stored as doubles; pointer-backed elements are packed.
+    /// `elem_hint` is the element type the destination expects, if known.
+    fn generate_array_literal_ir(
+        &mut self,
+        elements: Vec<Expression>,
+        elem_hint: Option<&Ty>,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let count = elements.len();
+        let arr_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        let mut elem_type = Ty::Int;
+        for (i, elem) in elements.into_iter().enumerate() {
+            let (val, ty) = self.generate_expression_ir_as(elem, elem_hint, function);
+            // Element type is determined by the first element
+            if i == 0 {
+                function.body.push(Inst::AllocaArray {
+                    result: arr_ptr.clone(),
+                    elem_type: "double".to_string(),
+                    count,
+                });
+                elem_type = ty.clone();
             }
-            Expression::Print {
-                format_string,
-                arguments,
-            } => {
-                self.generate_print_ir_for_function(format_string, arguments, false, function_body)
-            }
-            Expression::Println {
-                format_string,
-                arguments,
-            } => self.generate_print_ir_for_function(format_string, arguments, true, function_body),
-            Expression::Comparison { op, left, right } => {
-                self.generate_comparison_ir_for_function(op, *left, *right, function_body)
-            }
-            Expression::Logical { op, left, right } => {
-                self.generate_logical_ir_for_function(op, *left, *right, function_body)
-            }
-            Expression::Unary { op, operand } => {
-                self.generate_unary_ir_for_function(op, *operand, function_body)
-            }
-            // Phase 4 stubs for function-level IR
-            Expression::StringLiteral(s) => (Value::ImmString(s), Ty::String),
-            Expression::MethodCall {
-                object,
-                method,
-                arguments,
-            } => {
-                let (object_value, object_ty) =
-                    self.generate_expression_ir_for_function(*object, function_body);
-                if method == "iter"
-                    && arguments.is_empty()
-                    && matches!(object_ty, Ty::Array(_, _) | Ty::Vec(_))
-                {
-                    (object_value, object_ty)
-                } else {
-                    (Value::ImmInt(0), Ty::Int)
-                }
-            }
-            Expression::ArrayLiteral(_)
-            | Expression::ArrayRepeat { .. }
-            | Expression::IndexAccess { .. }
-            | Expression::FieldAccess { .. }
-            | Expression::TupleLiteral(_)
-            | Expression::TupleIndex { .. }
-            | Expression::StructLiteral { .. }
-            | Expression::EnumVariant { .. }
-            | Expression::Match { .. }
-            | Expression::Borrow { .. }
-            | Expression::Deref(_) => (Value::ImmInt(0), Ty::Int),
-            Expression::Closure { params, body } => self.lower_closure_expression(params, *body),
+            let val = self.generate_value_to_slot_ir(val, &ty, function);
+            let elem_ptr = Value::Reg(self.next_ptr);
+            self.next_ptr += 1;
+            function.body.push(Inst::GetElementPtr {
+                result: elem_ptr.clone(),
+                base: arr_ptr.clone(),
+                index: Value::ImmInt(i as i64),
+                elem_type: format!("[{} x double]", count),
+            });
+            function.body.push(Inst::Store(elem_ptr, val));
+        }
+        if count == 0 {
+            function.body.push(Inst::AllocaArray {
+                result: arr_ptr.clone(),
+                elem_type: "double".to_string(),
+                count: 0,
+            });
         }
+        (arr_ptr, Ty::Array(Box::new(elem_type), count))
     }
 
-    fn handle_type_promotion_for_function(
+    /// Convert a value to the type expected by its destination: ints are
+    /// promoted to float, and a struct or enum is unsized into a `dyn Trait`.
+    fn generate_coercion_ir(
         &mut self,
-        lhs_val: Value,
-        lhs_type: Ty,
-        rhs_val: Value,
-        rhs_type: Ty,
-        target_type: &Ty,
-        function_body: &mut Vec<Inst>,
-    ) -> (Value, Value) {
-        let promoted_lhs = if needs_promotion(&lhs_type, target_type) {
+        value: Value,
+        ty: Ty,
+        expected: &Ty,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        // Nothing reaches past a diverging expression, so it takes any type.
+        if ty == Ty::Never {
+            return (value, expected.clone());
+        }
+        if needs_promotion(&ty, expected) {
             let promoted_reg = Value::Reg(self.next_reg);
             self.next_reg += 1;
-            function_body.push(Inst::SIToFP(promoted_reg.clone(), lhs_val));
-            promoted_reg
-        } else {
-            lhs_val
+            function
+                .body
+                .push(Inst::SIToFP(promoted_reg.clone(), value));
+            return (promoted_reg, Ty::Float);
+        }
+        // Both integer types are held the same way, so widening only retypes.
+        if (&ty, expected) == (&Ty::Int, &Ty::Int64) {
+            return (value, Ty::Int64);
+        }
+        let (Ty::Struct(type_name) | Ty::Enum(type_name), Ty::TraitObject(trait_name)) =
+            (&ty, expected)
+        else {
+            return (value, ty);
         };
 
-        let promoted_rhs = if needs_promotion(&rhs_type, target_type) {
-            let promoted_reg = Value::Reg(self.next_reg);
-            self.next_reg += 1;
-            function_body.push(Inst::SIToFP(promoted_reg.clone(), rhs_val));
-            promoted_reg
-        } else {
-            rhs_val
-        };
+        // Fat pointer: field 0 = data pointer, field 1 = vtable pointer.
+        let object_type = expected.clone();
+        let struct_type = Self::aggregate_struct_type(&object_type).unwrap_or_default();
+        let object = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::AllocaStruct {
+            result: object.clone(),
+            struct_type: struct_type.clone(),
+        });
+        let data = self.generate_value_to_slot_ir(value, &ty, function);
+        let data_ptr = self.generate_field_ptr_ir(&object, &struct_type, 0, function);
+        function.body.push(Inst::Store(data_ptr, data));
+        let vtable = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function.body.push(Inst::VTableAddr {
+            result: vtable.clone(),
+            name: Self::vtable_name(trait_name, type_name),
+        });
+        let vtable_ptr = self.generate_field_ptr_ir(&object, &struct_type, 1, function);
+        function.body.push(Inst::Store(vtable_ptr, vtable));
+        (object, object_type)
+    }
 
-        (promoted_lhs, promoted_rhs)
+    /// Lower `expr` for a destination of type `expected` (a let annotation
+    /// or parameter), applying coercions, including to array elements.
+    fn generate_expression_ir_as(
+        &mut self,
+        expr: Expression,
+        expected: Option<&Ty>,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let Some(expected) = expected else {
+            return self.generate_expression_ir(expr, function);
+        };
+        match (expr, expected) {
+            (Expression::ArrayLiteral(elements), Ty::Array(elem, _) | Ty::Vec(elem)) => {
+                self.generate_array_literal_ir(elements, Some(elem), function)
+            }
+            (expr, expected) => {
+                let (value, ty) = self.generate_expression_ir(expr, function);
+                self.generate_coercion_ir(value, ty, expected, function)
+            }
+        }
     }
 
-    fn generate_function_call_ir(
+    /// Dispatch `method` on a trait object through its vtable.
+    fn generate_dynamic_call_ir(
         &mut self,
-        name: String,
+        object: Value,
+        trait_name: &str,
+        method: &str,
         arguments: Vec<Expression>,
-        function_body: &mut Vec<Inst>,
+        function: &mut Function,
     ) -> (Value, Ty) {
-        // Generate IR for arguments
-        let mut arg_values = Vec::new();
+        let trait_methods = self
+            .trait_methods
+            .get(trait_name)
+            .cloned()
+            .unwrap_or_default();
+        let Some(slot) = trait_methods.iter().position(|m| m.name == method) else {
+            return (Value::ImmInt(0), Ty::Int);
+        };
+        let signature = &trait_methods[slot];
+
+        let struct_type = Self::aggregate_struct_type(&Ty::TraitObject(trait_name.to_string()))
+            .unwrap_or_default();
+        let data = self.generate_field_load_ir(&object, &struct_type, 0, function);
+        let vtable = self.generate_field_load_ir(&object, &struct_type, 1, function);
+        let mut arg_values = vec![data];
         for arg in arguments {
-            let (arg_value, _) = self.generate_expression_ir_for_function(arg, function_body);
-            arg_values.push(arg_value);
+            let (arg_value, arg_type) = self.generate_expression_ir(arg, function);
+            arg_values.push(self.generate_value_to_slot_ir(arg_value, &arg_type, function));
         }
 
-        // Generate result register for function call
-        let result_reg = Value::Reg(self.next_reg);
-        self.next_reg += 1;
-
-        // Resolve closure variables to their generated function symbol.
-        let function_name = self.resolve_callable_name(&name);
-
-        // Create function call instruction
-        let call_inst = Inst::Call {
-            function: function_name,
-            arguments: arg_values,
-            result: Some(result_reg.clone()),
+        let parameters = signature
+            .parameters
+            .iter()
+            .map(|param| Self::ir_param_type_name(&param.param_type))
+            .collect();
+        let return_ty = signature
+            .return_type
+            .as_ref()
+            .map_or(Ty::Unit, |ty| self.ast_type_to_ty(ty));
+        let return_type = signature
+            .return_type
+            .as_ref()
+            .map(|ty| self.ast_type_to_ir_name(ty));
+        let (result, result_type) = match return_ty {
+            Ty::Unit => (None, Ty::Unit),
+            Ty::Float => (Some(Value::Reg(self.next_reg)), Ty::Float),
+            // Other results are treated as int until calls carry full types.
+            _ => (Some(Value::Reg(self.next_reg)), Ty::Int),
         };
-
-        function_body.push(call_inst);
-
-        // For now, assume function calls return int (this should be looked up from function table in semantic analysis)
-        (result_reg, Ty::Int)
+        if result.is_some() {
+            self.next_reg += 1;
+        }
+        function.body.push(Inst::CallIndirect {
+            vtable,
+            slot: slot as u32,
+            parameters,
+            return_type,
+            arguments: arg_values,
+            result: result.clone(),
+        });
+        (result.unwrap_or(Value::ImmInt(0)), result_type)
     }
 
-    // Control flow IR generation methods
-    fn generate_if_statement_ir(
+    fn generate_method_ir(
         &mut self,
-        condition: Expression,
-        then_block: crate::ast::Block,
-        else_block: Option<Box<Statement>>,
+        type_name: &str,
+        name: String,
+        parameters: Vec<Parameter>,
+        return_type: Option<Type>,
+        body: Block,
         current_function: &mut Function,
     ) {
-        // Generate condition evaluation
-        let (cond_value, _) = self.generate_expression_ir(condition, current_function);
+        let symbol = format!("{}.{}", type_name, name);
+        if parameters
+            .iter()
+            .any(|param| param.name == "self" && !matches!(param.param_type, Type::Reference(..)))
+        {
+            self.by_value_receivers.insert(symbol.clone());
+        }
+        let substitution =
+            HashMap::from([("Self".to_string(), Type::Named(type_name.to_string()))]);
+        let parameters = parameters
+            .into_iter()
+            .map(|param| {
+                // Aggregates are passed by pointer anyway, so `&self` and
+                // `self` share one representation; callers copy for `self`.
+                let param_type = match param.param_type {
+                    Type::Reference(inner, _) if param.name == "self" => {
+                        Self::substitute_type(&inner, &substitution, &HashMap::new())
+                    }
+                    other => Self::substitute_type(&other, &substitution, &HashMap::new()),
+                };
+                Parameter {
+                    name: param.name,
+                    param_type,
+                }
+            })
+            .collect();
+        let return_type =
+            return_type.map(|ty| Self::substitute_type(&ty, &substitution, &HashMap::new()));
+        self.generate_function_definition_ir(
+            symbol,
+            parameters,
+            return_type,
+            body,
+            current_function,
+        );
+    }
 
-        // Generate unique labels
-        let then_label = format!("if_then_{}", self.next_reg);
-        self.next_reg += 1;
-        let else_label = format!("if_else_{}", self.next_reg);
-        self.next_reg += 1;
-        let end_label = format!("if_end_{}", self.next_reg);
-        self.next_reg += 1;
+    /// Monomorphize a generic function for the argument types of a call,
+    /// lowering each distinct instantiation once as `name.Type1.Type2`
+    /// (const array lengths follow the types, e.g. `sum.3`).
+    fn instantiate_generic_function(&mut self, name: &str, arg_types: &[Ty]) -> String {
+        let generic = self.generic_functions[name].clone();
+        let mut bindings = HashMap::new();
+        let mut lengths = HashMap::new();
+        for (param, arg_type) in generic.parameters.iter().zip(arg_types) {
+            Self::bind_type_params(
+                &param.param_type,
+                arg_type,
+                &generic.type_params,
+                &mut bindings,
+                &mut lengths,
+            );
+        }
 
-        // Branch based on condition
-        current_function.body.push(Inst::Branch {
-            condition: cond_value,
-            true_label: then_label.clone(),
-            false_label: else_label.clone(),
-        });
+        let mut suffix: Vec<String> = generic
+            .type_params
+            .iter()
+            .map(|param| {
+                bindings.get(param).map_or("_".to_string(), |ty| {
+                    ty.to_string()
+                        .chars()
+                        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                        .collect()
+                })
+            })
+            .collect();
+        suffix.extend(generic.const_params.iter().map(|param| {
+            lengths
+                .get(param)
+                .map_or("_".to_string(), |len| len.to_string())
+        }));
+        let instance = format!("{}.{}", name, suffix.join("."));
 
-        // Generate then block
-        current_function.body.push(Inst::Label(then_label));
-        for stmt in then_block.statements {
-            self.generate_statement_ir(stmt, current_function);
+        // Recorded on entry to the definition, which also stops recursion.
+        if !self.function_returns.contains_key(&instance) {
+            let substitution: HashMap<String, Type> = bindings
+                .iter()
+                .map(|(param, ty)| (param.clone(), Self::ty_to_ast_type(ty)))
+                .collect();
+            let parameters = generic
+                .parameters
+                .iter()
+                .map(|param| Parameter {
+                    name: param.name.clone(),
+                    param_type: Self::substitute_type(&param.param_type, &substitution, &lengths),
+                })
+                .collect();
+            let return_type = generic
+                .return_type
+                .as_ref()
+                .map(|ty| Self::substitute_type(ty, &substitution, &lengths));
+            // Each const parameter is an ordinary immutable binding in the body.
+            let mut body = generic.body;
+            for param in generic.const_params.iter().rev() {
+                let len = lengths.get(param).copied().unwrap_or(0);
+                body.statements.insert(
+                    0,
+                    Statement::Let {
+                        name: param.clone(),
+                        mutable: false,
+                        type_annotation: None,
+                        value: Some(Expression::IntegerLiteral(len as i64)),
+                    },
+                );
+            }
+            // Instantiation happens mid-body, so the definition is held by
+            // its own function (like closures) rather than the caller's.
+            let mut holder = Function {
+                name: instance.clone(),
+                body: Vec::new(),
+                next_reg: 0,
+                next_ptr: 0,
+            };
+            self.generate_function_definition_ir(
+                instance.clone(),
+                parameters,
+                return_type,
+                body,
+                &mut holder,
+            );
+            if let Some(defined) = self.functions.get_mut(&instance) {
+                defined.body = holder.body;
+            }
         }
-        if let Some(expr) = then_block.expression {
-            self.generate_expression_ir(expr, current_function);
+        instance
+    }
+
+    /// Infer type parameter bindings (and const array lengths) by matching a
+    /// declared parameter type against the type of the argument passed for it.
+    fn bind_type_params(
+        declared: &Type,
+        actual: &Ty,
+        type_params: &[String],
+        bindings: &mut HashMap<String, Ty>,
+        lengths: &mut HashMap<String, usize>,
+    ) {
+        match (declared, actual) {
+            (Type::Named(name), _) if type_params.contains(name) => {
+                bindings
+                    .entry(name.clone())
+                    .or_insert_with(|| actual.clone());
+            }
+            (Type::Reference(inner, _), Ty::Reference(actual, _)) => {
+                Self::bind_type_params(inner, actual, type_params, bindings, lengths)
+            }
+            (Type::Reference(inner, _), _) => {
+                Self::bind_type_params(inner, actual, type_params, bindings, lengths)
+            }
+            (Type::Array(inner, _), Ty::Array(actual, _)) => {
+                Self::bind_type_params(inner, actual, type_params, bindings, lengths)
+            }
+            (Type::ConstArray(inner, param), Ty::Array(actual, len)) => {
+                lengths.entry(param.clone()).or_insert(*len);
+                Self::bind_type_params(inner, actual, type_params, bindings, lengths)
+            }
+            (Type::Tuple(declared), Ty::Tuple(actual)) => {
+                for (declared, actual) in declared.iter().zip(actual) {
+                    Self::bind_type_params(declared, actual, type_params, bindings, lengths);
+                }
+            }
+            (Type::Generic(_, args), Ty::Option(actual) | Ty::Vec(actual) | Ty::Box(actual)) => {
+                if let Some(arg) = args.first() {
+                    Self::bind_type_params(arg, actual, type_params, bindings, lengths);
+                }
+            }
+            _ => {}
         }
-        current_function.body.push(Inst::Jump(end_label.clone()));
+    }
 
-        // Generate else block
-        current_function.body.push(Inst::Label(else_label));
-        if let Some(else_stmt) = else_block {
-            self.generate_statement_ir(*else_stmt, current_function);
+    /// Replace named types (type parameters, `Self`) according to `substitution`,
+    /// and const array lengths according to `lengths`.
+    fn substitute_type(
+        ty: &Type,
+        substitution: &HashMap<String, Type>,
+        lengths: &HashMap<String, usize>,
+    ) -> Type {
+        match ty {
+            Type::Named(name) => substitution
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| ty.clone()),
+            Type::Array(inner, size) => Type::Array(
+                Box::new(Self::substitute_type(inner, substitution, lengths)),
+                *size,
+            ),
+            Type::ConstArray(inner, param) => {
+                let inner = Box::new(Self::substitute_type(inner, substitution, lengths));
+                match lengths.get(param) {
+                    Some(len) => Type::Array(inner, *len),
+                    None => Type::ConstArray(inner, param.clone()),
+                }
+            }
+            Type::Tuple(types) => Type::Tuple(
+                types
+                    .iter()
+                    .map(|ty| Self::substitute_type(ty, substitution, lengths))
+                    .collect(),
+            ),
+            Type::Reference(inner, mutable) => Type::Reference(
+                Box::new(Self::substitute_type(inner, substitution, lengths)),
+                *mutable,
+            ),
+            Type::Generic(name, args) => Type::Generic(
+                name.clone(),
+                args.iter()
+                    .map(|ty| Self::substitute_type(ty, substitution, lengths))
+                    .collect(),
+            ),
+            Type::TraitObject(_) | Type::ImplTrait(_) => ty.clone(),
         }
-        current_function.body.push(Inst::Jump(end_label.clone()));
+    }
 
-        // End label
-        current_function.body.push(Inst::Label(end_label));
+    /// The source-level spelling of a resolved type, used to instantiate generics.
+    pub(crate) fn ty_to_ast_type(ty: &Ty) -> Type {
+        let generic = |name: &str, args: Vec<&Ty>| {
+            Type::Generic(
+                name.to_string(),
+                args.into_iter().map(Self::ty_to_ast_type).collect(),
+            )
+        };
+        match ty {
+            Ty::Int => Type::Named("i32".to_string()),
+            Ty::Int64 => Type::Named("i64".to_string()),
+            Ty::Float => Type::Named("f64".to_string()),
+            Ty::Bool => Type::Named("bool".to_string()),
+            Ty::Char => Type::Named("char".to_string()),
+            Ty::String => Type::Named("String".to_string()),
+            Ty::Struct(name) | Ty::Enum(name) | Ty::TypeParam(name) | Ty::Fn(name) => {
+                Type::Named(name.clone())
+            }
+            Ty::TraitObject(name) => Type::TraitObject(name.clone()),
+            Ty::Array(elem, size) => Type::Array(Box::new(Self::ty_to_ast_type(elem)), *size),
+            Ty::Tuple(elems) => Type::Tuple(elems.iter().map(Self::ty_to_ast_type).collect()),
+            Ty::Unit => Type::Tuple(Vec::new()),
+            Ty::Never => Type::Named("!".to_string()),
+            Ty::Reference(inner, mutable) => {
+                Type::Reference(Box::new(Self::ty_to_ast_type(inner)), *mutable)
+            }
+            Ty::Option(inner) => generic("Option", vec![inner]),
+            Ty::Result(ok, err) => generic("Result", vec![ok, err]),
+            Ty::Vec(elem) => generic("Vec", vec![elem]),
+            Ty::HashMap(key, value) => generic("HashMap", vec![key, value]),
+            Ty::Box(inner) => generic("Box", vec![inner]),
+            Ty::Iter(elem, _) => generic("Iter", vec![elem]),
+            Ty::Enumerate(source) => generic("Enumerate", vec![source]),
+            Ty::Zip(a, b) => generic("Zip", vec![a, b]),
+            Ty::Take(source) => generic("Take", vec![source]),
+        }
     }
 
-    fn generate_while_loop_ir(
+    /// `collection.get(index)`: a bounds-checked read producing `Option<T>`.
+    /// Unlike indexing, an out-of-range index yields `None` without reading.
+    fn generate_collection_get_ir(
         &mut self,
-        condition: Expression,
-        body: crate::ast::Block,
-        current_function: &mut Function,
-    ) {
-        // Generate unique labels
-        let loop_start = format!("while_start_{}", self.next_reg);
-        self.next_reg += 1;
-        let loop_body = format!("while_body_{}", self.next_reg);
-        self.next_reg += 1;
-        let loop_end = format!("while_end_{}", self.next_reg);
-        self.next_reg += 1;
+        collection: Value,
+        elem_ty: Ty,
+        len: usize,
+        index: Expression,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let (index, _) = self.generate_expression_ir(index, function);
 
-        // Push loop labels onto stack for break/continue
-        self.loop_label_stack
-            .push((loop_start.clone(), loop_end.clone()));
+        let option_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::AllocaStruct {
+            result: option_ptr.clone(),
+            struct_type: "Option".to_string(),
+        });
 
-        // Jump to loop start
-        current_function.body.push(Inst::Jump(loop_start.clone()));
+        let non_negative =
+            self.generate_value_compare_ir(">=", &index, &Value::ImmInt(0), function);
+        let below_len =
+            self.generate_value_compare_ir("<", &index, &Value::ImmInt(len as i64), function);
+        let in_bounds = self.generate_and_ir(non_negative, below_len, function);
 
-        // Loop start - evaluate condition
-        current_function.body.push(Inst::Label(loop_start.clone()));
-        let (cond_value, _) = self.generate_expression_ir(condition, current_function);
-        current_function.body.push(Inst::Branch {
-            condition: cond_value,
-            true_label: loop_body.clone(),
-            false_label: loop_end.clone(),
-        });
+        let get_id = self.next_reg;
+        self.next_reg += 1;
+        let some_label = format!("get_some_{}", get_id);
+        let none_label = format!("get_none_{}", get_id);
+        let end_label = format!("get_end_{}", get_id);
+        let (emit_some, emit_none) = match in_bounds {
+            Value::ImmInt(0) => (false, true),
+            Value::ImmInt(_) => (true, false),
+            condition => {
+                function.body.push(Inst::Branch {
+                    condition,
+                    true_label: some_label.clone(),
+                    false_label: none_label.clone(),
+                });
+                function.body.push(Inst::Label(some_label));
+                (true, true)
+            }
+        };
 
-        // Loop body
-        current_function.body.push(Inst::Label(loop_body));
-        for stmt in body.statements {
-            self.generate_statement_ir(stmt, current_function);
+        if emit_some {
+            let tag_ptr = self.generate_field_ptr_ir(&option_ptr, "Option", 0, function);
+            function.body.push(Inst::Store(tag_ptr, Value::ImmInt(1)));
+            let elem_ptr = Value::Reg(self.next_ptr);
+            self.next_ptr += 1;
+            function.body.push(Inst::GetElementPtr {
+                result: elem_ptr.clone(),
+                base: collection,
+                index,
+                elem_type: format!("[{} x double]", len),
+            });
+            let element = Value::Reg(self.next_reg);
+            self.next_reg += 1;
+            function.body.push(Inst::Load(element.clone(), elem_ptr));
+            // The element is already in slot form.
+            let payload_ptr = self.generate_field_ptr_ir(&option_ptr, "Option", 1, function);
+            function.body.push(Inst::Store(payload_ptr, element));
         }
-        if let Some(expr) = body.expression {
-            self.generate_expression_ir(expr, current_function);
+        if emit_some && emit_none {
+            function.body.push(Inst::Jump(end_label.clone()));
+            function.body.push(Inst::Label(none_label));
+        }
+        if emit_none {
+            let tag_ptr = self.generate_field_ptr_ir(&option_ptr, "Option", 0, function);
+            function.body.push(Inst::Store(tag_ptr, Value::ImmInt(0)));
+        }
+        if emit_some && emit_none {
+            function.body.push(Inst::Jump(end_label.clone()));
+            function.body.push(Inst::Label(end_label));
         }
-        current_function.body.push(Inst::Jump(loop_start));
 
-        // Pop loop labels
-        self.loop_label_stack.pop();
+        (option_ptr, Ty::Option(Box::new(elem_ty)))
+    }
 
-        // Loop end
-        current_function.body.push(Inst::Label(loop_end));
+    /// `collection.iter()`: a `{ collection, index }` cursor, stored as an
+    /// `ITER_STRUCT`, that `next()` reads through and advances.
+    fn generate_iter_ir(
+        &mut self,
+        collection: Value,
+        elem_ty: Ty,
+        len: usize,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let iter_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::AllocaStruct {
+            result: iter_ptr.clone(),
+            struct_type: ITER_STRUCT.to_string(),
+        });
+        let collection_ty = Ty::Array(Box::new(elem_ty.clone()), len);
+        let packed = self.generate_value_to_slot_ir(collection, &collection_ty, function);
+        let collection_ptr = self.generate_field_ptr_ir(&iter_ptr, ITER_STRUCT, 0, function);
+        function.body.push(Inst::Store(collection_ptr, packed));
+        let index_ptr = self.generate_field_ptr_ir(&iter_ptr, ITER_STRUCT, 1, function);
+        function.body.push(Inst::Store(index_ptr, Value::ImmInt(0)));
+        (iter_ptr, Ty::Iter(Box::new(elem_ty), len))
     }
 
-    fn generate_for_loop_ir(
+    /// `iterator.enumerate()`, `.zip(other)` or `.take(n)`: an adapter over
+    /// the packed source iterator that `next()` reads through.
+    fn generate_iter_adapter_ir(
         &mut self,
-        variable: String,
-        iterable: Expression,
-        body: crate::ast::Block,
-        current_function: &mut Function,
-    ) {
-        let (iter_value, iter_type) = self.generate_expression_ir(iterable, current_function);
-        match iter_type {
-            Ty::Array(elem_ty, len) => {
-                self.generate_array_for_loop_ir(
-                    variable,
-                    iter_value,
-                    *elem_ty,
-                    len,
-                    body,
-                    current_function,
-                );
-            }
-            other => {
-                // Preserve the legacy numeric lowering behavior for non-array iterables.
-                self.generate_legacy_for_loop_ir(
-                    variable,
-                    iter_value,
-                    other,
-                    body,
-                    current_function,
-                );
-            }
+        source: Value,
+        source_ty: Ty,
+        method: &str,
+        arguments: Vec<Expression>,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let mut operands = Vec::new();
+        for argument in arguments {
+            let (value, ty) = self.generate_expression_ir(argument, function);
+            operands.push(self.generate_auto_deref_ir(value, ty, function));
         }
+        let source_box = Box::new(source_ty.clone());
+        let (adapter_ty, (state, state_ty)) = match (method, operands.pop()) {
+            ("zip", Some((other, other_ty))) => (
+                Ty::Zip(source_box, Box::new(other_ty.clone())),
+                (other, other_ty),
+            ),
+            ("take", Some(count)) => (Ty::Take(source_box), count),
+            _ => (Ty::Enumerate(source_box), (Value::ImmInt(0), Ty::Int)),
+        };
+        let struct_type = Self::aggregate_struct_type(&adapter_ty).unwrap_or_default();
+
+        let adapter_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::AllocaStruct {
+            result: adapter_ptr.clone(),
+            struct_type: struct_type.clone(),
+        });
+        let packed = self.generate_value_to_slot_ir(source, &source_ty, function);
+        let source_ptr = self.generate_field_ptr_ir(&adapter_ptr, &struct_type, 0, function);
+        function.body.push(Inst::Store(source_ptr, packed));
+        let state = self.generate_value_to_slot_ir(state, &state_ty, function);
+        let state_ptr = self.generate_field_ptr_ir(&adapter_ptr, &struct_type, 1, function);
+        function.body.push(Inst::Store(state_ptr, state));
+        (adapter_ptr, adapter_ty)
     }
 
-    fn generate_array_for_loop_ir(
+    /// `iter.next()`: write `Some(item)` into `option_ptr` and advance `iter`,
+    /// or `None` once it is exhausted. Adapters advance their sources through
+    /// the same `option_ptr`, then rewrite its payload.
+    fn generate_iter_next_ir(
         &mut self,
-        variable: String,
-        array_ptr: Value,
-        element_ty: Ty,
-        array_len: usize,
-        body: crate::ast::Block,
-        current_function: &mut Function,
+        iter: &Value,
+        iter_ty: &Ty,
+        option_ptr: &Value,
+        function: &mut Function,
     ) {
-        let loop_start = format!("for_start_{}", self.next_reg);
-        self.next_reg += 1;
-        let loop_body = format!("for_body_{}", self.next_reg);
-        self.next_reg += 1;
-        let loop_end = format!("for_end_{}", self.next_reg);
+        let next_id = self.next_reg;
         self.next_reg += 1;
+        let end_label = format!("adapter_end_{}", next_id);
+        match iter_ty {
+            Ty::Iter(_, len) => {
+                self.generate_cursor_next_ir(iter, *len, option_ptr, function);
+                return;
+            }
+            Ty::Enumerate(source_ty) => {
+                let source =
+                    self.generate_adapter_source_ir(iter, ENUMERATE_STRUCT, 0, source_ty, function);
+                self.generate_iter_next_ir(&source, source_ty, option_ptr, function);
+                let some_label = format!("enumerate_some_{}", next_id);
+                self.generate_branch_on_some_ir(option_ptr, &some_label, &end_label, function);
+
+                function.body.push(Inst::Label(some_label));
+                let item = self.generate_field_load_ir(option_ptr, "Option", 1, function);
+                let index_ptr = self.generate_field_ptr_ir(iter, ENUMERATE_STRUCT, 1, function);
+                let index = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function
+                    .body
+                    .push(Inst::Load(index.clone(), index_ptr.clone()));
+                let pair_ty = iter_ty.iterator_item().unwrap_or(Ty::Unit);
+                self.generate_pair_payload_ir(index.clone(), item, &pair_ty, option_ptr, function);
+                let next_index = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function
+                    .body
+                    .push(Inst::Add(next_index.clone(), index, Value::ImmInt(1)));
+                function.body.push(Inst::Store(index_ptr, next_index));
+            }
+            Ty::Zip(a_ty, b_ty) => {
+                let a = self.generate_adapter_source_ir(iter, ZIP_STRUCT, 0, a_ty, function);
+                self.generate_iter_next_ir(&a, a_ty, option_ptr, function);
+                let a_some_label = format!("zip_a_some_{}", next_id);
+                self.generate_branch_on_some_ir(option_ptr, &a_some_label, &end_label, function);
+
+                function.body.push(Inst::Label(a_some_label));
+                let a_item = self.generate_field_load_ir(option_ptr, "Option", 1, function);
+                let b = self.generate_adapter_source_ir(iter, ZIP_STRUCT, 1, b_ty, function);
+                self.generate_iter_next_ir(&b, b_ty, option_ptr, function);
+                let b_some_label = format!("zip_b_some_{}", next_id);
+                self.generate_branch_on_some_ir(option_ptr, &b_some_label, &end_label, function);
+
+                function.body.push(Inst::Label(b_some_label));
+                let b_item = self.generate_field_load_ir(option_ptr, "Option", 1, function);
+                let pair_ty = iter_ty.iterator_item().unwrap_or(Ty::Unit);
+                self.generate_pair_payload_ir(a_item, b_item, &pair_ty, option_ptr, function);
+            }
+            Ty::Take(source_ty) => {
+                let remaining_ptr = self.generate_field_ptr_ir(iter, TAKE_STRUCT, 1, function);
+                let remaining = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function
+                    .body
+                    .push(Inst::Load(remaining.clone(), remaining_ptr.clone()));
+                let any_left =
+                    self.generate_value_compare_ir(">", &remaining, &Value::ImmInt(0), function);
+                let some_label = format!("take_some_{}", next_id);
+                let none_label = format!("take_none_{}", next_id);
+                function.body.push(Inst::Branch {
+                    condition: any_left,
+                    true_label: some_label.clone(),
+                    false_label: none_label.clone(),
+                });
 
-        self.loop_label_stack
-            .push((loop_start.clone(), loop_end.clone()));
+                function.body.push(Inst::Label(some_label));
+                let left = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function
+                    .body
+                    .push(Inst::Sub(left.clone(), remaining, Value::ImmInt(1)));
+                function.body.push(Inst::Store(remaining_ptr, left));
+                let source =
+                    self.generate_adapter_source_ir(iter, TAKE_STRUCT, 0, source_ty, function);
+                self.generate_iter_next_ir(&source, source_ty, option_ptr, function);
+                function.body.push(Inst::Jump(end_label.clone()));
+
+                function.body.push(Inst::Label(none_label));
+                let tag_ptr = self.generate_field_ptr_ir(option_ptr, "Option", 0, function);
+                function.body.push(Inst::Store(tag_ptr, Value::ImmInt(0)));
+            }
+            _ => return,
+        }
+        function.body.push(Inst::Jump(end_label.clone()));
+        function.body.push(Inst::Label(end_label));
+    }
 
-        // User-visible loop variable slot (updated each iteration with current element).
-        let loop_var_ptr = Value::Reg(self.next_ptr);
-        self.next_ptr += 1;
-        current_function
-            .body
-            .push(Inst::Alloca(loop_var_ptr.clone(), variable.clone()));
-        current_function
-            .body
-            .push(Inst::Store(loop_var_ptr.clone(), Value::ImmInt(0)));
-        self.symbol_table
-            .insert(variable.clone(), (loop_var_ptr.clone(), element_ty));
+    /// The source iterator packed in field `field` of the adapter `iter`.
+    fn generate_adapter_source_ir(
+        &mut self,
+        iter: &Value,
+        struct_type: &str,
+        field: u32,
+        source_ty: &Ty,
+        function: &mut Function,
+    ) -> Value {
+        let packed = self.generate_field_load_ir(iter, struct_type, field, function);
+        self.generate_slot_to_value_ir(packed, source_ty, function)
+    }
 
-        // Internal iteration index.
-        let index_ptr = Value::Reg(self.next_ptr);
-        self.next_ptr += 1;
-        current_function.body.push(Inst::Alloca(
-            index_ptr.clone(),
-            format!("__for_idx_{}", variable),
-        ));
-        current_function
-            .body
-            .push(Inst::Store(index_ptr.clone(), Value::ImmInt(0)));
+    /// Jump to `some_label` if the `Option` at `option_ptr` is `Some`, else
+    /// to `end_label`.
+    fn generate_branch_on_some_ir(
+        &mut self,
+        option_ptr: &Value,
+        some_label: &str,
+        end_label: &str,
+        function: &mut Function,
+    ) {
+        let tag = self.generate_field_load_ir(option_ptr, "Option", 0, function);
+        let is_some = self.generate_value_compare_ir("==", &tag, &Value::ImmInt(1), function);
+        function.body.push(Inst::Branch {
+            condition: is_some,
+            true_label: some_label.to_string(),
+            false_label: end_label.to_string(),
+        });
+    }
 
-        current_function.body.push(Inst::Jump(loop_start.clone()));
+    /// Replace the payload of the `Some` at `option_ptr` with a fresh
+    /// `(first, second)` tuple of `pair_ty`; both values are in slot form.
+    fn generate_pair_payload_ir(
+        &mut self,
+        first: Value,
+        second: Value,
+        pair_ty: &Ty,
+        option_ptr: &Value,
+        function: &mut Function,
+    ) {
+        let tuple_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::HeapAlloc {
+            result: tuple_ptr.clone(),
+            struct_type: Some("tuple.2".to_string()),
+        });
+        for (index, value) in [first, second].into_iter().enumerate() {
+            let field_ptr =
+                self.generate_field_ptr_ir(&tuple_ptr, "tuple.2", index as u32, function);
+            function.body.push(Inst::Store(field_ptr, value));
+        }
+        let payload = self.generate_value_to_slot_ir(tuple_ptr, pair_ty, function);
+        let payload_ptr = self.generate_field_ptr_ir(option_ptr, "Option", 1, function);
+        function.body.push(Inst::Store(payload_ptr, payload));
+    }
 
-        // Header: idx < len
-        current_function.body.push(Inst::Label(loop_start.clone()));
-        let index_reg = Value::Reg(self.next_reg);
+    /// `iter.next()` on a `{ collection, index }` cursor: write
+    /// `Some(element)` into `option_ptr` and advance it, or `None` once all
+    /// `len` elements have been produced.
+    fn generate_cursor_next_ir(
+        &mut self,
+        iter: &Value,
+        len: usize,
+        option_ptr: &Value,
+        function: &mut Function,
+    ) {
+        let index_ptr = self.generate_field_ptr_ir(iter, ITER_STRUCT, 1, function);
+        let index = Value::Reg(self.next_reg);
         self.next_reg += 1;
-        current_function
+        function
             .body
-            .push(Inst::Load(index_reg.clone(), index_ptr.clone()));
-
-        let cond_reg = Value::Reg(self.next_reg);
+            .push(Inst::Load(index.clone(), index_ptr.clone()));
+        let in_bounds = Value::Reg(self.next_reg);
         self.next_reg += 1;
-        current_function.body.push(Inst::ICmp {
+        function.body.push(Inst::ICmp {
             op: "slt".to_string(),
-            result: cond_reg.clone(),
-            left: index_reg.clone(),
-            right: Value::ImmInt(array_len as i64),
+            result: in_bounds.clone(),
+            left: index.clone(),
+            right: Value::ImmInt(len as i64),
         });
-        current_function.body.push(Inst::Branch {
-            condition: cond_reg,
-            true_label: loop_body.clone(),
-            false_label: loop_end.clone(),
+
+        let next_id = self.next_reg;
+        self.next_reg += 1;
+        let some_label = format!("next_some_{}", next_id);
+        let none_label = format!("next_none_{}", next_id);
+        let end_label = format!("next_end_{}", next_id);
+        function.body.push(Inst::Branch {
+            condition: in_bounds,
+            true_label: some_label.clone(),
+            false_label: none_label.clone(),
         });
 
-        // Body: load element at idx, assign loop variable, execute body, idx += 1.
-        current_function.body.push(Inst::Label(loop_body));
+        function.body.push(Inst::Label(some_label));
+        let tag_ptr = self.generate_field_ptr_ir(option_ptr, "Option", 0, function);
+        function.body.push(Inst::Store(tag_ptr, Value::ImmInt(1)));
+        let packed = self.generate_field_load_ir(iter, ITER_STRUCT, 0, function);
+        let collection_ty = Ty::Array(Box::new(Ty::Int), len);
+        let collection = self.generate_slot_to_value_ir(packed, &collection_ty, function);
         let elem_ptr = Value::Reg(self.next_ptr);
         self.next_ptr += 1;
-        current_function.body.push(Inst::GetElementPtr {
+        function.body.push(Inst::GetElementPtr {
             result: elem_ptr.clone(),
-            base: array_ptr.clone(),
-            index: index_reg.clone(),
-            elem_type: format!("[{} x double]", array_len),
+            base: collection,
+            index: index.clone(),
+            elem_type: format!("[{} x double]", len),
         });
-        let elem_val = Value::Reg(self.next_reg);
+        // Elements are already in slot form, as the payload must be.
+        let element = Value::Reg(self.next_reg);
         self.next_reg += 1;
-        current_function
-            .body
-            .push(Inst::Load(elem_val.clone(), elem_ptr));
-        current_function
-            .body
-            .push(Inst::Store(loop_var_ptr, elem_val));
-
-        for stmt in body.statements {
-            self.generate_statement_ir(stmt, current_function);
-        }
-        if let Some(expr) = body.expression {
-            self.generate_expression_ir(expr, current_function);
-        }
-
+        function.body.push(Inst::Load(element.clone(), elem_ptr));
+        let payload_ptr = self.generate_field_ptr_ir(option_ptr, "Option", 1, function);
+        function.body.push(Inst::Store(payload_ptr, element));
         let next_index = Value::Reg(self.next_reg);
         self.next_reg += 1;
-        current_function
-            .body
-            .push(Inst::Add(next_index.clone(), index_reg, Value::ImmInt(1)));
-        current_function
+        function
             .body
-            .push(Inst::Store(index_ptr, next_index));
-        current_function.body.push(Inst::Jump(loop_start));
-
-        self.loop_label_stack.pop();
-        current_function.body.push(Inst::Label(loop_end));
+            .push(Inst::Add(next_index.clone(), index, Value::ImmInt(1)));
+        function.body.push(Inst::Store(index_ptr, next_index));
+        function.body.push(Inst::Jump(end_label.clone()));
+
+        function.body.push(Inst::Label(none_label));
+        let tag_ptr = self.generate_field_ptr_ir(option_ptr, "Option", 0, function);
+        function.body.push(Inst::Store(tag_ptr, Value::ImmInt(0)));
+        function.body.push(Inst::Jump(end_label.clone()));
+        function.body.push(Inst::Label(end_label));
     }
 
-    fn generate_legacy_for_loop_ir(
+    /// Address of `object[index]` as an assignment target. Array indices are
+    /// bounds-checked first.
+    fn generate_index_ptr_ir(
         &mut self,
-        variable: String,
-        start_value: Value,
-        var_type: Ty,
-        body: crate::ast::Block,
-        current_function: &mut Function,
-    ) {
-        let loop_start = format!("for_start_{}", self.next_reg);
-        self.next_reg += 1;
-        let loop_body = format!("for_body_{}", self.next_reg);
-        self.next_reg += 1;
-        let loop_end = format!("for_end_{}", self.next_reg);
-        self.next_reg += 1;
+        object: Expression,
+        index: Expression,
+        location: Option<SourceLocation>,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let (base, object_type) = self.generate_expression_ir(object, function);
+        let (index, _) = self.generate_expression_ir(index, function);
+        let (elem_ty, elem_type) = match object_type {
+            Ty::Array(elem, len) => {
+                self.generate_bounds_check_ir(&index, len, location, function);
+                (*elem, format!("[{} x double]", len))
+            }
+            Ty::Vec(elem) => (*elem, "double".to_string()),
+            _ => (Ty::Int, "double".to_string()),
+        };
+        let elem_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::GetElementPtr {
+            result: elem_ptr.clone(),
+            base,
+            index,
+            elem_type,
+        });
+        (elem_ptr, elem_ty)
+    }
 
-        self.loop_label_stack
-            .push((loop_start.clone(), loop_end.clone()));
+    /// Address of element `index` of the `len`-element array at `array`.
+    fn generate_array_element_ptr_ir(
+        &mut self,
+        array: &Value,
+        index: Value,
+        len: usize,
+        function: &mut Function,
+    ) -> Value {
+        let elem_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::GetElementPtr {
+            result: elem_ptr.clone(),
+            base: array.clone(),
+            index,
+            elem_type: format!("[{} x double]", len),
+        });
+        elem_ptr
+    }
 
-        let var_ptr = Value::Reg(self.next_ptr);
+    /// A stack counter starting at `start`, for the loops of in-place
+    /// array operations.
+    fn generate_counter_slot_ir(
+        &mut self,
+        name: &str,
+        start: i64,
+        function: &mut Function,
+    ) -> Value {
+        let slot = Value::Reg(self.next_ptr);
         self.next_ptr += 1;
-        current_function
+        function
             .body
-            .push(Inst::Alloca(var_ptr.clone(), variable.clone()));
-        current_function
+            .push(Inst::Alloca(slot.clone(), name.to_string()));
+        function
             .body
-            .push(Inst::Store(var_ptr.clone(), start_value));
-        self.symbol_table
-            .insert(variable.clone(), (var_ptr.clone(), var_type));
-
-        current_function.body.push(Inst::Jump(loop_start.clone()));
+            .push(Inst::Store(slot.clone(), Value::ImmInt(start)));
+        slot
+    }
 
-        current_function.body.push(Inst::Label(loop_start.clone()));
-        let loop_var_reg = Value::Reg(self.next_reg);
+    fn generate_load_ir(&mut self, ptr: Value, function: &mut Function) -> Value {
+        let value = Value::Reg(self.next_reg);
         self.next_reg += 1;
-        current_function
-            .body
-            .push(Inst::Load(loop_var_reg.clone(), var_ptr.clone()));
+        function.body.push(Inst::Load(value.clone(), ptr));
+        value
+    }
 
-        let cond_reg = Value::Reg(self.next_reg);
+    /// `v.sort()`: an insertion sort of the `len` elements at `array` into
+    /// ascending order, in place.
+    fn generate_array_sort_ir(&mut self, array: &Value, len: usize, function: &mut Function) {
+        let sort_id = self.next_reg;
         self.next_reg += 1;
-        current_function.body.push(Inst::ICmp {
-            op: "slt".to_string(),
-            result: cond_reg.clone(),
-            left: loop_var_reg.clone(),
-            right: Value::ImmInt(10),
+        let outer_cond = format!("sort_outer_cond_{}", sort_id);
+        let outer_body = format!("sort_outer_body_{}", sort_id);
+        let inner_cond = format!("sort_inner_cond_{}", sort_id);
+        let inner_compare = format!("sort_inner_compare_{}", sort_id);
+        let inner_swap = format!("sort_inner_swap_{}", sort_id);
+        let outer_next = format!("sort_outer_next_{}", sort_id);
+        let end = format!("sort_end_{}", sort_id);
+
+        let outer_slot = self.generate_counter_slot_ir("sort_outer", 1, function);
+        let inner_slot = self.generate_counter_slot_ir("sort_inner", 0, function);
+        function.body.push(Inst::Jump(outer_cond.clone()));
+
+        // Elements before `outer` are sorted; insert the next one.
+        function.body.push(Inst::Label(outer_cond.clone()));
+        let outer = self.generate_load_ir(outer_slot.clone(), function);
+        let in_range =
+            self.generate_value_compare_ir("<", &outer, &Value::ImmInt(len as i64), function);
+        function.body.push(Inst::Branch {
+            condition: in_range,
+            true_label: outer_body.clone(),
+            false_label: end.clone(),
         });
-        current_function.body.push(Inst::Branch {
-            condition: cond_reg,
-            true_label: loop_body.clone(),
-            false_label: loop_end.clone(),
+        function.body.push(Inst::Label(outer_body));
+        function.body.push(Inst::Store(inner_slot.clone(), outer));
+        function.body.push(Inst::Jump(inner_cond.clone()));
+
+        // Swap it down while its predecessor is greater.
+        function.body.push(Inst::Label(inner_cond.clone()));
+        let inner = self.generate_load_ir(inner_slot.clone(), function);
+        let positive = self.generate_value_compare_ir(">", &inner, &Value::ImmInt(0), function);
+        function.body.push(Inst::Branch {
+            condition: positive,
+            true_label: inner_compare.clone(),
+            false_label: outer_next.clone(),
         });
-
-        current_function.body.push(Inst::Label(loop_body));
-        for stmt in body.statements {
-            self.generate_statement_ir(stmt, current_function);
-        }
-        if let Some(expr) = body.expression {
-            self.generate_expression_ir(expr, current_function);
-        }
-
-        let incremented_reg = Value::Reg(self.next_reg);
+        function.body.push(Inst::Label(inner_compare));
+        let previous_index = Value::Reg(self.next_reg);
         self.next_reg += 1;
-        current_function.body.push(Inst::Add(
-            incremented_reg.clone(),
-            loop_var_reg,
+        function.body.push(Inst::Sub(
+            previous_index.clone(),
+            inner.clone(),
             Value::ImmInt(1),
         ));
-        current_function
+        let previous_ptr =
+            self.generate_array_element_ptr_ir(array, previous_index.clone(), len, function);
+        let current_ptr = self.generate_array_element_ptr_ir(array, inner, len, function);
+        let previous = self.generate_load_ir(previous_ptr.clone(), function);
+        let current = self.generate_load_ir(current_ptr.clone(), function);
+        let out_of_order = self.generate_value_compare_ir(">", &previous, &current, function);
+        function.body.push(Inst::Branch {
+            condition: out_of_order,
+            true_label: inner_swap.clone(),
+            false_label: outer_next.clone(),
+        });
+        function.body.push(Inst::Label(inner_swap));
+        function.body.push(Inst::Store(previous_ptr, current));
+        function.body.push(Inst::Store(current_ptr, previous));
+        function.body.push(Inst::Store(inner_slot, previous_index));
+        function.body.push(Inst::Jump(inner_cond));
+
+        function.body.push(Inst::Label(outer_next));
+        let outer = self.generate_load_ir(outer_slot.clone(), function);
+        let next_outer = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function
             .body
-            .push(Inst::Store(var_ptr, incremented_reg));
-        current_function.body.push(Inst::Jump(loop_start));
+            .push(Inst::Add(next_outer.clone(), outer, Value::ImmInt(1)));
+        function.body.push(Inst::Store(outer_slot, next_outer));
+        function.body.push(Inst::Jump(outer_cond));
+        function.body.push(Inst::Label(end));
+    }
 
-        self.loop_label_stack.pop();
-        current_function.body.push(Inst::Label(loop_end));
+    /// `v.reverse()`: swap the `len` elements at `array` pairwise from both
+    /// ends toward the middle.
+    fn generate_array_reverse_ir(&mut self, array: &Value, len: usize, function: &mut Function) {
+        let reverse_id = self.next_reg;
+        self.next_reg += 1;
+        let cond = format!("reverse_cond_{}", reverse_id);
+        let body = format!("reverse_body_{}", reverse_id);
+        let end = format!("reverse_end_{}", reverse_id);
+
+        let front_slot = self.generate_counter_slot_ir("reverse_front", 0, function);
+        let back_slot = self.generate_counter_slot_ir("reverse_back", len as i64 - 1, function);
+        function.body.push(Inst::Jump(cond.clone()));
+
+        // Stop once the indices meet; the middle element of an odd length
+        // stays where it is.
+        function.body.push(Inst::Label(cond.clone()));
+        let front = self.generate_load_ir(front_slot.clone(), function);
+        let back = self.generate_load_ir(back_slot.clone(), function);
+        let before_back = self.generate_value_compare_ir("<", &front, &back, function);
+        function.body.push(Inst::Branch {
+            condition: before_back,
+            true_label: body.clone(),
+            false_label: end.clone(),
+        });
+        function.body.push(Inst::Label(body));
+        let front_ptr = self.generate_array_element_ptr_ir(array, front.clone(), len, function);
+        let back_ptr = self.generate_array_element_ptr_ir(array, back.clone(), len, function);
+        let front_element = self.generate_load_ir(front_ptr.clone(), function);
+        let back_element = self.generate_load_ir(back_ptr.clone(), function);
+        function.body.push(Inst::Store(front_ptr, back_element));
+        function.body.push(Inst::Store(back_ptr, front_element));
+        let next_front = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function
+            .body
+            .push(Inst::Add(next_front.clone(), front, Value::ImmInt(1)));
+        let next_back = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function
+            .body
+            .push(Inst::Sub(next_back.clone(), back, Value::ImmInt(1)));
+        function.body.push(Inst::Store(front_slot, next_front));
+        function.body.push(Inst::Store(back_slot, next_back));
+        function.body.push(Inst::Jump(cond));
+        function.body.push(Inst::Label(end));
     }
 
-    fn generate_infinite_loop_ir(
+    /// Panic unless `0 <= index < len`.
+    fn generate_bounds_check_ir(
         &mut self,
-        body: crate::ast::Block,
-        current_function: &mut Function,
+        index: &Value,
+        len: usize,
+        location: Option<SourceLocation>,
+        function: &mut Function,
     ) {
-        // Generate unique labels
-        let loop_start = format!("loop_start_{}", self.next_reg);
-        self.next_reg += 1;
-        let loop_end = format!("loop_end_{}", self.next_reg);
-        self.next_reg += 1;
+        let non_negative = self.generate_value_compare_ir(">=", index, &Value::ImmInt(0), function);
+        let below_len =
+            self.generate_value_compare_ir("<", index, &Value::ImmInt(len as i64), function);
+        let in_bounds = self.generate_and_ir(non_negative, below_len, function);
 
-        // Push loop labels onto stack for break/continue
-        self.loop_label_stack
-            .push((loop_start.clone(), loop_end.clone()));
+        let check_id = self.next_reg;
+        self.next_reg += 1;
+        let ok_label = format!("index_ok_{}", check_id);
+        match in_bounds {
+            Value::ImmInt(n) if n != 0 => return,
+            Value::ImmInt(_) => {}
+            condition => {
+                let oob_label = format!("index_oob_{}", check_id);
+                function.body.push(Inst::Branch {
+                    condition,
+                    true_label: ok_label.clone(),
+                    false_label: oob_label.clone(),
+                });
+                function.body.push(Inst::Label(oob_label));
+            }
+        }
+        Self::generate_panic_ir(
+            format!("index out of bounds: the len is {}", len),
+            location,
+            &mut function.body,
+        );
+        function.body.push(Inst::Label(ok_label));
+    }
 
-        // Jump to loop start
-        current_function.body.push(Inst::Jump(loop_start.clone()));
+    /// Abort with `message`, reporting `location` as the panic site when
+    /// known. The `unreachable` ends the current block.
+    fn generate_panic_ir(message: String, location: Option<SourceLocation>, body: &mut Vec<Inst>) {
+        let mut arguments = vec![Value::ImmString(message)];
+        arguments.extend(location.map(|location| Value::ImmString(location.to_string())));
+        body.push(Inst::Call {
+            result: None,
+            function: "panic".to_string(),
+            arguments,
+        });
+        body.push(Inst::Unreachable);
+    }
 
-        // Loop start
-        current_function.body.push(Inst::Label(loop_start.clone()));
+    /// `todo!()` / `unimplemented!()`: panic, then resume in a block nothing
+    /// branches to so any code that follows still has a home.
+    fn generate_diverging_panic_ir(
+        &mut self,
+        message: String,
+        location: Option<SourceLocation>,
+        body: &mut Vec<Inst>,
+    ) -> (Value, Ty) {
+        Self::generate_panic_ir(message, location, body);
+        let label = format!("after_panic_{}", self.next_reg);
+        self.next_reg += 1;
+        body.push(Inst::Label(label));
+        (Value::ImmInt(0), Ty::Never)
+    }
 
-        // Loop body
-        for stmt in body.statements {
-            self.generate_statement_ir(stmt, current_function);
-        }
-        if let Some(expr) = body.expression {
-            self.generate_expression_ir(expr, current_function);
+    fn variant_index(&self, enum_name: &str, variant: &str) -> i64 {
+        match (enum_name, variant) {
+            ("Option", "None") | ("Result", "Ok") => 0,
+            ("Option", "Some") | ("Result", "Err") => 1,
+            _ => self
+                .enum_variants
+                .get(enum_name)
+                .and_then(|variants| variants.iter().position(|(v, _)| v == variant))
+                .map_or(0, |index| index as i64),
         }
+    }
 
-        // Jump back to start (infinite loop)
-        current_function.body.push(Inst::Jump(loop_start));
+    fn generate_field_ptr_ir(
+        &mut self,
+        base: &Value,
+        struct_type: &str,
+        field_index: u32,
+        function: &mut Function,
+    ) -> Value {
+        let field_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::GetFieldPtr {
+            result: field_ptr.clone(),
+            base: base.clone(),
+            field_index,
+            struct_type: struct_type.to_string(),
+        });
+        field_ptr
+    }
 
-        // Pop loop labels
-        self.loop_label_stack.pop();
+    /// Index and type of `field` in the layout of `struct_name`.
+    fn struct_field(&self, struct_name: &str, field: &str) -> Option<(u32, Ty)> {
+        let layout = self.struct_layouts.get(struct_name)?;
+        let index = layout.iter().position(|(name, _)| name == field)?;
+        Some((index as u32, layout[index].1.clone()))
+    }
 
-        // Loop end (reachable via break)
-        current_function.body.push(Inst::Label(loop_end));
+    /// Whether `name` is a tuple struct, whose constructor `name(a, b)` looks
+    /// like a call.
+    fn is_tuple_struct(&self, name: &str) -> bool {
+        !self.function_returns.contains_key(name)
+            && self
+                .struct_layouts
+                .get(name)
+                .and_then(|layout| layout.first())
+                .is_some_and(|(field, _)| field == "0")
     }
 
-    fn generate_break_ir(&mut self, current_function: &mut Function) {
-        if let Some((_loop_start, loop_end)) = self.loop_label_stack.last() {
-            let break_label = loop_end.clone();
-            current_function.body.push(Inst::Jump(break_label));
-        } else {
-            panic!("Break statement outside of loop");
-        }
+    /// `object.field = value`: store into the field of the struct `object`
+    /// points at, in place.
+    fn generate_field_store_ir(
+        &mut self,
+        object: Expression,
+        field: &str,
+        value: Value,
+        function: &mut Function,
+    ) {
+        let (struct_ptr, object_type) = self.generate_expression_ir(object, function);
+        let Ty::Struct(struct_name) = object_type else {
+            return;
+        };
+        let Some((field_index, field_type)) = self.struct_field(&struct_name, field) else {
+            return;
+        };
+        let value = match field_type {
+            Ty::String => self.generate_string_slot_ir(value, function),
+            Ty::Bool => self.generate_bool_to_slot_ir(value, function),
+            _ => value,
+        };
+        let field_ptr =
+            self.generate_field_ptr_ir(&struct_ptr, &struct_name, field_index, function);
+        function.body.push(Inst::Store(field_ptr, value));
     }
 
-    fn generate_continue_ir(&mut self, current_function: &mut Function) {
-        if let Some((loop_start, _loop_end)) = self.loop_label_stack.last() {
-            let continue_label = loop_start.clone();
-            current_function.body.push(Inst::Jump(continue_label));
-        } else {
-            panic!("Continue statement outside of loop");
-        }
+    fn generate_field_load_ir(
+        &mut self,
+        base: &Value,
+        struct_type: &str,
+        field_index: u32,
+        function: &mut Function,
+    ) -> Value {
+        let field_ptr = self.generate_field_ptr_ir(base, struct_type, field_index, function);
+        let loaded = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function.body.push(Inst::Load(loaded.clone(), field_ptr));
+        loaded
     }
 
-    fn ast_type_to_ty(&self, ty: &Type) -> Ty {
-        match ty {
-            Type::Named(name) => match name.as_str() {
-                "i32" | "int" => Ty::Int,
-                "f64" | "float" => Ty::Float,
-                "bool" => Ty::Bool,
-                "String" => Ty::String,
-                other => Ty::Struct(other.to_string()),
-            },
-            Type::Array(elem, size) => Ty::Array(Box::new(self.ast_type_to_ty(elem)), *size),
-            Type::Tuple(types) => Ty::Tuple(types.iter().map(|t| self.ast_type_to_ty(t)).collect()),
-            Type::Reference(inner, mutable) => {
-                Ty::Reference(Box::new(self.ast_type_to_ty(inner)), *mutable)
-            }
-            Type::Generic(name, _) => Ty::TypeParam(name.clone()),
+    /// Load a struct field as a value of `field_type`.
+    fn generate_field_value_ir(
+        &mut self,
+        base: &Value,
+        struct_type: &str,
+        field_index: u32,
+        field_type: &Ty,
+        function: &mut Function,
+    ) -> Value {
+        let loaded = self.generate_field_load_ir(base, struct_type, field_index, function);
+        match field_type {
+            Ty::Bool => self.generate_slot_to_bool_ir(loaded, function),
+            _ => loaded,
         }
     }
 
-    fn ast_type_to_ir_name(&self, ty: &Type) -> String {
-        match self.ast_type_to_ty(ty) {
-            Ty::Int => "i32".to_string(),
-            Ty::Float => "f64".to_string(),
-            Ty::Bool => "bool".to_string(),
-            Ty::String => "String".to_string(),
-            Ty::Void => "void".to_string(),
-            Ty::Array(_, _) => "array".to_string(),
-            Ty::Tuple(_) => "tuple".to_string(),
-            Ty::Struct(name) => name,
-            Ty::Enum(name) => name,
-            Ty::Reference(_, mutable) => {
-                if mutable {
-                    "&mut".to_string()
-                } else {
-                    "&".to_string()
-                }
+    /// Emit the checks for `pattern` against `value` and return a boolean
+    /// value that is true when the pattern matches. Checks on immediates fold
+    /// to `ImmInt(0/1)`.
+    fn generate_pattern_check(
+        &mut self,
+        value: &Value,
+        value_type: &Ty,
+        pattern: &Pattern,
+        function: &mut Function,
+    ) -> Value {
+        match pattern {
+            Pattern::Wildcard | Pattern::Identifier(_) => Value::ImmInt(1),
+            Pattern::Literal(literal) => {
+                let (expected, _) = self.generate_expression_ir(literal.clone(), function);
+                let value = match value_type {
+                    Ty::Bool => self.generate_bool_to_slot_ir(value.clone(), function),
+                    _ => value.clone(),
+                };
+                self.generate_value_compare_ir("==", &value, &expected, function)
             }
-            Ty::TypeParam(name) => name,
-            Ty::Option(_) => "Option".to_string(),
-            Ty::Result(_, _) => "Result".to_string(),
-            Ty::Vec(_) => "Vec".to_string(),
-            Ty::HashMap(_, _) => "HashMap".to_string(),
-            Ty::Fn(name) => name,
-        }
-    }
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let (low, _) = self.generate_expression_ir(start.clone(), function);
+                let (high, _) = self.generate_expression_ir(end.clone(), function);
+                let above = self.generate_value_compare_ir(">=", value, &low, function);
+                let upper_op = if *inclusive { "<=" } else { "<" };
+                let below = self.generate_value_compare_ir(upper_op, value, &high, function);
+                self.generate_and_ir(above, below, function)
+            }
+            Pattern::Enum {
+                enum_name,
+                variant,
+                data,
+            } => {
+                // Enum values are pointer-backed; anything else can't match.
+                if !matches!(value, Value::Reg(_)) {
+                    return Value::ImmInt(0);
+                }
+                let tag = self.generate_field_load_ir(value, enum_name, 0, function);
+                let expected_tag = Value::ImmInt(self.variant_index(enum_name, variant));
+                let tag_matches =
+                    self.generate_value_compare_ir("==", &tag, &expected_tag, function);
 
-    fn resolve_callable_name(&self, name: &str) -> String {
-        if let Some((_, Ty::Fn(target))) = self.symbol_table.get(name) {
-            return target.clone();
+                let Some(inner) = data else {
+                    return tag_matches;
+                };
+                let payload_types = self.variant_payload_types(enum_name, variant, value_type);
+                let mut matched = tag_matches;
+                for (field_index, field_pattern) in
+                    self.payload_patterns(enum_name, variant, inner, &payload_types)
+                {
+                    if Self::is_irrefutable_pattern(field_pattern) {
+                        continue;
+                    }
+                    let field_type = payload_types
+                        .get(field_index as usize - 1)
+                        .cloned()
+                        .unwrap_or(Ty::Int);
+                    let payload = self.generate_payload_load_ir(
+                        value,
+                        enum_name,
+                        field_index,
+                        &field_type,
+                        function,
+                    );
+                    let payload_matches =
+                        self.generate_pattern_check(&payload, &field_type, field_pattern, function);
+                    matched = self.generate_and_ir(matched, payload_matches, function);
+                }
+                matched
+            }
+            Pattern::Struct { name, fields } => {
+                let layout = self.struct_layouts.get(name).cloned().unwrap_or_default();
+                let mut matched = Value::ImmInt(1);
+                for (field_name, field_pattern) in fields {
+                    if Self::is_irrefutable_pattern(field_pattern) {
+                        continue;
+                    }
+                    let Some(field_index) = layout.iter().position(|(n, _)| n == field_name) else {
+                        return Value::ImmInt(0);
+                    };
+                    let field_type = layout[field_index].1.clone();
+                    let field_value = self.generate_field_value_ir(
+                        value,
+                        name,
+                        field_index as u32,
+                        &field_type,
+                        function,
+                    );
+                    let field_matches = self.generate_pattern_check(
+                        &field_value,
+                        &field_type,
+                        field_pattern,
+                        function,
+                    );
+                    matched = self.generate_and_ir(matched, field_matches, function);
+                }
+                matched
+            }
+            Pattern::Binding { pattern, .. } => {
+                self.generate_pattern_check(value, value_type, pattern, function)
+            }
+            // Array lengths are static, so only the elements need runtime checks.
+            Pattern::Array { elements, rest } => {
+                let Ty::Array(elem_ty, len) = value_type else {
+                    return Value::ImmInt(0);
+                };
+                let length_matches = match rest {
+                    Some(_) => *len >= elements.len(),
+                    None => *len == elements.len(),
+                };
+                if !length_matches {
+                    return Value::ImmInt(0);
+                }
+                let mut matched = Value::ImmInt(1);
+                for (index, element_pattern) in Self::array_pattern_indices(elements, rest, *len) {
+                    if Self::is_irrefutable_pattern(element_pattern) {
+                        continue;
+                    }
+                    let element =
+                        self.generate_element_load_ir(value, elem_ty, *len, index, function);
+                    let element_matches =
+                        self.generate_pattern_check(&element, elem_ty, element_pattern, function);
+                    matched = self.generate_and_ir(matched, element_matches, function);
+                }
+                matched
+            }
+            Pattern::Tuple(elements) => {
+                let Ty::Tuple(elem_types) = value_type else {
+                    return Value::ImmInt(elements.iter().all(Self::is_irrefutable_pattern) as i64);
+                };
+                if elem_types.len() != elements.len() {
+                    return Value::ImmInt(0);
+                }
+                let mut matched = Value::ImmInt(1);
+                for (index, (element_pattern, elem_ty)) in
+                    elements.iter().zip(elem_types).enumerate()
+                {
+                    if Self::is_irrefutable_pattern(element_pattern) {
+                        continue;
+                    }
+                    let element =
+                        self.generate_tuple_element_load_ir(value, value_type, index, function);
+                    let element_matches =
+                        self.generate_pattern_check(&element, elem_ty, element_pattern, function);
+                    matched = self.generate_and_ir(matched, element_matches, function);
+                }
+                matched
+            }
         }
-        name.to_string()
     }
 
-    fn lower_closure_expression(
-        &mut self,
-        params: Vec<crate::ast::Parameter>,
-        body: Expression,
-    ) -> (Value, Ty) {
-        let closure_id = self.closure_count as i64;
-        let closure_name = format!("__closure_{}", self.closure_count);
-        self.closure_count += 1;
-
-        let ir_params: Vec<(String, String)> = params
+    /// Pair each element sub-pattern with the array index it matches. Those
+    /// after a `..` count back from the end of the array.
+    fn array_pattern_indices<'p>(
+        elements: &'p [Pattern],
+        rest: &Option<(usize, Option<String>)>,
+        len: usize,
+    ) -> Vec<(usize, &'p Pattern)> {
+        let rest_start = rest.as_ref().map_or(elements.len(), |(start, _)| *start);
+        elements
             .iter()
-            .map(|p| {
-                let ty_str = match &p.param_type {
-                    Type::Named(n) => match n.as_str() {
-                        "i32" | "int" => "i32".to_string(),
-                        "f64" | "float" => "double".to_string(),
-                        "bool" => "i1".to_string(),
-                        _ => "i32".to_string(),
-                    },
-                    _ => "i32".to_string(),
+            .enumerate()
+            .map(|(i, element)| {
+                let index = if i < rest_start {
+                    i
+                } else {
+                    len - (elements.len() - i)
                 };
-                (p.name.clone(), ty_str)
+                (index, element)
             })
-            .collect();
-
-        // Closures currently do not capture outer variables; compile them as plain
-        // standalone functions with their own symbol table/register space.
-        let saved_symbol_table = self.symbol_table.clone();
-        let saved_next_reg = self.next_reg;
-        let saved_next_ptr = self.next_ptr;
+            .collect()
+    }
 
-        self.symbol_table.clear();
-        self.next_reg = 0;
-        self.next_ptr = 0;
+    fn generate_element_load_ir(
+        &mut self,
+        array: &Value,
+        elem_ty: &Ty,
+        len: usize,
+        index: usize,
+        function: &mut Function,
+    ) -> Value {
+        let elem_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::GetElementPtr {
+            result: elem_ptr.clone(),
+            base: array.clone(),
+            index: Value::ImmInt(index as i64),
+            elem_type: format!("[{} x double]", len),
+        });
+        let loaded = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function.body.push(Inst::Load(loaded.clone(), elem_ptr));
+        self.generate_slot_to_value_ir(loaded, elem_ty, function)
+    }
 
-        let mut closure_body = Vec::new();
-        for p in &params {
-            let ptr = Value::Reg(self.next_ptr);
-            self.next_ptr += 1;
-            closure_body.push(Inst::Alloca(ptr.clone(), p.name.clone()));
-            let ty = self.ast_type_to_ty(&p.param_type);
-            self.symbol_table.insert(p.name.clone(), (ptr, ty));
+    /// Lower a tuple to a `tuple.N` struct with one double field per element.
+    /// Tuples live on the heap so a function can return the one it builds.
+    fn generate_tuple_literal_ir(
+        &mut self,
+        elements: Vec<Expression>,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let struct_type = format!("tuple.{}", elements.len());
+        let tuple_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::HeapAlloc {
+            result: tuple_ptr.clone(),
+            struct_type: Some(struct_type.clone()),
+        });
+        let mut elem_types = Vec::new();
+        for (index, element) in elements.into_iter().enumerate() {
+            let (value, ty) = self.generate_expression_ir(element, function);
+            let value = self.generate_value_to_slot_ir(value, &ty, function);
+            let field_ptr =
+                self.generate_field_ptr_ir(&tuple_ptr, &struct_type, index as u32, function);
+            function.body.push(Inst::Store(field_ptr, value));
+            elem_types.push(ty);
         }
+        (tuple_ptr, Ty::Tuple(elem_types))
+    }
 
-        let (body_val, body_ty) = self.generate_expression_ir_for_function(body, &mut closure_body);
-        closure_body.push(Inst::Return(body_val));
-
-        let return_type = match &body_ty {
-            Ty::Int => Some("i32".to_string()),
-            Ty::Float => Some("double".to_string()),
-            Ty::Bool => Some("i1".to_string()),
-            _ => Some("i32".to_string()),
-        };
-
-        let closure_fn = Function {
-            name: closure_name.clone(),
-            body: vec![Inst::FunctionDef {
-                name: closure_name.clone(),
-                parameters: ir_params,
-                return_type,
-                body: closure_body,
-            }],
-            next_reg: self.next_reg,
-            next_ptr: self.next_ptr,
+    /// Load element `index` of a tuple held by pointer.
+    fn generate_tuple_element_load_ir(
+        &mut self,
+        tuple: &Value,
+        tuple_type: &Ty,
+        index: usize,
+        function: &mut Function,
+    ) -> Value {
+        let Ty::Tuple(elem_types) = tuple_type else {
+            return Value::ImmInt(0);
         };
-        self.functions.insert(closure_name.clone(), closure_fn);
-
-        self.symbol_table = saved_symbol_table;
-        self.next_reg = saved_next_reg;
-        self.next_ptr = saved_next_ptr;
-
-        (Value::ImmInt(closure_id), Ty::Fn(closure_name))
+        let struct_type = format!("tuple.{}", elem_types.len());
+        let loaded = self.generate_field_load_ir(tuple, &struct_type, index as u32, function);
+        self.generate_slot_to_value_ir(loaded, &elem_types[index], function)
     }
 
-    // I/O and enhanced expression IR generation methods
-    fn generate_print_ir(
+    /// Copy `count` elements of `array` starting at `start` into a new array,
+    /// which is what a `..rest` pattern binds.
+    fn generate_subarray_copy_ir(
         &mut self,
-        format_string: String,
-        arguments: Vec<Expression>,
-        newline: bool,
+        array: &Value,
+        len: usize,
+        start: usize,
+        count: usize,
         function: &mut Function,
-    ) -> (Value, Ty) {
-        // Generate IR for arguments
-        let mut arg_values = Vec::new();
-        for arg in arguments {
-            let (arg_value, _) = self.generate_expression_ir(arg, function);
-            arg_values.push(arg_value);
+    ) -> Value {
+        let slice = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::AllocaArray {
+            result: slice.clone(),
+            elem_type: "double".to_string(),
+            count,
+        });
+        for i in 0..count {
+            let source_ptr = Value::Reg(self.next_ptr);
+            self.next_ptr += 1;
+            function.body.push(Inst::GetElementPtr {
+                result: source_ptr.clone(),
+                base: array.clone(),
+                index: Value::ImmInt((start + i) as i64),
+                elem_type: format!("[{} x double]", len),
+            });
+            let element = Value::Reg(self.next_reg);
+            self.next_reg += 1;
+            function.body.push(Inst::Load(element.clone(), source_ptr));
+            let dest_ptr = Value::Reg(self.next_ptr);
+            self.next_ptr += 1;
+            function.body.push(Inst::GetElementPtr {
+                result: dest_ptr.clone(),
+                base: slice.clone(),
+                index: Value::ImmInt(i as i64),
+                elem_type: format!("[{} x double]", count),
+            });
+            function.body.push(Inst::Store(dest_ptr, element));
         }
+        slice
+    }
 
-        // Modify format string to add newline if needed
-        let final_format = if newline {
-            format!("{}\n", format_string)
-        } else {
-            format_string
-        };
+    /// Pair each payload sub-pattern with its field index in the enum struct.
+    /// Multi-field variants are matched with a tuple of sub-patterns.
+    fn payload_patterns<'p>(
+        &self,
+        enum_name: &str,
+        variant: &str,
+        pattern: &'p Pattern,
+        payload_types: &[Ty],
+    ) -> Vec<(u32, &'p Pattern)> {
+        match pattern {
+            Pattern::Tuple(elements) if payload_types.len() > 1 => elements
+                .iter()
+                .enumerate()
+                .map(|(i, element)| (i as u32 + 1, element))
+                .collect(),
+            // Struct variants bind by field name, so `..` may skip fields.
+            Pattern::Struct { fields, .. } => {
+                let Some(names) = self
+                    .variant_fields
+                    .get(&(enum_name.to_string(), variant.to_string()))
+                else {
+                    return Vec::new();
+                };
+                fields
+                    .iter()
+                    .filter_map(|(field, field_pattern)| {
+                        let index = names.iter().position(|name| name == field)?;
+                        Some((index as u32 + 1, field_pattern))
+                    })
+                    .collect()
+            }
+            _ => vec![(1, pattern)],
+        }
+    }
 
-        // Create print instruction
-        let print_inst = Inst::Print {
-            format_string: final_format,
-            arguments: arg_values,
-        };
+    /// Declared payload field types of `enum_name::variant`. Option and
+    /// Result take theirs from the scrutinee type.
+    fn variant_payload_types(&self, enum_name: &str, variant: &str, value_type: &Ty) -> Vec<Ty> {
+        match (value_type, variant) {
+            (Ty::Option(inner), _) => vec![(**inner).clone()],
+            (Ty::Result(ok, _), "Ok") => vec![(**ok).clone()],
+            (Ty::Result(_, err), _) => vec![(**err).clone()],
+            _ => self
+                .enum_variants
+                .get(enum_name)
+                .and_then(|variants| variants.iter().find(|(v, _)| v == variant))
+                .map_or_else(Vec::new, |(_, payload)| payload.clone()),
+        }
+    }
 
-        function.body.push(print_inst);
+    fn generate_payload_load_ir(
+        &mut self,
+        base: &Value,
+        enum_name: &str,
+        field_index: u32,
+        field_type: &Ty,
+        function: &mut Function,
+    ) -> Value {
+        let loaded = self.generate_field_load_ir(base, enum_name, field_index, function);
+        self.generate_slot_to_value_ir(loaded, field_type, function)
+    }
+
+    /// Bind the names introduced by `pattern` against an already-matched value.
+    fn generate_pattern_bindings(
+        &mut self,
+        value: &Value,
+        value_type: &Ty,
+        pattern: &Pattern,
+        function: &mut Function,
+    ) {
+        match pattern {
+            Pattern::Identifier(name) => {
+                if Self::stores_value_directly(value_type) {
+                    self.symbol_table
+                        .insert(name.clone(), (value.clone(), value_type.clone()));
+                } else {
+                    let slot = Value::Reg(self.next_ptr);
+                    self.next_ptr += 1;
+                    function.body.push(Inst::Alloca(slot.clone(), name.clone()));
+                    let stored =
+                        self.generate_value_to_slot_ir(value.clone(), value_type, function);
+                    function.body.push(Inst::Store(slot.clone(), stored));
+                    self.symbol_table
+                        .insert(name.clone(), (slot, value_type.clone()));
+                }
+            }
+            Pattern::Binding { name, pattern } => {
+                let name = Pattern::Identifier(name.clone());
+                self.generate_pattern_bindings(value, value_type, &name, function);
+                self.generate_pattern_bindings(value, value_type, pattern, function);
+            }
+            Pattern::Array { elements, rest } => {
+                let Ty::Array(elem_ty, len) = value_type else {
+                    return;
+                };
+                for (index, element_pattern) in Self::array_pattern_indices(elements, rest, *len) {
+                    if matches!(element_pattern, Pattern::Wildcard | Pattern::Literal(_)) {
+                        continue;
+                    }
+                    let element =
+                        self.generate_element_load_ir(value, elem_ty, *len, index, function);
+                    self.generate_pattern_bindings(&element, elem_ty, element_pattern, function);
+                }
+                if let Some((start, Some(name))) = rest {
+                    let rest_len = len.saturating_sub(elements.len());
+                    let slice =
+                        self.generate_subarray_copy_ir(value, *len, *start, rest_len, function);
+                    let rest_type = Ty::Array(elem_ty.clone(), rest_len);
+                    let name = Pattern::Identifier(name.clone());
+                    self.generate_pattern_bindings(&slice, &rest_type, &name, function);
+                }
+            }
+            Pattern::Tuple(elements) => {
+                let Ty::Tuple(elem_types) = value_type else {
+                    return;
+                };
+                for (index, (element_pattern, elem_ty)) in
+                    elements.iter().zip(elem_types).enumerate()
+                {
+                    if matches!(element_pattern, Pattern::Wildcard | Pattern::Literal(_)) {
+                        continue;
+                    }
+                    let element =
+                        self.generate_tuple_element_load_ir(value, value_type, index, function);
+                    self.generate_pattern_bindings(&element, elem_ty, element_pattern, function);
+                }
+            }
+            Pattern::Enum {
+                enum_name,
+                variant,
+                data: Some(inner),
+            } => self.generate_variant_data_bindings(
+                value, value_type, enum_name, variant, inner, function,
+            ),
+            Pattern::Struct { name, fields } => {
+                let layout = self.struct_layouts.get(name).cloned().unwrap_or_default();
+                for (field_name, field_pattern) in fields {
+                    let Some(field_index) = layout.iter().position(|(n, _)| n == field_name) else {
+                        continue;
+                    };
+                    let field_type = layout[field_index].1.clone();
+                    let field_value = self.generate_field_value_ir(
+                        value,
+                        name,
+                        field_index as u32,
+                        &field_type,
+                        function,
+                    );
+                    self.generate_pattern_bindings(
+                        &field_value,
+                        &field_type,
+                        field_pattern,
+                        function,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
 
-        // Print operations return unit type (represented as 0 for now)
-        (Value::ImmInt(0), Ty::Int)
+    fn generate_variant_data_bindings(
+        &mut self,
+        value: &Value,
+        value_type: &Ty,
+        enum_name: &str,
+        variant: &str,
+        inner: &Pattern,
+        function: &mut Function,
+    ) {
+        let payload_types = self.variant_payload_types(enum_name, variant, value_type);
+        for (field_index, field_pattern) in
+            self.payload_patterns(enum_name, variant, inner, &payload_types)
+        {
+            if matches!(field_pattern, Pattern::Wildcard | Pattern::Literal(_)) {
+                continue;
+            }
+            let field_type = payload_types
+                .get(field_index as usize - 1)
+                .cloned()
+                .unwrap_or(Ty::Int);
+            let payload =
+                self.generate_payload_load_ir(value, enum_name, field_index, &field_type, function);
+            self.generate_pattern_bindings(&payload, &field_type, field_pattern, function);
+        }
     }
 
-    fn generate_comparison_ir(
+    /// Lower `match` to a chain of pattern checks. Each arm jumps to the end
+    /// label, where its value is picked up by `generate_merge_value_ir`.
+    fn generate_match_expression_ir(
         &mut self,
-        op: crate::ast::ComparisonOp,
-        left: Expression,
-        right: Expression,
+        expr: Expression,
+        arms: Vec<MatchArm>,
         function: &mut Function,
     ) -> (Value, Ty) {
-        let (left_val, left_type) = self.generate_expression_ir(left, function);
-        let (right_val, right_type) = self.generate_expression_ir(right, function);
+        let (scrutinee, scrutinee_type) = self.generate_expression_ir(expr, function);
 
-        let result_reg = Value::Reg(self.next_reg);
+        let match_id = self.next_reg;
         self.next_reg += 1;
+        let end_label = format!("match_end_{}", match_id);
+        let slot_index = function.body.len();
+
+        // The semantic analyzer checked that the arms agree, so the first arm
+        // that yields a value gives the type of the whole match.
+        let mut result_type = None;
+        let mut exits = Vec::new();
+        for (index, arm) in arms.into_iter().enumerate() {
+            let next_label = format!("match_next_{}_{}", match_id, index);
+            match self.generate_pattern_check(&scrutinee, &scrutinee_type, &arm.pattern, function) {
+                Value::ImmInt(0) => continue, // Arm can never match
+                Value::ImmInt(_) => {}        // Arm always matches
+                condition => {
+                    let arm_label = format!("match_arm_{}_{}", match_id, index);
+                    function.body.push(Inst::Branch {
+                        condition,
+                        true_label: arm_label.clone(),
+                        false_label: next_label.clone(),
+                    });
+                    function.body.push(Inst::Label(arm_label));
+                }
+            }
 
-        // Convert ComparisonOp to string for IR instruction
-        let op_str = match op {
-            crate::ast::ComparisonOp::Equal => "eq",
-            crate::ast::ComparisonOp::NotEqual => "ne",
-            crate::ast::ComparisonOp::LessThan => "slt",
-            crate::ast::ComparisonOp::GreaterThan => "sgt",
-            crate::ast::ComparisonOp::LessEqual => "sle",
-            crate::ast::ComparisonOp::GreaterEqual => "sge",
-        };
+            // Pattern bindings are only visible inside their arm.
+            let saved_symbol_table = self.symbol_table.clone();
+            self.generate_pattern_bindings(&scrutinee, &scrutinee_type, &arm.pattern, function);
+            let (arm_value, arm_type) = self.generate_expression_ir(arm.body, function);
+            self.symbol_table = saved_symbol_table;
 
-        // Generate appropriate comparison instruction based on operand types
-        let inst = match (&left_type, &right_type) {
-            (Ty::Int, Ty::Int) => Inst::ICmp {
-                op: op_str.to_string(),
-                result: result_reg.clone(),
-                left: left_val,
-                right: right_val,
-            },
-            (Ty::Float, Ty::Float) => {
-                // Convert integer comparison ops to float comparison ops
-                let float_op = match op_str {
-                    "eq" => "oeq",
-                    "ne" => "one",
-                    "slt" => "olt",
-                    "sgt" => "ogt",
-                    "sle" => "ole",
-                    "sge" => "oge",
-                    _ => op_str,
-                };
-                Inst::FCmp {
-                    op: float_op.to_string(),
-                    result: result_reg.clone(),
-                    left: left_val,
-                    right: right_val,
-                }
+            if matches!(result_type, None | Some(Ty::Unit | Ty::Never)) {
+                result_type = Some(arm_type.clone());
             }
-            (Ty::Int, Ty::Float) => {
-                // Promote left operand to float
-                let promoted_left = Value::Reg(self.next_reg);
-                self.next_reg += 1;
-                function
-                    .body
-                    .push(Inst::SIToFP(promoted_left.clone(), left_val));
+            exits.push(self.generate_branch_exit_ir(arm_value, arm_type, &end_label, function));
+            function.body.push(Inst::Label(next_label));
+        }
+        // Falling past the last arm only happens for a non-exhaustive match,
+        // where the result is unspecified.
+        exits.push(self.generate_branch_exit_ir(Value::ImmInt(0), Ty::Int, &end_label, function));
+        function.body.push(Inst::Label(end_label));
+
+        let result_type = result_type.unwrap_or(Ty::Unit);
+        if matches!(result_type, Ty::Unit | Ty::Never) {
+            return (Value::ImmInt(0), result_type);
+        }
+        let result = self.generate_merge_value_ir(
+            exits,
+            &result_type,
+            slot_index,
+            format!("match_result_{}", match_id),
+            function,
+        );
+        (result, result_type)
+    }
 
-                let float_op = match op_str {
-                    "eq" => "oeq",
-                    "ne" => "one",
-                    "slt" => "olt",
-                    "sgt" => "ogt",
-                    "sle" => "ole",
-                    "sge" => "oge",
-                    _ => op_str,
-                };
-                Inst::FCmp {
-                    op: float_op.to_string(),
-                    result: result_reg.clone(),
-                    left: promoted_left,
-                    right: right_val,
-                }
-            }
-            (Ty::Float, Ty::Int) => {
-                // Promote right operand to float
-                let promoted_right = Value::Reg(self.next_reg);
-                self.next_reg += 1;
-                function
-                    .body
-                    .push(Inst::SIToFP(promoted_right.clone(), right_val));
+    /// Lower `if let` to a pattern check that branches to the then-block with
+    /// the pattern bound, or to the else-block. As with `match`, the branch
+    /// values meet at the end label.
+    fn generate_if_let_ir(
+        &mut self,
+        pattern: Pattern,
+        expr: Expression,
+        then_block: Block,
+        else_block: Option<Box<Block>>,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let (scrutinee, scrutinee_type) = self.generate_expression_ir(expr, function);
 
-                let float_op = match op_str {
-                    "eq" => "oeq",
-                    "ne" => "one",
-                    "slt" => "olt",
-                    "sgt" => "ogt",
-                    "sle" => "ole",
-                    "sge" => "oge",
-                    _ => op_str,
-                };
-                Inst::FCmp {
-                    op: float_op.to_string(),
-                    result: result_reg.clone(),
-                    left: left_val,
-                    right: promoted_right,
-                }
+        let if_let_id = self.next_reg;
+        self.next_reg += 1;
+        let then_label = format!("if_let_then_{}", if_let_id);
+        let else_label = format!("if_let_else_{}", if_let_id);
+        let end_label = format!("if_let_end_{}", if_let_id);
+        let slot_index = function.body.len();
+
+        match self.generate_pattern_check(&scrutinee, &scrutinee_type, &pattern, function) {
+            Value::ImmInt(0) => function.body.push(Inst::Jump(else_label.clone())),
+            Value::ImmInt(_) => function.body.push(Inst::Jump(then_label.clone())),
+            condition => function.body.push(Inst::Branch {
+                condition,
+                true_label: then_label.clone(),
+                false_label: else_label.clone(),
+            }),
+        }
+
+        // Pattern bindings are only visible inside the then-block.
+        function.body.push(Inst::Label(then_label));
+        let saved_symbol_table = self.symbol_table.clone();
+        self.generate_pattern_bindings(&scrutinee, &scrutinee_type, &pattern, function);
+        let (then_value, then_type) = self.generate_branch_value_ir(then_block, function);
+        self.symbol_table = saved_symbol_table;
+        let then_exit =
+            self.generate_branch_exit_ir(then_value, then_type.clone(), &end_label, function);
+
+        function.body.push(Inst::Label(else_label));
+        let (else_value, else_type) = match else_block {
+            Some(block) => {
+                let saved_symbol_table = self.symbol_table.clone();
+                let (else_value, else_type) = self.generate_branch_value_ir(*block, function);
+                self.symbol_table = saved_symbol_table;
+                (else_value, Some(else_type))
             }
-            (Ty::Bool, Ty::Bool) => Inst::ICmp {
-                op: op_str.to_string(),
-                result: result_reg.clone(),
-                left: left_val,
-                right: right_val,
-            },
-            _ => panic!(
-                "Unsupported comparison between {:?} and {:?}",
-                left_type, right_type
-            ),
+            None => (Value::ImmInt(0), None),
         };
-
-        function.body.push(inst);
-        (result_reg, Ty::Bool)
+        let else_exit = self.generate_branch_exit_ir(
+            else_value,
+            else_type.clone().unwrap_or(Ty::Unit),
+            &end_label,
+            function,
+        );
+        function.body.push(Inst::Label(end_label));
+
+        // Without an else-block there is no value; a branch without a value
+        // defers to the other one.
+        let result_type = match (then_type, else_type) {
+            (_, None) => Ty::Unit,
+            (Ty::Unit | Ty::Never, Some(else_type)) => else_type,
+            (then_type, Some(_)) => then_type,
+        };
+        if matches!(result_type, Ty::Unit | Ty::Never) {
+            return (Value::ImmInt(0), result_type);
+        }
+        let result = self.generate_merge_value_ir(
+            vec![then_exit, else_exit],
+            &result_type,
+            slot_index,
+            format!("if_let_result_{}", if_let_id),
+            function,
+        );
+        (result, result_type)
     }
 
-    fn generate_logical_ir(
+    /// Lower `if`/`else` in value position the way `generate_if_let_ir` does;
+    /// an `else if` chain nests.
+    fn generate_if_value_ir(
         &mut self,
-        op: crate::ast::LogicalOp,
-        left: Expression,
-        right: Expression,
+        condition: Expression,
+        then_block: Block,
+        else_stmt: Statement,
         function: &mut Function,
     ) -> (Value, Ty) {
-        let (left_val, _) = self.generate_expression_ir(left, function);
-        let (right_val, _) = self.generate_expression_ir(right, function);
+        let (condition, _) = self.generate_expression_ir(condition, function);
 
-        let result_reg = Value::Reg(self.next_reg);
+        let if_id = self.next_reg;
         self.next_reg += 1;
+        let then_label = format!("if_then_{}", if_id);
+        let else_label = format!("if_else_{}", if_id);
+        let end_label = format!("if_end_{}", if_id);
+        let slot_index = function.body.len();
+
+        match condition {
+            Value::ImmInt(0) => function.body.push(Inst::Jump(else_label.clone())),
+            Value::ImmInt(_) => function.body.push(Inst::Jump(then_label.clone())),
+            condition => function.body.push(Inst::Branch {
+                condition,
+                true_label: then_label.clone(),
+                false_label: else_label.clone(),
+            }),
+        }
 
-        let inst = match op {
-            crate::ast::LogicalOp::And => Inst::And {
-                result: result_reg.clone(),
-                left: left_val,
-                right: right_val,
-            },
-            crate::ast::LogicalOp::Or => Inst::Or {
-                result: result_reg.clone(),
-                left: left_val,
-                right: right_val,
-            },
+        function.body.push(Inst::Label(then_label));
+        let saved_symbol_table = self.symbol_table.clone();
+        let (then_value, then_type) = self.generate_branch_value_ir(then_block, function);
+        self.symbol_table = saved_symbol_table;
+        let then_exit =
+            self.generate_branch_exit_ir(then_value, then_type.clone(), &end_label, function);
+
+        function.body.push(Inst::Label(else_label));
+        let saved_symbol_table = self.symbol_table.clone();
+        let (else_value, else_type) = match else_stmt {
+            Statement::Block(block) => self.generate_branch_value_ir(block, function),
+            Statement::If {
+                condition,
+                then_block,
+                else_block: Some(else_stmt),
+            } => self.generate_if_value_ir(condition, then_block, *else_stmt, function),
+            other => {
+                self.generate_statement_ir(other, function);
+                (Value::ImmInt(0), Ty::Unit)
+            }
+        };
+        self.symbol_table = saved_symbol_table;
+        let else_exit =
+            self.generate_branch_exit_ir(else_value, else_type.clone(), &end_label, function);
+        function.body.push(Inst::Label(end_label));
+
+        // A branch without a value defers to the other one.
+        let result_type = match (then_type, else_type) {
+            (Ty::Unit | Ty::Never, else_type) => else_type,
+            (then_type, _) => then_type,
         };
+        if matches!(result_type, Ty::Unit | Ty::Never) {
+            return (Value::ImmInt(0), result_type);
+        }
+        let result = self.generate_merge_value_ir(
+            vec![then_exit, else_exit],
+            &result_type,
+            slot_index,
+            format!("if_result_{}", if_id),
+            function,
+        );
+        (result, result_type)
+    }
 
-        function.body.push(inst);
-        (result_reg, Ty::Bool)
+    /// Lower a branch block, returning its tail value (unit if it has none).
+    fn generate_branch_value_ir(&mut self, block: Block, function: &mut Function) -> (Value, Ty) {
+        for stmt in block.statements {
+            self.generate_statement_ir(stmt, function);
+        }
+        match block.expression {
+            Some(expr) => self.generate_expression_ir(expr, function),
+            None => (Value::ImmInt(0), Ty::Unit),
+        }
     }
 
-    fn generate_unary_ir(
+    /// End a branch with a jump to `end_label`, recording the block it leaves
+    /// from and the value it carries there.
+    fn generate_branch_exit_ir(
         &mut self,
-        op: crate::ast::UnaryOp,
-        operand: Expression,
+        value: Value,
+        ty: Ty,
+        end_label: &str,
         function: &mut Function,
-    ) -> (Value, Ty) {
-        let (operand_val, operand_type) = self.generate_expression_ir(operand, function);
+    ) -> BranchExit {
+        // A branch that already left (e.g. through `return` or a panic)
+        // carries nothing, and its block keeps the terminator it has.
+        let terminated = matches!(
+            function.body.last(),
+            Some(Inst::Return(_) | Inst::Jump(_) | Inst::Branch { .. } | Inst::Unreachable)
+        );
+        let value = (!matches!(ty, Ty::Unit | Ty::Never) && !terminated)
+            .then(|| self.generate_value_to_slot_ir(value, &ty, function));
+        let block = function
+            .body
+            .iter()
+            .rev()
+            .find_map(|inst| match inst {
+                Inst::Label(label) => Some(label.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| "entry".to_string());
+        if !terminated {
+            function.body.push(Inst::Jump(end_label.to_string()));
+        }
+        BranchExit {
+            jump_index: function.body.len() - 1,
+            block,
+            value,
+        }
+    }
 
-        let result_reg = Value::Reg(self.next_reg);
+    /// The value branches deliver to the merge label just emitted. If every
+    /// exit carries a value, a `Phi` selects it by predecessor; otherwise the
+    /// values go through a slot allocated at `slot_index`.
+    fn generate_merge_value_ir(
+        &mut self,
+        exits: Vec<BranchExit>,
+        result_type: &Ty,
+        slot_index: usize,
+        slot_name: String,
+        function: &mut Function,
+    ) -> Value {
+        let incoming: Option<Vec<(Value, String)>> = exits
+            .iter()
+            .map(|exit| exit.value.clone().map(|value| (value, exit.block.clone())))
+            .collect();
+        let merged = Value::Reg(self.next_reg);
         self.next_reg += 1;
+        match incoming {
+            Some(incoming) => function.body.push(Inst::Phi {
+                result: merged.clone(),
+                incoming,
+            }),
+            None => {
+                let slot = Value::Reg(self.next_ptr);
+                self.next_ptr += 1;
+                for exit in exits.into_iter().rev() {
+                    if let Some(value) = exit.value {
+                        function
+                            .body
+                            .insert(exit.jump_index, Inst::Store(slot.clone(), value));
+                    }
+                }
+                function
+                    .body
+                    .insert(slot_index, Inst::Alloca(slot.clone(), slot_name));
+                function.body.push(Inst::Load(merged.clone(), slot));
+            }
+        }
+        self.generate_slot_to_value_ir(merged, result_type, function)
+    }
 
-        let (inst, result_type) = match op {
-            crate::ast::UnaryOp::Not => (
-                Inst::Not {
-                    result: result_reg.clone(),
-                    operand: operand_val,
-                },
-                Ty::Bool,
-            ),
-            crate::ast::UnaryOp::Negate => (
-                Inst::Neg {
-                    result: result_reg.clone(),
-                    operand: operand_val,
-                },
-                operand_type,
-            ),
+    /// `Box::new(value)`: aggregates are copied field by field into a heap
+    /// struct; anything else gets a single heap slot.
+    fn generate_box_new_ir(&mut self, value: Expression, function: &mut Function) -> (Value, Ty) {
+        let (value, value_type) = self.generate_expression_ir(value, function);
+        let struct_type = Self::aggregate_struct_type(&value_type);
+
+        let heap_ptr = match struct_type {
+            Some(struct_type) => self.generate_heap_copy_ir(&value, &struct_type, function),
+            None => {
+                let heap_ptr = Value::Reg(self.next_ptr);
+                self.next_ptr += 1;
+                function.body.push(Inst::HeapAlloc {
+                    result: heap_ptr.clone(),
+                    struct_type: None,
+                });
+                let value = self.generate_value_to_slot_ir(value, &value_type, function);
+                function.body.push(Inst::Store(heap_ptr.clone(), value));
+                heap_ptr
+            }
         };
 
-        function.body.push(inst);
-        (result_reg, result_type)
+        (heap_ptr, Ty::Box(Box::new(value_type)))
     }
 
-    // Function-level I/O and enhanced expression IR generation methods
-    fn generate_print_ir_for_function(
+    /// A heap copy of the `struct_type` aggregate at `value`, field by field.
+    fn generate_heap_copy_ir(
         &mut self,
-        format_string: String,
-        arguments: Vec<Expression>,
-        newline: bool,
-        function_body: &mut Vec<Inst>,
-    ) -> (Value, Ty) {
-        // Generate IR for arguments
-        let mut arg_values = Vec::new();
-        for arg in arguments {
-            let (arg_value, _) = self.generate_expression_ir_for_function(arg, function_body);
-            arg_values.push(arg_value);
+        value: &Value,
+        struct_type: &str,
+        function: &mut Function,
+    ) -> Value {
+        let heap_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::HeapAlloc {
+            result: heap_ptr.clone(),
+            struct_type: Some(struct_type.to_string()),
+        });
+        for field_index in 0..self.aggregate_field_count(struct_type) {
+            let field = self.generate_field_load_ir(value, struct_type, field_index, function);
+            let dest = self.generate_field_ptr_ir(&heap_ptr, struct_type, field_index, function);
+            function.body.push(Inst::Store(dest, field));
         }
+        heap_ptr
+    }
 
-        // Modify format string to add newline if needed
-        let final_format = if newline {
-            format!("{}\n", format_string)
-        } else {
-            format_string
-        };
+    /// A returned value that can outlive the function's frame: structs,
+    /// enums, `Option`s and `Result`s are built in stack slots, so they are
+    /// copied to the heap.
+    fn generate_escaping_value_ir(
+        &mut self,
+        value: Value,
+        ty: &Ty,
+        function: &mut Function,
+    ) -> Value {
+        match ty {
+            Ty::Struct(_) | Ty::Enum(_) | Ty::Option(_) | Ty::Result(_, _) => {
+                let struct_type = Self::aggregate_struct_type(ty).unwrap_or_default();
+                self.generate_heap_copy_ir(&value, &struct_type, function)
+            }
+            _ => value,
+        }
+    }
 
-        // Create print instruction
-        let print_inst = Inst::Print {
-            format_string: final_format,
-            arguments: arg_values,
-        };
+    /// Number of double fields in the LLVM struct backing an aggregate type.
+    fn aggregate_field_count(&self, struct_type: &str) -> u32 {
+        if let Some(layout) = self.struct_layouts.get(struct_type) {
+            return layout.len() as u32;
+        }
+        if let Some(arity) = struct_type.strip_prefix("tuple.") {
+            return arity.parse().unwrap_or(0);
+        }
+        match struct_type {
+            "Option" | "Result" => 2,
+            _ => self.enum_variants.get(struct_type).map_or(1, |variants| {
+                1 + variants
+                    .iter()
+                    .map(|(_, payload)| payload.len() as u32)
+                    .max()
+                    .unwrap_or(0)
+            }),
+        }
+    }
 
-        function_body.push(print_inst);
+    /// Struct type behind a by-pointer aggregate value.
+    fn aggregate_struct_type(ty: &Ty) -> Option<String> {
+        match ty {
+            Ty::Struct(name) | Ty::Enum(name) => Some(name.clone()),
+            Ty::Option(_) => Some("Option".to_string()),
+            Ty::Result(_, _) => Some("Result".to_string()),
+            Ty::TraitObject(name) => Some(format!("dyn.{}", name)),
+            Ty::Tuple(elems) if !elems.is_empty() => Some(format!("tuple.{}", elems.len())),
+            Ty::Iter(_, _) => Some(ITER_STRUCT.to_string()),
+            Ty::Enumerate(_) => Some(ENUMERATE_STRUCT.to_string()),
+            Ty::Zip(_, _) => Some(ZIP_STRUCT.to_string()),
+            Ty::Take(_) => Some(TAKE_STRUCT.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Struct type the pointer of a pointer-backed value points at; `None`
+    /// means a plain double cell (e.g. `Box<i32>`). Fixed-size arrays point
+    /// at their `[N x double]` storage.
+    fn pointee_struct_type(ty: &Ty) -> Option<String> {
+        match ty {
+            Ty::Box(inner) => Self::aggregate_struct_type(inner),
+            Ty::Array(_, len) => Some(format!("[{} x double]", len)),
+            other => Self::aggregate_struct_type(other),
+        }
+    }
 
-        // Print operations return unit type (represented as 0 for now)
-        (Value::ImmInt(0), Ty::Int)
+    /// Values held as a pointer that must be packed to live in a double slot.
+    fn is_pointer_backed(ty: &Ty) -> bool {
+        matches!(ty, Ty::Box(_) | Ty::Array(_, _))
+            || Self::aggregate_struct_type(ty).is_some()
+            || Self::is_scalar_reference(ty)
     }
 
-    fn generate_comparison_ir_for_function(
+    /// `&T` of a scalar `T`, held as a pointer to the double cell storing it.
+    fn is_scalar_reference(ty: &Ty) -> bool {
+        matches!(ty, Ty::Reference(inner, _) if Self::is_scalar(inner))
+    }
+
+    fn is_scalar(ty: &Ty) -> bool {
+        matches!(ty, Ty::Int | Ty::Int64 | Ty::Float | Ty::Bool | Ty::Char)
+    }
+
+    /// Load through a scalar reference used where its pointee is expected,
+    /// as an operand of arithmetic, a comparison or a method call. `&&T` is
+    /// not a scalar reference, so it is left for an explicit `*`.
+    fn generate_auto_deref_ir(
         &mut self,
-        op: crate::ast::ComparisonOp,
-        left: Expression,
-        right: Expression,
-        function_body: &mut Vec<Inst>,
+        value: Value,
+        ty: Ty,
+        function: &mut Function,
     ) -> (Value, Ty) {
-        let (left_val, left_type) = self.generate_expression_ir_for_function(left, function_body);
-        let (right_val, right_type) =
-            self.generate_expression_ir_for_function(right, function_body);
+        match ty {
+            Ty::Reference(inner, _) if Self::is_scalar(&inner) => {
+                let loaded = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function.body.push(Inst::Load(loaded.clone(), value));
+                let loaded = self.generate_slot_to_value_ir(loaded, &inner, function);
+                (loaded, *inner)
+            }
+            ty => (value, ty),
+        }
+    }
 
-        let result_reg = Value::Reg(self.next_reg);
+    /// Convert a value into its double-slot form, packing pointers.
+    /// A string as a double slot: literals are placed in read-only memory,
+    /// while runtime strings already are their packed pointer.
+    fn generate_string_slot_ir(&mut self, value: Value, function: &mut Function) -> Value {
+        let Value::ImmString(text) = value else {
+            return value;
+        };
+        let result = Value::Reg(self.next_reg);
         self.next_reg += 1;
+        function.body.push(Inst::StrConst {
+            result: result.clone(),
+            text,
+        });
+        result
+    }
 
-        // Convert ComparisonOp to string for IR instruction
-        let op_str = match op {
-            crate::ast::ComparisonOp::Equal => "eq",
-            crate::ast::ComparisonOp::NotEqual => "ne",
-            crate::ast::ComparisonOp::LessThan => "slt",
-            crate::ast::ComparisonOp::GreaterThan => "sgt",
-            crate::ast::ComparisonOp::LessEqual => "sle",
-            crate::ast::ComparisonOp::GreaterEqual => "sge",
+    fn generate_value_to_slot_ir(
+        &mut self,
+        value: Value,
+        ty: &Ty,
+        function: &mut Function,
+    ) -> Value {
+        if *ty == Ty::Bool {
+            return self.generate_bool_to_slot_ir(value, function);
+        }
+        if !Self::is_pointer_backed(ty) {
+            return value;
+        }
+        let packed = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function.body.push(Inst::PtrToSlot {
+            result: packed.clone(),
+            ptr: value,
+            struct_type: Self::pointee_struct_type(ty),
+        });
+        packed
+    }
+
+    /// Inverse of `generate_value_to_slot_ir` for a value loaded from a slot.
+    fn generate_slot_to_value_ir(
+        &mut self,
+        value: Value,
+        ty: &Ty,
+        function: &mut Function,
+    ) -> Value {
+        if *ty == Ty::Bool {
+            return self.generate_slot_to_bool_ir(value, function);
+        }
+        if !Self::is_pointer_backed(ty) {
+            return value;
+        }
+        let ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::SlotToPtr {
+            result: ptr.clone(),
+            value,
+            struct_type: Self::pointee_struct_type(ty),
+        });
+        ptr
+    }
+
+    /// A runtime `bool` is an `i1` in registers and 0.0 or 1.0 in a slot.
+    fn generate_bool_to_slot_ir(&mut self, value: Value, function: &mut Function) -> Value {
+        if !matches!(value, Value::Reg(_)) {
+            return value;
+        }
+        let result = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function.body.push(Inst::Select {
+            result: result.clone(),
+            condition: value,
+            if_true: Value::ImmFloat(1.0),
+            if_false: Value::ImmFloat(0.0),
+        });
+        result
+    }
+
+    fn generate_slot_to_bool_ir(&mut self, value: Value, function: &mut Function) -> Value {
+        if !matches!(value, Value::Reg(_)) {
+            return value;
+        }
+        let result = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function.body.push(Inst::FCmp {
+            op: "one".to_string(),
+            result: result.clone(),
+            left: value,
+            right: Value::ImmFloat(0.0),
+        });
+        result
+    }
+
+    fn is_irrefutable_pattern(pattern: &Pattern) -> bool {
+        match pattern {
+            Pattern::Wildcard | Pattern::Identifier(_) => true,
+            Pattern::Tuple(elements) => elements.iter().all(Self::is_irrefutable_pattern),
+            Pattern::Binding { pattern, .. } => Self::is_irrefutable_pattern(pattern),
+            _ => false,
+        }
+    }
+
+    /// Compare two scalar values. Values live in double slots, so runtime
+    /// comparisons use ordered float predicates.
+    fn generate_value_compare_ir(
+        &mut self,
+        op: &str,
+        left: &Value,
+        right: &Value,
+        function: &mut Function,
+    ) -> Value {
+        if let (Some(folded), _) = self.try_constant_fold(op, left, right, &Ty::Bool) {
+            return folded;
+        }
+        if let (Value::ImmString(l), Value::ImmString(r)) = (left, right) {
+            let equal = l == r;
+            return Value::ImmInt(if op == "!=" { !equal } else { equal } as i64);
+        }
+
+        let float_op = match op {
+            "==" => "oeq",
+            "!=" => "one",
+            "<" => "olt",
+            ">" => "ogt",
+            "<=" => "ole",
+            _ => "oge",
         };
+        let result = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function.body.push(Inst::FCmp {
+            op: float_op.to_string(),
+            result: result.clone(),
+            left: left.clone(),
+            right: right.clone(),
+        });
+        result
+    }
 
-        // Generate appropriate comparison instruction based on operand types
-        let inst = match (&left_type, &right_type) {
-            (Ty::Int, Ty::Int) => Inst::ICmp {
-                op: op_str.to_string(),
-                result: result_reg.clone(),
-                left: left_val,
-                right: right_val,
-            },
-            (Ty::Float, Ty::Float) => {
-                // Convert integer comparison ops to float comparison ops
-                let float_op = match op_str {
-                    "eq" => "oeq",
-                    "ne" => "one",
-                    "slt" => "olt",
-                    "sgt" => "ogt",
-                    "sle" => "ole",
-                    "sge" => "oge",
-                    _ => op_str,
-                };
-                Inst::FCmp {
-                    op: float_op.to_string(),
-                    result: result_reg.clone(),
-                    left: left_val,
-                    right: right_val,
-                }
-            }
-            (Ty::Int, Ty::Float) => {
-                // Promote left operand to float
-                let promoted_left = Value::Reg(self.next_reg);
+    fn generate_and_ir(&mut self, left: Value, right: Value, function: &mut Function) -> Value {
+        match (&left, &right) {
+            (Value::ImmInt(0), _) | (_, Value::ImmInt(0)) => Value::ImmInt(0),
+            (Value::ImmInt(_), _) => right,
+            (_, Value::ImmInt(_)) => left,
+            _ => {
+                let result = Value::Reg(self.next_reg);
                 self.next_reg += 1;
-                function_body.push(Inst::SIToFP(promoted_left.clone(), left_val));
+                function.body.push(Inst::And {
+                    result: result.clone(),
+                    left,
+                    right,
+                });
+                result
+            }
+        }
+    }
 
-                let float_op = match op_str {
-                    "eq" => "oeq",
-                    "ne" => "one",
-                    "slt" => "olt",
-                    "sgt" => "ogt",
-                    "sle" => "ole",
-                    "sge" => "oge",
-                    _ => op_str,
+    fn try_constant_fold(
+        &self,
+        op: &str,
+        lhs: &Value,
+        rhs: &Value,
+        result_type: &Ty,
+    ) -> (Option<Value>, Option<Ty>) {
+        match (lhs, rhs, result_type) {
+            (Value::ImmInt(l), Value::ImmInt(r), ty @ (Ty::Int | Ty::Int64)) => {
+                // Results that overflow an i64 are left to the runtime.
+                let result = match op {
+                    "+" => l.checked_add(*r),
+                    "-" => l.checked_sub(*r),
+                    "*" => l.checked_mul(*r),
+                    "/" => l.checked_div(*r),
+                    _ => None,
                 };
-                Inst::FCmp {
-                    op: float_op.to_string(),
-                    result: result_reg.clone(),
-                    left: promoted_left,
-                    right: right_val,
+                match result {
+                    Some(result) => (Some(Value::ImmInt(result)), Some(ty.clone())),
+                    None => (None, None),
                 }
             }
-            (Ty::Float, Ty::Int) => {
-                // Promote right operand to float
-                let promoted_right = Value::Reg(self.next_reg);
-                self.next_reg += 1;
-                function_body.push(Inst::SIToFP(promoted_right.clone(), right_val));
-
-                let float_op = match op_str {
-                    "eq" => "oeq",
-                    "ne" => "one",
-                    "slt" => "olt",
-                    "sgt" => "ogt",
-                    "sle" => "ole",
-                    "sge" => "oge",
-                    _ => op_str,
+            (Value::ImmFloat(l), Value::ImmFloat(r), Ty::Float) => {
+                let result = match op {
+                    "+" => l + r,
+                    "-" => l - r,
+                    "*" => l * r,
+                    "/" => l / r,
+                    _ => return (None, None),
                 };
-                Inst::FCmp {
-                    op: float_op.to_string(),
-                    result: result_reg.clone(),
-                    left: left_val,
-                    right: promoted_right,
-                }
+                (Some(Value::ImmFloat(result)), Some(Ty::Float))
             }
-            (Ty::Bool, Ty::Bool) => Inst::ICmp {
-                op: op_str.to_string(),
-                result: result_reg.clone(),
-                left: left_val,
-                right: right_val,
-            },
-            _ => panic!(
-                "Unsupported comparison between {:?} and {:?}",
-                left_type, right_type
-            ),
-        };
-
-        function_body.push(inst);
-        (result_reg, Ty::Bool)
+            // Booleans are represented as ImmInt(0/1), so logical ops and
+            // comparisons on int/bool immediates fold the same way.
+            (Value::ImmInt(l), Value::ImmInt(r), Ty::Bool) => {
+                let result = match op {
+                    "&&" => *l != 0 && *r != 0,
+                    "||" => *l != 0 || *r != 0,
+                    "==" => l == r,
+                    "!=" => l != r,
+                    "<" => l < r,
+                    ">" => l > r,
+                    "<=" => l <= r,
+                    ">=" => l >= r,
+                    _ => return (None, None),
+                };
+                (Some(Value::ImmInt(result as i64)), Some(Ty::Bool))
+            }
+            (Value::ImmFloat(l), Value::ImmFloat(r), Ty::Bool) => {
+                let result = match op {
+                    "==" => l == r,
+                    "!=" => l != r,
+                    "<" => l < r,
+                    ">" => l > r,
+                    "<=" => l <= r,
+                    ">=" => l >= r,
+                    _ => return (None, None),
+                };
+                (Some(Value::ImmInt(result as i64)), Some(Ty::Bool))
+            }
+            _ => (None, None),
+        }
     }
 
-    fn generate_logical_ir_for_function(
+    fn generate_function_definition_ir(
         &mut self,
-        op: crate::ast::LogicalOp,
-        left: Expression,
-        right: Expression,
-        function_body: &mut Vec<Inst>,
-    ) -> (Value, Ty) {
-        let (left_val, _) = self.generate_expression_ir_for_function(left, function_body);
-        let (right_val, _) = self.generate_expression_ir_for_function(right, function_body);
+        name: String,
+        parameters: Vec<crate::ast::Parameter>,
+        return_type: Option<Type>,
+        body: crate::ast::Block,
+        current_function: &mut Function,
+    ) {
+        // Record the return type up front so recursive calls see it.
+        let declared_return = return_type
+            .as_ref()
+            .map_or(Ty::Unit, |ty| self.ast_type_to_ty(ty));
+        self.function_returns
+            .insert(name.clone(), declared_return.clone());
+        let declared_params = parameters
+            .iter()
+            .map(|param| self.ast_type_to_ty(&param.param_type))
+            .collect();
+        self.function_params.insert(name.clone(), declared_params);
 
-        let result_reg = Value::Reg(self.next_reg);
-        self.next_reg += 1;
+        // Save current state
+        let saved_symbol_table = self.symbol_table.clone();
+        let saved_next_reg = self.next_reg;
+        let saved_next_ptr = self.next_ptr;
+        let saved_function_name = std::mem::replace(&mut self.current_function_name, name.clone());
 
-        let inst = match op {
-            crate::ast::LogicalOp::And => Inst::And {
-                result: result_reg.clone(),
-                left: left_val,
-                right: right_val,
-            },
-            crate::ast::LogicalOp::Or => Inst::Or {
-                result: result_reg.clone(),
-                left: left_val,
-                right: right_val,
-            },
-        };
+        // Reset for function generation
+        self.symbol_table.clear();
+        self.next_reg = 0;
+        self.next_ptr = 0;
 
-        function_body.push(inst);
-        (result_reg, Ty::Bool)
-    }
+        // Create parameter names and types for IR
+        let param_names: Vec<(String, String)> = parameters
+            .iter()
+            .map(|p| (p.name.clone(), Self::ir_param_type_name(&p.param_type)))
+            .collect();
 
-    fn generate_unary_ir_for_function(
-        &mut self,
-        op: crate::ast::UnaryOp,
-        operand: Expression,
-        function_body: &mut Vec<Inst>,
-    ) -> (Value, Ty) {
-        let (operand_val, operand_type) =
-            self.generate_expression_ir_for_function(operand, function_body);
+        // Set up parameter variables in symbol table
+        for param in &parameters {
+            let ptr_reg = Value::Reg(self.next_ptr);
+            self.next_ptr += 1;
 
-        let result_reg = Value::Reg(self.next_reg);
-        self.next_reg += 1;
+            // Convert AST Type to Ty
+            let param_type = self.ast_type_to_ty(&param.param_type);
 
-        let (inst, result_type) = match op {
-            crate::ast::UnaryOp::Not => (
-                Inst::Not {
-                    result: result_reg.clone(),
-                    operand: operand_val,
-                },
-                Ty::Bool,
-            ),
-            crate::ast::UnaryOp::Negate => (
-                Inst::Neg {
-                    result: result_reg.clone(),
-                    operand: operand_val,
-                },
-                operand_type,
-            ),
+            self.symbol_table
+                .insert(param.name.clone(), (ptr_reg, param_type));
+        }
+
+        // Generate function body IR
+        let mut function_ir = Function {
+            name: name.clone(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
         };
 
-        function_body.push(inst);
-        (result_reg, result_type)
-    }
-}
+        // Allocate parameters
+        for param in &parameters {
+            let (ptr_reg, param_type) = self.symbol_table.get(&param.name).unwrap().clone();
+            function_ir
+                .body
+                .push(Inst::Alloca(ptr_reg.clone(), param.name.clone()));
+
+            // Aggregate parameters arrive as packed pointers; recover the
+            // pointer so the body can address fields directly.
+            if Self::is_pointer_backed(&param_type) {
+                let packed = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function_ir.body.push(Inst::Load(packed.clone(), ptr_reg));
+                let ptr = self.generate_slot_to_value_ir(packed, &param_type, &mut function_ir);
+                self.symbol_table
+                    .insert(param.name.clone(), (ptr, param_type));
+            }
+        }
+
+        // Generate statements. A trailing `if`/`else` is the value of a
+        // function that returns one.
+        let mut statements = body.statements;
+        let trailing_if = match statements.last() {
+            Some(Statement::If {
+                else_block: Some(_),
+                ..
+            }) if body.expression.is_none() && declared_return != Ty::Unit => statements.pop(),
+            _ => None,
+        };
+        for stmt in statements {
+            self.generate_statement_ir(stmt, &mut function_ir);
+        }
+        let tail = match (body.expression, trailing_if) {
+            (Some(expr), _) => Some(self.generate_expression_ir(expr, &mut function_ir)),
+            (
+                None,
+                Some(Statement::If {
+                    condition,
+                    then_block,
+                    else_block: Some(else_stmt),
+                }),
+            ) => {
+                Some(self.generate_if_value_ir(condition, then_block, *else_stmt, &mut function_ir))
+            }
+            _ => None,
+        };
+
+        // Handle block expression (implicit return) or default return when needed.
+        if let Some((return_value, tail_type)) = tail {
+            let (return_value, tail_type) = if return_type.is_some() {
+                self.generate_coercion_ir(
+                    return_value,
+                    tail_type,
+                    &declared_return,
+                    &mut function_ir,
+                )
+            } else {
+                (return_value, tail_type)
+            };
+            // A `main` without a return type exits with 0 whatever its tail is.
+            let return_value = if name == "main" && return_type.is_none() {
+                Value::ImmInt(0)
+            } else {
+                let return_value =
+                    self.generate_escaping_value_ir(return_value, &tail_type, &mut function_ir);
+                self.generate_value_to_slot_ir(return_value, &tail_type, &mut function_ir)
+            };
+            function_ir.body.push(Inst::Return(return_value));
+        } else if !matches!(
+            function_ir.body.last(),
+            Some(Inst::Return(_) | Inst::Unreachable | Inst::Jump(_) | Inst::Branch { .. })
+        ) {
+            if declared_return == Ty::Unit {
+                // Falling off the end of a unit function returns; codegen
+                // lowers it as `ret void` (`ret i32 0` for `main`).
+                function_ir.body.push(Inst::Return(Value::ImmInt(0)));
+            } else {
+                // The semantic analyzer checked that every path returns.
+                function_ir.body.push(Inst::Unreachable);
+            }
+        }
+
+        // `-> ()` is lowered like an omitted return type.
+        let ir_return_type = return_type
+            .as_ref()
+            .filter(|_| declared_return != Ty::Unit)
+            .map(|ty| self.ast_type_to_ir_name(ty));
+
+        // Create function definition instruction
+        let func_def = Inst::FunctionDef {
+            name: name.clone(),
+            parameters: param_names,
+            return_type: ir_return_type,
+            body: function_ir.body.clone(),
+            no_inline: self.no_inline.contains(&name),
+        };
+
+        // Add function definition to current function (main)
+        current_function.body.push(func_def);
+
+        // Create and store function in functions map
+        let function = Function {
+            name: name.clone(),
+            body: vec![], // The actual body is in the FunctionDef instruction
+            next_reg: self.next_reg,
+            next_ptr: self.next_ptr,
+        };
+        self.functions.insert(name, function);
+
+        // Restore state
+        self.symbol_table = saved_symbol_table;
+        self.next_reg = saved_next_reg;
+        self.next_ptr = saved_next_ptr;
+        self.current_function_name = saved_function_name;
+    }
+
+    fn generate_statement_ir_for_function(
+        &mut self,
+        stmt: Statement,
+        function_body: &mut Vec<Inst>,
+    ) {
+        match stmt {
+            Statement::Let {
+                name,
+                mutable: _,
+                type_annotation: _,
+                value,
+            } => {
+                let (expr_value, expr_type) = if let Some(val) = value {
+                    self.generate_expression_ir_for_function(val, function_body)
+                } else {
+                    (Value::ImmInt(0), Ty::Int)
+                };
+
+                if Self::stores_value_directly(&expr_type) {
+                    self.symbol_table.insert(name, (expr_value, expr_type));
+                } else {
+                    // Allocate a stack slot for the variable
+                    let ptr_reg = Value::Reg(self.next_ptr);
+                    self.next_ptr += 1;
+                    function_body.push(Inst::Alloca(ptr_reg.clone(), name.clone()));
+                    self.symbol_table.insert(name, (ptr_reg.clone(), expr_type));
+
+                    // Store the expression result into the allocated slot
+                    function_body.push(Inst::Store(ptr_reg, expr_value));
+                }
+            }
+            Statement::Return(expr) => {
+                let (return_value, _) = if let Some(val) = expr {
+                    self.generate_expression_ir_for_function(val, function_body)
+                } else {
+                    (Value::ImmInt(0), Ty::Int)
+                };
+                function_body.push(Inst::Return(return_value));
+            }
+            Statement::Function { .. } => {
+                // Nested functions not supported yet
+                println!("Warning: Nested function definitions are not supported");
+            }
+            _ => {
+                // Other statements not implemented yet
+                println!("Warning: Statement type not yet implemented in function body");
+            }
+        }
+    }
+
+    fn generate_expression_ir_for_function(
+        &mut self,
+        expr: Expression,
+        function_body: &mut Vec<Inst>,
+    ) -> (Value, Ty) {
+        match expr {
+            Expression::IntegerLiteral(n) => (Value::ImmInt(n), Ty::of_integer_literal(n)),
+            Expression::FloatLiteral(f) => (Value::ImmFloat(f), Ty::Float),
+            Expression::BooleanLiteral(b) => (Value::ImmInt(b as i64), Ty::Bool),
+            Expression::CharLiteral(c) => (Value::ImmInt(c as i64), Ty::Char),
+            Expression::Identifier(name) => {
+                let (storage, var_type) = self
+                    .symbol_table
+                    .get(&name)
+                    .expect("Undeclared variable")
+                    .clone();
+                if Self::stores_value_directly(&var_type) {
+                    return (storage, var_type);
+                }
+                let result_reg = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function_body.push(Inst::Load(result_reg.clone(), storage));
+                (result_reg, var_type)
+            }
+            Expression::Binary {
+                op,
+                left,
+                right,
+                ty,
+            } => {
+                let (lhs_val, lhs_type) =
+                    self.generate_expression_ir_for_function(*left, function_body);
+                let (rhs_val, rhs_type) =
+                    self.generate_expression_ir_for_function(*right, function_body);
+
+                // Prefer semantic type annotation when present, but don't require it.
+                // Some front-end paths still produce untyped binary nodes.
+                let result_type = ty.unwrap_or_else(|| match (&lhs_type, &rhs_type) {
+                    (Ty::Float, _) | (_, Ty::Float) => Ty::Float,
+                    (Ty::Int, Ty::Int) => Ty::Int,
+                    (l, r) if l.is_integer() && r.is_integer() => Ty::Int64,
+                    (l, r) => panic!(
+                        "Cannot infer binary op result type for operand types {:?} and {:?}",
+                        l, r
+                    ),
+                });
+
+                // Handle type promotion if needed
+                let (promoted_lhs, promoted_rhs) = self.handle_type_promotion_for_function(
+                    lhs_val,
+                    lhs_type,
+                    rhs_val,
+                    rhs_type,
+                    &result_type,
+                    function_body,
+                );
+
+                // Try constant folding first
+                if let (Some(folded_value), Some(folded_type)) =
+                    self.try_constant_fold(op.as_str(), &promoted_lhs, &promoted_rhs, &result_type)
+                {
+                    return (folded_value, folded_type);
+                }
+
+                let result_reg = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+
+                // Generate the appropriate instruction based on result type
+                let inst = match (&result_type, op.as_str()) {
+                    (Ty::Int | Ty::Int64, "+") => {
+                        Inst::Add(result_reg.clone(), promoted_lhs, promoted_rhs)
+                    }
+                    (Ty::Float, "+") => Inst::FAdd(result_reg.clone(), promoted_lhs, promoted_rhs),
+                    (Ty::Int | Ty::Int64, "-") => {
+                        Inst::Sub(result_reg.clone(), promoted_lhs, promoted_rhs)
+                    }
+                    (Ty::Float, "-") => Inst::FSub(result_reg.clone(), promoted_lhs, promoted_rhs),
+                    (Ty::Int | Ty::Int64, "*") => {
+                        Inst::Mul(result_reg.clone(), promoted_lhs, promoted_rhs)
+                    }
+                    (Ty::Float, "*") => Inst::FMul(result_reg.clone(), promoted_lhs, promoted_rhs),
+                    (Ty::Int | Ty::Int64, "/") => {
+                        Inst::Div(result_reg.clone(), promoted_lhs, promoted_rhs)
+                    }
+                    (Ty::Float, "/") => Inst::FDiv(result_reg.clone(), promoted_lhs, promoted_rhs),
+                    _ => panic!(
+                        "Unsupported binary operation: {} for type {:?}",
+                        op, result_type
+                    ),
+                };
+
+                function_body.push(inst);
+                (result_reg, result_type)
+            }
+            Expression::FunctionCall { name, arguments } => {
+                self.generate_function_call_ir(name, arguments, function_body)
+            }
+            Expression::Print {
+                format_string,
+                arguments,
+            } => {
+                self.generate_print_ir_for_function(format_string, arguments, false, function_body)
+            }
+            Expression::Println {
+                format_string,
+                arguments,
+            } => self.generate_print_ir_for_function(format_string, arguments, true, function_body),
+            Expression::Comparison { op, left, right } => {
+                self.generate_comparison_ir_for_function(op, *left, *right, function_body)
+            }
+            Expression::Logical { op, left, right } => {
+                self.generate_logical_ir_for_function(op, *left, *right, function_body)
+            }
+            Expression::Unary { op, operand } => {
+                self.generate_unary_ir_for_function(op, *operand, function_body)
+            }
+            // Phase 4 stubs for function-level IR
+            Expression::StringLiteral(s) => (Value::ImmString(s), Ty::String),
+            Expression::MethodCall {
+                object,
+                method,
+                arguments,
+                ..
+            } => {
+                let (object_value, object_ty) =
+                    self.generate_expression_ir_for_function(*object, function_body);
+                if method == "iter"
+                    && arguments.is_empty()
+                    && matches!(object_ty, Ty::Array(_, _) | Ty::Vec(_))
+                {
+                    (object_value, object_ty)
+                } else {
+                    (Value::ImmInt(0), Ty::Int)
+                }
+            }
+            Expression::ArrayLiteral(_)
+            | Expression::ArrayRepeat { .. }
+            | Expression::IndexAccess { .. }
+            | Expression::FieldAccess { .. }
+            | Expression::TupleLiteral(_)
+            | Expression::TupleIndex { .. }
+            | Expression::StructLiteral { .. }
+            | Expression::EnumVariant { .. }
+            | Expression::Match { .. }
+            | Expression::IfLet { .. }
+            | Expression::Block(_)
+            | Expression::Borrow { .. }
+            | Expression::Deref(_)
+            | Expression::BoxNew(_)
+            | Expression::Range { .. }
+            | Expression::NamedArgument { .. } => (Value::ImmInt(0), Ty::Int),
+            Expression::Matches { .. } => (Value::ImmInt(0), Ty::Bool),
+            Expression::Panic { message, location } => {
+                self.generate_diverging_panic_ir(message, location, function_body)
+            }
+            Expression::Closure { params, body } => self.lower_closure_expression(params, *body),
+        }
+    }
+
+    fn handle_type_promotion_for_function(
+        &mut self,
+        lhs_val: Value,
+        lhs_type: Ty,
+        rhs_val: Value,
+        rhs_type: Ty,
+        target_type: &Ty,
+        function_body: &mut Vec<Inst>,
+    ) -> (Value, Value) {
+        let promoted_lhs = if needs_promotion(&lhs_type, target_type) {
+            let promoted_reg = Value::Reg(self.next_reg);
+            self.next_reg += 1;
+            function_body.push(Inst::SIToFP(promoted_reg.clone(), lhs_val));
+            promoted_reg
+        } else {
+            lhs_val
+        };
+
+        let promoted_rhs = if needs_promotion(&rhs_type, target_type) {
+            let promoted_reg = Value::Reg(self.next_reg);
+            self.next_reg += 1;
+            function_body.push(Inst::SIToFP(promoted_reg.clone(), rhs_val));
+            promoted_reg
+        } else {
+            rhs_val
+        };
+
+        (promoted_lhs, promoted_rhs)
+    }
+
+    fn generate_function_call_ir(
+        &mut self,
+        name: String,
+        arguments: Vec<Expression>,
+        function_body: &mut Vec<Inst>,
+    ) -> (Value, Ty) {
+        // Generate IR for arguments, promoting ints passed to float parameters
+        let mut arg_values = Vec::new();
+        let param_types = self.function_params.get(&name).cloned().unwrap_or_default();
+        for (i, arg) in arguments.into_iter().enumerate() {
+            let (arg_value, arg_type) =
+                self.generate_expression_ir_for_function(arg, function_body);
+            let arg_value = match param_types.get(i) {
+                Some(param_type) if needs_promotion(&arg_type, param_type) => {
+                    let promoted_reg = Value::Reg(self.next_reg);
+                    self.next_reg += 1;
+                    function_body.push(Inst::SIToFP(promoted_reg.clone(), arg_value));
+                    promoted_reg
+                }
+                _ => arg_value,
+            };
+            arg_values.push(arg_value);
+        }
+
+        // Generate result register for function call
+        let result_reg = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+
+        // Resolve closure variables to their generated function symbol.
+        let function_name = self.resolve_callable_name(&name);
+
+        // Create function call instruction
+        let call_inst = Inst::Call {
+            function: function_name,
+            arguments: arg_values,
+            result: Some(result_reg.clone()),
+        };
+
+        function_body.push(call_inst);
+
+        // For now, assume function calls return int (this should be looked up from function table in semantic analysis)
+        (result_reg, Ty::Int)
+    }
+
+    // Control flow IR generation methods
+    fn generate_if_statement_ir(
+        &mut self,
+        condition: Expression,
+        then_block: crate::ast::Block,
+        else_block: Option<Box<Statement>>,
+        current_function: &mut Function,
+    ) {
+        // Generate condition evaluation
+        let (cond_value, _) = self.generate_expression_ir(condition, current_function);
+
+        // Generate unique labels
+        let then_label = format!("if_then_{}", self.next_reg);
+        self.next_reg += 1;
+        let else_label = format!("if_else_{}", self.next_reg);
+        self.next_reg += 1;
+        let end_label = format!("if_end_{}", self.next_reg);
+        self.next_reg += 1;
+
+        // Branch based on condition
+        current_function.body.push(Inst::Branch {
+            condition: cond_value,
+            true_label: then_label.clone(),
+            false_label: else_label.clone(),
+        });
+
+        // Generate then block
+        current_function.body.push(Inst::Label(then_label));
+        for stmt in then_block.statements {
+            self.generate_statement_ir(stmt, current_function);
+        }
+        if let Some(expr) = then_block.expression {
+            self.generate_expression_ir(expr, current_function);
+        }
+        current_function.body.push(Inst::Jump(end_label.clone()));
+
+        // Generate else block
+        current_function.body.push(Inst::Label(else_label));
+        if let Some(else_stmt) = else_block {
+            self.generate_statement_ir(*else_stmt, current_function);
+        }
+        current_function.body.push(Inst::Jump(end_label.clone()));
+
+        // End label
+        current_function.body.push(Inst::Label(end_label));
+    }
+
+    fn generate_while_loop_ir(
+        &mut self,
+        condition: Expression,
+        body: crate::ast::Block,
+        current_function: &mut Function,
+    ) {
+        // Generate unique labels
+        let loop_start = format!("while_start_{}", self.next_reg);
+        self.next_reg += 1;
+        let loop_body = format!("while_body_{}", self.next_reg);
+        self.next_reg += 1;
+        let loop_end = format!("while_end_{}", self.next_reg);
+        self.next_reg += 1;
+
+        // Push loop labels onto stack for break/continue
+        self.loop_label_stack
+            .push((loop_start.clone(), loop_end.clone()));
+
+        // Jump to loop start
+        current_function.body.push(Inst::Jump(loop_start.clone()));
+
+        // Loop start - evaluate condition
+        current_function.body.push(Inst::Label(loop_start.clone()));
+        let (cond_value, _) = self.generate_expression_ir(condition, current_function);
+        current_function.body.push(Inst::Branch {
+            condition: cond_value,
+            true_label: loop_body.clone(),
+            false_label: loop_end.clone(),
+        });
+
+        // Loop body
+        current_function.body.push(Inst::Label(loop_body));
+        for stmt in body.statements {
+            self.generate_statement_ir(stmt, current_function);
+        }
+        if let Some(expr) = body.expression {
+            self.generate_expression_ir(expr, current_function);
+        }
+        current_function.body.push(Inst::Jump(loop_start));
+
+        // Pop loop labels
+        self.loop_label_stack.pop();
+
+        // Loop end
+        current_function.body.push(Inst::Label(loop_end));
+    }
+
+    fn generate_for_loop_ir(
+        &mut self,
+        variable: String,
+        iterable: Expression,
+        body: crate::ast::Block,
+        current_function: &mut Function,
+    ) {
+        if let Expression::Range {
+            start,
+            end,
+            inclusive,
+        } = iterable
+        {
+            let start = self.generate_expression_ir(*start, current_function);
+            let start = self.generate_auto_deref_ir(start.0, start.1, current_function);
+            let (end, end_type) = self.generate_expression_ir(*end, current_function);
+            let (end, end_type) = self.generate_auto_deref_ir(end, end_type, current_function);
+            let start = match end_type {
+                Ty::Int64 => {
+                    self.generate_coercion_ir(start.0, start.1, &Ty::Int64, current_function)
+                }
+                _ => start,
+            };
+            self.generate_counted_for_loop_ir(
+                variable,
+                start,
+                end,
+                inclusive,
+                body,
+                current_function,
+            );
+            return;
+        }
+        let (iter_value, iter_type) = self.generate_expression_ir(iterable, current_function);
+        match iter_type {
+            Ty::Array(elem_ty, len) => {
+                let (iter, iter_ty) =
+                    self.generate_iter_ir(iter_value, *elem_ty, len, current_function);
+                self.generate_iterator_for_loop_ir(variable, iter, iter_ty, body, current_function);
+            }
+            iter_ty if iter_ty.iterator_item().is_some() => {
+                self.generate_iterator_for_loop_ir(
+                    variable,
+                    iter_value,
+                    iter_ty,
+                    body,
+                    current_function,
+                );
+            }
+            other => {
+                // Legacy lowering of a bare range start: count up to 10.
+                self.generate_counted_for_loop_ir(
+                    variable,
+                    (iter_value, other),
+                    Value::ImmInt(10),
+                    false,
+                    body,
+                    current_function,
+                );
+            }
+        }
+    }
+
+    /// `for x in iter`: call `next()` at the top of every iteration and leave
+    /// the loop on `None`. Arrays are walked through a fresh iterator.
+    fn generate_iterator_for_loop_ir(
+        &mut self,
+        variable: String,
+        iter: Value,
+        iter_ty: Ty,
+        body: crate::ast::Block,
+        current_function: &mut Function,
+    ) {
+        let element_ty = iter_ty.iterator_item().unwrap_or(Ty::Int);
+        let loop_start = format!("for_start_{}", self.next_reg);
+        self.next_reg += 1;
+        let loop_body = format!("for_body_{}", self.next_reg);
+        self.next_reg += 1;
+        let loop_end = format!("for_end_{}", self.next_reg);
+        self.next_reg += 1;
+
+        self.loop_label_stack
+            .push((loop_start.clone(), loop_end.clone()));
+
+        // User-visible loop variable slot (updated each iteration with current element).
+        let loop_var_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        current_function
+            .body
+            .push(Inst::Alloca(loop_var_ptr.clone(), variable.clone()));
+        current_function
+            .body
+            .push(Inst::Store(loop_var_ptr.clone(), Value::ImmInt(0)));
+        // The loop variable is only visible in the body, so a nested loop
+        // reusing its name does not replace it.
+        let saved_symbol_table = self.symbol_table.clone();
+        self.symbol_table
+            .insert(variable.clone(), (loop_var_ptr.clone(), element_ty.clone()));
+
+        // One `Option` reused by every `next()`, so the loop does not grow the stack.
+        let option_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        current_function.body.push(Inst::AllocaStruct {
+            result: option_ptr.clone(),
+            struct_type: "Option".to_string(),
+        });
+
+        current_function.body.push(Inst::Jump(loop_start.clone()));
+
+        // Header: next() returned Some?
+        current_function.body.push(Inst::Label(loop_start.clone()));
+        self.generate_iter_next_ir(&iter, &iter_ty, &option_ptr, current_function);
+        let tag = self.generate_field_load_ir(&option_ptr, "Option", 0, current_function);
+        let is_some =
+            self.generate_value_compare_ir("==", &tag, &Value::ImmInt(1), current_function);
+        current_function.body.push(Inst::Branch {
+            condition: is_some,
+            true_label: loop_body.clone(),
+            false_label: loop_end.clone(),
+        });
+
+        // Body: bind the payload to the loop variable, then execute the body.
+        current_function.body.push(Inst::Label(loop_body));
+        let element = self.generate_field_load_ir(&option_ptr, "Option", 1, current_function);
+        if Self::is_pointer_backed(&element_ty) {
+            // Aggregates are bound by pointer, as `let` binds them.
+            let element = self.generate_slot_to_value_ir(element, &element_ty, current_function);
+            self.symbol_table.insert(variable, (element, element_ty));
+        } else {
+            current_function
+                .body
+                .push(Inst::Store(loop_var_ptr, element));
+        }
+
+        for stmt in body.statements {
+            self.generate_statement_ir(stmt, current_function);
+        }
+        if let Some(expr) = body.expression {
+            self.generate_expression_ir(expr, current_function);
+        }
+        self.symbol_table = saved_symbol_table;
+        current_function.body.push(Inst::Jump(loop_start));
+
+        self.loop_label_stack.pop();
+        current_function.body.push(Inst::Label(loop_end));
+    }
+
+    /// `for i in start..end` (or `..=end`): the bound is evaluated once,
+    /// before the loop. `continue` jumps to the step block, which advances
+    /// the counter before the condition is checked again.
+    fn generate_counted_for_loop_ir(
+        &mut self,
+        variable: String,
+        (start_value, var_type): (Value, Ty),
+        end_value: Value,
+        inclusive: bool,
+        body: crate::ast::Block,
+        current_function: &mut Function,
+    ) {
+        let loop_start = format!("for_start_{}", self.next_reg);
+        self.next_reg += 1;
+        let loop_body = format!("for_body_{}", self.next_reg);
+        self.next_reg += 1;
+        let loop_step = format!("for_step_{}", self.next_reg);
+        self.next_reg += 1;
+        let loop_end = format!("for_end_{}", self.next_reg);
+        self.next_reg += 1;
+
+        self.loop_label_stack
+            .push((loop_step.clone(), loop_end.clone()));
+
+        let var_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        current_function
+            .body
+            .push(Inst::Alloca(var_ptr.clone(), variable.clone()));
+        current_function
+            .body
+            .push(Inst::Store(var_ptr.clone(), start_value));
+        // Scoped to the body, like the iterator loop's variable.
+        let saved_symbol_table = self.symbol_table.clone();
+        self.symbol_table
+            .insert(variable.clone(), (var_ptr.clone(), var_type));
+
+        current_function.body.push(Inst::Jump(loop_start.clone()));
+
+        current_function.body.push(Inst::Label(loop_start.clone()));
+        let counter = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        current_function
+            .body
+            .push(Inst::Load(counter.clone(), var_ptr.clone()));
+        let op = if inclusive { "<=" } else { "<" };
+        let in_range = self.generate_value_compare_ir(op, &counter, &end_value, current_function);
+        current_function.body.push(Inst::Branch {
+            condition: in_range,
+            true_label: loop_body.clone(),
+            false_label: loop_end.clone(),
+        });
+
+        current_function.body.push(Inst::Label(loop_body));
+        for stmt in body.statements {
+            self.generate_statement_ir(stmt, current_function);
+        }
+        if let Some(expr) = body.expression {
+            self.generate_expression_ir(expr, current_function);
+        }
+        self.symbol_table = saved_symbol_table;
+        current_function.body.push(Inst::Jump(loop_step.clone()));
+
+        current_function.body.push(Inst::Label(loop_step));
+        let counter = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        current_function
+            .body
+            .push(Inst::Load(counter.clone(), var_ptr.clone()));
+        let incremented = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        current_function
+            .body
+            .push(Inst::Add(incremented.clone(), counter, Value::ImmInt(1)));
+        current_function
+            .body
+            .push(Inst::Store(var_ptr, incremented));
+        current_function.body.push(Inst::Jump(loop_start));
+
+        self.loop_label_stack.pop();
+        current_function.body.push(Inst::Label(loop_end));
+    }
+
+    fn generate_infinite_loop_ir(
+        &mut self,
+        body: crate::ast::Block,
+        current_function: &mut Function,
+    ) {
+        // Generate unique labels
+        let loop_start = format!("loop_start_{}", self.next_reg);
+        self.next_reg += 1;
+        let loop_end = format!("loop_end_{}", self.next_reg);
+        self.next_reg += 1;
+
+        // Push loop labels onto stack for break/continue
+        self.loop_label_stack
+            .push((loop_start.clone(), loop_end.clone()));
+
+        // Jump to loop start
+        current_function.body.push(Inst::Jump(loop_start.clone()));
+
+        // Loop start
+        current_function.body.push(Inst::Label(loop_start.clone()));
+
+        // Loop body
+        for stmt in body.statements {
+            self.generate_statement_ir(stmt, current_function);
+        }
+        if let Some(expr) = body.expression {
+            self.generate_expression_ir(expr, current_function);
+        }
+
+        // Jump back to start (infinite loop)
+        current_function.body.push(Inst::Jump(loop_start));
+
+        // Pop loop labels
+        self.loop_label_stack.pop();
+
+        // Loop end (reachable via break)
+        current_function.body.push(Inst::Label(loop_end));
+    }
+
+    fn generate_break_ir(&mut self, current_function: &mut Function) {
+        if let Some((_loop_start, loop_end)) = self.loop_label_stack.last() {
+            let break_label = loop_end.clone();
+            current_function.body.push(Inst::Jump(break_label));
+        } else {
+            panic!("Break statement outside of loop");
+        }
+    }
+
+    fn generate_continue_ir(&mut self, current_function: &mut Function) {
+        if let Some((loop_start, _loop_end)) = self.loop_label_stack.last() {
+            let continue_label = loop_start.clone();
+            current_function.body.push(Inst::Jump(continue_label));
+        } else {
+            panic!("Continue statement outside of loop");
+        }
+    }
+
+    fn ast_type_to_ty(&self, ty: &Type) -> Ty {
+        match ty {
+            Type::Named(name) => match name.as_str() {
+                "i32" | "int" => Ty::Int,
+                "i64" => Ty::Int64,
+                "f64" | "float" => Ty::Float,
+                "bool" => Ty::Bool,
+                "char" => Ty::Char,
+                "String" => Ty::String,
+                "!" => Ty::Never,
+                other if self.enum_variants.contains_key(other) => Ty::Enum(other.to_string()),
+                other => Ty::Struct(other.to_string()),
+            },
+            Type::Array(elem, size) => Ty::Array(Box::new(self.ast_type_to_ty(elem)), *size),
+            // Only reachable before instantiation fixes the length.
+            Type::ConstArray(elem, _) => Ty::Vec(Box::new(self.ast_type_to_ty(elem))),
+            Type::Tuple(types) if types.is_empty() => Ty::Unit,
+            Type::Tuple(types) => Ty::Tuple(types.iter().map(|t| self.ast_type_to_ty(t)).collect()),
+            Type::Reference(inner, mutable) => match self.ast_type_to_ty(inner) {
+                // References to pointer-backed values share their pointer.
+                inner if Self::is_pointer_backed(&inner) => inner,
+                inner => Ty::Reference(Box::new(inner), *mutable),
+            },
+            // Analysis replaces `impl Trait` with the concrete type it hides.
+            Type::TraitObject(name) | Type::ImplTrait(name) => Ty::TraitObject(name.clone()),
+            Type::Generic(name, args) if name == "Vec" && args.len() == 1 => {
+                Ty::Vec(Box::new(self.ast_type_to_ty(&args[0])))
+            }
+            Type::Generic(name, args) if name == "Box" && args.len() == 1 => {
+                Ty::Box(Box::new(self.ast_type_to_ty(&args[0])))
+            }
+            Type::Generic(name, args) if name == "Option" && args.len() == 1 => {
+                Ty::Option(Box::new(self.ast_type_to_ty(&args[0])))
+            }
+            Type::Generic(name, args) if name == "Result" && args.len() == 2 => Ty::Result(
+                Box::new(self.ast_type_to_ty(&args[0])),
+                Box::new(self.ast_type_to_ty(&args[1])),
+            ),
+            Type::Generic(name, _) => Ty::TypeParam(name.clone()),
+        }
+    }
+
+    /// IR type name of a parameter as declared (codegen maps it to LLVM).
+    fn ir_param_type_name(ty: &Type) -> String {
+        match ty {
+            Type::Named(name) => name.clone(),
+            Type::Array(_, _) | Type::ConstArray(_, _) => "array".to_string(),
+            Type::Tuple(_) => "tuple".to_string(),
+            Type::Reference(_, mutable) => {
+                if *mutable {
+                    "&mut".to_string()
+                } else {
+                    "&".to_string()
+                }
+            }
+            Type::Generic(name, _) => name.clone(),
+            Type::TraitObject(_) | Type::ImplTrait(_) => "dyn".to_string(),
+        }
+    }
+
+    fn ast_type_to_ir_name(&self, ty: &Type) -> String {
+        match self.ast_type_to_ty(ty) {
+            Ty::Int => "i32".to_string(),
+            Ty::Int64 => "i64".to_string(),
+            Ty::Float => "f64".to_string(),
+            Ty::Bool => "bool".to_string(),
+            Ty::Char => "char".to_string(),
+            Ty::String => "String".to_string(),
+            Ty::Unit | Ty::Never => "void".to_string(),
+            Ty::Array(_, _) => "array".to_string(),
+            Ty::Tuple(_) => "tuple".to_string(),
+            Ty::Struct(name) => name,
+            Ty::Enum(name) => name,
+            Ty::Reference(_, mutable) => {
+                if mutable {
+                    "&mut".to_string()
+                } else {
+                    "&".to_string()
+                }
+            }
+            Ty::TypeParam(name) => name,
+            Ty::TraitObject(_) => "dyn".to_string(),
+            Ty::Option(_) => "Option".to_string(),
+            Ty::Result(_, _) => "Result".to_string(),
+            Ty::Vec(_) => "Vec".to_string(),
+            Ty::HashMap(_, _) => "HashMap".to_string(),
+            Ty::Box(_) => "Box".to_string(),
+            Ty::Iter(_, _) => "Iter".to_string(),
+            Ty::Enumerate(_) => "Enumerate".to_string(),
+            Ty::Zip(_, _) => "Zip".to_string(),
+            Ty::Take(_) => "Take".to_string(),
+            Ty::Fn(name) => name,
+        }
+    }
+
+    fn resolve_callable_name(&self, name: &str) -> String {
+        if let Some((_, Ty::Fn(target))) = self.symbol_table.get(name) {
+            return target.clone();
+        }
+        name.to_string()
+    }
+
+    fn lower_closure_expression(
+        &mut self,
+        params: Vec<crate::ast::Parameter>,
+        body: Expression,
+    ) -> (Value, Ty) {
+        let closure_id = self.closure_count as i64;
+        let closure_name = format!("__closure_{}", self.closure_count);
+        self.closure_count += 1;
+
+        let ir_params: Vec<(String, String)> = params
+            .iter()
+            .map(|p| {
+                let ty_str = match &p.param_type {
+                    Type::Named(n) => match n.as_str() {
+                        "i32" | "int" => "i32".to_string(),
+                        "f64" | "float" => "double".to_string(),
+                        "bool" => "i1".to_string(),
+                        _ => "i32".to_string(),
+                    },
+                    _ => "i32".to_string(),
+                };
+                (p.name.clone(), ty_str)
+            })
+            .collect();
+
+        // Closures currently do not capture outer variables; compile them as plain
+        // standalone functions with their own symbol table/register space.
+        let saved_symbol_table = self.symbol_table.clone();
+        let saved_next_reg = self.next_reg;
+        let saved_next_ptr = self.next_ptr;
+
+        self.symbol_table.clear();
+        self.next_reg = 0;
+        self.next_ptr = 0;
+
+        let mut closure_body = Vec::new();
+        for p in &params {
+            let ptr = Value::Reg(self.next_ptr);
+            self.next_ptr += 1;
+            closure_body.push(Inst::Alloca(ptr.clone(), p.name.clone()));
+            let ty = self.ast_type_to_ty(&p.param_type);
+            self.symbol_table.insert(p.name.clone(), (ptr, ty));
+        }
+
+        let (body_val, body_ty) = self.generate_expression_ir_for_function(body, &mut closure_body);
+        closure_body.push(Inst::Return(body_val));
+
+        let return_type = match &body_ty {
+            Ty::Int => Some("i32".to_string()),
+            Ty::Float => Some("double".to_string()),
+            Ty::Bool => Some("i1".to_string()),
+            _ => Some("i32".to_string()),
+        };
+
+        let closure_fn = Function {
+            name: closure_name.clone(),
+            body: vec![Inst::FunctionDef {
+                name: closure_name.clone(),
+                parameters: ir_params,
+                return_type,
+                body: closure_body,
+                no_inline: false,
+            }],
+            next_reg: self.next_reg,
+            next_ptr: self.next_ptr,
+        };
+        self.functions.insert(closure_name.clone(), closure_fn);
+
+        self.symbol_table = saved_symbol_table;
+        self.next_reg = saved_next_reg;
+        self.next_ptr = saved_next_ptr;
+
+        (Value::ImmInt(closure_id), Ty::Fn(closure_name))
+    }
+
+    // I/O and enhanced expression IR generation methods
+    fn generate_print_ir(
+        &mut self,
+        format_string: String,
+        arguments: Vec<Expression>,
+        newline: bool,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        // Generate IR for arguments
+        let mut typed_args = Vec::new();
+        for arg in arguments {
+            typed_args.push(self.generate_expression_ir(arg, function));
+        }
+
+        // Modify format string to add newline if needed
+        let final_format = if newline {
+            format!("{}\n", format_string)
+        } else {
+            format_string
+        };
+
+        // Bools have no printf specifier, so the format is split around each
+        // bool placeholder and `true`/`false` is printed separately. Chars are
+        // marked `{:c}` and runtime strings `{:s}` so the code generator prints
+        // them with `%c` and `%s`, and
+        // `{:?}` of anything but a number is printed piecewise as well.
+        let mut pending_format = String::new();
+        let mut pending_args = Vec::new();
+        let mut typed_args = typed_args.into_iter();
+        let mut last = 0;
+        for (pos, debug) in format_placeholders(&final_format) {
+            pending_format.push_str(&final_format[last..pos]);
+            last = pos + if debug { 4 } else { 2 };
+            match (typed_args.next(), debug) {
+                (Some((value, ty)), debug)
+                    if ty == Ty::Bool
+                        || (debug && !matches!(ty, Ty::Int | Ty::Int64 | Ty::Float)) =>
+                {
+                    Self::flush_print_ir(&mut pending_format, &mut pending_args, function);
+                    self.generate_debug_print_ir(value, &ty, function);
+                }
+                (Some((value, Ty::Char)), _) => {
+                    pending_format.push_str("{:c}");
+                    pending_args.push(value);
+                }
+                (Some((value @ Value::Reg(_), Ty::String)), _) => {
+                    pending_format.push_str("{:s}");
+                    pending_args.push(value);
+                }
+                (Some((value, _)), _) => {
+                    pending_format.push_str("{}");
+                    pending_args.push(value);
+                }
+                (None, _) => pending_format.push_str(&final_format[pos..last]),
+            }
+        }
+        pending_format.push_str(&final_format[last..]);
+        Self::flush_print_ir(&mut pending_format, &mut pending_args, function);
+
+        // Print operations return unit
+        (Value::ImmInt(0), Ty::Unit)
+    }
+
+    /// Emit the format text and arguments collected so far as one `Print`.
+    fn flush_print_ir(
+        format_string: &mut String,
+        arguments: &mut Vec<Value>,
+        function: &mut Function,
+    ) {
+        if format_string.is_empty() && arguments.is_empty() {
+            return;
+        }
+        function.body.push(Inst::Print {
+            format_string: std::mem::take(format_string),
+            arguments: std::mem::take(arguments),
+        });
+    }
+
+    /// Print `value` as `{:?}` shows it. Strings and chars are quoted and
+    /// escaped, a struct lists its fields by name, and an enum branches on
+    /// its tag to the variant's name, with the payload formatted the same way.
+    fn generate_debug_print_ir(&mut self, value: Value, ty: &Ty, function: &mut Function) {
+        let print = |format_string: String, arguments: Vec<Value>| Inst::Print {
+            format_string,
+            arguments,
+        };
+        // Variants in tag order.
+        let (enum_name, variants): (String, Vec<DebugVariant>) = match ty {
+            Ty::Option(inner) => (
+                "Option".to_string(),
+                vec![
+                    ("None".to_string(), Vec::new(), None),
+                    ("Some".to_string(), vec![(**inner).clone()], None),
+                ],
+            ),
+            Ty::Result(ok, err) => (
+                "Result".to_string(),
+                vec![
+                    ("Ok".to_string(), vec![(**ok).clone()], None),
+                    ("Err".to_string(), vec![(**err).clone()], None),
+                ],
+            ),
+            Ty::Enum(name) if self.enum_variants.contains_key(name) => {
+                let variants = self.enum_variants[name]
+                    .iter()
+                    .map(|(variant, payload)| {
+                        let names = self
+                            .variant_fields
+                            .get(&(name.clone(), variant.clone()))
+                            .cloned();
+                        (variant.clone(), payload.clone(), names)
+                    })
+                    .collect();
+                (name.clone(), variants)
+            }
+            Ty::Struct(name) if self.struct_layouts.contains_key(name) => {
+                let layout = self.struct_layouts[name].clone();
+                let mut fields = Vec::new();
+                for (index, (_, field_ty)) in layout.iter().enumerate() {
+                    let field = self.generate_field_load_ir(&value, name, index as u32, function);
+                    fields.push((field, field_ty.clone()));
+                }
+                let names = layout.into_iter().map(|(field, _)| field).collect();
+                return self.generate_debug_fields_ir(name, fields, Some(names), function);
+            }
+            Ty::Bool => return self.generate_bool_print_ir(value, function),
+            // Literals are formatted here, runtime strings by the `{:q}`
+            // helper the code generator emits.
+            Ty::String => {
+                let inst = match value {
+                    Value::ImmString(text) => print(
+                        "{}".to_string(),
+                        vec![Value::ImmString(format!("{:?}", text))],
+                    ),
+                    value => print("{:q}".to_string(), vec![value]),
+                };
+                return function.body.push(inst);
+            }
+            Ty::Char => {
+                let inst = match value {
+                    Value::ImmInt(code) if let Some(ch) = char::from_u32(code as u32) => print(
+                        "{}".to_string(),
+                        vec![Value::ImmString(format!("{:?}", ch))],
+                    ),
+                    value => print("'{:c}'".to_string(), vec![value]),
+                };
+                return function.body.push(inst);
+            }
+            _ => return function.body.push(print("{}".to_string(), vec![value])),
+        };
+
+        let debug_id = self.next_reg;
+        self.next_reg += 1;
+        let labels: Vec<String> = variants
+            .iter()
+            .map(|(name, _, _)| format!("debug_{}_{}", name.to_lowercase(), debug_id))
+            .collect();
+        let end_label = format!("debug_end_{}", debug_id);
+        // Test the tags from the last down, so the first variant is reached
+        // when no other matched.
+        let tag = self.generate_field_load_ir(&value, &enum_name, 0, function);
+        for index in (1..variants.len()).rev() {
+            let is_variant =
+                self.generate_value_compare_ir("==", &tag, &Value::ImmInt(index as i64), function);
+            let otherwise = if index == 1 {
+                labels[0].clone()
+            } else {
+                format!(
+                    "debug_not_{}_{}",
+                    variants[index].0.to_lowercase(),
+                    debug_id
+                )
+            };
+            function.body.push(Inst::Branch {
+                condition: is_variant,
+                true_label: labels[index].clone(),
+                false_label: otherwise.clone(),
+            });
+            if index > 1 {
+                function.body.push(Inst::Label(otherwise));
+            }
+        }
+        for ((name, payload, names), label) in variants.into_iter().zip(labels) {
+            function.body.push(Inst::Label(label));
+            let mut fields = Vec::new();
+            for (index, field_ty) in payload.into_iter().enumerate() {
+                let field = self.generate_payload_load_ir(
+                    &value,
+                    &enum_name,
+                    index as u32 + 1,
+                    &field_ty,
+                    function,
+                );
+                fields.push((field, field_ty));
+            }
+            self.generate_debug_fields_ir(&name, fields, names, function);
+            function.body.push(Inst::Jump(end_label.clone()));
+        }
+        function.body.push(Inst::Label(end_label));
+    }
+
+    /// Print `Name { a: .., b: .. }` when the fields are named, `Name(.., ..)`
+    /// when they are not, or just `Name` when there are none.
+    fn generate_debug_fields_ir(
+        &mut self,
+        name: &str,
+        fields: Vec<(Value, Ty)>,
+        names: Option<Vec<String>>,
+        function: &mut Function,
+    ) {
+        let print = |format_string: String| Inst::Print {
+            format_string,
+            arguments: Vec::new(),
+        };
+        if fields.is_empty() {
+            return function.body.push(print(name.to_string()));
+        }
+        let (open, close) = if names.is_some() {
+            (" { ", " }")
+        } else {
+            ("(", ")")
+        };
+        for (index, (field, field_ty)) in fields.into_iter().enumerate() {
+            let separator = if index == 0 {
+                format!("{}{}", name, open)
+            } else {
+                ", ".to_string()
+            };
+            let label = match &names {
+                Some(names) => format!("{}: ", names[index]),
+                None => String::new(),
+            };
+            function.body.push(print(separator + &label));
+            self.generate_debug_print_ir(field, &field_ty, function);
+        }
+        function.body.push(print(close.to_string()));
+    }
+
+    /// Print `true` or `false` for a bool, branching between the two string
+    /// constants when the value is only known at runtime.
+    fn generate_bool_print_ir(&mut self, value: Value, function: &mut Function) {
+        let print = |text: &str| Inst::Print {
+            format_string: "{}".to_string(),
+            arguments: vec![Value::ImmString(text.to_string())],
+        };
+        if let Value::ImmInt(flag) = value {
+            function
+                .body
+                .push(print(if flag != 0 { "true" } else { "false" }));
+            return;
+        }
+
+        let print_id = self.next_reg;
+        self.next_reg += 1;
+        let true_label = format!("print_true_{}", print_id);
+        let false_label = format!("print_false_{}", print_id);
+        let end_label = format!("print_bool_end_{}", print_id);
+        function.body.push(Inst::Branch {
+            condition: value,
+            true_label: true_label.clone(),
+            false_label: false_label.clone(),
+        });
+        function.body.push(Inst::Label(true_label));
+        function.body.push(print("true"));
+        function.body.push(Inst::Jump(end_label.clone()));
+        function.body.push(Inst::Label(false_label));
+        function.body.push(print("false"));
+        function.body.push(Inst::Jump(end_label.clone()));
+        function.body.push(Inst::Label(end_label));
+    }
+
+    fn generate_comparison_ir(
+        &mut self,
+        op: crate::ast::ComparisonOp,
+        left: Expression,
+        right: Expression,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let (left_val, left_type) = self.generate_expression_ir(left, function);
+        let (left_val, left_type) = self.generate_auto_deref_ir(left_val, left_type, function);
+        let (right_val, right_type) = self.generate_expression_ir(right, function);
+        let (right_val, right_type) = self.generate_auto_deref_ir(right_val, right_type, function);
+
+        if left_type == right_type
+            && let (Some(folded), _) =
+                self.try_constant_fold(op.as_str(), &left_val, &right_val, &Ty::Bool)
+        {
+            return (folded, Ty::Bool);
+        }
+
+        let result_reg = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+
+        // Convert ComparisonOp to string for IR instruction
+        let op_str = match op {
+            crate::ast::ComparisonOp::Equal => "eq",
+            crate::ast::ComparisonOp::NotEqual => "ne",
+            crate::ast::ComparisonOp::LessThan => "slt",
+            crate::ast::ComparisonOp::GreaterThan => "sgt",
+            crate::ast::ComparisonOp::LessEqual => "sle",
+            crate::ast::ComparisonOp::GreaterEqual => "sge",
+        };
+
+        // Generate appropriate comparison instruction based on operand types
+        let inst = match (&left_type, &right_type) {
+            (l, r) if l.is_integer() && r.is_integer() => Inst::ICmp {
+                op: op_str.to_string(),
+                result: result_reg.clone(),
+                left: left_val,
+                right: right_val,
+            },
+            (Ty::Float, Ty::Float) => {
+                // Convert integer comparison ops to float comparison ops
+                let float_op = match op_str {
+                    "eq" => "oeq",
+                    "ne" => "one",
+                    "slt" => "olt",
+                    "sgt" => "ogt",
+                    "sle" => "ole",
+                    "sge" => "oge",
+                    _ => op_str,
+                };
+                Inst::FCmp {
+                    op: float_op.to_string(),
+                    result: result_reg.clone(),
+                    left: left_val,
+                    right: right_val,
+                }
+            }
+            (l, Ty::Float) if l.is_integer() => {
+                // Promote left operand to float
+                let promoted_left = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function
+                    .body
+                    .push(Inst::SIToFP(promoted_left.clone(), left_val));
+
+                let float_op = match op_str {
+                    "eq" => "oeq",
+                    "ne" => "one",
+                    "slt" => "olt",
+                    "sgt" => "ogt",
+                    "sle" => "ole",
+                    "sge" => "oge",
+                    _ => op_str,
+                };
+                Inst::FCmp {
+                    op: float_op.to_string(),
+                    result: result_reg.clone(),
+                    left: promoted_left,
+                    right: right_val,
+                }
+            }
+            (Ty::Float, r) if r.is_integer() => {
+                // Promote right operand to float
+                let promoted_right = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function
+                    .body
+                    .push(Inst::SIToFP(promoted_right.clone(), right_val));
+
+                let float_op = match op_str {
+                    "eq" => "oeq",
+                    "ne" => "one",
+                    "slt" => "olt",
+                    "sgt" => "ogt",
+                    "sle" => "ole",
+                    "sge" => "oge",
+                    _ => op_str,
+                };
+                Inst::FCmp {
+                    op: float_op.to_string(),
+                    result: result_reg.clone(),
+                    left: left_val,
+                    right: promoted_right,
+                }
+            }
+            // Registers hold bools as `i1`, so compare their slot forms.
+            (Ty::Bool, Ty::Bool) => Inst::FCmp {
+                op: format!("o{}", op_str.trim_start_matches('s')),
+                result: result_reg.clone(),
+                left: self.generate_bool_to_slot_ir(left_val, function),
+                right: self.generate_bool_to_slot_ir(right_val, function),
+            },
+            (Ty::String, Ty::String) if matches!(op_str, "eq" | "ne") => {
+                let (value, insts) =
+                    self.generate_string_eq_ir(op_str == "ne", result_reg, left_val, right_val);
+                function.body.extend(insts);
+                return (value, Ty::Bool);
+            }
+            _ => panic!(
+                "Unsupported comparison between {:?} and {:?}",
+                left_type, right_type
+            ),
+        };
+
+        function.body.push(inst);
+        (result_reg, Ty::Bool)
+    }
+
+    /// `left + right` on strings: a new heap string holding both, in order.
+    fn generate_string_concat_ir(
+        &mut self,
+        left: Value,
+        right: Value,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let result = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        function.body.push(Inst::StrConcat {
+            result: result.clone(),
+            left,
+            right,
+        });
+        (result, Ty::String)
+    }
+
+    /// `value.to_string()`: a new heap string holding `value` as `{}` prints
+    /// it. A string is copied, so the result owns its own allocation.
+    fn generate_to_string_ir(
+        &mut self,
+        value: Value,
+        ty: Ty,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        if let (Value::ImmInt(flag), Ty::Bool) = (&value, &ty) {
+            let text = if *flag != 0 { "true" } else { "false" };
+            return (Value::ImmString(text.to_string()), Ty::String);
+        }
+        let result = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        let inst = match ty {
+            Ty::String => Inst::StrConcat {
+                result: result.clone(),
+                left: value,
+                right: Value::ImmString(String::new()),
+            },
+            Ty::Bool => {
+                let [if_true, if_false] = ["true", "false"].map(|text| {
+                    let constant = Value::Reg(self.next_reg);
+                    self.next_reg += 1;
+                    function.body.push(Inst::StrConst {
+                        result: constant.clone(),
+                        text: text.to_string(),
+                    });
+                    constant
+                });
+                Inst::Select {
+                    result: result.clone(),
+                    condition: value,
+                    if_true,
+                    if_false,
+                }
+            }
+            Ty::Char => Inst::StrFormat {
+                result: result.clone(),
+                value,
+                specifier: 'c',
+            },
+            _ => Inst::StrFormat {
+                result: result.clone(),
+                value,
+                specifier: 'g',
+            },
+        };
+        function.body.push(inst);
+        (result, Ty::String)
+    }
+
+    /// `option.ok_or(error)`: `Ok` with the payload of a `Some`, else
+    /// `Err(error)`. `ok_or_else` passes its closure's body as `error`, which
+    /// is then only evaluated for a `None`.
+    fn generate_ok_or_ir(
+        &mut self,
+        option: Value,
+        ok_ty: Ty,
+        error: Expression,
+        eager: bool,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let eager_error = eager.then(|| self.generate_expression_ir(error.clone(), function));
+        let result_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::AllocaStruct {
+            result: result_ptr.clone(),
+            struct_type: "Result".to_string(),
+        });
+
+        let ok_or_id = self.next_reg;
+        self.next_reg += 1;
+        let some_label = format!("ok_or_some_{}", ok_or_id);
+        let none_label = format!("ok_or_none_{}", ok_or_id);
+        let end_label = format!("ok_or_end_{}", ok_or_id);
+        let tag = self.generate_field_load_ir(&option, "Option", 0, function);
+        let is_some = self.generate_value_compare_ir("==", &tag, &Value::ImmInt(1), function);
+        function.body.push(Inst::Branch {
+            condition: is_some,
+            true_label: some_label.clone(),
+            false_label: none_label.clone(),
+        });
+
+        // The `Some` payload is already in slot form.
+        function.body.push(Inst::Label(some_label));
+        let payload = self.generate_field_load_ir(&option, "Option", 1, function);
+        self.generate_result_store_ir(&result_ptr, "Ok", payload, function);
+        function.body.push(Inst::Jump(end_label.clone()));
+
+        function.body.push(Inst::Label(none_label));
+        let (error, error_ty) =
+            eager_error.unwrap_or_else(|| self.generate_expression_ir(error, function));
+        let error = match error_ty {
+            Ty::String => self.generate_string_slot_ir(error, function),
+            _ => self.generate_value_to_slot_ir(error, &error_ty, function),
+        };
+        self.generate_result_store_ir(&result_ptr, "Err", error, function);
+        function.body.push(Inst::Jump(end_label.clone()));
+
+        function.body.push(Inst::Label(end_label));
+        (result_ptr, Ty::Result(Box::new(ok_ty), Box::new(error_ty)))
+    }
+
+    /// `result.unwrap_err()`: the payload of an `Err`; an `Ok` panics with
+    /// `message`.
+    fn generate_unwrap_err_ir(
+        &mut self,
+        result: Value,
+        err_ty: Ty,
+        message: String,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let unwrap_id = self.next_reg;
+        self.next_reg += 1;
+        let err_label = format!("unwrap_err_{}", unwrap_id);
+        let ok_label = format!("unwrap_err_ok_{}", unwrap_id);
+        let tag = self.generate_field_load_ir(&result, "Result", 0, function);
+        let err_tag = Value::ImmInt(self.variant_index("Result", "Err"));
+        let is_err = self.generate_value_compare_ir("==", &tag, &err_tag, function);
+        function.body.push(Inst::Branch {
+            condition: is_err,
+            true_label: err_label.clone(),
+            false_label: ok_label.clone(),
+        });
+        function.body.push(Inst::Label(ok_label));
+        Self::generate_panic_ir(message, None, &mut function.body);
+        function.body.push(Inst::Label(err_label));
+        let error = self.generate_payload_load_ir(&result, "Result", 1, &err_ty, function);
+        (error, err_ty)
+    }
+
+    /// Fill the `Result` at `result_ptr` with `variant` and its payload in
+    /// slot form.
+    fn generate_result_store_ir(
+        &mut self,
+        result_ptr: &Value,
+        variant: &str,
+        payload: Value,
+        function: &mut Function,
+    ) {
+        let tag_ptr = self.generate_field_ptr_ir(result_ptr, "Result", 0, function);
+        let tag = self.variant_index("Result", variant);
+        function.body.push(Inst::Store(tag_ptr, Value::ImmInt(tag)));
+        let payload_ptr = self.generate_field_ptr_ir(result_ptr, "Result", 1, function);
+        function.body.push(Inst::Store(payload_ptr, payload));
+    }
+
+    /// `text.parse::<T>()`: a `Result<T, ParseError>` with the number in its
+    /// payload, or `Err` when `text` isn't entirely a number that fits `T`.
+    fn generate_parse_number_ir(
+        &mut self,
+        text: Value,
+        target: Ty,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let result_ptr = Value::Reg(self.next_ptr);
+        self.next_ptr += 1;
+        function.body.push(Inst::AllocaStruct {
+            result: result_ptr.clone(),
+            struct_type: "Result".to_string(),
+        });
+        let tag_ptr = self.generate_field_ptr_ir(&result_ptr, "Result", 0, function);
+        let value_ptr = self.generate_field_ptr_ir(&result_ptr, "Result", 1, function);
+        function.body.push(Inst::ParseNumber {
+            source: text,
+            float: target == Ty::Float,
+            value_ptr,
+            tag_ptr,
+        });
+        let error = Ty::Struct("ParseError".to_string());
+        (result_ptr, Ty::Result(Box::new(target), Box::new(error)))
+    }
+
+    /// Compare two strings by contents; `!=` negates the equality result.
+    fn generate_string_eq_ir(
+        &mut self,
+        negate: bool,
+        result_reg: Value,
+        left: Value,
+        right: Value,
+    ) -> (Value, Vec<Inst>) {
+        let mut insts = vec![Inst::StrEq {
+            result: result_reg.clone(),
+            left,
+            right,
+        }];
+        if !negate {
+            return (result_reg, insts);
+        }
+        let negated = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+        insts.push(Inst::Not {
+            result: negated.clone(),
+            operand: result_reg,
+        });
+        (negated, insts)
+    }
+
+    fn generate_logical_ir(
+        &mut self,
+        op: crate::ast::LogicalOp,
+        left: Expression,
+        right: Expression,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let (left_val, left_type) = self.generate_expression_ir(left, function);
+        let (right_val, right_type) = self.generate_expression_ir(right, function);
+
+        if left_type == Ty::Bool
+            && right_type == Ty::Bool
+            && let (Some(folded), _) =
+                self.try_constant_fold(op.as_str(), &left_val, &right_val, &Ty::Bool)
+        {
+            return (folded, Ty::Bool);
+        }
+
+        let result_reg = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+
+        let inst = match op {
+            crate::ast::LogicalOp::And => Inst::And {
+                result: result_reg.clone(),
+                left: left_val,
+                right: right_val,
+            },
+            crate::ast::LogicalOp::Or => Inst::Or {
+                result: result_reg.clone(),
+                left: left_val,
+                right: right_val,
+            },
+        };
+
+        function.body.push(inst);
+        (result_reg, Ty::Bool)
+    }
+
+    fn generate_unary_ir(
+        &mut self,
+        op: crate::ast::UnaryOp,
+        operand: Expression,
+        function: &mut Function,
+    ) -> (Value, Ty) {
+        let (operand_val, operand_type) = self.generate_expression_ir(operand, function);
+
+        let result_reg = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+
+        let (inst, result_type) = match op {
+            crate::ast::UnaryOp::Not => (
+                Inst::Not {
+                    result: result_reg.clone(),
+                    operand: operand_val,
+                },
+                Ty::Bool,
+            ),
+            crate::ast::UnaryOp::Negate => (
+                Inst::Neg {
+                    result: result_reg.clone(),
+                    operand: operand_val,
+                },
+                operand_type,
+            ),
+        };
+
+        function.body.push(inst);
+        (result_reg, result_type)
+    }
+
+    // Function-level I/O and enhanced expression IR generation methods
+    fn generate_print_ir_for_function(
+        &mut self,
+        format_string: String,
+        arguments: Vec<Expression>,
+        newline: bool,
+        function_body: &mut Vec<Inst>,
+    ) -> (Value, Ty) {
+        // Generate IR for arguments
+        let mut arg_values = Vec::new();
+        for arg in arguments {
+            let (arg_value, _) = self.generate_expression_ir_for_function(arg, function_body);
+            arg_values.push(arg_value);
+        }
+
+        // Modify format string to add newline if needed
+        let final_format = if newline {
+            format!("{}\n", format_string)
+        } else {
+            format_string
+        };
+
+        // Create print instruction
+        let print_inst = Inst::Print {
+            format_string: final_format,
+            arguments: arg_values,
+        };
+
+        function_body.push(print_inst);
+
+        // Print operations return unit
+        (Value::ImmInt(0), Ty::Unit)
+    }
+
+    fn generate_comparison_ir_for_function(
+        &mut self,
+        op: crate::ast::ComparisonOp,
+        left: Expression,
+        right: Expression,
+        function_body: &mut Vec<Inst>,
+    ) -> (Value, Ty) {
+        let (left_val, left_type) = self.generate_expression_ir_for_function(left, function_body);
+        let (right_val, right_type) =
+            self.generate_expression_ir_for_function(right, function_body);
+
+        if left_type == right_type
+            && let (Some(folded), _) =
+                self.try_constant_fold(op.as_str(), &left_val, &right_val, &Ty::Bool)
+        {
+            return (folded, Ty::Bool);
+        }
+
+        let result_reg = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+
+        // Convert ComparisonOp to string for IR instruction
+        let op_str = match op {
+            crate::ast::ComparisonOp::Equal => "eq",
+            crate::ast::ComparisonOp::NotEqual => "ne",
+            crate::ast::ComparisonOp::LessThan => "slt",
+            crate::ast::ComparisonOp::GreaterThan => "sgt",
+            crate::ast::ComparisonOp::LessEqual => "sle",
+            crate::ast::ComparisonOp::GreaterEqual => "sge",
+        };
+
+        // Generate appropriate comparison instruction based on operand types
+        let inst = match (&left_type, &right_type) {
+            (l, r) if l.is_integer() && r.is_integer() => Inst::ICmp {
+                op: op_str.to_string(),
+                result: result_reg.clone(),
+                left: left_val,
+                right: right_val,
+            },
+            (Ty::Float, Ty::Float) => {
+                // Convert integer comparison ops to float comparison ops
+                let float_op = match op_str {
+                    "eq" => "oeq",
+                    "ne" => "one",
+                    "slt" => "olt",
+                    "sgt" => "ogt",
+                    "sle" => "ole",
+                    "sge" => "oge",
+                    _ => op_str,
+                };
+                Inst::FCmp {
+                    op: float_op.to_string(),
+                    result: result_reg.clone(),
+                    left: left_val,
+                    right: right_val,
+                }
+            }
+            (l, Ty::Float) if l.is_integer() => {
+                // Promote left operand to float
+                let promoted_left = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function_body.push(Inst::SIToFP(promoted_left.clone(), left_val));
+
+                let float_op = match op_str {
+                    "eq" => "oeq",
+                    "ne" => "one",
+                    "slt" => "olt",
+                    "sgt" => "ogt",
+                    "sle" => "ole",
+                    "sge" => "oge",
+                    _ => op_str,
+                };
+                Inst::FCmp {
+                    op: float_op.to_string(),
+                    result: result_reg.clone(),
+                    left: promoted_left,
+                    right: right_val,
+                }
+            }
+            (Ty::Float, r) if r.is_integer() => {
+                // Promote right operand to float
+                let promoted_right = Value::Reg(self.next_reg);
+                self.next_reg += 1;
+                function_body.push(Inst::SIToFP(promoted_right.clone(), right_val));
+
+                let float_op = match op_str {
+                    "eq" => "oeq",
+                    "ne" => "one",
+                    "slt" => "olt",
+                    "sgt" => "ogt",
+                    "sle" => "ole",
+                    "sge" => "oge",
+                    _ => op_str,
+                };
+                Inst::FCmp {
+                    op: float_op.to_string(),
+                    result: result_reg.clone(),
+                    left: left_val,
+                    right: promoted_right,
+                }
+            }
+            (Ty::Bool, Ty::Bool) => Inst::ICmp {
+                op: op_str.to_string(),
+                result: result_reg.clone(),
+                left: left_val,
+                right: right_val,
+            },
+            (Ty::String, Ty::String) if matches!(op_str, "eq" | "ne") => {
+                let (value, insts) =
+                    self.generate_string_eq_ir(op_str == "ne", result_reg, left_val, right_val);
+                function_body.extend(insts);
+                return (value, Ty::Bool);
+            }
+            _ => panic!(
+                "Unsupported comparison between {:?} and {:?}",
+                left_type, right_type
+            ),
+        };
+
+        function_body.push(inst);
+        (result_reg, Ty::Bool)
+    }
+
+    fn generate_logical_ir_for_function(
+        &mut self,
+        op: crate::ast::LogicalOp,
+        left: Expression,
+        right: Expression,
+        function_body: &mut Vec<Inst>,
+    ) -> (Value, Ty) {
+        let (left_val, left_type) = self.generate_expression_ir_for_function(left, function_body);
+        let (right_val, right_type) =
+            self.generate_expression_ir_for_function(right, function_body);
+
+        if left_type == Ty::Bool
+            && right_type == Ty::Bool
+            && let (Some(folded), _) =
+                self.try_constant_fold(op.as_str(), &left_val, &right_val, &Ty::Bool)
+        {
+            return (folded, Ty::Bool);
+        }
+
+        let result_reg = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+
+        let inst = match op {
+            crate::ast::LogicalOp::And => Inst::And {
+                result: result_reg.clone(),
+                left: left_val,
+                right: right_val,
+            },
+            crate::ast::LogicalOp::Or => Inst::Or {
+                result: result_reg.clone(),
+                left: left_val,
+                right: right_val,
+            },
+        };
+
+        function_body.push(inst);
+        (result_reg, Ty::Bool)
+    }
+
+    fn generate_unary_ir_for_function(
+        &mut self,
+        op: crate::ast::UnaryOp,
+        operand: Expression,
+        function_body: &mut Vec<Inst>,
+    ) -> (Value, Ty) {
+        let (operand_val, operand_type) =
+            self.generate_expression_ir_for_function(operand, function_body);
+
+        let result_reg = Value::Reg(self.next_reg);
+        self.next_reg += 1;
+
+        let (inst, result_type) = match op {
+            crate::ast::UnaryOp::Not => (
+                Inst::Not {
+                    result: result_reg.clone(),
+                    operand: operand_val,
+                },
+                Ty::Bool,
+            ),
+            crate::ast::UnaryOp::Negate => (
+                Inst::Neg {
+                    result: result_reg.clone(),
+                    operand: operand_val,
+                },
+                operand_type,
+            ),
+        };
+
+        function_body.push(inst);
+        (result_reg, result_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{AstNode, BinaryOp, Block, Expression, Parameter, Statement, Type};
+    use crate::types::Ty;
+
+    #[test]
+    fn generates_main_function() {
+        let mut ir_gen = IrGenerator::new();
+        let ir = ir_gen.generate_ir(vec![]);
+        assert!(ir.contains_key("main"));
+    }
+
+    #[test]
+    fn let_with_integer_emits_alloca_and_store() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![AstNode::Statement(Statement::Let {
+            name: "x".to_string(),
+            mutable: false,
+            type_annotation: None,
+            value: Some(Expression::IntegerLiteral(1)),
+        })];
+
+        let ir = ir_gen.generate_ir(ast);
+        let main = &ir["main"].body;
+
+        assert!(
+            main.iter()
+                .any(|i| matches!(i, crate::ir::Inst::Alloca(_, n) if n == "x"))
+        );
+        assert!(
+            main.iter()
+                .any(|i| matches!(i, crate::ir::Inst::Store(_, crate::ir::Value::ImmInt(1))))
+        );
+    }
+
+    #[test]
+    fn binary_expression_requires_type_annotation_in_ast() {
+        let mut ir_gen = IrGenerator::new();
+        let expr = Expression::Binary {
+            op: BinaryOp::Add,
+            left: Box::new(Expression::IntegerLiteral(1)),
+            right: Box::new(Expression::IntegerLiteral(2)),
+            ty: Some(Ty::Int),
+        };
+
+        let (val, ty) = ir_gen.generate_expression_ir(
+            expr,
+            &mut crate::ir::Function {
+                name: "main".to_string(),
+                body: vec![],
+                next_reg: 0,
+                next_ptr: 0,
+            },
+        );
+        assert_eq!(ty, Ty::Int);
+        // should be immediate foldable
+        assert!(matches!(
+            val,
+            crate::ir::Value::ImmInt(3) | crate::ir::Value::Reg(_)
+        ));
+    }
+
+    #[test]
+    fn closure_call_uses_generated_function_symbol() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![
+            AstNode::Statement(Statement::Let {
+                name: "add".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::Closure {
+                    params: vec![
+                        Parameter {
+                            name: "x".to_string(),
+                            param_type: Type::Named("i32".to_string()),
+                        },
+                        Parameter {
+                            name: "y".to_string(),
+                            param_type: Type::Named("i32".to_string()),
+                        },
+                    ],
+                    body: Box::new(Expression::Binary {
+                        op: BinaryOp::Add,
+                        left: Box::new(Expression::Identifier("x".to_string())),
+                        right: Box::new(Expression::Identifier("y".to_string())),
+                        ty: Some(Ty::Int),
+                    }),
+                }),
+            }),
+            AstNode::Statement(Statement::Expression(Expression::FunctionCall {
+                name: "add".to_string(),
+                arguments: vec![Expression::IntegerLiteral(1), Expression::IntegerLiteral(2)],
+            })),
+        ];
+
+        let ir = ir_gen.generate_ir(ast);
+        let main = &ir["main"].body;
+
+        assert!(
+            main.iter()
+                .any(|inst| matches!(inst, crate::ir::Inst::Call { function, .. } if function == "__closure_0"))
+        );
+        assert!(ir.contains_key("__closure_0"));
+
+        let closure_func = &ir["__closure_0"];
+        assert!(
+            closure_func
+                .body
+                .iter()
+                .any(|inst| matches!(inst, crate::ir::Inst::FunctionDef { body, .. } if body.iter().any(|i| matches!(i, crate::ir::Inst::Return(_)))))
+        );
+    }
+
+    #[test]
+    fn for_loop_over_array_emits_indexed_iteration() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![
+            AstNode::Statement(Statement::Let {
+                name: "values".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::ArrayLiteral(vec![
+                    Expression::IntegerLiteral(1),
+                    Expression::IntegerLiteral(2),
+                    Expression::IntegerLiteral(3),
+                ])),
+            }),
+            AstNode::Statement(Statement::For {
+                variable: "v".to_string(),
+                iterable: Expression::Identifier("values".to_string()),
+                body: Block {
+                    statements: vec![],
+                    expression: None,
+                },
+            }),
+        ];
+
+        let ir = ir_gen.generate_ir(ast);
+        let main = &ir["main"].body;
+
+        assert!(
+            main.iter()
+                .any(|inst| matches!(inst, crate::ir::Inst::Alloca(_, name) if name == "v"))
+        );
+        assert!(
+            main.iter()
+                .any(|inst| matches!(inst, crate::ir::Inst::GetElementPtr { .. }))
+        );
+        assert!(
+            main.iter()
+                .any(|inst| matches!(inst, crate::ir::Inst::ICmp { op, .. } if op == "slt"))
+        );
+    }
+
+    #[test]
+    fn print_argument_keeps_string_immediate() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![
+            AstNode::Statement(Statement::Let {
+                name: "name".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::StringLiteral("Aero".to_string())),
+            }),
+            AstNode::Statement(Statement::Expression(Expression::Println {
+                format_string: "{}".to_string(),
+                arguments: vec![Expression::Identifier("name".to_string())],
+            })),
+        ];
+
+        let ir = ir_gen.generate_ir(ast);
+        let main = &ir["main"].body;
+
+        assert!(main.iter().any(|inst| {
+            matches!(
+                inst,
+                crate::ir::Inst::Print { arguments, .. }
+                    if arguments.iter().any(|arg| matches!(arg, crate::ir::Value::ImmString(s) if s == "Aero"))
+            )
+        }));
+    }
+
+    #[test]
+    fn bool_print_argument_selects_true_or_false_constant() {
+        let mut ir_gen = IrGenerator::new();
+        let int_let = |name: &str, value| {
+            AstNode::Statement(Statement::Let {
+                name: name.to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::IntegerLiteral(value)),
+            })
+        };
+        let ast = vec![
+            int_let("a", 1),
+            int_let("b", 2),
+            AstNode::Statement(Statement::Let {
+                name: "flag".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::Comparison {
+                    op: crate::ast::ComparisonOp::LessThan,
+                    left: Box::new(Expression::Identifier("a".to_string())),
+                    right: Box::new(Expression::Identifier("b".to_string())),
+                }),
+            }),
+            AstNode::Statement(Statement::Expression(Expression::Println {
+                format_string: "{}".to_string(),
+                arguments: vec![Expression::Identifier("flag".to_string())],
+            })),
+        ];
+
+        let ir = ir_gen.generate_ir(ast);
+        let main = &ir["main"].body;
+        let (true_label, false_label) = main
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::Branch {
+                    true_label,
+                    false_label,
+                    ..
+                } if true_label.starts_with("print_true_") => {
+                    Some((true_label.clone(), false_label.clone()))
+                }
+                _ => None,
+            })
+            .expect("a runtime bool branches between the constants");
+        let printed_after = |label: &String| {
+            let start = main
+                .iter()
+                .position(|inst| *inst == Inst::Label(label.clone()))
+                .unwrap();
+            match &main[start + 1] {
+                Inst::Print { arguments, .. } => arguments.clone(),
+                other => panic!("expected print after {}, got {:?}", label, other),
+            }
+        };
+        assert_eq!(
+            printed_after(&true_label),
+            [Value::ImmString("true".to_string())]
+        );
+        assert_eq!(
+            printed_after(&false_label),
+            [Value::ImmString("false".to_string())]
+        );
+        // The trailing newline is printed once both paths rejoin.
+        assert!(matches!(
+            main.last(),
+            Some(Inst::Print { format_string, arguments }) if format_string == "\n" && arguments.is_empty()
+        ));
+    }
+
+    #[test]
+    fn char_print_argument_uses_char_placeholder() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![AstNode::Statement(Statement::Expression(
+            Expression::Println {
+                format_string: "c={}".to_string(),
+                arguments: vec![Expression::CharLiteral('x')],
+            },
+        ))];
+
+        let ir = ir_gen.generate_ir(ast);
+        assert!(ir["main"].body.contains(&Inst::Print {
+            format_string: "c={:c}\n".to_string(),
+            arguments: vec![Value::ImmInt('x' as i64)],
+        }));
+    }
+
+    #[test]
+    fn debug_print_of_some_branches_to_the_some_rendering() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![AstNode::Statement(Statement::Expression(
+            Expression::Println {
+                format_string: "{:?}".to_string(),
+                arguments: vec![Expression::EnumVariant {
+                    enum_name: "Option".to_string(),
+                    variant: "Some".to_string(),
+                    data: Some(Box::new(Expression::IntegerLiteral(5))),
+                }],
+            },
+        ))];
+
+        let ir = ir_gen.generate_ir(ast);
+        let body = &ir["main"].body;
+        let some_label = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::Branch { true_label, .. } if true_label.starts_with("debug_some_") => {
+                    Some(true_label.clone())
+                }
+                _ => None,
+            })
+            .expect("expected a branch on the Option tag");
+        let start = body
+            .iter()
+            .position(|inst| *inst == Inst::Label(some_label.clone()))
+            .unwrap();
+        let prints: Vec<&str> = body[start..]
+            .iter()
+            .take_while(|inst| !matches!(inst, Inst::Jump(_)))
+            .filter_map(|inst| match inst {
+                Inst::Print { format_string, .. } => Some(format_string.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(prints, vec!["Some(", "{}", ")"]);
+    }
+
+    #[test]
+    fn debug_placeholder_quotes_strings_and_display_does_not() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![AstNode::Statement(Statement::Expression(
+            Expression::Println {
+                format_string: "{:?} {}".to_string(),
+                arguments: vec![
+                    Expression::StringLiteral("hi".to_string()),
+                    Expression::StringLiteral("hi".to_string()),
+                ],
+            },
+        ))];
+
+        let ir = ir_gen.generate_ir(ast);
+        let prints: Vec<_> = ir["main"]
+            .body
+            .iter()
+            .filter(|inst| matches!(inst, Inst::Print { .. }))
+            .collect();
+        assert_eq!(
+            prints,
+            vec![
+                &Inst::Print {
+                    format_string: "{}".to_string(),
+                    arguments: vec![Value::ImmString("\"hi\"".to_string())],
+                },
+                &Inst::Print {
+                    format_string: " {}\n".to_string(),
+                    arguments: vec![Value::ImmString("hi".to_string())],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn boolean_literal_is_typed_bool() {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+
+        let (value, ty) =
+            ir_gen.generate_expression_ir(Expression::BooleanLiteral(true), &mut function);
+
+        assert!(matches!(value, crate::ir::Value::ImmInt(1)));
+        assert_eq!(ty, Ty::Bool);
+        assert!(function.body.is_empty());
+    }
+
+    #[test]
+    fn logical_and_on_boolean_literals_folds() {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+
+        let (value, ty) = ir_gen.generate_expression_ir(
+            Expression::Logical {
+                op: crate::ast::LogicalOp::And,
+                left: Box::new(Expression::BooleanLiteral(true)),
+                right: Box::new(Expression::BooleanLiteral(false)),
+            },
+            &mut function,
+        );
+
+        assert!(matches!(value, crate::ir::Value::ImmInt(0)));
+        assert_eq!(ty, Ty::Bool);
+        assert!(function.body.is_empty());
+    }
+
+    #[test]
+    fn comparison_on_boolean_literals_folds() {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+
+        let (value, ty) = ir_gen.generate_expression_ir(
+            Expression::Comparison {
+                op: crate::ast::ComparisonOp::NotEqual,
+                left: Box::new(Expression::BooleanLiteral(true)),
+                right: Box::new(Expression::BooleanLiteral(false)),
+            },
+            &mut function,
+        );
+
+        assert!(matches!(value, crate::ir::Value::ImmInt(1)));
+        assert_eq!(ty, Ty::Bool);
+    }
+
+    #[test]
+    fn let_binding_existing_struct_copies_into_fresh_slot() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![
+            AstNode::Statement(Statement::StructDef {
+                name: "Point".to_string(),
+                fields: vec![
+                    crate::ast::FieldDecl {
+                        name: "x".to_string(),
+                        field_type: Type::Named("i32".to_string()),
+                    },
+                    crate::ast::FieldDecl {
+                        name: "y".to_string(),
+                        field_type: Type::Named("i32".to_string()),
+                    },
+                ],
+                type_params: vec![],
+            }),
+            AstNode::Statement(Statement::Let {
+                name: "a".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::StructLiteral {
+                    name: "Point".to_string(),
+                    fields: vec![
+                        ("x".to_string(), Expression::IntegerLiteral(1)),
+                        ("y".to_string(), Expression::IntegerLiteral(2)),
+                    ],
+                }),
+            }),
+            AstNode::Statement(Statement::Let {
+                name: "b".to_string(),
+                mutable: true,
+                type_annotation: None,
+                value: Some(Expression::Identifier("a".to_string())),
+            }),
+        ];
+
+        let ir = ir_gen.generate_ir(ast);
+        let main = &ir["main"].body;
+
+        let original = main
+            .iter()
+            .find_map(|inst| match inst {
+                crate::ir::Inst::AllocaStruct { result, .. } => Some(result.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert!(main.iter().any(|inst| matches!(
+            inst,
+            crate::ir::Inst::StructCopy { result, source, field_count: 2, .. }
+                if *source == original && *result != original
+        )));
+    }
+
+    /// Resolve a folded or emitted `matches!` result for an enum scrutinee by
+    /// following the tag comparison back to the tag stored at construction.
+    fn evaluate_tag_check(body: &[crate::ir::Inst], result: &crate::ir::Value) -> bool {
+        use crate::ir::{Inst, Value};
+
+        let (tag_reg, expected) = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::FCmp {
+                    result: r,
+                    left,
+                    right: Value::ImmInt(expected),
+                    ..
+                } if r == result => Some((left.clone(), *expected)),
+                _ => None,
+            })
+            .expect("tag comparison");
+        let tag_ptr = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::Load(r, ptr) if *r == tag_reg => Some(ptr.clone()),
+                _ => None,
+            })
+            .unwrap();
+        let enum_ptr = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::GetFieldPtr { result, base, .. } if *result == tag_ptr => Some(base.clone()),
+                _ => None,
+            })
+            .unwrap();
+        let stored_tag = body
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::GetFieldPtr {
+                    result,
+                    base,
+                    field_index: 0,
+                    ..
+                } if *base == enum_ptr => Some(result.clone()),
+                _ => None,
+            })
+            .find_map(|field_ptr| {
+                body.iter().find_map(|inst| match inst {
+                    Inst::Store(ptr, Value::ImmInt(tag)) if *ptr == field_ptr => Some(*tag),
+                    _ => None,
+                })
+            })
+            .unwrap();
+        stored_tag == expected
+    }
+
+    fn matches_some_wildcard(scrutinee: Expression) -> bool {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        ir_gen.generate_statement_ir(
+            Statement::Let {
+                name: "opt".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(scrutinee),
+            },
+            &mut function,
+        );
+
+        let (result, ty) = ir_gen.generate_expression_ir(
+            Expression::Matches {
+                expr: Box::new(Expression::Identifier("opt".to_string())),
+                pattern: Box::new(crate::ast::Pattern::Enum {
+                    enum_name: "Option".to_string(),
+                    variant: "Some".to_string(),
+                    data: Some(Box::new(crate::ast::Pattern::Wildcard)),
+                }),
+            },
+            &mut function,
+        );
+        assert_eq!(ty, Ty::Bool);
+        evaluate_tag_check(&function.body, &result)
+    }
+
+    #[test]
+    fn matches_some_wildcard_is_true_for_some() {
+        assert!(matches_some_wildcard(Expression::EnumVariant {
+            enum_name: "Option".to_string(),
+            variant: "Some".to_string(),
+            data: Some(Box::new(Expression::IntegerLiteral(1))),
+        }));
+    }
+
+    #[test]
+    fn matches_some_wildcard_is_false_for_none() {
+        assert!(!matches_some_wildcard(Expression::EnumVariant {
+            enum_name: "Option".to_string(),
+            variant: "None".to_string(),
+            data: None,
+        }));
+    }
+
+    #[test]
+    fn matches_literal_in_inclusive_range_folds_to_true() {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        let range = |n| Expression::Matches {
+            expr: Box::new(Expression::IntegerLiteral(n)),
+            pattern: Box::new(crate::ast::Pattern::Range {
+                start: Expression::IntegerLiteral(1),
+                end: Expression::IntegerLiteral(5),
+                inclusive: true,
+            }),
+        };
+
+        let (inside, ty) = ir_gen.generate_expression_ir(range(3), &mut function);
+        let (edge, _) = ir_gen.generate_expression_ir(range(5), &mut function);
+        let (outside, _) = ir_gen.generate_expression_ir(range(7), &mut function);
+
+        assert_eq!(ty, Ty::Bool);
+        assert!(matches!(inside, crate::ir::Value::ImmInt(1)));
+        assert!(matches!(edge, crate::ir::Value::ImmInt(1)));
+        assert!(matches!(outside, crate::ir::Value::ImmInt(0)));
+        assert!(function.body.is_empty());
+    }
+
+    /// Lower `if let Some(x) = <scrutinee> { x } else { 0 }` and resolve the
+    /// value stored into the result slot on the branch the tag check takes.
+    fn if_let_some_or_zero(scrutinee: Expression) -> i64 {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        let (_, ty) = ir_gen.generate_expression_ir(
+            Expression::IfLet {
+                pattern: Box::new(crate::ast::Pattern::Enum {
+                    enum_name: "Option".to_string(),
+                    variant: "Some".to_string(),
+                    data: Some(Box::new(crate::ast::Pattern::Identifier("x".to_string()))),
+                }),
+                expr: Box::new(scrutinee),
+                then_block: Box::new(Block {
+                    statements: vec![],
+                    expression: Some(Expression::Identifier("x".to_string())),
+                }),
+                else_block: Some(Box::new(Block {
+                    statements: vec![],
+                    expression: Some(Expression::IntegerLiteral(0)),
+                })),
+            },
+            &mut function,
+        );
+        assert_eq!(ty, Ty::Int);
+        // The binding does not leak out of the then-block.
+        assert!(!ir_gen.symbol_table.contains_key("x"));
+
+        let body = &function.body;
+        let (condition, true_label, false_label) = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::Branch {
+                    condition,
+                    true_label,
+                    false_label,
+                } => Some((condition.clone(), true_label.clone(), false_label.clone())),
+                _ => None,
+            })
+            .expect("if let branches on the tag check");
+        let taken = if evaluate_tag_check(body, &condition) {
+            true_label
+        } else {
+            false_label
+        };
+        let (value, _) = merge_incoming(body)
+            .into_iter()
+            .find(|(_, block)| *block == taken)
+            .expect("the taken branch reaches the merge point");
+
+        resolve_stored_int(body, &value)
+    }
+
+    /// The incoming (value, predecessor) edges of the phi at a merge point.
+    fn merge_incoming(body: &[Inst]) -> Vec<(crate::ir::Value, String)> {
+        body.iter()
+            .find_map(|inst| match inst {
+                Inst::Phi { incoming, .. } => Some(incoming.clone()),
+                _ => None,
+            })
+            .expect("branch values merge through a phi")
+    }
+
+    /// Follow a value back through loads, local slots, and enum fields to the
+    /// integer constant that was originally stored.
+    fn resolve_stored_int(body: &[crate::ir::Inst], value: &crate::ir::Value) -> i64 {
+        use crate::ir::Value;
+
+        if let Value::ImmInt(n) = value {
+            return *n;
+        }
+        let ptr = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::Load(r, ptr) if r == value => Some(ptr.clone()),
+                _ => None,
+            })
+            .expect("value is loaded from memory");
+        // Any pointer to the same field or element of the same aggregate aliases `ptr`.
+        let element_of = |inst: &Inst| match inst {
+            Inst::GetFieldPtr {
+                result,
+                base,
+                field_index,
+                ..
+            } => Some((
+                result.clone(),
+                base.clone(),
+                Value::ImmInt(*field_index as i64),
+            )),
+            Inst::GetElementPtr {
+                result,
+                base,
+                index,
+                ..
+            } => Some((result.clone(), base.clone(), index.clone())),
+            _ => None,
+        };
+        let aliases: Vec<Value> = match body
+            .iter()
+            .filter_map(element_of)
+            .find(|(result, _, _)| *result == ptr)
+        {
+            Some((_, base, index)) => body
+                .iter()
+                .filter_map(element_of)
+                .filter(|(_, b, i)| *b == base && *i == index)
+                .map(|(result, _, _)| result)
+                .collect(),
+            None => vec![ptr],
+        };
+        let stored = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::Store(p, v) if aliases.contains(p) => Some(v.clone()),
+                _ => None,
+            })
+            .expect("slot is stored before it is loaded");
+        resolve_stored_int(body, &stored)
+    }
+
+    #[test]
+    fn if_let_binds_payload_of_some() {
+        assert_eq!(
+            if_let_some_or_zero(Expression::EnumVariant {
+                enum_name: "Option".to_string(),
+                variant: "Some".to_string(),
+                data: Some(Box::new(Expression::IntegerLiteral(5))),
+            }),
+            5
+        );
+    }
+
+    #[test]
+    fn if_let_takes_else_block_for_none() {
+        assert_eq!(
+            if_let_some_or_zero(Expression::EnumVariant {
+                enum_name: "Option".to_string(),
+                variant: "None".to_string(),
+                data: None,
+            }),
+            0
+        );
+    }
+
+    /// Lower `if let Some(x) = Some(5) { <then_block> } else { 0 }`.
+    fn lower_if_let_some(then_block: Block) -> Vec<Inst> {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        ir_gen.generate_expression_ir(
+            Expression::IfLet {
+                pattern: Box::new(crate::ast::Pattern::Enum {
+                    enum_name: "Option".to_string(),
+                    variant: "Some".to_string(),
+                    data: Some(Box::new(crate::ast::Pattern::Identifier("x".to_string()))),
+                }),
+                expr: Box::new(Expression::EnumVariant {
+                    enum_name: "Option".to_string(),
+                    variant: "Some".to_string(),
+                    data: Some(Box::new(Expression::IntegerLiteral(5))),
+                }),
+                then_block: Box::new(then_block),
+                else_block: Some(Box::new(Block {
+                    statements: vec![],
+                    expression: Some(Expression::IntegerLiteral(0)),
+                })),
+            },
+            &mut function,
+        );
+        function.body
+    }
+
+    #[test]
+    fn if_let_result_is_a_phi_over_both_branches() {
+        let body = lower_if_let_some(Block {
+            statements: vec![],
+            expression: Some(Expression::Identifier("x".to_string())),
+        });
+
+        let blocks: Vec<String> = merge_incoming(&body)
+            .into_iter()
+            .map(|(_, block)| block)
+            .collect();
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0].starts_with("if_let_then_"), "{:?}", blocks);
+        assert!(blocks[1].starts_with("if_let_else_"), "{:?}", blocks);
+        assert!(!body.iter().any(
+            |inst| matches!(inst, Inst::Alloca(_, name) if name.starts_with("if_let_result_"))
+        ));
+
+        // The phi heads the merge block.
+        let phi = body
+            .iter()
+            .position(|inst| matches!(inst, Inst::Phi { .. }))
+            .unwrap();
+        assert!(matches!(&body[phi - 1], Inst::Label(label) if label.starts_with("if_let_end_")));
+    }
+
+    #[test]
+    fn branch_without_a_value_falls_back_to_result_slot() {
+        let body = lower_if_let_some(Block {
+            statements: vec![Statement::Return(Some(Expression::IntegerLiteral(1)))],
+            expression: None,
+        });
+
+        assert!(!body.iter().any(|inst| matches!(inst, Inst::Phi { .. })));
+        let slot = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::Alloca(slot, name) if name.starts_with("if_let_result_") => {
+                    Some(slot.clone())
+                }
+                _ => None,
+            })
+            .expect("the result goes through a slot");
+        let stores: Vec<&Inst> = body
+            .iter()
+            .filter(|inst| matches!(inst, Inst::Store(ptr, _) if *ptr == slot))
+            .collect();
+        assert_eq!(
+            stores,
+            [&Inst::Store(slot.clone(), crate::ir::Value::ImmInt(0))]
+        );
+        assert!(matches!(body.last(), Some(Inst::Load(_, ptr)) if *ptr == slot));
+    }
+
+    fn get_from_three_element_array(index: i64) -> Expression {
+        Expression::MethodCall {
+            object: Box::new(Expression::ArrayLiteral(vec![
+                Expression::IntegerLiteral(10),
+                Expression::IntegerLiteral(20),
+                Expression::IntegerLiteral(30),
+            ])),
+            method: "get".to_string(),
+            arguments: vec![Expression::IntegerLiteral(index)],
+            type_args: vec![],
+        }
+    }
+
+    #[test]
+    fn get_out_of_bounds_is_none() {
+        assert!(!matches_some_wildcard(get_from_three_element_array(10)));
+        assert_eq!(if_let_some_or_zero(get_from_three_element_array(10)), 0);
+    }
+
+    #[test]
+    fn get_in_bounds_is_some_element() {
+        assert!(matches_some_wildcard(get_from_three_element_array(1)));
+        assert_eq!(if_let_some_or_zero(get_from_three_element_array(1)), 20);
+    }
+
+    #[test]
+    fn binding_pattern_tests_range_and_binds_value() {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        let arm = |pattern, body| crate::ast::MatchArm { pattern, body };
+        let (_, ty) = ir_gen.generate_expression_ir(
+            Expression::Match {
+                expr: Box::new(Expression::IntegerLiteral(3)),
+                arms: vec![
+                    arm(
+                        crate::ast::Pattern::Binding {
+                            name: "n".to_string(),
+                            pattern: Box::new(crate::ast::Pattern::Range {
+                                start: Expression::IntegerLiteral(1),
+                                end: Expression::IntegerLiteral(5),
+                                inclusive: true,
+                            }),
+                        },
+                        Expression::Binary {
+                            op: BinaryOp::Multiply,
+                            left: Box::new(Expression::Identifier("n".to_string())),
+                            right: Box::new(Expression::IntegerLiteral(2)),
+                            ty: None,
+                        },
+                    ),
+                    arm(crate::ast::Pattern::Wildcard, Expression::IntegerLiteral(0)),
+                ],
+            },
+            &mut function,
+        );
+        assert_eq!(ty, Ty::Int);
+        assert!(!ir_gen.symbol_table.contains_key("n"));
+
+        // 3 is in 1..=5, so the first arm is taken unconditionally.
+        let body = &function.body;
+        assert!(!body.iter().any(|inst| matches!(inst, Inst::Branch { .. })));
+        let (stored, _) = merge_incoming(body)[0].clone();
+        let (n, factor) = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::Mul(result, left, right) if *result == stored => Some((
+                    resolve_stored_int(body, left),
+                    resolve_stored_int(body, right),
+                )),
+                _ => None,
+            })
+            .expect("first arm multiplies the bound value");
+        assert_eq!(n, 3);
+        assert_eq!(n * factor, 6);
+    }
+
+    #[test]
+    fn let_array_pattern_binds_each_element() {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        ir_gen.generate_statement_ir(
+            Statement::LetPattern {
+                pattern: crate::ast::Pattern::Array {
+                    elements: vec![
+                        crate::ast::Pattern::Identifier("a".to_string()),
+                        crate::ast::Pattern::Identifier("b".to_string()),
+                    ],
+                    rest: None,
+                },
+                mutable: false,
+                value: Expression::ArrayLiteral(vec![
+                    Expression::IntegerLiteral(1),
+                    Expression::IntegerLiteral(2),
+                ]),
+            },
+            &mut function,
+        );
+        let (a, _) =
+            ir_gen.generate_expression_ir(Expression::Identifier("a".to_string()), &mut function);
+        let (b, _) =
+            ir_gen.generate_expression_ir(Expression::Identifier("b".to_string()), &mut function);
+        assert_eq!(resolve_stored_int(&function.body, &a), 1);
+        assert_eq!(resolve_stored_int(&function.body, &b), 2);
+    }
+
+    /// Lower `match <elements> { [first, ..] => first, [] => 0 }` and return
+    /// the value stored by the arm that is taken.
+    fn first_or_zero(elements: Vec<Expression>) -> i64 {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        let arm = |pattern, body| crate::ast::MatchArm { pattern, body };
+        ir_gen.generate_expression_ir(
+            Expression::Match {
+                expr: Box::new(Expression::ArrayLiteral(elements)),
+                arms: vec![
+                    arm(
+                        crate::ast::Pattern::Array {
+                            elements: vec![crate::ast::Pattern::Identifier("first".to_string())],
+                            rest: Some((1, None)),
+                        },
+                        Expression::Identifier("first".to_string()),
+                    ),
+                    arm(
+                        crate::ast::Pattern::Array {
+                            elements: vec![],
+                            rest: None,
+                        },
+                        Expression::IntegerLiteral(0),
+                    ),
+                ],
+            },
+            &mut function,
+        );
+
+        // Array lengths are static, so the arm is chosen without branching.
+        let body = &function.body;
+        assert!(!body.iter().any(|inst| matches!(inst, Inst::Branch { .. })));
+        let (stored, _) = merge_incoming(body)[0].clone();
+        resolve_stored_int(body, &stored)
+    }
+
+    #[test]
+    fn match_result_takes_the_type_of_its_arms() {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        let arm = |pattern, body| crate::ast::MatchArm { pattern, body };
+        let (_, result_type) = ir_gen.generate_expression_ir(
+            Expression::Match {
+                expr: Box::new(Expression::IntegerLiteral(1)),
+                arms: vec![
+                    arm(
+                        crate::ast::Pattern::Literal(Expression::IntegerLiteral(1)),
+                        Expression::FloatLiteral(1.5),
+                    ),
+                    arm(crate::ast::Pattern::Wildcard, Expression::FloatLiteral(2.5)),
+                ],
+            },
+            &mut function,
+        );
+
+        assert_eq!(result_type, Ty::Float);
+    }
+
+    #[test]
+    fn array_pattern_matches_non_empty_and_empty_arrays() {
+        assert_eq!(
+            first_or_zero(vec![
+                Expression::IntegerLiteral(7),
+                Expression::IntegerLiteral(8),
+            ]),
+            7
+        );
+        assert_eq!(first_or_zero(vec![]), 0);
+    }
+
+    #[test]
+    fn calling_unit_function_produces_no_result_register() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![
+            AstNode::Statement(Statement::Function {
+                name: "greet".to_string(),
+                parameters: vec![],
+                return_type: None,
+                body: Block {
+                    statements: vec![Statement::Expression(Expression::Println {
+                        format_string: "hi".to_string(),
+                        arguments: vec![],
+                    })],
+                    expression: None,
+                },
+                type_params: vec![],
+                const_params: vec![],
+                trait_bounds: vec![],
+                no_inline: false,
+            }),
+            AstNode::Statement(Statement::Let {
+                name: "x".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::FunctionCall {
+                    name: "greet".to_string(),
+                    arguments: vec![],
+                }),
+            }),
+        ];
+
+        let ir = ir_gen.generate_ir(ast);
+        let main = &ir["main"].body;
+
+        assert!(main.iter().any(|inst| matches!(
+            inst,
+            Inst::Call { function, result: None, .. } if function == "greet"
+        )));
+        // A unit binding has nothing to store.
+        assert!(
+            !main
+                .iter()
+                .any(|inst| matches!(inst, Inst::Alloca(_, name) if name == "x"))
+        );
+    }
+
+    #[test]
+    fn int_argument_to_float_parameter_is_promoted() {
+        let mut ir_gen = IrGenerator::new();
+        let ast = vec![
+            AstNode::Statement(Statement::Function {
+                name: "f".to_string(),
+                parameters: vec![Parameter {
+                    name: "x".to_string(),
+                    param_type: Type::Named("f64".to_string()),
+                }],
+                return_type: Some(Type::Named("f64".to_string())),
+                body: Block {
+                    statements: vec![],
+                    expression: Some(Expression::Identifier("x".to_string())),
+                },
+                type_params: vec![],
+                const_params: vec![],
+                trait_bounds: vec![],
+                no_inline: false,
+            }),
+            AstNode::Statement(Statement::Let {
+                name: "y".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::FunctionCall {
+                    name: "f".to_string(),
+                    arguments: vec![Expression::IntegerLiteral(3)],
+                }),
+            }),
+        ];
+
+        let ir = ir_gen.generate_ir(ast);
+        let main = &ir["main"].body;
+
+        let call = main
+            .iter()
+            .position(|inst| matches!(inst, Inst::Call { function, .. } if function == "f"))
+            .expect("main calls f");
+        let Inst::Call { arguments, .. } = &main[call] else {
+            unreachable!()
+        };
+        assert!(main[..call].iter().any(|inst| matches!(
+            inst,
+            Inst::SIToFP(promoted, crate::ir::Value::ImmInt(3)) if arguments[0] == *promoted
+        )));
+    }
+
+    fn string_literal_method(text: &str, method: &str) -> (crate::ir::Value, Ty, Vec<Inst>) {
+        let mut ir_gen = IrGenerator::new();
+        let mut function = Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        };
+        let (value, ty) = ir_gen.generate_expression_ir(
+            Expression::MethodCall {
+                object: Box::new(Expression::StringLiteral(text.to_string())),
+                method: method.to_string(),
+                arguments: vec![],
+                type_args: vec![],
+            },
+            &mut function,
+        );
+        (value, ty, function.body)
+    }
+
+    #[test]
+    fn string_literal_len_folds_to_constant() {
+        let (value, ty, body) = string_literal_method("abc", "len");
+        assert_eq!(value, crate::ir::Value::ImmInt(3));
+        assert_eq!(ty, Ty::Int);
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn string_literal_as_bytes_is_constant_byte_array() {
+        let (_, ty, body) = string_literal_method("abc", "as_bytes");
+        assert_eq!(ty, Ty::Array(Box::new(Ty::Int), 3));
+        assert!(body.iter().any(|inst| matches!(
+            inst,
+            Inst::GlobalDef { values, array_len: Some(3), mutable: false, .. }
+                if values[1] == crate::ir::Value::ImmInt(b'b' as i64)
+        )));
+    }
+
+    /// Run the straight-line-with-branches body of `main` in `source` and
+    /// return the final contents of its arrays, keyed by (array, index), or
+    /// the message of the panic it hit.
+    fn run_array_program(source: &str) -> Result<HashMap<(u32, i64), f64>, String> {
+        use crate::ir::Value;
+
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let Some(Inst::FunctionDef { body, .. }) = ir["main"].body.first() else {
+            panic!("main is lowered to a function definition");
+        };
+
+        let mut regs: HashMap<u32, f64> = HashMap::new();
+        let mut slots: HashMap<u32, f64> = HashMap::new();
+        let mut element_ptrs: HashMap<u32, (u32, i64)> = HashMap::new();
+        let mut elements: HashMap<(u32, i64), f64> = HashMap::new();
+        let value = |regs: &HashMap<u32, f64>, value: &Value| match value {
+            Value::Reg(r) => regs[r],
+            Value::ImmInt(n) => *n as f64,
+            Value::ImmFloat(f) => *f,
+            Value::ImmString(_) => panic!("unexpected string operand"),
+        };
+
+        let mut pc = 0;
+        while let Some(inst) = body.get(pc) {
+            pc += 1;
+            match inst {
+                Inst::AllocaArray { .. } | Inst::Alloca(..) | Inst::Label(_) => {}
+                Inst::GetElementPtr {
+                    result: Value::Reg(result),
+                    base: Value::Reg(base),
+                    index,
+                    ..
+                } => {
+                    element_ptrs.insert(*result, (*base, value(&regs, index) as i64));
+                }
+                Inst::Store(Value::Reg(ptr), stored) => {
+                    let stored = value(&regs, stored);
+                    match element_ptrs.get(ptr) {
+                        Some(element) => elements.insert(*element, stored),
+                        None => slots.insert(*ptr, stored),
+                    };
+                }
+                Inst::Load(Value::Reg(result), Value::Reg(ptr)) => {
+                    let loaded = match element_ptrs.get(ptr) {
+                        Some(element) => elements[element],
+                        None => slots[ptr],
+                    };
+                    regs.insert(*result, loaded);
+                }
+                Inst::Add(Value::Reg(result), left, right) => {
+                    regs.insert(*result, value(&regs, left) + value(&regs, right));
+                }
+                Inst::FCmp {
+                    op,
+                    result: Value::Reg(result),
+                    left,
+                    right,
+                } => {
+                    let (left, right) = (value(&regs, left), value(&regs, right));
+                    let holds = match op.as_str() {
+                        "oge" => left >= right,
+                        "olt" => left < right,
+                        "ole" => left <= right,
+                        other => panic!("unexpected comparison {}", other),
+                    };
+                    regs.insert(*result, holds as i64 as f64);
+                }
+                Inst::And {
+                    result: Value::Reg(result),
+                    left,
+                    right,
+                } => {
+                    let both = value(&regs, left) != 0.0 && value(&regs, right) != 0.0;
+                    regs.insert(*result, both as i64 as f64);
+                }
+                Inst::Branch {
+                    condition,
+                    true_label,
+                    false_label,
+                } => {
+                    let target = if value(&regs, condition) != 0.0 {
+                        true_label
+                    } else {
+                        false_label
+                    };
+                    pc = body
+                        .iter()
+                        .position(|inst| *inst == Inst::Label(target.clone()))
+                        .expect("branch target exists");
+                }
+                Inst::Jump(target) => {
+                    pc = body
+                        .iter()
+                        .position(|inst| *inst == Inst::Label(target.clone()))
+                        .expect("branch target exists");
+                }
+                Inst::Call {
+                    function,
+                    arguments,
+                    ..
+                } if function == "panic" => {
+                    let [Value::ImmString(message)] = arguments.as_slice() else {
+                        panic!("panic takes a message");
+                    };
+                    return Err(message.clone());
+                }
+                Inst::Return(_) => break,
+                other => panic!("unexpected instruction {:?}", other),
+            }
+        }
+        Ok(elements)
+    }
+
+    #[test]
+    fn assigning_through_an_index_updates_only_that_element() {
+        let elements =
+            run_array_program("fn main() { let mut v = [1, 2, 3]; let i = 1; v[i] = v[i] + 1; }")
+                .unwrap();
+        assert_eq!(elements[&(0, 0)], 1.0);
+        assert_eq!(elements[&(0, 1)], 3.0);
+        assert_eq!(elements[&(0, 2)], 3.0);
+    }
+
+    #[test]
+    fn range_loops_run_once_per_value_in_the_range() {
+        for (range, iterations) in [("0..3", 3.0), ("0..=3", 4.0), ("5..5", 0.0), ("5..=5", 1.0)] {
+            let source = format!(
+                "fn main() {{ let mut n = [0]; for i in {} {{ n[0] = n[0] + 1; }} }}",
+                range
+            );
+            let elements = run_array_program(&source).unwrap();
+            assert_eq!(elements[&(0, 0)], iterations, "for i in {}", range);
+        }
+    }
+
+    #[test]
+    fn vec_repeat_fills_every_element_with_the_value() {
+        let source = "fn main() { let v = vec![7; 3]; }";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        assert!(matches!(
+            &ast[0],
+            AstNode::Statement(Statement::Function { body, .. })
+                if matches!(
+                    &body.statements[0],
+                    Statement::Let { value: Some(Expression::ArrayRepeat { count: 3, .. }), .. }
+                )
+        ));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ast::{AstNode, BinaryOp, Block, Expression, Parameter, Statement, Type};
-    use crate::types::Ty;
+        let elements = run_array_program(source).unwrap();
+        assert_eq!(
+            elements,
+            HashMap::from([((0, 0), 7.0), ((0, 1), 7.0), ((0, 2), 7.0)])
+        );
+    }
 
     #[test]
-    fn generates_main_function() {
-        let mut ir_gen = IrGenerator::new();
-        let ir = ir_gen.generate_ir(vec![]);
-        assert!(ir.contains_key("main"));
+    fn assigning_out_of_bounds_panics() {
+        assert_eq!(
+            run_array_program("fn main() { let mut v = [1, 2, 3]; let i = 3; v[i] = 0; }"),
+            Err("index out of bounds: the len is 3".to_string())
+        );
     }
 
     #[test]
-    fn let_with_integer_emits_alloca_and_store() {
-        let mut ir_gen = IrGenerator::new();
-        let ast = vec![AstNode::Statement(Statement::Let {
-            name: "x".to_string(),
-            mutable: false,
-            type_annotation: None,
-            value: Some(Expression::IntegerLiteral(1)),
-        })];
+    fn todo_lowers_to_a_panic() {
+        assert_eq!(
+            run_array_program("fn main() { let x: i32 = todo!(); let y = [x]; }"),
+            Err("not yet implemented".to_string())
+        );
+    }
 
-        let ir = ir_gen.generate_ir(ast);
-        let main = &ir["main"].body;
+    #[test]
+    fn panics_pass_their_source_location_to_the_runtime() {
+        use crate::ir::Value;
+
+        let source = "fn main() {\n    assert!(false);\n    let x: i32 = todo!();\n}\n";
+        let tokens =
+            crate::lexer::tokenize_with_locations(source, Some("src/main.aero".to_string()));
+        let ast = crate::parser::Parser::new(tokens).parse().unwrap();
+        let ir = IrGenerator::new().generate_ir(ast);
+        let Some(Inst::FunctionDef { body, .. }) = ir["main"].body.first() else {
+            panic!("main is lowered to a function definition");
+        };
 
-        assert!(
-            main.iter()
-                .any(|i| matches!(i, crate::ir::Inst::Alloca(_, n) if n == "x"))
+        let panics: Vec<&[Value]> = body
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::Call {
+                    function,
+                    arguments,
+                    ..
+                } if function == "panic" => Some(arguments.as_slice()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            panics,
+            [
+                [
+                    Value::ImmString("assertion failed".to_string()),
+                    Value::ImmString("src/main.aero:2:5".to_string()),
+                ],
+                [
+                    Value::ImmString("not yet implemented".to_string()),
+                    Value::ImmString("src/main.aero:3:18".to_string()),
+                ],
+            ]
         );
+    }
+
+    #[test]
+    fn let_wildcard_evaluates_the_initializer_without_a_slot() {
+        let source = "
+            fn main() { let _ = f(); let _ = f(); }
+            fn f() -> i32 { 3 }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let Some(Inst::FunctionDef { body, .. }) = ir["main"].body.first() else {
+            panic!("main is lowered to a function definition");
+        };
+
+        let calls = body
+            .iter()
+            .filter(|inst| matches!(inst, Inst::Call { function, .. } if function == "f"))
+            .count();
+        assert_eq!(calls, 2);
         assert!(
-            main.iter()
-                .any(|i| matches!(i, crate::ir::Inst::Store(_, crate::ir::Value::ImmInt(1))))
+            !body
+                .iter()
+                .any(|inst| matches!(inst, Inst::Alloca(..) | Inst::Store(..)))
         );
     }
 
     #[test]
-    fn binary_expression_requires_type_annotation_in_ast() {
-        let mut ir_gen = IrGenerator::new();
-        let expr = Expression::Binary {
-            op: BinaryOp::Add,
-            left: Box::new(Expression::IntegerLiteral(1)),
-            right: Box::new(Expression::IntegerLiteral(2)),
-            ty: Some(Ty::Int),
+    fn continue_in_a_range_loop_runs_the_increment() {
+        let source = "
+            fn main() -> i32 { count() }
+            fn count() -> i32 {
+                let mut n = 0;
+                for i in 0..5 { if i == 2 { continue; } n = n + 1; }
+                n
+            }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let body = ir["main"].body.iter().find_map(|inst| match inst {
+            Inst::FunctionDef { name, body, .. } if name == "count" => Some(body.clone()),
+            _ => None,
+        });
+        let Some(body) = body else {
+            panic!("count is lowered to a function definition");
         };
 
-        let (val, ty) = ir_gen.generate_expression_ir(
-            expr,
-            &mut crate::ir::Function {
-                name: "main".to_string(),
-                body: vec![],
-                next_reg: 0,
-                next_ptr: 0,
-            },
+        let label = |prefix: &str| {
+            body.iter()
+                .position(|inst| matches!(inst, Inst::Label(l) if l.starts_with(prefix)))
+                .unwrap_or_else(|| panic!("no {} label in {:#?}", prefix, body))
+        };
+        let (body_at, step_at) = (label("for_body"), label("for_step"));
+        let Inst::Label(step) = &body[step_at] else {
+            unreachable!()
+        };
+        // Both the `continue` and the end of the body go through the step,
+        // and the condition header is only entered from there.
+        let to_step = body[body_at..step_at]
+            .iter()
+            .filter(|inst| matches!(inst, Inst::Jump(l) if l == step))
+            .count();
+        assert_eq!(to_step, 2, "{:#?}", body);
+        assert!(
+            !body[body_at..step_at]
+                .iter()
+                .any(|inst| matches!(inst, Inst::Jump(l) if l.starts_with("for_start"))),
+            "{:#?}",
+            body
         );
-        assert_eq!(ty, Ty::Int);
-        // should be immediate foldable
         assert!(matches!(
-            val,
-            crate::ir::Value::ImmInt(3) | crate::ir::Value::Reg(_)
+            &body[step_at + 1..step_at + 5],
+            [
+                Inst::Load(..),
+                Inst::Add(_, _, Value::ImmInt(1)),
+                Inst::Store(..),
+                Inst::Jump(start),
+            ] if start.starts_with("for_start")
         ));
     }
 
     #[test]
-    fn closure_call_uses_generated_function_symbol() {
-        let mut ir_gen = IrGenerator::new();
-        let ast = vec![
-            AstNode::Statement(Statement::Let {
-                name: "add".to_string(),
-                mutable: false,
-                type_annotation: None,
-                value: Some(Expression::Closure {
-                    params: vec![
-                        Parameter {
-                            name: "x".to_string(),
-                            param_type: Type::Named("i32".to_string()),
-                        },
-                        Parameter {
-                            name: "y".to_string(),
-                            param_type: Type::Named("i32".to_string()),
-                        },
-                    ],
-                    body: Box::new(Expression::Binary {
-                        op: BinaryOp::Add,
-                        left: Box::new(Expression::Identifier("x".to_string())),
-                        right: Box::new(Expression::Identifier("y".to_string())),
-                        ty: Some(Ty::Int),
-                    }),
-                }),
-            }),
-            AstNode::Statement(Statement::Expression(Expression::FunctionCall {
-                name: "add".to_string(),
-                arguments: vec![Expression::IntegerLiteral(1), Expression::IntegerLiteral(2)],
-            })),
-        ];
+    fn unit_functions_return_void_when_they_fall_off_the_end() {
+        let source = "
+            fn main() { early(true); }
+            fn early(c: bool) -> () { if c { return; } println!(\"late\"); }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let early = ir["main"].body.iter().find_map(|inst| match inst {
+            Inst::FunctionDef {
+                name,
+                return_type,
+                body,
+                ..
+            } if name == "early" => Some((return_type.clone(), body.clone())),
+            _ => None,
+        });
+        let Some((return_type, body)) = early else {
+            panic!("early is lowered to a function definition");
+        };
 
-        let ir = ir_gen.generate_ir(ast);
-        let main = &ir["main"].body;
+        // `-> ()` is lowered like an omitted return type, i.e. `ret void`.
+        assert_eq!(return_type, None);
+        assert!(matches!(body.last(), Some(Inst::Return(_))), "{:#?}", body);
+    }
+
+    #[test]
+    fn a_trailing_if_else_is_the_function_value() {
+        let source = "
+            fn main() -> i32 { sign(-3) }
+            fn sign(x: i32) -> i32 { if x < 0 { 0 - 1 } else if x == 0 { 0 } else { 1 } }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let body = ir["main"].body.iter().find_map(|inst| match inst {
+            Inst::FunctionDef { name, body, .. } if name == "sign" => Some(body.clone()),
+            _ => None,
+        });
+        let Some(body) = body else {
+            panic!("sign is lowered to a function definition");
+        };
 
+        assert!(!body.iter().any(|inst| matches!(inst, Inst::Unreachable)));
         assert!(
-            main.iter()
-                .any(|inst| matches!(inst, crate::ir::Inst::Call { function, .. } if function == "__closure_0"))
+            matches!(body.last(), Some(Inst::Return(Value::Reg(_)))),
+            "{:#?}",
+            body
         );
-        assert!(ir.contains_key("__closure_0"));
+    }
 
-        let closure_func = &ir["__closure_0"];
-        assert!(
-            closure_func
-                .body
-                .iter()
-                .any(|inst| matches!(inst, crate::ir::Inst::FunctionDef { body, .. } if body.iter().any(|i| matches!(i, crate::ir::Inst::Return(_)))))
+    #[test]
+    fn to_string_copies_strings_and_formats_other_values() {
+        use crate::ir::Value;
+
+        let source = "
+            fn main() {
+                let s = \"x\".to_string();
+                let t = s.as_str();
+                let n = 7.to_string();
+                let c = 'c'.to_string();
+                let b = true.to_string();
+            }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let Some(Inst::FunctionDef { body, .. }) = ir["main"].body.first() else {
+            panic!("main is lowered to a function definition");
+        };
+
+        // Only `to_string` on the literal allocates; `as_str` reuses it.
+        let copies = body
+            .iter()
+            .filter(|inst| matches!(inst, Inst::StrConcat { left: Value::ImmString(text), .. } if text == "x"))
+            .count();
+        assert_eq!(copies, 1, "{:#?}", body);
+        let specifiers: Vec<char> = body
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::StrFormat { specifier, .. } => Some(*specifier),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(specifiers, vec!['g', 'c']);
+        // A constant bool is formatted at compile time.
+        assert!(!body.iter().any(|inst| matches!(inst, Inst::Select { .. })));
+    }
+
+    #[test]
+    fn main_exit_code_comes_from_its_return_type() {
+        use crate::ir::Value;
+
+        let main_return = |source| {
+            let ast = crate::parser::parse(crate::lexer::tokenize(source));
+            let ir = IrGenerator::new().generate_ir(ast);
+            let Some(Inst::FunctionDef { body, .. }) = ir["main"].body.first() else {
+                panic!("main is lowered to a function definition");
+            };
+            body.iter().rev().find_map(|inst| match inst {
+                Inst::Return(value) => Some(value.clone()),
+                _ => None,
+            })
+        };
+
+        assert_eq!(
+            main_return("fn main() -> i32 { 7 }"),
+            Some(Value::ImmInt(7))
+        );
+        assert_eq!(
+            main_return("fn main() { let x = 4; x }"),
+            Some(Value::ImmInt(0))
         );
     }
 
     #[test]
-    fn for_loop_over_array_emits_indexed_iteration() {
-        let mut ir_gen = IrGenerator::new();
-        let ast = vec![
-            AstNode::Statement(Statement::Let {
-                name: "values".to_string(),
-                mutable: false,
-                type_annotation: None,
-                value: Some(Expression::ArrayLiteral(vec![
-                    Expression::IntegerLiteral(1),
-                    Expression::IntegerLiteral(2),
-                    Expression::IntegerLiteral(3),
-                ])),
-            }),
-            AstNode::Statement(Statement::For {
-                variable: "v".to_string(),
-                iterable: Expression::Identifier("values".to_string()),
-                body: Block {
-                    statements: vec![],
-                    expression: None,
-                },
-            }),
-        ];
+    fn calls_in_statement_position_define_no_result() {
+        let source = "
+            fn main() { greet(); answer(); let x = answer(); }
+            fn greet() { let y = 1; }
+            fn answer() -> i32 { 42 }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let Some(Inst::FunctionDef { body, .. }) = ir["main"].body.first() else {
+            panic!("main is lowered to a function definition");
+        };
 
-        let ir = ir_gen.generate_ir(ast);
-        let main = &ir["main"].body;
+        let calls: Vec<(&str, bool)> = body
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::Call {
+                    function, result, ..
+                } => Some((function.as_str(), result.is_some())),
+                _ => None,
+            })
+            .collect();
+        // `greet` is unit even though it is defined after `main`, and only
+        // the `answer()` bound to `x` keeps its result.
+        assert_eq!(
+            calls,
+            [("greet", false), ("answer", false), ("answer", true)]
+        );
+    }
+
+    #[test]
+    fn const_generic_length_is_instantiated_per_array_length() {
+        let source = "
+            fn sum<const N: usize>(a: [i32; N]) -> i32 {
+                let mut total = 0;
+                let mut i = 0;
+                while i < N { total = total + a[i]; i = i + 1; }
+                total
+            }
+            fn main() -> i32 { sum([1, 2, 3]) + sum([1, 2, 3, 4, 5]) }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+
+        let mut instances: Vec<&String> =
+            ir.keys().filter(|name| name.starts_with("sum.")).collect();
+        instances.sort();
+        assert_eq!(instances, ["sum.3", "sum.5"]);
+        // `N` is bound to each instance's own length.
+        for (instance, len) in [("sum.3", 3), ("sum.5", 5)] {
+            let Some(Inst::FunctionDef { body, .. }) = ir[instance].body.first() else {
+                panic!("{} is lowered to a function definition", instance);
+            };
+            assert!(body.iter().any(|inst| matches!(
+                inst,
+                Inst::Store(_, crate::ir::Value::ImmInt(n)) if *n == len
+            )));
+        }
+    }
+
+    #[test]
+    fn int_returns_are_converted_to_a_declared_f64() {
+        let source = "
+            fn f() -> f64 { 3 }
+            fn g() -> f64 { return 4; }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+
+        for name in ["f", "g"] {
+            let body = ir["main"]
+                .body
+                .iter()
+                .find_map(|inst| match inst {
+                    Inst::FunctionDef { name: n, body, .. } if n == name => Some(body),
+                    _ => None,
+                })
+                .expect("function is defined");
+            let converted = body.iter().find_map(|inst| match inst {
+                Inst::SIToFP(result, _) => Some(result),
+                _ => None,
+            });
+            assert!(
+                matches!(body.last(), Some(Inst::Return(value)) if Some(value) == converted),
+                "{}: {:?}",
+                name,
+                body
+            );
+        }
+        let llvm_ir = crate::code_generator::generate_code(ir);
+        let f = &llvm_ir[llvm_ir.find("define double @f()").expect("f is emitted")..];
+        let f = &f[..f.find("\n}").unwrap()];
+        assert!(f.contains("ret double %reg"), "{}", f);
+    }
 
+    #[test]
+    fn match_arm_that_returns_ends_its_block_with_the_return() {
+        let source = "
+            fn pick(x: i32) -> i32 {
+                let y = match x {
+                    0 => { return 0; }
+                    1 => 10,
+                    _ => 20,
+                };
+                y + 1
+            }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let llvm_ir = crate::code_generator::generate_code(IrGenerator::new().generate_ir(ast));
+        let pick = &llvm_ir[llvm_ir.find("define i32 @pick").expect("pick is emitted")..];
+        let pick = &pick[pick.find('{').unwrap() + 1..pick.find("\n}").unwrap()];
+
+        let is_terminator = |line: &str| {
+            ["ret ", "br ", "unreachable"]
+                .iter()
+                .any(|op| line.trim_start().starts_with(op))
+        };
+        let mut blocks: Vec<Vec<&str>> = Vec::new();
+        for line in pick.lines().filter(|line| !line.trim().is_empty()) {
+            if line.ends_with(':') {
+                blocks.push(Vec::new());
+            } else {
+                blocks
+                    .last_mut()
+                    .expect("body starts with a label")
+                    .push(line);
+            }
+        }
+        for block in &blocks {
+            let terminators = block.iter().filter(|line| is_terminator(line)).count();
+            assert_eq!(terminators, 1, "{:?}\n{}", block, pick);
+            assert!(is_terminator(block.last().unwrap()), "{:?}", block);
+        }
         assert!(
-            main.iter()
-                .any(|inst| matches!(inst, crate::ir::Inst::Alloca(_, name) if name == "v"))
+            blocks
+                .iter()
+                .any(|block| block.as_slice() == ["  ret i32 0"]),
+            "{}",
+            pick
         );
-        assert!(
-            main.iter()
-                .any(|inst| matches!(inst, crate::ir::Inst::GetElementPtr { .. }))
+    }
+
+    #[test]
+    fn numeric_builtins_fold_constant_operands() {
+        let source = "
+            let a = max(3, 7);
+            let b = min(2.0, 1.5);
+            let c = abs(4).max(2);
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let stored: Vec<&crate::ir::Value> = ir["main"]
+            .body
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::Store(_, value) => Some(value),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            stored,
+            [
+                &crate::ir::Value::ImmInt(7),
+                &crate::ir::Value::ImmFloat(1.5),
+                &crate::ir::Value::ImmInt(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn numeric_builtins_select_ints_and_call_float_intrinsics() {
+        let source = "
+            fn pick(x: i32, y: i32, f: f64) -> f64 {
+                let a = max(x, y);
+                let b = x.abs();
+                f.min(2.5) + abs(f)
+            }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let body = ir["main"]
+            .body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::FunctionDef { name, body, .. } if name == "pick" => Some(body),
+                _ => None,
+            })
+            .expect("pick is lowered to a function definition");
+        assert_eq!(
+            body.iter()
+                .filter(|inst| matches!(inst, Inst::Select { .. }))
+                .count(),
+            2
         );
+        let intrinsics: Vec<&str> = body
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::FloatIntrinsic { intrinsic, .. } => Some(intrinsic.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(intrinsics, ["llvm.minnum.f64", "llvm.fabs.f64"]);
+
+        let llvm_ir = crate::code_generator::generate_code(ir);
+        assert!(llvm_ir.contains("declare double @llvm.fabs.f64(double)"));
+        assert!(llvm_ir.contains("declare double @llvm.minnum.f64(double, double)"));
+        assert!(llvm_ir.contains("= select i1 "));
+    }
+
+    #[test]
+    fn enum_pattern_bindings_take_the_payload_type() {
+        let source = "
+            enum Shape { Circle(f64), Square(i32) }
+            fn main() {
+                let o = Some(3.5);
+                let a = match o { Some(x) => x * x, None => 0.0 };
+                let s = Shape::Circle(2.5);
+                if let Shape::Circle(r) = s { let b = r + r; }
+            }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let body = ir["main"]
+            .body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::FunctionDef { name, body, .. } if name == "main" => Some(body),
+                _ => None,
+            })
+            .expect("main is lowered to a function definition");
+
+        assert!(body.iter().any(|inst| matches!(inst, Inst::FMul(..))));
+        assert!(body.iter().any(|inst| matches!(inst, Inst::FAdd(..))));
         assert!(
-            main.iter()
-                .any(|inst| matches!(inst, crate::ir::Inst::ICmp { op, .. } if op == "slt"))
+            !body
+                .iter()
+                .any(|inst| matches!(inst, Inst::Mul(..) | Inst::Add(..)))
         );
     }
 
     #[test]
-    fn print_argument_keeps_string_immediate() {
-        let mut ir_gen = IrGenerator::new();
-        let ast = vec![
-            AstNode::Statement(Statement::Let {
-                name: "name".to_string(),
-                mutable: false,
-                type_annotation: None,
-                value: Some(Expression::StringLiteral("Aero".to_string())),
-            }),
-            AstNode::Statement(Statement::Expression(Expression::Println {
-                format_string: "{}".to_string(),
-                arguments: vec![Expression::Identifier("name".to_string())],
-            })),
-        ];
+    fn ref_self_methods_borrow_the_receiver_and_self_methods_get_a_copy() {
+        let source = "
+            struct Counter { n: i32 }
+            impl Counter {
+                fn get(&self) -> i32 { self.n }
+                fn take(self) -> i32 { self.n }
+            }
+            fn main() {
+                let c = Counter { n: 4 };
+                let a = c.get();
+                let b = c.take();
+            }
+        ";
+        let ast = crate::parser::parse(crate::lexer::tokenize(source));
+        let ir = IrGenerator::new().generate_ir(ast);
+        let body = ir["main"]
+            .body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::FunctionDef { name, body, .. } if name == "main" => Some(body),
+                _ => None,
+            })
+            .expect("main is lowered to a function definition");
 
-        let ir = ir_gen.generate_ir(ast);
-        let main = &ir["main"].body;
+        let receiver = body
+            .iter()
+            .find_map(|inst| match inst {
+                Inst::AllocaStruct { result, .. } => Some(result),
+                _ => None,
+            })
+            .expect("the receiver is allocated");
+        // The pointer each call's receiver slot was packed from.
+        let receiver_of = |method: &str| {
+            let slot = body
+                .iter()
+                .find_map(|inst| match inst {
+                    Inst::Call {
+                        function,
+                        arguments,
+                        ..
+                    } if function == method => arguments.first(),
+                    _ => None,
+                })
+                .expect("the method is called");
+            body.iter()
+                .find_map(|inst| match inst {
+                    Inst::PtrToSlot { result, ptr, .. } if result == slot => Some(ptr),
+                    _ => None,
+                })
+                .expect("the receiver is packed into a slot")
+        };
 
-        assert!(main.iter().any(|inst| {
-            matches!(
-                inst,
-                crate::ir::Inst::Print { arguments, .. }
-                    if arguments.iter().any(|arg| matches!(arg, crate::ir::Value::ImmString(s) if s == "Aero"))
-            )
-        }));
+        assert_eq!(receiver_of("Counter.get"), receiver);
+        let copy = receiver_of("Counter.take");
+        assert!(body.iter().any(|inst| matches!(
+            inst,
+            Inst::StructCopy { result, source, .. } if result == copy && source == receiver
+        )));
     }
 }
@@ -0,0 +1,164 @@
+// src/compiler/src/ir_graph.rs
+
+//! Graphviz DOT rendering of the IR's control flow, for `--emit=cfg-dot`.
+//!
+//! Each function is split into basic blocks at its labels and after each
+//! terminator; edges follow `Jump`/`Branch` targets and fall-through into
+//! the next label. A function becomes one `cluster_` subgraph, so
+//! `dot -Tsvg` draws them side by side.
+
+use crate::ir::{Function, Inst};
+use std::collections::HashMap;
+
+/// A maximal run of instructions entered only at its first one.
+#[derive(Debug, PartialEq)]
+pub struct BasicBlock<'a> {
+    pub label: String,
+    pub instructions: Vec<&'a Inst>,
+    pub successors: Vec<String>,
+}
+
+/// Split `body` into basic blocks. Nested `FunctionDef`s are not part of
+/// this body's control flow and are skipped.
+pub fn basic_blocks(body: &[Inst]) -> Vec<BasicBlock<'_>> {
+    let mut blocks = vec![BasicBlock {
+        label: "entry".to_string(),
+        instructions: Vec::new(),
+        successors: Vec::new(),
+    }];
+    let mut terminated = false;
+    for inst in body {
+        if let Inst::Label(label) = inst {
+            let current = blocks.last_mut().expect("there is always a current block");
+            if !terminated {
+                current.successors.push(label.clone());
+            }
+            blocks.push(BasicBlock {
+                label: label.clone(),
+                instructions: Vec::new(),
+                successors: Vec::new(),
+            });
+            terminated = false;
+            continue;
+        }
+        if matches!(inst, Inst::FunctionDef { .. }) {
+            continue;
+        }
+        if terminated {
+            // Nothing branches here; keep the code visible as its own block.
+            let label = format!("unreachable_{}", blocks.len());
+            blocks.push(BasicBlock {
+                label,
+                instructions: Vec::new(),
+                successors: Vec::new(),
+            });
+            terminated = false;
+        }
+        let current = blocks.last_mut().expect("there is always a current block");
+        current.instructions.push(inst);
+        match inst {
+            Inst::Jump(target) => {
+                current.successors.push(target.clone());
+                terminated = true;
+            }
+            Inst::Branch {
+                true_label,
+                false_label,
+                ..
+            } => {
+                current.successors.push(true_label.clone());
+                current.successors.push(false_label.clone());
+                terminated = true;
+            }
+            Inst::Return(_) | Inst::Unreachable => terminated = true,
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// Render the control flow graph of every function, in name order.
+pub fn render_dot(functions: &HashMap<String, Function>) -> String {
+    let mut names: Vec<&String> = functions.keys().collect();
+    names.sort();
+
+    let mut dot = String::from("digraph cfg {\n");
+    dot.push_str("  node [shape=box, fontname=\"monospace\"];\n");
+    for name in names {
+        // Each function's body is wrapped in its `FunctionDef`.
+        for inst in &functions[name].body {
+            if let Inst::FunctionDef {
+                name: def_name,
+                body,
+                ..
+            } = inst
+            {
+                render_function(&mut dot, def_name, body);
+            }
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn render_function(dot: &mut String, name: &str, body: &[Inst]) {
+    let node = |label: &str| escape(&format!("{}.{}", name, label));
+    dot.push_str(&format!("  subgraph \"cluster_{}\" {{\n", escape(name)));
+    dot.push_str(&format!("    label = \"{}\";\n", escape(name)));
+    let blocks = basic_blocks(body);
+    for block in &blocks {
+        let mut text = format!("{}:\\l", escape(&block.label));
+        for inst in &block.instructions {
+            text.push_str(&escape(&format!("{:?}", inst)));
+            text.push_str("\\l");
+        }
+        dot.push_str(&format!(
+            "    \"{}\" [label=\"{}\"];\n",
+            node(&block.label),
+            text
+        ));
+    }
+    for block in &blocks {
+        for successor in &block.successors {
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\";\n",
+                node(&block.label),
+                node(successor)
+            ));
+        }
+    }
+    dot.push_str("  }\n");
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir_generator::IrGenerator;
+    use crate::semantic_analyzer::SemanticAnalyzer;
+    use crate::{lexer, parser};
+
+    #[test]
+    fn if_else_renders_a_diamond() {
+        let ast = parser::parse(lexer::tokenize(
+            "fn pick(x: i32) -> i32 {
+                let mut r = 0;
+                if x > 0 { r = 1; } else { r = 2; }
+                r
+            }",
+        ));
+        let (_, ast) = SemanticAnalyzer::new().analyze(ast).unwrap();
+        let ir = IrGenerator::new().generate_ir(ast);
+        let dot = render_dot(&ir);
+
+        assert!(dot.starts_with("digraph cfg {"), "{}", dot);
+        assert!(dot.contains("subgraph \"cluster_pick\""), "{}", dot);
+        // entry, then, else and the join, with an edge along each side.
+        assert_eq!(dot.matches(" [label=").count(), 4, "{}", dot);
+        assert_eq!(dot.matches(" -> ").count(), 4, "{}", dot);
+        assert!(dot.contains("\"pick.entry\" -> \"pick.if_then_"), "{}", dot);
+    }
+}
@@ -0,0 +1,1001 @@
+// src/compiler/src/ir_optimizer.rs
+
+//! IR-level optimization passes, run between IR generation and code generation
+//! as selected by `CompilerOptions::opt_level`.
+
+use crate::ir::{Function, Inst, Value};
+use std::collections::{HashMap, HashSet};
+
+/// An IR pass run by `optimize`, named as in `--dump-ir-after=<pass>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrPass {
+    ConstFold, // `propagate_constants`
+    Dce,       // `forward_stores`, which also drops dead stores
+    Licp,      // `hoist_loop_invariants`
+}
+
+impl IrPass {
+    pub const ALL: [IrPass; 3] = [IrPass::ConstFold, IrPass::Dce, IrPass::Licp];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IrPass::ConstFold => "const-fold",
+            IrPass::Dce => "dce",
+            IrPass::Licp => "licp",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<IrPass> {
+        IrPass::ALL.into_iter().find(|pass| pass.name() == name)
+    }
+}
+
+/// Run the IR optimization passes selected by `opt_level` over every
+/// function: none at 0, the cheap local passes (store forwarding, dead-store
+/// elimination and constant propagation) at 1, and everything, including
+/// loop-invariant code motion, at 2 and above.
+pub fn optimize(functions: &mut HashMap<String, Function>, opt_level: u8) {
+    optimize_with(functions, opt_level, |_, _| {});
+}
+
+/// `optimize`, calling `after_pass` with the function each time a pass has
+/// changed it. Functions are visited by name, so the calls are in a stable
+/// order.
+pub fn optimize_with(
+    functions: &mut HashMap<String, Function>,
+    opt_level: u8,
+    mut after_pass: impl FnMut(IrPass, &Function),
+) {
+    if opt_level == 0 {
+        return;
+    }
+    let mut names: Vec<String> = functions.keys().cloned().collect();
+    names.sort();
+    for name in names {
+        let function = functions
+            .get_mut(&name)
+            .expect("name was taken from the map");
+        // A folded value can be stored and forwarded again, so alternate
+        // until neither pass finds anything; both only ever remove code.
+        loop {
+            let forwarded = forward_stores(&mut function.body);
+            if forwarded {
+                after_pass(IrPass::Dce, function);
+            }
+            let folded = propagate_constants(&mut function.body);
+            if folded {
+                after_pass(IrPass::ConstFold, function);
+            }
+            if !forwarded && !folded {
+                break;
+            }
+        }
+        if opt_level >= 2 && hoist_loop_invariants(function) {
+            after_pass(IrPass::Licp, function);
+        }
+    }
+}
+
+/// Store-to-load forwarding and dead-store elimination for scalar slots.
+///
+/// A `Load` from an `Alloca` slot whose most recent `Store` is in the same
+/// straight-line region is dropped and its uses read the stored value
+/// directly. Slots that are then never loaded lose their `Store`s and
+/// `Alloca`. Only slots that do not escape through any other instruction are
+/// touched, so calls cannot observe the rewritten memory. Function bodies
+/// nested in `body` are processed as functions of their own.
+pub fn forward_stores(body: &mut Vec<Inst>) -> bool {
+    let mut changed = false;
+    for inst in body.iter_mut() {
+        if let Inst::FunctionDef { body, .. } = inst {
+            changed |= forward_stores(body);
+        }
+    }
+
+    let slots = private_slots(body);
+    let forwardable = |reg: u32| {
+        body.iter()
+            .filter(|inst| !matches!(inst, Inst::FunctionDef { .. }))
+            .all(|inst| reads_only_as_value(inst, reg))
+    };
+
+    let mut last_stored: HashMap<u32, Value> = HashMap::new();
+    let mut replacements: HashMap<u32, Value> = HashMap::new();
+    let mut forwarded = Vec::new();
+    for (index, inst) in body.iter().enumerate() {
+        match inst {
+            Inst::Label(_) | Inst::Jump(_) | Inst::Branch { .. } => last_stored.clear(),
+            Inst::Alloca(Value::Reg(ptr), _) => {
+                last_stored.remove(ptr);
+            }
+            Inst::Store(Value::Reg(ptr), value) if slots.contains(ptr) => {
+                last_stored.insert(*ptr, resolve(value, &replacements));
+            }
+            Inst::Load(Value::Reg(result), Value::Reg(ptr)) => {
+                if let Some(value) = last_stored.get(ptr)
+                    && forwardable(*result)
+                {
+                    replacements.insert(*result, value.clone());
+                    forwarded.push(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !forwarded.is_empty() {
+        for index in forwarded.iter().rev() {
+            body.remove(*index);
+        }
+        for inst in body.iter_mut() {
+            for operand in value_operands_mut(inst) {
+                *operand = resolve(operand, &replacements);
+            }
+        }
+        changed = true;
+    }
+
+    // Slots that are written but never read are dead.
+    let loaded: HashSet<u32> = body
+        .iter()
+        .filter_map(|inst| match inst {
+            Inst::Load(_, Value::Reg(ptr)) => Some(*ptr),
+            _ => None,
+        })
+        .collect();
+    let dead: HashSet<u32> = slots.difference(&loaded).copied().collect();
+    let before = body.len();
+    body.retain(|inst| match inst {
+        Inst::Alloca(Value::Reg(ptr), _) | Inst::Store(Value::Reg(ptr), _) => !dead.contains(ptr),
+        _ => true,
+    });
+
+    changed || body.len() != before
+}
+
+/// Constant propagation for `%regN` values.
+///
+/// Arithmetic whose operands are all immediates is evaluated at compile time
+/// and its uses read the result directly. Registers are assigned once, so a
+/// known register stays known across blocks; slot contents are tracked only
+/// within a block, by `forward_stores`. An instruction is folded only when
+/// every use of its result can be rewritten. Function bodies nested in
+/// `body` are processed as functions of their own.
+pub fn propagate_constants(body: &mut Vec<Inst>) -> bool {
+    let mut changed = false;
+    for inst in body.iter_mut() {
+        if let Inst::FunctionDef { body, .. } = inst {
+            changed |= propagate_constants(body);
+        }
+    }
+
+    let rewritable = |body: &[Inst], reg: u32| {
+        body.iter()
+            .filter(|inst| !matches!(inst, Inst::FunctionDef { .. }))
+            .all(|inst| reads_only_as_value(inst, reg))
+    };
+
+    let mut known: HashMap<u32, Value> = HashMap::new();
+    let mut folded = Vec::new();
+    for index in 0..body.len() {
+        for operand in value_operands_mut(&mut body[index]) {
+            *operand = resolve(operand, &known);
+        }
+        if let (Some(reg), Some(value)) = (defined_register(&body[index]), evaluate(&body[index]))
+            && rewritable(body, reg)
+        {
+            known.insert(reg, value);
+            folded.push(index);
+        }
+    }
+
+    if folded.is_empty() {
+        return changed;
+    }
+    for index in folded.iter().rev() {
+        body.remove(*index);
+    }
+    // Uses that precede the definition in program order, such as loop phis.
+    for inst in body.iter_mut() {
+        for operand in value_operands_mut(inst) {
+            *operand = resolve(operand, &known);
+        }
+    }
+    true
+}
+
+/// The immediate an arithmetic instruction evaluates to when its operands
+/// are immediates. Every value is a double at run time, so integer results
+/// fold only when they are exact: no overflow and no remainder on division.
+fn evaluate(inst: &Inst) -> Option<Value> {
+    let (op, l, r) = match inst {
+        Inst::Add(_, l, r) | Inst::FAdd(_, l, r) => ('+', l, r),
+        Inst::Sub(_, l, r) | Inst::FSub(_, l, r) => ('-', l, r),
+        Inst::Mul(_, l, r) | Inst::FMul(_, l, r) => ('*', l, r),
+        Inst::Div(_, l, r) | Inst::FDiv(_, l, r) => ('/', l, r),
+        _ => return None,
+    };
+    let float = |value: &Value| match value {
+        Value::ImmInt(n) => Some(*n as f64),
+        Value::ImmFloat(f) => Some(*f),
+        _ => None,
+    };
+    match (l, r) {
+        (Value::ImmInt(l), Value::ImmInt(r)) => match op {
+            '+' => l.checked_add(*r),
+            '-' => l.checked_sub(*r),
+            '*' => l.checked_mul(*r),
+            _ => l.checked_rem(*r).filter(|rem| *rem == 0).map(|_| l / r),
+        }
+        .map(Value::ImmInt),
+        _ => {
+            let (l, r) = (float(l)?, float(r)?);
+            Some(Value::ImmFloat(match op {
+                '+' => l + r,
+                '-' => l - r,
+                '*' => l * r,
+                _ => l / r,
+            }))
+        }
+    }
+}
+
+fn resolve(value: &Value, replacements: &HashMap<u32, Value>) -> Value {
+    match value {
+        Value::Reg(reg) => replacements.get(reg).cloned().unwrap_or(Value::Reg(*reg)),
+        other => other.clone(),
+    }
+}
+
+/// `Alloca` slots that are only ever the pointer operand of `Store`/`Load`.
+fn private_slots(body: &[Inst]) -> HashSet<u32> {
+    let mut slots: HashSet<u32> = body
+        .iter()
+        .filter_map(|inst| match inst {
+            Inst::Alloca(Value::Reg(ptr), _) => Some(*ptr),
+            _ => None,
+        })
+        .collect();
+    for inst in body {
+        match inst {
+            Inst::Alloca(..) | Inst::FunctionDef { .. } => {}
+            inst if moves_values_only(inst) => {}
+            // Other instructions do not say which namespace an operand lives
+            // in, so any mention is treated as a potential escape.
+            _ => {
+                for operand in all_operands(inst) {
+                    if let Value::Reg(reg) = operand {
+                        slots.remove(reg);
+                    }
+                }
+            }
+        }
+    }
+    slots
+}
+
+/// True if `inst` mentions `reg` only in positions `value_operands_mut`
+/// rewrites, so every use of a forwarded load can be replaced.
+fn reads_only_as_value(inst: &Inst, reg: u32) -> bool {
+    moves_values_only(inst)
+        || !all_operands(inst)
+            .into_iter()
+            .any(|value| matches!(value, Value::Reg(r) if *r == reg))
+}
+
+/// True if every register `inst` reads is a `%regN` value. `Store`/`Load`
+/// address slots through `%ptrN`, which is a separate namespace, so the
+/// same number there never refers to a slot.
+fn moves_values_only(inst: &Inst) -> bool {
+    matches!(
+        inst,
+        Inst::Store(..)
+            | Inst::Load(..)
+            | Inst::Add(..)
+            | Inst::FAdd(..)
+            | Inst::Sub(..)
+            | Inst::FSub(..)
+            | Inst::Mul(..)
+            | Inst::FMul(..)
+            | Inst::Div(..)
+            | Inst::FDiv(..)
+            | Inst::ICmp { .. }
+            | Inst::FCmp { .. }
+            | Inst::SIToFP(..)
+            | Inst::FPToSI(..)
+            | Inst::Neg { .. }
+            | Inst::Phi { .. }
+            | Inst::Call { .. }
+            | Inst::Print { .. }
+            | Inst::Println { .. }
+            | Inst::Return(_)
+    )
+}
+
+/// Operands that hold plain values, which a forwarded load's uses may be
+/// rewritten in.
+fn value_operands_mut(inst: &mut Inst) -> Vec<&mut Value> {
+    match inst {
+        Inst::Add(_, l, r)
+        | Inst::FAdd(_, l, r)
+        | Inst::Sub(_, l, r)
+        | Inst::FSub(_, l, r)
+        | Inst::Mul(_, l, r)
+        | Inst::FMul(_, l, r)
+        | Inst::Div(_, l, r)
+        | Inst::FDiv(_, l, r)
+        | Inst::ICmp {
+            left: l, right: r, ..
+        }
+        | Inst::FCmp {
+            left: l, right: r, ..
+        } => vec![l, r],
+        Inst::Store(_, operand)
+        | Inst::SIToFP(_, operand)
+        | Inst::FPToSI(_, operand)
+        | Inst::Neg { operand, .. }
+        | Inst::Return(operand) => vec![operand],
+        Inst::Phi { incoming, .. } => incoming.iter_mut().map(|(value, _)| value).collect(),
+        Inst::Call { arguments, .. }
+        | Inst::Print { arguments, .. }
+        | Inst::Println { arguments, .. } => arguments.iter_mut().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Every operand an instruction reads, excluding its result.
+pub fn all_operands(inst: &Inst) -> Vec<&Value> {
+    match inst {
+        Inst::Store(ptr, value) => vec![ptr, value],
+        Inst::Load(_, ptr) => vec![ptr],
+        Inst::Return(value)
+        | Inst::Branch {
+            condition: value, ..
+        } => vec![value],
+        Inst::Call { arguments, .. }
+        | Inst::Print { arguments, .. }
+        | Inst::Println { arguments, .. } => arguments.iter().collect(),
+        Inst::CallIndirect {
+            vtable, arguments, ..
+        } => std::iter::once(vtable).chain(arguments).collect(),
+        Inst::GetElementPtr { base, index, .. } => vec![base, index],
+        Inst::GetFieldPtr { base, .. } => vec![base],
+        Inst::StructCopy { source, .. } => vec![source],
+        Inst::PtrToSlot { ptr, .. } => vec![ptr],
+        Inst::SlotToPtr { value, .. } => vec![value],
+        Inst::StrEq { left, right, .. } | Inst::StrConcat { left, right, .. } => {
+            vec![left, right]
+        }
+        Inst::StrFormat { value, .. } => vec![value],
+        Inst::StrLen { source, .. } => vec![source],
+        Inst::ParseNumber {
+            source,
+            value_ptr,
+            tag_ptr,
+            ..
+        } => vec![source, value_ptr, tag_ptr],
+        Inst::VecPush { vec_ptr, value } => vec![vec_ptr, value],
+        Inst::VecPop { vec_ptr, .. }
+        | Inst::VecLength { vec_ptr, .. }
+        | Inst::VecCapacity { vec_ptr, .. } => vec![vec_ptr],
+        Inst::VecAccess { vec_ptr, index, .. } => vec![vec_ptr, index],
+        Inst::VecInit { elements, .. } => elements.iter().collect(),
+        Inst::ArrayLength { array_ptr, .. } => vec![array_ptr],
+        Inst::ArrayAccess {
+            array_ptr, index, ..
+        } => vec![array_ptr, index],
+        Inst::EnumDiscriminant { enum_ptr, .. } | Inst::EnumVariantData { enum_ptr, .. } => {
+            vec![enum_ptr]
+        }
+        Inst::EnumConstruct { data, .. } => data.iter().collect(),
+        Inst::Phi { incoming, .. } => incoming.iter().map(|(value, _)| value).collect(),
+        Inst::GlobalDef { values, .. } => values.iter().collect(),
+        _ => pure_operands(inst).unwrap_or_default(),
+    }
+}
+
+/// Unused-register pruning.
+///
+/// Drops side-effect-free instructions whose result register is never read:
+/// pure arithmetic and comparisons, loads and string constants. Each removal
+/// releases the registers it read, and any of those left without a reader
+/// is queued to be pruned in turn. Calls, stores, prints and every other
+/// instruction with an effect stay even when their result is unused.
+/// `%ptrN` and `%regN` share numbers, so a register counts as read if that
+/// number is read in either namespace. Function bodies nested in `body` are
+/// processed as functions of their own.
+pub fn prune_unused_registers(body: &mut Vec<Inst>) -> bool {
+    let mut changed = false;
+    for inst in body.iter_mut() {
+        if let Inst::FunctionDef { body, .. } = inst {
+            changed |= prune_unused_registers(body);
+        }
+    }
+
+    let removable = |inst: &Inst| {
+        pure_operands(inst).is_some() || matches!(inst, Inst::Load(..) | Inst::StrConst { .. })
+    };
+    let mut reads: HashMap<u32, usize> = HashMap::new();
+    for inst in body.iter() {
+        for operand in all_operands(inst) {
+            if let Value::Reg(reg) = operand {
+                *reads.entry(*reg).or_default() += 1;
+            }
+        }
+    }
+    let definitions: HashMap<u32, usize> = body
+        .iter()
+        .enumerate()
+        .filter(|(_, inst)| removable(inst))
+        .filter_map(|(index, inst)| defined_register(inst).map(|reg| (reg, index)))
+        .collect();
+
+    let mut dead = vec![false; body.len()];
+    let mut worklist: Vec<usize> = definitions.values().copied().collect();
+    while let Some(index) = worklist.pop() {
+        let Some(reg) = defined_register(&body[index]) else {
+            continue;
+        };
+        if dead[index] || reads.get(&reg).is_some_and(|&count| count > 0) {
+            continue;
+        }
+        dead[index] = true;
+        for operand in all_operands(&body[index]) {
+            if let Value::Reg(read) = operand
+                && let Some(count) = reads.get_mut(read)
+            {
+                *count -= 1;
+                if *count == 0
+                    && let Some(&definition) = definitions.get(read)
+                {
+                    worklist.push(definition);
+                }
+            }
+        }
+    }
+
+    if dead.contains(&true) {
+        let mut index = 0;
+        body.retain(|_| {
+            index += 1;
+            !dead[index - 1]
+        });
+        changed = true;
+    }
+    changed
+}
+
+/// Loop-invariant code motion.
+///
+/// A loop is a `Label(l)` followed later by a back-edge `Jump(l)`. Pure
+/// instructions inside that range whose operands are all defined outside the
+/// loop are moved into the preheader, i.e. just before the `Jump(l)` that
+/// enters the loop. Returns true if anything was hoisted.
+pub fn hoist_loop_invariants(function: &mut Function) -> bool {
+    let mut changed = false;
+
+    // Hoisting out of an inner loop can expose invariants of the enclosing
+    // loop, so iterate until nothing moves.
+    while let Some((preheader, start, end)) = find_hoistable_loop(&function.body) {
+        let hoisted = collect_invariants(&function.body, start, end);
+        let mut moved = Vec::new();
+        for index in hoisted.iter().rev() {
+            moved.push(function.body.remove(*index));
+        }
+        moved.reverse();
+        for (offset, inst) in moved.into_iter().enumerate() {
+            function.body.insert(preheader + offset, inst);
+        }
+        changed = true;
+    }
+
+    changed
+}
+
+/// Find the first loop with a unique preheader that still has something to
+/// hoist. Returns (preheader insertion index, loop start, back-edge index).
+fn find_hoistable_loop(body: &[Inst]) -> Option<(usize, usize, usize)> {
+    for (start, inst) in body.iter().enumerate() {
+        let Inst::Label(label) = inst else {
+            continue;
+        };
+        let Some(end) = body
+            .iter()
+            .rposition(|inst| matches!(inst, Inst::Jump(target) if target == label))
+        else {
+            continue;
+        };
+        if end <= start || start == 0 {
+            continue;
+        }
+
+        // The loop must be entered only through a `Jump(label)` directly
+        // before its header; anything else means there is no preheader.
+        if !matches!(&body[start - 1], Inst::Jump(target) if target == label) {
+            continue;
+        }
+        let entered_elsewhere = body
+            .iter()
+            .enumerate()
+            .any(|(i, inst)| (i < start - 1 || i > end) && branches_to(inst, label));
+        if entered_elsewhere {
+            continue;
+        }
+
+        if !collect_invariants(body, start, end).is_empty() {
+            return Some((start - 1, start, end));
+        }
+    }
+    None
+}
+
+fn branches_to(inst: &Inst, label: &str) -> bool {
+    match inst {
+        Inst::Jump(target) => target == label,
+        Inst::Branch {
+            true_label,
+            false_label,
+            ..
+        } => true_label == label || false_label == label,
+        _ => false,
+    }
+}
+
+/// Indices of instructions in `body[start..=end]` that can be hoisted.
+fn collect_invariants(body: &[Inst], start: usize, end: usize) -> Vec<usize> {
+    let region = &body[start..=end];
+
+    let scalar_slots: HashSet<u32> = body
+        .iter()
+        .filter_map(|inst| match inst {
+            Inst::Alloca(Value::Reg(ptr), _) => Some(*ptr),
+            _ => None,
+        })
+        .collect();
+    let stored_slots: HashSet<u32> = region
+        .iter()
+        .filter_map(|inst| match inst {
+            Inst::Store(Value::Reg(ptr), _) | Inst::Alloca(Value::Reg(ptr), _) => Some(*ptr),
+            _ => None,
+        })
+        .collect();
+    let has_calls = region.iter().any(|inst| matches!(inst, Inst::Call { .. }));
+
+    let mut defined_in_loop: HashSet<u32> = region.iter().filter_map(defined_register).collect();
+    let mut hoisted = Vec::new();
+
+    for (offset, inst) in region.iter().enumerate() {
+        let invariant = match inst {
+            Inst::Load(_, Value::Reg(ptr)) => {
+                !has_calls && scalar_slots.contains(ptr) && !stored_slots.contains(ptr)
+            }
+            Inst::Div(_, _, rhs) if !matches!(rhs, Value::ImmInt(n) if *n != 0) => false,
+            _ => match pure_operands(inst) {
+                Some(operands) => operands.iter().all(|operand| match operand {
+                    Value::Reg(reg) => !defined_in_loop.contains(reg),
+                    _ => true,
+                }),
+                None => false,
+            },
+        };
+
+        if invariant {
+            if let Some(reg) = defined_register(inst) {
+                defined_in_loop.remove(&reg);
+            }
+            hoisted.push(start + offset);
+        }
+    }
+
+    hoisted
+}
+
+/// The register written by a value-producing instruction.
+fn defined_register(inst: &Inst) -> Option<u32> {
+    match inst {
+        Inst::Add(Value::Reg(r), _, _)
+        | Inst::FAdd(Value::Reg(r), _, _)
+        | Inst::Sub(Value::Reg(r), _, _)
+        | Inst::FSub(Value::Reg(r), _, _)
+        | Inst::Mul(Value::Reg(r), _, _)
+        | Inst::FMul(Value::Reg(r), _, _)
+        | Inst::Div(Value::Reg(r), _, _)
+        | Inst::FDiv(Value::Reg(r), _, _)
+        | Inst::Load(Value::Reg(r), _)
+        | Inst::SIToFP(Value::Reg(r), _)
+        | Inst::FPToSI(Value::Reg(r), _)
+        | Inst::ICmp {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::FCmp {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::StrEq {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::StrConcat {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::StrFormat {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::StrLen {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::StrConst {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::Select {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::FloatIntrinsic {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::And {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::Or {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::Not {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::Neg {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::Phi {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::Call {
+            result: Some(Value::Reg(r)),
+            ..
+        }
+        | Inst::GetElementPtr {
+            result: Value::Reg(r),
+            ..
+        }
+        | Inst::GetFieldPtr {
+            result: Value::Reg(r),
+            ..
+        } => Some(*r),
+        _ => None,
+    }
+}
+
+/// Operands of side-effect-free instructions; `None` for anything else.
+fn pure_operands(inst: &Inst) -> Option<Vec<&Value>> {
+    match inst {
+        Inst::Add(_, l, r)
+        | Inst::FAdd(_, l, r)
+        | Inst::Sub(_, l, r)
+        | Inst::FSub(_, l, r)
+        | Inst::Mul(_, l, r)
+        | Inst::FMul(_, l, r)
+        | Inst::Div(_, l, r)
+        | Inst::FDiv(_, l, r)
+        | Inst::ICmp {
+            left: l, right: r, ..
+        }
+        | Inst::FCmp {
+            left: l, right: r, ..
+        }
+        | Inst::And {
+            left: l, right: r, ..
+        }
+        | Inst::Or {
+            left: l, right: r, ..
+        } => Some(vec![l, r]),
+        Inst::SIToFP(_, operand)
+        | Inst::FPToSI(_, operand)
+        | Inst::Not { operand, .. }
+        | Inst::Neg { operand, .. } => Some(vec![operand]),
+        Inst::Select {
+            condition,
+            if_true,
+            if_false,
+            ..
+        } => Some(vec![condition, if_true, if_false]),
+        Inst::FloatIntrinsic { arguments, .. } => Some(arguments.iter().collect()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{AstNode, Block, ComparisonOp, Expression, Statement};
+    use crate::ir_generator::IrGenerator;
+
+    fn loop_bounds(body: &[Inst]) -> (usize, usize) {
+        let start = body
+            .iter()
+            .position(|inst| matches!(inst, Inst::Label(l) if l.starts_with("while_start")))
+            .unwrap();
+        let end = body
+            .iter()
+            .rposition(|inst| matches!(inst, Inst::Jump(l) if l.starts_with("while_start")))
+            .unwrap();
+        (start, end)
+    }
+
+    #[test]
+    fn invariant_multiplication_is_hoisted_out_of_while_body() {
+        let ast = vec![
+            AstNode::Statement(Statement::Let {
+                name: "n".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::IntegerLiteral(3)),
+            }),
+            AstNode::Statement(Statement::While {
+                condition: Expression::Comparison {
+                    op: ComparisonOp::LessThan,
+                    left: Box::new(Expression::Identifier("n".to_string())),
+                    right: Box::new(Expression::IntegerLiteral(10)),
+                },
+                body: Block {
+                    statements: vec![Statement::Let {
+                        name: "m".to_string(),
+                        mutable: false,
+                        type_annotation: None,
+                        value: Some(Expression::Binary {
+                            op: crate::ast::BinaryOp::Multiply,
+                            left: Box::new(Expression::Identifier("n".to_string())),
+                            right: Box::new(Expression::IntegerLiteral(2)),
+                            ty: None,
+                        }),
+                    }],
+                    expression: None,
+                },
+            }),
+        ];
+
+        let mut functions = IrGenerator::new().generate_ir(ast);
+        let (start, end) = loop_bounds(&functions["main"].body);
+        assert!(
+            functions["main"].body[start..=end]
+                .iter()
+                .any(|inst| matches!(inst, Inst::Mul(..)))
+        );
+
+        hoist_loop_invariants(functions.get_mut("main").unwrap());
+
+        let main = &functions["main"].body;
+        let (start, end) = loop_bounds(main);
+        assert!(
+            !main[start..=end]
+                .iter()
+                .any(|inst| matches!(inst, Inst::Mul(..)))
+        );
+        assert!(
+            main[..start]
+                .iter()
+                .any(|inst| matches!(inst, Inst::Mul(..)))
+        );
+        // The store into the loop-local `m` still happens every iteration.
+        assert!(
+            main[start..=end]
+                .iter()
+                .any(|inst| matches!(inst, Inst::Store(..)))
+        );
+    }
+
+    #[test]
+    fn stored_value_is_forwarded_to_following_load() {
+        let ast = vec![
+            AstNode::Statement(Statement::Let {
+                name: "x".to_string(),
+                mutable: false,
+                type_annotation: None,
+                value: Some(Expression::IntegerLiteral(5)),
+            }),
+            AstNode::Statement(Statement::Return(Some(Expression::Binary {
+                op: crate::ast::BinaryOp::Add,
+                left: Box::new(Expression::Identifier("x".to_string())),
+                right: Box::new(Expression::IntegerLiteral(1)),
+                ty: None,
+            }))),
+        ];
+
+        let mut functions = IrGenerator::new().generate_ir(ast);
+        assert!(
+            functions["main"]
+                .body
+                .iter()
+                .any(|inst| matches!(inst, Inst::Load(..)))
+        );
+
+        forward_stores(&mut functions.get_mut("main").unwrap().body);
+
+        let main = &functions["main"].body;
+        assert!(
+            !main
+                .iter()
+                .any(|inst| matches!(inst, Inst::Load(..) | Inst::Store(..) | Inst::Alloca(..)))
+        );
+        assert!(
+            main.iter()
+                .any(|inst| matches!(inst, Inst::Add(_, Value::ImmInt(5), Value::ImmInt(1))))
+        );
+    }
+
+    #[test]
+    fn constants_propagate_through_slots_and_arithmetic() {
+        let mut functions = HashMap::new();
+        functions.insert(
+            "main".to_string(),
+            Function {
+                name: "main".to_string(),
+                body: vec![
+                    Inst::Alloca(Value::Reg(0), "a".to_string()),
+                    Inst::Store(Value::Reg(0), Value::ImmInt(2)),
+                    Inst::Load(Value::Reg(0), Value::Reg(0)),
+                    Inst::Mul(Value::Reg(1), Value::Reg(0), Value::ImmInt(3)),
+                    Inst::Alloca(Value::Reg(1), "b".to_string()),
+                    Inst::Store(Value::Reg(1), Value::Reg(1)),
+                    Inst::Load(Value::Reg(2), Value::Reg(1)),
+                    Inst::Println {
+                        format_string: "{}".to_string(),
+                        arguments: vec![Value::Reg(2)],
+                    },
+                    Inst::Sub(Value::Reg(3), Value::Reg(2), Value::ImmInt(5)),
+                    Inst::Return(Value::Reg(3)),
+                ],
+                next_reg: 4,
+                next_ptr: 2,
+            },
+        );
+
+        optimize(&mut functions, 1);
+
+        assert_eq!(
+            functions["main"].body,
+            vec![
+                Inst::Println {
+                    format_string: "{}".to_string(),
+                    arguments: vec![Value::ImmInt(6)],
+                },
+                Inst::Return(Value::ImmInt(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn inexact_integer_division_is_left_for_run_time() {
+        let mut body = vec![
+            Inst::Div(Value::Reg(0), Value::ImmInt(7), Value::ImmInt(2)),
+            Inst::Div(Value::Reg(1), Value::ImmInt(8), Value::ImmInt(2)),
+            Inst::Add(Value::Reg(2), Value::Reg(0), Value::Reg(1)),
+            Inst::Return(Value::Reg(2)),
+        ];
+
+        assert!(propagate_constants(&mut body));
+        assert_eq!(
+            body,
+            vec![
+                Inst::Div(Value::Reg(0), Value::ImmInt(7), Value::ImmInt(2)),
+                Inst::Add(Value::Reg(2), Value::Reg(0), Value::ImmInt(4)),
+                Inst::Return(Value::Reg(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn opt_level_zero_keeps_the_dead_store_that_level_two_removes() {
+        let dead_store = |opt_level| {
+            let mut functions = HashMap::new();
+            functions.insert(
+                "main".to_string(),
+                Function {
+                    name: "main".to_string(),
+                    body: vec![
+                        Inst::Alloca(Value::Reg(0), "unused".to_string()),
+                        Inst::Store(Value::Reg(0), Value::ImmInt(7)),
+                        Inst::Return(Value::ImmInt(0)),
+                    ],
+                    next_reg: 0,
+                    next_ptr: 1,
+                },
+            );
+            optimize(&mut functions, opt_level);
+            functions["main"]
+                .body
+                .iter()
+                .any(|inst| matches!(inst, Inst::Store(..)))
+        };
+
+        assert!(dead_store(0));
+        assert!(!dead_store(2));
+    }
+
+    #[test]
+    fn loads_after_a_label_are_not_forwarded() {
+        let mut body = vec![
+            Inst::Alloca(Value::Reg(0), "i".to_string()),
+            Inst::Store(Value::Reg(0), Value::ImmInt(0)),
+            Inst::Jump("loop_start_0".to_string()),
+            Inst::Label("loop_start_0".to_string()),
+            Inst::Load(Value::Reg(1), Value::Reg(0)),
+            Inst::Add(Value::Reg(2), Value::Reg(1), Value::ImmInt(1)),
+            Inst::Store(Value::Reg(0), Value::Reg(2)),
+            Inst::Jump("loop_start_0".to_string()),
+        ];
+        let before = body.clone();
+
+        assert!(!forward_stores(&mut body));
+        assert_eq!(body, before);
+    }
+
+    #[test]
+    fn loads_of_slots_written_in_loop_stay_put() {
+        let mut function = Function {
+            name: "main".to_string(),
+            body: vec![
+                Inst::Alloca(Value::Reg(0), "i".to_string()),
+                Inst::Store(Value::Reg(0), Value::ImmInt(0)),
+                Inst::Jump("loop_start_0".to_string()),
+                Inst::Label("loop_start_0".to_string()),
+                Inst::Load(Value::Reg(1), Value::Reg(0)),
+                Inst::Add(Value::Reg(2), Value::Reg(1), Value::ImmInt(1)),
+                Inst::Store(Value::Reg(0), Value::Reg(2)),
+                Inst::Jump("loop_start_0".to_string()),
+                Inst::Label("loop_end_0".to_string()),
+            ],
+            next_reg: 3,
+            next_ptr: 1,
+        };
+        let before = function.body.clone();
+
+        assert!(!hoist_loop_invariants(&mut function));
+        assert_eq!(function.body, before);
+    }
+
+    #[test]
+    fn unused_arithmetic_is_pruned_while_stores_and_calls_stay() {
+        let mut body = vec![
+            Inst::Alloca(Value::Reg(0), "x".to_string()),
+            Inst::Load(Value::Reg(1), Value::Reg(0)),
+            // %reg3 is never read; pruning it leaves %reg2 and then the
+            // load without a reader.
+            Inst::Add(Value::Reg(2), Value::Reg(1), Value::ImmInt(1)),
+            Inst::Mul(Value::Reg(3), Value::Reg(2), Value::ImmInt(2)),
+            Inst::Store(Value::Reg(0), Value::ImmInt(7)),
+            Inst::Call {
+                function: "f".to_string(),
+                arguments: vec![],
+                result: Some(Value::Reg(4)),
+            },
+            Inst::Return(Value::ImmInt(0)),
+        ];
+
+        assert!(prune_unused_registers(&mut body));
+        assert_eq!(
+            body,
+            vec![
+                Inst::Alloca(Value::Reg(0), "x".to_string()),
+                Inst::Store(Value::Reg(0), Value::ImmInt(7)),
+                Inst::Call {
+                    function: "f".to_string(),
+                    arguments: vec![],
+                    result: Some(Value::Reg(4)),
+                },
+                Inst::Return(Value::ImmInt(0)),
+            ]
+        );
+        assert!(!prune_unused_registers(&mut body));
+    }
+}
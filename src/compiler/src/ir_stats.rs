@@ -0,0 +1,151 @@
+// src/compiler/src/ir_stats.rs
+
+//! Size metrics of the IR for `--stats`, to track code bloat and how much
+//! the optimizer removes.
+
+use crate::ir::{Function, Inst, Value};
+use crate::ir_graph::basic_blocks;
+use crate::ir_optimizer::all_operands;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// Size of one lowered function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionStats {
+    pub name: String,
+    /// Instructions in the body, not counting labels.
+    pub instructions: usize,
+    pub basic_blocks: usize,
+}
+
+/// Size of a whole program's IR.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IrStats {
+    /// In name order.
+    pub functions: Vec<FunctionStats>,
+    /// Distinct string literals the IR carries, as constants or operands.
+    pub string_constants: usize,
+}
+
+impl IrStats {
+    pub fn collect(functions: &HashMap<String, Function>) -> Self {
+        let mut stats = Vec::new();
+        let mut strings = BTreeSet::new();
+        for function in functions.values() {
+            // Each function's body is wrapped in its `FunctionDef`.
+            for inst in &function.body {
+                if let Inst::FunctionDef { name, body, .. } = inst {
+                    stats.push(FunctionStats {
+                        name: name.clone(),
+                        instructions: body
+                            .iter()
+                            .filter(|inst| !matches!(inst, Inst::Label(_)))
+                            .count(),
+                        basic_blocks: basic_blocks(body).len(),
+                    });
+                    collect_strings(body, &mut strings);
+                }
+            }
+        }
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        IrStats {
+            functions: stats,
+            string_constants: strings.len(),
+        }
+    }
+
+    pub fn total_instructions(&self) -> usize {
+        self.functions.iter().map(|f| f.instructions).sum()
+    }
+
+    pub fn total_basic_blocks(&self) -> usize {
+        self.functions.iter().map(|f| f.basic_blocks).sum()
+    }
+}
+
+fn collect_strings<'a>(body: &'a [Inst], strings: &mut BTreeSet<&'a str>) {
+    for inst in body {
+        if let Inst::StrConst { text, .. } = inst {
+            strings.insert(text);
+        }
+        for operand in all_operands(inst) {
+            if let Value::ImmString(text) = operand {
+                strings.insert(text);
+            }
+        }
+    }
+}
+
+impl fmt::Display for IrStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for function in &self.functions {
+            writeln!(
+                f,
+                "  {}: {} instructions, {} basic blocks",
+                function.name, function.instructions, function.basic_blocks
+            )?;
+        }
+        write!(
+            f,
+            "  total: {} instructions, {} basic blocks, {} string constants",
+            self.total_instructions(),
+            self.total_basic_blocks(),
+            self.string_constants
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir_generator::IrGenerator;
+    use crate::semantic_analyzer::SemanticAnalyzer;
+    use crate::{ir_optimizer, lexer, parser};
+
+    fn lower(source: &str) -> HashMap<String, Function> {
+        let ast = parser::parse(lexer::tokenize(source));
+        let (_, ast) = SemanticAnalyzer::new().analyze(ast).unwrap();
+        IrGenerator::new().generate_ir(ast)
+    }
+
+    #[test]
+    fn counts_instructions_blocks_and_strings_per_function() {
+        let ir = lower(
+            "fn main() -> i32 {
+                let x = 2;
+                let s = \"hi\";
+                println!(\"{} {}\", s, \"there\");
+                x + 1
+            }",
+        );
+        let stats = IrStats::collect(&ir);
+
+        assert_eq!(stats.functions.len(), 1);
+        let main = &stats.functions[0];
+        assert_eq!(main.name, "main");
+        // alloca x, store 2, println, load x, add, return; `s` is "hi" itself
+        assert_eq!(main.instructions, 6, "{:#?}", ir);
+        assert_eq!(main.basic_blocks, 1);
+        assert_eq!(stats.total_instructions(), 6);
+        assert_eq!(
+            stats.to_string(),
+            "  main: 6 instructions, 1 basic blocks\n  total: 6 instructions, 1 basic blocks, 2 string constants"
+        );
+    }
+
+    #[test]
+    fn optimizing_reduces_the_instruction_count() {
+        let mut ir = lower(
+            "fn main() -> i32 {
+                let x = 2;
+                let y = x * 3;
+                y + x
+            }",
+        );
+        let before = IrStats::collect(&ir).total_instructions();
+        ir_optimizer::optimize(&mut ir, 1);
+        let after = IrStats::collect(&ir).total_instructions();
+
+        assert!(after < before, "{} -> {}", before, after);
+    }
+}
@@ -0,0 +1,264 @@
+// src/compiler/src/keyword_arguments.rs
+
+//! Keyword arguments: `f(width: 10, height: 20)`.
+//!
+//! Every call that names its arguments is rewritten into the equivalent
+//! positional call by matching the names against the callee's declared
+//! parameters, so later phases never see an `Expression::NamedArgument`.
+
+use crate::ast::{AstNode, Block, Expression, Statement};
+use std::collections::HashMap;
+
+/// Reorder the keyword arguments of every call in `ast` into positional
+/// order, or report the first unknown, duplicate or missing argument of each
+/// top-level item, keyed by the item's index.
+pub fn resolve(mut ast: Vec<AstNode>) -> Result<Vec<AstNode>, Vec<(usize, String)>> {
+    let parameters: HashMap<String, Vec<String>> = ast
+        .iter()
+        .filter_map(|node| match node {
+            AstNode::Statement(Statement::Function {
+                name, parameters, ..
+            }) => Some((
+                name.clone(),
+                parameters.iter().map(|p| p.name.clone()).collect(),
+            )),
+            _ => None,
+        })
+        .collect();
+    let resolver = Resolver { parameters };
+    let mut errors = Vec::new();
+    for (index, node) in ast.iter_mut().enumerate() {
+        let resolved = match node {
+            AstNode::Statement(stmt) => resolver.statement(stmt),
+            AstNode::Expression(expr) => resolver.expression(expr),
+        };
+        if let Err(err) = resolved {
+            errors.push((index, err));
+        }
+    }
+    if errors.is_empty() {
+        Ok(ast)
+    } else {
+        Err(errors)
+    }
+}
+
+struct Resolver {
+    /// Parameter names of each function declared with `fn`
+    parameters: HashMap<String, Vec<String>>,
+}
+
+impl Resolver {
+    fn block(&self, block: &mut Block) -> Result<(), String> {
+        for stmt in &mut block.statements {
+            self.statement(stmt)?;
+        }
+        match &mut block.expression {
+            Some(expr) => self.expression(expr),
+            None => Ok(()),
+        }
+    }
+
+    fn statement(&self, stmt: &mut Statement) -> Result<(), String> {
+        match stmt {
+            Statement::Let {
+                value: Some(expr), ..
+            }
+            | Statement::LetPattern { value: expr, .. }
+            | Statement::Return(Some(expr))
+            | Statement::Expression(expr)
+            | Statement::Static { value: expr, .. } => self.expression(expr),
+            Statement::Assign { target, value } => {
+                self.expression(target)?;
+                self.expression(value)
+            }
+            Statement::Block(block)
+            | Statement::Function { body: block, .. }
+            | Statement::Loop { body: block } => self.block(block),
+            Statement::While { condition, body } => {
+                self.expression(condition)?;
+                self.block(body)
+            }
+            Statement::For { iterable, body, .. } => {
+                self.expression(iterable)?;
+                self.block(body)
+            }
+            Statement::If {
+                condition: expr,
+                then_block,
+                else_block,
+            }
+            | Statement::IfLet {
+                expression: expr,
+                then_block,
+                else_block,
+                ..
+            } => {
+                self.expression(expr)?;
+                self.block(then_block)?;
+                match else_block {
+                    Some(else_block) => self.statement(else_block),
+                    None => Ok(()),
+                }
+            }
+            Statement::ImplBlock { methods, .. } => {
+                for method in methods {
+                    self.statement(method)?;
+                }
+                Ok(())
+            }
+            Statement::Cfg { item, .. } => self.statement(item),
+            _ => Ok(()),
+        }
+    }
+
+    fn expression(&self, expr: &mut Expression) -> Result<(), String> {
+        match expr {
+            Expression::FunctionCall { name, arguments } => {
+                for argument in arguments.iter_mut() {
+                    match argument {
+                        Expression::NamedArgument { value, .. } => self.expression(value)?,
+                        positional => self.expression(positional)?,
+                    }
+                }
+                if arguments
+                    .iter()
+                    .any(|argument| matches!(argument, Expression::NamedArgument { .. }))
+                {
+                    *arguments = self.reorder(name, std::mem::take(arguments))?;
+                }
+                Ok(())
+            }
+            Expression::MethodCall {
+                object, arguments, ..
+            } => {
+                self.expression(object)?;
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+                Ok(())
+            }
+            Expression::Print { arguments, .. }
+            | Expression::Println { arguments, .. }
+            | Expression::ArrayLiteral(arguments)
+            | Expression::TupleLiteral(arguments) => {
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+                Ok(())
+            }
+            Expression::Binary { left, right, .. }
+            | Expression::Comparison { left, right, .. }
+            | Expression::Logical { left, right, .. }
+            | Expression::IndexAccess {
+                object: left,
+                index: right,
+                ..
+            }
+            | Expression::Range {
+                start: left,
+                end: right,
+                ..
+            } => {
+                self.expression(left)?;
+                self.expression(right)
+            }
+            Expression::Unary { operand: expr, .. }
+            | Expression::ArrayRepeat { value: expr, .. }
+            | Expression::FieldAccess { object: expr, .. }
+            | Expression::TupleIndex { object: expr, .. }
+            | Expression::Matches { expr, .. }
+            | Expression::Borrow { expr, .. }
+            | Expression::Deref(expr)
+            | Expression::BoxNew(expr)
+            | Expression::Closure { body: expr, .. }
+            | Expression::EnumVariant {
+                data: Some(expr), ..
+            } => self.expression(expr),
+            Expression::StructLiteral { fields, .. } => {
+                for (_, value) in fields {
+                    self.expression(value)?;
+                }
+                Ok(())
+            }
+            Expression::Match { expr, arms } => {
+                self.expression(expr)?;
+                for arm in arms {
+                    self.expression(&mut arm.body)?;
+                }
+                Ok(())
+            }
+            Expression::IfLet {
+                expr,
+                then_block,
+                else_block,
+                ..
+            } => {
+                self.expression(expr)?;
+                self.block(then_block)?;
+                match else_block {
+                    Some(else_block) => self.block(else_block),
+                    None => Ok(()),
+                }
+            }
+            Expression::Block(block) => self.block(block),
+            Expression::NamedArgument { name, .. } => Err(format!(
+                "Error: keyword argument `{}` outside of a function call.",
+                name
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The positional arguments of a call to `function` given `arguments`:
+    /// positional ones first, then keyword ones in any order.
+    fn reorder(
+        &self,
+        function: &str,
+        arguments: Vec<Expression>,
+    ) -> Result<Vec<Expression>, String> {
+        let Some(parameters) = self.parameters.get(function) else {
+            return Err(format!(
+                "Error: keyword arguments need a function declared with `fn`, but `{}` is not one.",
+                function
+            ));
+        };
+        if arguments.len() != parameters.len() {
+            return Err(format!(
+                "Error: Function `{}` expects {} arguments, but {} were provided.",
+                function,
+                parameters.len(),
+                arguments.len()
+            ));
+        }
+        let mut slots: Vec<Option<Expression>> = vec![None; parameters.len()];
+        for (position, argument) in arguments.into_iter().enumerate() {
+            let (index, value) = match argument {
+                Expression::NamedArgument { name, value } => {
+                    let Some(index) = parameters.iter().position(|p| *p == name) else {
+                        return Err(format!(
+                            "Error: Function `{}` has no parameter named `{}`.",
+                            function, name
+                        ));
+                    };
+                    if slots[index].is_some() {
+                        return Err(format!(
+                            "Error: Argument `{}` of `{}` is given more than once.",
+                            name, function
+                        ));
+                    }
+                    (index, *value)
+                }
+                positional => (position, positional),
+            };
+            slots[index] = Some(value);
+        }
+        if let Some(missing) = slots.iter().position(Option::is_none) {
+            return Err(format!(
+                "Error: Call to `{}` is missing argument `{}`.",
+                function, parameters[missing]
+            ));
+        }
+        Ok(slots.into_iter().flatten().collect())
+    }
+}
@@ -1,10 +1,12 @@
-use crate::errors::SourceLocation;
+use crate::errors::{SourceLocation, Span};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     // Literals
     IntegerLiteral(i64),
+    InvalidNumber(String), // integer literal too large for an i64
     FloatLiteral(f64),
+    BooleanLiteral(bool),
     Identifier(String),
 
     // Keywords
@@ -12,6 +14,8 @@ pub enum Token {
     Fn,
     Return,
     Mut,
+    Static,
+    Const,
 
     // Control flow keywords
     If,
@@ -33,6 +37,7 @@ pub enum Token {
     // Phase 5 keywords
     Trait,
     Where,
+    Dyn,
 
     // Phase 7 keywords (v1.0.0 module system + closures)
     Mod,
@@ -42,12 +47,22 @@ pub enum Token {
 
     // String literal
     StringLiteral(String),
-    FStringLiteral(String), // f"hello {name}"
+    CharLiteral(char),
+    FStringLiteral(String),     // f"hello {name}"
+    ByteStringLiteral(Vec<u8>), // b"bytes"
 
     // I/O Macros
-    PrintMacro,   // print!
-    PrintlnMacro, // println!
-    VecMacro,     // vec!
+    PrintMacro,         // print!
+    PrintlnMacro,       // println!
+    VecMacro,           // vec!
+    MatchesMacro,       // matches!
+    TodoMacro,          // todo!
+    UnimplementedMacro, // unimplemented!
+    PanicMacro,         // panic!
+    AssertMacro,        // assert!
+    AssertEqMacro,      // assert_eq!
+    WriteMacro,         // write!
+    WritelnMacro,       // writeln!
 
     // Operators
     Plus,
@@ -80,6 +95,8 @@ pub enum Token {
     LeftBracket,
     RightBracket,
     Dot,
+    DotDot,   // ..
+    DotDotEq, // ..=
     Colon,
     DoubleColon, // ::
     Comma,
@@ -87,6 +104,11 @@ pub enum Token {
     Underscore, // _ (wildcard pattern)
     Ampersand,  // & (borrow / reference)
     Pipe,       // | (single pipe, for closures/patterns)
+    At,         // @ (binding pattern)
+
+    // Trivia, only produced by `tokenize_with_trivia`
+    LineComment(String),  // `// ...`, without the line break
+    BlockComment(String), // `/* ... */`, which may nest
 
     // End of file
     Eof,
@@ -111,10 +133,25 @@ pub fn tokenize(source: &str) -> Vec<Token> {
 }
 
 pub fn tokenize_with_locations(source: &str, filename: Option<String>) -> Vec<LocatedToken> {
+    lex(source, filename, false)
+}
+
+/// Like `tokenize_with_locations`, but keeps comments as `LineComment` and
+/// `BlockComment` tokens interleaved with the rest of the stream, for tools
+/// such as formatters that need them. Comment tokens carry their full text.
+#[cfg_attr(not(test), allow(dead_code))] // Library API; the binary does not call it.
+pub fn tokenize_with_trivia(source: &str, filename: Option<String>) -> Vec<LocatedToken> {
+    lex(source, filename, true)
+}
+
+fn lex(source: &str, filename: Option<String>, keep_trivia: bool) -> Vec<LocatedToken> {
     let mut tokens = Vec::new();
     let mut chars = source.chars().peekable();
-    let mut line = 1;
-    let mut column = 1;
+    let mut pos = Position {
+        line: 1,
+        column: 1,
+        offset: 0,
+    };
 
     // Helper function to create location
     let make_location = |line: usize, column: usize| match &filename {
@@ -122,298 +159,354 @@ pub fn tokenize_with_locations(source: &str, filename: Option<String>) -> Vec<Lo
         None => SourceLocation::new(line, column),
     };
 
-    // Helper function to advance position tracking
-    let advance_position = |c: char, line: &mut usize, column: &mut usize| {
-        if c == '\n' {
-            *line += 1;
-            *column = 1;
-        } else {
-            *column += 1;
-        }
-    };
+    let mut spanned = 0;
+    let mut span_start = 0;
 
     while let Some(&c) = chars.peek() {
-        let token_start_line = line;
-        let token_start_column = column;
+        // Tokens pushed by the previous iteration end where this one starts.
+        set_spans(&mut tokens[spanned..], span_start, pos.offset);
+        spanned = tokens.len();
+        span_start = pos.offset;
+
+        let start = pos;
 
         match c {
             // Whitespace
             ' ' | '\t' | '\n' | '\r' => {
-                let ch = chars.next().unwrap();
-                advance_position(ch, &mut line, &mut column);
+                for &b in source.as_bytes()[pos.offset..]
+                    .iter()
+                    .take_while(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+                {
+                    pos.advance(b as char);
+                }
+                chars = source[pos.offset..].chars().peekable();
             }
             // Operators and delimiters
             '+' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::Plus,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             '*' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::Multiply,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             '/' => {
                 let ch = chars.next().unwrap(); // consume first '/'
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 if let Some(&'/') = chars.peek() {
                     // Line comment - consume until end of line
-                    let ch2 = chars.next().unwrap(); // consume second '/'
-                    advance_position(ch2, &mut line, &mut column);
-                    while let Some(&c) = chars.peek() {
-                        if c == '\n' || c == '\r' {
+                    let rest = &source[pos.offset..];
+                    let comment = &rest[..rest.find(['\n', '\r']).unwrap_or(rest.len())];
+                    pos.advance_within_line(comment);
+                    chars = source[pos.offset..].chars().peekable();
+                    if keep_trivia {
+                        tokens.push(LocatedToken::new(
+                            Token::LineComment(source[start.offset..pos.offset].to_string()),
+                            make_location(start.line, start.column),
+                        ));
+                    }
+                } else if let Some(&'*') = chars.peek() {
+                    // Block comment - consume through the matching `*/`,
+                    // counting nested `/*` pairs
+                    let ch2 = chars.next().unwrap(); // consume '*'
+                    pos.advance(ch2);
+                    let mut depth = 1;
+                    while depth > 0 {
+                        let Some(ch) = chars.next() else {
+                            eprintln!(
+                                "Unterminated block comment at {}:{}",
+                                start.line, start.column
+                            );
                             break;
+                        };
+                        pos.advance(ch);
+                        let closes = ch == '*' && chars.peek() == Some(&'/');
+                        let opens = ch == '/' && chars.peek() == Some(&'*');
+                        if closes || opens {
+                            let ch = chars.next().unwrap();
+                            pos.advance(ch);
+                            if closes {
+                                depth -= 1;
+                            } else {
+                                depth += 1;
+                            }
                         }
-                        let ch = chars.next().unwrap();
-                        advance_position(ch, &mut line, &mut column);
+                    }
+                    if keep_trivia {
+                        tokens.push(LocatedToken::new(
+                            Token::BlockComment(source[start.offset..pos.offset].to_string()),
+                            make_location(start.line, start.column),
+                        ));
                     }
                 } else {
                     tokens.push(LocatedToken::new(
                         Token::Divide,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
+            '@' => {
+                chars.next();
+                pos.advance(c);
+                tokens.push(LocatedToken::new(
+                    Token::At,
+                    make_location(start.line, start.column),
+                ));
+            }
             '%' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::Modulo,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             ';' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::Semicolon,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             '{' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::LeftBrace,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             '}' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::RightBrace,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             '(' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::LeftParen,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             ')' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::RightParen,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             '[' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::LeftBracket,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             ']' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::RightBracket,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             ':' => {
                 let ch = chars.next().unwrap();
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 if let Some(&':') = chars.peek() {
                     let ch2 = chars.next().unwrap();
-                    advance_position(ch2, &mut line, &mut column);
+                    pos.advance(ch2);
                     tokens.push(LocatedToken::new(
                         Token::DoubleColon,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
                     tokens.push(LocatedToken::new(
                         Token::Colon,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             ',' => {
                 chars.next();
-                advance_position(c, &mut line, &mut column);
+                pos.advance(c);
                 tokens.push(LocatedToken::new(
                     Token::Comma,
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             // Handle minus and arrow (->)
             '-' => {
                 let ch = chars.next().unwrap(); // consume '-'
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 if let Some(&'>') = chars.peek() {
                     let ch2 = chars.next().unwrap(); // consume '>'
-                    advance_position(ch2, &mut line, &mut column);
+                    pos.advance(ch2);
                     tokens.push(LocatedToken::new(
                         Token::Arrow,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
                     tokens.push(LocatedToken::new(
                         Token::Minus,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             // Handle assignment, equality, and fat arrow
             '=' => {
                 let ch = chars.next().unwrap(); // consume '='
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 if let Some(&'=') = chars.peek() {
                     let ch2 = chars.next().unwrap(); // consume second '='
-                    advance_position(ch2, &mut line, &mut column);
+                    pos.advance(ch2);
                     tokens.push(LocatedToken::new(
                         Token::Equal,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else if let Some(&'>') = chars.peek() {
                     let ch2 = chars.next().unwrap(); // consume '>'
-                    advance_position(ch2, &mut line, &mut column);
+                    pos.advance(ch2);
                     tokens.push(LocatedToken::new(
                         Token::FatArrow,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
                     tokens.push(LocatedToken::new(
                         Token::Assign,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             // Handle not equal and logical not
             '!' => {
                 let ch = chars.next().unwrap(); // consume '!'
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 if let Some(&'=') = chars.peek() {
                     let ch2 = chars.next().unwrap(); // consume '='
-                    advance_position(ch2, &mut line, &mut column);
+                    pos.advance(ch2);
                     tokens.push(LocatedToken::new(
                         Token::NotEqual,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
                     tokens.push(LocatedToken::new(
                         Token::LogicalNot,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             // Handle less than and less equal
             '<' => {
                 let ch = chars.next().unwrap(); // consume '<'
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 if let Some(&'=') = chars.peek() {
                     let ch2 = chars.next().unwrap(); // consume '='
-                    advance_position(ch2, &mut line, &mut column);
+                    pos.advance(ch2);
                     tokens.push(LocatedToken::new(
                         Token::LessEqual,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
                     tokens.push(LocatedToken::new(
                         Token::LessThan,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             // Handle greater than and greater equal
             '>' => {
                 let ch = chars.next().unwrap(); // consume '>'
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 if let Some(&'=') = chars.peek() {
                     let ch2 = chars.next().unwrap(); // consume '='
-                    advance_position(ch2, &mut line, &mut column);
+                    pos.advance(ch2);
                     tokens.push(LocatedToken::new(
                         Token::GreaterEqual,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
                     tokens.push(LocatedToken::new(
                         Token::GreaterThan,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             // Handle & (reference/borrow) and && (logical and)
             '&' => {
                 let ch = chars.next().unwrap(); // consume '&'
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 if let Some(&'&') = chars.peek() {
                     let ch2 = chars.next().unwrap(); // consume second '&'
-                    advance_position(ch2, &mut line, &mut column);
+                    pos.advance(ch2);
                     tokens.push(LocatedToken::new(
                         Token::LogicalAnd,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
                     tokens.push(LocatedToken::new(
                         Token::Ampersand,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             // Handle | (single pipe) and || (logical or)
             '|' => {
                 let ch = chars.next().unwrap(); // consume '|'
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 if let Some(&'|') = chars.peek() {
                     let ch2 = chars.next().unwrap(); // consume second '|'
-                    advance_position(ch2, &mut line, &mut column);
+                    pos.advance(ch2);
                     tokens.push(LocatedToken::new(
                         Token::LogicalOr,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
                     tokens.push(LocatedToken::new(
                         Token::Pipe,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             // Dot operator
             '.' => {
                 let ch = chars.next().unwrap(); // consume the '.'
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
+                let token = if let Some(&'.') = chars.peek() {
+                    let ch2 = chars.next().unwrap(); // consume the second '.'
+                    pos.advance(ch2);
+                    if let Some(&'=') = chars.peek() {
+                        let ch3 = chars.next().unwrap(); // consume '='
+                        pos.advance(ch3);
+                        Token::DotDotEq
+                    } else {
+                        Token::DotDot
+                    }
+                } else {
+                    Token::Dot
+                };
                 tokens.push(LocatedToken::new(
-                    Token::Dot,
-                    make_location(token_start_line, token_start_column),
+                    token,
+                    make_location(start.line, start.column),
                 ));
             }
             // Integer and Float Literals
             '0'..='9' => {
-                let mut num_str = String::new();
                 let mut has_dot = false;
                 let mut has_exponent = false;
 
@@ -421,8 +514,7 @@ pub fn tokenize_with_locations(source: &str, filename: Option<String>) -> Vec<Lo
                 while let Some(&d) = chars.peek() {
                     if d.is_ascii_digit() {
                         let ch = chars.next().unwrap();
-                        advance_position(ch, &mut line, &mut column);
-                        num_str.push(ch);
+                        pos.advance(ch);
                     } else if d == '.' && !has_dot && !has_exponent {
                         // Look ahead to see if this is a range operator (..) or a float
                         let mut lookahead = chars.clone();
@@ -435,8 +527,7 @@ pub fn tokenize_with_locations(source: &str, filename: Option<String>) -> Vec<Lo
                                 // This is a float literal
                                 has_dot = true;
                                 let ch = chars.next().unwrap();
-                                advance_position(ch, &mut line, &mut column);
-                                num_str.push(ch);
+                                pos.advance(ch);
                             } else {
                                 // Single dot followed by non-digit, don't consume
                                 break;
@@ -448,52 +539,88 @@ pub fn tokenize_with_locations(source: &str, filename: Option<String>) -> Vec<Lo
                     } else if (d == 'e' || d == 'E') && !has_exponent {
                         has_exponent = true;
                         let ch = chars.next().unwrap();
-                        advance_position(ch, &mut line, &mut column);
-                        num_str.push(ch);
+                        pos.advance(ch);
                         // Handle optional sign after exponent
                         if let Some(&sign) = chars.peek()
                             && (sign == '+' || sign == '-')
                         {
                             let ch = chars.next().unwrap();
-                            advance_position(ch, &mut line, &mut column);
-                            num_str.push(ch);
+                            pos.advance(ch);
                         }
                     } else {
                         break;
                     }
                 }
 
+                let num_str = &source[start.offset..pos.offset];
                 if has_dot || has_exponent {
                     let float_val: f64 = num_str.parse().unwrap_or(0.0);
                     tokens.push(LocatedToken::new(
                         Token::FloatLiteral(float_val),
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
-                    let int_val: i64 = num_str.parse().unwrap_or(0);
+                    let token = match num_str.parse() {
+                        Ok(int_val) => Token::IntegerLiteral(int_val),
+                        Err(_) => Token::InvalidNumber(num_str.to_string()),
+                    };
                     tokens.push(LocatedToken::new(
-                        Token::IntegerLiteral(int_val),
-                        make_location(token_start_line, token_start_column),
+                        token,
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             // String literals
+            '\'' => {
+                let quote = chars.next().unwrap(); // consume opening quote
+                pos.advance(quote);
+                let mut value = '\0';
+                if let Some(ch) = chars.next() {
+                    pos.advance(ch);
+                    value = ch;
+                }
+                if value == '\\'
+                    && let Some(escaped) = chars.next()
+                {
+                    pos.advance(escaped);
+                    value = match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        '0' => '\0',
+                        other => other,
+                    };
+                }
+                if let Some(&'\'') = chars.peek() {
+                    let ch = chars.next().unwrap(); // consume closing quote
+                    pos.advance(ch);
+                } else {
+                    eprintln!(
+                        "Unterminated character literal at {}:{}",
+                        start.line, start.column
+                    );
+                }
+                tokens.push(LocatedToken::new(
+                    Token::CharLiteral(value),
+                    make_location(start.line, start.column),
+                ));
+            }
             '"' => {
                 let ch = chars.next().unwrap(); // consume opening quote
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
                 let mut string_content = String::new();
                 while let Some(&c) = chars.peek() {
                     if c == '"' {
                         let ch = chars.next().unwrap(); // consume closing quote
-                        advance_position(ch, &mut line, &mut column);
+                        pos.advance(ch);
                         break;
                     } else if c == '\\' {
                         // Handle escape sequences
                         let _ch = chars.next().unwrap(); // consume backslash
-                        advance_position(_ch, &mut line, &mut column);
+                        pos.advance(_ch);
                         if let Some(&escaped) = chars.peek() {
                             let ch = chars.next().unwrap(); // consume escaped char
-                            advance_position(ch, &mut line, &mut column);
+                            pos.advance(ch);
                             match escaped {
                                 'n' => string_content.push('\n'),
                                 't' => string_content.push('\t'),
@@ -509,27 +636,26 @@ pub fn tokenize_with_locations(source: &str, filename: Option<String>) -> Vec<Lo
                         }
                     } else {
                         let ch = chars.next().unwrap();
-                        advance_position(ch, &mut line, &mut column);
+                        pos.advance(ch);
                         string_content.push(ch);
                     }
                 }
                 tokens.push(LocatedToken::new(
                     Token::StringLiteral(string_content),
-                    make_location(token_start_line, token_start_column),
+                    make_location(start.line, start.column),
                 ));
             }
             // Identifiers and Keywords
             'a'..='z' | 'A'..='Z' | '_' => {
-                let mut ident_str = String::new();
-                while let Some(&d) = chars.peek() {
-                    if d.is_ascii_alphanumeric() || d == '_' {
-                        let ch = chars.next().unwrap();
-                        advance_position(ch, &mut line, &mut column);
-                        ident_str.push(ch);
-                    } else {
-                        break;
-                    }
-                }
+                // Identifiers are ASCII, so they are scanned as bytes and
+                // sliced; only non-keywords allocate.
+                let len = source.as_bytes()[pos.offset..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+                    .count();
+                let ident_str = &source[pos.offset..pos.offset + len];
+                pos.advance_within_line(ident_str);
+                chars = source[pos.offset..].chars().peekable();
 
                 // f"..." interpolation literal.
                 // Keep the token as a single literal so parser can desugar placeholders.
@@ -537,20 +663,20 @@ pub fn tokenize_with_locations(source: &str, filename: Option<String>) -> Vec<Lo
                     && let Some(&'"') = chars.peek()
                 {
                     let quote = chars.next().unwrap(); // consume opening quote
-                    advance_position(quote, &mut line, &mut column);
+                    pos.advance(quote);
                     let mut string_content = String::new();
                     while let Some(&c) = chars.peek() {
                         if c == '"' {
                             let ch = chars.next().unwrap(); // consume closing quote
-                            advance_position(ch, &mut line, &mut column);
+                            pos.advance(ch);
                             break;
                         } else if c == '\\' {
                             // Handle escape sequences
                             let _ch = chars.next().unwrap(); // consume backslash
-                            advance_position(_ch, &mut line, &mut column);
+                            pos.advance(_ch);
                             if let Some(&escaped) = chars.peek() {
                                 let ch = chars.next().unwrap(); // consume escaped char
-                                advance_position(ch, &mut line, &mut column);
+                                pos.advance(ch);
                                 match escaped {
                                     'n' => string_content.push('\n'),
                                     't' => string_content.push('\t'),
@@ -566,48 +692,181 @@ pub fn tokenize_with_locations(source: &str, filename: Option<String>) -> Vec<Lo
                             }
                         } else {
                             let ch = chars.next().unwrap();
-                            advance_position(ch, &mut line, &mut column);
+                            pos.advance(ch);
                             string_content.push(ch);
                         }
                     }
                     tokens.push(LocatedToken::new(
                         Token::FStringLiteral(string_content),
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
+                    ));
+                    continue;
+                }
+
+                // r"..." / r#"..."# raw strings: no escape processing, and the
+                // `#`s in the delimiter let the body contain `"`.
+                if ident_str == "r" {
+                    let mut lookahead = chars.clone();
+                    let mut hashes = 0;
+                    while lookahead.next_if_eq(&'#').is_some() {
+                        hashes += 1;
+                    }
+                    if lookahead.peek() == Some(&'"') {
+                        for _ in 0..=hashes {
+                            let ch = chars.next().unwrap(); // consume `#`s and opening quote
+                            pos.advance(ch);
+                        }
+                        let closing = format!("\"{}", "#".repeat(hashes));
+                        let mut string_content = String::new();
+                        let mut terminated = false;
+                        for ch in chars.by_ref() {
+                            pos.advance(ch);
+                            string_content.push(ch);
+                            if string_content.ends_with(&closing) {
+                                string_content.truncate(string_content.len() - closing.len());
+                                terminated = true;
+                                break;
+                            }
+                        }
+                        if !terminated {
+                            eprintln!(
+                                "Unterminated raw string literal at {}:{}",
+                                start.line, start.column
+                            );
+                        }
+                        tokens.push(LocatedToken::new(
+                            Token::StringLiteral(string_content),
+                            make_location(start.line, start.column),
+                        ));
+                        continue;
+                    }
+                }
+
+                // b"..." byte string, kept as the literal bytes
+                if ident_str == "b"
+                    && let Some(&'"') = chars.peek()
+                {
+                    let quote = chars.next().unwrap(); // consume opening quote
+                    pos.advance(quote);
+                    let mut bytes = Vec::new();
+                    while let Some(c) = chars.next() {
+                        pos.advance(c);
+                        match c {
+                            '"' => break,
+                            '\\' => {
+                                let Some(escaped) = chars.next() else {
+                                    break;
+                                };
+                                pos.advance(escaped);
+                                match escaped {
+                                    'n' => bytes.push(b'\n'),
+                                    't' => bytes.push(b'\t'),
+                                    'r' => bytes.push(b'\r'),
+                                    '0' => bytes.push(0),
+                                    'x' => {
+                                        let mut hex = String::new();
+                                        while hex.len() < 2
+                                            && let Some(d) = chars.next_if(char::is_ascii_hexdigit)
+                                        {
+                                            pos.advance(d);
+                                            hex.push(d);
+                                        }
+                                        bytes.push(u8::from_str_radix(&hex, 16).unwrap_or(0));
+                                    }
+                                    other => {
+                                        let mut buf = [0; 4];
+                                        bytes.extend_from_slice(
+                                            other.encode_utf8(&mut buf).as_bytes(),
+                                        );
+                                    }
+                                }
+                            }
+                            other => {
+                                let mut buf = [0; 4];
+                                bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+                            }
+                        }
+                    }
+                    tokens.push(LocatedToken::new(
+                        Token::ByteStringLiteral(bytes),
+                        make_location(start.line, start.column),
                     ));
                     continue;
                 }
 
                 // Check for I/O macros (identifiers followed by !)
                 if let Some(&'!') = chars.peek() {
-                    let token = match ident_str.as_str() {
+                    let token = match ident_str {
                         "print" => {
                             let ch = chars.next().unwrap(); // consume '!'
-                            advance_position(ch, &mut line, &mut column);
+                            pos.advance(ch);
                             Token::PrintMacro
                         }
                         "println" => {
                             let ch = chars.next().unwrap(); // consume '!'
-                            advance_position(ch, &mut line, &mut column);
+                            pos.advance(ch);
                             Token::PrintlnMacro
                         }
                         "vec" => {
                             let ch = chars.next().unwrap(); // consume '!'
-                            advance_position(ch, &mut line, &mut column);
+                            pos.advance(ch);
                             Token::VecMacro
                         }
-                        _ => Token::Identifier(ident_str), // Regular identifier, don't consume '!'
+                        "matches" => {
+                            let ch = chars.next().unwrap(); // consume '!'
+                            pos.advance(ch);
+                            Token::MatchesMacro
+                        }
+                        "todo" => {
+                            let ch = chars.next().unwrap(); // consume '!'
+                            pos.advance(ch);
+                            Token::TodoMacro
+                        }
+                        "unimplemented" => {
+                            let ch = chars.next().unwrap(); // consume '!'
+                            pos.advance(ch);
+                            Token::UnimplementedMacro
+                        }
+                        "panic" => {
+                            let ch = chars.next().unwrap(); // consume '!'
+                            pos.advance(ch);
+                            Token::PanicMacro
+                        }
+                        "assert" => {
+                            let ch = chars.next().unwrap(); // consume '!'
+                            pos.advance(ch);
+                            Token::AssertMacro
+                        }
+                        "assert_eq" => {
+                            let ch = chars.next().unwrap(); // consume '!'
+                            pos.advance(ch);
+                            Token::AssertEqMacro
+                        }
+                        "write" => {
+                            let ch = chars.next().unwrap(); // consume '!'
+                            pos.advance(ch);
+                            Token::WriteMacro
+                        }
+                        "writeln" => {
+                            let ch = chars.next().unwrap(); // consume '!'
+                            pos.advance(ch);
+                            Token::WritelnMacro
+                        }
+                        _ => Token::Identifier(ident_str.to_string()), // Regular identifier, don't consume '!'
                     };
                     tokens.push(LocatedToken::new(
                         token,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 } else {
                     // Regular keywords and identifiers
-                    let token = match ident_str.as_str() {
+                    let token = match ident_str {
                         "let" => Token::Let,
                         "fn" => Token::Fn,
                         "return" => Token::Return,
                         "mut" => Token::Mut,
+                        "static" => Token::Static,
+                        "const" => Token::Const,
                         "if" => Token::If,
                         "else" => Token::Else,
                         "while" => Token::While,
@@ -623,36 +882,88 @@ pub fn tokenize_with_locations(source: &str, filename: Option<String>) -> Vec<Lo
                         "self" => Token::Self_,
                         "trait" => Token::Trait,
                         "where" => Token::Where,
+                        "dyn" => Token::Dyn,
                         "mod" => Token::Mod,
                         "use" => Token::Use,
                         "pub" => Token::Pub,
                         "as" => Token::As,
                         "_" => Token::Underscore,
-                        _ => Token::Identifier(ident_str),
+                        "true" => Token::BooleanLiteral(true),
+                        "false" => Token::BooleanLiteral(false),
+                        _ => Token::Identifier(ident_str.to_string()),
                     };
                     tokens.push(LocatedToken::new(
                         token,
-                        make_location(token_start_line, token_start_column),
+                        make_location(start.line, start.column),
                     ));
                 }
             }
             _ => {
                 // Handle unexpected characters or errors
-                eprintln!("Unexpected character: {} at {}:{}", c, line, column);
+                eprintln!("Unexpected character: {} at {}:{}", c, pos.line, pos.column);
                 let ch = chars.next().unwrap();
-                advance_position(ch, &mut line, &mut column);
+                pos.advance(ch);
             }
         }
     }
 
-    tokens.push(LocatedToken::new(Token::Eof, make_location(line, column)));
+    set_spans(&mut tokens[spanned..], span_start, source.len());
+    tokens.push(LocatedToken::new(
+        Token::Eof,
+        make_location(pos.line, pos.column).with_span(source.len(), source.len()),
+    ));
     tokens
 }
 
+fn set_spans(tokens: &mut [LocatedToken], start: usize, end: usize) {
+    for token in tokens {
+        token.location.span = Some(Span { start, end });
+    }
+}
+
+/// Where the lexer is in the source: the 1-based line and column reported
+/// in locations, and the byte offset used for spans and slicing.
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    line: usize,
+    column: usize,
+    offset: usize,
+}
+
+impl Position {
+    fn advance(&mut self, c: char) {
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        self.offset += c.len_utf8();
+    }
+
+    /// Move past `text`, which must not contain a line break.
+    fn advance_within_line(&mut self, text: &str) {
+        self.column += text.chars().count();
+        self.offset += text.len();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn integer_literals_beyond_i64_are_invalid_numbers() {
+        assert_eq!(
+            tokenize("9223372036854775807 9223372036854775808"),
+            vec![
+                Token::IntegerLiteral(i64::MAX),
+                Token::InvalidNumber("9223372036854775808".to_string()),
+                Token::Eof,
+            ]
+        );
+    }
+
     #[test]
     fn test_location_tracking() {
         let source = "let x = 5;\nlet y = 10;";
@@ -671,6 +982,30 @@ mod tests {
         assert_eq!(tokens[5].location.column, 1);
     }
 
+    #[test]
+    fn test_token_spans_are_byte_ranges() {
+        let source = "let s = \"héllo\";";
+        let tokens = tokenize_with_locations(source, None);
+
+        let text = |i: usize| {
+            let span = tokens[i].location.span.unwrap();
+            &source[span.start..span.end]
+        };
+        assert_eq!(text(0), "let");
+        assert_eq!(text(3), "\"héllo\"");
+        assert_eq!(text(4), ";");
+        assert_eq!(tokens[5].token, Token::Eof);
+        assert_eq!(text(5), "");
+    }
+
+    #[test]
+    fn test_char_literals() {
+        let tokens = tokenize(r"'a' '\n' '\''");
+        assert_eq!(tokens[0], Token::CharLiteral('a'));
+        assert_eq!(tokens[1], Token::CharLiteral('\n'));
+        assert_eq!(tokens[2], Token::CharLiteral('\''));
+    }
+
     #[test]
     fn test_function_tokens() {
         let source = "fn main() -> i32 { let mut x = 5; }";
@@ -718,6 +1053,38 @@ mod tests {
         assert_eq!(tokens[3], Token::Eof);
     }
 
+    #[test]
+    fn test_range_tokens() {
+        let source = "1..5 1..=5 a.b";
+        let tokens = tokenize(source);
+
+        assert_eq!(tokens[0], Token::IntegerLiteral(1));
+        assert_eq!(tokens[1], Token::DotDot);
+        assert_eq!(tokens[2], Token::IntegerLiteral(5));
+        assert_eq!(tokens[3], Token::IntegerLiteral(1));
+        assert_eq!(tokens[4], Token::DotDotEq);
+        assert_eq!(tokens[5], Token::IntegerLiteral(5));
+        assert_eq!(tokens[7], Token::Dot);
+    }
+
+    #[test]
+    fn test_matches_macro_token() {
+        let tokens = tokenize("matches!(x, Some(_))");
+        assert_eq!(tokens[0], Token::MatchesMacro);
+        assert_eq!(tokens[1], Token::LeftParen);
+    }
+
+    #[test]
+    fn test_boolean_literals() {
+        let source = "true false truthy";
+        let tokens = tokenize(source);
+
+        assert_eq!(tokens[0], Token::BooleanLiteral(true));
+        assert_eq!(tokens[1], Token::BooleanLiteral(false));
+        assert_eq!(tokens[2], Token::Identifier("truthy".to_string()));
+        assert_eq!(tokens[3], Token::Eof);
+    }
+
     #[test]
     fn test_io_macros() {
         let source = r#"print!("Hello") println!("World")"#;
@@ -741,6 +1108,29 @@ mod tests {
         assert!(tokens.iter().any(|t| matches!(t, Token::VecMacro)));
     }
 
+    #[test]
+    fn test_todo_and_unimplemented_macros() {
+        let tokens = tokenize("todo!() unimplemented!() todo");
+        assert_eq!(tokens[0], Token::TodoMacro);
+        assert_eq!(tokens[3], Token::UnimplementedMacro);
+        assert_eq!(tokens[6], Token::Identifier("todo".to_string()));
+    }
+
+    #[test]
+    fn test_panic_macro() {
+        let tokens = tokenize("panic!(\"x\") panic");
+        assert_eq!(tokens[0], Token::PanicMacro);
+        assert_eq!(tokens[4], Token::Identifier("panic".to_string()));
+    }
+
+    #[test]
+    fn test_write_macros() {
+        let tokens = tokenize("write!(buf) writeln!(buf) write");
+        assert_eq!(tokens[0], Token::WriteMacro);
+        assert_eq!(tokens[4], Token::WritelnMacro);
+        assert_eq!(tokens[8], Token::Identifier("write".to_string()));
+    }
+
     #[test]
     fn test_f_string_token() {
         let source = r#"println!(f"hello {name}")"#;
@@ -765,4 +1155,143 @@ mod tests {
         assert!(tokens.iter().any(|t| *t == Token::Match));
         assert!(tokens.iter().any(|t| *t == Token::Impl));
     }
+
+    #[test]
+    fn test_trivia_preserves_comments_with_positions() {
+        let source =
+            "// header\nlet x = /* inline */ 1; // tail\n/* a\n/* nested */ b */ let y = 2;";
+        let tokens = tokenize_with_trivia(source, None);
+
+        let comments: Vec<(&Token, usize, usize, &str)> = tokens
+            .iter()
+            .filter(|t| matches!(t.token, Token::LineComment(_) | Token::BlockComment(_)))
+            .map(|t| {
+                let span = t.location.span.unwrap();
+                (
+                    &t.token,
+                    t.location.line,
+                    t.location.column,
+                    &source[span.start..span.end],
+                )
+            })
+            .collect();
+        assert_eq!(
+            comments,
+            [
+                (
+                    &Token::LineComment("// header".to_string()),
+                    1,
+                    1,
+                    "// header"
+                ),
+                (
+                    &Token::BlockComment("/* inline */".to_string()),
+                    2,
+                    9,
+                    "/* inline */"
+                ),
+                (&Token::LineComment("// tail".to_string()), 2, 25, "// tail"),
+                (
+                    &Token::BlockComment("/* a\n/* nested */ b */".to_string()),
+                    3,
+                    1,
+                    "/* a\n/* nested */ b */"
+                ),
+            ]
+        );
+
+        // Comments are interleaved where they occur in the source.
+        assert_eq!(tokens[0].token, Token::LineComment("// header".to_string()));
+        assert_eq!(
+            tokens[4].token,
+            Token::BlockComment("/* inline */".to_string())
+        );
+        assert_eq!(tokens[5].token, Token::IntegerLiteral(1));
+    }
+
+    #[test]
+    fn test_nested_block_comments_are_skipped() {
+        let source = "let /* outer /* inner */ still comment */ x = 1;";
+        assert_eq!(
+            tokenize(source),
+            [
+                Token::Let,
+                Token::Identifier("x".to_string()),
+                Token::Assign,
+                Token::IntegerLiteral(1),
+                Token::Semicolon,
+                Token::Eof,
+            ]
+        );
+        // Without trivia the remaining tokens are unchanged.
+        let without: Vec<Token> = tokenize_with_trivia(source, None)
+            .into_iter()
+            .map(|t| t.token)
+            .filter(|t| !matches!(t, Token::BlockComment(_)))
+            .collect();
+        assert_eq!(without, tokenize(source));
+    }
+
+    #[test]
+    fn test_raw_string_skips_escape_processing() {
+        let tokens = tokenize(r#"let p = r"\n";"#);
+        // A backslash and an `n`, not a newline
+        assert_eq!(
+            tokens[3],
+            Token::StringLiteral(['\\', 'n'].iter().collect())
+        );
+    }
+
+    #[test]
+    fn test_hashed_raw_string_allows_embedded_quotes() {
+        let tokens = tokenize(r###"let q = r#"has "quotes""#;"###);
+        assert_eq!(
+            tokens[3],
+            Token::StringLiteral(r#"has "quotes""#.to_string())
+        );
+        assert_eq!(tokens[4], Token::Semicolon);
+    }
+
+    #[test]
+    fn test_byte_string_yields_bytes() {
+        let tokens = tokenize(r#"let b = b"AB";"#);
+        assert_eq!(tokens[3], Token::ByteStringLiteral(vec![65, 66]));
+        assert_eq!(tokens[4], Token::Semicolon);
+    }
+
+    #[test]
+    fn test_large_input_matches_the_repeated_reference_stream() {
+        // Most kinds of token, with non-ASCII text so that columns and byte
+        // offsets disagree.
+        const UNIT: &str = "// réf: ×2\nfn step_1(x: i32) -> f64 { /* a /* b */ */\n    let s = \"héllo\\n\"; let c = 'é'; let r = r#\"q\"#; let b = b\"\\x41\";\n    if x >= 10 && x != 0 || !done { return 1.5e3; }\n    v[0..=2].len() as f64 * 0.5 / f\"{x}\".len() % 7\n}\n";
+        let reference = tokenize_with_trivia(UNIT, None);
+        let reference = &reference[..reference.len() - 1]; // without Eof
+        let lines = UNIT.matches('\n').count();
+        let copies = 2_000;
+
+        let tokens = tokenize_with_trivia(&UNIT.repeat(copies), None);
+        assert_eq!(tokens.len(), reference.len() * copies + 1);
+        for (copy, chunk) in tokens.chunks(reference.len()).take(copies).enumerate() {
+            let shift = copy * UNIT.len();
+            for (token, expected) in chunk.iter().zip(reference) {
+                let span = expected.location.span.unwrap();
+                assert_eq!(token.token, expected.token);
+                assert_eq!(
+                    (token.location.line, token.location.column),
+                    (
+                        expected.location.line + copy * lines,
+                        expected.location.column
+                    )
+                );
+                assert_eq!(
+                    token.location.span,
+                    Some(Span {
+                        start: span.start + shift,
+                        end: span.end + shift,
+                    })
+                );
+            }
+        }
+        assert_eq!(tokens.last().unwrap().token, Token::Eof);
+    }
 }
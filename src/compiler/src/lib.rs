@@ -1,25 +1,40 @@
 pub mod accelerator;
 pub mod ast;
+mod borrow_checker;
+pub mod cfg;
 mod code_generator;
 pub mod conformance;
 pub mod errors;
 pub mod gpu;
 pub mod graph_compiler;
+pub mod hover;
 mod ir;
 mod ir_generator;
+mod ir_graph;
+mod ir_optimizer;
+mod ir_stats;
+mod keyword_arguments;
 pub mod lexer;
+pub mod manifest;
 pub mod module_resolver;
 pub mod parser;
+pub mod phases;
 pub mod quantization;
 pub mod registry;
+pub mod runner;
 pub mod semantic_analyzer;
 pub mod stdlib;
+pub mod test_harness;
+mod toolchain;
 pub mod types;
 
-pub use code_generator::{CodeGenerator, generate_code};
+pub use code_generator::{CodeGenerator, PanicStrategy, generate_code};
+pub use errors::Diagnostic;
 pub use ir_generator::IrGenerator;
-pub use lexer::{LocatedToken, Token, tokenize, tokenize_with_locations};
+pub use ir_optimizer::IrPass;
+pub use lexer::{LocatedToken, Token, tokenize, tokenize_with_locations, tokenize_with_trivia};
 pub use parser::{Parser, parse, parse_with_locations};
+pub use runner::{ProgramOutput, run_source};
 pub use semantic_analyzer::SemanticAnalyzer;
 
 #[cfg(test)]
@@ -28,32 +43,35 @@ mod error_test;
 /// Compiler options for benchmarking
 #[derive(Debug, Clone, Default)]
 pub struct CompilerOptions {
-    pub optimize: bool,
+    /// 0 runs no IR passes, 1 the cheap ones, 2 all of them.
+    pub opt_level: u8,
     pub debug_info: bool,
     pub target: String,
+    /// Features enabled for `@cfg(feature = "...")` items.
+    pub features: Vec<String>,
 }
 
 /// Main compilation function for benchmarking
-pub fn compile_program(source: &str, _options: CompilerOptions) -> Result<String, String> {
-    // Lexical analysis
-    let tokens = tokenize(source);
-
-    // Parsing
-    let ast = parse(tokens);
-
-    // Semantic analysis
-    let mut semantic_analyzer = SemanticAnalyzer::new();
-    let (_analyzed_result, analyzed_ast) = match semantic_analyzer.analyze(ast.clone()) {
-        Ok((msg, typed_ast)) => (msg, typed_ast),
-        Err(err) => return Err(format!("Semantic Analysis Error: {}", err)),
-    };
-
-    // IR generation
-    let mut ir_generator = IrGenerator::new();
-    let ir = ir_generator.generate_ir(analyzed_ast);
-
-    // Code generation
-    let llvm_code = generate_code(ir);
+pub fn compile_program(source: &str, options: CompilerOptions) -> Result<String, String> {
+    compile_program_diagnostics(source, options).map_err(|diagnostics| {
+        diagnostics
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
 
-    Ok(llvm_code)
+/// Like `compile_program`, but reports failures as individual located
+/// diagnostics rather than one formatted string.
+pub fn compile_program_diagnostics(
+    source: &str,
+    options: CompilerOptions,
+) -> Result<String, Vec<Diagnostic>> {
+    let tokens = phases::tokenize(source);
+    let ast = phases::strip_cfg(phases::parse(tokens)?, &options.features);
+    let typed_ast = phases::analyze(ast)?;
+    let mut ir = phases::generate_ir(typed_ast);
+    phases::optimize(&mut ir, options.opt_level);
+    Ok(phases::generate_code(ir))
 }
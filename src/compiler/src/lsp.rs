@@ -1,4 +1,5 @@
 use crate::errors::{CompilerError, SourceLocation};
+use crate::hover::resolve_at;
 use crate::lexer::{Token, tokenize_with_locations};
 use crate::parser::parse_with_locations;
 use serde::Serialize;
@@ -469,6 +470,17 @@ fn hover_result(
         }));
     }
 
+    let line = position.line as usize + 1;
+    let column = position.character as usize + 1;
+    if let Some((_, ty)) = resolve_at(&document.text, line, column) {
+        return Some(json!({
+            "contents": {
+                "kind": "markdown",
+                "value": format!("```aero\n{}\n```", ty)
+            }
+        }));
+    }
+
     if let Some(builtin) = builtin_hover(&word) {
         return Some(json!({
             "contents": {
@@ -744,45 +756,6 @@ fn read_message(reader: &mut dyn BufRead) -> Result<Option<Value>, String> {
     Ok(Some(message))
 }
 
-trait ErrorLocation {
-    fn location(&self) -> Option<&SourceLocation>;
-}
-
-impl ErrorLocation for CompilerError {
-    fn location(&self) -> Option<&SourceLocation> {
-        match self {
-            CompilerError::UnexpectedCharacter { location, .. }
-            | CompilerError::UnterminatedString { location }
-            | CompilerError::InvalidNumber { location, .. }
-            | CompilerError::UnexpectedToken { location, .. }
-            | CompilerError::UnexpectedEndOfInput { location, .. }
-            | CompilerError::InvalidSyntax { location, .. }
-            | CompilerError::FunctionRedefinition { location, .. }
-            | CompilerError::UndefinedFunction { location, .. }
-            | CompilerError::ArityMismatch { location, .. }
-            | CompilerError::ParameterTypeMismatch { location, .. }
-            | CompilerError::ReturnTypeMismatch { location, .. }
-            | CompilerError::BreakOutsideLoop { location }
-            | CompilerError::ContinueOutsideLoop { location }
-            | CompilerError::UnreachableCode { location }
-            | CompilerError::InvalidConditionType { location, .. }
-            | CompilerError::UndefinedVariable { location, .. }
-            | CompilerError::VariableRedefinition { location, .. }
-            | CompilerError::ImmutableAssignment { location, .. }
-            | CompilerError::UninitializedVariable { location, .. }
-            | CompilerError::TypeMismatch { location, .. }
-            | CompilerError::IncompatibleTypes { location, .. }
-            | CompilerError::InvalidTypeAnnotation { location, .. }
-            | CompilerError::InvalidFormatString { location, .. }
-            | CompilerError::FormatArgumentMismatch { location, .. }
-            | CompilerError::InvalidFormatSpecifier { location, .. }
-            | CompilerError::InvalidOperation { location, .. }
-            | CompilerError::ScopeError { location, .. } => Some(location),
-            CompilerError::MultiError { .. } => None,
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
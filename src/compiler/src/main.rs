@@ -1,5 +1,7 @@
 mod accelerator;
 mod ast;
+mod borrow_checker;
+mod cfg;
 mod code_generator;
 mod compatibility;
 mod conformance;
@@ -7,10 +9,16 @@ mod doc_generator;
 mod errors;
 mod gpu;
 mod graph_compiler;
+mod hover;
 mod ir;
 mod ir_generator;
+mod ir_graph;
+mod ir_optimizer;
+mod ir_stats;
+mod keyword_arguments;
 mod lexer;
 mod lsp;
+mod manifest;
 mod module_resolver;
 mod optimizations;
 mod parser;
@@ -20,19 +28,24 @@ mod project_init;
 mod quantization;
 mod registry;
 mod semantic_analyzer;
+mod test_harness;
+mod toolchain;
 mod types;
 
 // (unit tests live in the library crate)
 
+use crate::code_generator::{CodeGenerator, PanicStrategy};
 use crate::ir_generator::IrGenerator;
 use crate::performance_optimizations::PerformanceOptimizer;
-use crate::semantic_analyzer::SemanticAnalyzer;
+use crate::semantic_analyzer::{SemanticAnalyzer, ShadowWarning, ShadowWarnings};
 use accelerator::AcceleratorBackend;
 use gpu::{DeviceProfile, GpuDevice, default_gpu_arch};
+use std::collections::HashMap;
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Command, exit};
+use std::process::{Command, Stdio, exit};
 use std::time::Instant;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -74,6 +87,22 @@ impl BuildTarget {
 struct BuildConfig {
     target: BuildTarget,
     gpu_arch: Option<String>,
+    max_errors: usize,
+    color: errors::ColorChoice,
+    opt_level: u8,
+    verify: bool,
+    emit_deps: bool,
+    emit_asm: bool,
+    /// Write the IR's control flow graph as Graphviz DOT instead of LLVM IR.
+    emit_cfg_dot: bool,
+    features: Vec<String>,
+    /// The `@test` function to build a harness program for, if any.
+    test: Option<String>,
+    panic: PanicStrategy,
+    /// Print instruction counts of the IR, before and after optimization.
+    stats: bool,
+    /// Print the IR to stderr after each of these passes changes it.
+    dump_ir_after: Vec<ir_optimizer::IrPass>,
 }
 
 impl Default for BuildConfig {
@@ -81,6 +110,18 @@ impl Default for BuildConfig {
         Self {
             target: BuildTarget::Cpu,
             gpu_arch: None,
+            max_errors: errors::DEFAULT_MAX_ERRORS,
+            color: errors::ColorChoice::default(),
+            opt_level: 0,
+            verify: false,
+            emit_deps: false,
+            emit_asm: false,
+            emit_cfg_dot: false,
+            features: Vec::new(),
+            test: None,
+            panic: PanicStrategy::default(),
+            stats: false,
+            dump_ir_after: Vec::new(),
         }
     }
 }
@@ -206,6 +247,11 @@ impl BuildConfig {
         default_gpu_arch_for_backend(backend).unwrap_or("x86_64")
     }
 
+    /// The `llc` optimization flag matching `--opt-level`.
+    fn llc_opt_flag(&self) -> Option<String> {
+        toolchain::llc_opt_flag(self.opt_level)
+    }
+
     fn llvm_target_triple(&self) -> &str {
         let backend = backend_for_target(self.target);
         let device = GpuDevice::new(backend, 0, self.gpu_arch.clone());
@@ -249,6 +295,13 @@ fn main() {
             return;
         }
         "build" => {
+            let args = match with_manifest_input(&args) {
+                Ok(args) => args,
+                Err(err) => {
+                    eprintln!("\x1b[1;31merror\x1b[0m: {}", err);
+                    exit(1);
+                }
+            };
             let (input_file, output_file, build_config) = match parse_build_args(&args) {
                 Ok(parsed) => parsed,
                 Err(usage) => {
@@ -256,6 +309,21 @@ fn main() {
                     return;
                 }
             };
+            if build_config.emit_deps {
+                // Make-style rule: the output depends on every reachable module.
+                match module_resolver::ModuleResolver::dependencies(&input_file) {
+                    Ok(files) => {
+                        let files: Vec<String> =
+                            files.iter().map(|f| f.display().to_string()).collect();
+                        println!("{}: {}", output_file, files.join(" "));
+                    }
+                    Err(err) => {
+                        eprintln!("\x1b[1;31merror\x1b[0m: {}", err);
+                        exit(1);
+                    }
+                }
+                return;
+            }
             apply_target_environment(&build_config);
 
             let source_code = match fs::read_to_string(&input_file) {
@@ -267,8 +335,24 @@ fn main() {
             };
 
             compile_to_llvm_ir(&source_code, &output_file, &input_file, &build_config);
+            if build_config.emit_asm && Path::new(&output_file).exists() {
+                match emit_assembly(Path::new(&output_file), &build_config) {
+                    Ok(asm_path) => println!("Assembly written to {}", asm_path.display()),
+                    Err(err) => {
+                        eprintln!("\x1b[1;31merror\x1b[0m: {}", err);
+                        exit(1);
+                    }
+                }
+            }
         }
         "run" => {
+            let args = match with_manifest_input(&args) {
+                Ok(args) => args,
+                Err(err) => {
+                    eprintln!("\x1b[1;31merror\x1b[0m: {}", err);
+                    exit(1);
+                }
+            };
             let (input_file, build_config) = match parse_run_args(&args) {
                 Ok(parsed) => parsed,
                 Err(usage) => {
@@ -292,11 +376,48 @@ fn main() {
             }
         }
         "check" => {
+            // Type-check only: no IR or LLVM is generated, so CI and editors
+            // can run this without a toolchain. Any failure exits non-zero.
             if args.len() < 3 {
-                eprintln!("Usage: {} check <input.aero>", args[0]);
-                return;
+                eprintln!(
+                    "Usage: {} check <input.aero> [--max-errors=N] [--color=auto|always|never] [--warn-shadow[=all]] [--feature <name>]",
+                    args[0]
+                );
+                exit(1);
             }
             let input_file = &args[2];
+            let mut max_errors = errors::DEFAULT_MAX_ERRORS;
+            let mut color = errors::ColorChoice::default();
+            let mut shadow_warnings = ShadowWarnings::Off;
+            let mut features = Vec::new();
+            let mut flags = args[3..].iter();
+            while let Some(flag) = flags.next() {
+                match flag.as_str() {
+                    "--warn-shadow" => shadow_warnings = ShadowWarnings::TypeChange,
+                    "--warn-shadow=all" => shadow_warnings = ShadowWarnings::All,
+                    "--feature" => match flags.next() {
+                        Some(feature) => features.push(feature.clone()),
+                        None => {
+                            eprintln!("error: `--feature` expects a feature name");
+                            exit(1);
+                        }
+                    },
+                    flag if flag.starts_with("--color=") => match parse_color_flag(flag) {
+                        Ok(choice) => color = choice,
+                        Err(err) => {
+                            eprintln!("{}", err);
+                            exit(1);
+                        }
+                    },
+                    flag => match parse_max_errors_flag(flag) {
+                        Ok(n) => max_errors = n,
+                        Err(err) => {
+                            eprintln!("{}", err);
+                            exit(1);
+                        }
+                    },
+                }
+            }
 
             let source_code = match fs::read_to_string(input_file) {
                 Ok(content) => content,
@@ -305,11 +426,45 @@ fn main() {
                         "\x1b[1;31merror\x1b[0m: could not read file {}: {}",
                         input_file, err
                     );
+                    exit(1);
+                }
+            };
+
+            check_aero_program(
+                &source_code,
+                input_file,
+                max_errors,
+                color,
+                shadow_warnings,
+                &features,
+            );
+        }
+        "test" if args.len() >= 3 => {
+            let (input_file, build_config) = match parse_run_args(&args) {
+                Ok(parsed) => parsed,
+                Err(usage) => {
+                    eprintln!("{}", usage);
                     return;
                 }
             };
+            apply_target_environment(&build_config);
 
-            check_aero_program(&source_code, input_file);
+            let source_code = match fs::read_to_string(&input_file) {
+                Ok(content) => content,
+                Err(err) => {
+                    eprintln!("Error reading file {}: {}", input_file, err);
+                    return;
+                }
+            };
+
+            match run_aero_tests(&source_code, &input_file, &build_config) {
+                Ok(0) => {}
+                Ok(_) => exit(1),
+                Err(err) => {
+                    eprintln!("\x1b[1;31merror\x1b[0m: {}", err);
+                    exit(1);
+                }
+            }
         }
         "test" => {
             // Discover and run *_test.aero files in examples/ and current directory
@@ -328,7 +483,7 @@ fn main() {
                                 println!("\x1b[1;36m     Running\x1b[0m {}", path.display());
                                 if let Ok(src) = fs::read_to_string(&path) {
                                     let tokens = lexer::tokenize(&src);
-                                    let ast = parser::parse(tokens);
+                                    let ast = cfg::strip_inactive_items(parser::parse(tokens), &[]);
                                     let mut analyzer = SemanticAnalyzer::new();
                                     match analyzer.analyze(ast) {
                                         Ok(_) => {
@@ -1250,10 +1405,50 @@ fn main() {
     }
 }
 
+/// `aero build`/`run` without an input file build the package whose
+/// `aero.toml` is in the current directory: its root file becomes the input,
+/// its features are enabled, and `build` writes `<name>.ll` unless `-o` is
+/// given.
+fn with_manifest_input(args: &[String]) -> Result<Vec<String>, String> {
+    if has_input_file(args) {
+        return Ok(args.to_vec());
+    }
+    let dir = env::current_dir()
+        .map_err(|err| format!("could not determine the current directory: {}", err))?;
+    let manifest = manifest::Manifest::load(&dir)?;
+
+    let mut expanded = args[..2].to_vec();
+    expanded.push(manifest.root.display().to_string());
+    expanded.extend_from_slice(&args[2..]);
+    for feature in manifest.features {
+        expanded.push("--feature".to_string());
+        expanded.push(feature);
+    }
+    if args[1] == "build" && !args.iter().any(|arg| arg == "-o") {
+        expanded.push("-o".to_string());
+        expanded.push(format!("{}.ll", manifest.name));
+    }
+    Ok(expanded)
+}
+
+fn has_input_file(args: &[String]) -> bool {
+    let mut rest = args.iter().skip(2);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-o" | "--target" | "--backend" | "--gpu" | "--feature" => {
+                rest.next();
+            }
+            flag if flag.starts_with('-') => {}
+            _ => return true,
+        }
+    }
+    false
+}
+
 fn parse_build_args(args: &[String]) -> Result<(String, String, BuildConfig), String> {
     if args.len() < 3 {
         return Err(format!(
-            "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+            "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>] [--emit=deps|cfg-dot] [--emit-asm]",
             args[0]
         ));
     }
@@ -1267,7 +1462,7 @@ fn parse_build_args(args: &[String]) -> Result<(String, String, BuildConfig), St
             "-o" => {
                 if i + 1 >= args.len() {
                     return Err(format!(
-                        "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+                        "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>] [--emit=deps|cfg-dot] [--emit-asm]",
                         args[0]
                     ));
                 }
@@ -1277,7 +1472,7 @@ fn parse_build_args(args: &[String]) -> Result<(String, String, BuildConfig), St
             "--target" | "--backend" => {
                 if i + 1 >= args.len() {
                     return Err(format!(
-                        "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+                        "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>] [--emit=deps|cfg-dot] [--emit-asm]",
                         args[0]
                     ));
                 }
@@ -1293,16 +1488,72 @@ fn parse_build_args(args: &[String]) -> Result<(String, String, BuildConfig), St
             "--gpu" => {
                 if i + 1 >= args.len() {
                     return Err(format!(
-                        "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+                        "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>] [--emit=deps|cfg-dot] [--emit-asm]",
                         args[0]
                     ));
                 }
                 config.gpu_arch = Some(args[i + 1].clone());
                 i += 2;
             }
+            "--feature" => {
+                if i + 1 >= args.len() {
+                    return Err(format!(
+                        "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>] [--emit=deps|cfg-dot] [--emit-asm]",
+                        args[0]
+                    ));
+                }
+                config.features.push(args[i + 1].clone());
+                i += 2;
+            }
+            flag if flag.starts_with("--max-errors=") => {
+                config.max_errors = parse_max_errors_flag(flag)?;
+                i += 1;
+            }
+            flag if flag.starts_with("--color=") => {
+                config.color = parse_color_flag(flag)?;
+                i += 1;
+            }
+            flag if flag.starts_with("--opt-level=") => {
+                config.opt_level = parse_opt_level_flag(flag)?;
+                i += 1;
+            }
+            flag if flag.starts_with("--panic=") => {
+                config.panic = parse_panic_flag(flag)?;
+                i += 1;
+            }
+            "--emit=deps" => {
+                config.emit_deps = true;
+                i += 1;
+            }
+            "--emit=cfg-dot" => {
+                config.emit_cfg_dot = true;
+                i += 1;
+            }
+            "--emit-asm" => {
+                config.emit_asm = true;
+                i += 1;
+            }
+            flag if flag.starts_with("--emit=") => {
+                return Err(format!(
+                    "error: unsupported emit kind `{}` (expected deps or cfg-dot)",
+                    &flag["--emit=".len()..]
+                ));
+            }
+            "--verify" => {
+                config.verify = true;
+                i += 1;
+            }
+            "--stats" => {
+                config.stats = true;
+                i += 1;
+            }
+            flag if flag.starts_with("--dump-ir-after=") => {
+                config.dump_ir_after.extend(parse_dump_ir_after_flag(flag)?);
+                i += 1;
+            }
             _ => {
                 return Err(format!(
-                    "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+                    "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>] [--emit=deps|cfg-dot] [--emit-asm]",
                     args[0]
                 ));
             }
@@ -1311,7 +1562,7 @@ fn parse_build_args(args: &[String]) -> Result<(String, String, BuildConfig), St
 
     let Some(output_file) = output_file else {
         return Err(format!(
-            "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+            "Usage: {} build <input.aero> -o <output.ll> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>] [--emit=deps|cfg-dot] [--emit-asm]",
             args[0]
         ));
     };
@@ -1322,7 +1573,7 @@ fn parse_build_args(args: &[String]) -> Result<(String, String, BuildConfig), St
 fn parse_run_args(args: &[String]) -> Result<(String, BuildConfig), String> {
     if args.len() < 3 {
         return Err(format!(
-            "Usage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]\n       {} run --target rocm --gpu gfx1101 <input.aero>",
+            "Usage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>]\n       {} run --target rocm --gpu gfx1101 <input.aero>",
             args[0], args[0]
         ));
     }
@@ -1336,7 +1587,7 @@ fn parse_run_args(args: &[String]) -> Result<(String, BuildConfig), String> {
             "--target" | "--backend" => {
                 if i + 1 >= args.len() {
                     return Err(format!(
-                        "Usage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+                        "Usage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>]",
                         args[0]
                     ));
                 }
@@ -1352,16 +1603,54 @@ fn parse_run_args(args: &[String]) -> Result<(String, BuildConfig), String> {
             "--gpu" => {
                 if i + 1 >= args.len() {
                     return Err(format!(
-                        "Usage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+                        "Usage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>]",
                         args[0]
                     ));
                 }
                 config.gpu_arch = Some(args[i + 1].clone());
                 i += 2;
             }
+            "--feature" => {
+                if i + 1 >= args.len() {
+                    return Err(format!(
+                        "Usage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>]",
+                        args[0]
+                    ));
+                }
+                config.features.push(args[i + 1].clone());
+                i += 2;
+            }
+            flag if flag.starts_with("--max-errors=") => {
+                config.max_errors = parse_max_errors_flag(flag)?;
+                i += 1;
+            }
+            flag if flag.starts_with("--color=") => {
+                config.color = parse_color_flag(flag)?;
+                i += 1;
+            }
+            flag if flag.starts_with("--opt-level=") => {
+                config.opt_level = parse_opt_level_flag(flag)?;
+                i += 1;
+            }
+            flag if flag.starts_with("--panic=") => {
+                config.panic = parse_panic_flag(flag)?;
+                i += 1;
+            }
+            "--verify" => {
+                config.verify = true;
+                i += 1;
+            }
+            "--stats" => {
+                config.stats = true;
+                i += 1;
+            }
+            flag if flag.starts_with("--dump-ir-after=") => {
+                config.dump_ir_after.extend(parse_dump_ir_after_flag(flag)?);
+                i += 1;
+            }
             value if value.starts_with('-') => {
                 return Err(format!(
-                    "error: unknown option `{}`\nUsage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+                    "error: unknown option `{}`\nUsage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>]",
                     value, args[0]
                 ));
             }
@@ -1369,7 +1658,7 @@ fn parse_run_args(args: &[String]) -> Result<(String, BuildConfig), String> {
                 if input_file.is_some() {
                     let existing = input_file.as_deref().unwrap_or("<unknown>");
                     return Err(format!(
-                        "error: multiple input files provided (`{}` and `{}`)\nUsage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]",
+                        "error: multiple input files provided (`{}` and `{}`)\nUsage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>]",
                         existing, value, args[0]
                     ));
                 }
@@ -1381,7 +1670,7 @@ fn parse_run_args(args: &[String]) -> Result<(String, BuildConfig), String> {
 
     let Some(input_file) = input_file else {
         return Err(format!(
-            "Usage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]\n       {} run --target rocm --gpu gfx1101 <input.aero>",
+            "Usage: {} run <input.aero> [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>]\n       {} run --target rocm --gpu gfx1101 <input.aero>",
             args[0], args[0]
         ));
     };
@@ -1447,10 +1736,14 @@ fn compile_to_llvm_ir(
     let source_hash = format!(
         "{:x}",
         md5::compute(format!(
-            "{}::target={}::gpu={}",
+            "{}::target={}::gpu={}::features={}::test={}::panic={:?}::cfg_dot={}",
             source_code,
             build_config.target.as_str(),
-            build_config.gpu_arch_or_default()
+            build_config.gpu_arch_or_default(),
+            build_config.features.join(","),
+            build_config.test.as_deref().unwrap_or(""),
+            build_config.panic,
+            build_config.emit_cfg_dot
         ))
     );
 
@@ -1472,13 +1765,24 @@ fn compile_to_llvm_ir(
 
     // Lexing with performance timing
     let lexing_start = Instant::now();
-    let tokens = lexer::tokenize(source_code);
+    let tokens = lexer::tokenize_with_locations(source_code, Some(input_file.to_string()));
     let lexing_time = lexing_start.elapsed();
     println!("Lexing completed in {:?}", lexing_time);
 
     // Optimized parsing with parser optimizer
     let parsing_start = Instant::now();
-    let mut ast = parser::parse(tokens);
+    let Some(ast) = parse_or_report(
+        tokens,
+        build_config.max_errors,
+        build_config.color.enabled(),
+    ) else {
+        return;
+    };
+    let ast = match &build_config.test {
+        Some(test) => test_harness::harness_program(ast, test),
+        None => ast,
+    };
+    let mut ast = cfg::strip_inactive_items(ast, &build_config.features);
 
     // Apply parser optimizations for complex constructs
     let parser_optimizer = perf_optimizer.get_parser_optimizer();
@@ -1505,7 +1809,7 @@ fn compile_to_llvm_ir(
                     );
                     let mod_tokens = lexer::tokenize(&resolved.source);
                     let mod_ast = parser::parse(mod_tokens);
-                    module_asts.extend(mod_ast);
+                    module_asts.extend(cfg::strip_inactive_items(mod_ast, &build_config.features));
                 }
                 Err(err) => {
                     eprintln!("\x1b[1;31merror\x1b[0m: {}", err);
@@ -1543,6 +1847,36 @@ fn compile_to_llvm_ir(
     let ir_start = Instant::now();
     let mut ir_gen = IrGenerator::new();
     let mut ir = ir_gen.generate_ir(analyzed_ast);
+    if build_config.stats {
+        println!("IR stats:\n{}", ir_stats::IrStats::collect(&ir));
+    }
+    if build_config.dump_ir_after.is_empty() {
+        ir_optimizer::optimize(&mut ir, build_config.opt_level);
+    } else {
+        if build_config.opt_level == 0 {
+            eprintln!("note: --dump-ir-after has nothing to show at --opt-level=0");
+        }
+        ir_optimizer::optimize_with(&mut ir, build_config.opt_level, |pass, function| {
+            if build_config.dump_ir_after.contains(&pass) {
+                eprintln!("; IR after {}\n{}", pass.name(), function);
+            }
+        });
+    }
+    if build_config.stats && build_config.opt_level > 0 {
+        println!(
+            "IR stats after optimization (-O{}):\n{}",
+            build_config.opt_level,
+            ir_stats::IrStats::collect(&ir)
+        );
+    }
+
+    if build_config.emit_cfg_dot {
+        match fs::write(output_file, ir_graph::render_dot(&ir)) {
+            Ok(_) => println!("Control flow graph written to {}", output_file),
+            Err(err) => eprintln!("Error writing control flow graph: {}", err),
+        }
+        return;
+    }
 
     // Apply function call optimizations
     let function_optimizer = perf_optimizer.get_function_optimizer();
@@ -1558,7 +1892,7 @@ fn compile_to_llvm_ir(
     let control_flow_optimizer = perf_optimizer.get_control_flow_optimizer();
     // Note: In a real implementation, we would optimize control flow generation here
 
-    let llvm_ir = code_generator::generate_code(ir);
+    let llvm_ir = CodeGenerator::with_panic_strategy(build_config.panic).generate_code(ir);
     let graph_compile_start = Instant::now();
     let graph_backend =
         AcceleratorBackend::from_env("AERO_ACCELERATOR").unwrap_or(match build_config.target {
@@ -1593,6 +1927,19 @@ fn compile_to_llvm_ir(
         graph_report.total_fused_ops
     );
 
+    if build_config.verify {
+        match verify_llvm_ir(&llvm_ir) {
+            Ok(true) => println!("LLVM IR verification passed"),
+            Ok(false) => {
+                println!("Skipping LLVM IR verification: neither opt nor llvm-as was found")
+            }
+            Err(err) => {
+                eprintln!("\x1b[1;31merror\x1b[0m: {}", err);
+                return;
+            }
+        }
+    }
+
     // Cache the compilation result
     perf_optimizer
         .get_compilation_cache()
@@ -1613,6 +1960,40 @@ fn compile_to_llvm_ir(
     println!("Performance-optimized compilation process completed successfully.");
 }
 
+/// Lower the LLVM IR at `ll_path` to native assembly with `llc -filetype=asm`
+/// for the `--target` triple, writing it beside the IR as a `.s` file.
+fn emit_assembly(ll_path: &Path, build_config: &BuildConfig) -> Result<PathBuf, String> {
+    let llc_bin = toolchain::find_llvm_tool("llc").ok_or_else(|| {
+        format!(
+            "Error executing llc: program not found. Make sure LLVM is installed and llc is in your PATH. LLVM IR remains at {}",
+            ll_path.display()
+        )
+    })?;
+
+    let asm_path = ll_path.with_extension("s");
+    let mut llc_args = vec![format!("-mtriple={}", build_config.llvm_target_triple())];
+    if build_config.target != BuildTarget::Cpu {
+        llc_args.push(format!("-mcpu={}", build_config.gpu_arch_or_default()));
+    }
+    llc_args.extend(build_config.llc_opt_flag());
+    llc_args.push("-filetype=asm".to_string());
+    llc_args.push(ll_path.to_string_lossy().to_string());
+    llc_args.push("-o".to_string());
+    llc_args.push(asm_path.to_string_lossy().to_string());
+
+    let llc_output = Command::new(&llc_bin)
+        .args(&llc_args)
+        .output()
+        .map_err(|err| format!("Error executing llc ({}): {}", llc_bin, err))?;
+    if !llc_output.status.success() {
+        return Err(format!(
+            "Error running llc: {}",
+            String::from_utf8_lossy(&llc_output.stderr)
+        ));
+    }
+    Ok(asm_path)
+}
+
 fn run_aero_program(
     source_code: &str,
     input_file: &str,
@@ -1620,7 +2001,6 @@ fn run_aero_program(
 ) -> Result<(), String> {
     let artifacts = create_run_artifact_paths(input_file, build_config)?;
     let ll_path = artifacts.ll_file.to_string_lossy().to_string();
-    let obj_path = artifacts.obj_file.to_string_lossy().to_string();
     let exe_path = artifacts.exe_file.to_string_lossy().to_string();
     let gpu_obj_path = artifacts.gpu_obj_file.to_string_lossy().to_string();
 
@@ -1635,53 +2015,7 @@ fn run_aero_program(
 
     match build_config.target {
         BuildTarget::Cpu => {
-            let clang_bin = find_llvm_tool("clang").ok_or_else(|| {
-                "Error executing clang: program not found. Make sure LLVM/clang is installed and in your PATH."
-                    .to_string()
-            })?;
-
-            if let Some(llc_bin) = find_llvm_tool("llc") {
-                let llc_output = Command::new(&llc_bin)
-                    .args(["-filetype=obj", &ll_path, "-o", &obj_path])
-                    .output()
-                    .map_err(|err| format!("Error executing llc ({}): {}", llc_bin, err))?;
-
-                if !llc_output.status.success() {
-                    return Err(format!(
-                        "Error running llc: {}",
-                        String::from_utf8_lossy(&llc_output.stderr)
-                    ));
-                }
-
-                let clang_output = Command::new(&clang_bin)
-                    .args([&obj_path, "-o", &exe_path])
-                    .output()
-                    .map_err(|err| format!("Error executing clang ({}): {}", clang_bin, err))?;
-
-                if !clang_output.status.success() {
-                    return Err(format!(
-                        "Error running clang: {}",
-                        String::from_utf8_lossy(&clang_output.stderr)
-                    ));
-                }
-            } else {
-                // Fallback path: clang can compile textual LLVM IR directly.
-                println!(
-                    "llc not found in PATH. Falling back to direct clang LLVM IR compilation."
-                );
-
-                let clang_output = Command::new(&clang_bin)
-                    .args([&ll_path, "-o", &exe_path])
-                    .output()
-                    .map_err(|err| format!("Error executing clang ({}): {}", clang_bin, err))?;
-
-                if !clang_output.status.success() {
-                    return Err(format!(
-                        "Error running clang on LLVM IR fallback path: {}",
-                        String::from_utf8_lossy(&clang_output.stderr)
-                    ));
-                }
-            }
+            link_cpu_executable(&artifacts, build_config)?;
 
             let run_output = Command::new(&exe_path)
                 .output()
@@ -1710,7 +2044,7 @@ fn run_aero_program(
             exit(exit_code);
         }
         BuildTarget::Rocm => {
-            let llc_bin = find_llvm_tool("llc").ok_or_else(|| {
+            let llc_bin = toolchain::find_llvm_tool("llc").ok_or_else(|| {
                 format!(
                     "Error executing llc for ROCm target: program not found. Make sure LLVM is installed and llc is in your PATH. LLVM IR remains at {}",
                     artifacts.ll_file.display()
@@ -1729,6 +2063,7 @@ fn run_aero_program(
                     "-mattr=+wavefrontsize64,+gfx11-insts".to_string(),
                 ]
             });
+            llc_args.extend(build_config.llc_opt_flag());
             llc_args.push("-filetype=obj".to_string());
             llc_args.push(ll_path.clone());
             llc_args.push("-o".to_string());
@@ -1775,35 +2110,159 @@ fn run_aero_program(
     Ok(())
 }
 
-fn find_llvm_tool(tool: &str) -> Option<String> {
-    if Command::new(tool).arg("--version").output().is_ok() {
-        return Some(tool.to_string());
-    }
-
-    if cfg!(windows) {
-        let exe_name = format!("{}.exe", tool);
-        let mut candidates = vec![PathBuf::from(r"C:\Program Files\LLVM\bin").join(&exe_name)];
-
-        // Local source-built LLVM fallback used by this repository.
-        if let Ok(repo_root) = env::current_dir() {
-            candidates.push(
-                repo_root
-                    .join("third_party")
-                    .join("llvm-project")
-                    .join("build-rocm-tools")
-                    .join("Release")
-                    .join("bin")
-                    .join(&exe_name),
-            );
-        }
+/// Lower the LLVM IR in `artifacts` to a native executable with llc and
+/// clang, or with clang alone when llc is missing.
+fn link_cpu_executable(
+    artifacts: &RunArtifactPaths,
+    build_config: &BuildConfig,
+) -> Result<(), String> {
+    let used_llc = toolchain::link_executable(
+        &artifacts.ll_file,
+        &artifacts.obj_file,
+        &artifacts.exe_file,
+        build_config.opt_level,
+    )?;
+    if !used_llc {
+        println!("llc not found in PATH. Falling back to direct clang LLVM IR compilation.");
+    }
+    Ok(())
+}
+
+/// `aero test <file>`: build and run each `@test` function of the file as a
+/// program of its own. A test passes when its process exits with 0; a failed
+/// assertion traps instead. Returns the number of failed tests.
+fn run_aero_tests(
+    source_code: &str,
+    input_file: &str,
+    build_config: &BuildConfig,
+) -> Result<usize, String> {
+    if build_config.target != BuildTarget::Cpu {
+        return Err("`aero test` runs tests on the cpu target only".to_string());
+    }
+    let tests = test_harness::test_names(&parser::parse(lexer::tokenize(source_code)));
+    if tests.is_empty() {
+        println!(
+            "\x1b[1;33mwarning\x1b[0m: no @test functions found in {}",
+            input_file
+        );
+        return Ok(0);
+    }
 
-        for candidate in candidates {
-            if candidate.exists() {
-                return Some(candidate.to_string_lossy().into_owned());
+    let mut failed = Vec::new();
+    for test in &tests {
+        let test_config = BuildConfig {
+            test: Some(test.clone()),
+            ..build_config.clone()
+        };
+        let artifacts = create_run_artifact_paths(input_file, &test_config)?;
+        let ll_path = artifacts.ll_file.to_string_lossy().to_string();
+        compile_to_llvm_ir(source_code, &ll_path, input_file, &test_config);
+        let outcome = if artifacts.ll_file.exists() {
+            link_cpu_executable(&artifacts, &test_config).and_then(|()| {
+                Command::new(&artifacts.exe_file)
+                    .status()
+                    .map_err(|err| format!("Error executing test {}: {}", test, err))
+            })
+        } else {
+            Err("test did not compile".to_string())
+        };
+        match outcome {
+            Ok(status) if status.success() => println!("test {} ... \x1b[1;32mok\x1b[0m", test),
+            Ok(status) => {
+                println!("test {} ... \x1b[1;31mFAILED\x1b[0m ({})", test, status);
+                failed.push(test);
+            }
+            Err(err) => {
+                println!("test {} ... \x1b[1;31mFAILED\x1b[0m: {}", test, err);
+                failed.push(test);
             }
         }
+
+        let _ = fs::remove_file(&artifacts.ll_file);
+        let _ = fs::remove_file(&artifacts.obj_file);
+        let _ = fs::remove_file(&artifacts.exe_file);
+        let _ = fs::remove_dir(&artifacts.directory);
     }
 
+    println!(
+        "\n\x1b[1mtest result\x1b[0m: {} passed, {} failed, {} total",
+        tests.len() - failed.len(),
+        failed.len(),
+        tests.len()
+    );
+    Ok(failed.len())
+}
+
+/// Run the LLVM verifier over `llvm_ir` with `opt`, or `llvm-as` when `opt` is
+/// missing. Returns `Ok(false)` if neither tool is installed.
+fn verify_llvm_ir(llvm_ir: &str) -> Result<bool, String> {
+    let null_device = if cfg!(windows) { "NUL" } else { "/dev/null" };
+    let (tool, args) = if let Some(opt_bin) = toolchain::find_llvm_tool("opt") {
+        (opt_bin, vec!["-passes=verify", "-disable-output", "-"])
+    } else if let Some(as_bin) = toolchain::find_llvm_tool("llvm-as") {
+        (as_bin, vec!["-o", null_device, "-"])
+    } else {
+        return Ok(false);
+    };
+
+    let mut child = Command::new(&tool)
+        .args(&args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Error executing {}: {}", tool, err))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(llvm_ir.as_bytes())
+            .map_err(|err| format!("Error writing LLVM IR to {}: {}", tool, err))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Error executing {}: {}", tool, err))?;
+    if output.status.success() {
+        return Ok(true);
+    }
+
+    let report = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    match verifier_error_function(llvm_ir, &report) {
+        Some(function) => Err(format!(
+            "LLVM IR verification failed in function `@{}`:\n{}",
+            function, report
+        )),
+        None => Err(format!("LLVM IR verification failed:\n{}", report)),
+    }
+}
+
+/// Find the function a verifier report points at, either through a
+/// `<stdin>:LINE:` location or by an instruction it quotes.
+fn verifier_error_function(llvm_ir: &str, report: &str) -> Option<String> {
+    let ir_lines: Vec<&str> = llvm_ir.lines().collect();
+    let reported_line = report.lines().find_map(|line| {
+        let rest = &line[line.find("<stdin>:")? + "<stdin>:".len()..];
+        let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse::<usize>().ok().filter(|&n| n > 0)
+    });
+    let line_index = reported_line.map(|n| n - 1).or_else(|| {
+        report.lines().find_map(|quoted| {
+            let quoted = quoted.trim();
+            if quoted.is_empty() {
+                return None;
+            }
+            ir_lines.iter().position(|line| line.trim() == quoted)
+        })
+    })?;
+
+    for line in ir_lines.get(..=line_index)?.iter().rev() {
+        if line.starts_with('}') {
+            return None;
+        }
+        if let Some(header) = line.strip_prefix("define ") {
+            let start = header.find('@')? + 1;
+            let end = start + header[start..].find('(')?;
+            return Some(header[start..end].to_string());
+        }
+    }
     None
 }
 
@@ -1815,13 +2274,17 @@ fn print_help(program_name: &str) {
     println!();
     println!("COMMANDS:");
     println!(
-        "    build <input.aero> -o <output.ll>    Compile Aero source to LLVM IR [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]"
+        "    build [<input.aero>] -o <output.ll>  Compile Aero source to LLVM IR [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>] [--emit=deps|cfg-dot] [--emit-asm]"
+    );
+    println!(
+        "    run [<input.aero>]                   Compile and run source [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>] [--max-errors=N] [--color=auto|always|never] [--opt-level=N] [--feature <name>] [--panic=abort|exit] [--verify] [--stats] [--dump-ir-after=<pass>]"
+    );
+    println!(
+        "    check <input.aero>                   Type-check only (no codegen) [--max-errors=N] [--warn-shadow[=all]] [--feature <name>]"
     );
     println!(
-        "    run <input.aero>                     Compile and run source [--target <cpu|rocm|cuda|gpu>] [--gpu <arch>]"
+        "    test [<input.aero>]                  Run the file's @test functions, or discover and check *_test.aero files"
     );
-    println!("    check <input.aero>                   Type-check only (no codegen)");
-    println!("    test                                 Discover and run *_test.aero files");
     println!("    fmt <input.aero>                     Auto-format Aero source");
     println!("    doc <input.aero> [-o <output.md>]    Generate Markdown API docs from source");
     println!("    profile <input.aero> [-o <trace.json>] Profile compilation phases");
@@ -1839,6 +2302,9 @@ fn print_help(program_name: &str) {
         "    conformance [-o <report.json>]       Run formal conformance and mechanized checks"
     );
     println!("    init [path]                          Initialize a new Aero project");
+    println!(
+        "                                         (build/run without <input.aero> use ./aero.toml)"
+    );
     println!("    lsp                                  Run Aero language server (stdio)");
     println!();
     println!("OPTIONS:");
@@ -1922,19 +2388,45 @@ fn print_registry_help(program_name: &str) {
 
 /// Type-check an Aero program without generating code.
 /// Runs lexer → parser → semantic analysis only.
-fn check_aero_program(source_code: &str, input_file: &str) {
+fn check_aero_program(
+    source_code: &str,
+    input_file: &str,
+    max_errors: usize,
+    color: errors::ColorChoice,
+    shadow_warnings: ShadowWarnings,
+    features: &[String],
+) {
     let check_start = Instant::now();
 
     // Lexing
-    let tokens = lexer::tokenize(source_code);
+    let tokens = lexer::tokenize_with_locations(source_code, Some(input_file.to_string()));
+    let let_names = let_declaration_locations(&tokens);
+    let loops = loop_keyword_locations(&tokens);
 
     // Parsing
-    let ast = parser::parse(tokens);
+    let color = color.enabled();
+    let Some(ast) = parse_or_report(tokens, max_errors, color) else {
+        std::process::exit(1);
+    };
+    let ast = cfg::strip_inactive_items(ast, features);
 
     // Semantic analysis
-    let mut analyzer = SemanticAnalyzer::new();
+    let mut analyzer = SemanticAnalyzer::new().with_shadow_warnings(shadow_warnings);
     match analyzer.analyze(ast) {
         Ok((msg, _typed_ast)) => {
+            for warning in analyzer.shadow_warnings() {
+                let diagnostic = shadow_diagnostic(warning, &let_names);
+                eprint!("{}", errors::render(&diagnostic, source_code, color));
+            }
+            for &index in analyzer.infinite_loops() {
+                let diagnostic = errors::CompilerError::InfiniteLoop {
+                    location: loops
+                        .get(index)
+                        .cloned()
+                        .unwrap_or_else(errors::SourceLocation::unknown),
+                };
+                eprint!("{}", errors::render(&diagnostic, source_code, color));
+            }
             let elapsed = check_start.elapsed();
             println!(
                 "\x1b[1;32m    Checking\x1b[0m {} ... \x1b[1;32mok\x1b[0m ({:?})",
@@ -1973,6 +2465,146 @@ fn check_aero_program(source_code: &str, input_file: &str) {
     }
 }
 
+/// Where each `let [mut] name` names its binding, per name in source order.
+fn let_declaration_locations(
+    tokens: &[lexer::LocatedToken],
+) -> HashMap<String, Vec<errors::SourceLocation>> {
+    let mut locations: HashMap<String, Vec<errors::SourceLocation>> = HashMap::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if token.token != lexer::Token::Let {
+            continue;
+        }
+        let name = match tokens.get(i + 1).map(|t| &t.token) {
+            Some(lexer::Token::Mut) => tokens.get(i + 2),
+            _ => tokens.get(i + 1),
+        };
+        if let Some(lexer::LocatedToken {
+            token: lexer::Token::Identifier(name),
+            location,
+        }) = name
+        {
+            locations
+                .entry(name.clone())
+                .or_default()
+                .push(location.clone());
+        }
+    }
+    locations
+}
+
+/// Where each `loop` keyword is, in source order.
+fn loop_keyword_locations(tokens: &[lexer::LocatedToken]) -> Vec<errors::SourceLocation> {
+    tokens
+        .iter()
+        .filter(|token| token.token == lexer::Token::Loop)
+        .map(|token| token.location.clone())
+        .collect()
+}
+
+/// The `--warn-shadow` diagnostic for `warning`, pointing at the shadowing
+/// `let` and noting the declaration it shadows.
+fn shadow_diagnostic(
+    warning: &ShadowWarning,
+    let_names: &HashMap<String, Vec<errors::SourceLocation>>,
+) -> errors::CompilerError {
+    let declarations = let_names.get(&warning.name);
+    let declared_at = |index: usize| declarations.and_then(|locations| locations.get(index));
+    errors::CompilerError::ShadowedBinding {
+        name: warning.name.clone(),
+        previous_type: warning.previous_type.clone(),
+        new_type: warning.new_type.clone(),
+        location: declared_at(warning.declaration)
+            .cloned()
+            .unwrap_or_else(errors::SourceLocation::unknown),
+        previous_location: warning.previous_declaration.and_then(declared_at).cloned(),
+    }
+}
+
+/// Parse `tokens`, printing at most `max_errors` parse errors plus a summary
+/// if it fails.
+fn parse_or_report(
+    tokens: Vec<lexer::LocatedToken>,
+    max_errors: usize,
+    color: bool,
+) -> Option<Vec<ast::AstNode>> {
+    match parser::parse_with_locations(tokens) {
+        Ok(ast) => Some(ast),
+        Err(err) => {
+            eprint!(
+                "{}",
+                errors::render_diagnostics(&err.diagnostics(), max_errors, color)
+            );
+            None
+        }
+    }
+}
+
+/// Parse the value of a `--max-errors=N` flag.
+fn parse_max_errors_flag(flag: &str) -> Result<usize, String> {
+    let value = flag
+        .strip_prefix("--max-errors=")
+        .ok_or_else(|| format!("error: unexpected argument `{}`", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("error: invalid value `{}` for --max-errors", value))
+}
+
+fn parse_opt_level_flag(flag: &str) -> Result<u8, String> {
+    let value = flag
+        .strip_prefix("--opt-level=")
+        .ok_or_else(|| format!("error: unexpected argument `{}`", flag))?;
+    match value.parse() {
+        Ok(level @ 0..=2) => Ok(level),
+        _ => Err(format!(
+            "error: invalid value `{}` for --opt-level (expected 0, 1 or 2)",
+            value
+        )),
+    }
+}
+
+/// Parse the value of a `--dump-ir-after=<pass>[,<pass>...]` flag.
+fn parse_dump_ir_after_flag(flag: &str) -> Result<Vec<ir_optimizer::IrPass>, String> {
+    let value = flag
+        .strip_prefix("--dump-ir-after=")
+        .ok_or_else(|| format!("error: unexpected argument `{}`", flag))?;
+    value
+        .split(',')
+        .map(|name| {
+            ir_optimizer::IrPass::parse(name).ok_or_else(|| {
+                format!(
+                    "error: unknown pass `{}` for --dump-ir-after (expected const-fold, dce or licp)",
+                    name
+                )
+            })
+        })
+        .collect()
+}
+
+/// Parse the value of a `--color=auto|always|never` flag.
+fn parse_color_flag(flag: &str) -> Result<errors::ColorChoice, String> {
+    let value = flag
+        .strip_prefix("--color=")
+        .ok_or_else(|| format!("error: unexpected argument `{}`", flag))?;
+    errors::ColorChoice::parse(value).ok_or_else(|| {
+        format!(
+            "error: invalid value `{}` for --color (expected auto, always or never)",
+            value
+        )
+    })
+}
+
+fn parse_panic_flag(flag: &str) -> Result<PanicStrategy, String> {
+    let value = flag
+        .strip_prefix("--panic=")
+        .ok_or_else(|| format!("error: unexpected argument `{}`", flag))?;
+    PanicStrategy::parse(value).ok_or_else(|| {
+        format!(
+            "error: invalid value `{}` for --panic (expected abort or exit)",
+            value
+        )
+    })
+}
+
 /// Attempt to extract a line number from a compiler error message
 fn extract_error_line(error_msg: &str) -> Option<usize> {
     // Look for patterns like "line 5" or "at line 5" or ":5:"
@@ -2030,6 +2662,200 @@ mod tests {
         assert_eq!(config.target, BuildTarget::Rocm);
     }
 
+    #[test]
+    fn parse_build_args_accepts_max_errors() {
+        let args = vec![
+            "aero".to_string(),
+            "build".to_string(),
+            "main.aero".to_string(),
+            "-o".to_string(),
+            "main.ll".to_string(),
+            "--max-errors=5".to_string(),
+        ];
+        let (_input, _output, config) =
+            parse_build_args(&args).expect("build args should parse with --max-errors");
+        assert_eq!(config.max_errors, 5);
+
+        let bad = vec![
+            "aero".to_string(),
+            "build".to_string(),
+            "main.aero".to_string(),
+            "-o".to_string(),
+            "main.ll".to_string(),
+            "--max-errors=many".to_string(),
+        ];
+        assert!(parse_build_args(&bad).is_err());
+    }
+
+    #[test]
+    fn parse_build_args_accepts_opt_level() {
+        let args = vec![
+            "aero".to_string(),
+            "build".to_string(),
+            "main.aero".to_string(),
+            "-o".to_string(),
+            "main.ll".to_string(),
+            "--opt-level=2".to_string(),
+        ];
+        let (_input, _output, config) =
+            parse_build_args(&args).expect("build args should parse with --opt-level");
+        assert_eq!(config.opt_level, 2);
+        assert_eq!(config.llc_opt_flag().as_deref(), Some("-O2"));
+        assert_eq!(BuildConfig::default().llc_opt_flag(), None);
+
+        let bad = vec![
+            "aero".to_string(),
+            "build".to_string(),
+            "main.aero".to_string(),
+            "-o".to_string(),
+            "main.ll".to_string(),
+            "--opt-level=3".to_string(),
+        ];
+        assert!(parse_build_args(&bad).is_err());
+    }
+
+    #[test]
+    fn parse_build_args_accepts_dump_ir_after() {
+        let args = [
+            "aero",
+            "build",
+            "main.aero",
+            "-o",
+            "main.ll",
+            "--dump-ir-after=const-fold,dce",
+            "--dump-ir-after=licp",
+        ]
+        .map(String::from);
+        let (_input, _output, config) =
+            parse_build_args(&args).expect("build args should parse with --dump-ir-after");
+        assert_eq!(
+            config.dump_ir_after,
+            [
+                ir_optimizer::IrPass::ConstFold,
+                ir_optimizer::IrPass::Dce,
+                ir_optimizer::IrPass::Licp
+            ]
+        );
+
+        let bad = ["aero", "run", "main.aero", "--dump-ir-after=inline"].map(String::from);
+        assert!(parse_run_args(&bad).is_err());
+    }
+
+    #[test]
+    fn parse_run_args_accepts_panic_strategy() {
+        let args = ["aero", "run", "main.aero", "--panic=exit"].map(String::from);
+        let (_input, config) = parse_run_args(&args).expect("run args should parse with --panic");
+        assert_eq!(config.panic, PanicStrategy::Exit);
+
+        let bad = ["aero", "run", "main.aero", "--panic=unwind"].map(String::from);
+        assert!(parse_run_args(&bad).is_err());
+    }
+
+    #[test]
+    fn parse_build_args_accepts_verify() {
+        let args = vec![
+            "aero".to_string(),
+            "build".to_string(),
+            "main.aero".to_string(),
+            "-o".to_string(),
+            "main.ll".to_string(),
+            "--verify".to_string(),
+        ];
+        let (_input, _output, config) =
+            parse_build_args(&args).expect("build args should parse with --verify");
+        assert!(config.verify);
+        assert!(!BuildConfig::default().verify);
+    }
+
+    #[test]
+    fn parse_build_and_run_args_accept_stats() {
+        let build = ["aero", "build", "main.aero", "-o", "main.ll", "--stats"].map(String::from);
+        let (_input, _output, config) =
+            parse_build_args(&build).expect("build args should parse with --stats");
+        assert!(config.stats);
+        let run = ["aero", "run", "main.aero", "--stats"].map(String::from);
+        let (_input, config) = parse_run_args(&run).expect("run args should parse with --stats");
+        assert!(config.stats);
+        assert!(!BuildConfig::default().stats);
+    }
+
+    #[test]
+    fn parse_build_args_accepts_emit_cfg_dot() {
+        let args = [
+            "aero",
+            "build",
+            "main.aero",
+            "-o",
+            "main.dot",
+            "--emit=cfg-dot",
+        ]
+        .map(String::from);
+        let (_input, _output, config) =
+            parse_build_args(&args).expect("build args should parse with --emit=cfg-dot");
+        assert!(config.emit_cfg_dot);
+        assert!(!config.emit_deps);
+    }
+
+    #[test]
+    fn parse_build_args_accepts_emit_asm() {
+        let args = vec![
+            "aero".to_string(),
+            "build".to_string(),
+            "main.aero".to_string(),
+            "-o".to_string(),
+            "main.ll".to_string(),
+            "--emit-asm".to_string(),
+        ];
+        let (_input, _output, config) =
+            parse_build_args(&args).expect("build args should parse with --emit-asm");
+        assert!(config.emit_asm);
+        assert!(!BuildConfig::default().emit_asm);
+    }
+
+    #[test]
+    fn parse_build_args_collects_features() {
+        let args: Vec<String> = [
+            "aero",
+            "build",
+            "main.aero",
+            "--feature",
+            "gpu",
+            "-o",
+            "main.ll",
+            "--feature",
+            "fast",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        let (_input, _output, config) =
+            parse_build_args(&args).expect("build args should parse with --feature");
+        assert_eq!(config.features, ["gpu", "fast"]);
+
+        let missing = vec![
+            "aero".to_string(),
+            "build".to_string(),
+            "main.aero".to_string(),
+            "--feature".to_string(),
+        ];
+        assert!(parse_build_args(&missing).is_err());
+    }
+
+    #[cfg(feature = "llvm-verify")]
+    #[test]
+    fn verifier_rejects_malformed_ir_and_names_function() {
+        let valid = "define i32 @main() {\nentry:\n  ret i32 0\n}\n";
+        assert_eq!(verify_llvm_ir(valid), Ok(true));
+
+        let malformed = format!(
+            "{}\ndefine i32 @broken() {{\nentry:\n  %x = add i32 %y, 1\n  ret i32 %x\n}}\n",
+            valid
+        );
+        let err = verify_llvm_ir(&malformed).expect_err("malformed IR must be rejected");
+        assert!(err.contains("in function `@broken`"), "{}", err);
+        assert!(err.contains("use of undefined value '%y'"), "{}", err);
+    }
+
     #[test]
     fn parse_run_args_supports_option_first_style() {
         let args = vec![
@@ -2084,6 +2910,7 @@ mod tests {
         let config = BuildConfig {
             target: BuildTarget::Rocm,
             gpu_arch: Some("gfx1101".to_string()),
+            ..BuildConfig::default()
         };
         let output = retarget_llvm_module(input, &config);
         assert!(output.contains("target triple = \"amdgcn-amd-amdhsa\""));
@@ -2105,6 +2932,7 @@ mod tests {
         let config = BuildConfig {
             target: BuildTarget::Rocm,
             gpu_arch: Some("gfx1101".to_string()),
+            ..BuildConfig::default()
         };
         let artifacts = create_run_artifact_paths("examples/hello.aero", &config)
             .expect("paths should be created");
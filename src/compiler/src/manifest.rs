@@ -0,0 +1,173 @@
+// src/compiler/src/manifest.rs
+
+//! `aero.toml` package manifests.
+//!
+//! Only the subset `aero build`/`run` need is understood: a `[package]` table
+//! with string keys and string arrays, one per line, and `#` comments.
+//!
+//! ```toml
+//! [package]
+//! name = "vision"
+//! root = "src/main.aero"
+//! features = ["gpu"]
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE: &str = "aero.toml";
+pub const DEFAULT_ROOT: &str = "src/main.aero";
+pub const DEFAULT_VERSION: &str = "0.1.0";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    /// Root source file, relative to the manifest's directory.
+    pub root: PathBuf,
+    /// Features enabled for every build of the package.
+    pub features: Vec<String>,
+}
+
+impl Manifest {
+    /// Read `aero.toml` from `dir`.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(MANIFEST_FILE);
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read manifest {}: {}", path.display(), err))?;
+        Self::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut section = String::new();
+        let mut name = None;
+        let mut version = None;
+        let mut root = None;
+        let mut features = Vec::new();
+
+        for (index, raw_line) in text.lines().enumerate() {
+            let line = strip_comment(raw_line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[') {
+                let Some(header) = header.strip_suffix(']') else {
+                    return Err(format!("line {}: expected `]` after table name", index + 1));
+                };
+                section = header.trim().to_string();
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected `key = value`", index + 1));
+            };
+            if section != "package" {
+                continue;
+            }
+            match key.trim() {
+                "name" => name = Some(parse_string(value, index)?),
+                "version" => version = Some(parse_string(value, index)?),
+                "root" => root = Some(PathBuf::from(parse_string(value, index)?)),
+                "features" => features = parse_string_array(value, index)?,
+                _ => {}
+            }
+        }
+
+        let Some(name) = name else {
+            return Err("missing `name` in [package]".to_string());
+        };
+        Ok(Self {
+            name,
+            version: version.unwrap_or_else(|| DEFAULT_VERSION.to_string()),
+            root: root.unwrap_or_else(|| PathBuf::from(DEFAULT_ROOT)),
+            features,
+        })
+    }
+}
+
+/// `line` up to its first `#` outside a quoted string.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (at, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..at],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_string(value: &str, index: usize) -> Result<String, String> {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .map(str::to_string)
+        .ok_or_else(|| format!("line {}: expected a quoted string", index + 1))
+}
+
+fn parse_string_array(value: &str, index: usize) -> Result<Vec<String>, String> {
+    let value = value.trim();
+    let Some(rest) = value.strip_prefix('[') else {
+        return Err(format!("line {}: expected an array of strings", index + 1));
+    };
+    let Some(items) = rest.strip_suffix(']') else {
+        return Err(format!(
+            "line {}: arrays must be written on a single line, e.g. `features = [\"gpu\"]`",
+            index + 1
+        ));
+    };
+    items
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| parse_string(item, index))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_package_root_and_features() {
+        let manifest = Manifest::parse(
+            "# demo\n[package]\nname = \"vision\"\nversion = \"0.1.0\"\nroot = \"src/app.aero\"\nfeatures = [\"gpu\", \"fast\"]\n\n[dependencies]\nname = \"other\"\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.name, "vision");
+        assert_eq!(manifest.version, "0.1.0");
+        assert_eq!(manifest.root, PathBuf::from("src/app.aero"));
+        assert_eq!(manifest.features, ["gpu", "fast"]);
+
+        let defaults = Manifest::parse("[package]\nname = \"vision\"\n").unwrap();
+        assert_eq!(defaults.version, DEFAULT_VERSION);
+        assert_eq!(defaults.root, PathBuf::from(DEFAULT_ROOT));
+        assert!(defaults.features.is_empty());
+
+        let err = Manifest::parse("[package]\nversion = \"0.1.0\"\n").unwrap_err();
+        assert!(err.contains("missing `name`"), "{}", err);
+    }
+
+    #[test]
+    fn parse_ignores_trailing_comments() {
+        let manifest = Manifest::parse(
+            "[package] # the package\nname = \"vision\" # its name\nroot = \"src/#1.aero\"\nfeatures = [\"gpu\"] # on\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.name, "vision");
+        assert_eq!(manifest.root, PathBuf::from("src/#1.aero"));
+        assert_eq!(manifest.features, ["gpu"]);
+    }
+
+    #[test]
+    fn parse_rejects_multi_line_arrays() {
+        let err = Manifest::parse("[package]\nname = \"vision\"\nfeatures = [\n  \"gpu\",\n]\n")
+            .unwrap_err();
+        assert!(
+            err.contains("line 3: arrays must be written on a single line"),
+            "{}",
+            err
+        );
+    }
+}
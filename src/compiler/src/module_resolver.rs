@@ -4,7 +4,9 @@
 ///   - `mod foo;` looks for `foo.aero` in the same directory, or `foo/mod.aero`
 ///
 /// Maintains a registry of resolved modules to detect and prevent circular imports.
-use std::collections::HashMap;
+use crate::ast::{AstNode, Statement};
+use crate::{lexer, parser};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 /// Represents a resolved module with its file path and parsed contents.
@@ -88,6 +90,75 @@ impl ModuleResolver {
     pub fn resolved_modules(&self) -> &HashMap<String, ResolvedModule> {
         &self.resolved
     }
+
+    /// Every `.aero` file `entry_file` depends on, starting with itself,
+    /// following `mod` declarations and `use` paths transitively. Each file
+    /// is listed once; a module that includes itself is reported as a cycle.
+    pub fn dependencies(entry_file: &str) -> Result<Vec<PathBuf>, String> {
+        let mut walk = DependencyWalk::default();
+        walk.visit(Path::new(entry_file))?;
+        Ok(walk.files)
+    }
+}
+
+#[derive(Default)]
+struct DependencyWalk {
+    /// Files in the order they were first reached
+    files: Vec<PathBuf>,
+    seen: HashSet<PathBuf>,
+    /// Files whose dependencies are being visited, as (canonical, displayed)
+    stack: Vec<(PathBuf, PathBuf)>,
+}
+
+impl DependencyWalk {
+    fn visit(&mut self, file: &Path) -> Result<(), String> {
+        let canonical = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        if let Some(start) = self.stack.iter().position(|(c, _)| *c == canonical) {
+            let cycle: Vec<String> = self.stack[start..]
+                .iter()
+                .map(|(_, shown)| shown.as_path())
+                .chain(std::iter::once(file))
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(format!("Module cycle detected: {}", cycle.join(" -> ")));
+        }
+        if !self.seen.insert(canonical.clone()) {
+            return Ok(());
+        }
+        self.files.push(file.to_path_buf());
+
+        let source = std::fs::read_to_string(file)
+            .map_err(|err| format!("Could not read module file `{}`: {}", file.display(), err))?;
+        let mut resolver = ModuleResolver::new(&file.to_string_lossy());
+        self.stack.push((canonical, file.to_path_buf()));
+        for (name, declared) in referenced_modules(&source) {
+            match resolver.resolve(&name) {
+                Ok(module) => self.visit(&module.file_path)?,
+                // `use` also names external paths such as `std::...`.
+                Err(_) if !declared => {}
+                Err(err) => return Err(err),
+            }
+        }
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+/// Module names referenced by `source`, paired with whether they come from a
+/// `mod` declaration (and so must exist) rather than a `use` path.
+fn referenced_modules(source: &str) -> Vec<(String, bool)> {
+    parser::parse(lexer::tokenize(source))
+        .into_iter()
+        .filter_map(|node| match node {
+            AstNode::Statement(Statement::ModDecl { name, .. }) => Some((name, true)),
+            AstNode::Statement(Statement::UseImport { path, .. }) => path
+                .into_iter()
+                .find(|segment| segment != "crate" && segment != "self")
+                .filter(|first| first != "std" && first != "super")
+                .map(|first| (first, false)),
+            _ => None,
+        })
+        .collect()
 }
 
 #[cfg(test)]
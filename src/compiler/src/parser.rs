@@ -1,20 +1,132 @@
 #![allow(clippy::result_large_err)]
 
 use crate::ast::{
-    AstNode, Block, Expression, FieldDecl, MatchArm, Parameter, Pattern, Statement, TraitMethod,
-    Type, VariantDecl, VariantDeclKind,
+    AstNode, Block, CfgPredicate, Expression, FieldDecl, MatchArm, Parameter, Pattern, Statement,
+    TraitMethod, Type, VariantDecl, VariantDeclKind, format_placeholders,
 };
 use crate::errors::{CompilerError, CompilerResult, SourceLocation};
 use crate::lexer::{LocatedToken, Token, tokenize_with_locations};
+use std::ops::Range;
+
+/// How deeply expressions and blocks may nest before parsing stops with an
+/// error instead of overflowing the stack.
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 128;
+
+/// Name of the call `Parser::with_type_probe` wraps its expression in; the
+/// semantic analyzer records the argument's type instead of calling anything.
+pub const TYPE_PROBE: &str = "__type_probe";
+
+/// Loop variable of a `for` whose pattern is bound by a `let` at the top of
+/// the body.
+const FOR_PATTERN_ITEM: &str = "__for_item";
+
+/// An expression together with the tokens it was parsed from.
+#[derive(Debug, Clone)]
+pub struct ExpressionSpan {
+    pub tokens: Range<usize>,
+    pub expression: Expression,
+}
 
 pub struct Parser {
     tokens: Vec<LocatedToken>,
     current: usize,
+    depth: usize,
+    max_depth: usize,
+    /// Every expression parsed so far, when enabled by `with_expression_spans`
+    spans: Option<Vec<ExpressionSpan>>,
+    probe: Option<Range<usize>>,
+    /// Where each top-level item parsed so far starts
+    item_locations: Vec<SourceLocation>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<LocatedToken>) -> Self {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            depth: 0,
+            max_depth: DEFAULT_MAX_NESTING_DEPTH,
+            spans: None,
+            probe: None,
+            item_locations: Vec::new(),
+        }
+    }
+
+    /// Keep the token range of every expression, innermost first, for
+    /// `expression_spans`.
+    pub fn with_expression_spans(mut self) -> Self {
+        self.spans = Some(Vec::new());
+        self
+    }
+
+    /// Wrap the expression parsed from exactly `tokens` in a `TYPE_PROBE`
+    /// call, so analysis reports its type.
+    pub fn with_type_probe(mut self, tokens: Range<usize>) -> Self {
+        self.probe = Some(tokens);
+        self
+    }
+
+    /// Where each top-level item returned by `parse` starts, in order.
+    #[allow(dead_code)] // Used by the library's `phases`, which the binary does not build.
+    pub fn item_locations(&self) -> &[SourceLocation] {
+        &self.item_locations
+    }
+
+    /// Expressions recorded since `with_expression_spans`.
+    pub fn expression_spans(&self) -> &[ExpressionSpan] {
+        self.spans.as_deref().unwrap_or_default()
+    }
+
+    /// Note `expr`, parsed from the tokens since `start`, for
+    /// `expression_spans` and the type probe.
+    fn spanned(&mut self, start: usize, expr: Expression) -> Expression {
+        let tokens = start..self.current;
+        if let Some(spans) = &mut self.spans {
+            spans.push(ExpressionSpan {
+                tokens: tokens.clone(),
+                expression: expr.clone(),
+            });
+        }
+        if self.probe.as_ref() == Some(&tokens) {
+            self.probe = None;
+            return Expression::FunctionCall {
+                name: TYPE_PROBE.to_string(),
+                arguments: vec![expr],
+            };
+        }
+        expr
+    }
+
+    /// Override the nesting limit (`DEFAULT_MAX_NESTING_DEPTH` by default).
+    #[cfg_attr(not(test), allow(dead_code))] // Library API; the binary does not call it.
+    pub fn with_max_nesting_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Run `parse` one nesting level deeper, failing once the limit is hit.
+    fn nested<T>(&mut self, parse: fn(&mut Self) -> CompilerResult<T>) -> CompilerResult<T> {
+        self.nested_by(1, parse)
+    }
+
+    /// Run `parse` `levels` nesting levels deeper. The operand after the
+    /// `n`th operator of a left-associative chain like `a + b + c` ends up
+    /// `n` levels down the tree, so long chains count against the limit too.
+    fn nested_by<T>(
+        &mut self,
+        levels: usize,
+        parse: fn(&mut Self) -> CompilerResult<T>,
+    ) -> CompilerResult<T> {
+        if self.depth + levels > self.max_depth {
+            return Err(CompilerError::InvalidSyntax {
+                message: format!("expression nesting too deep (limit is {})", self.max_depth),
+                location: self.peek().location.clone(),
+            });
+        }
+        self.depth += levels;
+        let result = parse(self);
+        self.depth -= levels;
+        result
     }
 
     pub fn parse(&mut self) -> CompilerResult<Vec<AstNode>> {
@@ -22,8 +134,12 @@ impl Parser {
         let mut errors = Vec::new();
 
         while !self.is_at_end() {
+            let location = self.peek().location.clone();
             match self.parse_statement() {
-                Ok(stmt) => ast_nodes.push(AstNode::Statement(stmt)),
+                Ok(stmt) => {
+                    ast_nodes.push(AstNode::Statement(stmt));
+                    self.item_locations.push(location);
+                }
                 Err(err) => {
                     errors.push(err);
                     // Try to recover by advancing to the next statement
@@ -45,6 +161,7 @@ impl Parser {
         match &self.peek().token {
             Token::Fn => self.parse_function_definition(),
             Token::Let => self.parse_let_statement(),
+            Token::Static => self.parse_static_item(),
             Token::Return => self.parse_return_statement(),
             Token::If => self.parse_if_statement(),
             Token::While => self.parse_while_statement(),
@@ -61,9 +178,20 @@ impl Parser {
             Token::Mod => self.parse_mod_declaration(),
             Token::Use => self.parse_use_import(),
             Token::Pub => self.parse_pub_item(),
+            Token::At => self.parse_cfg_item(),
+            Token::AssertMacro | Token::AssertEqMacro => self.parse_assert_statement(),
+            Token::WriteMacro | Token::WritelnMacro => self.parse_write_statement(),
             _ => {
                 // Try to parse as expression statement
                 let expr = self.parse_expression()?;
+                if self.match_token(&Token::Assign) {
+                    let value = self.parse_expression()?;
+                    self.consume(Token::Semicolon, "Expected ';' after assignment")?;
+                    return Ok(Statement::Assign {
+                        target: expr,
+                        value,
+                    });
+                }
                 self.consume(Token::Semicolon, "Expected ';' after expression")?;
                 Ok(Statement::Expression(expr))
             }
@@ -90,7 +218,7 @@ impl Parser {
         };
 
         // Parse optional generic type parameters: fn name<T: Bound, U>(...)
-        let (type_params, trait_bounds) = self.parse_optional_type_params()?;
+        let (type_params, const_params, trait_bounds) = self.parse_optional_type_params()?;
 
         self.consume(Token::LeftParen, "Expected '(' after function name")?;
 
@@ -194,8 +322,22 @@ impl Parser {
                             self.advance();
                         }
                     }
-                    if !param_bounds.is_empty() {
-                        all_bounds.push((where_param, param_bounds));
+                    // `T: A` inline and `where T: B` give `T` one bound set.
+                    match all_bounds
+                        .iter_mut()
+                        .find(|(param, _)| *param == where_param)
+                    {
+                        Some((_, bounds)) => {
+                            for bound in param_bounds {
+                                if !bounds.contains(&bound) {
+                                    bounds.push(bound);
+                                }
+                            }
+                        }
+                        None if !param_bounds.is_empty() => {
+                            all_bounds.push((where_param, param_bounds));
+                        }
+                        None => {}
                     }
                 }
                 if !self.match_token(&Token::Comma) {
@@ -212,7 +354,9 @@ impl Parser {
             return_type,
             body,
             type_params,
+            const_params,
             trait_bounds: all_bounds,
+            no_inline: false,
         })
     }
 
@@ -221,6 +365,20 @@ impl Parser {
 
         let mutable = self.match_token(&Token::Mut);
 
+        if self.is_let_pattern_start() {
+            // Destructuring let: let [a, b] = expr; let (x, y) = expr;
+            // let Point { x, y } = expr;
+            let pattern = self.parse_pattern()?;
+            self.consume(Token::Assign, "Expected '=' after let pattern")?;
+            let value = self.parse_expression()?;
+            self.consume(Token::Semicolon, "Expected ';' after let statement")?;
+            return Ok(Statement::LetPattern {
+                pattern,
+                mutable,
+                value,
+            });
+        }
+
         let name = match &self.peek().token {
             Token::Identifier(name) => {
                 let name = name.clone();
@@ -258,6 +416,299 @@ impl Parser {
         })
     }
 
+    /// Parse `@cfg(predicate) item`, where the item is a function or type,
+    /// trait, impl, static, module or import declaration, or `@test fn ..`,
+    /// which is a cfg item only `aero test` enables. `@inline(never) fn ..`
+    /// is not a cfg item and yields the function itself.
+    fn parse_cfg_item(&mut self) -> CompilerResult<Statement> {
+        self.consume(Token::At, "Expected '@'")?;
+        let predicate = match &self.peek().token {
+            Token::Identifier(name) if name == "cfg" => {
+                self.advance();
+                self.consume(Token::LeftParen, "Expected '(' after 'cfg'")?;
+                let predicate = self.parse_cfg_predicate()?;
+                self.consume(Token::RightParen, "Expected ')' after cfg predicate")?;
+                predicate
+            }
+            Token::Identifier(name) if name == "test" => {
+                self.advance();
+                if !self.check(&Token::Fn) {
+                    return Err(CompilerError::unexpected_token(
+                        "function after `@test`",
+                        &format!("{:?}", self.peek().token),
+                        self.peek().location.clone(),
+                    ));
+                }
+                CfgPredicate::Test
+            }
+            Token::Identifier(name) if name == "inline" => {
+                self.advance();
+                self.consume(Token::LeftParen, "Expected '(' after 'inline'")?;
+                if !matches!(&self.peek().token, Token::Identifier(hint) if hint == "never") {
+                    return Err(CompilerError::unexpected_token(
+                        "`never` in `@inline(...)`",
+                        &format!("{:?}", self.peek().token),
+                        self.peek().location.clone(),
+                    ));
+                }
+                self.advance();
+                self.consume(Token::RightParen, "Expected ')' after inline hint")?;
+                if !self.check(&Token::Fn) {
+                    return Err(CompilerError::unexpected_token(
+                        "function after `@inline(never)`",
+                        &format!("{:?}", self.peek().token),
+                        self.peek().location.clone(),
+                    ));
+                }
+                let mut function = self.parse_function_definition()?;
+                if let Statement::Function { no_inline, .. } = &mut function {
+                    *no_inline = true;
+                }
+                return Ok(function);
+            }
+            _ => {
+                return Err(CompilerError::unexpected_token(
+                    "attribute name `cfg`, `test` or `inline`",
+                    &format!("{:?}", self.peek().token),
+                    self.peek().location.clone(),
+                ));
+            }
+        };
+
+        let item = match &self.peek().token {
+            Token::Fn => self.parse_function_definition()?,
+            Token::Struct => self.parse_struct_def()?,
+            Token::Enum => self.parse_enum_def()?,
+            Token::Impl => self.parse_impl_block()?,
+            Token::Trait => self.parse_trait_def()?,
+            Token::Static => self.parse_static_item()?,
+            Token::Mod => self.parse_mod_declaration()?,
+            Token::Use => self.parse_use_import()?,
+            Token::Pub => self.parse_pub_item()?,
+            Token::At => self.parse_cfg_item()?,
+            _ => {
+                return Err(CompilerError::unexpected_token(
+                    "item after `@cfg(...)`",
+                    &format!("{:?}", self.peek().token),
+                    self.peek().location.clone(),
+                ));
+            }
+        };
+
+        Ok(Statement::Cfg {
+            predicate,
+            item: Box::new(item),
+        })
+    }
+
+    /// `assert!(cond)` / `assert_eq!(left, right)`, desugared to an `if` that
+    /// panics when the assertion does not hold.
+    fn parse_assert_statement(&mut self) -> CompilerResult<Statement> {
+        let is_eq = self.check(&Token::AssertEqMacro);
+        let location = self.panic_location(self.current);
+        self.advance();
+        self.consume(Token::LeftParen, "Expected '(' after macro name")?;
+        let first = self.parse_expression()?;
+        let (condition, message) = if is_eq {
+            self.consume(Token::Comma, "Expected ',' between assert_eq! operands")?;
+            let right = self.parse_expression()?;
+            let condition = Expression::Comparison {
+                op: crate::ast::ComparisonOp::NotEqual,
+                left: Box::new(first),
+                right: Box::new(right),
+            };
+            (condition, "assertion `left == right` failed")
+        } else {
+            let condition = Expression::Unary {
+                op: crate::ast::UnaryOp::Not,
+                operand: Box::new(first),
+            };
+            (condition, "assertion failed")
+        };
+        self.match_token(&Token::Comma);
+        self.consume(Token::RightParen, "Expected ')' after macro arguments")?;
+        self.consume(Token::Semicolon, "Expected ';' after assertion")?;
+        Ok(Statement::If {
+            condition,
+            then_block: Block {
+                statements: vec![Statement::Expression(Expression::Panic {
+                    message: message.to_string(),
+                    location,
+                })],
+                expression: None,
+            },
+            else_block: None,
+        })
+    }
+
+    /// `write!(buf, "a{}b", x)` / `writeln!(..)`, desugared to appending the
+    /// formatted text to the `String` buffer:
+    /// `buf = buf + "a" + x.to_string() + "b"`.
+    fn parse_write_statement(&mut self) -> CompilerResult<Statement> {
+        let newline = self.check(&Token::WritelnMacro);
+        let location = self.peek().location.clone();
+        self.advance();
+        self.consume(Token::LeftParen, "Expected '(' after macro name")?;
+        let buffer = self.parse_expression()?;
+        self.consume(Token::Comma, "Expected ',' after the buffer to write to")?;
+        let (mut format_string, arguments) = self.parse_format_arguments()?;
+        self.consume(Token::RightParen, "Expected ')' after macro arguments")?;
+        self.consume(Token::Semicolon, "Expected ';' after write")?;
+        if newline {
+            format_string.push('\n');
+        }
+
+        let placeholders = format_placeholders(&format_string);
+        if placeholders.iter().any(|&(_, debug)| debug) {
+            return Err(CompilerError::InvalidSyntax {
+                message: "`{:?}` is not supported when writing to a string".to_string(),
+                location,
+            });
+        }
+        if placeholders.len() != arguments.len() {
+            return Err(CompilerError::InvalidSyntax {
+                message: format!(
+                    "format string has {} placeholders but {} arguments were provided",
+                    placeholders.len(),
+                    arguments.len()
+                ),
+                location,
+            });
+        }
+
+        let mut pieces = Vec::new();
+        let mut last = 0;
+        for ((pos, _), argument) in placeholders.into_iter().zip(arguments) {
+            if pos > last {
+                pieces.push(Expression::StringLiteral(
+                    format_string[last..pos].to_string(),
+                ));
+            }
+            pieces.push(Expression::MethodCall {
+                object: Box::new(argument),
+                method: "to_string".to_string(),
+                arguments: Vec::new(),
+                type_args: Vec::new(),
+            });
+            last = pos + 2;
+        }
+        if last < format_string.len() {
+            pieces.push(Expression::StringLiteral(format_string[last..].to_string()));
+        }
+        let value = pieces
+            .into_iter()
+            .fold(buffer.clone(), |text, piece| Expression::Binary {
+                op: crate::ast::BinaryOp::Add,
+                left: Box::new(text),
+                right: Box::new(piece),
+                ty: None,
+            });
+        Ok(Statement::Assign {
+            target: buffer,
+            value,
+        })
+    }
+
+    /// Parse a cfg predicate: `feature = "x"`, `not(p)`, `all(p, ..)` or `any(p, ..)`.
+    fn parse_cfg_predicate(&mut self) -> CompilerResult<CfgPredicate> {
+        let location = self.peek().location.clone();
+        let name = match &self.peek().token {
+            Token::Identifier(name) => name.clone(),
+            _ => {
+                return Err(CompilerError::unexpected_token(
+                    "cfg predicate",
+                    &format!("{:?}", self.peek().token),
+                    location,
+                ));
+            }
+        };
+        self.advance();
+
+        match name.as_str() {
+            "feature" => {
+                self.consume(Token::Assign, "Expected '=' after 'feature'")?;
+                match &self.peek().token {
+                    Token::StringLiteral(feature) => {
+                        let feature = feature.clone();
+                        self.advance();
+                        Ok(CfgPredicate::Feature(feature))
+                    }
+                    _ => Err(CompilerError::unexpected_token(
+                        "feature name string",
+                        &format!("{:?}", self.peek().token),
+                        self.peek().location.clone(),
+                    )),
+                }
+            }
+            "not" | "all" | "any" => {
+                self.consume(Token::LeftParen, "Expected '(' after cfg combinator")?;
+                let mut predicates = Vec::new();
+                while !self.check(&Token::RightParen) && !self.is_at_end() {
+                    predicates.push(self.parse_cfg_predicate()?);
+                    if !self.match_token(&Token::Comma) {
+                        break;
+                    }
+                }
+                self.consume(Token::RightParen, "Expected ')' after cfg predicates")?;
+                match name.as_str() {
+                    "all" => Ok(CfgPredicate::All(predicates)),
+                    "any" => Ok(CfgPredicate::Any(predicates)),
+                    _ if predicates.len() == 1 => {
+                        Ok(CfgPredicate::Not(Box::new(predicates.remove(0))))
+                    }
+                    _ => Err(CompilerError::InvalidSyntax {
+                        message: format!(
+                            "`not` takes exactly one cfg predicate, found {}",
+                            predicates.len()
+                        ),
+                        location,
+                    }),
+                }
+            }
+            _ => Err(CompilerError::InvalidSyntax {
+                message: format!(
+                    "unknown cfg predicate `{}` (expected feature, not, all or any)",
+                    name
+                ),
+                location,
+            }),
+        }
+    }
+
+    fn parse_static_item(&mut self) -> CompilerResult<Statement> {
+        self.consume(Token::Static, "Expected 'static'")?;
+
+        let mutable = self.match_token(&Token::Mut);
+
+        let name = match &self.peek().token {
+            Token::Identifier(name) => {
+                let name = name.clone();
+                self.advance();
+                name
+            }
+            _ => {
+                return Err(CompilerError::unexpected_token(
+                    "static name",
+                    &format!("{:?}", self.peek().token),
+                    self.peek().location.clone(),
+                ));
+            }
+        };
+
+        self.consume(Token::Colon, "Expected ':' after static name")?;
+        let type_annotation = self.parse_type()?;
+        self.consume(Token::Assign, "Expected '=' in static item")?;
+        let value = self.parse_expression()?;
+        self.consume(Token::Semicolon, "Expected ';' after static item")?;
+
+        Ok(Statement::Static {
+            name,
+            mutable,
+            type_annotation,
+            value,
+        })
+    }
+
     fn parse_return_statement(&mut self) -> CompilerResult<Statement> {
         self.consume(Token::Return, "Expected 'return'")?;
 
@@ -274,24 +725,75 @@ impl Parser {
     fn parse_if_statement(&mut self) -> CompilerResult<Statement> {
         self.consume(Token::If, "Expected 'if'")?;
 
+        if self.match_token(&Token::Let) {
+            let pattern = self.parse_pattern()?;
+            self.consume(Token::Assign, "Expected '=' after 'if let' pattern")?;
+            let expression = self.parse_expression()?;
+            let then_block = self.parse_block()?;
+            let else_block = self.parse_else_branch()?;
+            return Ok(Statement::IfLet {
+                pattern: Box::new(pattern),
+                expression,
+                then_block,
+                else_block,
+            });
+        }
+
         let condition = self.parse_expression()?;
         let then_block = self.parse_block()?;
+        let else_block = self.parse_else_branch()?;
+
+        Ok(Statement::If {
+            condition,
+            then_block,
+            else_block,
+        })
+    }
+
+    fn parse_else_branch(&mut self) -> CompilerResult<Option<Box<Statement>>> {
+        if !self.match_token(&Token::Else) {
+            return Ok(None);
+        }
+        if self.check(&Token::If) {
+            // else if
+            Ok(Some(Box::new(self.parse_if_statement()?)))
+        } else {
+            // else block
+            Ok(Some(Box::new(Statement::Block(self.parse_block()?))))
+        }
+    }
+
+    /// `if let` in value position. An `else if let` chain nests as the tail
+    /// expression of the else block.
+    fn parse_if_let_expression(&mut self) -> CompilerResult<Expression> {
+        self.consume(Token::If, "Expected 'if'")?;
+        self.consume(
+            Token::Let,
+            "Expected 'let' after 'if' in expression position",
+        )?;
+        let pattern = self.parse_pattern()?;
+        self.consume(Token::Assign, "Expected '=' after 'if let' pattern")?;
+        let expr = self.parse_expression()?;
+        let then_block = self.parse_block()?;
 
         let else_block = if self.match_token(&Token::Else) {
             if self.check(&Token::If) {
-                // else if
-                Some(Box::new(self.parse_if_statement()?))
+                let nested = self.parse_if_let_expression()?;
+                Some(Box::new(Block {
+                    statements: Vec::new(),
+                    expression: Some(nested),
+                }))
             } else {
-                // else block
-                Some(Box::new(Statement::Block(self.parse_block()?)))
+                Some(Box::new(self.parse_block()?))
             }
         } else {
             None
         };
 
-        Ok(Statement::If {
-            condition,
-            then_block,
+        Ok(Expression::IfLet {
+            pattern: Box::new(pattern),
+            expr: Box::new(expr),
+            then_block: Box::new(then_block),
             else_block,
         })
     }
@@ -308,12 +810,18 @@ impl Parser {
     fn parse_for_statement(&mut self) -> CompilerResult<Statement> {
         self.consume(Token::For, "Expected 'for'")?;
 
+        // `for (a, b) in ..` binds the tuple pattern from a hidden loop variable.
+        let mut pattern = None;
         let variable = match &self.peek().token {
             Token::Identifier(name) => {
                 let name = name.clone();
                 self.advance();
                 name
             }
+            Token::LeftParen => {
+                pattern = Some(self.parse_pattern()?);
+                FOR_PATTERN_ITEM.to_string()
+            }
             _ => {
                 return Err(CompilerError::unexpected_token(
                     "loop variable",
@@ -325,8 +833,27 @@ impl Parser {
 
         self.consume(Token::In, "Expected 'in' after for loop variable")?;
 
-        let iterable = self.parse_expression()?;
-        let body = self.parse_block()?;
+        let mut iterable = self.parse_expression()?;
+        if self.check(&Token::DotDot) || self.check(&Token::DotDotEq) {
+            let inclusive = self.check(&Token::DotDotEq);
+            self.advance();
+            iterable = Expression::Range {
+                start: Box::new(iterable),
+                end: Box::new(self.parse_expression()?),
+                inclusive,
+            };
+        }
+        let mut body = self.parse_block()?;
+        if let Some(pattern) = pattern {
+            body.statements.insert(
+                0,
+                Statement::LetPattern {
+                    pattern,
+                    mutable: false,
+                    value: Expression::Identifier(variable.clone()),
+                },
+            );
+        }
 
         Ok(Statement::For {
             variable,
@@ -359,6 +886,10 @@ impl Parser {
     }
 
     fn parse_block(&mut self) -> CompilerResult<Block> {
+        self.nested(Self::parse_block_contents)
+    }
+
+    fn parse_block_contents(&mut self) -> CompilerResult<Block> {
         self.consume(Token::LeftBrace, "Expected '{'")?;
 
         let mut statements = Vec::new();
@@ -403,71 +934,106 @@ impl Parser {
     }
 
     fn parse_expression(&mut self) -> CompilerResult<Expression> {
-        self.parse_logical_or()
+        self.nested(Self::parse_logical_or)
     }
 
     fn parse_logical_or(&mut self) -> CompilerResult<Expression> {
+        let start = self.current;
         let mut expr = self.parse_logical_and()?;
+        let mut links = 0;
 
         while self.match_token(&Token::LogicalOr) {
-            let right = self.parse_logical_and()?;
-            expr = Expression::Logical {
-                op: crate::ast::LogicalOp::Or,
-                left: Box::new(expr),
-                right: Box::new(right),
-            };
+            links += 1;
+            let right = self.nested_by(links, Self::parse_logical_and)?;
+            expr = self.spanned(
+                start,
+                Expression::Logical {
+                    op: crate::ast::LogicalOp::Or,
+                    left: Box::new(expr),
+                    right: Box::new(right),
+                },
+            );
         }
 
         Ok(expr)
     }
 
     fn parse_logical_and(&mut self) -> CompilerResult<Expression> {
+        let start = self.current;
         let mut expr = self.parse_equality()?;
+        let mut links = 0;
 
         while self.match_token(&Token::LogicalAnd) {
-            let right = self.parse_equality()?;
-            expr = Expression::Logical {
-                op: crate::ast::LogicalOp::And,
-                left: Box::new(expr),
-                right: Box::new(right),
-            };
+            links += 1;
+            let right = self.nested_by(links, Self::parse_equality)?;
+            expr = self.spanned(
+                start,
+                Expression::Logical {
+                    op: crate::ast::LogicalOp::And,
+                    left: Box::new(expr),
+                    right: Box::new(right),
+                },
+            );
         }
 
         Ok(expr)
     }
 
     fn parse_equality(&mut self) -> CompilerResult<Expression> {
+        let start = self.current;
         let mut expr = self.parse_comparison()?;
+        let mut links = 0;
 
         while let Some(op) = self.match_equality_operator() {
-            let right = self.parse_comparison()?;
-            expr = Expression::Comparison {
-                op,
-                left: Box::new(expr),
-                right: Box::new(right),
-            };
+            links += 1;
+            let right = self.nested_by(links, Self::parse_comparison)?;
+            expr = self.spanned(
+                start,
+                Expression::Comparison {
+                    op,
+                    left: Box::new(expr),
+                    right: Box::new(right),
+                },
+            );
         }
 
         Ok(expr)
     }
 
     fn parse_comparison(&mut self) -> CompilerResult<Expression> {
+        let start = self.current;
         let mut expr = self.parse_term()?;
 
-        while let Some(op) = self.match_comparison_operator() {
+        if let Some(op) = self.match_comparison_operator() {
             let right = self.parse_term()?;
-            expr = Expression::Comparison {
-                op,
-                left: Box::new(expr),
-                right: Box::new(right),
-            };
+            // `a < b < c` would compare the bool `a < b` against `c`.
+            if let Some(next) = self.match_comparison_operator() {
+                return Err(CompilerError::InvalidSyntax {
+                    message: format!(
+                        "comparison operators cannot be chained; use `a {} b && b {} c` instead",
+                        op.as_str(),
+                        next.as_str()
+                    ),
+                    location: self.previous().location.clone(),
+                });
+            }
+            expr = self.spanned(
+                start,
+                Expression::Comparison {
+                    op,
+                    left: Box::new(expr),
+                    right: Box::new(right),
+                },
+            );
         }
 
         Ok(expr)
     }
 
     fn parse_term(&mut self) -> CompilerResult<Expression> {
+        let start = self.current;
         let mut expr = self.parse_factor()?;
+        let mut links = 0;
 
         while self.match_token(&Token::Plus) || self.match_token(&Token::Minus) {
             let op = match self.previous().token {
@@ -475,20 +1041,26 @@ impl Parser {
                 Token::Minus => crate::ast::BinaryOp::Subtract,
                 _ => unreachable!(),
             };
-            let right = self.parse_factor()?;
-            expr = Expression::Binary {
-                op,
-                left: Box::new(expr),
-                right: Box::new(right),
-                ty: None,
-            };
+            links += 1;
+            let right = self.nested_by(links, Self::parse_factor)?;
+            expr = self.spanned(
+                start,
+                Expression::Binary {
+                    op,
+                    left: Box::new(expr),
+                    right: Box::new(right),
+                    ty: None,
+                },
+            );
         }
 
         Ok(expr)
     }
 
     fn parse_factor(&mut self) -> CompilerResult<Expression> {
+        let start = self.current;
         let mut expr = self.parse_unary()?;
+        let mut links = 0;
 
         while self.match_token(&Token::Multiply)
             || self.match_token(&Token::Divide)
@@ -500,61 +1072,103 @@ impl Parser {
                 Token::Modulo => crate::ast::BinaryOp::Modulo,
                 _ => unreachable!(),
             };
-            let right = self.parse_unary()?;
-            expr = Expression::Binary {
-                op,
-                left: Box::new(expr),
-                right: Box::new(right),
-                ty: None,
-            };
+            links += 1;
+            let right = self.nested_by(links, Self::parse_unary)?;
+            expr = self.spanned(
+                start,
+                Expression::Binary {
+                    op,
+                    left: Box::new(expr),
+                    right: Box::new(right),
+                    ty: None,
+                },
+            );
         }
 
         Ok(expr)
     }
 
     fn parse_unary(&mut self) -> CompilerResult<Expression> {
+        let start = self.current;
         if self.match_token(&Token::LogicalNot) || self.match_token(&Token::Minus) {
             let op = match self.previous().token {
                 Token::LogicalNot => crate::ast::UnaryOp::Not,
                 Token::Minus => crate::ast::UnaryOp::Negate,
                 _ => unreachable!(),
             };
-            let operand = self.parse_unary()?;
-            return Ok(Expression::Unary {
+            let operand = self.nested(Self::parse_unary)?;
+            let unary = Expression::Unary {
                 op,
                 operand: Box::new(operand),
-            });
+            };
+            return Ok(self.spanned(start, unary));
         }
 
         // Phase 5: Borrow expressions &x and &mut x
         if self.match_token(&Token::Ampersand) {
             let mutable = self.match_token(&Token::Mut);
-            let expr = self.parse_unary()?;
-            return Ok(Expression::Borrow {
+            let expr = self.nested(Self::parse_unary)?;
+            let borrow = Expression::Borrow {
                 expr: Box::new(expr),
                 mutable,
-            });
+            };
+            return Ok(self.spanned(start, borrow));
         }
 
         // Phase 5: Dereference expression *x
         if self.match_token(&Token::Multiply) {
-            let expr = self.parse_unary()?;
-            return Ok(Expression::Deref(Box::new(expr)));
+            let expr = self.nested(Self::parse_unary)?;
+            let deref = Expression::Deref(Box::new(expr));
+            return Ok(self.spanned(start, deref));
         }
 
         self.parse_call()
     }
 
+    /// One argument of a call: `name: value` or a positional expression,
+    /// which may not follow a keyword argument in `previous`.
+    fn parse_call_argument(&mut self, previous: &[Expression]) -> CompilerResult<Expression> {
+        if let Token::Identifier(name) = &self.peek().token
+            && self
+                .tokens
+                .get(self.current + 1)
+                .is_some_and(|next| next.token == Token::Colon)
+        {
+            let name = name.clone();
+            self.advance();
+            self.advance();
+            let value = self.parse_expression()?;
+            return Ok(Expression::NamedArgument {
+                name,
+                value: Box::new(value),
+            });
+        }
+        if matches!(previous.last(), Some(Expression::NamedArgument { .. })) {
+            return Err(CompilerError::InvalidSyntax {
+                message: "positional argument after a keyword argument".to_string(),
+                location: self.peek().location.clone(),
+            });
+        }
+        self.parse_expression()
+    }
+
     fn parse_call(&mut self) -> CompilerResult<Expression> {
+        let start = self.current;
         let mut expr = self.parse_primary()?;
+        // `::<T>` after a method name, waiting for its arguments.
+        let mut type_args = Vec::new();
 
         loop {
+            // A callee is only complete once its arguments are parsed.
+            if !self.check(&Token::LeftParen) {
+                expr = self.spanned(start, expr);
+            }
             if self.match_token(&Token::LeftParen) {
                 // Function call
                 let mut arguments = Vec::new();
                 if !self.check(&Token::RightParen) {
                     loop {
-                        arguments.push(self.parse_expression()?);
+                        arguments.push(self.parse_call_argument(&arguments)?);
                         if !self.match_token(&Token::Comma) {
                             break;
                         }
@@ -562,6 +1176,16 @@ impl Parser {
                 }
                 self.consume(Token::RightParen, "Expected ')' after arguments")?;
 
+                let named = arguments
+                    .iter()
+                    .any(|argument| matches!(argument, Expression::NamedArgument { .. }));
+                if named && !matches!(expr, Expression::Identifier(_)) {
+                    return Err(CompilerError::InvalidSyntax {
+                        message: "keyword arguments are only supported in function calls"
+                            .to_string(),
+                        location: self.previous().location.clone(),
+                    });
+                }
                 if let Expression::Identifier(name) = expr {
                     expr = Expression::FunctionCall { name, arguments };
                 } else if let Expression::FieldAccess { object, field } = expr {
@@ -570,6 +1194,7 @@ impl Parser {
                         object,
                         method: field,
                         arguments,
+                        type_args: std::mem::take(&mut type_args),
                     };
                 } else {
                     return Err(CompilerError::InvalidSyntax {
@@ -584,6 +1209,7 @@ impl Parser {
                 expr = Expression::IndexAccess {
                     object: Box::new(expr),
                     index: Box::new(index),
+                    location: self.panic_location(start),
                 };
             } else if self.match_token(&Token::Dot) {
                 // Field access or tuple index: expr.field or expr.0
@@ -603,6 +1229,16 @@ impl Parser {
                             object: Box::new(expr),
                             field,
                         };
+                        if self.match_token(&Token::DoubleColon) {
+                            type_args = self.parse_turbofish()?;
+                            if !self.check(&Token::LeftParen) {
+                                return Err(CompilerError::unexpected_token(
+                                    "'(' after method type arguments",
+                                    &format!("{:?}", self.peek().token),
+                                    self.peek().location.clone(),
+                                ));
+                            }
+                        }
                     }
                     _ => {
                         return Err(CompilerError::unexpected_token(
@@ -620,6 +1256,17 @@ impl Parser {
         Ok(expr)
     }
 
+    /// Parse the `<T, ...>` of a turbofish; the `::` is already consumed.
+    fn parse_turbofish(&mut self) -> CompilerResult<Vec<Type>> {
+        self.consume(Token::LessThan, "Expected '<' after '::'")?;
+        let mut types = vec![self.parse_type()?];
+        while self.match_token(&Token::Comma) {
+            types.push(self.parse_type()?);
+        }
+        self.consume(Token::GreaterThan, "Expected '>' after type arguments")?;
+        Ok(types)
+    }
+
     fn parse_primary(&mut self) -> CompilerResult<Expression> {
         match &self.peek().token {
             Token::IntegerLiteral(value) => {
@@ -627,16 +1274,39 @@ impl Parser {
                 self.advance();
                 Ok(Expression::IntegerLiteral(value))
             }
+            Token::InvalidNumber(text) => Err(CompilerError::InvalidSyntax {
+                message: format!("integer literal `{}` does not fit in an i64", text),
+                location: self.peek().location.clone(),
+            }),
             Token::FloatLiteral(value) => {
                 let value = *value;
                 self.advance();
                 Ok(Expression::FloatLiteral(value))
             }
+            Token::BooleanLiteral(value) => {
+                let value = *value;
+                self.advance();
+                Ok(Expression::BooleanLiteral(value))
+            }
             Token::StringLiteral(s) => {
                 let s = s.clone();
                 self.advance();
                 Ok(Expression::StringLiteral(s))
             }
+            Token::CharLiteral(c) => {
+                let c = *c;
+                self.advance();
+                Ok(Expression::CharLiteral(c))
+            }
+            // b"..." is an array of its bytes
+            Token::ByteStringLiteral(bytes) => {
+                let elements = bytes
+                    .iter()
+                    .map(|byte| Expression::IntegerLiteral(*byte as i64))
+                    .collect();
+                self.advance();
+                Ok(Expression::ArrayLiteral(elements))
+            }
             Token::FStringLiteral(s) => {
                 // Outside print!/println!, keep f-strings as raw string literals for now.
                 let s = s.clone();
@@ -644,6 +1314,15 @@ impl Parser {
                 Ok(Expression::StringLiteral(s))
             }
             Token::VecMacro => self.parse_vec_macro_literal(),
+            Token::MatchesMacro => self.parse_matches_macro(),
+            Token::TodoMacro => self.parse_panic_macro("not yet implemented"),
+            Token::UnimplementedMacro => self.parse_panic_macro("not implemented"),
+            Token::PanicMacro => self.parse_panic_with_message(),
+            // `self` inside a method body is an ordinary binding
+            Token::Self_ => {
+                self.advance();
+                Ok(Expression::Identifier("self".to_string()))
+            }
             Token::Identifier(name) => {
                 let name = name.clone();
                 self.advance();
@@ -695,12 +1374,8 @@ impl Parser {
                 }
 
                 // Check for struct literal: Name { field: value, ... }
-                if self.check(&Token::LeftBrace) {
-                    // Peek ahead to see if this looks like a struct literal
-                    // (identifier followed by colon means struct literal)
-                    if self.is_struct_literal_start() {
-                        return self.parse_struct_literal(name);
-                    }
+                if self.is_struct_literal_start() {
+                    return self.parse_struct_literal(name);
                 }
                 // Check for enum variant: Name::Variant
                 if self.check(&Token::DoubleColon) {
@@ -737,10 +1412,11 @@ impl Parser {
             }
             Token::LeftBracket => self.parse_array_literal(),
             Token::Match => self.parse_match_expression(),
+            Token::If => self.parse_if_let_expression(),
             Token::PrintMacro => self.parse_print_macro(false),
             Token::PrintlnMacro => self.parse_print_macro(true),
             // Phase 7: Closure expressions |params| body
-            Token::Pipe => self.parse_closure(),
+            Token::Pipe | Token::LogicalOr => self.parse_closure(),
             _ => Err(CompilerError::unexpected_token(
                 "expression",
                 &format!("{:?}", self.peek().token),
@@ -751,6 +1427,10 @@ impl Parser {
 
     /// Parse closure expression: `|x: i32, y: i32| x + y` or `|x| { ... }`
     fn parse_closure(&mut self) -> CompilerResult<Expression> {
+        // `||` lexes as a single token: a closure without parameters.
+        if self.match_token(&Token::LogicalOr) {
+            return self.parse_closure_body(Vec::new());
+        }
         self.consume(Token::Pipe, "Expected '|' to start closure")?;
 
         let mut params = Vec::new();
@@ -793,7 +1473,10 @@ impl Parser {
         }
 
         self.consume(Token::Pipe, "Expected '|' after closure parameters")?;
+        self.parse_closure_body(params)
+    }
 
+    fn parse_closure_body(&mut self, params: Vec<Parameter>) -> CompilerResult<Expression> {
         // Parse body: either a block { ... } or a single expression
         let body = if self.check(&Token::LeftBrace) {
             let block = self.parse_block()?;
@@ -822,7 +1505,25 @@ impl Parser {
         }
 
         self.consume(Token::LeftParen, "Expected '(' after print macro")?;
+        let (format_string, arguments) = self.parse_format_arguments()?;
+        self.consume(Token::RightParen, "Expected ')' after print arguments")?;
+
+        if is_println {
+            Ok(Expression::Println {
+                format_string,
+                arguments,
+            })
+        } else {
+            Ok(Expression::Print {
+                format_string,
+                arguments,
+            })
+        }
+    }
 
+    /// A format string followed by its comma-separated arguments; an
+    /// f-string contributes its interpolated expressions first.
+    fn parse_format_arguments(&mut self) -> CompilerResult<(String, Vec<Expression>)> {
         let mut arguments = Vec::new();
         let format_string = match &self.peek().token {
             Token::StringLiteral(s) => {
@@ -857,20 +1558,7 @@ impl Parser {
         while self.match_token(&Token::Comma) {
             arguments.push(self.parse_expression()?);
         }
-
-        self.consume(Token::RightParen, "Expected ')' after print arguments")?;
-
-        if is_println {
-            Ok(Expression::Println {
-                format_string,
-                arguments,
-            })
-        } else {
-            Ok(Expression::Print {
-                format_string,
-                arguments,
-            })
-        }
+        Ok((format_string, arguments))
     }
 
     fn parse_interpolated_format_string(
@@ -981,6 +1669,41 @@ impl Parser {
                 let inner = self.parse_type()?;
                 Ok(Type::Reference(Box::new(inner), mutable))
             }
+            // The never type of functions that do not return
+            Token::LogicalNot => {
+                self.advance();
+                Ok(Type::Named("!".to_string()))
+            }
+            Token::Dyn => {
+                self.advance();
+                match &self.peek().token {
+                    Token::Identifier(name) => {
+                        let name = name.clone();
+                        self.advance();
+                        Ok(Type::TraitObject(name))
+                    }
+                    _ => Err(CompilerError::unexpected_token(
+                        "trait name after 'dyn'",
+                        &format!("{:?}", self.peek().token),
+                        self.peek().location.clone(),
+                    )),
+                }
+            }
+            Token::Impl => {
+                self.advance();
+                match &self.peek().token {
+                    Token::Identifier(name) => {
+                        let name = name.clone();
+                        self.advance();
+                        Ok(Type::ImplTrait(name))
+                    }
+                    _ => Err(CompilerError::unexpected_token(
+                        "trait name after 'impl'",
+                        &format!("{:?}", self.peek().token),
+                        self.peek().location.clone(),
+                    )),
+                }
+            }
             Token::Identifier(name) => {
                 let name = name.clone();
                 self.advance();
@@ -1024,6 +1747,13 @@ impl Parser {
                         self.advance();
                         n
                     }
+                    // A const generic length: `[T; N]`
+                    Token::Identifier(name) => {
+                        let name = name.clone();
+                        self.advance();
+                        self.consume(Token::RightBracket, "Expected ']' after array type")?;
+                        return Ok(Type::ConstArray(Box::new(elem_type), name));
+                    }
                     _ => {
                         return Err(CompilerError::unexpected_token(
                             "array size",
@@ -1103,10 +1833,54 @@ impl Parser {
 
     fn parse_vec_macro_literal(&mut self) -> CompilerResult<Expression> {
         self.consume(Token::VecMacro, "Expected 'vec!'")?;
-        // For now vec! lowers to the same IR/semantics as array literals.
+        // For now vec! lowers to the same IR/semantics as array literals,
+        // including the `vec![value; count]` repeat form.
         self.parse_array_literal()
     }
 
+    fn parse_matches_macro(&mut self) -> CompilerResult<Expression> {
+        self.consume(Token::MatchesMacro, "Expected 'matches!'")?;
+        self.consume(Token::LeftParen, "Expected '(' after 'matches!'")?;
+        let expr = self.parse_expression()?;
+        self.consume(Token::Comma, "Expected ',' after matches! expression")?;
+        let pattern = self.parse_pattern()?;
+        self.consume(Token::RightParen, "Expected ')' after matches! pattern")?;
+        Ok(Expression::Matches {
+            expr: Box::new(expr),
+            pattern: Box::new(pattern),
+        })
+    }
+
+    /// `todo!()` / `unimplemented!()`; the macro token is still current.
+    fn parse_panic_macro(&mut self, message: &str) -> CompilerResult<Expression> {
+        let location = self.panic_location(self.current);
+        self.advance();
+        self.consume(Token::LeftParen, "Expected '(' after macro name")?;
+        self.consume(Token::RightParen, "Expected ')' after macro arguments")?;
+        Ok(Expression::Panic {
+            message: message.to_string(),
+            location,
+        })
+    }
+
+    /// `panic!("message")`, or `panic!()` for Rust's "explicit panic"; the
+    /// macro token is still current.
+    fn parse_panic_with_message(&mut self) -> CompilerResult<Expression> {
+        let location = self.panic_location(self.current);
+        self.advance();
+        self.consume(Token::LeftParen, "Expected '(' after 'panic!'")?;
+        let message = match &self.peek().token {
+            Token::StringLiteral(message) => {
+                let message = message.clone();
+                self.advance();
+                message
+            }
+            _ => "explicit panic".to_string(),
+        };
+        self.consume(Token::RightParen, "Expected ')' after panic message")?;
+        Ok(Expression::Panic { message, location })
+    }
+
     fn parse_struct_def(&mut self) -> CompilerResult<Statement> {
         self.consume(Token::Struct, "Expected 'struct'")?;
         let name = match &self.peek().token {
@@ -1123,7 +1897,27 @@ impl Parser {
                 ));
             }
         };
-        let (type_params, _bounds) = self.parse_optional_type_params()?;
+        let (type_params, _consts, _bounds) = self.parse_optional_type_params()?;
+        // `struct Pair(i32, i32);` names its fields by position.
+        if self.match_token(&Token::LeftParen) {
+            let mut fields = Vec::new();
+            while !self.check(&Token::RightParen) && !self.is_at_end() {
+                fields.push(FieldDecl {
+                    name: fields.len().to_string(),
+                    field_type: self.parse_type()?,
+                });
+                if !self.match_token(&Token::Comma) {
+                    break;
+                }
+            }
+            self.consume(Token::RightParen, "Expected ')' after tuple struct fields")?;
+            self.consume(Token::Semicolon, "Expected ';' after tuple struct")?;
+            return Ok(Statement::StructDef {
+                name,
+                fields,
+                type_params,
+            });
+        }
         self.consume(Token::LeftBrace, "Expected '{' after struct name")?;
         let mut fields = Vec::new();
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
@@ -1175,7 +1969,7 @@ impl Parser {
                 ));
             }
         };
-        let (type_params, _bounds) = self.parse_optional_type_params()?;
+        let (type_params, _consts, _bounds) = self.parse_optional_type_params()?;
         self.consume(Token::LeftBrace, "Expected '{' after enum name")?;
         let mut variants = Vec::new();
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
@@ -1253,7 +2047,7 @@ impl Parser {
         self.consume(Token::Impl, "Expected 'impl'")?;
 
         // Parse optional generic type parameters: impl<T>
-        let (type_params, _bounds) = self.parse_optional_type_params()?;
+        let (type_params, _consts, _bounds) = self.parse_optional_type_params()?;
 
         let first_name = match &self.peek().token {
             Token::Identifier(n) => {
@@ -1298,7 +2092,11 @@ impl Parser {
         self.consume(Token::LeftBrace, "Expected '{' after impl type")?;
         let mut methods = Vec::new();
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
-            methods.push(self.parse_function_definition()?);
+            if self.check(&Token::At) {
+                methods.push(self.parse_cfg_item()?);
+            } else {
+                methods.push(self.parse_function_definition()?);
+            }
         }
         self.consume(Token::RightBrace, "Expected '}' after impl block")?;
         Ok(Statement::ImplBlock {
@@ -1327,8 +2125,13 @@ impl Parser {
                     ));
                 }
             };
-            self.consume(Token::Colon, "Expected ':' after field name")?;
-            let value = self.parse_expression()?;
+            // `field` alone is shorthand for `field: field`
+            let value = if self.check(&Token::Comma) || self.check(&Token::RightBrace) {
+                Expression::Identifier(field_name.clone())
+            } else {
+                self.consume(Token::Colon, "Expected ':' after field name")?;
+                self.parse_expression()?
+            };
             fields.push((field_name, value));
             if !self.match_token(&Token::Comma) {
                 break;
@@ -1338,6 +2141,42 @@ impl Parser {
         Ok(Expression::StructLiteral { name, fields })
     }
 
+    /// Parse the fields of a struct pattern after its `{`, through the
+    /// closing `}`: `field`, `field: pattern`, and a trailing `..`.
+    fn parse_struct_pattern_fields(&mut self) -> CompilerResult<Vec<(String, Pattern)>> {
+        let mut fields = Vec::new();
+        while !self.check(&Token::RightBrace) {
+            if self.match_token(&Token::DotDot) {
+                break;
+            }
+            let field = match &self.peek().token {
+                Token::Identifier(field) => {
+                    let field = field.clone();
+                    self.advance();
+                    field
+                }
+                _ => {
+                    return Err(CompilerError::unexpected_token(
+                        "field name",
+                        &format!("{:?}", self.peek().token),
+                        self.peek().location.clone(),
+                    ));
+                }
+            };
+            let pattern = if self.match_token(&Token::Colon) {
+                self.parse_pattern()?
+            } else {
+                Pattern::Identifier(field.clone())
+            };
+            fields.push((field, pattern));
+            if !self.match_token(&Token::Comma) {
+                break;
+            }
+        }
+        self.consume(Token::RightBrace, "Expected '}' after struct pattern")?;
+        Ok(fields)
+    }
+
     fn parse_enum_variant(&mut self, enum_name: String) -> CompilerResult<Expression> {
         self.consume(Token::DoubleColon, "Expected '::'")?;
         let variant = match &self.peek().token {
@@ -1354,11 +2193,30 @@ impl Parser {
                 ));
             }
         };
-        // Check for variant data: Variant(expr)
+        // `Box::new(value)` is a heap allocation, not an enum variant
+        if enum_name == "Box" && variant == "new" {
+            self.consume(Token::LeftParen, "Expected '(' after 'Box::new'")?;
+            let value = self.parse_expression()?;
+            self.consume(Token::RightParen, "Expected ')' after boxed value")?;
+            return Ok(Expression::BoxNew(Box::new(value)));
+        }
+        // Check for variant data: Variant(expr) or Variant(expr, expr, ...)
         let data = if self.match_token(&Token::LeftParen) {
-            let expr = self.parse_expression()?;
+            let mut values = vec![self.parse_expression()?];
+            while self.match_token(&Token::Comma) {
+                values.push(self.parse_expression()?);
+            }
             self.consume(Token::RightParen, "Expected ')' after variant data")?;
+            // Multi-field tuple variants carry their fields as a tuple
+            let expr = if values.len() == 1 {
+                values.remove(0)
+            } else {
+                Expression::TupleLiteral(values)
+            };
             Some(Box::new(expr))
+        } else if self.is_struct_literal_start() {
+            // Struct variant: Enum::Variant { field: value, .. }
+            Some(Box::new(self.parse_struct_literal(variant.clone())?))
         } else {
             None
         };
@@ -1377,7 +2235,11 @@ impl Parser {
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
             let pattern = self.parse_pattern()?;
             self.consume(Token::FatArrow, "Expected '=>' after pattern")?;
-            let body = self.parse_expression()?;
+            let body = if self.check(&Token::LeftBrace) {
+                Expression::Block(Box::new(self.parse_block()?))
+            } else {
+                self.parse_expression()?
+            };
             arms.push(MatchArm { pattern, body });
             // Comma is optional between arms
             self.match_token(&Token::Comma);
@@ -1390,6 +2252,32 @@ impl Parser {
     }
 
     fn parse_pattern(&mut self) -> CompilerResult<Pattern> {
+        let pattern = self.parse_single_pattern()?;
+
+        // Range pattern: 1..=5 or 1..5
+        if let Pattern::Literal(start) = &pattern
+            && (self.check(&Token::DotDotEq) || self.check(&Token::DotDot))
+        {
+            let inclusive = self.check(&Token::DotDotEq);
+            self.advance();
+            let Pattern::Literal(end) = self.parse_single_pattern()? else {
+                return Err(CompilerError::unexpected_token(
+                    "literal range bound",
+                    &format!("{:?}", self.previous().token),
+                    self.previous().location.clone(),
+                ));
+            };
+            return Ok(Pattern::Range {
+                start: start.clone(),
+                end,
+                inclusive,
+            });
+        }
+
+        Ok(pattern)
+    }
+
+    fn parse_single_pattern(&mut self) -> CompilerResult<Pattern> {
         match &self.peek().token {
             Token::Underscore => {
                 self.advance();
@@ -1405,11 +2293,21 @@ impl Parser {
                 self.advance();
                 Ok(Pattern::Literal(Expression::FloatLiteral(f)))
             }
+            Token::BooleanLiteral(b) => {
+                let b = *b;
+                self.advance();
+                Ok(Pattern::Literal(Expression::BooleanLiteral(b)))
+            }
             Token::StringLiteral(s) => {
                 let s = s.clone();
                 self.advance();
                 Ok(Pattern::Literal(Expression::StringLiteral(s)))
             }
+            Token::CharLiteral(c) => {
+                let c = *c;
+                self.advance();
+                Ok(Pattern::Literal(Expression::CharLiteral(c)))
+            }
             Token::Identifier(name) => {
                 let name = name.clone();
                 self.advance();
@@ -1478,9 +2376,24 @@ impl Parser {
                         }
                     };
                     let data = if self.match_token(&Token::LeftParen) {
-                        let inner = self.parse_pattern()?;
+                        let mut inner = vec![self.parse_pattern()?];
+                        while self.match_token(&Token::Comma) {
+                            inner.push(self.parse_pattern()?);
+                        }
                         self.consume(Token::RightParen, "Expected ')'")?;
+                        let inner = if inner.len() == 1 {
+                            inner.remove(0)
+                        } else {
+                            Pattern::Tuple(inner)
+                        };
                         Some(Box::new(inner))
+                    } else if self.match_token(&Token::LeftBrace) {
+                        // Struct variant: Enum::Variant { field, field: pattern, .. }
+                        let fields = self.parse_struct_pattern_fields()?;
+                        Some(Box::new(Pattern::Struct {
+                            name: variant.clone(),
+                            fields,
+                        }))
                     } else {
                         None
                     };
@@ -1489,6 +2402,17 @@ impl Parser {
                         variant,
                         data,
                     })
+                } else if self.match_token(&Token::LeftBrace) {
+                    // Struct pattern: Name { field, field: pattern, .. }
+                    let fields = self.parse_struct_pattern_fields()?;
+                    Ok(Pattern::Struct { name, fields })
+                } else if self.match_token(&Token::At) {
+                    // Binding pattern: name @ subpattern
+                    let pattern = self.parse_pattern()?;
+                    Ok(Pattern::Binding {
+                        name,
+                        pattern: Box::new(pattern),
+                    })
                 } else {
                     // Variable binding pattern
                     Ok(Pattern::Identifier(name))
@@ -1509,6 +2433,38 @@ impl Parser {
                 self.consume(Token::RightParen, "Expected ')'")?;
                 Ok(Pattern::Tuple(patterns))
             }
+            Token::LeftBracket => {
+                // Array pattern: [a, b], [first, ..], [first, ..rest]
+                self.advance();
+                let mut elements = Vec::new();
+                let mut rest = None;
+                while !self.check(&Token::RightBracket) {
+                    if self.match_token(&Token::DotDot) {
+                        if rest.is_some() {
+                            return Err(CompilerError::InvalidSyntax {
+                                message: "`..` can only be used once per array pattern".to_string(),
+                                location: self.previous().location.clone(),
+                            });
+                        }
+                        let name = match &self.peek().token {
+                            Token::Identifier(name) => {
+                                let name = name.clone();
+                                self.advance();
+                                Some(name)
+                            }
+                            _ => None,
+                        };
+                        rest = Some((elements.len(), name));
+                    } else {
+                        elements.push(self.parse_pattern()?);
+                    }
+                    if !self.match_token(&Token::Comma) {
+                        break;
+                    }
+                }
+                self.consume(Token::RightBracket, "Expected ']' after array pattern")?;
+                Ok(Pattern::Array { elements, rest })
+            }
             _ => Err(CompilerError::unexpected_token(
                 "pattern",
                 &format!("{:?}", self.peek().token),
@@ -1519,16 +2475,26 @@ impl Parser {
 
     // --- Phase 5 parsing methods ---
 
-    /// Parse optional generic type parameters: <T, U, V>
+    /// Parse `<T: Bound, const N: usize>`, returning the type parameters,
+    /// the const parameters and the trait bounds.
+    #[allow(clippy::type_complexity)]
     fn parse_optional_type_params(
         &mut self,
-    ) -> CompilerResult<(Vec<String>, Vec<(String, Vec<String>)>)> {
+    ) -> CompilerResult<(Vec<String>, Vec<String>, Vec<(String, Vec<String>)>)> {
         if !self.match_token(&Token::LessThan) {
-            return Ok((vec![], vec![]));
+            return Ok((vec![], vec![], vec![]));
         }
         let mut params = Vec::new();
+        let mut const_params = Vec::new();
         let mut bounds = Vec::new();
         loop {
+            if self.match_token(&Token::Const) {
+                const_params.push(self.parse_const_param()?);
+                if !self.match_token(&Token::Comma) {
+                    break;
+                }
+                continue;
+            }
             let param_name = match &self.peek().token {
                 Token::Identifier(name) => {
                     let name = name.clone();
@@ -1569,7 +2535,37 @@ impl Parser {
             }
         }
         self.consume(Token::GreaterThan, "Expected '>' after type parameters")?;
-        Ok((params, bounds))
+        Ok((params, const_params, bounds))
+    }
+
+    /// `N: usize` after `const`; only integer lengths are supported.
+    fn parse_const_param(&mut self) -> CompilerResult<String> {
+        let name = match &self.peek().token {
+            Token::Identifier(name) => {
+                let name = name.clone();
+                self.advance();
+                name
+            }
+            _ => {
+                return Err(CompilerError::unexpected_token(
+                    "const parameter name",
+                    &format!("{:?}", self.peek().token),
+                    self.peek().location.clone(),
+                ));
+            }
+        };
+        self.consume(Token::Colon, "Expected ':' after const parameter name")?;
+        match &self.peek().token {
+            Token::Identifier(ty) if ty == "usize" => {
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(CompilerError::unexpected_token(
+                "usize",
+                &format!("{:?}", self.peek().token),
+                self.peek().location.clone(),
+            )),
+        }
     }
 
     /// Skip generic type arguments like `<T>` or `<T, U>` if present.
@@ -1605,7 +2601,7 @@ impl Parser {
                 ));
             }
         };
-        let (type_params, _bounds) = self.parse_optional_type_params()?;
+        let (type_params, _consts, _bounds) = self.parse_optional_type_params()?;
         self.consume(Token::LeftBrace, "Expected '{' after trait name")?;
         let mut methods = Vec::new();
         while !self.check(&Token::RightBrace) && !self.is_at_end() {
@@ -1702,16 +2698,18 @@ impl Parser {
 
     /// Check if the next tokens look like a struct literal (Name { field: ... })
     /// as opposed to a block statement after an identifier
+    /// Whether the `{` at the cursor opens struct literal fields: `field:`
+    /// or shorthand `field,` (`field` for `field: field`). A lone `{ w }`
+    /// stays a block, since after a name it may be the body of an `if` or
+    /// `while`.
     fn is_struct_literal_start(&self) -> bool {
-        // Look for pattern: { identifier : ... }
-        if self.current + 2 < self.tokens.len() {
-            let after_brace = &self.tokens[self.current + 1];
-            let after_ident = &self.tokens[self.current + 2];
-            matches!(after_brace.token, Token::Identifier(_))
-                && matches!(after_ident.token, Token::Colon)
-        } else {
-            false
+        if !self.check(&Token::LeftBrace) || self.current + 2 >= self.tokens.len() {
+            return false;
         }
+        let after_brace = &self.tokens[self.current + 1];
+        let after_ident = &self.tokens[self.current + 2];
+        matches!(after_brace.token, Token::Identifier(_))
+            && matches!(after_ident.token, Token::Colon | Token::Comma)
     }
 
     // Helper methods
@@ -1771,6 +2769,13 @@ impl Parser {
         &self.tokens[self.current]
     }
 
+    /// Where the token at `index` starts, for a panic raised there at run
+    /// time. Tokens lexed without locations give `None`.
+    fn panic_location(&self, index: usize) -> Option<SourceLocation> {
+        let location = &self.tokens[index].location;
+        (location.line > 0).then(|| location.clone())
+    }
+
     fn previous(&self) -> &LocatedToken {
         &self.tokens[self.current - 1]
     }
@@ -1788,25 +2793,57 @@ impl Parser {
     }
 
     fn is_expression_start(&self) -> bool {
-        matches!(
-            self.peek().token,
-            Token::IntegerLiteral(_)
+        self.is_if_let_start()
+            || matches!(
+                self.peek().token,
+                Token::IntegerLiteral(_)
                 | Token::FloatLiteral(_)
+                | Token::BooleanLiteral(_)
                 | Token::StringLiteral(_)
+                | Token::CharLiteral(_)
                 | Token::FStringLiteral(_)
+                | Token::ByteStringLiteral(_)
                 | Token::Identifier(_)
+                | Token::Self_
                 | Token::LeftParen
                 | Token::LeftBracket
                 | Token::LogicalNot
                 | Token::Minus
                 | Token::Match
                 | Token::Pipe // closures
+                | Token::LogicalOr
             | Token::PrintMacro
                 | Token::PrintlnMacro
                 | Token::VecMacro
+                | Token::MatchesMacro
+                | Token::TodoMacro
+                | Token::UnimplementedMacro
+                | Token::PanicMacro
                 | Token::Ampersand
                 | Token::Multiply
-        )
+            )
+    }
+
+    /// Whether a `let` binds a pattern rather than a single name.
+    fn is_let_pattern_start(&self) -> bool {
+        match self.peek().token {
+            Token::LeftBracket | Token::LeftParen | Token::Underscore => true,
+            Token::Identifier(_) => self.tokens.get(self.current + 1).is_some_and(|next| {
+                matches!(
+                    next.token,
+                    Token::LeftParen | Token::LeftBrace | Token::DoubleColon
+                )
+            }),
+            _ => false,
+        }
+    }
+
+    fn is_if_let_start(&self) -> bool {
+        self.check(&Token::If)
+            && self
+                .tokens
+                .get(self.current + 1)
+                .is_some_and(|next| next.token == Token::Let)
     }
 
     fn synchronize(&mut self) {
@@ -1946,6 +2983,7 @@ impl Parser {
             Token::Struct => self.parse_struct_def(),
             Token::Enum => self.parse_enum_def(),
             Token::Trait => self.parse_trait_def(),
+            Token::Static => self.parse_static_item(),
             Token::Mod => {
                 // pub mod foo;
                 self.consume(Token::Mod, "Expected 'mod'")?;
@@ -1970,7 +3008,7 @@ impl Parser {
                 })
             }
             _ => Err(CompilerError::unexpected_token(
-                "fn, struct, enum, trait, or mod after 'pub'",
+                "fn, struct, enum, trait, static, or mod after 'pub'",
                 &format!("{:?}", self.peek().token),
                 self.peek().location.clone(),
             )),
@@ -2056,4 +3094,170 @@ mod tests {
             _ => panic!("expected vec![] to parse as array literal"),
         }
     }
+
+    #[test]
+    fn matches_macro_parses_expression_and_range_pattern() {
+        let source = "let ok = matches!(n, 1..=5);";
+        let tokens = tokenize_with_locations(source, None);
+        let mut parser = Parser::new(tokens);
+        let ast = parser.parse().expect("parser should succeed");
+
+        match &ast[0] {
+            AstNode::Statement(Statement::Let {
+                value: Some(Expression::Matches { expr, pattern }),
+                ..
+            }) => {
+                assert!(matches!(**expr, Expression::Identifier(ref s) if s == "n"));
+                assert!(matches!(
+                    **pattern,
+                    Pattern::Range {
+                        start: Expression::IntegerLiteral(1),
+                        end: Expression::IntegerLiteral(5),
+                        inclusive: true,
+                    }
+                ));
+            }
+            _ => panic!("expected matches! expression"),
+        }
+    }
+
+    #[test]
+    fn at_binding_wraps_range_pattern() {
+        let source = "let y = match 3 { n @ 1..=5 => n * 2, _ => 0 };";
+        let tokens = tokenize_with_locations(source, None);
+        let mut parser = Parser::new(tokens);
+        let ast = parser.parse().expect("parser should succeed");
+
+        match &ast[0] {
+            AstNode::Statement(Statement::Let {
+                value: Some(Expression::Match { arms, .. }),
+                ..
+            }) => match &arms[0].pattern {
+                Pattern::Binding { name, pattern } => {
+                    assert_eq!(name, "n");
+                    assert!(matches!(
+                        **pattern,
+                        Pattern::Range {
+                            start: Expression::IntegerLiteral(1),
+                            end: Expression::IntegerLiteral(5),
+                            inclusive: true,
+                        }
+                    ));
+                }
+                other => panic!("expected binding pattern, got {:?}", other),
+            },
+            _ => panic!("expected match expression"),
+        }
+    }
+
+    #[test]
+    fn slice_pattern_with_rest_parses() {
+        let source = "let [first, ..rest] = v;";
+        let tokens = tokenize_with_locations(source, None);
+        let mut parser = Parser::new(tokens);
+        let ast = parser.parse().expect("parser should succeed");
+
+        match &ast[0] {
+            AstNode::Statement(Statement::LetPattern {
+                pattern: Pattern::Array { elements, rest },
+                value: Expression::Identifier(value),
+                ..
+            }) => {
+                assert!(
+                    matches!(elements.as_slice(), [Pattern::Identifier(name)] if name == "first")
+                );
+                assert_eq!(rest, &Some((1, Some("rest".to_string()))));
+                assert_eq!(value, "v");
+            }
+            other => panic!("expected array pattern let, got {:?}", other),
+        }
+    }
+
+    fn nested_parens(depth: usize) -> Vec<LocatedToken> {
+        let source = format!("let x = {}1{};", "(".repeat(depth), ")".repeat(depth));
+        tokenize_with_locations(&source, None)
+    }
+
+    #[test]
+    fn pathologically_nested_expression_reports_depth_error() {
+        // Debug builds need a main-thread sized stack to reach the default
+        // limit; the default test thread stack is smaller.
+        let result = std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(|| Parser::new(nested_parens(2_000)).parse())
+            .unwrap()
+            .join()
+            .expect("parser should not overflow the stack");
+
+        let err = result.expect_err("nesting beyond the limit is an error");
+        let first = err.diagnostics()[0].to_string();
+        assert!(first.contains("expression nesting too deep"), "{}", first);
+    }
+
+    #[test]
+    fn long_operator_chain_counts_against_the_nesting_limit() {
+        let source = format!("let x = {};", vec!["1"; 2_000].join(" + "));
+        let err = Parser::new(tokenize_with_locations(&source, None))
+            .parse()
+            .expect_err("a chain deeper than the limit is an error");
+        let first = err.diagnostics()[0].to_string();
+        assert!(first.contains("expression nesting too deep"), "{}", first);
+
+        let source = format!("let x = {};", vec!["1"; 100].join(" * "));
+        assert!(
+            Parser::new(tokenize_with_locations(&source, None))
+                .parse()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn chained_comparison_suggests_splitting_it() {
+        let source = "let x = 1 < 2 < 3;";
+        let err = Parser::new(tokenize_with_locations(source, None))
+            .parse()
+            .expect_err("chained comparisons are rejected");
+        let first = err.diagnostics()[0].to_string();
+        assert!(
+            first.contains("comparison operators cannot be chained; use `a < b && b < c` instead"),
+            "{}",
+            first
+        );
+    }
+
+    #[test]
+    fn nesting_limit_can_be_overridden() {
+        let parse = |depth| {
+            Parser::new(nested_parens(depth))
+                .with_max_nesting_depth(8)
+                .parse()
+        };
+        assert!(parse(4).is_ok());
+        assert!(parse(20).is_err());
+    }
+
+    #[test]
+    fn box_new_and_multi_field_variant_parse() {
+        let source = "let l = List::Cons(1, Box::new(List::Nil));";
+        let tokens = tokenize_with_locations(source, None);
+        let mut parser = Parser::new(tokens);
+        let ast = parser.parse().expect("parser should succeed");
+
+        match &ast[0] {
+            AstNode::Statement(Statement::Let {
+                value: Some(Expression::EnumVariant { variant, data, .. }),
+                ..
+            }) => {
+                assert_eq!(variant, "Cons");
+                match data.as_deref() {
+                    Some(Expression::TupleLiteral(fields)) => {
+                        assert_eq!(fields.len(), 2);
+                        assert!(matches!(fields[1], Expression::BoxNew(_)));
+                    }
+                    other => panic!("expected tuple payload, got {:?}", other),
+                }
+            }
+            _ => panic!("expected enum variant"),
+        }
+    }
 }
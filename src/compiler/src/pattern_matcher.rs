@@ -566,6 +566,7 @@ impl PatternMatcher {
         match expr {
             crate::ast::Expression::IntegerLiteral(value) => Some(PatternValue::Integer(*value)),
             crate::ast::Expression::FloatLiteral(value) => Some(PatternValue::Float(*value)),
+            crate::ast::Expression::BooleanLiteral(value) => Some(PatternValue::Bool(*value)),
             // Add more literal types as needed
             _ => None,
         }
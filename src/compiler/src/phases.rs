@@ -0,0 +1,105 @@
+// src/compiler/src/phases.rs
+
+//! The compiler pipeline split into its phases. Each function takes the
+//! previous phase's output, so a phase can be run (or benchmarked) on its
+//! own; `compile_program` runs them in sequence.
+
+use crate::ast::AstNode;
+use crate::cfg;
+use crate::errors::{CompilerError, Diagnostic, SourceLocation};
+use crate::ir::Function;
+use crate::ir_generator::IrGenerator;
+use crate::ir_optimizer;
+pub use crate::ir_stats::{FunctionStats, IrStats};
+use crate::lexer::{self, LocatedToken};
+use crate::parser::Parser;
+use crate::semantic_analyzer::SemanticAnalyzer;
+use std::collections::HashMap;
+
+/// The top-level items of a parsed program, as produced by `parse`, with
+/// where each one starts so that semantic errors can point at their item.
+#[derive(Debug, Clone)]
+pub struct Program {
+    items: Vec<AstNode>,
+    locations: Vec<SourceLocation>,
+}
+
+impl Program {
+    pub fn items(&self) -> &[AstNode] {
+        &self.items
+    }
+}
+
+/// The IR of a whole program, as produced by `generate_ir`.
+pub struct ProgramIr(HashMap<String, Function>);
+
+pub fn tokenize(source: &str) -> Vec<LocatedToken> {
+    lexer::tokenize_with_locations(source, None)
+}
+
+pub fn parse(tokens: Vec<LocatedToken>) -> Result<Program, Vec<Diagnostic>> {
+    let mut parser = Parser::new(tokens);
+    let items = parser.parse().map_err(CompilerError::into_diagnostics)?;
+    Ok(Program {
+        items,
+        locations: parser.item_locations().to_vec(),
+    })
+}
+
+/// Drop the `@cfg` items of `program` that are disabled for `features` and
+/// unwrap the enabled ones.
+pub fn strip_cfg(program: Program, features: &[String]) -> Program {
+    let (items, locations) = program
+        .items
+        .into_iter()
+        .zip(program.locations)
+        .filter_map(|(item, location)| {
+            let item = cfg::strip_inactive_items(vec![item], features).pop()?;
+            Some((item, location))
+        })
+        .unzip();
+    Program { items, locations }
+}
+
+/// Type-check `program`, returning its items with types filled in, or an
+/// error for every item that fails to check.
+pub fn analyze(program: Program) -> Result<Vec<AstNode>, Vec<Diagnostic>> {
+    SemanticAnalyzer::new()
+        .analyze_items(program.items)
+        .map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|(item, message)| Diagnostic::SemanticError {
+                    message,
+                    location: program.locations[item].clone(),
+                })
+                .collect()
+        })
+}
+
+pub fn generate_ir(typed_ast: Vec<AstNode>) -> ProgramIr {
+    ProgramIr(IrGenerator::new().generate_ir(typed_ast))
+}
+
+/// Run the IR optimization passes selected by `opt_level`, as
+/// `CompilerOptions::opt_level` does.
+pub fn optimize(ir: &mut ProgramIr, opt_level: u8) {
+    ir_optimizer::optimize(&mut ir.0, opt_level);
+}
+
+/// Lower IR to LLVM IR text.
+pub fn generate_code(ir: ProgramIr) -> String {
+    crate::code_generator::generate_code(ir.0)
+}
+
+/// Render the control flow graph of each function as Graphviz DOT, as
+/// `aero build --emit=cfg-dot` does.
+pub fn cfg_dot(ir: &ProgramIr) -> String {
+    crate::ir_graph::render_dot(&ir.0)
+}
+
+/// Instruction, basic block and string constant counts, as `aero build
+/// --stats` prints them.
+pub fn stats(ir: &ProgramIr) -> IrStats {
+    IrStats::collect(&ir.0)
+}
@@ -57,7 +57,7 @@ pub fn init_project(target_dir: &Path) -> Result<InitResult, String> {
 
 fn render_manifest(package_name: &str) -> String {
     format!(
-        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2026\"\n",
+        "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2026\"\nroot = \"src/main.aero\"\n",
         package_name
     )
 }
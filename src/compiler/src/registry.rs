@@ -1,3 +1,4 @@
+use crate::manifest::{MANIFEST_FILE, Manifest};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use std::env;
@@ -212,7 +213,7 @@ pub fn build_publish_preview(
     client: &RegistryClient,
     package_dir: &Path,
 ) -> Result<PublishPreview, String> {
-    let manifest_path = package_dir.join("aero.toml");
+    let manifest_path = package_dir.join(MANIFEST_FILE);
     let manifest = fs::read_to_string(&manifest_path).map_err(|err| {
         format!(
             "failed to read manifest {}: {}",
@@ -220,18 +221,13 @@ pub fn build_publish_preview(
             err
         )
     })?;
-    let package_name = parse_manifest_field(&manifest, "name").ok_or_else(|| {
-        format!(
-            "manifest {} is missing `name = \"...\"`",
-            manifest_path.display()
-        )
-    })?;
-    let version = parse_manifest_field(&manifest, "version").unwrap_or_else(|| "0.1.0".to_string());
+    let Manifest { name, version, .. } = Manifest::parse(&manifest)
+        .map_err(|err| format!("{}: {}", manifest_path.display(), err))?;
     let manifest_sha256 = sha256_hex(manifest.as_bytes());
 
     Ok(PublishPreview {
         endpoint: format!("{}/packages/publish", client.base_url),
-        package_name,
+        package_name: name,
         version,
         manifest_path: manifest_path.display().to_string(),
         manifest_sha256,
@@ -502,24 +498,6 @@ fn parse_registry_index(text: &str) -> Result<Vec<RegistryPackage>, String> {
     Err("registry index must be either an array or an object with `packages`".to_string())
 }
 
-fn parse_manifest_field(manifest: &str, field_name: &str) -> Option<String> {
-    for raw_line in manifest.lines() {
-        let line = raw_line.trim();
-        if line.is_empty() || line.starts_with('#') {
-            continue;
-        }
-        let (key, value) = line.split_once('=')?;
-        if key.trim() != field_name {
-            continue;
-        }
-        let clean = value.trim().trim_matches('"').to_string();
-        if !clean.is_empty() {
-            return Some(clean);
-        }
-    }
-    None
-}
-
 fn collect_package_files(package_dir: &Path) -> Result<Vec<Value>, String> {
     let src_dir = package_dir.join("src");
     let mut files = Vec::new();
@@ -700,20 +678,21 @@ mod tests {
     }
 
     #[test]
-    fn parse_manifest_field_extracts_string_values() {
-        let manifest = r#"
-name = "vision"
-version = "1.2.3"
-"#;
-        assert_eq!(
-            parse_manifest_field(manifest, "name"),
-            Some("vision".to_string())
-        );
-        assert_eq!(
-            parse_manifest_field(manifest, "version"),
-            Some("1.2.3".to_string())
-        );
-        assert_eq!(parse_manifest_field(manifest, "missing"), None);
+    fn publish_preview_reads_name_and_version_from_the_package_table() {
+        let dir = unique_temp_path("aero_registry_package", "d");
+        fs::create_dir_all(&dir).expect("should create package dir");
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            "[package]\nname = \"vision\" # the name\nversion = \"1.2.3\"\n\n[dependencies]\nname = \"other\"\n",
+        )
+        .expect("should write manifest");
+
+        let preview = build_publish_preview(&RegistryClient::new(None), &dir)
+            .expect("preview should succeed");
+        assert_eq!(preview.package_name, "vision");
+        assert_eq!(preview.version, "1.2.3");
+
+        let _ = fs::remove_dir_all(&dir);
     }
 
     #[test]
@@ -0,0 +1,94 @@
+// src/compiler/src/runner.rs
+
+//! Build and run a program from source, capturing what it prints.
+//!
+//! This is `aero run` for tests and tools: the program's output is returned
+//! rather than echoed, and the compiler itself prints nothing. It needs
+//! `clang` on `PATH`, and uses `llc` to assemble when that is available too.
+
+use crate::toolchain;
+use crate::{CompilerOptions, compile_program};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What a finished program reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramOutput {
+    /// `-1` when the program was ended by a signal.
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Compile `source` for the host, link it and run it to completion.
+pub fn run_source(source: &str) -> Result<ProgramOutput, String> {
+    let options = CompilerOptions::default();
+    let llvm_ir = compile_program(source, options.clone())?;
+
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| format!("system clock error while creating run artifacts: {}", err))?
+        .as_nanos();
+    let dir = env::temp_dir().join(format!("aero-run-{}-{}", process::id(), nonce));
+    fs::create_dir_all(&dir)
+        .map_err(|err| format!("failed to create {}: {}", dir.display(), err))?;
+
+    let result = build_and_run(&dir, &llvm_ir, &options);
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+fn build_and_run(
+    dir: &Path,
+    llvm_ir: &str,
+    options: &CompilerOptions,
+) -> Result<ProgramOutput, String> {
+    let ll_file = dir.join("program.ll");
+    let obj_file = dir.join("program.o");
+    let exe_file = dir.join(if cfg!(windows) {
+        "program.exe"
+    } else {
+        "program"
+    });
+    fs::write(&ll_file, llvm_ir)
+        .map_err(|err| format!("failed to write {}: {}", ll_file.display(), err))?;
+    toolchain::link_executable(&ll_file, &obj_file, &exe_file, options.opt_level)?;
+
+    let output = Command::new(&exe_file)
+        .output()
+        .map_err(|err| format!("Error executing compiled program: {}", err))?;
+    Ok(ProgramOutput {
+        exit_code: output.status.code().unwrap_or(-1),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_source_captures_stdout_and_exit_code() {
+        if toolchain::find_llvm_tool("clang").is_none() {
+            eprintln!("skipping: clang not found");
+            return;
+        }
+        let output = run_source("fn main() { println!(\"hi\"); }").unwrap();
+        assert_eq!(
+            output,
+            ProgramOutput {
+                exit_code: 0,
+                stdout: "hi\n".to_string(),
+                stderr: String::new(),
+            }
+        );
+
+        let output = run_source("fn main() -> i32 { 7 }").unwrap();
+        assert_eq!(output.exit_code, 7);
+        assert!(output.stdout.is_empty());
+    }
+}
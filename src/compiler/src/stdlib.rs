@@ -4,6 +4,20 @@
 use crate::ir::{Function, Inst, Value};
 use std::collections::HashMap;
 
+/// Take the next unused register of `function`.
+fn fresh_reg(function: &mut Function) -> Value {
+    let reg = Value::Reg(function.next_reg);
+    function.next_reg += 1;
+    reg
+}
+
+/// Take the next unused pointer register of `function`.
+fn fresh_ptr(function: &mut Function) -> Value {
+    let ptr = Value::Reg(function.next_ptr);
+    function.next_ptr += 1;
+    ptr
+}
+
 /// Built-in Vec<T> implementation
 pub struct VecType {
     pub element_type: String,
@@ -51,27 +65,32 @@ impl VecType {
     }
 
     /// Generate LLVM IR for Vec method call
-    pub fn generate_method_call(&self, method: &str, args: &[Value]) -> Vec<Inst> {
+    pub fn generate_method_call(
+        &self,
+        method: &str,
+        args: &[Value],
+        function: &mut Function,
+    ) -> Vec<Inst> {
         match self.methods.get(method) {
-            Some(VecMethod::New) => self.generate_vec_new(),
+            Some(VecMethod::New) => self.generate_vec_new(function),
             Some(VecMethod::Push) => self.generate_vec_push(args),
-            Some(VecMethod::Pop) => self.generate_vec_pop(args),
-            Some(VecMethod::Len) => self.generate_vec_len(args),
-            Some(VecMethod::Capacity) => self.generate_vec_capacity(args),
-            Some(VecMethod::IsEmpty) => self.generate_vec_is_empty(args),
+            Some(VecMethod::Pop) => self.generate_vec_pop(args, function),
+            Some(VecMethod::Len) => self.generate_vec_len(args, function),
+            Some(VecMethod::Capacity) => self.generate_vec_capacity(args, function),
+            Some(VecMethod::IsEmpty) => self.generate_vec_is_empty(args, function),
             Some(VecMethod::Clear) => self.generate_vec_clear(args),
-            Some(VecMethod::Get) => self.generate_vec_get(args),
+            Some(VecMethod::Get) => self.generate_vec_get(args, function),
             Some(VecMethod::Insert) => self.generate_vec_insert(args),
-            Some(VecMethod::Remove) => self.generate_vec_remove(args),
-            Some(VecMethod::Contains) => self.generate_vec_contains(args),
-            Some(VecMethod::Iter) => self.generate_vec_iter(args),
+            Some(VecMethod::Remove) => self.generate_vec_remove(args, function),
+            Some(VecMethod::Contains) => self.generate_vec_contains(args, function),
+            Some(VecMethod::Iter) => self.generate_vec_iter(args, function),
             None => panic!("Unknown Vec method: {}", method),
         }
     }
 
-    fn generate_vec_new(&self) -> Vec<Inst> {
+    fn generate_vec_new(&self, function: &mut Function) -> Vec<Inst> {
         vec![Inst::VecAlloca {
-            result: Value::Reg(1),
+            result: fresh_reg(function),
             element_type: self.element_type.clone(),
         }]
     }
@@ -86,50 +105,52 @@ impl VecType {
         }]
     }
 
-    fn generate_vec_pop(&self, args: &[Value]) -> Vec<Inst> {
+    fn generate_vec_pop(&self, args: &[Value], function: &mut Function) -> Vec<Inst> {
         if args.len() != 1 {
             panic!("Vec::pop requires 1 argument (self)");
         }
         vec![Inst::VecPop {
-            result: Value::Reg(2),
+            result: fresh_reg(function),
             vec_ptr: args[0].clone(),
         }]
     }
 
-    fn generate_vec_len(&self, args: &[Value]) -> Vec<Inst> {
+    fn generate_vec_len(&self, args: &[Value], function: &mut Function) -> Vec<Inst> {
         if args.len() != 1 {
             panic!("Vec::len requires 1 argument (self)");
         }
         vec![Inst::VecLength {
-            result: Value::Reg(3),
+            result: fresh_reg(function),
             vec_ptr: args[0].clone(),
         }]
     }
 
-    fn generate_vec_capacity(&self, args: &[Value]) -> Vec<Inst> {
+    fn generate_vec_capacity(&self, args: &[Value], function: &mut Function) -> Vec<Inst> {
         if args.len() != 1 {
             panic!("Vec::capacity requires 1 argument (self)");
         }
         vec![Inst::VecCapacity {
-            result: Value::Reg(4),
+            result: fresh_reg(function),
             vec_ptr: args[0].clone(),
         }]
     }
 
-    fn generate_vec_is_empty(&self, args: &[Value]) -> Vec<Inst> {
+    fn generate_vec_is_empty(&self, args: &[Value], function: &mut Function) -> Vec<Inst> {
         if args.len() != 1 {
             panic!("Vec::is_empty requires 1 argument (self)");
         }
+        let length = fresh_reg(function);
         vec![
             Inst::VecLength {
-                result: Value::Reg(5),
+                result: length.clone(),
                 vec_ptr: args[0].clone(),
             },
-            Inst::FCmp {
-                op: "oeq".to_string(),
-                result: Value::Reg(6),
-                left: Value::Reg(5),
-                right: Value::ImmFloat(0.0),
+            // Lengths are integers, so compare with `icmp`
+            Inst::ICmp {
+                op: "eq".to_string(),
+                result: fresh_reg(function),
+                left: length,
+                right: Value::ImmInt(0),
             },
         ]
     }
@@ -144,15 +165,75 @@ impl VecType {
         ]
     }
 
-    fn generate_vec_get(&self, args: &[Value]) -> Vec<Inst> {
+    fn generate_vec_get(&self, args: &[Value], function: &mut Function) -> Vec<Inst> {
         if args.len() != 2 {
             panic!("Vec::get requires 2 arguments (self, index)");
         }
-        vec![Inst::VecAccess {
-            result: Value::Reg(7),
-            vec_ptr: args[0].clone(),
-            index: args[1].clone(),
-        }]
+        // Every call gets its own labels so two `get`s can share a function.
+        let get_id = function.next_reg;
+        let length = fresh_reg(function);
+        let non_negative = fresh_reg(function);
+        let below_length = fresh_reg(function);
+        let in_bounds = fresh_reg(function);
+        let element = fresh_reg(function);
+        let in_bounds_label = format!("get_in_bounds_{}", get_id);
+        let out_of_bounds_label = format!("get_out_of_bounds_{}", get_id);
+        let end_label = format!("end_get_{}", get_id);
+        vec![
+            // Bounds check: 0 <= index < len
+            Inst::VecLength {
+                result: length.clone(),
+                vec_ptr: args[0].clone(),
+            },
+            Inst::FCmp {
+                op: "oge".to_string(),
+                result: non_negative.clone(),
+                left: args[1].clone(),
+                right: Value::ImmFloat(0.0),
+            },
+            Inst::FCmp {
+                op: "olt".to_string(),
+                result: below_length.clone(),
+                left: args[1].clone(),
+                right: length,
+            },
+            Inst::And {
+                result: in_bounds.clone(),
+                left: non_negative,
+                right: below_length,
+            },
+            Inst::Branch {
+                condition: in_bounds,
+                true_label: in_bounds_label.clone(),
+                false_label: out_of_bounds_label.clone(),
+            },
+            // Only read the element once the index is known to be valid
+            Inst::Label(in_bounds_label),
+            Inst::VecAccess {
+                result: element.clone(),
+                vec_ptr: args[0].clone(),
+                index: args[1].clone(),
+            },
+            // Create Some(element)
+            Inst::EnumConstruct {
+                result: fresh_reg(function),
+                enum_name: format!("Option<{}>", self.element_type),
+                variant_name: "Some".to_string(),
+                variant_index: 1,
+                data: vec![element],
+            },
+            Inst::Jump(end_label.clone()),
+            Inst::Label(out_of_bounds_label),
+            // Create None
+            Inst::EnumConstruct {
+                result: fresh_reg(function),
+                enum_name: format!("Option<{}>", self.element_type),
+                variant_name: "None".to_string(),
+                variant_index: 0,
+                data: vec![],
+            },
+            Inst::Label(end_label),
+        ]
     }
 
     fn generate_vec_insert(&self, args: &[Value]) -> Vec<Inst> {
@@ -166,39 +247,65 @@ impl VecType {
         }]
     }
 
-    fn generate_vec_remove(&self, args: &[Value]) -> Vec<Inst> {
+    fn generate_vec_remove(&self, args: &[Value], function: &mut Function) -> Vec<Inst> {
         if args.len() != 2 {
             panic!("Vec::remove requires 2 arguments (self, index)");
         }
         // Simplified implementation - should shift elements
         vec![Inst::VecPop {
-            result: Value::Reg(8),
+            result: fresh_reg(function),
             vec_ptr: args[0].clone(),
         }]
     }
 
-    fn generate_vec_contains(&self, args: &[Value]) -> Vec<Inst> {
+    fn generate_vec_contains(&self, args: &[Value], function: &mut Function) -> Vec<Inst> {
         if args.len() != 2 {
             panic!("Vec::contains requires 2 arguments (self, value)");
         }
         // Simplified implementation - should iterate and compare
         vec![Inst::FCmp {
             op: "oeq".to_string(),
-            result: Value::Reg(9),
+            result: fresh_reg(function),
             left: args[1].clone(),
             right: Value::ImmFloat(0.0),
         }]
     }
 
-    fn generate_vec_iter(&self, args: &[Value]) -> Vec<Inst> {
+    fn generate_vec_iter(&self, args: &[Value], function: &mut Function) -> Vec<Inst> {
         if args.len() != 1 {
             panic!("Vec::iter requires 1 argument (self)");
         }
-        // Return iterator (simplified)
-        vec![Inst::VecLength {
-            result: Value::Reg(10),
-            vec_ptr: args[0].clone(),
-        }]
+        // A `{ vec_ptr, index }` cursor in the layout the IR generator gives
+        // `Ty::Iter`, starting at the first element.
+        let iter = fresh_ptr(function);
+        let packed = fresh_reg(function);
+        let vec_field = fresh_ptr(function);
+        let index_field = fresh_ptr(function);
+        vec![
+            Inst::AllocaStruct {
+                result: iter.clone(),
+                struct_type: "vec.iter".to_string(),
+            },
+            Inst::PtrToSlot {
+                result: packed.clone(),
+                ptr: args[0].clone(),
+                struct_type: None,
+            },
+            Inst::GetFieldPtr {
+                result: vec_field.clone(),
+                base: iter.clone(),
+                field_index: 0,
+                struct_type: "vec.iter".to_string(),
+            },
+            Inst::Store(vec_field, packed),
+            Inst::GetFieldPtr {
+                result: index_field.clone(),
+                base: iter,
+                field_index: 1,
+                struct_type: "vec.iter".to_string(),
+            },
+            Inst::Store(index_field, Value::ImmInt(0)),
+        ]
     }
 }
 
@@ -245,11 +352,11 @@ impl ArrayOps {
                     result: Value::Reg(17),
                     array_ptr: args[0].clone(),
                 },
-                Inst::FCmp {
-                    op: "oeq".to_string(),
+                Inst::ICmp {
+                    op: "eq".to_string(),
                     result: Value::Reg(18),
                     left: Value::Reg(17),
-                    right: Value::ImmFloat(0.0),
+                    right: Value::ImmInt(0),
                 },
             ],
             "first" => vec![Inst::ArrayAccess {
@@ -288,24 +395,32 @@ pub struct StringOps;
 
 impl StringOps {
     /// Generate string concatenation
-    pub fn generate_concat(left: Value, right: Value) -> Vec<Inst> {
-        vec![
-            // Simplified string concatenation - should allocate new string
-            Inst::Alloca(Value::Reg(24), "concat_result".to_string()),
-            // Copy left string (simplified)
-            Inst::Store(Value::Reg(24), left),
-            // Append right string (simplified)
-            Inst::Store(Value::Reg(25), right),
-        ]
+    pub fn generate_concat(result: Value, left: Value, right: Value) -> Vec<Inst> {
+        vec![Inst::StrConcat {
+            result,
+            left,
+            right,
+        }]
     }
 
-    /// Generate string length
+    /// Generate string length: a literal's is known here, any other string
+    /// is measured at run time
     pub fn generate_len(string: Value) -> Vec<Inst> {
-        vec![
-            // String length (simplified - should access string metadata)
+        let (mut instructions, length) = match string {
+            Value::ImmString(literal) => (Vec::new(), Value::ImmInt(literal.len() as i64)),
+            string => (
+                vec![Inst::StrLen {
+                    result: Value::Reg(25),
+                    source: string,
+                }],
+                Value::Reg(25),
+            ),
+        };
+        instructions.extend([
             Inst::Alloca(Value::Reg(26), "string_len".to_string()),
-            Inst::Store(Value::Reg(26), Value::ImmFloat(10.0)), // Placeholder length
-        ]
+            Inst::Store(Value::Reg(26), length),
+        ]);
+        instructions
     }
 
     /// Generate string slicing
@@ -318,29 +433,31 @@ impl StringOps {
         ]
     }
 
-    /// Generate string comparison
-    pub fn generate_eq(left: Value, right: Value) -> Vec<Inst> {
-        vec![
-            // String comparison (simplified - should compare byte by byte)
-            Inst::FCmp {
-                op: "oeq".to_string(),
-                result: Value::Reg(30),
-                left,
-                right,
-            },
-        ]
+    /// Generate string equality: `result` is true when both strings have
+    /// the same length and bytes, not merely the same address
+    pub fn generate_eq(result: Value, left: Value, right: Value) -> Vec<Inst> {
+        vec![Inst::StrEq {
+            result,
+            left,
+            right,
+        }]
     }
 
     /// Generate string method calls
     pub fn generate_method_call(method: &str, args: &[Value]) -> Vec<Inst> {
         match method {
             "len" => Self::generate_len(args[0].clone()),
-            "is_empty" => vec![Inst::FCmp {
-                op: "oeq".to_string(),
-                result: Value::Reg(31),
-                left: args[0].clone(),
-                right: Value::ImmFloat(0.0),
-            }],
+            "is_empty" => {
+                let mut instructions = Self::generate_len(args[0].clone());
+                instructions.push(Inst::Load(Value::Reg(30), Value::Reg(26)));
+                instructions.push(Inst::ICmp {
+                    op: "eq".to_string(),
+                    result: Value::Reg(31),
+                    left: Value::Reg(30),
+                    right: Value::ImmInt(0),
+                });
+                instructions
+            }
             "chars" => vec![
                 // Return character iterator (simplified)
                 Inst::Alloca(Value::Reg(32), "char_iter".to_string()),
@@ -489,6 +606,7 @@ impl CollectionLibrary {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
 
     #[test]
     fn test_vec_type_creation() {
@@ -502,10 +620,128 @@ mod tests {
     #[test]
     fn test_vec_method_generation() {
         let vec_type = VecType::new("f64".to_string());
-        let instructions = vec_type.generate_method_call("new", &[]);
+        let instructions = vec_type.generate_method_call("new", &[], &mut empty_function());
         assert!(!instructions.is_empty());
     }
 
+    fn empty_function() -> Function {
+        Function {
+            name: "main".to_string(),
+            body: Vec::new(),
+            next_reg: 0,
+            next_ptr: 0,
+        }
+    }
+
+    #[test]
+    fn test_vec_methods_never_reuse_result_registers() {
+        let vec_type = VecType::new("i32".to_string());
+        let mut function = empty_function();
+        let mut instructions = Vec::new();
+        let mut vecs = Vec::new();
+        for _ in 0..2 {
+            let new = vec_type.generate_method_call("new", &[], &mut function);
+            let Some(Inst::VecAlloca { result, .. }) = new.first() else {
+                panic!("Vec::new allocates a vec");
+            };
+            vecs.push(result.clone());
+            instructions.extend(new);
+        }
+        for (vec_ptr, value) in vecs.iter().zip([1, 2]) {
+            let args = [vec_ptr.clone(), Value::ImmInt(value)];
+            instructions.extend(vec_type.generate_method_call("push", &args, &mut function));
+        }
+        for vec_ptr in &vecs {
+            for method in ["len", "pop", "is_empty"] {
+                let args = [vec_ptr.clone()];
+                instructions.extend(vec_type.generate_method_call(method, &args, &mut function));
+            }
+            let args = [vec_ptr.clone(), Value::ImmFloat(0.0)];
+            instructions.extend(vec_type.generate_method_call("get", &args, &mut function));
+        }
+
+        let results: Vec<u32> = instructions
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::VecAlloca {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::VecPop {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::VecLength {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::VecAccess {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::FCmp {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::ICmp {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::And {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::EnumConstruct {
+                    result: Value::Reg(r),
+                    ..
+                } => Some(*r),
+                _ => None,
+            })
+            .collect();
+        assert_ne!(vecs[0], vecs[1]);
+        let unique: HashSet<&u32> = results.iter().collect();
+        assert_eq!(unique.len(), results.len(), "reused a result register");
+
+        let labels: Vec<_> = instructions
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::Label(label) => Some(label),
+                _ => None,
+            })
+            .collect();
+        let unique_labels: HashSet<_> = labels.iter().collect();
+        assert_eq!(unique_labels.len(), labels.len(), "reused a label");
+    }
+
+    #[test]
+    fn test_vec_get_reads_only_in_bounds() {
+        let vec_type = VecType::new("i32".to_string());
+        let instructions = vec_type.generate_method_call(
+            "get",
+            &[Value::Reg(1), Value::Reg(2)],
+            &mut empty_function(),
+        );
+
+        let branch = instructions
+            .iter()
+            .position(|inst| matches!(inst, Inst::Branch { .. }))
+            .expect("get should branch on the bounds check");
+        let access = instructions
+            .iter()
+            .position(|inst| matches!(inst, Inst::VecAccess { .. }))
+            .unwrap();
+        assert!(branch < access, "element must not be read before the check");
+
+        let variants: Vec<&str> = instructions
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::EnumConstruct { variant_name, .. } => Some(variant_name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(variants, ["Some", "None"]);
+    }
+
     #[test]
     fn test_array_operations() {
         let instructions = ArrayOps::generate_method_call("len", &[Value::Reg(1)]);
@@ -518,12 +754,255 @@ mod tests {
         assert!(!instructions.is_empty());
     }
 
+    /// Where `run` left a generated instruction sequence.
+    #[derive(Debug, PartialEq)]
+    enum Outcome {
+        /// Ran off the end, with the last comparison made and the last
+        /// variant payload read on the way.
+        Finished {
+            compared: Option<bool>,
+            payload: Option<Value>,
+        },
+        /// Took a panic path with this message.
+        Panicked(Value),
+        /// Constructed this enum variant and data.
+        Constructed(String, Vec<Value>),
+    }
+
+    /// Interpret `instructions`, following branches. Collection lengths and
+    /// enum discriminants read as `input`, variant data as `payload`.
+    /// Integers must only ever be compared as integers.
+    fn run(instructions: &[Inst], input: i64, payload: Value) -> Outcome {
+        let mut regs: HashMap<u32, Value> = HashMap::new();
+        let mut compared = None;
+        let mut extracted = None;
+        let mut pc = 0;
+        while let Some(inst) = instructions.get(pc) {
+            pc += 1;
+            let mut jump_to = None;
+            match inst {
+                Inst::VecLength {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::StrLen {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::ArrayLength {
+                    result: Value::Reg(r),
+                    ..
+                }
+                | Inst::EnumDiscriminant {
+                    result: Value::Reg(r),
+                    ..
+                } => {
+                    regs.insert(*r, Value::ImmInt(input));
+                }
+                Inst::Store(Value::Reg(slot), value) => {
+                    let value = match value {
+                        Value::Reg(r) => regs[r].clone(),
+                        other => other.clone(),
+                    };
+                    regs.insert(*slot, value);
+                }
+                Inst::Load(Value::Reg(r), Value::Reg(slot)) => {
+                    regs.insert(*r, regs[slot].clone());
+                }
+                Inst::ICmp {
+                    op,
+                    result: Value::Reg(r),
+                    left: Value::Reg(l),
+                    right: Value::ImmInt(n),
+                } => {
+                    let equal = regs[l] == Value::ImmInt(*n);
+                    let holds = match op.as_str() {
+                        "eq" => equal,
+                        "ne" => !equal,
+                        _ => panic!("unexpected comparison `{}`", op),
+                    };
+                    regs.insert(*r, Value::ImmInt(holds as i64));
+                    compared = Some(holds);
+                }
+                Inst::FCmp { .. } => panic!("compared an integer as a float"),
+                Inst::Branch {
+                    condition: Value::Reg(c),
+                    true_label,
+                    false_label,
+                } => {
+                    let holds = regs[c] != Value::ImmInt(0);
+                    jump_to = Some(if holds { true_label } else { false_label });
+                }
+                Inst::Jump(label) => jump_to = Some(label),
+                Inst::EnumVariantData {
+                    result: Value::Reg(r),
+                    variant_index,
+                    ..
+                } => {
+                    assert_eq!(*variant_index as i64, input);
+                    regs.insert(*r, payload.clone());
+                    extracted = Some(payload.clone());
+                }
+                Inst::Call {
+                    function,
+                    arguments,
+                    ..
+                } if function == "panic" => {
+                    assert_eq!(instructions.get(pc), Some(&Inst::Unreachable));
+                    return Outcome::Panicked(arguments[0].clone());
+                }
+                Inst::EnumConstruct {
+                    variant_name, data, ..
+                } => {
+                    let data = data
+                        .iter()
+                        .map(|value| match value {
+                            Value::Reg(r) => regs.get(r).cloned().unwrap_or(value.clone()),
+                            other => other.clone(),
+                        })
+                        .collect();
+                    return Outcome::Constructed(variant_name.clone(), data);
+                }
+                _ => {}
+            }
+            if let Some(target) = jump_to {
+                pc = instructions
+                    .iter()
+                    .position(|inst| *inst == Inst::Label(target.clone()))
+                    .expect("branch target exists");
+            }
+        }
+        Outcome::Finished {
+            compared,
+            payload: extracted,
+        }
+    }
+
+    /// Whether an `is_empty` sequence finds a collection of `length` empty.
+    fn is_empty_for(instructions: &[Inst], length: i64) -> bool {
+        match run(instructions, length, Value::ImmInt(0)) {
+            Outcome::Finished {
+                compared: Some(is_empty),
+                ..
+            } => is_empty,
+            other => panic!("is_empty produced {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_is_empty_compares_integer_lengths() {
+        let vec_type = VecType::new("i32".to_string());
+        let vec_is_empty =
+            vec_type.generate_method_call("is_empty", &[Value::Reg(1)], &mut empty_function());
+        assert!(is_empty_for(&vec_is_empty, 0));
+        assert!(!is_empty_for(&vec_is_empty, 3));
+
+        let array_is_empty = ArrayOps::generate_method_call("is_empty", &[Value::Reg(1)]);
+        assert!(is_empty_for(&array_is_empty, 0));
+        assert!(!is_empty_for(&array_is_empty, 3));
+
+        let string_is_empty = |literal: &str| {
+            let instructions = StringOps::generate_method_call(
+                "is_empty",
+                &[Value::ImmString(literal.to_string())],
+            );
+            is_empty_for(&instructions, 0)
+        };
+        assert!(string_is_empty(""));
+        assert!(!string_is_empty("abc"));
+
+        let runtime_is_empty = StringOps::generate_method_call("is_empty", &[Value::Reg(1)]);
+        assert!(is_empty_for(&runtime_is_empty, 0));
+        assert!(!is_empty_for(&runtime_is_empty, 3));
+    }
+
     #[test]
     fn test_collection_library() {
         let mut library = CollectionLibrary::new();
         library.register_vec_type("i32".to_string());
         assert!(library.get_vec_type("i32").is_some());
     }
+
+    #[test]
+    fn test_option_ok_or_converts_to_result() {
+        let option = OptionImpl::new("i32".to_string());
+        let nope = Value::ImmString("nope".to_string());
+        let ok_or = option.generate_method_call("ok_or", &[Value::Reg(1), nope.clone()]);
+
+        assert_eq!(
+            run(&ok_or, 1, Value::ImmInt(5)),
+            Outcome::Constructed("Ok".to_string(), vec![Value::ImmInt(5)])
+        );
+        assert_eq!(
+            run(&ok_or, 0, Value::ImmInt(0)),
+            Outcome::Constructed("Err".to_string(), vec![nope])
+        );
+    }
+
+    #[test]
+    fn test_option_ok_or_else_calls_closure_only_for_none() {
+        let option = OptionImpl::new("i32".to_string());
+        let ok_or_else = option.generate_method_call("ok_or_else", &[Value::Reg(1), Value::Reg(2)]);
+
+        assert_eq!(
+            run(&ok_or_else, 1, Value::ImmInt(5)),
+            Outcome::Constructed("Ok".to_string(), vec![Value::ImmInt(5)])
+        );
+        let closure_result = ok_or_else.iter().find_map(|inst| match inst {
+            Inst::Call {
+                result, function, ..
+            } if function == "closure_call" => result.clone(),
+            _ => None,
+        });
+        assert_eq!(
+            run(&ok_or_else, 0, Value::ImmInt(0)),
+            Outcome::Constructed(
+                "Err".to_string(),
+                vec![closure_result.expect("closure is called")]
+            )
+        );
+    }
+
+    #[test]
+    fn test_result_unwrap_err_extracts_err_payload() {
+        let result = ResultImpl::new("i32".to_string(), "i32".to_string());
+        let unwrap_err = result.generate_method_call("unwrap_err", &[Value::Reg(1)]);
+        assert!(matches!(
+            run(&unwrap_err, 1, Value::ImmInt(5)),
+            Outcome::Finished {
+                payload: Some(Value::ImmInt(5)),
+                ..
+            }
+        ));
+
+        let message = Value::ImmString("expected an error".to_string());
+        let expect_err = result.generate_method_call("expect_err", &[Value::Reg(1), message]);
+        assert!(matches!(
+            run(&expect_err, 1, Value::ImmInt(5)),
+            Outcome::Finished {
+                payload: Some(Value::ImmInt(5)),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_result_unwrap_err_on_ok_panics() {
+        let result = ResultImpl::new("i32".to_string(), "i32".to_string());
+        let unwrap_err = result.generate_method_call("unwrap_err", &[Value::Reg(1)]);
+        assert!(matches!(
+            run(&unwrap_err, 0, Value::ImmInt(1)),
+            Outcome::Panicked(Value::ImmString(message)) if message.contains("unwrap_err")
+        ));
+
+        let message = Value::ImmString("expected an error".to_string());
+        let expect_err =
+            result.generate_method_call("expect_err", &[Value::Reg(1), message.clone()]);
+        assert_eq!(
+            run(&expect_err, 0, Value::ImmInt(1)),
+            Outcome::Panicked(message)
+        );
+    }
 }
 
 // Built-in Result<T, E> type for error handling - Task 12.1
@@ -550,6 +1029,8 @@ pub enum ResultMethod {
     UnwrapOr,
     UnwrapOrElse,
     Expect,
+    UnwrapErr,
+    ExpectErr,
     Map,
     MapErr,
     And,
@@ -571,6 +1052,8 @@ impl ResultImpl {
         methods.insert("unwrap_or".to_string(), ResultMethod::UnwrapOr);
         methods.insert("unwrap_or_else".to_string(), ResultMethod::UnwrapOrElse);
         methods.insert("expect".to_string(), ResultMethod::Expect);
+        methods.insert("unwrap_err".to_string(), ResultMethod::UnwrapErr);
+        methods.insert("expect_err".to_string(), ResultMethod::ExpectErr);
         methods.insert("map".to_string(), ResultMethod::Map);
         methods.insert("map_err".to_string(), ResultMethod::MapErr);
         methods.insert("and".to_string(), ResultMethod::And);
@@ -596,6 +1079,8 @@ impl ResultImpl {
             Some(ResultMethod::UnwrapOr) => self.generate_unwrap_or(args),
             Some(ResultMethod::UnwrapOrElse) => self.generate_unwrap_or_else(args),
             Some(ResultMethod::Expect) => self.generate_expect(args),
+            Some(ResultMethod::UnwrapErr) => self.generate_unwrap_err(args),
+            Some(ResultMethod::ExpectErr) => self.generate_expect_err(args),
             Some(ResultMethod::Map) => self.generate_map(args),
             Some(ResultMethod::MapErr) => self.generate_map_err(args),
             Some(ResultMethod::And) => self.generate_and(args),
@@ -906,6 +1391,90 @@ impl ResultImpl {
         ]
     }
 
+    fn generate_unwrap_err(&self, args: &[Value]) -> Vec<Inst> {
+        if args.len() != 1 {
+            panic!("Result::unwrap_err requires 1 argument (self)");
+        }
+        vec![
+            // Check if Err variant
+            Inst::EnumDiscriminant {
+                result: Value::Reg(163),
+                enum_ptr: args[0].clone(),
+            },
+            Inst::ICmp {
+                op: "eq".to_string(),
+                result: Value::Reg(164),
+                left: Value::Reg(163),
+                right: Value::ImmInt(1),
+            },
+            // Branch based on discriminant
+            Inst::Branch {
+                condition: Value::Reg(164),
+                true_label: "unwrap_err_err".to_string(),
+                false_label: "panic_unwrap_err".to_string(),
+            },
+            Inst::Label("unwrap_err_err".to_string()),
+            Inst::EnumVariantData {
+                result: Value::Reg(165),
+                enum_ptr: args[0].clone(),
+                variant_index: 1,
+            },
+            Inst::Jump("end_unwrap_err".to_string()),
+            Inst::Label("panic_unwrap_err".to_string()),
+            // Panic: called `unwrap_err` on an Ok value
+            Inst::Call {
+                result: None,
+                function: "panic".to_string(),
+                arguments: vec![Value::ImmString(
+                    "called `Result::unwrap_err()` on an `Ok` value".to_string(),
+                )],
+            },
+            Inst::Unreachable,
+            Inst::Label("end_unwrap_err".to_string()),
+        ]
+    }
+
+    fn generate_expect_err(&self, args: &[Value]) -> Vec<Inst> {
+        if args.len() != 2 {
+            panic!("Result::expect_err requires 2 arguments (self, message)");
+        }
+        vec![
+            // Check if Err variant
+            Inst::EnumDiscriminant {
+                result: Value::Reg(166),
+                enum_ptr: args[0].clone(),
+            },
+            Inst::ICmp {
+                op: "eq".to_string(),
+                result: Value::Reg(167),
+                left: Value::Reg(166),
+                right: Value::ImmInt(1),
+            },
+            // Branch based on discriminant
+            Inst::Branch {
+                condition: Value::Reg(167),
+                true_label: "expect_err_err".to_string(),
+                false_label: "panic_expect_err".to_string(),
+            },
+            Inst::Label("expect_err_err".to_string()),
+            Inst::EnumVariantData {
+                result: Value::Reg(168),
+                enum_ptr: args[0].clone(),
+                variant_index: 1,
+            },
+            Inst::Jump("end_expect_err".to_string()),
+            Inst::Label("panic_expect_err".to_string()),
+            // Panic with custom message
+            Inst::Call {
+                result: None,
+                function: "panic".to_string(),
+                arguments: vec![args[1].clone()],
+            },
+            Inst::Unreachable,
+            Inst::Label("end_expect_err".to_string()),
+        ]
+    }
+
     fn generate_map(&self, args: &[Value]) -> Vec<Inst> {
         if args.len() != 2 {
             panic!("Result::map requires 2 arguments (self, closure)");
@@ -1236,6 +1805,8 @@ pub enum OptionMethod {
     Filter,
     Take,
     Replace,
+    OkOr,
+    OkOrElse,
 }
 
 impl OptionImpl {
@@ -1259,6 +1830,8 @@ impl OptionImpl {
         methods.insert("filter".to_string(), OptionMethod::Filter);
         methods.insert("take".to_string(), OptionMethod::Take);
         methods.insert("replace".to_string(), OptionMethod::Replace);
+        methods.insert("ok_or".to_string(), OptionMethod::OkOr);
+        methods.insert("ok_or_else".to_string(), OptionMethod::OkOrElse);
 
         OptionImpl {
             inner_type,
@@ -1285,6 +1858,8 @@ impl OptionImpl {
             Some(OptionMethod::Filter) => self.generate_filter(args),
             Some(OptionMethod::Take) => self.generate_take(args),
             Some(OptionMethod::Replace) => self.generate_replace(args),
+            Some(OptionMethod::OkOr) => self.generate_ok_or(args),
+            Some(OptionMethod::OkOrElse) => self.generate_ok_or_else(args),
             None => panic!("Unknown Option method: {}", method),
         }
     }
@@ -1992,6 +2567,111 @@ impl OptionImpl {
             Inst::Store(args[0].clone(), Value::Reg(270)),
         ]
     }
+
+    fn generate_ok_or(&self, args: &[Value]) -> Vec<Inst> {
+        if args.len() != 2 {
+            panic!("Option::ok_or requires 2 arguments (self, err)");
+        }
+        vec![
+            // Check if Some variant
+            Inst::EnumDiscriminant {
+                result: Value::Reg(271),
+                enum_ptr: args[0].clone(),
+            },
+            Inst::ICmp {
+                op: "eq".to_string(),
+                result: Value::Reg(272),
+                left: Value::Reg(271),
+                right: Value::ImmInt(1),
+            },
+            // Branch based on discriminant
+            Inst::Branch {
+                condition: Value::Reg(272),
+                true_label: "ok_or_some".to_string(),
+                false_label: "ok_or_none".to_string(),
+            },
+            Inst::Label("ok_or_some".to_string()),
+            // Extract Some value and wrap it in Ok
+            Inst::EnumVariantData {
+                result: Value::Reg(273),
+                enum_ptr: args[0].clone(),
+                variant_index: 1,
+            },
+            Inst::EnumConstruct {
+                result: Value::Reg(274),
+                enum_name: format!("Result<{}, E>", self.inner_type),
+                variant_name: "Ok".to_string(),
+                variant_index: 0,
+                data: vec![Value::Reg(273)],
+            },
+            Inst::Jump("end_ok_or".to_string()),
+            Inst::Label("ok_or_none".to_string()),
+            // Return Err with the provided value
+            Inst::EnumConstruct {
+                result: Value::Reg(275),
+                enum_name: format!("Result<{}, E>", self.inner_type),
+                variant_name: "Err".to_string(),
+                variant_index: 1,
+                data: vec![args[1].clone()],
+            },
+            Inst::Label("end_ok_or".to_string()),
+        ]
+    }
+
+    fn generate_ok_or_else(&self, args: &[Value]) -> Vec<Inst> {
+        if args.len() != 2 {
+            panic!("Option::ok_or_else requires 2 arguments (self, closure)");
+        }
+        vec![
+            // Check if Some variant
+            Inst::EnumDiscriminant {
+                result: Value::Reg(276),
+                enum_ptr: args[0].clone(),
+            },
+            Inst::ICmp {
+                op: "eq".to_string(),
+                result: Value::Reg(277),
+                left: Value::Reg(276),
+                right: Value::ImmInt(1),
+            },
+            // Branch based on discriminant
+            Inst::Branch {
+                condition: Value::Reg(277),
+                true_label: "ok_or_else_some".to_string(),
+                false_label: "ok_or_else_none".to_string(),
+            },
+            Inst::Label("ok_or_else_some".to_string()),
+            // Extract Some value and wrap it in Ok
+            Inst::EnumVariantData {
+                result: Value::Reg(278),
+                enum_ptr: args[0].clone(),
+                variant_index: 1,
+            },
+            Inst::EnumConstruct {
+                result: Value::Reg(279),
+                enum_name: format!("Result<{}, E>", self.inner_type),
+                variant_name: "Ok".to_string(),
+                variant_index: 0,
+                data: vec![Value::Reg(278)],
+            },
+            Inst::Jump("end_ok_or_else".to_string()),
+            Inst::Label("ok_or_else_none".to_string()),
+            // Call closure for the error value and wrap it in Err
+            Inst::Call {
+                result: Some(Value::Reg(280)),
+                function: "closure_call".to_string(),
+                arguments: vec![args[1].clone()],
+            },
+            Inst::EnumConstruct {
+                result: Value::Reg(281),
+                enum_name: format!("Result<{}, E>", self.inner_type),
+                variant_name: "Err".to_string(),
+                variant_index: 1,
+                data: vec![Value::Reg(280)],
+            },
+            Inst::Label("end_ok_or_else".to_string()),
+        ]
+    }
 }
 
 /// Error handling library manager for Result and Option types - Task 12.1
@@ -0,0 +1,82 @@
+// src/compiler/src/test_harness.rs
+
+//! `@test` functions and the programs `aero test` runs them in.
+//!
+//! Each test gets a program of its own: the file with that test unwrapped
+//! into an ordinary function and the file's `main` replaced by a call to it,
+//! so a panicking test aborts only its own process.
+
+use crate::ast::{AstNode, CfgPredicate, Expression, Statement};
+
+/// Names of the `@test` functions in `ast`, in source order.
+pub fn test_names(ast: &[AstNode]) -> Vec<String> {
+    ast.iter()
+        .filter_map(|node| match node {
+            AstNode::Statement(Statement::Cfg {
+                predicate: CfgPredicate::Test,
+                item,
+            }) => match item.as_ref() {
+                Statement::Function { name, .. } => Some(name.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// The program that runs only the test `name`. Other tests stay `@test`
+/// items, so `cfg::strip_inactive_items` drops them as in any other build.
+pub fn harness_program(ast: Vec<AstNode>, name: &str) -> Vec<AstNode> {
+    let mut program: Vec<AstNode> = ast
+        .into_iter()
+        .filter_map(|node| match node {
+            AstNode::Statement(Statement::Cfg {
+                predicate: CfgPredicate::Test,
+                item,
+            }) if matches!(item.as_ref(), Statement::Function { name: n, .. } if n == name) => {
+                Some(AstNode::Statement(*item))
+            }
+            AstNode::Statement(Statement::Function { name, .. }) if name == "main" => None,
+            node => Some(node),
+        })
+        .collect();
+    program.push(AstNode::Statement(Statement::Expression(
+        Expression::FunctionCall {
+            name: name.to_string(),
+            arguments: Vec::new(),
+        },
+    )));
+    program
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cfg, lexer, parser};
+
+    #[test]
+    fn harness_keeps_only_the_selected_test_and_calls_it() {
+        let ast = parser::parse(lexer::tokenize(
+            "fn helper() -> i32 { 1 }
+            @test fn first() { assert!(helper() == 1); }
+            @test fn second() { assert_eq!(helper(), 2); }
+            fn main() -> i32 { helper() }",
+        ));
+        assert_eq!(test_names(&ast), vec!["first", "second"]);
+
+        let program = cfg::strip_inactive_items(harness_program(ast, "second"), &[]);
+        let functions: Vec<&str> = program
+            .iter()
+            .filter_map(|node| match node {
+                AstNode::Statement(Statement::Function { name, .. }) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(functions, vec!["helper", "second"]);
+        assert!(matches!(
+            program.last(),
+            Some(AstNode::Statement(Statement::Expression(Expression::FunctionCall { name, .. })))
+                if name == "second"
+        ));
+    }
+}
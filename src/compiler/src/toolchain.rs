@@ -0,0 +1,113 @@
+// src/compiler/src/toolchain.rs
+
+//! Finding the LLVM tools and linking generated LLVM IR into an executable,
+//! shared by `aero build`/`run` and `runner::run_source`.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Path or name of `tool` if it can be run: from `PATH`, or on Windows from
+/// the usual LLVM install and source-build locations.
+pub fn find_llvm_tool(tool: &str) -> Option<String> {
+    if Command::new(tool).arg("--version").output().is_ok() {
+        return Some(tool.to_string());
+    }
+
+    if cfg!(windows) {
+        let exe_name = format!("{}.exe", tool);
+        let mut candidates = vec![PathBuf::from(r"C:\Program Files\LLVM\bin").join(&exe_name)];
+
+        // Local source-built LLVM fallback used by this repository.
+        if let Ok(repo_root) = env::current_dir() {
+            candidates.push(
+                repo_root
+                    .join("third_party")
+                    .join("llvm-project")
+                    .join("build-rocm-tools")
+                    .join("Release")
+                    .join("bin")
+                    .join(&exe_name),
+            );
+        }
+
+        for candidate in candidates {
+            if candidate.exists() {
+                return Some(candidate.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    None
+}
+
+/// llc's flag for `opt_level`. Level 0 passes none and so keeps llc's
+/// default (`-O2`): its `-O0` instruction selector miscompiles the pointer
+/// round-trips through `double` that the generated code relies on.
+pub fn llc_opt_flag(opt_level: u8) -> Option<String> {
+    (opt_level > 0).then(|| format!("-O{}", opt_level))
+}
+
+/// Lower the LLVM IR in `ll_file` to the executable `exe_file` with llc (via
+/// `obj_file`) and clang, or with clang alone when llc is missing. Returns
+/// whether llc was used.
+pub fn link_executable(
+    ll_file: &Path,
+    obj_file: &Path,
+    exe_file: &Path,
+    opt_level: u8,
+) -> Result<bool, String> {
+    let clang_bin = find_llvm_tool("clang").ok_or_else(|| {
+        "Error executing clang: program not found. Make sure LLVM/clang is installed and in your PATH."
+            .to_string()
+    })?;
+
+    let Some(llc_bin) = find_llvm_tool("llc") else {
+        // Fallback path: clang can compile textual LLVM IR directly.
+        run_tool(
+            Command::new(&clang_bin)
+                .arg(ll_file)
+                .arg("-o")
+                .arg(exe_file),
+            "clang",
+            &clang_bin,
+        )?;
+        return Ok(false);
+    };
+    run_tool(
+        Command::new(&llc_bin)
+            .args(llc_opt_flag(opt_level))
+            .arg("-filetype=obj")
+            .arg(ll_file)
+            .arg("-o")
+            .arg(obj_file),
+        "llc",
+        &llc_bin,
+    )?;
+    run_tool(
+        Command::new(&clang_bin)
+            .arg(obj_file)
+            .arg("-o")
+            .arg(exe_file),
+        "clang",
+        &clang_bin,
+    )?;
+    Ok(true)
+}
+
+/// Run `command`, which runs `tool` as found at `bin`, reporting the tool's
+/// stderr if it fails.
+fn run_tool(command: &mut Command, tool: &str, bin: &str) -> Result<(), String> {
+    let output = command
+        .output()
+        .map_err(|err| format!("Error executing {} ({}): {}", tool, bin, err))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Error running {}: {}",
+            tool,
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
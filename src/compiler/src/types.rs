@@ -5,22 +5,31 @@ use std::fmt;
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Ty {
     Int,
+    Int64, // integers that do not fit in `Int`
     Float,
     Bool,
+    Char,
     String,
     Array(Box<Ty>, usize), // element type, size (fixed-size array)
     Tuple(Vec<Ty>),        // product type
     Struct(String),        // struct name (fields resolved via StructRegistry)
     Enum(String),          // enum name (variants resolved via EnumRegistry)
-    Void,                  // unit / no value
+    Unit,                  // unit `()` / no value
+    Never,                 // `!`: diverges (e.g. `todo!()`), unifies with any type
     // Phase 5: Ownership & borrowing
     Reference(Box<Ty>, bool), // &T (false=immutable) or &mut T (true=mutable)
     TypeParam(String),        // generic type parameter (e.g., T)
+    TraitObject(String),      // `dyn Trait`, dispatched through a vtable
     // Phase 6: Standard library types
     Option(Box<Ty>),           // Option<T> - Some(T) or None
     Result(Box<Ty>, Box<Ty>),  // Result<T, E> - Ok(T) or Err(E)
     Vec(Box<Ty>),              // Vec<T> - dynamic/growable array
     HashMap(Box<Ty>, Box<Ty>), // HashMap<K, V> - key-value store
+    Box(Box<Ty>),              // Box<T> - heap-allocated owner of a T
+    Iter(Box<Ty>, usize),      // `.iter()` cursor over a fixed-size collection of that length
+    Enumerate(Box<Ty>),        // `iter.enumerate()`: (index, item) pairs of the source iterator
+    Zip(Box<Ty>, Box<Ty>),     // `a.zip(b)`: pairs of items until either iterator ends
+    Take(Box<Ty>),             // `iter.take(n)`: at most n items of the source iterator
     // Phase 7: Function pointer type (closures)
     Fn(String), // Function pointer referencing a named function
 }
@@ -29,8 +38,10 @@ impl fmt::Display for Ty {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Ty::Int => f.write_str("int"),
+            Ty::Int64 => f.write_str("i64"),
             Ty::Float => f.write_str("float"),
             Ty::Bool => f.write_str("bool"),
+            Ty::Char => f.write_str("char"),
             Ty::String => f.write_str("String"),
             Ty::Array(elem, size) => write!(f, "[{}; {}]", elem, size),
             Ty::Tuple(elems) => {
@@ -45,7 +56,8 @@ impl fmt::Display for Ty {
             }
             Ty::Struct(name) => write!(f, "{}", name),
             Ty::Enum(name) => write!(f, "{}", name),
-            Ty::Void => f.write_str("()"),
+            Ty::Unit => f.write_str("()"),
+            Ty::Never => f.write_str("!"),
             Ty::Reference(inner, mutable) => {
                 if *mutable {
                     write!(f, "&mut {}", inner)
@@ -54,11 +66,17 @@ impl fmt::Display for Ty {
                 }
             }
             Ty::TypeParam(name) => write!(f, "{}", name),
+            Ty::TraitObject(name) => write!(f, "dyn {}", name),
             // Phase 6: Standard library types
             Ty::Option(inner) => write!(f, "Option<{}>", inner),
             Ty::Result(ok_ty, err_ty) => write!(f, "Result<{}, {}>", ok_ty, err_ty),
             Ty::Vec(elem) => write!(f, "Vec<{}>", elem),
             Ty::HashMap(key, val) => write!(f, "HashMap<{}, {}>", key, val),
+            Ty::Box(inner) => write!(f, "Box<{}>", inner),
+            Ty::Iter(elem, _) => write!(f, "Iter<{}>", elem),
+            Ty::Enumerate(source) => write!(f, "Enumerate<{}>", source),
+            Ty::Zip(a, b) => write!(f, "Zip<{}, {}>", a, b),
+            Ty::Take(source) => write!(f, "Take<{}>", source),
             Ty::Fn(name) => write!(f, "fn({})", name),
         }
     }
@@ -126,32 +144,133 @@ pub enum OwnershipState {
 }
 
 impl Ty {
+    /// Parse a type written out as source, e.g. `HashMap<String, Vec<i32>>`.
+    /// Type arguments are split at the commas outside any nested brackets,
+    /// so they may be generic themselves. Unknown names give `None`.
     pub fn from_string(s: &str) -> Option<Ty> {
+        let s = s.trim();
+        if let Some(inner) = s.strip_prefix("&mut ") {
+            return Some(Ty::Reference(Box::new(Ty::from_string(inner)?), true));
+        }
+        if let Some(inner) = s.strip_prefix('&') {
+            return Some(Ty::Reference(Box::new(Ty::from_string(inner)?), false));
+        }
+        if let Some(inner) = s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            let elems = Self::split_type_arguments(inner)?;
+            return match elems.as_slice() {
+                [] => Some(Ty::Unit),
+                _ => elems
+                    .iter()
+                    .map(|elem| Ty::from_string(elem))
+                    .collect::<Option<_>>()
+                    .map(Ty::Tuple),
+            };
+        }
+        if let Some(inner) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            let (elem, size) = inner.rsplit_once(';')?;
+            let elem = Ty::from_string(elem)?;
+            return Some(Ty::Array(Box::new(elem), size.trim().parse().ok()?));
+        }
+        if let Some((name, args)) = s.strip_suffix('>').and_then(|s| s.split_once('<')) {
+            let args = Self::split_type_arguments(args)?
+                .into_iter()
+                .map(Ty::from_string)
+                .collect::<Option<Vec<_>>>()?;
+            let mut args = args.into_iter().map(Box::new);
+            let ty = match (name.trim(), args.len()) {
+                ("Option", 1) => Ty::Option(args.next()?),
+                ("Vec", 1) => Ty::Vec(args.next()?),
+                ("Box", 1) => Ty::Box(args.next()?),
+                ("Result", 2) => Ty::Result(args.next()?, args.next()?),
+                ("HashMap", 2) => Ty::HashMap(args.next()?, args.next()?),
+                _ => return None,
+            };
+            return Some(ty);
+        }
         match s {
             "int" | "i32" => Some(Ty::Int),
+            "i64" => Some(Ty::Int64),
             "float" | "f64" => Some(Ty::Float),
             "bool" => Some(Ty::Bool),
+            "char" => Some(Ty::Char),
             "String" => Some(Ty::String),
             _ => None,
         }
     }
 
+    /// Split a comma-separated list of types at the commas that are not nested
+    /// inside `<>`, `()` or `[]`. `None` if the brackets do not balance.
+    #[cfg_attr(not(test), allow(dead_code))] // Only reached through `from_string`.
+    fn split_type_arguments(s: &str) -> Option<Vec<&str>> {
+        let mut parts = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            match c {
+                '<' | '(' | '[' => depth += 1,
+                '>' | ')' | ']' => depth = depth.checked_sub(1)?,
+                ',' if depth == 0 => {
+                    parts.push(&s[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if depth != 0 {
+            return None;
+        }
+        // A trailing comma, as in `(i32,)`, adds no element.
+        if !s[start..].trim().is_empty() {
+            parts.push(&s[start..]);
+        }
+        Some(parts)
+    }
+
     /// Returns true if this type is a Copy type (cheap stack copy, no move semantics).
-    /// Copy types: integers, floats, booleans, references, and tuples/arrays of Copy types.
+    /// Copy types: integers, floats, booleans, chars, references, and tuples/arrays of Copy types.
     pub fn is_copy_type(&self) -> bool {
         match self {
-            Ty::Int | Ty::Float | Ty::Bool | Ty::Void => true,
+            Ty::Int | Ty::Int64 | Ty::Float | Ty::Bool | Ty::Char | Ty::Unit | Ty::Never => true,
             Ty::Reference(_, _) => true, // references are always Copy
             Ty::Tuple(elems) => elems.iter().all(|t| t.is_copy_type()),
             Ty::Array(elem, _) => elem.is_copy_type(),
-            // Move types: String, Struct, Enum, Option, Result, Vec, HashMap
+            // Move types: String, Struct, Enum, Option, Result, Vec, HashMap, Box
             Ty::String | Ty::Struct(_) | Ty::Enum(_) => false,
             Ty::Option(_) | Ty::Result(_, _) | Ty::Vec(_) | Ty::HashMap(_, _) => false,
+            Ty::Box(_) => false,
+            // stateful: copies would advance independently
+            Ty::Iter(_, _) | Ty::Enumerate(_) | Ty::Zip(_, _) | Ty::Take(_) => false,
             Ty::TypeParam(_) => false, // conservative: generics are not Copy by default
+            Ty::TraitObject(_) => false, // unsized; only used behind a reference
             Ty::Fn(_) => true,         // function pointers are Copy
         }
     }
 
+    /// The type of the items `next()` yields, if this is an iterator.
+    pub fn iterator_item(&self) -> Option<Ty> {
+        match self {
+            Ty::Iter(elem, _) => Some((**elem).clone()),
+            Ty::Enumerate(source) => Some(Ty::Tuple(vec![Ty::Int, source.iterator_item()?])),
+            Ty::Zip(a, b) => Some(Ty::Tuple(vec![a.iterator_item()?, b.iterator_item()?])),
+            Ty::Take(source) => source.iterator_item(),
+            _ => None,
+        }
+    }
+
+    /// The type of an integer literal without context: `Int` when it fits in
+    /// an i32, `Int64` otherwise.
+    pub fn of_integer_literal(value: i64) -> Ty {
+        if i32::try_from(value).is_ok() {
+            Ty::Int
+        } else {
+            Ty::Int64
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Ty::Int | Ty::Int64)
+    }
+
     /// Returns the inner type if this is a reference, otherwise None.
     pub fn deref_type(&self) -> Option<&Ty> {
         match self {
@@ -164,6 +283,16 @@ impl Ty {
     pub fn is_mut_ref(&self) -> bool {
         matches!(self, Ty::Reference(_, true))
     }
+
+    /// The type an operand of arithmetic, a comparison or a method call is
+    /// used at: `&T` coerces to `T`. Only one level is stripped, so `&&T`
+    /// still needs an explicit `*`.
+    pub fn auto_deref(&self) -> &Ty {
+        match self {
+            Ty::Reference(inner, _) if !matches!(**inner, Ty::Reference(..)) => inner,
+            other => other,
+        }
+    }
 }
 
 /// Type inference and promotion rules for binary operations
@@ -171,9 +300,15 @@ pub fn infer_binary_type(op: &str, lhs: &Ty, rhs: &Ty) -> Result<Ty, String> {
     match op {
         // Arithmetic operations
         "+" | "-" | "*" | "/" | "%" => match (lhs, rhs) {
+            (Ty::String, Ty::String) if op == "+" => Ok(Ty::String), // concatenation
+            (Ty::String, _) | (_, Ty::String) if op == "+" => Err(format!(
+                "Type mismatch in arithmetic operation `+`: {} vs {} (convert to String explicitly to concatenate)",
+                lhs, rhs
+            )),
             (Ty::Int, Ty::Int) => Ok(Ty::Int),
+            (Ty::Int64, Ty::Int64) | (Ty::Int64, Ty::Int) | (Ty::Int, Ty::Int64) => Ok(Ty::Int64), // widen to i64
             (Ty::Float, Ty::Float) => Ok(Ty::Float),
-            (Ty::Int, Ty::Float) | (Ty::Float, Ty::Int) => Ok(Ty::Float), // promote to float
+            (l, Ty::Float) | (Ty::Float, l) if l.is_integer() => Ok(Ty::Float), // promote to float
             _ => Err(format!(
                 "Type mismatch in arithmetic operation `{}`: {} vs {}",
                 op, lhs, rhs
@@ -181,8 +316,12 @@ pub fn infer_binary_type(op: &str, lhs: &Ty, rhs: &Ty) -> Result<Ty, String> {
         },
         // Comparison operations
         "==" | "!=" | "<" | ">" | "<=" | ">=" => match (lhs, rhs) {
-            (Ty::Int, Ty::Int) | (Ty::Float, Ty::Float) | (Ty::Bool, Ty::Bool) => Ok(Ty::Bool),
-            (Ty::Int, Ty::Float) | (Ty::Float, Ty::Int) => Ok(Ty::Bool), // allow comparison with promotion
+            (Ty::Int, Ty::Int)
+            | (Ty::Float, Ty::Float)
+            | (Ty::Bool, Ty::Bool)
+            | (Ty::Char, Ty::Char) => Ok(Ty::Bool),
+            (l, r) if l.is_integer() && r.is_integer() => Ok(Ty::Bool),
+            (l, Ty::Float) | (Ty::Float, l) if l.is_integer() => Ok(Ty::Bool), // allow comparison with promotion
             _ => Err(format!(
                 "Type mismatch in comparison operation `{}`: {} vs {}",
                 op, lhs, rhs
@@ -202,7 +341,7 @@ pub fn infer_binary_type(op: &str, lhs: &Ty, rhs: &Ty) -> Result<Ty, String> {
 
 /// Check if a type promotion is needed from source to target
 pub fn needs_promotion(from: &Ty, to: &Ty) -> bool {
-    matches!((from, to), (Ty::Int, Ty::Float))
+    matches!((from, to), (Ty::Int | Ty::Int64, Ty::Float))
 }
 
 #[cfg(test)]
@@ -245,4 +384,60 @@ mod tests {
         assert_eq!(opt1, opt2);
         assert_ne!(opt1, opt3);
     }
+
+    #[test]
+    fn from_string_parses_nested_generic_types() {
+        let vec_of = |ty| Ty::Vec(Box::new(ty));
+        assert_eq!(
+            Ty::from_string("Vec<Vec<i32>>"),
+            Some(vec_of(vec_of(Ty::Int)))
+        );
+        assert_eq!(
+            Ty::from_string("HashMap<String, Vec<i32>>"),
+            Some(Ty::HashMap(Box::new(Ty::String), Box::new(vec_of(Ty::Int))))
+        );
+        assert_eq!(
+            Ty::from_string("Option<HashMap<i32, f64>>"),
+            Some(Ty::Option(Box::new(Ty::HashMap(
+                Box::new(Ty::Int),
+                Box::new(Ty::Float)
+            ))))
+        );
+        assert_eq!(
+            Ty::from_string("Result<(i32, Vec<bool>), String>"),
+            Some(Ty::Result(
+                Box::new(Ty::Tuple(vec![Ty::Int, vec_of(Ty::Bool)])),
+                Box::new(Ty::String)
+            ))
+        );
+        assert_eq!(
+            Ty::from_string("&mut [Option<char>; 4]"),
+            Some(Ty::Reference(
+                Box::new(Ty::Array(Box::new(Ty::Option(Box::new(Ty::Char))), 4)),
+                true
+            ))
+        );
+    }
+
+    #[test]
+    fn from_string_rejects_unbalanced_or_unknown_types() {
+        for source in [
+            "Vec<Vec<i32>",
+            "HashMap<i32>",
+            "HashMap<i32, Vec<i32>>>",
+            "Vec<>",
+            "Vec<Foo>",
+        ] {
+            assert_eq!(Ty::from_string(source), None, "{}", source);
+        }
+    }
+
+    #[test]
+    fn from_string_reads_back_displayed_types() {
+        let ty = Ty::HashMap(
+            Box::new(Ty::String),
+            Box::new(Ty::Option(Box::new(Ty::Vec(Box::new(Ty::Float))))),
+        );
+        assert_eq!(Ty::from_string(&ty.to_string()), Some(ty));
+    }
 }
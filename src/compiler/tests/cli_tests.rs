@@ -0,0 +1,1584 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// A fresh scratch directory for one test's input and output files.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aero-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("create scratch directory");
+    dir
+}
+
+fn produced_ll_files(dir: &PathBuf) -> usize {
+    fs::read_dir(dir)
+        .expect("read scratch directory")
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "ll"))
+        .count()
+}
+
+#[test]
+fn check_accepts_valid_program_without_generating_ir() {
+    let dir = scratch_dir("check-ok");
+    let input = dir.join("main.aero");
+    fs::write(&input, "fn main() {\n    let x = 1 + 2;\n}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("check")
+        .arg(&input)
+        .output()
+        .expect("run aero check");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(produced_ll_files(&dir), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_rejects_type_error() {
+    let dir = scratch_dir("check-type-error");
+    let input = dir.join("main.aero");
+    fs::write(&input, "fn main() {\n    let y = 1 + true;\n}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("check")
+        .arg(&input)
+        .output()
+        .expect("run aero check");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Type mismatch"));
+    assert_eq!(produced_ll_files(&dir), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_reports_long_operator_chains_instead_of_overflowing() {
+    let dir = scratch_dir("check-long-chain");
+    let input = dir.join("main.aero");
+    let terms = vec!["1"; 2_000].join(" + ");
+    fs::write(&input, format!("fn main() -> i32 {{\n    {}\n}}\n", terms)).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("check")
+        .arg(&input)
+        .output()
+        .expect("run aero check");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(stderr.contains("expression nesting too deep"), "{}", stderr);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// `aero run` links with clang; tests that run programs are skipped without it.
+fn clang_available() -> bool {
+    Command::new("clang").arg("--version").output().is_ok()
+}
+
+fn llc_available() -> bool {
+    Command::new("llc").arg("--version").output().is_ok()
+}
+
+/// `aero run` `source` with default flags in a scratch directory named after
+/// the test, returning its output.
+fn run_program(name: &str, source: &str) -> Output {
+    let dir = scratch_dir(name);
+    let input = dir.join("main.aero");
+    fs::write(&input, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+    fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+/// Assert that a program run by `run_program` exited with `code`.
+fn assert_exit_code(output: &Output, code: i32) {
+    assert_eq!(
+        output.status.code(),
+        Some(code),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn build_emit_asm_writes_native_assembly() {
+    if !llc_available() {
+        eprintln!("skipping: llc not found");
+        return;
+    }
+    let dir = scratch_dir("build-emit-asm");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {\n    let answer = 42;\n    answer\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("build")
+        .arg(&input)
+        .arg("-o")
+        .arg(dir.join("main.ll"))
+        .arg("--emit-asm")
+        .output()
+        .expect("run aero build");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let asm = fs::read_to_string(dir.join("main.s")).expect("assembly file is written");
+    assert!(asm.contains("main:"), "{}", asm);
+    assert!(dir.join("main.ll").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn build_dump_ir_after_prints_the_ir_each_named_pass_produced() {
+    let dir = scratch_dir("build-dump-ir-after");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {\n    let x = 6;\n    let y = x * 7;\n    return y;\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("build")
+        .arg(&input)
+        .arg("-o")
+        .arg(dir.join("main.ll"))
+        .arg("--opt-level=1")
+        .arg("--dump-ir-after=dce")
+        .arg("--dump-ir-after=const-fold")
+        .output()
+        .expect("run aero build");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    // Forwarding the store to `x` leaves a multiply of immediates, which
+    // only const-fold turns into the product.
+    let (before, after) = stderr
+        .split_once("; IR after const-fold\n")
+        .expect("IR is dumped after const-fold");
+    let before = &before[before
+        .find("; IR after dce\n")
+        .expect("IR is dumped after dce")..];
+    assert!(
+        before.contains("Mul(Reg(1), ImmInt(6), ImmInt(7))"),
+        "{}",
+        stderr
+    );
+    assert!(!before.contains("ImmInt(42)"), "{}", stderr);
+    assert!(after.contains("Return(ImmInt(42))"), "{}", stderr);
+    assert!(!after.contains("Mul("), "{}", stderr);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn build_without_input_compiles_the_manifest_root() {
+    let dir = scratch_dir("build-manifest");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(
+        dir.join("aero.toml"),
+        "[package]\nname = \"demo\"\nroot = \"src/main.aero\"\nfeatures = [\"fast\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("src/main.aero"),
+        "@cfg(feature = \"fast\")\nfn fast_path() -> i32 { 7 }\nfn main() -> i32 { fast_path() }\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("build")
+        .output()
+        .expect("run aero build");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let ir = fs::read_to_string(dir.join("demo.ll")).expect("demo.ll is written");
+    assert!(ir.contains("define i32 @fast_path("), "{}", ir);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_exits_with_the_value_main_returns() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-exit-code");
+    let input = dir.join("main.aero");
+    fs::write(&input, "fn main() -> i32 {\n    7\n}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(7),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_with_panic_exit_reports_the_message_and_exits_101() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-panic-exit");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn check(x: i32) -> i32 {\n    assert!(x < 3);\n    x\n}\nfn main() -> i32 {\n    check(5)\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .args(["run", "main.aero", "--panic=exit"])
+        .output()
+        .expect("run aero run --panic=exit");
+
+    assert_eq!(
+        output.status.code(),
+        Some(101),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("thread 'main' panicked at 'assertion failed' main.aero:2:5"),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_keeps_output_printed_before_an_aborting_panic() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-panic-flushes-stdout",
+        "fn main() -> i32 {
+    let a = [1, 2, 3];
+    let mut i = 1;
+    println!(\"before\");
+    i = i + 4;
+    a[i]
+}
+",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Output: before\n"), "{}", stdout);
+    assert!(
+        stdout.contains("panicked at 'index out of bounds: the len is 3'"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn run_reports_where_an_index_panic_happened() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-panic-location");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {\n    let mut a = [1, 2, 3];\n    let i = 5;\n    a[i] = 4;\n    0\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .args(["run", "main.aero"])
+        .output()
+        .expect("run aero run");
+
+    // `aero run` echoes the program's stderr after "Error output:".
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = stdout
+        .split_once("Error output: ")
+        .map(|(_, stderr)| stderr)
+        .unwrap_or_else(|| panic!("the program wrote to stderr\n{}", stdout));
+    assert!(
+        stderr.starts_with(
+            "thread 'main' panicked at 'index out of bounds: the len is 3' main.aero:4:5\n"
+        ),
+        "{}",
+        stdout
+    );
+    assert_ne!(output.status.code(), Some(0));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_panics_on_an_out_of_bounds_index_read() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-index-read-panic",
+        "fn main() -> i32 {\n    let bytes = \"abc\".as_bytes();\n    let i = 5;\n    bytes[i]\n}\n",
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("panicked at 'index out of bounds: the len is 3'")
+            && stdout.contains("main.aero:4:5\n"),
+        "{}",
+        stdout
+    );
+    assert_ne!(output.status.code(), Some(0));
+}
+
+#[test]
+fn run_sums_arrays_through_a_const_generic_length() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-const-generic");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn sum<const N: usize>(a: [i32; N]) -> i32 {
+    let mut total = 0;
+    let mut i = 0;
+    while i < N {
+        total = total + a[i];
+        i = i + 1;
+    }
+    total
+}
+
+fn main() -> i32 {
+    sum([1, 2, 3]) * 10 + sum([1, 2, 3, 4, 5])
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(75),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_compares_strings_by_contents() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-string-eq");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {
+    let a = \"abc\";
+    let b = \"abc\";
+    let mut n = 0;
+    if a == b { n = n + 1; }
+    if a != \"abd\" { n = n + 10; }
+    if \"ab\" == a { n = n + 100; }
+    n
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(11),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_concatenates_strings_with_plus() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-string-concat");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {
+    let a = \"a\";
+    let ab = a + \"b\";
+    let abc = ab + \"c\";
+    let mut n = 0;
+    if ab == \"ab\" { n = n + 1; }
+    if abc == \"abc\" { n = n + 10; }
+    if \"a\" + \"b\" == \"ab\" { n = n + 100; }
+    n
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(111),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_parses_numbers_from_strings() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-parse");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn code(r: Result<i32, ParseError>) -> i32 {
+    match r {
+        Ok(n) => n,
+        Err(e) => 100,
+    }
+}
+
+fn main() -> i32 {
+    let half = match \"3.5\".parse::<f64>() {
+        Ok(x) => x * 2.0,
+        Err(e) => 0.0,
+    };
+    let quarter: Result<f64, ParseError> = \"0.25\".parse();
+    let whole = match quarter {
+        Ok(x) => x * 4.0,
+        Err(e) => 0.0,
+    };
+    let mut n = code(\"42\".parse()) + code(\"abc\".parse()) + code(\"4x\".parse());
+    if half == 7.0 { n = n + 1; }
+    if whole == 1.0 { n = n + 10; }
+    n
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(253),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_evaluates_numeric_builtins() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-numeric-builtins");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn check(x: i32, y: i32, f: f64) -> i32 {
+    let mut n = 0;
+    if max(x, y) == 7 { n = n + 1; }
+    if abs(-4) == 4 { n = n + 2; }
+    if min(2.0, f) == 1.5 { n = n + 4; }
+    if (x - y).abs() == 4 { n = n + 8; }
+    if f.max(-3.0) == 1.5 { n = n + 16; }
+    if abs(0.0 - f) == 1.5 { n = n + 32; }
+    n
+}
+
+fn main() -> i32 {
+    check(3, 7, 1.5)
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(63),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_returns_implicitly_from_unit_and_value_functions() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-implicit-return");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn note(x: i32) -> () {
+    if x > 1 { return; }
+    let y = x + 1;
+}
+
+fn sign(x: i32) -> i32 {
+    if x < 0 { 0 - 1 } else if x == 0 { 0 } else { 1 }
+}
+
+fn main() -> i32 {
+    note(5);
+    note(0);
+    sign(9) + sign(0) * 10 + sign(-4) + 20
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(20),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_converts_strings_and_values_with_to_string_and_as_str() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-to-string");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {
+    let s = \"x\".to_string();
+    let t = s.as_str();
+    let u = String::from(t);
+    let n = 42.to_string();
+    let f = 1.5.to_string();
+    let b = (n == \"42\").to_string();
+    let c = 'z'.to_string();
+    let mut code = 0;
+    if t == \"x\" { code = code + 1; }
+    if u == \"x\" { code = code + 2; }
+    if n == \"42\" { code = code + 4; }
+    if f == \"1.5\" { code = code + 8; }
+    if b == \"true\" { code = code + 16; }
+    if c == \"z\" { code = code + 32; }
+    code
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(63),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_measures_runtime_strings_with_len() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-string-len",
+        "fn main() -> i32 {
+    let greeting = \"hello\".to_string();
+    let line = greeting + \", world\";
+    let digits = 42.to_string();
+    let empty = String::from(\"\");
+    line.len() * 10 + digits.as_str().len() + empty.len()
+}
+",
+    );
+    assert_exit_code(&output, 122);
+}
+
+#[test]
+fn run_is_empty_on_strings_and_vecs() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-is-empty",
+        "fn main() -> i32 {
+    let empty = String::from(\"\");
+    let word = \"ab\".to_string();
+    let none = vec![];
+    let some = vec![1, 2];
+    let mut code = 0;
+    if empty.is_empty() { code = code + 1; }
+    if !word.is_empty() { code = code + 2; }
+    if \"\".is_empty() { code = code + 4; }
+    if !some.is_empty() { code = code + 8; }
+    if none.is_empty() { code = code + 16; }
+    println!(\"{} {}\", empty.is_empty(), some.is_empty());
+    code
+}
+",
+    );
+    assert_exit_code(&output, 31);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Output: true false\n"));
+}
+
+#[test]
+fn run_swaps_scalars_structs_and_vecs() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-swap");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "struct P { x: i32, y: i32 }
+fn main() -> i32 {
+    let mut a = 3;
+    let mut b = 40;
+    swap(&mut a, &mut b);
+    let mut p = P { x: 1, y: 2 };
+    let mut q = P { x: 5, y: 6 };
+    swap(&mut p, &mut q);
+    let mut u = vec![1, 2, 3];
+    let mut v = vec![7, 8, 9];
+    swap(&mut u, &mut v);
+    a - b + p.x + q.y + u[0] + v[2] * 10
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    // 40 - 3 + 5 + 2 + 7 + 30
+    assert_eq!(
+        output.status.code(),
+        Some(81),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_continue_in_a_range_loop_advances_the_counter() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-range-continue");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {
+    let mut seen = 0;
+    let mut skipped = 0;
+    for i in 0..5 {
+        if i == 1 || i == 3 {
+            skipped = skipped + 1;
+            continue;
+        }
+        seen = seen * 10 + i;
+    }
+    let mut total = 0;
+    for j in 1..=4 {
+        total = total + j;
+    }
+    seen + skipped * 100 + total - 10
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    // seen = 24 from 0, 2 and 4; two iterations skipped; 1..=4 sums to 10
+    assert_eq!(
+        output.status.code(),
+        Some(224),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_assigns_tuple_struct_and_named_fields_in_place() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-field-store");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "struct Pair(i32, i32);
+struct Point { x: i32, y: f64 }
+impl Point {
+    fn shift(&mut self, by: i32) { self.x = self.x + by; }
+}
+fn bump(p: &mut Point) { p.x = p.x + 100; }
+fn main() -> i32 {
+    let mut pair = Pair(1, 2);
+    pair.1 = 9;
+    let mut p = Point { x: 1, y: 0.5 };
+    p.x = 5;
+    p.y = 2.5;
+    p.shift(20);
+    bump(&mut p);
+    pair.0 + pair.1 * 10 + p.x
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    // pair is (1, 9); p.x goes 5, 25, 125
+    assert_eq!(
+        output.status.code(),
+        Some(216),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_bare_return_leaves_a_unit_function_early() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-bare-return");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "struct Counter { n: i32 }
+fn bump(c: &mut Counter, stop: i32) {
+    if stop > 0 {
+        return;
+    }
+    c.n = c.n + 1;
+}
+fn main() -> i32 {
+    let mut c = Counter { n: 40 };
+    bump(&mut c, 0);
+    bump(&mut c, 1);
+    bump(&mut c, 0);
+    return c.n;
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(42),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_iterates_vec_elements_in_order() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-vec-iter");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {
+    let v = vec![10, 20, 30];
+    let mut seen = 0;
+    for x in v.iter() {
+        seen = seen * 100 + x;
+    }
+    let it = v.iter();
+    let mut last = 0;
+    for _step in [0, 1, 2] {
+        last = match it.next() { Some(x) => x, None => 0 };
+    }
+    let after = match it.next() { Some(x) => x, None => 1 };
+    seen - 102030 + last + after
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(31),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_iterator_adapters_compose_next() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-iter-adapters");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {
+    let mut enumerated = 0;
+    for (i, x) in [10, 20].iter().enumerate() {
+        enumerated = enumerated * 100 + i * 10 + x;
+    }
+    let a = [1, 2, 3];
+    let b = [4, 5];
+    let mut pairs = 0;
+    let mut dot = 0;
+    for (x, y) in a.iter().zip(b.iter()) {
+        pairs = pairs + 1;
+        dot = dot + x * y;
+    }
+    let mut taken = 0;
+    for x in [7, 8, 9].iter().take(2) {
+        taken = taken * 10 + x;
+    }
+    if enumerated == 1030 && pairs == 2 && dot == 14 && taken == 78 { 42 } else { 1 }
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(42),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_mixed_type_struct_fields_round_trip() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-mixed-struct");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "struct Mixed { flag: bool, big: i64, ratio: f64, count: i32 }
+fn main() -> i32 {
+    let mut m = Mixed { flag: true, big: 5000000000, ratio: 2.5, count: 7 };
+    m.flag = false;
+    m.big = m.big + 1;
+    m.ratio = m.ratio * 4.0;
+    m.count = m.count + 35;
+    if m.flag == false && m.big - 5000000000 == 1 && m.ratio == 10.0 { m.count } else { 1 }
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(42),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_nested_for_loops_keep_their_own_variable() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-nested-for-shadow");
+    let input = dir.join("main.aero");
+    // The inner `i` shadows the outer one only inside the inner loop.
+    fs::write(
+        &input,
+        "fn main() -> i32 {
+    let mut count = 0;
+    let mut outer_sum = 0;
+    let mut inner_sum = 0;
+    for i in 0..2 {
+        for i in 0..2 {
+            count = count + 1;
+            inner_sum = inner_sum + i;
+        }
+        outer_sum = outer_sum + i * 10;
+    }
+    count * 10 + outer_sum + inner_sum
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    // 4 iterations, outer `i` of 0 and 1, inner `i` of 0 and 1 twice.
+    assert_eq!(
+        output.status.code(),
+        Some(52),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_write_appends_to_a_string_buffer() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("run-write-buffer");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() -> i32 {
+    let mut buf = String::from(\"\");
+    write!(buf, \"a{}b\", 1);
+    write!(buf, \"a{}b\", 1);
+    write!(buf, \"a{}b\", 1);
+    writeln!(buf, \"{}\", true);
+    if buf == \"a1ba1ba1btrue\\n\" { 42 } else { 1 }
+}
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("run")
+        .arg(&input)
+        .output()
+        .expect("run aero run");
+
+    assert_eq!(
+        output.status.code(),
+        Some(42),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_destructures_a_returned_tuple_with_default_flags() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-tuple-default-flags",
+        "fn triple() -> (i32, i32, i32) { (1, 2, 3) }
+fn main() -> i32 {
+    let (a, b, c) = triple();
+    a * 100 + b * 10 + c
+}
+",
+    );
+    assert_exit_code(&output, 123);
+}
+
+#[test]
+fn run_reads_through_reference_arguments() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-reference-arguments",
+        "fn add(a: &i32, b: &i32) -> i32 { *a + *b }
+fn main() -> i32 {
+    let x = 3;
+    let y = 4;
+    add(&x, &y)
+}
+",
+    );
+    assert_exit_code(&output, 7);
+}
+
+#[test]
+fn run_returned_structs_outlive_the_callee_frame() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    // `clobber` reuses the stack `mk` and `make` built their results in.
+    let output = run_program(
+        "run-returned-structs",
+        "trait Shape { fn area(&self) -> f64; }
+struct Circle { r: f64 }
+impl Shape for Circle { fn area(&self) -> f64 { self.r * 3.0 } }
+struct P { x: i32, y: i32 }
+fn make() -> impl Shape { Circle { r: 1.0 } }
+fn mk(a: i32) -> P { P { x: a, y: a + 1 } }
+fn clobber(a: i32) -> i32 { let q = P { x: 100, y: 200 }; q.x + a }
+fn main() -> i32 {
+    let p = mk(20);
+    let z = clobber(1);
+    if make().area() == 3.0 { p.x + p.y + z - 100 } else { 1 }
+}
+",
+    );
+    assert_exit_code(&output, 42);
+}
+
+#[test]
+fn run_sorts_and_reverses_vecs_in_place() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-vec-sort-reverse",
+        "fn main() -> i32 {
+    let mut v = vec![5, 3, 9, -1, 3];
+    v.sort();
+    let mut f = vec![2.5, 0.5, 1.5];
+    f.sort();
+    let mut w = vec![1, 2, 3];
+    w.reverse();
+    if v[0] == -1 && v[1] == 3 && v[2] == 3 && v[3] == 5 && v[4] == 9 {
+        if f[0] == 0.5 && f[1] == 1.5 && f[2] == 2.5 && w[0] == 3 && w[1] == 2 && w[2] == 1 {
+            return 42;
+        }
+    }
+    1
+}
+",
+    );
+    assert_exit_code(&output, 42);
+}
+
+#[test]
+fn run_stores_and_reloads_bool_variables() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-bool-variables",
+        "fn pick(flag: bool, a: i32, b: i32) -> i32 {
+    if flag { return a; }
+    return b;
+}
+
+fn main() -> i32 {
+    let x = 2;
+    let b = true;
+    let s = x < 3 && true;
+    let mut done = false;
+    let mut count = 0;
+    while !done {
+        count = count + 1;
+        done = count >= 3 || false;
+    }
+    let mut r = count;
+    if b { r = r + 10; }
+    if s == b { r = r + 100; }
+    r + pick(x > 1, 1000, 0)
+}
+",
+    );
+    assert_exit_code(&output, 1113 % 256);
+}
+
+#[test]
+fn run_reads_bools_through_options_and_references() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-bool-payloads",
+        "fn main() -> i32 {
+    let x = 4;
+    let flags = [x < 3, x > 3];
+    let mut r = 0;
+    match flags.get(1) {
+        Some(f) => { if f { r = r + 5; } }
+        None => { r = r + 50; }
+    };
+    let nums = [7, 8];
+    match nums.get(0) {
+        Some(n) => { r = r + n; }
+        None => { r = r + 50; }
+    };
+    let b = x > 1;
+    r + is_on(&b)
+}
+fn is_on(flag: &bool) -> i32 {
+    if *flag { 100 } else { 0 }
+}
+",
+    );
+    assert_exit_code(&output, 112);
+}
+
+#[test]
+fn run_prints_runtime_bools() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-print-bools",
+        "struct Flags { on: bool }
+fn main() -> i32 {
+    let flag = 1 < 2;
+    let x = 5;
+    let f = Flags { on: x > 9 };
+    println!(\"{} {} {}\", flag, f.on, x == 5);
+    0
+}
+",
+    );
+    assert_exit_code(&output, 0);
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("Output: true false true\n"),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn run_converts_options_to_results_with_ok_or() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let output = run_program(
+        "run-ok-or",
+        "fn main() -> i32 {
+    let some = Some(5);
+    let none: Option<i32> = None;
+    let a = some.ok_or(7);
+    let b = none.ok_or(7);
+    let c = none.ok_or_else(|| 30);
+    let d = some.ok_or_else(|| 30);
+    let mut r = 0;
+    match a {
+        Ok(v) => { r = r + v; }
+        Err(e) => { r = r + 1000; }
+    };
+    match b {
+        Ok(v) => { r = r + 1000; }
+        Err(e) => { r = r + e * 10; }
+    };
+    match c {
+        Ok(v) => { r = r + 1000; }
+        Err(e) => { r = r + e; }
+    };
+    match d {
+        Ok(v) => { r = r + v * 20; }
+        Err(e) => { r = r + 1000; }
+    };
+    match none.ok_or(\"nope\") {
+        Ok(v) => { r = r + 1000; }
+        Err(e) => { if e == \"nope\" { r = r + 1; } }
+    };
+    r
+}
+",
+    );
+    assert_exit_code(&output, 206);
+}
+
+#[test]
+fn run_unwrap_err_yields_the_error_and_panics_on_ok() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let check = "fn check(n: i32) -> Result<i32, i32> {
+    if n > 0 { Ok(n) } else { Err(n - 40) }
+}
+";
+    let output = run_program(
+        "run-unwrap-err",
+        &format!(
+            "{}fn main() -> i32 {{
+    let e = check(-2).unwrap_err();
+    let f = check(0).expect_err(\"zero is fine\");
+    0 - e - f
+}}
+",
+            check
+        ),
+    );
+    assert_exit_code(&output, 82);
+
+    let output = run_program(
+        "run-unwrap-err-on-ok",
+        &format!(
+            "{}fn main() -> i32 {{\n    check(3).unwrap_err()\n}}\n",
+            check
+        ),
+    );
+    assert_ne!(output.status.code(), Some(0));
+    assert!(
+        String::from_utf8_lossy(&output.stdout)
+            .contains("panicked at 'called `Result::unwrap_err()` on an `Ok` value'"),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn test_command_runs_each_test_function_and_reports_failures() {
+    if !clang_available() {
+        eprintln!("skipping: clang not found");
+        return;
+    }
+    let dir = scratch_dir("test-runner");
+    let input = dir.join("math.aero");
+    fs::write(
+        &input,
+        "fn double(x: i32) -> i32 { x * 2 }
+
+@test
+fn doubles_small_numbers() {
+    assert_eq!(double(2), 4);
+    assert!(double(0) == 0);
+}
+
+@test
+fn doubles_wrongly() {
+    assert_eq!(double(3), 7);
+}
+
+fn main() -> i32 { double(1) }
+",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("test")
+        .arg(&input)
+        .output()
+        .expect("run aero test");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success(), "{}", stdout);
+    assert!(
+        stdout.contains("test doubles_small_numbers ... "),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("1 passed, 1 failed, 2 total"), "{}", stdout);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_warn_shadow_points_at_both_declarations() {
+    let dir = scratch_dir("check-warn-shadow");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() {\n    let x = 1;\n    if x > 0 {\n        let x = \"s\";\n    }\n}\n",
+    )
+    .unwrap();
+    let check = |flags: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_aero"))
+            .current_dir(&dir)
+            .arg("check")
+            .arg(&input)
+            .args(flags)
+            .output()
+            .expect("run aero check")
+    };
+
+    let output = check(&["--warn-shadow"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("main.aero:4:13: Variable 'x' of type &str shadows a binding of type i32")
+    );
+    assert!(stderr.contains("previously declared at"));
+    assert!(stderr.contains("main.aero:2:9"));
+
+    let output = check(&[]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("shadows"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_warns_about_a_loop_that_never_terminates() {
+    let dir = scratch_dir("check-infinite-loop");
+    let input = dir.join("main.aero");
+    fs::write(
+        &input,
+        "fn main() {\n    loop {\n        break;\n    }\n    loop {}\n}\n",
+    )
+    .unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .arg("check")
+        .arg(&input)
+        .output()
+        .expect("run aero check");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("main.aero:5:5: this loop never terminates"),
+        "{}",
+        stderr
+    );
+    assert_eq!(stderr.matches("never terminates").count(), 1, "{}", stderr);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_color_flag_controls_diagnostic_escapes() {
+    let dir = scratch_dir("check-color");
+    let input = dir.join("main.aero");
+    fs::write(&input, "fn main() {\n    let = 1;\n}\n").unwrap();
+    let check = |flag: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+            .current_dir(&dir)
+            .arg("check")
+            .arg(&input)
+            .arg(flag)
+            .env_remove("NO_COLOR")
+            .output()
+            .expect("run aero check");
+        assert!(!output.status.success());
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    };
+
+    let plain = check("--color=never");
+    assert!(plain.contains("error: aborting due to"));
+    assert!(!plain.contains('\x1b'));
+
+    let colored = check("--color=always");
+    assert!(colored.contains("\x1b[1;31merror\x1b[0m: aborting due to"));
+
+    // Piped stderr is not a terminal, so `auto` stays plain.
+    assert!(!check("--color=auto").contains('\x1b'));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_fails_on_missing_input() {
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .arg("check")
+        .arg("does-not-exist.aero")
+        .output()
+        .expect("run aero check");
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn emit_deps_lists_each_transitive_module_once() {
+    let dir = scratch_dir("emit-deps");
+    fs::write(dir.join("main.aero"), "mod a;\nmod b;\nfn main() {}\n").unwrap();
+    fs::write(dir.join("a.aero"), "mod c;\nfn a() -> i32 { return 1; }\n").unwrap();
+    fs::write(dir.join("b.aero"), "mod c;\nuse a::a;\nuse std::io;\n").unwrap();
+    fs::write(dir.join("c.aero"), "fn c() -> i32 { return 3; }\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .args(["build", "main.aero", "-o", "main.ll", "--emit=deps"])
+        .output()
+        .expect("run aero build --emit=deps");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "main.ll: main.aero a.aero c.aero b.aero"
+    );
+    assert_eq!(produced_ll_files(&dir), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn emit_deps_reports_module_cycles() {
+    let dir = scratch_dir("emit-deps-cycle");
+    fs::write(dir.join("main.aero"), "mod x;\n").unwrap();
+    fs::write(dir.join("x.aero"), "mod y;\n").unwrap();
+    fs::write(dir.join("y.aero"), "mod x;\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aero"))
+        .current_dir(&dir)
+        .args(["build", "main.aero", "-o", "main.ll", "--emit=deps"])
+        .output()
+        .expect("run aero build --emit=deps");
+
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("Module cycle detected: x.aero -> y.aero -> x.aero"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -59,6 +59,104 @@ fn test_parse_index_access() {
     }
 }
 
+#[test]
+fn test_parse_index_assignment() {
+    let source = "fn main() { let mut v = [1, 2, 3]; v[1] = v[1] + 1; }";
+    let ast = parser::parse(lexer::tokenize(source));
+    let compiler::ast::AstNode::Statement(compiler::ast::Statement::Function { body, .. }) =
+        &ast[0]
+    else {
+        panic!("Expected function definition");
+    };
+    assert!(matches!(
+        &body.statements[1],
+        compiler::ast::Statement::Assign {
+            target: compiler::ast::Expression::IndexAccess { .. },
+            ..
+        }
+    ));
+
+    let result = SemanticAnalyzer::new().analyze(ast);
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[test]
+fn test_semantic_index_assignment_requires_mutable_array() {
+    let source = "fn main() { let v = [1, 2, 3]; v[1] = 5; }";
+    let ast = parser::parse(lexer::tokenize(source));
+    let err = SemanticAnalyzer::new().analyze(ast).unwrap_err();
+    assert_eq!(
+        err,
+        "Error: Cannot assign to an element of immutable variable `v`."
+    );
+}
+
+#[test]
+fn test_semantic_main_must_return_i32_or_unit() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(analyze("fn main() -> i32 { 7 }").is_ok());
+    assert!(analyze("fn main() { let x = 1; }").is_ok());
+    assert_eq!(
+        analyze("fn main() -> f64 { 1.5 }").unwrap_err(),
+        "Error: `main` must return `i32` or `()`, found `float`."
+    );
+}
+
+#[test]
+fn test_semantic_warn_shadow_reports_type_changing_shadow() {
+    use compiler::semantic_analyzer::ShadowWarnings;
+
+    let source = "fn main() { let x = 1; let y = 2; if y > 1 { let x = \"s\"; let y = 3; } }";
+    let shadows = |mode| {
+        let mut analyzer = SemanticAnalyzer::new().with_shadow_warnings(mode);
+        analyzer
+            .analyze(parser::parse(lexer::tokenize(source)))
+            .unwrap();
+        analyzer.shadow_warnings().to_vec()
+    };
+
+    let warnings = shadows(ShadowWarnings::TypeChange);
+    assert_eq!(
+        warnings.len(),
+        1,
+        "same-type re-binding of `y` must not warn"
+    );
+    assert_eq!(warnings[0].name, "x");
+    assert_eq!(warnings[0].previous_type, "i32");
+    assert_eq!(warnings[0].new_type, "&str");
+    assert_eq!(warnings[0].previous_declaration, Some(0));
+    assert_eq!(warnings[0].declaration, 1);
+
+    let all: Vec<String> = shadows(ShadowWarnings::All)
+        .into_iter()
+        .map(|warning| warning.name)
+        .collect();
+    assert_eq!(all, ["x", "y"]);
+    assert!(shadows(ShadowWarnings::Off).is_empty());
+}
+
+#[test]
+fn test_semantic_loop_without_break_never_terminates() {
+    let infinite_loops = |source: &str| {
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer
+            .analyze(parser::parse(lexer::tokenize(source)))
+            .unwrap();
+        analyzer.infinite_loops().to_vec()
+    };
+
+    assert_eq!(infinite_loops("fn main() { loop {} }"), [0]);
+    assert!(infinite_loops("fn main() { loop { break; } }").is_empty());
+    // A `break` in a nested loop only leaves that loop; `return` leaves both.
+    assert_eq!(
+        infinite_loops("fn main() { loop { loop { break; } } }"),
+        [0]
+    );
+    assert!(
+        infinite_loops("fn f() -> i32 { loop { loop { return 1; } } } fn main() {}").is_empty()
+    );
+}
+
 #[test]
 fn test_parse_struct_def() {
     let source = "struct Point { x: i32, y: i32 }";
@@ -697,6 +795,29 @@ fn test_parse_generic_function_multi_params() {
     }
 }
 
+#[test]
+fn test_parse_const_generic_array_length() {
+    let source = "fn sum<const N: usize>(a: [i32; N]) -> i32 { return N; }";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    match &ast[0] {
+        compiler::ast::AstNode::Statement(compiler::ast::Statement::Function {
+            type_params,
+            const_params,
+            parameters,
+            ..
+        }) => {
+            assert!(type_params.is_empty());
+            assert_eq!(const_params, &vec!["N".to_string()]);
+            assert!(matches!(
+                &parameters[0].param_type,
+                compiler::ast::Type::ConstArray(_, n) if n == "N"
+            ));
+        }
+        _ => panic!("Expected const generic function"),
+    }
+}
+
 #[test]
 fn test_parse_generic_struct_definition() {
     let source = "struct Container<T> { value: T }";
@@ -1040,6 +1161,42 @@ fn test_semantic_trait_bound_satisfied_ok() {
     );
 }
 
+#[test]
+fn test_where_clause_bounds_are_checked_at_every_call_site() {
+    let program = |call: &str| {
+        format!(
+            r#"
+            trait Display {{ fn display(&self) -> i32; }}
+            trait Clone {{ fn clone(&self) -> i32; }}
+            fn show<T: Display>(item: &T) -> i32 where T: Clone {{ return 1; }}
+            struct Both {{ x: i32 }}
+            impl Display for Both {{ fn display(&self) -> i32 {{ return 1; }} }}
+            impl Clone for Both {{ fn clone(&self) -> i32 {{ return 2; }} }}
+            struct OnlyDisplay {{ x: i32 }}
+            impl Display for OnlyDisplay {{ fn display(&self) -> i32 {{ return 3; }} }}
+            fn main() {{
+                let both = Both {{ x: 1 }};
+                let half = OnlyDisplay {{ x: 2 }};
+                let shown = {};
+            }}
+        "#,
+            call
+        )
+    };
+    let analyze = |source: String| {
+        let ast = parser::parse(lexer::tokenize(&source));
+        SemanticAnalyzer::new().analyze(ast)
+    };
+
+    assert!(analyze(program("show(&both)")).is_ok());
+    let err = analyze(program("1 + show(&half)")).unwrap_err();
+    assert!(
+        err.contains("the trait bound 'OnlyDisplay: Clone' is not satisfied"),
+        "{}",
+        err
+    );
+}
+
 #[test]
 fn test_parse_trait_bounds_stored_in_ast() {
     // Verify trait bounds are stored in the Function AST node
@@ -1062,3 +1219,1262 @@ fn test_parse_trait_bounds_stored_in_ast() {
         _ => panic!("Expected function with trait bounds"),
     }
 }
+
+#[test]
+fn test_static_array_indexed_from_function_emits_global_constant() {
+    let source = "static TABLE: [i32; 3] = [1, 2, 3];
+        fn get(i: i32) -> i32 { return TABLE[i]; }
+        fn main() { let x = get(2); return x; }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("static table should compile");
+
+    assert!(llvm_ir.contains("@TABLE = constant [3 x double]"));
+    assert!(llvm_ir.contains("[3 x double]* @TABLE, i64 0"));
+}
+
+#[test]
+fn test_semantic_static_requires_constant_initializer() {
+    let source = "let n = 3; static LIMIT: i32 = n;";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    let mut analyzer = SemanticAnalyzer::new();
+    let err = analyzer.analyze(ast).unwrap_err();
+    assert!(err.contains("must be initialized with a constant expression"));
+}
+
+#[test]
+fn test_semantic_mutable_borrow_of_immutable_static_is_rejected() {
+    let source = "static TABLE: [i32; 3] = [1, 2, 3]; let r = &mut TABLE;";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    let mut analyzer = SemanticAnalyzer::new();
+    let err = analyzer.analyze(ast).unwrap_err();
+    assert!(err.contains("not declared as mutable"));
+}
+
+#[test]
+fn test_boxed_cons_list_summed_recursively() {
+    let source = "enum List { Cons(i32, Box<List>), Nil }
+        fn sum(list: List) -> i32 {
+            return match list {
+                List::Cons(value, rest) => value + sum(*rest),
+                List::Nil => 0,
+            };
+        }
+        fn main() {
+            let list = List::Cons(1, Box::new(List::Cons(2, Box::new(List::Nil))));
+            return sum(list);
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("boxed cons-list should compile");
+
+    // Tag plus both payload fields, with the tail stored as a packed pointer.
+    assert!(llvm_ir.contains("%List = type { double, double, double }"));
+    assert!(llvm_ir.contains("declare i8* @malloc(i64)"));
+    assert_eq!(llvm_ir.matches("call i8* @malloc").count(), 2);
+    assert!(llvm_ir.contains("inttoptr i64"));
+    assert!(llvm_ir.contains("call i32 @sum(double"));
+}
+
+#[test]
+fn test_runtime_preamble_declares_only_what_is_used() {
+    let compile = |source| {
+        compiler::compile_program(source, compiler::CompilerOptions::default())
+            .expect("program should compile")
+    };
+
+    let llvm_ir = compile("fn main() { println!(\"{}\", 1); }");
+    assert_eq!(llvm_ir.matches("declare i32 @printf").count(), 1);
+    // Format strings live on the stack, so `printf` is all they need.
+    assert_eq!(
+        llvm_ir
+            .lines()
+            .filter(|line| line.starts_with("declare i"))
+            .count(),
+        1,
+        "{}",
+        llvm_ir
+    );
+
+    // Boxes and string concatenation both allocate, through one `malloc`.
+    let llvm_ir =
+        compile("fn main() -> i32 { let b = Box::new(5); let s = \"a\".to_string() + \"b\"; *b }");
+    assert_eq!(llvm_ir.matches("declare i8* @malloc").count(), 1);
+    assert!(!llvm_ir.contains("@printf"));
+}
+
+#[test]
+fn test_semantic_recursive_enum_requires_box() {
+    let source = "enum List { Cons(i32, List), Nil }";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    let mut analyzer = SemanticAnalyzer::new();
+    let err = analyzer.analyze(ast).unwrap_err();
+    assert!(
+        err.contains("Recursive enum `List` has infinite size"),
+        "{}",
+        err
+    );
+    assert!(err.contains("Box<List>"), "{}", err);
+}
+
+#[test]
+fn test_if_let_expression_branches_on_option_tag() {
+    let source = "fn pick(o: Option<i32>) -> i32 {
+            if let Some(x) = o { x } else { 0 }
+        }
+        fn main() {
+            return pick(Some(5)) + pick(None);
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("if let over an Option parameter should compile");
+
+    assert!(llvm_ir.contains("%Option = type { double, double }"));
+    assert!(llvm_ir.contains("label %if_let_then_"));
+    assert!(llvm_ir.contains("if_let_else_"));
+    assert!(llvm_ir.contains("if_let_end_"));
+}
+
+#[test]
+fn test_semantic_if_let_binding_scoped_to_then_block() {
+    let source = "fn main() {
+            if let Some(x) = Some(1) { let y = x; } else { let z = 2; }
+            let w = x;
+        }";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    let mut analyzer = SemanticAnalyzer::new();
+    let err = analyzer.analyze(ast).unwrap_err();
+    assert!(err.contains("undeclared variable `x`"), "{}", err);
+}
+
+#[test]
+fn test_semantic_if_let_pattern_must_fit_scrutinee() {
+    let source = "fn main() {
+            let n = 3;
+            let v = if let Some(x) = n { x } else { 0 };
+        }";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    let mut analyzer = SemanticAnalyzer::new();
+    let err = analyzer.analyze(ast).unwrap_err();
+    assert!(
+        err.contains("Pattern `Some` cannot match a value of type int"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_let_destructures_tuple_and_struct_patterns() {
+    let source = "struct Point { x: i32, y: i32 }
+        fn triple() -> (i32, i32, i32) { (1, 2, 3) }
+        fn main() -> i32 {
+            let (a, b, c) = triple();
+            let Point { x, y: py } = Point { x: 10, y: 20 };
+            let (_, e) = (4, 5);
+            a + b + c + x + py + e
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("irrefutable let patterns should compile");
+
+    assert!(llvm_ir.contains("%tuple.3 = type { double, double, double }"));
+    assert!(llvm_ir.contains("%tuple.2 = type { double, double }"));
+    assert!(llvm_ir.contains("define double @triple()"));
+}
+
+#[test]
+fn test_semantic_refutable_let_pattern_suggests_if_let() {
+    let source = "fn main() {
+            let o = Some(1);
+            let Some(v) = o;
+        }";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    let mut analyzer = SemanticAnalyzer::new();
+    let err = analyzer.analyze(ast).unwrap_err();
+    assert!(err.contains("Refutable pattern `Some"), "{}", err);
+    assert!(err.contains("use `if let` instead"), "{}", err);
+}
+
+#[test]
+fn test_cfg_drops_items_gated_on_inactive_features() {
+    let source = "@cfg(feature = \"gpu\")
+        fn accelerated() -> i32 { 1 }
+        @cfg(not(feature = \"gpu\"))
+        fn fallback() -> i32 { 2 }
+        @cfg(all(feature = \"gpu\", any(feature = \"fast\", feature = \"small\")))
+        fn tuned() -> i32 { 3 }
+        fn main() -> i32 { 0 }";
+    let compile = |features: &[&str]| {
+        let options = compiler::CompilerOptions {
+            features: features.iter().map(|f| f.to_string()).collect(),
+            ..Default::default()
+        };
+        compiler::compile_program(source, options).expect("cfg-gated program should compile")
+    };
+
+    let plain = compile(&[]);
+    assert!(!plain.contains("@accelerated("), "{}", plain);
+    assert!(plain.contains("define i32 @fallback("), "{}", plain);
+    assert!(!plain.contains("@tuned("), "{}", plain);
+
+    let gpu = compile(&["gpu", "fast"]);
+    assert!(gpu.contains("define i32 @accelerated("), "{}", gpu);
+    assert!(!gpu.contains("@fallback("), "{}", gpu);
+    assert!(gpu.contains("define i32 @tuned("), "{}", gpu);
+}
+
+#[test]
+fn test_inline_never_marks_the_function_noinline() {
+    let source = "@inline(never)
+        fn cold(x: i32) -> i32 { x + 1 }
+        fn hot(x: i32) -> i32 { x * 2 }
+        fn main() -> i32 { cold(hot(1)) }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("`@inline(never)` function should compile");
+
+    assert!(
+        llvm_ir.contains("define i32 @cold(i32 %x) noinline nounwind readnone {"),
+        "{}",
+        llvm_ir
+    );
+    assert!(
+        llvm_ir.contains("define i32 @hot(i32 %x) nounwind readnone {"),
+        "{}",
+        llvm_ir
+    );
+
+    let tokens = lexer::tokenize_with_locations("@inline(always) fn f() {}", None);
+    assert!(parser::Parser::new(tokens).parse().is_err());
+}
+
+#[test]
+fn test_string_equality_compares_contents() {
+    let source = "fn main() -> i32 {
+            let a = \"same\";
+            let b = \"same\";
+            if a == b { 1 } else { 0 }
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("string equality should compile");
+
+    assert!(llvm_ir.contains("declare i64 @strlen(i8*)"), "{}", llvm_ir);
+    assert!(llvm_ir.contains("call i32 @memcmp("), "{}", llvm_ir);
+    assert!(!llvm_ir.contains("fcmp oeq"), "{}", llvm_ir);
+}
+
+#[test]
+fn test_function_emission_order_is_deterministic() {
+    let source = "fn zeta(x: i32) -> i32 { return x + 1; }
+        fn alpha(x: i32) -> i32 { return zeta(x) * 2; }
+        fn mid(x: i32) -> i32 { return alpha(x) - 3; }
+        fn beta(x: i32) -> i32 { return mid(x) + zeta(x); }
+        fn gamma(x: i32) -> i32 { return beta(x) + alpha(x); }
+        fn main() {
+            return gamma(4);
+        }";
+    let compile = || {
+        compiler::compile_program(source, compiler::CompilerOptions::default())
+            .expect("multi-function program should compile")
+    };
+    let first = compile();
+    for _ in 0..4 {
+        assert_eq!(compile(), first);
+    }
+
+    // Functions appear in declaration order with `main` last.
+    let defines: Vec<&str> = first
+        .lines()
+        .filter_map(|line| line.strip_prefix("define "))
+        .filter_map(|rest| rest.split('@').nth(1)?.split('(').next())
+        .collect();
+    assert_eq!(defines, ["zeta", "alpha", "mid", "beta", "gamma", "main"]);
+}
+
+#[test]
+fn test_vec_get_with_runtime_index_is_bounds_checked() {
+    let source = "fn at(i: i32) -> i32 {
+            let v = vec![10, 20, 30];
+            if let Some(x) = v.get(i) { x } else { 0 }
+        }
+        fn main() {
+            return at(1);
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("vec get should compile");
+
+    // The element is only loaded on the in-bounds path.
+    let some = llvm_ir.find("get_some_").expect("in-bounds block");
+    let none = llvm_ir.rfind("get_none_").expect("out-of-bounds block");
+    assert!(some < none);
+    assert!(llvm_ir.contains("fcmp olt double"));
+    assert!(llvm_ir.contains("getelementptr inbounds [3 x double]"));
+}
+
+#[test]
+fn test_trait_bounded_generic_is_monomorphized_per_impl() {
+    let source = "trait Shape { fn area(&self) -> i32; }
+        struct Circle { r: i32 }
+        struct Square { s: i32 }
+        impl Shape for Circle {
+            fn area(&self) -> i32 { 3 * self.r * self.r }
+        }
+        impl Shape for Square {
+            fn area(&self) -> i32 { self.s * self.s }
+        }
+        fn area_of<T: Shape>(item: T) -> i32 {
+            item.area()
+        }
+        fn main() {
+            let c = Circle { r: 2 };
+            let d = Circle { r: 1 };
+            let s = Square { s: 3 };
+            return area_of(c) + area_of(s) + area_of(d);
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("trait-bounded generic should compile");
+
+    // One instantiation per implementing type, each calling its own impl.
+    assert!(llvm_ir.contains("define i32 @Circle.area(double %self)"));
+    assert!(llvm_ir.contains("define i32 @Square.area(double %self)"));
+    assert_eq!(llvm_ir.matches("define i32 @area_of.Circle(").count(), 1);
+    assert_eq!(llvm_ir.matches("define i32 @area_of.Square(").count(), 1);
+    assert!(llvm_ir.contains("call i32 @Circle.area(double"));
+    assert!(llvm_ir.contains("call i32 @Square.area(double"));
+    assert_eq!(
+        llvm_ir.matches("call i32 @area_of.Circle(double").count(),
+        2
+    );
+}
+
+#[test]
+fn test_impl_trait_return_is_the_concrete_type_to_callers() {
+    let source = "trait Shape { fn area(&self) -> f64; }
+        struct Circle { r: f64 }
+        struct Square { s: f64 }
+        impl Shape for Circle {
+            fn area(&self) -> f64 { 3.0 * self.r * self.r }
+        }
+        impl Shape for Square {
+            fn area(&self) -> f64 { self.s * self.s }
+        }
+        fn make() -> impl Shape { Circle { r: 1.0 } }
+        fn main() {
+            let shape = make();
+            let wrong = shape.area() && true;
+        }";
+    // `area()` resolves on the hidden `Circle`...
+    let err = SemanticAnalyzer::new()
+        .analyze(parser::parse(lexer::tokenize(source)))
+        .unwrap_err();
+    assert!(err.contains("must be boolean, found: float"), "{}", err);
+
+    let source = source.replace(
+        "let wrong = shape.area() && true;",
+        "let area = shape.area();",
+    );
+    let llvm_ir = compiler::compile_program(&source, compiler::CompilerOptions::default())
+        .expect("impl Trait return should compile");
+    assert!(
+        llvm_ir.contains("call double @Circle.area(double"),
+        "{}",
+        llvm_ir
+    );
+
+    // ...and there has to be exactly one of it.
+    let mixed = source.replace(
+        "fn make() -> impl Shape { Circle { r: 1.0 } }",
+        "fn make(square: bool) -> impl Shape {
+            if square { return Square { s: 1.0 }; }
+            Circle { r: 1.0 }
+        }",
+    );
+    let mixed = mixed.replace("make()", "make(true)");
+    let err = SemanticAnalyzer::new()
+        .analyze(parser::parse(lexer::tokenize(&mixed)))
+        .unwrap_err();
+    assert!(
+        err.contains("must be a single concrete type, but returns both `Square` and `Circle`"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_semantic_method_call_typed_from_impl() {
+    let source = "trait Shape { fn area(&self) -> f64; }
+        struct Circle { r: f64 }
+        impl Shape for Circle {
+            fn area(&self) -> f64 { 3.0 * self.r * self.r }
+        }
+        fn main() {
+            let c = Circle { r: 2.0 };
+            let wrong = c.area() && true;
+        }";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    let mut analyzer = SemanticAnalyzer::new();
+    let err = analyzer.analyze(ast).unwrap_err();
+    assert!(err.contains("must be boolean, found: float"), "{}", err);
+}
+
+#[test]
+fn test_trait_objects_dispatch_through_vtables() {
+    let source = "trait Shape { fn area(&self) -> i32; }
+        struct Circle { r: i32 }
+        struct Square { s: i32 }
+        impl Shape for Circle {
+            fn area(&self) -> i32 { 3 * self.r * self.r }
+        }
+        impl Shape for Square {
+            fn area(&self) -> i32 { self.s * self.s }
+        }
+        fn main() {
+            let c = Circle { r: 2 };
+            let s = Square { s: 3 };
+            let shapes: Vec<&dyn Shape> = vec![&c, &s];
+            return shapes[0].area() + shapes[1].area();
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("trait objects should compile");
+
+    // One vtable per impl; each trait object pairs data and vtable pointers.
+    assert!(llvm_ir.contains(
+        "@vtable.Shape.Circle = constant [1 x i8*] [i8* bitcast (i32 (double)* @Circle.area to i8*)]"
+    ));
+    assert!(llvm_ir.contains("@vtable.Shape.Square = constant [1 x i8*]"));
+    assert!(llvm_ir.contains("%dyn.Shape = type { double, double }"));
+
+    // Both calls go through the vtable slot rather than a named function.
+    assert_eq!(
+        llvm_ir.matches("getelementptr inbounds i8*, i8**").count(),
+        2
+    );
+    assert_eq!(llvm_ir.matches("bitcast i8* ").count(), 2);
+    assert!(!llvm_ir.contains("call i32 @Circle.area"));
+    assert!(!llvm_ir.contains("call i32 @Square.area"));
+}
+
+#[test]
+fn test_semantic_trait_object_requires_impl() {
+    let source = "trait Shape { fn area(&self) -> i32; }
+        struct Circle { r: i32 }
+        struct Point { x: i32 }
+        impl Shape for Circle {
+            fn area(&self) -> i32 { 3 * self.r * self.r }
+        }
+        fn main() {
+            let c = Circle { r: 2 };
+            let p = Point { x: 1 };
+            let shapes: Vec<&dyn Shape> = vec![&c, &p];
+        }";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    let mut analyzer = SemanticAnalyzer::new();
+    let err = analyzer.analyze(ast).unwrap_err();
+    assert!(
+        err.contains("Type `Point` does not implement trait `Shape`"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_string_literal_byte_indexed_through_constant_array() {
+    let source = "fn main() {
+            return \"abc\".as_bytes()[1] + \"abc\".len();
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("byte access should compile");
+
+    // 'a', 'b', 'c' as doubles; the length folds away entirely.
+    assert!(llvm_ir.contains(
+        "@bytes.0 = constant [3 x double] [double 0x4058400000000000, double 0x4058800000000000, double 0x4058C00000000000]"
+    ));
+    assert!(
+        llvm_ir.contains("getelementptr inbounds [3 x double], [3 x double]* %ptr0, i64 0, i64 1")
+    );
+    assert!(llvm_ir.contains("0x4008000000000000"));
+}
+
+#[test]
+fn test_semantic_constant_index_out_of_bounds() {
+    let source = "fn main() {
+            let b = \"abc\".as_bytes()[3];
+        }";
+    let tokens = lexer::tokenize(source);
+    let ast = parser::parse(tokens);
+    let mut analyzer = SemanticAnalyzer::new();
+    let err = analyzer.analyze(ast).unwrap_err();
+    assert!(
+        err.contains("Index 3 is out of bounds for an array of length 3"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_compile_program_diagnostics_reports_each_located_error() {
+    let source = "let = 1;\nlet y = 2;\nlet = 3;";
+    let diagnostics = compiler::compile_program_diagnostics(source, Default::default())
+        .expect_err("both malformed lets should be reported");
+    assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+
+    let spans: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let location = diagnostic.location().expect("parse errors are located");
+            (location.line, location.span.expect("tokens carry spans"))
+        })
+        .collect();
+    assert_eq!(spans[0].0, 1);
+    assert_eq!(spans[1].0, 3);
+    assert_ne!(spans[0].1, spans[1].1);
+
+    let message = compiler::compile_program(source, Default::default()).unwrap_err();
+    assert_eq!(message.lines().count(), 2);
+}
+
+#[test]
+fn test_compile_program_diagnostics_locates_every_type_error() {
+    let source = "fn ok(x: i32) -> i32 { x }\n\nfn bad() -> i32 { ok(1) + true }\n  fn worse() {\n    if 1 {}\n}\n";
+    let diagnostics = compiler::compile_program_diagnostics(source, Default::default())
+        .expect_err("both ill-typed functions should be reported");
+    assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+
+    let positions: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let location = diagnostic.location().expect("semantic errors are located");
+            (location.line, location.column)
+        })
+        .collect();
+    assert_eq!(positions, [(3, 1), (4, 3)]);
+    assert!(
+        diagnostics[0].to_string().contains("int vs bool"),
+        "{}",
+        diagnostics[0]
+    );
+}
+
+#[test]
+fn test_phases_compose_to_compile_program() {
+    use compiler::phases;
+
+    let source = "fn double(x: i32) -> i32 { return x * 2; }\nlet y = double(21);";
+    for opt_level in [0, 1, 2] {
+        let options = compiler::CompilerOptions {
+            opt_level,
+            ..Default::default()
+        };
+        let tokens = phases::tokenize(source);
+        let ast = phases::parse(tokens).unwrap();
+        let typed_ast = phases::analyze(ast).unwrap();
+        let mut ir = phases::generate_ir(typed_ast);
+        phases::optimize(&mut ir, opt_level);
+        let staged = phases::generate_code(ir);
+
+        assert_eq!(staged, compiler::compile_program(source, options).unwrap());
+    }
+}
+
+#[test]
+fn test_enum_struct_variants_construct_and_match_by_field_name() {
+    let source = "enum Shape { Rect { w: i32, h: i32 }, Circle(i32) }
+        fn area(s: Shape) -> i32 {
+            match s {
+                Shape::Rect { h, .. } => h,
+                Shape::Circle(r) => r,
+            }
+        }
+        fn main() -> i32 {
+            let r = Shape::Rect { h: 4, w: 3 };
+            area(r)
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("struct variants should compile");
+    assert!(llvm_ir.contains("define i32 @area("), "{}", llvm_ir);
+
+    let analyze = |source: &str| {
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(parser::parse(lexer::tokenize(source)))
+    };
+    let err = analyze(
+        "enum Shape { Rect { w: i32, h: i32 } }
+        fn main() { let r = Shape::Rect { w: 1, d: 2 }; }",
+    )
+    .unwrap_err();
+    assert!(err.contains("has no field `d`"), "{}", err);
+    let err = analyze(
+        "enum Shape { Rect { w: i32, h: i32 } }
+        fn main() { let r = Shape::Rect { w: 1 }; }",
+    )
+    .unwrap_err();
+    assert!(err.contains("Missing field `h`"), "{}", err);
+}
+
+#[test]
+fn test_constant_propagation_folds_values_stored_in_variables() {
+    let source = "fn main() {
+            let a = 2;
+            let b = a * 3;
+            println!(\"{}\", b);
+        }";
+    let optimized = |opt_level| {
+        let options = compiler::CompilerOptions {
+            opt_level,
+            ..Default::default()
+        };
+        compiler::compile_program(source, options).unwrap()
+    };
+
+    let six = format!("double 0x{:016X}", 6.0f64.to_bits());
+    let llvm_ir = optimized(1);
+    assert!(llvm_ir.contains(&six), "{}", llvm_ir);
+    assert!(!llvm_ir.contains("fmul"), "{}", llvm_ir);
+    assert!(optimized(0).contains("fmul"));
+}
+
+#[test]
+fn test_vec_iter_lowers_to_cursor_with_next() {
+    let source = "fn main() -> i32 {
+            let v = vec![10, 20, 30];
+            let it = v.iter();
+            let first = match it.next() { Some(x) => x, None => 0 };
+            let mut sum = 0;
+            for x in v { sum = sum + x; }
+            first + sum
+        }";
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer
+        .analyze(parser::parse(lexer::tokenize(source)))
+        .expect("iterators should type-check");
+
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("iterators should compile");
+    assert!(
+        llvm_ir.contains("%vec.iter = type { double, double }"),
+        "{}",
+        llvm_ir
+    );
+    // One `next()` call plus the one driving the `for` loop.
+    assert_eq!(llvm_ir.matches("next_none_").count(), 4, "{}", llvm_ir);
+}
+
+#[test]
+fn test_iterator_adapters_wrap_their_source() {
+    let analyze =
+        |source: &str| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    let source = "fn main() -> i32 {
+            let mut sum = 0;
+            for (i, x) in [10, 20].iter().enumerate() { sum = sum + i + x; }
+            for (x, y) in [1, 2, 3].iter().zip([4, 5].iter().take(1)) { sum = sum + x * y; }
+            sum
+        }";
+    analyze(source).expect("adapters should type-check");
+
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("adapters should compile");
+    assert!(
+        llvm_ir.contains("%iter.enumerate = type { double, double }"),
+        "{}",
+        llvm_ir
+    );
+    assert!(llvm_ir.contains("%iter.zip = type { double, double }"));
+    assert!(llvm_ir.contains("%iter.take = type { double, double }"));
+
+    let err = analyze("fn main() { let it = [1].iter().zip(3); }").unwrap_err();
+    assert_eq!(err, "Error: `zip` expects an iterator, found `int`.");
+    let err = analyze("fn main() { let it = [1].iter().take(true); }").unwrap_err();
+    assert_eq!(err, "Error: `take` expects an integer count, found `bool`.");
+}
+
+#[test]
+fn test_mixed_type_struct_fields_use_typed_aligned_geps() {
+    let source = "struct Mixed { flag: bool, big: i64, ratio: f64, count: i32 }
+        fn main() -> i32 {
+            let mut m = Mixed { flag: true, big: 5000000000, ratio: 2.5, count: 7 };
+            m.count = m.count + 35;
+            m.count
+        }";
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("mixed struct should compile");
+
+    assert!(
+        llvm_ir.contains("%Mixed = type { double, double, double, double }"),
+        "{}",
+        llvm_ir
+    );
+    for field in 0..4 {
+        assert!(
+            llvm_ir.contains(&format!(
+                "getelementptr inbounds %Mixed, %Mixed* %ptr0, i32 0, i32 {}",
+                field
+            )),
+            "{}",
+            llvm_ir
+        );
+    }
+    for line in llvm_ir.lines() {
+        if line.contains("store double") || line.contains("load double") {
+            assert!(line.ends_with("align 8"), "{}", line);
+        }
+    }
+    assert!(
+        !llvm_ir.contains("getelementptr inbounds i8"),
+        "{}",
+        llvm_ir
+    );
+}
+
+#[test]
+fn test_conditions_must_be_bool_without_int_coercion() {
+    let analyze =
+        |source: &str| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+
+    let err = analyze("fn main() { if 5 { } }").unwrap_err();
+    assert!(err.contains("expected bool, found i32"), "{}", err);
+    let err = analyze("fn main() { let x = 1.5; while x { } }").unwrap_err();
+    assert!(
+        err.contains("`while` condition: expected bool, found f64"),
+        "{}",
+        err
+    );
+
+    assert!(analyze("fn main() { let x = 3; if x > 0 { } }").is_ok());
+}
+
+#[test]
+fn test_scalar_references_auto_deref_in_arithmetic_and_comparisons() {
+    let source = "fn add(a: &i32, b: &i32) -> i32 { a + b }
+        fn bigger(a: &i32, b: &i32) -> bool { a > b }
+        fn main() -> i32 { let x = 3; let y = 4; add(&x, &y) }";
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer
+        .analyze(parser::parse(lexer::tokenize(source)))
+        .expect("references should coerce to their pointee");
+
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("references should compile");
+    let add = &llvm_ir[llvm_ir.find("define i32 @add(").unwrap()..];
+    let add = &add[..add.find("\n}").unwrap()];
+    assert_eq!(
+        add.matches("load double, double* %ptr").count(),
+        4,
+        "{}",
+        add
+    );
+    assert!(add.contains("fadd double"), "{}", add);
+    // `main` passes the addresses of `x` and `y`, not their values.
+    assert!(
+        llvm_ir.contains("ptrtoint double* %ptr0 to i64"),
+        "{}",
+        llvm_ir
+    );
+
+    let err = SemanticAnalyzer::new()
+        .analyze(parser::parse(lexer::tokenize(
+            "fn f(a: & &i32, b: & &i32) -> i32 { a + b }",
+        )))
+        .unwrap_err();
+    assert!(err.contains("&&int vs &&int"), "{}", err);
+}
+
+#[test]
+fn test_struct_literal_field_punning_uses_variables_in_scope() {
+    use compiler::ast::{AstNode, Expression, Statement};
+
+    let source = "struct Point { x: i32, y: i32 }
+        fn main() -> i32 { let x = 1; let y = 2; let p = Point { x, y }; p.x * 10 + p.y }";
+    let ast = parser::parse(lexer::tokenize(source));
+    let AstNode::Statement(Statement::Function { body, .. }) = &ast[1] else {
+        panic!("expected main, got {:?}", ast[1]);
+    };
+    let Statement::Let {
+        value: Some(Expression::StructLiteral { fields, .. }),
+        ..
+    } = &body.statements[2]
+    else {
+        panic!("expected a struct literal, got {:?}", body.statements[2]);
+    };
+    assert!(matches!(
+        fields.as_slice(),
+        [(x, Expression::Identifier(vx)), (y, Expression::Identifier(vy))]
+            if x == "x" && vx == "x" && y == "y" && vy == "y"
+    ));
+    SemanticAnalyzer::new()
+        .analyze(ast)
+        .expect("punned fields should resolve to the variables");
+    compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("punned struct literal should compile");
+
+    let err = SemanticAnalyzer::new()
+        .analyze(parser::parse(lexer::tokenize(
+            "struct Point { x: i32, y: i32 }
+            fn main() { let x = 1; let p = Point { x, y }; }",
+        )))
+        .unwrap_err();
+    assert!(err.contains("undeclared variable `y`"), "{}", err);
+}
+
+#[test]
+fn test_string_parse_returns_a_result_of_the_turbofish_type() {
+    let analyze = |source: &str| {
+        let ast = parser::parse(lexer::tokenize(source));
+        SemanticAnalyzer::new().analyze(ast)
+    };
+    let ok = analyze(
+        r#"
+        fn main() {
+            let n: Result<i32, ParseError> = "42".parse();
+            let x: Result<f64, ParseError> = "2.5".parse::<f64>();
+            let y: Result<f64, ParseError> = "2.5".parse();
+        }
+    "#,
+    );
+    assert!(ok.is_ok(), "{:?}", ok);
+
+    let err = analyze(r#"fn main() { let n: Result<String, ParseError> = "1".parse(); }"#);
+    assert!(err.is_err(), "the annotation picks the parse target");
+    let err = analyze(r#"fn main() { let b = "true".parse::<bool>(); }"#).unwrap_err();
+    assert!(err.contains("Cannot parse a string into `bool`"), "{}", err);
+}
+
+#[test]
+fn test_string_plus_concatenates_and_rejects_mixed_operands() {
+    let source = "fn main() { let s = \"a\" + \"b\"; }";
+    SemanticAnalyzer::new()
+        .analyze(parser::parse(lexer::tokenize(source)))
+        .expect("String + String should type-check");
+    let llvm_ir = compiler::compile_program(source, compiler::CompilerOptions::default())
+        .expect("string concatenation should compile");
+    assert!(llvm_ir.contains("call i8* @malloc(i64"), "{}", llvm_ir);
+    assert_eq!(
+        llvm_ir.matches("call void @llvm.memcpy").count(),
+        2,
+        "{}",
+        llvm_ir
+    );
+
+    let err = SemanticAnalyzer::new()
+        .analyze(parser::parse(lexer::tokenize(
+            "fn main() { let s = \"a\" + 1; }",
+        )))
+        .unwrap_err();
+    assert!(err.contains("String vs int"), "{}", err);
+}
+
+#[test]
+fn test_semantic_option_prints_only_with_debug_placeholder() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(analyze("fn main() { let x: Option<i32> = Some(5); println!(\"{:?}\", x); }").is_ok());
+    assert!(
+        analyze("fn main() { let r: Result<i32, String> = Err(\"x\"); println!(\"{:?}\", r); }")
+            .is_ok()
+    );
+    let err =
+        analyze("fn main() { let x: Option<i32> = Some(5); println!(\"{}\", x); }").unwrap_err();
+    assert!(err.ends_with("Use `{:?}` to print it."), "{}", err);
+}
+
+#[test]
+fn test_semantic_structs_and_enums_print_only_with_debug_placeholder() {
+    let analyze =
+        |source: &str| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    let types = "struct P { x: i32, name: String } enum E { A, B(i32), C { w: f64 } }";
+    assert!(analyze(&format!(
+        "{} fn main() {{ let p = P {{ x: 1, name: \"a\" }}; let e = E::B(2); println!(\"{{:?}} {{:?}}\", p, e); }}",
+        types
+    ))
+    .is_ok());
+    let err = analyze(&format!(
+        "{} fn main() {{ let p = P {{ x: 1, name: \"a\" }}; println!(\"{{}}\", p); }}",
+        types
+    ))
+    .unwrap_err();
+    assert_eq!(
+        err,
+        "Error: Argument 1 of type `P` is not printable. Use `{:?}` to print it."
+    );
+}
+
+#[test]
+fn test_semantic_match_arm_block_may_return_early() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(
+        analyze(
+            "fn pick(x: i32) -> i32 { let y = match x { 0 => { return 0; } 1 => { let z = 4; z * 2 } _ => 20 }; y } fn main() {}"
+        )
+        .is_ok()
+    );
+    assert_eq!(
+        analyze("fn main() { let y = match 1 { 0 => { return; } 1 => { true } _ => 2 }; }")
+            .unwrap_err(),
+        "Error: `match` arms have incompatible types: bool vs int"
+    );
+}
+
+#[test]
+fn test_semantic_numeric_builtins_pick_int_or_float() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(
+        analyze(
+            "fn main() { let a: i32 = max(3, 7); let b: f64 = min(2.0, 1.5); let c: i32 = a.abs(); let d: f64 = b.max(1); }"
+        )
+        .is_ok()
+    );
+    assert_eq!(
+        analyze("fn main() { let a = abs(\"x\"); }").unwrap_err(),
+        "Error: `abs` expects numbers, but `String` was provided."
+    );
+    assert_eq!(
+        analyze("fn main() { let a = max(1); }").unwrap_err(),
+        "Error: `max` expects 2 arguments, but 1 were provided."
+    );
+    // A function of the program's own shadows the builtin.
+    assert!(
+        analyze("fn max(s: String) -> String { s } fn main() { let a: String = max(\"x\"); }")
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_integer_literal_beyond_i64_is_rejected() {
+    let tokens =
+        lexer::tokenize_with_locations("fn main() { let x = 99999999999999999999; }", None);
+    let error = parser::Parser::new(tokens).parse().unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("integer literal `99999999999999999999` does not fit in an i64"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_semantic_rejects_non_unit_function_that_can_fall_off_the_end() {
+    let missing = |source: &str| {
+        let tokens = lexer::tokenize_with_locations(source, None);
+        let ast = parser::Parser::new(tokens)
+            .parse()
+            .unwrap_or_else(|err| panic!("{} does not parse: {}", source, err));
+        SemanticAnalyzer::new().analyze(ast).err()
+    };
+
+    assert_eq!(
+        missing("fn g(x: i32) -> i32 { if x > 0 { return 1; } } fn main() { g(1); }").as_deref(),
+        Some("Error: Function `g` returns `int`, but not all control paths return a value.")
+    );
+    assert!(
+        missing("fn g(x: i32) -> i32 { if x > 0 { 1 } else { 2 } } fn main() { g(1); }").is_none()
+    );
+    assert!(
+        missing("fn g(x: i32) -> i32 { if x > 0 { return 1; } return 2; } fn main() { g(1); }")
+            .is_none()
+    );
+    assert!(missing("fn g(x: i32) -> i32 { loop { return x; } } fn main() { g(1); }").is_none());
+    assert!(missing("fn g() -> i32 { todo!(); } fn main() { g(); }").is_none());
+    assert!(
+        missing(
+            "fn g(x: i32) -> i32 { if x > 0 { return 1; } unimplemented!(); } fn main() { g(1); }"
+        )
+        .is_none()
+    );
+    assert!(missing("fn g(x: i32) { if x > 0 { return; } } fn main() { g(1); }").is_none());
+}
+
+#[test]
+fn test_semantic_never_returning_functions_diverge() {
+    let analyze = |source: &str| {
+        let tokens = lexer::tokenize_with_locations(source, None);
+        let ast = parser::Parser::new(tokens)
+            .parse()
+            .unwrap_or_else(|err| panic!("{} does not parse: {}", source, err));
+        SemanticAnalyzer::new().analyze(ast).map(|_| ())
+    };
+    let fail = "fn fail() -> ! { panic!(\"unreachable\") }";
+
+    // `!` takes the annotated type: `x` is a `bool` here and an `i32` below.
+    assert!(
+        analyze(&format!(
+            "{} fn main() {{ let x: bool = fail(); if x {{}} }}",
+            fail
+        ))
+        .is_ok()
+    );
+    assert_eq!(
+        analyze(&format!(
+            "{} fn main() {{ let x: i32 = fail(); if x {{}} }}",
+            fail
+        ))
+        .unwrap_err(),
+        "Error: Mismatched types in `if` condition: expected bool, found i32."
+    );
+    // A call to `fail` ends its path, so no value is needed after it.
+    assert!(
+        analyze(&format!(
+            "{} fn g(x: i32) -> i32 {{ if x > 0 {{ return x; }} fail(); }} fn main() {{ g(1); }}",
+            fail
+        ))
+        .is_ok()
+    );
+    // Calling it from a `-> !` function keeps that one diverging too.
+    assert!(
+        analyze(&format!(
+            "{} fn stop() -> ! {{ fail(); }} fn main() {{}}",
+            fail
+        ))
+        .is_ok()
+    );
+    // `panic!` diverges as a statement as well as in tail position.
+    assert!(analyze("fn fail() -> ! { panic!(\"unreachable\"); } fn main() {}").is_ok());
+    assert_eq!(
+        analyze("fn fail() -> ! { let x = 1; } fn main() {}").unwrap_err(),
+        "Error: Function `fail` returns `!`, but its body can finish."
+    );
+}
+
+#[test]
+fn test_ok_or_else_takes_a_closure_without_parameters() {
+    let analyze = |source: &str| {
+        let tokens = lexer::tokenize_with_locations(source, None);
+        let ast = parser::Parser::new(tokens)
+            .parse()
+            .unwrap_or_else(|err| panic!("{} does not parse: {}", source, err));
+        SemanticAnalyzer::new().analyze(ast).map(|_| ())
+    };
+    assert!(
+        analyze("fn main() { let o = Some(1); let r = o.ok_or_else(|| 2); let s = o.ok_or(3); }")
+            .is_ok()
+    );
+    assert_eq!(
+        analyze("fn main() { let o = Some(1); let r = o.ok_or_else(2); }").unwrap_err(),
+        "Error: `ok_or_else` expects a closure with no parameters."
+    );
+}
+
+#[test]
+fn test_semantic_string_conversions() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(
+        analyze(
+            "fn main() { let s: String = \"x\".to_string(); let t: String = String::from(s.as_str()); let n: String = 1.to_string(); let b: String = true.to_string(); }"
+        )
+        .is_ok()
+    );
+    assert_eq!(
+        analyze("fn main() { let s = String::from(5); }").unwrap_err(),
+        "Error: `String::from` expects a string, but `int` was provided."
+    );
+}
+
+#[test]
+fn test_semantic_swap_requires_two_mutable_references_of_one_type() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(analyze("fn main() { let mut a = 1; let mut b = 2; swap(&mut a, &mut b); }").is_ok());
+    assert_eq!(
+        analyze("fn main() { let mut a = 1; let mut b = 2.5; swap(&mut a, &mut b); }").unwrap_err(),
+        "Error: `swap` expects both references to have the same type, but `int` and `float` were provided."
+    );
+    assert_eq!(
+        analyze("fn main() { let mut a = 1; let mut b = 2; swap(&a, &mut b); }").unwrap_err(),
+        "Error: `swap` expects two `&mut` references, but `&int` and `&mut int` were provided."
+    );
+}
+
+#[test]
+fn test_semantic_range_bounds_must_be_integers() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(analyze("fn main() { let n = 3; for i in 0..=n { let j = i + 1; } }").is_ok());
+    assert_eq!(
+        analyze("fn main() { for i in 0..2.5 { } }").unwrap_err(),
+        "Error: Range bounds must be integers, but `int` and `float` were provided."
+    );
+}
+
+#[test]
+fn test_semantic_field_access_needs_a_declared_field() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(
+        analyze(
+            "struct Point { x: i32, y: f64 }
+             fn main() { let p = Point { x: 1, y: 2.0 }; let r = &p; let z = r.y + 0.5; }"
+        )
+        .is_ok()
+    );
+    assert!(
+        analyze(
+            "struct Point { x: i32, y: f64 }
+             fn origin() -> Point { Point { x: 0, y: 0.0 } }
+             fn main() { let p = origin(); let z = p.x + origin().x; }"
+        )
+        .is_ok()
+    );
+    assert_eq!(
+        analyze("fn main() { let y = (5).field; }").unwrap_err(),
+        "Error: No field `field` on type `i32`."
+    );
+    assert_eq!(
+        analyze(
+            "struct Point { x: i32, y: f64 }
+             fn main() { let p = Point { x: 1, y: 2.0 }; let z = p.nonexistent; }"
+        )
+        .unwrap_err(),
+        "Error: No field `nonexistent` on type `Point`."
+    );
+}
+
+#[test]
+fn test_semantic_tuple_struct_fields_are_checked_by_position() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(
+        analyze(
+            "struct Pair(i32, f64);
+             fn main() { let mut p = Pair(1, 2.0); p.1 = 9.5; p.0 = p.0 + 1; }"
+        )
+        .is_ok()
+    );
+    assert_eq!(
+        analyze(
+            "struct Pair(i32, f64);
+             fn main() { let p = Pair(1, 2.0); let z = p.2; }"
+        )
+        .unwrap_err(),
+        "Error: No field `2` on type `Pair`."
+    );
+    assert_eq!(
+        analyze(
+            "struct Pair(i32, f64);
+             fn main() { let p = Pair(1, 2.0); p.0 = 3; }"
+        )
+        .unwrap_err(),
+        "Error: Cannot assign to a field of immutable variable `p`."
+    );
+    assert_eq!(
+        analyze(
+            "struct Pair(i32, f64);
+             fn main() { let mut p = Pair(1, 2.0); p.1 = 3; }"
+        )
+        .unwrap_err(),
+        "Error: Cannot assign a value of type `int` to a place of type `float`."
+    );
+    assert_eq!(
+        analyze(
+            "struct Pair(i32, f64);
+             fn main() { let p = Pair(1); }"
+        )
+        .unwrap_err(),
+        "Error: Tuple struct `Pair` has 2 fields, but 1 value was provided."
+    );
+}
+
+#[test]
+fn test_semantic_let_wildcard_discards_without_moving() {
+    let source = "fn f() -> i32 { 3 } fn main() { let s = String::from(\"a\"); let _ = s; let _ = f(); let _ = f(); let t = s; }";
+    // Analysis also runs the borrow checker.
+    let ast = parser::parse(lexer::tokenize(source));
+    assert!(SemanticAnalyzer::new().analyze(ast).is_ok());
+}
+
+#[test]
+fn test_semantic_bare_return_needs_a_unit_function() {
+    let analyze = |source| SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(source)));
+    assert!(
+        analyze("fn log(x: i32) { if x > 0 { return; } } fn main() { log(1); return; }").is_ok()
+    );
+    assert_eq!(
+        analyze("fn f(x: i32) -> i32 { if x > 0 { return; } x } fn main() { let y = f(1); }")
+            .unwrap_err(),
+        "Error: Type mismatch in `return`: expected `int`, found `()`."
+    );
+}
+
+#[test]
+fn test_keyword_arguments_compile_like_positional_ones() {
+    let program = |call: &str| {
+        format!(
+            "fn area(width: i32, height: i32) -> i32 {{ width * 10 + height }}
+             fn main() -> i32 {{ area({}) }}",
+            call
+        )
+    };
+    let compile = |call: &str| {
+        compiler::compile_program(&program(call), compiler::CompilerOptions::default()).unwrap()
+    };
+
+    let positional = compile("1, 2");
+    assert_eq!(compile("width: 1, height: 2"), positional);
+    assert_eq!(compile("height: 2, width: 1"), positional);
+    assert_eq!(compile("1, height: 2"), positional);
+
+    let analyze = |call: &str| {
+        SemanticAnalyzer::new().analyze(parser::parse(lexer::tokenize(&program(call))))
+    };
+    assert_eq!(
+        analyze("width: 1, depth: 2").unwrap_err(),
+        "Error: Function `area` has no parameter named `depth`."
+    );
+    assert_eq!(
+        analyze("1, width: 2").unwrap_err(),
+        "Error: Argument `width` of `area` is given more than once."
+    );
+
+    let tokens = lexer::tokenize_with_locations(&program("width: 1, 2"), None);
+    let error = parser::Parser::new(tokens).parse().unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("positional argument after a keyword argument"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_write_macros_append_to_a_mutable_string() {
+    let analyze = |source: &str| {
+        SemanticAnalyzer::new()
+            .analyze(parser::parse(lexer::tokenize(source)))
+            .map(|_| ())
+    };
+    assert!(
+        analyze(
+            "fn main() { let mut buf = String::from(\"\"); write!(buf, \"a{}b\", 1); writeln!(buf, \"{}\", true); }"
+        )
+        .is_ok()
+    );
+    assert_eq!(
+        analyze("fn main() { let buf = String::from(\"\"); write!(buf, \"a{}b\", 1); }")
+            .unwrap_err(),
+        "Error: Cannot assign to immutable variable `buf`."
+    );
+
+    let tokens = lexer::tokenize_with_locations(
+        "fn main() { let mut buf = String::from(\"\"); write!(buf, \"{}{}\", 1); }",
+        None,
+    );
+    let error = parser::Parser::new(tokens).parse().unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("format string has 2 placeholders but 1 arguments were provided"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_sort_needs_numeric_elements() {
+    let analyze = |source: &str| {
+        SemanticAnalyzer::new()
+            .analyze(parser::parse(lexer::tokenize(source)))
+            .map(|_| ())
+    };
+    assert!(analyze("fn main() { let mut v = vec![2, 1]; v.sort(); v.reverse(); }").is_ok());
+    assert_eq!(
+        analyze("fn main() { let mut v = vec![true, false]; v.sort(); }").unwrap_err(),
+        "Error: `sort` needs elements that compare as numbers, found `bool`."
+    );
+}